@@ -0,0 +1,61 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use thiserror::Error;
+
+pub type PsbtResult<T = ()> = Result<T, PsbtError>;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PsbtError {
+    #[error("unexpected end of data while reading {context}")]
+    UnexpectedEnd { context: &'static str },
+
+    #[error("invalid PSBT magic bytes")]
+    InvalidMagic,
+
+    #[error("invalid base64 PSBT: {error}")]
+    InvalidBase64 { error: String },
+
+    #[error("{length} bytes were left after the end of the {context}")]
+    TrailingData {
+        context: &'static str,
+        length: usize,
+    },
+
+    #[error("duplicate key {key} in the {map} map")]
+    DuplicateKey { map: &'static str, key: String },
+
+    #[error("the PSBT has no unsigned transaction")]
+    MissingUnsignedTransaction,
+
+    #[error("PSBT version {version} is not supported")]
+    UnsupportedVersion { version: u32 },
+
+    #[error("invalid unsigned transaction: {error}")]
+    InvalidUnsignedTransaction { error: String },
+
+    #[error("invalid value of key type {key_type} in input {index}: {error}")]
+    InvalidInputValue {
+        index: usize,
+        key_type: u8,
+        error: String,
+    },
+
+    #[error("input {index} does not exist")]
+    InputOutOfRange { index: usize },
+
+    #[error("the UTXO spent by input {index} is unknown")]
+    MissingUtxo { index: usize },
+
+    #[error("sighash type {sighash_type:#x} of input {index} is not supported")]
+    UnsupportedSighashType { index: usize, sighash_type: u32 },
+
+    #[error("input {index} is not finalized")]
+    InputNotFinalized { index: usize },
+
+    #[error("invalid dWallet keys: {error}")]
+    InvalidDWalletKeys { error: String },
+
+    #[error("failed to sign input {index}: {error}")]
+    Signing { index: usize, error: String },
+}
@@ -0,0 +1,32 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Signing Bitcoin PSBTs with dWallets.
+//!
+//! This crate parses a PSBT (BIP-174), computes the legacy and SegWit v0 (BIP-143) signature
+//! hashes of the P2PKH, P2WPKH and P2SH-P2WPKH inputs controlled by a dWallet public key,
+//! signs each of them with its own presign, and writes the finalized signatures back into
+//! the PSBT. Submitting the presign and sign requests to the network is left to
+//! a [`DWalletSigningSession`], so this crate has no node or Sui dependencies.
+//!
+//! ```ignore
+//! let mut psbt = Psbt::from_base64(&unsigned_psbt)?;
+//! let signed_inputs = sign_psbt(&mut psbt, &user_keys, &mut session).await?;
+//! let transaction = psbt.extract_transaction()?.to_bytes();
+//! ```
+
+pub mod error;
+pub mod psbt;
+pub mod sighash;
+pub mod signer;
+pub mod transaction;
+
+pub use dwallet_mpc_centralized_party::sign_session::{
+    DWalletSignRequest, DWalletSigningSession, DWalletUserKeys,
+};
+pub use error::{PsbtError, PsbtResult};
+pub use psbt::Psbt;
+pub use signer::{
+    DWalletInput, DWalletSpendKind, dwallet_inputs, finalize_dwallet_input, sign_psbt,
+};
+pub use transaction::{OutPoint, Transaction, TxIn, TxOut};
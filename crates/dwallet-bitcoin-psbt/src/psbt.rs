@@ -0,0 +1,304 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Partially Signed Bitcoin Transactions (BIP-174, version 0).
+//!
+//! The maps of a PSBT are kept as raw key-value pairs, so that entries this crate does not
+//! interpret (e.g. BIP-32 derivations and proprietary entries) are serialized back unchanged.
+//! Only the entries needed to sign and finalize an input are interpreted.
+
+use crate::error::{PsbtError, PsbtResult};
+use crate::transaction::{
+    Reader, Transaction, TxOut, read_witness, write_var_bytes, write_witness,
+};
+use base64::Engine;
+use std::collections::BTreeMap;
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+const PSBT_SEPARATOR: u8 = 0x00;
+
+pub const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+pub const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+
+pub const PSBT_IN_NON_WITNESS_UTXO: u8 = 0x00;
+pub const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+pub const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+pub const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+pub const PSBT_IN_REDEEM_SCRIPT: u8 = 0x04;
+pub const PSBT_IN_WITNESS_SCRIPT: u8 = 0x05;
+pub const PSBT_IN_BIP32_DERIVATION: u8 = 0x06;
+pub const PSBT_IN_FINAL_SCRIPTSIG: u8 = 0x07;
+pub const PSBT_IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+
+/// The input entries a finalizer removes, as they are only needed to produce the final scripts.
+const PSBT_IN_FINALIZED_KEY_TYPES: [u8; 5] = [
+    PSBT_IN_PARTIAL_SIG,
+    PSBT_IN_SIGHASH_TYPE,
+    PSBT_IN_REDEEM_SCRIPT,
+    PSBT_IN_WITNESS_SCRIPT,
+    PSBT_IN_BIP32_DERIVATION,
+];
+
+/// The key-value pairs of a PSBT map, by their full key (its type followed by its data).
+pub type KeyValueMap = BTreeMap<Vec<u8>, Vec<u8>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Psbt {
+    pub unsigned_transaction: Transaction,
+    /// The global entries other than the unsigned transaction.
+    pub global: KeyValueMap,
+    pub inputs: Vec<KeyValueMap>,
+    pub outputs: Vec<KeyValueMap>,
+}
+
+impl Psbt {
+    pub fn from_bytes(bytes: &[u8]) -> PsbtResult<Self> {
+        let mut reader = Reader::new(bytes);
+        if reader.read_bytes(PSBT_MAGIC.len()).ok() != Some(PSBT_MAGIC) {
+            return Err(PsbtError::InvalidMagic);
+        }
+
+        let mut global = read_map(&mut reader, "global")?;
+        let unsigned_transaction = global
+            .remove([PSBT_GLOBAL_UNSIGNED_TX].as_slice())
+            .ok_or(PsbtError::MissingUnsignedTransaction)?;
+        let unsigned_transaction = Transaction::from_bytes(&unsigned_transaction).map_err(|e| {
+            PsbtError::InvalidUnsignedTransaction {
+                error: e.to_string(),
+            }
+        })?;
+        if unsigned_transaction
+            .inputs
+            .iter()
+            .any(|input| !input.script_sig.is_empty() || !input.witness.is_empty())
+        {
+            return Err(PsbtError::InvalidUnsignedTransaction {
+                error: "the unsigned transaction has signatures".to_string(),
+            });
+        }
+        if let Some(version) = global.get([PSBT_GLOBAL_VERSION].as_slice()) {
+            let version = <[u8; 4]>::try_from(version.as_slice())
+                .map(u32::from_le_bytes)
+                .unwrap_or(u32::MAX);
+            if version != 0 {
+                return Err(PsbtError::UnsupportedVersion { version });
+            }
+        }
+
+        let inputs = (0..unsigned_transaction.inputs.len())
+            .map(|_| read_map(&mut reader, "input"))
+            .collect::<PsbtResult<_>>()?;
+        let outputs = (0..unsigned_transaction.outputs.len())
+            .map(|_| read_map(&mut reader, "output"))
+            .collect::<PsbtResult<_>>()?;
+        reader.finish("PSBT")?;
+
+        Ok(Psbt {
+            unsigned_transaction,
+            global,
+            inputs,
+            outputs,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = PSBT_MAGIC.to_vec();
+        write_var_bytes(&mut bytes, &[PSBT_GLOBAL_UNSIGNED_TX]);
+        write_var_bytes(&mut bytes, &self.unsigned_transaction.to_bytes());
+        write_map(&mut bytes, &self.global);
+        for map in self.inputs.iter().chain(&self.outputs) {
+            write_map(&mut bytes, map);
+        }
+        bytes
+    }
+
+    pub fn from_base64(psbt: &str) -> PsbtResult<Self> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(psbt)
+            .map_err(|e| PsbtError::InvalidBase64 {
+                error: e.to_string(),
+            })?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.to_bytes())
+    }
+
+    /// Returns the output spent by the input at `index`,
+    /// from its witness UTXO or, if it has none, its non-witness UTXO.
+    pub fn spent_output(&self, index: usize) -> PsbtResult<TxOut> {
+        let input = self.input(index)?;
+        if let Some(witness_utxo) = input.get([PSBT_IN_WITNESS_UTXO].as_slice()) {
+            let mut reader = Reader::new(witness_utxo);
+            let read_spent_output = |reader: &mut Reader| {
+                let spent_output = TxOut {
+                    value: reader.read_u64()?,
+                    script_pubkey: reader.read_var_bytes()?,
+                };
+                reader.finish("witness UTXO")?;
+                Ok(spent_output)
+            };
+            return read_spent_output(&mut reader).map_err(|e: PsbtError| {
+                PsbtError::InvalidInputValue {
+                    index,
+                    key_type: PSBT_IN_WITNESS_UTXO,
+                    error: e.to_string(),
+                }
+            });
+        }
+
+        let Some(non_witness_utxo) = input.get([PSBT_IN_NON_WITNESS_UTXO].as_slice()) else {
+            return Err(PsbtError::MissingUtxo { index });
+        };
+        let invalid_non_witness_utxo = |error: String| PsbtError::InvalidInputValue {
+            index,
+            key_type: PSBT_IN_NON_WITNESS_UTXO,
+            error,
+        };
+        let previous_transaction = Transaction::from_bytes(non_witness_utxo)
+            .map_err(|e| invalid_non_witness_utxo(e.to_string()))?;
+        let previous_output = &self.unsigned_transaction.inputs[index].previous_output;
+        if previous_transaction.txid() != previous_output.txid {
+            return Err(invalid_non_witness_utxo(
+                "the transaction is not the one spent by the input".to_string(),
+            ));
+        }
+        previous_transaction
+            .outputs
+            .get(previous_output.vout as usize)
+            .cloned()
+            .ok_or_else(|| invalid_non_witness_utxo("the spent output does not exist".to_string()))
+    }
+
+    /// Returns the sighash type the input at `index` must be signed with, if it is set.
+    pub fn sighash_type(&self, index: usize) -> PsbtResult<Option<u32>> {
+        self.input(index)?
+            .get([PSBT_IN_SIGHASH_TYPE].as_slice())
+            .map(|sighash_type| {
+                <[u8; 4]>::try_from(sighash_type.as_slice())
+                    .map(u32::from_le_bytes)
+                    .map_err(|_| PsbtError::InvalidInputValue {
+                        index,
+                        key_type: PSBT_IN_SIGHASH_TYPE,
+                        error: "a sighash type is 4 bytes long".to_string(),
+                    })
+            })
+            .transpose()
+    }
+
+    /// Returns the redeem script of the P2SH input at `index`, if it is set.
+    pub fn redeem_script(&self, index: usize) -> PsbtResult<Option<&[u8]>> {
+        Ok(self
+            .input(index)?
+            .get([PSBT_IN_REDEEM_SCRIPT].as_slice())
+            .map(Vec::as_slice))
+    }
+
+    pub fn is_finalized(&self, index: usize) -> PsbtResult<bool> {
+        let input = self.input(index)?;
+        Ok(input.contains_key([PSBT_IN_FINAL_SCRIPTSIG].as_slice())
+            || input.contains_key([PSBT_IN_FINAL_SCRIPTWITNESS].as_slice()))
+    }
+
+    /// Adds the signature of `public_key` (its sighash type appended) to the input at `index`.
+    pub fn add_partial_signature(
+        &mut self,
+        index: usize,
+        public_key: &[u8],
+        signature: Vec<u8>,
+    ) -> PsbtResult {
+        let mut key = vec![PSBT_IN_PARTIAL_SIG];
+        key.extend(public_key);
+        self.input_mut(index)?.insert(key, signature);
+        Ok(())
+    }
+
+    /// Sets the final script signature and witness of the input at `index`,
+    /// and removes the entries that were only needed to produce them.
+    /// An empty script signature or witness is not set.
+    pub fn finalize_input(
+        &mut self,
+        index: usize,
+        final_script_sig: Vec<u8>,
+        final_script_witness: Vec<Vec<u8>>,
+    ) -> PsbtResult {
+        let input = self.input_mut(index)?;
+        input.retain(|key, _| {
+            key.first()
+                .is_none_or(|key_type| !PSBT_IN_FINALIZED_KEY_TYPES.contains(key_type))
+        });
+        if !final_script_sig.is_empty() {
+            input.insert(vec![PSBT_IN_FINAL_SCRIPTSIG], final_script_sig);
+        }
+        if !final_script_witness.is_empty() {
+            let mut witness = Vec::new();
+            write_witness(&mut witness, &final_script_witness);
+            input.insert(vec![PSBT_IN_FINAL_SCRIPTWITNESS], witness);
+        }
+        Ok(())
+    }
+
+    /// Returns the signed transaction, once every input is finalized.
+    pub fn extract_transaction(&self) -> PsbtResult<Transaction> {
+        let mut transaction = self.unsigned_transaction.clone();
+        for (index, input) in transaction.inputs.iter_mut().enumerate() {
+            if !self.is_finalized(index)? {
+                return Err(PsbtError::InputNotFinalized { index });
+            }
+            let map = &self.inputs[index];
+            if let Some(final_script_sig) = map.get([PSBT_IN_FINAL_SCRIPTSIG].as_slice()) {
+                input.script_sig = final_script_sig.clone();
+            }
+            if let Some(final_script_witness) = map.get([PSBT_IN_FINAL_SCRIPTWITNESS].as_slice()) {
+                let mut reader = Reader::new(final_script_witness);
+                input.witness = read_witness(&mut reader)
+                    .and_then(|witness| reader.finish("witness").map(|()| witness))
+                    .map_err(|e| PsbtError::InvalidInputValue {
+                        index,
+                        key_type: PSBT_IN_FINAL_SCRIPTWITNESS,
+                        error: e.to_string(),
+                    })?;
+            }
+        }
+        Ok(transaction)
+    }
+
+    fn input(&self, index: usize) -> PsbtResult<&KeyValueMap> {
+        self.inputs
+            .get(index)
+            .ok_or(PsbtError::InputOutOfRange { index })
+    }
+
+    fn input_mut(&mut self, index: usize) -> PsbtResult<&mut KeyValueMap> {
+        self.inputs
+            .get_mut(index)
+            .ok_or(PsbtError::InputOutOfRange { index })
+    }
+}
+
+fn read_map(reader: &mut Reader, map: &'static str) -> PsbtResult<KeyValueMap> {
+    let mut entries = KeyValueMap::new();
+    loop {
+        let key = reader.read_var_bytes()?;
+        if key.is_empty() {
+            return Ok(entries);
+        }
+        let value = reader.read_var_bytes()?;
+        if entries.contains_key(&key) {
+            return Err(PsbtError::DuplicateKey {
+                map,
+                key: key.iter().map(|byte| format!("{byte:02x}")).collect(),
+            });
+        }
+        entries.insert(key, value);
+    }
+}
+
+fn write_map(bytes: &mut Vec<u8>, map: &KeyValueMap) {
+    for (key, value) in map {
+        write_var_bytes(bytes, key);
+        write_var_bytes(bytes, value);
+    }
+    bytes.push(PSBT_SEPARATOR);
+}
@@ -0,0 +1,209 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Signature hashes of legacy and SegWit v0 (BIP-143) inputs.
+//!
+//! The signature hash of an input is the double SHA-256 digest of its preimage.
+//! The preimages are returned rather than their digests, since a dWallet hashes
+//! the message it signs itself, see [`crate::signer`].
+
+use crate::error::{PsbtError, PsbtResult};
+use crate::transaction::{
+    Transaction, double_sha256, write_compact_size, write_out_point, write_tx_out, write_var_bytes,
+};
+
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+const SIGHASH_BASE_TYPE_MASK: u32 = 0x1f;
+
+/// The value of the outputs before the signed one in a legacy `SIGHASH_SINGLE` preimage.
+const BLANK_OUTPUT_VALUE: u64 = u64::MAX;
+
+/// Returns the preimage of the signature hash of the legacy input at `input_index`.
+///
+/// `script_code` is the script the input spends, e.g. its P2PKH output script.
+/// `SIGHASH_SINGLE` without an output at `input_index` signs the constant `1` rather than
+/// a digest of the transaction, so it has no preimage and is rejected.
+pub fn legacy_sighash_preimage(
+    transaction: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    sighash_type: u32,
+) -> PsbtResult<Vec<u8>> {
+    let base_type = validate_sighash_type(transaction, input_index, sighash_type)?;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+
+    let mut preimage = transaction.version.to_le_bytes().to_vec();
+    let signed_inputs: Vec<usize> = if anyone_can_pay {
+        vec![input_index]
+    } else {
+        (0..transaction.inputs.len()).collect()
+    };
+    write_compact_size(&mut preimage, signed_inputs.len() as u64);
+    for index in signed_inputs {
+        let input = &transaction.inputs[index];
+        write_out_point(&mut preimage, &input.previous_output);
+        if index == input_index {
+            write_var_bytes(&mut preimage, script_code);
+            preimage.extend(input.sequence.to_le_bytes());
+        } else {
+            write_var_bytes(&mut preimage, &[]);
+            let sequence = if base_type == SIGHASH_ALL {
+                input.sequence
+            } else {
+                0
+            };
+            preimage.extend(sequence.to_le_bytes());
+        }
+    }
+
+    match base_type {
+        SIGHASH_NONE => write_compact_size(&mut preimage, 0),
+        SIGHASH_SINGLE => {
+            write_compact_size(&mut preimage, input_index as u64 + 1);
+            for _ in 0..input_index {
+                preimage.extend(BLANK_OUTPUT_VALUE.to_le_bytes());
+                write_var_bytes(&mut preimage, &[]);
+            }
+            write_tx_out(&mut preimage, &transaction.outputs[input_index]);
+        }
+        _ => {
+            write_compact_size(&mut preimage, transaction.outputs.len() as u64);
+            for output in &transaction.outputs {
+                write_tx_out(&mut preimage, output);
+            }
+        }
+    }
+
+    preimage.extend(transaction.lock_time.to_le_bytes());
+    preimage.extend(sighash_type.to_le_bytes());
+    Ok(preimage)
+}
+
+/// Returns the BIP-143 preimage of the signature hash of the SegWit v0 input at `input_index`,
+/// spending an output of `value` satoshis.
+///
+/// `script_code` is the BIP-143 script code of the input,
+/// e.g. [`p2wpkh_script_code`] for a P2WPKH input.
+pub fn segwit_v0_sighash_preimage(
+    transaction: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    value: u64,
+    sighash_type: u32,
+) -> PsbtResult<Vec<u8>> {
+    let base_type = validate_sighash_type(transaction, input_index, sighash_type)?;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+
+    let hash_previous_outputs = if anyone_can_pay {
+        [0; 32]
+    } else {
+        let mut previous_outputs = Vec::new();
+        for input in &transaction.inputs {
+            write_out_point(&mut previous_outputs, &input.previous_output);
+        }
+        double_sha256(&previous_outputs)
+    };
+    let hash_sequence = if anyone_can_pay || base_type != SIGHASH_ALL {
+        [0; 32]
+    } else {
+        let sequences: Vec<u8> = transaction
+            .inputs
+            .iter()
+            .flat_map(|input| input.sequence.to_le_bytes())
+            .collect();
+        double_sha256(&sequences)
+    };
+    let hash_outputs = match base_type {
+        SIGHASH_NONE => [0; 32],
+        SIGHASH_SINGLE => {
+            let mut output = Vec::new();
+            write_tx_out(&mut output, &transaction.outputs[input_index]);
+            double_sha256(&output)
+        }
+        _ => {
+            let mut outputs = Vec::new();
+            for output in &transaction.outputs {
+                write_tx_out(&mut outputs, output);
+            }
+            double_sha256(&outputs)
+        }
+    };
+
+    let input = &transaction.inputs[input_index];
+    let mut preimage = transaction.version.to_le_bytes().to_vec();
+    preimage.extend(hash_previous_outputs);
+    preimage.extend(hash_sequence);
+    write_out_point(&mut preimage, &input.previous_output);
+    write_var_bytes(&mut preimage, script_code);
+    preimage.extend(value.to_le_bytes());
+    preimage.extend(input.sequence.to_le_bytes());
+    preimage.extend(hash_outputs);
+    preimage.extend(transaction.lock_time.to_le_bytes());
+    preimage.extend(sighash_type.to_le_bytes());
+    Ok(preimage)
+}
+
+/// Returns the signature hash of a preimage, its double SHA-256 digest.
+pub fn sighash(preimage: &[u8]) -> [u8; 32] {
+    double_sha256(preimage)
+}
+
+/// Returns the P2PKH output script of a public key hash,
+/// which is also the BIP-143 script code of a P2WPKH input.
+pub fn p2pkh_script(public_key_hash: &[u8; 20]) -> Vec<u8> {
+    // OP_DUP OP_HASH160 <public key hash> OP_EQUALVERIFY OP_CHECKSIG
+    let mut script = vec![0x76, 0xa9, 0x14];
+    script.extend(public_key_hash);
+    script.extend([0x88, 0xac]);
+    script
+}
+
+/// Returns the BIP-143 script code of a P2WPKH input.
+pub fn p2wpkh_script_code(public_key_hash: &[u8; 20]) -> Vec<u8> {
+    p2pkh_script(public_key_hash)
+}
+
+/// Returns the P2WPKH output script of a public key hash.
+pub fn p2wpkh_script(public_key_hash: &[u8; 20]) -> Vec<u8> {
+    // OP_0 <public key hash>
+    let mut script = vec![0x00, 0x14];
+    script.extend(public_key_hash);
+    script
+}
+
+/// Returns the P2SH output script of a script hash.
+pub fn p2sh_script(script_hash: &[u8; 20]) -> Vec<u8> {
+    // OP_HASH160 <script hash> OP_EQUAL
+    let mut script = vec![0xa9, 0x14];
+    script.extend(script_hash);
+    script.push(0x87);
+    script
+}
+
+/// Returns the base type of a sighash type, if it is supported for the input at `input_index`.
+fn validate_sighash_type(
+    transaction: &Transaction,
+    input_index: usize,
+    sighash_type: u32,
+) -> PsbtResult<u32> {
+    if input_index >= transaction.inputs.len() {
+        return Err(PsbtError::InputOutOfRange { index: input_index });
+    }
+    let base_type = sighash_type & SIGHASH_BASE_TYPE_MASK;
+    let is_supported = match base_type {
+        SIGHASH_ALL | SIGHASH_NONE => true,
+        SIGHASH_SINGLE => input_index < transaction.outputs.len(),
+        _ => false,
+    };
+    if !is_supported || sighash_type & !(SIGHASH_BASE_TYPE_MASK | SIGHASH_ANYONECANPAY) != 0 {
+        return Err(PsbtError::UnsupportedSighashType {
+            index: input_index,
+            sighash_type,
+        });
+    }
+    Ok(base_type)
+}
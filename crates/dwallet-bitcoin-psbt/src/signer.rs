@@ -0,0 +1,179 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Signing the inputs of a PSBT that are controlled by a dWallet.
+//!
+//! A dWallet signs the digest of a message under one of the [`Hash`] functions it supports,
+//! rather than a digest it is given. Bitcoin signs the double SHA-256 digest of the sighash
+//! preimage, so the message signed by the dWallet is the SHA-256 digest of the preimage,
+//! hashed again with [`Hash::SHA256`] by the dWallet.
+
+use crate::error::{PsbtError, PsbtResult};
+use crate::psbt::Psbt;
+use crate::sighash::{
+    SIGHASH_ALL, legacy_sighash_preimage, p2pkh_script, p2sh_script, p2wpkh_script,
+    p2wpkh_script_code, segwit_v0_sighash_preimage,
+};
+use dwallet_mpc_centralized_party::encodings::{public_key_to_sec1, signature_to_der};
+use dwallet_mpc_centralized_party::sign_session::{
+    DWalletSigningSession, DWalletUserKeys, sign_with_dwallet,
+};
+use message_digest::message_digest::Hash;
+use sha2::Digest;
+
+/// The largest data pushed by a single opcode, rather than with `OP_PUSHDATA1`.
+const MAX_DIRECT_PUSH_LENGTH: usize = 0x4b;
+const OP_PUSHDATA1: u8 = 0x4c;
+
+/// How an input controlled by a dWallet spends its output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DWalletSpendKind {
+    P2pkh,
+    P2wpkh,
+    /// P2WPKH nested in P2SH.
+    P2shP2wpkh,
+}
+
+/// An input of a PSBT that is controlled by a dWallet public key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DWalletInput {
+    pub index: usize,
+    pub kind: DWalletSpendKind,
+    pub sighash_type: u32,
+    /// The preimage of the signature hash of the input.
+    pub sighash_preimage: Vec<u8>,
+}
+
+/// Returns the inputs of `psbt` that spend an output controlled by `public_key`
+/// (SEC1-compressed) and are not finalized yet.
+///
+/// Inputs that spend other outputs are skipped, so a PSBT may be signed by several wallets.
+pub fn dwallet_inputs(psbt: &Psbt, public_key: &[u8]) -> PsbtResult<Vec<DWalletInput>> {
+    let public_key_hash = hash160(public_key);
+    let p2wpkh_script = p2wpkh_script(&public_key_hash);
+
+    let mut inputs = Vec::new();
+    for index in 0..psbt.inputs.len() {
+        if psbt.is_finalized(index)? {
+            continue;
+        }
+        let spent_output = match psbt.spent_output(index) {
+            Ok(spent_output) => spent_output,
+            Err(PsbtError::MissingUtxo { .. }) => continue,
+            Err(e) => return Err(e),
+        };
+        let kind = if spent_output.script_pubkey == p2pkh_script(&public_key_hash) {
+            DWalletSpendKind::P2pkh
+        } else if spent_output.script_pubkey == p2wpkh_script {
+            DWalletSpendKind::P2wpkh
+        } else if psbt.redeem_script(index)? == Some(p2wpkh_script.as_slice())
+            && spent_output.script_pubkey == p2sh_script(&hash160(&p2wpkh_script))
+        {
+            DWalletSpendKind::P2shP2wpkh
+        } else {
+            continue;
+        };
+
+        let sighash_type = psbt.sighash_type(index)?.unwrap_or(SIGHASH_ALL);
+        let transaction = &psbt.unsigned_transaction;
+        let sighash_preimage = match kind {
+            DWalletSpendKind::P2pkh => legacy_sighash_preimage(
+                transaction,
+                index,
+                &spent_output.script_pubkey,
+                sighash_type,
+            )?,
+            DWalletSpendKind::P2wpkh | DWalletSpendKind::P2shP2wpkh => segwit_v0_sighash_preimage(
+                transaction,
+                index,
+                &p2wpkh_script_code(&public_key_hash),
+                spent_output.value,
+                sighash_type,
+            )?,
+        };
+        inputs.push(DWalletInput {
+            index,
+            kind,
+            sighash_type,
+            sighash_preimage,
+        });
+    }
+    Ok(inputs)
+}
+
+/// Signs every input of `psbt` controlled by the dWallet (or its child key),
+/// with a presign and a sign session per input, and finalizes the signed inputs.
+///
+/// Returns the indices of the signed inputs.
+pub async fn sign_psbt(
+    psbt: &mut Psbt,
+    keys: &DWalletUserKeys,
+    session: &mut impl DWalletSigningSession,
+) -> PsbtResult<Vec<usize>> {
+    let invalid_keys = |error: anyhow::Error| PsbtError::InvalidDWalletKeys {
+        error: format!("{error:#}"),
+    };
+    let public_key =
+        public_key_to_sec1(keys.public_key().map_err(invalid_keys)?, true).map_err(invalid_keys)?;
+    let inputs = dwallet_inputs(psbt, &public_key)?;
+
+    for input in &inputs {
+        let signing_error = |error: anyhow::Error| PsbtError::Signing {
+            index: input.index,
+            error: format!("{error:#}"),
+        };
+        let sign_output = sign_with_dwallet(
+            keys,
+            session,
+            sha2::Sha256::digest(&input.sighash_preimage).to_vec(),
+            Hash::SHA256 as u32,
+        )
+        .await
+        .map_err(signing_error)?;
+
+        let mut signature = signature_to_der(sign_output).map_err(signing_error)?;
+        // The sighash type is serialized in a single byte after the signature.
+        signature.push(input.sighash_type as u8);
+        psbt.add_partial_signature(input.index, &public_key, signature.clone())?;
+        finalize_dwallet_input(psbt, input, &public_key, signature)?;
+    }
+
+    Ok(inputs.iter().map(|input| input.index).collect())
+}
+
+/// Finalizes an input controlled by a dWallet with its signature (its sighash type appended).
+pub fn finalize_dwallet_input(
+    psbt: &mut Psbt,
+    input: &DWalletInput,
+    public_key: &[u8],
+    signature: Vec<u8>,
+) -> PsbtResult {
+    let (final_script_sig, final_script_witness) = match input.kind {
+        DWalletSpendKind::P2pkh => {
+            let mut script_sig = push_data(&signature);
+            script_sig.extend(push_data(public_key));
+            (script_sig, vec![])
+        }
+        DWalletSpendKind::P2wpkh => (vec![], vec![signature, public_key.to_vec()]),
+        DWalletSpendKind::P2shP2wpkh => (
+            push_data(&p2wpkh_script(&hash160(public_key))),
+            vec![signature, public_key.to_vec()],
+        ),
+    };
+    psbt.finalize_input(input.index, final_script_sig, final_script_witness)
+}
+
+fn hash160(data: &[u8]) -> [u8; 20] {
+    <ripemd::Ripemd160 as ripemd::Digest>::digest(sha2::Sha256::digest(data)).into()
+}
+
+/// Returns a script pushing `data` onto the stack.
+fn push_data(data: &[u8]) -> Vec<u8> {
+    let mut script = if data.len() <= MAX_DIRECT_PUSH_LENGTH {
+        vec![data.len() as u8]
+    } else {
+        vec![OP_PUSHDATA1, data.len() as u8]
+    };
+    script.extend(data);
+    script
+}
@@ -0,0 +1,254 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! The consensus serialization of Bitcoin transactions, with and without witnesses (BIP-144).
+
+use crate::error::{PsbtError, PsbtResult};
+use sha2::Digest;
+
+const SEGWIT_MARKER: u8 = 0x00;
+const SEGWIT_FLAG: u8 = 0x01;
+
+/// A reference to an output of a previous transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutPoint {
+    /// The ID of the previous transaction, in its serialized (internal) byte order.
+    pub txid: [u8; 32],
+    pub vout: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxIn {
+    pub previous_output: OutPoint,
+    pub script_sig: Vec<u8>,
+    pub sequence: u32,
+    pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxOut {
+    /// The value of the output, in satoshis.
+    pub value: u64,
+    pub script_pubkey: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    pub version: u32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+    pub lock_time: u32,
+}
+
+impl Transaction {
+    /// Deserializes a transaction, serialized with or without witnesses.
+    pub fn from_bytes(bytes: &[u8]) -> PsbtResult<Self> {
+        let mut reader = Reader::new(bytes);
+        let transaction = Self::read(&mut reader)?;
+        reader.finish("transaction")?;
+        Ok(transaction)
+    }
+
+    /// Serializes the transaction, with its witnesses if any input has one.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.serialize(self.inputs.iter().any(|input| !input.witness.is_empty()))
+    }
+
+    /// Serializes the transaction without its witnesses.
+    pub fn to_bytes_without_witness(&self) -> Vec<u8> {
+        self.serialize(false)
+    }
+
+    /// Returns the ID of the transaction, in its serialized (internal) byte order.
+    pub fn txid(&self) -> [u8; 32] {
+        double_sha256(&self.to_bytes_without_witness())
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> PsbtResult<Self> {
+        let version = reader.read_u32()?;
+        let mut input_count = reader.read_compact_size()?;
+        let has_witness = input_count == u64::from(SEGWIT_MARKER);
+        if has_witness {
+            if reader.read_u8()? != SEGWIT_FLAG {
+                return Err(PsbtError::InvalidUnsignedTransaction {
+                    error: "invalid segwit flag".to_string(),
+                });
+            }
+            input_count = reader.read_compact_size()?;
+        }
+
+        let mut inputs = (0..input_count)
+            .map(|_| {
+                Ok(TxIn {
+                    previous_output: OutPoint {
+                        txid: reader.read_array()?,
+                        vout: reader.read_u32()?,
+                    },
+                    script_sig: reader.read_var_bytes()?,
+                    sequence: reader.read_u32()?,
+                    witness: Vec::new(),
+                })
+            })
+            .collect::<PsbtResult<Vec<_>>>()?;
+        let outputs = (0..reader.read_compact_size()?)
+            .map(|_| {
+                Ok(TxOut {
+                    value: reader.read_u64()?,
+                    script_pubkey: reader.read_var_bytes()?,
+                })
+            })
+            .collect::<PsbtResult<Vec<_>>>()?;
+        if has_witness {
+            for input in &mut inputs {
+                input.witness = read_witness(reader)?;
+            }
+        }
+
+        Ok(Transaction {
+            version,
+            inputs,
+            outputs,
+            lock_time: reader.read_u32()?,
+        })
+    }
+
+    fn serialize(&self, with_witness: bool) -> Vec<u8> {
+        let mut bytes = self.version.to_le_bytes().to_vec();
+        if with_witness {
+            bytes.extend([SEGWIT_MARKER, SEGWIT_FLAG]);
+        }
+        write_compact_size(&mut bytes, self.inputs.len() as u64);
+        for input in &self.inputs {
+            write_out_point(&mut bytes, &input.previous_output);
+            write_var_bytes(&mut bytes, &input.script_sig);
+            bytes.extend(input.sequence.to_le_bytes());
+        }
+        write_compact_size(&mut bytes, self.outputs.len() as u64);
+        for output in &self.outputs {
+            write_tx_out(&mut bytes, output);
+        }
+        if with_witness {
+            for input in &self.inputs {
+                write_witness(&mut bytes, &input.witness);
+            }
+        }
+        bytes.extend(self.lock_time.to_le_bytes());
+        bytes
+    }
+}
+
+pub(crate) fn double_sha256(data: &[u8]) -> [u8; 32] {
+    sha2::Sha256::digest(sha2::Sha256::digest(data)).into()
+}
+
+pub(crate) fn write_compact_size(bytes: &mut Vec<u8>, value: u64) {
+    match value {
+        0..=0xfc => bytes.push(value as u8),
+        0xfd..=0xffff => {
+            bytes.push(0xfd);
+            bytes.extend((value as u16).to_le_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            bytes.push(0xfe);
+            bytes.extend((value as u32).to_le_bytes());
+        }
+        _ => {
+            bytes.push(0xff);
+            bytes.extend(value.to_le_bytes());
+        }
+    }
+}
+
+pub(crate) fn write_var_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    write_compact_size(bytes, data.len() as u64);
+    bytes.extend(data);
+}
+
+pub(crate) fn write_out_point(bytes: &mut Vec<u8>, out_point: &OutPoint) {
+    bytes.extend(out_point.txid);
+    bytes.extend(out_point.vout.to_le_bytes());
+}
+
+pub(crate) fn write_tx_out(bytes: &mut Vec<u8>, output: &TxOut) {
+    bytes.extend(output.value.to_le_bytes());
+    write_var_bytes(bytes, &output.script_pubkey);
+}
+
+pub(crate) fn write_witness(bytes: &mut Vec<u8>, witness: &[Vec<u8>]) {
+    write_compact_size(bytes, witness.len() as u64);
+    for item in witness {
+        write_var_bytes(bytes, item);
+    }
+}
+
+pub(crate) fn read_witness(reader: &mut Reader) -> PsbtResult<Vec<Vec<u8>>> {
+    (0..reader.read_compact_size()?)
+        .map(|_| reader.read_var_bytes())
+        .collect()
+}
+
+/// A cursor over serialized data.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Fails if any data is left after the end of `context`.
+    pub(crate) fn finish(&self, context: &'static str) -> PsbtResult {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(PsbtError::TrailingData {
+                context,
+                length: self.bytes.len(),
+            })
+        }
+    }
+
+    pub(crate) fn read_bytes(&mut self, length: usize) -> PsbtResult<&'a [u8]> {
+        if self.bytes.len() < length {
+            return Err(PsbtError::UnexpectedEnd { context: "bytes" });
+        }
+        let (bytes, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_array<const N: usize>(&mut self) -> PsbtResult<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> PsbtResult<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub(crate) fn read_u32(&mut self) -> PsbtResult<u32> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub(crate) fn read_u64(&mut self) -> PsbtResult<u64> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    pub(crate) fn read_compact_size(&mut self) -> PsbtResult<u64> {
+        Ok(match self.read_u8()? {
+            0xfd => u64::from(u16::from_le_bytes(self.read_array()?)),
+            0xfe => u64::from(u32::from_le_bytes(self.read_array()?)),
+            0xff => self.read_u64()?,
+            value => u64::from(value),
+        })
+    }
+
+    pub(crate) fn read_var_bytes(&mut self) -> PsbtResult<Vec<u8>> {
+        let length = self.read_compact_size()?;
+        let length =
+            usize::try_from(length).map_err(|_| PsbtError::UnexpectedEnd { context: "bytes" })?;
+        Ok(self.read_bytes(length)?.to_vec())
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Offline test vectors, taken from the native P2WPKH example of BIP-143.
+
+use dwallet_bitcoin_psbt::psbt::{KeyValueMap, PSBT_IN_PARTIAL_SIG, PSBT_IN_WITNESS_UTXO};
+use dwallet_bitcoin_psbt::sighash::{
+    SIGHASH_ALL, legacy_sighash_preimage, p2wpkh_script_code, segwit_v0_sighash_preimage, sighash,
+};
+use dwallet_bitcoin_psbt::{
+    DWalletSpendKind, Psbt, PsbtError, Transaction, dwallet_inputs, finalize_dwallet_input,
+};
+
+const UNSIGNED_TRANSACTION: &str = "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000";
+const SIGNED_TRANSACTION: &str = "01000000000102fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000494830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac000247304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee0121025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee635711000000";
+
+/// The P2PK output script spent by the first (legacy) input.
+const P2PK_SCRIPT: &str = "2103c9f4836b9a4f77fc0d81f7bcb01b7f1b35916864b9476c241ce9fc198bd25432ac";
+const P2PK_SIGHASH: &str = "63cec688ee06a91e913875356dd4dea2f8e0f2a2659885372da2a37e32c7532e";
+const P2PK_SCRIPT_SIG: &str = "4830450221008b9d1dc26ba6a9cb62127b02742fa9d754cd3bebf337f7a55d114c8e5cdd30be022040529b194ba3f9281a99f2b1c0a19c0489bc22ede944ccf4ecbab4cc618ef3ed01";
+
+/// The public key controlling the P2WPKH output spent by the second input, and its hash.
+const P2WPKH_PUBLIC_KEY: &str =
+    "025476c2e83188368da1ff3e292e7acafcdb3566bb0ad253f62fc70f07aeee6357";
+const P2WPKH_PUBLIC_KEY_HASH: &str = "1d0f172a0ecb48aee1be1f2687d2963ae33f71a1";
+const P2WPKH_VALUE: u64 = 600_000_000;
+const P2WPKH_SIGHASH: &str = "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670";
+const P2WPKH_SIGNATURE: &str = "304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee01";
+
+fn unsigned_transaction() -> Transaction {
+    Transaction::from_bytes(&hex::decode(UNSIGNED_TRANSACTION).unwrap()).unwrap()
+}
+
+fn public_key_hash() -> [u8; 20] {
+    hex::decode(P2WPKH_PUBLIC_KEY_HASH)
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+/// A PSBT of the unsigned transaction, with the witness UTXO of its P2WPKH input.
+fn unsigned_psbt() -> Psbt {
+    let mut witness_utxo = P2WPKH_VALUE.to_le_bytes().to_vec();
+    witness_utxo.push(0x16);
+    witness_utxo.extend([0x00, 0x14]);
+    witness_utxo.extend(public_key_hash());
+
+    Psbt {
+        unsigned_transaction: unsigned_transaction(),
+        global: KeyValueMap::new(),
+        inputs: vec![
+            KeyValueMap::new(),
+            KeyValueMap::from([(vec![PSBT_IN_WITNESS_UTXO], witness_utxo)]),
+        ],
+        outputs: vec![KeyValueMap::new(), KeyValueMap::new()],
+    }
+}
+
+#[test]
+fn transaction_round_trips() {
+    let transaction = unsigned_transaction();
+    assert_eq!(hex::encode(transaction.to_bytes()), UNSIGNED_TRANSACTION);
+
+    let signed_transaction =
+        Transaction::from_bytes(&hex::decode(SIGNED_TRANSACTION).unwrap()).unwrap();
+    assert_eq!(
+        hex::encode(signed_transaction.to_bytes()),
+        SIGNED_TRANSACTION
+    );
+    assert_eq!(signed_transaction.outputs, transaction.outputs);
+    assert_eq!(signed_transaction.inputs[1].witness.len(), 2);
+}
+
+#[test]
+fn legacy_sighash_matches_test_vector() {
+    let preimage = legacy_sighash_preimage(
+        &unsigned_transaction(),
+        0,
+        &hex::decode(P2PK_SCRIPT).unwrap(),
+        SIGHASH_ALL,
+    )
+    .unwrap();
+    assert_eq!(hex::encode(sighash(&preimage)), P2PK_SIGHASH);
+}
+
+#[test]
+fn segwit_v0_sighash_matches_test_vector() {
+    let preimage = segwit_v0_sighash_preimage(
+        &unsigned_transaction(),
+        1,
+        &p2wpkh_script_code(&public_key_hash()),
+        P2WPKH_VALUE,
+        SIGHASH_ALL,
+    )
+    .unwrap();
+    assert_eq!(hex::encode(sighash(&preimage)), P2WPKH_SIGHASH);
+}
+
+#[test]
+fn unsupported_sighash_type_is_rejected() {
+    assert_eq!(
+        legacy_sighash_preimage(&unsigned_transaction(), 0, &[], 0x04),
+        Err(PsbtError::UnsupportedSighashType {
+            index: 0,
+            sighash_type: 0x04
+        })
+    );
+}
+
+#[test]
+fn psbt_round_trips() {
+    let psbt = unsigned_psbt();
+    assert_eq!(Psbt::from_bytes(&psbt.to_bytes()).unwrap(), psbt);
+    assert_eq!(Psbt::from_base64(&psbt.to_base64()).unwrap(), psbt);
+}
+
+#[test]
+fn invalid_psbts_are_rejected() {
+    let bytes = unsigned_psbt().to_bytes();
+
+    let mut invalid_magic = bytes.clone();
+    invalid_magic[0] = b'P';
+    assert_eq!(
+        Psbt::from_bytes(&invalid_magic),
+        Err(PsbtError::InvalidMagic)
+    );
+
+    let mut trailing_data = bytes.clone();
+    trailing_data.push(0);
+    assert!(matches!(
+        Psbt::from_bytes(&trailing_data),
+        Err(PsbtError::TrailingData { .. })
+    ));
+
+    assert!(matches!(
+        Psbt::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(PsbtError::UnexpectedEnd { .. })
+    ));
+}
+
+#[test]
+fn dwallet_inputs_are_the_inputs_controlled_by_the_public_key() {
+    let psbt = unsigned_psbt();
+    let inputs = dwallet_inputs(&psbt, &hex::decode(P2WPKH_PUBLIC_KEY).unwrap()).unwrap();
+
+    assert_eq!(inputs.len(), 1);
+    assert_eq!(inputs[0].index, 1);
+    assert_eq!(inputs[0].kind, DWalletSpendKind::P2wpkh);
+    assert_eq!(inputs[0].sighash_type, SIGHASH_ALL);
+    assert_eq!(
+        hex::encode(sighash(&inputs[0].sighash_preimage)),
+        P2WPKH_SIGHASH
+    );
+
+    let other_public_key = hex::decode(P2PK_SCRIPT).unwrap()[1..34].to_vec();
+    assert!(dwallet_inputs(&psbt, &other_public_key).unwrap().is_empty());
+}
+
+#[test]
+fn finalized_psbt_extracts_signed_transaction() {
+    let mut psbt = unsigned_psbt();
+    let public_key = hex::decode(P2WPKH_PUBLIC_KEY).unwrap();
+    let signature = hex::decode(P2WPKH_SIGNATURE).unwrap();
+    let input = dwallet_inputs(&psbt, &public_key).unwrap().remove(0);
+
+    assert_eq!(
+        psbt.extract_transaction(),
+        Err(PsbtError::InputNotFinalized { index: 0 })
+    );
+
+    psbt.add_partial_signature(1, &public_key, signature.clone())
+        .unwrap();
+    finalize_dwallet_input(&mut psbt, &input, &public_key, signature).unwrap();
+    psbt.finalize_input(0, hex::decode(P2PK_SCRIPT_SIG).unwrap(), vec![])
+        .unwrap();
+
+    assert!(
+        psbt.inputs[1]
+            .keys()
+            .all(|key| key[0] != PSBT_IN_PARTIAL_SIG)
+    );
+    assert!(dwallet_inputs(&psbt, &public_key).unwrap().is_empty());
+    assert_eq!(
+        hex::encode(psbt.extract_transaction().unwrap().to_bytes()),
+        SIGNED_TRANSACTION
+    );
+}
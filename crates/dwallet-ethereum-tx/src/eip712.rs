@@ -0,0 +1,278 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Hashing of EIP-712 typed structured data, as given to `eth_signTypedData_v4`.
+
+use crate::error::{EthereumError, EthereumResult};
+use crate::transaction::parse_address;
+use serde::Deserialize;
+use serde_json::Value;
+use sha3::Digest;
+use std::collections::{BTreeMap, BTreeSet};
+
+const EIP_712_DOMAIN_TYPE: &str = "EIP712Domain";
+
+/// The prefix of the signed message, making it an invalid RLP encoding of a transaction.
+const EIP_712_PREFIX: [u8; 2] = [0x19, 0x01];
+
+/// The fields of the EIP-712 domain, in their canonical order.
+const EIP_712_DOMAIN_FIELDS: [(&str, &str); 5] = [
+    ("name", "string"),
+    ("version", "string"),
+    ("chainId", "uint256"),
+    ("verifyingContract", "address"),
+    ("salt", "bytes32"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct TypedDataField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    /// The struct types, by name.
+    /// If `EIP712Domain` is not given, it is inferred from the fields of `domain`.
+    pub types: BTreeMap<String, Vec<TypedDataField>>,
+    pub primary_type: String,
+    pub domain: Value,
+    pub message: Value,
+}
+
+impl TypedData {
+    pub fn from_json(typed_data: &str) -> EthereumResult<Self> {
+        serde_json::from_str(typed_data).map_err(|e| invalid_typed_data(e.to_string()))
+    }
+
+    /// Returns the message the typed data is signed over:
+    /// `0x19 0x01 || domainSeparator || hashStruct(message)`.
+    pub fn signing_payload(&self) -> EthereumResult<Vec<u8>> {
+        let mut payload = EIP_712_PREFIX.to_vec();
+        payload.extend(self.domain_separator()?);
+        if self.primary_type != EIP_712_DOMAIN_TYPE {
+            payload.extend(self.hash_struct(&self.primary_type, &self.message)?);
+        }
+        Ok(payload)
+    }
+
+    /// Returns the keccak digest of the signing payload.
+    pub fn signing_hash(&self) -> EthereumResult<[u8; 32]> {
+        Ok(keccak256(&self.signing_payload()?))
+    }
+
+    pub fn domain_separator(&self) -> EthereumResult<[u8; 32]> {
+        self.hash_struct(EIP_712_DOMAIN_TYPE, &self.domain)
+    }
+
+    /// Returns `hashStruct(value)`: the keccak digest of the type hash and encoded fields of
+    /// a struct of type `struct_type`.
+    pub fn hash_struct(&self, struct_type: &str, value: &Value) -> EthereumResult<[u8; 32]> {
+        let fields = self.struct_fields(struct_type)?;
+        let value = value
+            .as_object()
+            .ok_or_else(|| invalid_typed_data(format!("{struct_type} is not an object")))?;
+
+        let mut encoding = self.type_hash(struct_type)?.to_vec();
+        for field in fields {
+            let field_value = value.get(&field.name).ok_or_else(|| {
+                invalid_typed_data(format!("{struct_type} has no field {}", field.name))
+            })?;
+            encoding.extend(self.encode_value(&field.field_type, field_value)?);
+        }
+        Ok(keccak256(&encoding))
+    }
+
+    /// Returns `encodeType(struct_type)`: the signature of the struct type,
+    /// followed by the signatures of the struct types it references, sorted by name.
+    pub fn encode_type(&self, struct_type: &str) -> EthereumResult<String> {
+        let mut referenced_types = BTreeSet::new();
+        self.collect_referenced_types(struct_type, &mut referenced_types)?;
+        referenced_types.remove(struct_type);
+
+        std::iter::once(struct_type)
+            .chain(referenced_types.iter().map(String::as_str))
+            .map(|struct_type| {
+                let fields: Vec<String> = self
+                    .struct_fields(struct_type)?
+                    .iter()
+                    .map(|field| format!("{} {}", field.field_type, field.name))
+                    .collect();
+                Ok(format!("{struct_type}({})", fields.join(",")))
+            })
+            .collect()
+    }
+
+    pub fn type_hash(&self, struct_type: &str) -> EthereumResult<[u8; 32]> {
+        Ok(keccak256(self.encode_type(struct_type)?.as_bytes()))
+    }
+
+    fn struct_fields(&self, struct_type: &str) -> EthereumResult<Vec<TypedDataField>> {
+        if let Some(fields) = self.types.get(struct_type) {
+            return Ok(fields.clone());
+        }
+        if struct_type != EIP_712_DOMAIN_TYPE {
+            return Err(invalid_typed_data(format!("unknown type {struct_type}")));
+        }
+        Ok(EIP_712_DOMAIN_FIELDS
+            .iter()
+            .filter(|(name, _)| self.domain.get(name).is_some())
+            .map(|(name, field_type)| TypedDataField {
+                name: name.to_string(),
+                field_type: field_type.to_string(),
+            })
+            .collect())
+    }
+
+    fn collect_referenced_types(
+        &self,
+        struct_type: &str,
+        referenced_types: &mut BTreeSet<String>,
+    ) -> EthereumResult {
+        if !referenced_types.insert(struct_type.to_string()) {
+            return Ok(());
+        }
+        for field in self.struct_fields(struct_type)? {
+            let base_type = field.field_type.split('[').next().unwrap_or_default();
+            if self.types.contains_key(base_type) {
+                self.collect_referenced_types(base_type, referenced_types)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `encodeData` of a single value: 32 bytes for atomic types,
+    /// and the keccak digest of the contents of dynamic, array and struct types.
+    fn encode_value(&self, field_type: &str, value: &Value) -> EthereumResult<[u8; 32]> {
+        let invalid_value = || invalid_typed_data(format!("invalid {field_type} value {value}"));
+
+        if let Some((element_type, length)) = field_type
+            .strip_suffix(']')
+            .and_then(|array_type| array_type.rsplit_once('['))
+        {
+            let elements = value.as_array().ok_or_else(invalid_value)?;
+            if !length.is_empty() && length.parse::<usize>().ok() != Some(elements.len()) {
+                return Err(invalid_value());
+            }
+            let mut encoding = Vec::new();
+            for element in elements {
+                encoding.extend(self.encode_value(element_type, element)?);
+            }
+            return Ok(keccak256(&encoding));
+        }
+        if self.types.contains_key(field_type) {
+            return self.hash_struct(field_type, value);
+        }
+
+        let mut encoding = [0; 32];
+        match field_type {
+            "string" => {
+                return Ok(keccak256(
+                    value.as_str().ok_or_else(invalid_value)?.as_bytes(),
+                ));
+            }
+            "bytes" => return Ok(keccak256(&decode_hex(value).ok_or_else(invalid_value)?)),
+            "bool" => encoding[31] = u8::from(value.as_bool().ok_or_else(invalid_value)?),
+            "address" => {
+                let address = parse_address(value.as_str().ok_or_else(invalid_value)?)?;
+                encoding[12..].copy_from_slice(&address);
+            }
+            _ => {
+                if let Some(length) = field_type.strip_prefix("bytes") {
+                    let bytes = decode_hex(value).ok_or_else(invalid_value)?;
+                    let length: usize = length.parse().map_err(|_| invalid_value())?;
+                    if !(1..=32).contains(&length) || bytes.len() != length {
+                        return Err(invalid_value());
+                    }
+                    encoding[..length].copy_from_slice(&bytes);
+                } else if let Some(bits) = field_type.strip_prefix("uint") {
+                    encoding = encode_integer(value, bits, false).ok_or_else(invalid_value)?;
+                } else if let Some(bits) = field_type.strip_prefix("int") {
+                    encoding = encode_integer(value, bits, true).ok_or_else(invalid_value)?;
+                } else {
+                    return Err(invalid_typed_data(format!("unknown type {field_type}")));
+                }
+            }
+        }
+        Ok(encoding)
+    }
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    sha3::Keccak256::digest(data).into()
+}
+
+fn invalid_typed_data(error: String) -> EthereumError {
+    EthereumError::InvalidTypedData { error }
+}
+
+fn decode_hex(value: &Value) -> Option<Vec<u8>> {
+    hex::decode(value.as_str()?.strip_prefix("0x")?).ok()
+}
+
+/// Encodes an integer of `bits` bits (a multiple of 8 up to 256), given as a JSON number or as
+/// a decimal or `0x`-prefixed hex string, as a 256-bit big-endian two's complement integer.
+fn encode_integer(value: &Value, bits: &str, is_signed: bool) -> Option<[u8; 32]> {
+    let bits: usize = bits.parse().ok()?;
+    if bits == 0 || bits > 256 || !bits.is_multiple_of(8) {
+        return None;
+    }
+    let value = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(string) => string.clone(),
+        _ => return None,
+    };
+    let (is_negative, magnitude) = match value.strip_prefix('-') {
+        Some(magnitude) if is_signed => (true, magnitude),
+        Some(_) => return None,
+        None => (false, value.as_str()),
+    };
+
+    let mut encoding = [0u8; 32];
+    if let Some(hex_magnitude) = magnitude.strip_prefix("0x") {
+        if hex_magnitude.is_empty() || hex_magnitude.len() > 64 {
+            return None;
+        }
+        let padded_magnitude = format!("{hex_magnitude:0>64}");
+        encoding.copy_from_slice(&hex::decode(padded_magnitude).ok()?);
+    } else {
+        if magnitude.is_empty() {
+            return None;
+        }
+        for digit in magnitude.chars() {
+            let mut carry = digit.to_digit(10)?;
+            for byte in encoding.iter_mut().rev() {
+                let product = u32::from(*byte) * 10 + carry;
+                *byte = product as u8;
+                carry = product >> 8;
+            }
+            if carry != 0 {
+                return None;
+            }
+        }
+    }
+
+    if is_negative {
+        // Two's complement negation: invert and add one.
+        let mut carry = true;
+        for byte in encoding.iter_mut().rev() {
+            let (sum, overflow) = (!*byte).overflowing_add(u8::from(carry));
+            *byte = sum;
+            carry = overflow;
+        }
+    }
+
+    // Every bit above the value bits must be zero for unsigned integers,
+    // and a copy of the sign bit, which must match the sign of the value, for signed integers.
+    let value_bytes = bits / 8;
+    let has_sign_bit = is_signed && encoding[32 - value_bytes] & 0x80 != 0;
+    let extension_byte = if has_sign_bit { 0xff } else { 0x00 };
+    let is_zero = encoding.iter().all(|byte| *byte == 0);
+    let is_in_range = encoding[..32 - value_bytes]
+        .iter()
+        .all(|byte| *byte == extension_byte)
+        && (is_zero || is_negative == has_sign_bit);
+    is_in_range.then_some(encoding)
+}
@@ -0,0 +1,24 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use thiserror::Error;
+
+pub type EthereumResult<T = ()> = Result<T, EthereumError>;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EthereumError {
+    #[error("invalid address {address}")]
+    InvalidAddress { address: String },
+
+    #[error("invalid typed data: {error}")]
+    InvalidTypedData { error: String },
+
+    #[error("invalid dWallet keys: {error}")]
+    InvalidDWalletKeys { error: String },
+
+    #[error("failed to sign: {error}")]
+    Signing { error: String },
+
+    #[error("recovery ID {recovery_id} has no y parity")]
+    InvalidRecoveryId { recovery_id: u8 },
+}
@@ -0,0 +1,35 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Signing Ethereum transactions and EIP-712 structured data with dWallets.
+//!
+//! This crate RLP-encodes EIP-2930 and EIP-1559 typed transactions and hashes EIP-712 typed data,
+//! signs their keccak digest with a dWallet, recovers the y parity of the signature, and returns
+//! a raw transaction ready for `eth_sendRawTransaction` (or an `r || s || v` signature).
+//! Submitting the presign and sign requests to the network is left to
+//! a [`DWalletSigningSession`], so this crate has no node or Sui dependencies.
+//!
+//! ```ignore
+//! let transaction = TypedTransaction::Eip1559(Eip1559Transaction { chain_id: 1, .. });
+//! let raw_transaction = sign_transaction(&transaction, &user_keys, &mut session).await?;
+//!
+//! let typed_data = TypedData::from_json(&typed_data_json)?;
+//! let signature = sign_typed_data(&typed_data, &user_keys, &mut session).await?.to_bytes();
+//! ```
+
+pub mod eip712;
+pub mod error;
+pub mod rlp;
+pub mod signer;
+pub mod transaction;
+
+pub use dwallet_mpc_centralized_party::sign_session::{
+    DWalletSignRequest, DWalletSigningSession, DWalletUserKeys,
+};
+pub use eip712::TypedData;
+pub use error::{EthereumError, EthereumResult};
+pub use signer::{sign_payload, sign_transaction, sign_typed_data};
+pub use transaction::{
+    AccessListItem, Address, Eip1559Transaction, Eip2930Transaction, EthereumSignature,
+    TypedTransaction, checksum_address, parse_address,
+};
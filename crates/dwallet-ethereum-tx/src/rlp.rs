@@ -0,0 +1,108 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Recursive Length Prefix (RLP) encoding.
+
+const SHORT_STRING_OFFSET: u8 = 0x80;
+const LONG_STRING_OFFSET: u8 = 0xb7;
+const SHORT_LIST_OFFSET: u8 = 0xc0;
+const LONG_LIST_OFFSET: u8 = 0xf7;
+
+/// The longest payload whose length is encoded in its prefix byte.
+const MAX_SHORT_PAYLOAD_LENGTH: usize = 55;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+impl RlpItem {
+    /// An unsigned integer, encoded as its big-endian bytes without leading zeros.
+    pub fn uint(value: impl Into<u128>) -> Self {
+        Self::uint_bytes(&value.into().to_be_bytes())
+    }
+
+    /// An unsigned integer given by its big-endian bytes, encoded without leading zeros.
+    pub fn uint_bytes(value: &[u8]) -> Self {
+        let first_non_zero_byte = value
+            .iter()
+            .position(|byte| *byte != 0)
+            .unwrap_or(value.len());
+        Self::Bytes(value[first_non_zero_byte..].to_vec())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            RlpItem::Bytes(bytes) if bytes.len() == 1 && bytes[0] < SHORT_STRING_OFFSET => {
+                bytes.clone()
+            }
+            RlpItem::Bytes(bytes) => {
+                let mut encoding =
+                    encode_length(bytes.len(), SHORT_STRING_OFFSET, LONG_STRING_OFFSET);
+                encoding.extend(bytes);
+                encoding
+            }
+            RlpItem::List(items) => {
+                let payload: Vec<u8> = items.iter().flat_map(RlpItem::encode).collect();
+                let mut encoding =
+                    encode_length(payload.len(), SHORT_LIST_OFFSET, LONG_LIST_OFFSET);
+                encoding.extend(payload);
+                encoding
+            }
+        }
+    }
+}
+
+fn encode_length(length: usize, short_offset: u8, long_offset: u8) -> Vec<u8> {
+    if length <= MAX_SHORT_PAYLOAD_LENGTH {
+        return vec![short_offset + length as u8];
+    }
+    let RlpItem::Bytes(length_bytes) = RlpItem::uint(length as u64) else {
+        unreachable!("an integer is encoded as bytes");
+    };
+    let mut encoding = vec![long_offset + length_bytes.len() as u8];
+    encoding.extend(length_bytes);
+    encoding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(value: &str) -> RlpItem {
+        RlpItem::Bytes(value.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn encodes_strings_and_integers() {
+        assert_eq!(string("dog").encode(), b"\x83dog");
+        assert_eq!(string("").encode(), [0x80]);
+        assert_eq!(RlpItem::uint(0u8).encode(), [0x80]);
+        assert_eq!(RlpItem::uint(15u8).encode(), [0x0f]);
+        assert_eq!(RlpItem::uint(1024u16).encode(), [0x82, 0x04, 0x00]);
+
+        let long_string = "Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let mut expected = vec![0xb8, 0x38];
+        expected.extend(long_string.as_bytes());
+        assert_eq!(string(long_string).encode(), expected);
+    }
+
+    #[test]
+    fn encodes_lists() {
+        assert_eq!(RlpItem::List(vec![]).encode(), [0xc0]);
+        assert_eq!(
+            RlpItem::List(vec![string("cat"), string("dog")]).encode(),
+            b"\xc8\x83cat\x83dog"
+        );
+
+        // The set theoretical representation of three.
+        let empty = || RlpItem::List(vec![]);
+        let one = || RlpItem::List(vec![empty()]);
+        let three = RlpItem::List(vec![empty(), one(), RlpItem::List(vec![empty(), one()])]);
+        assert_eq!(
+            three.encode(),
+            [0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0]
+        );
+    }
+}
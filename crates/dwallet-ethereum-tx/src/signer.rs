@@ -0,0 +1,62 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Signing Ethereum transactions and typed data with a dWallet.
+//!
+//! Ethereum signs the keccak digest of a payload, which a dWallet computes itself
+//! with [`Hash::KECCAK256`], so the dWallet signs the payload as is.
+//! The network returns `(r, s)` alone; the y parity is found by public key recovery.
+
+use crate::eip712::TypedData;
+use crate::error::{EthereumError, EthereumResult};
+use crate::transaction::{EthereumSignature, TypedTransaction};
+use dwallet_mpc_centralized_party::encodings::signature_to_recoverable;
+use dwallet_mpc_centralized_party::sign_session::{
+    DWalletSigningSession, DWalletUserKeys, sign_with_dwallet,
+};
+use message_digest::message_digest::Hash;
+
+/// Signs a typed transaction with the dWallet (or its child key),
+/// and returns the raw signed transaction, ready to broadcast.
+pub async fn sign_transaction(
+    transaction: &TypedTransaction,
+    keys: &DWalletUserKeys,
+    session: &mut impl DWalletSigningSession,
+) -> EthereumResult<Vec<u8>> {
+    let signature = sign_payload(transaction.signing_payload(), keys, session).await?;
+    Ok(transaction.encode_signed(&signature))
+}
+
+/// Signs EIP-712 typed data with the dWallet (or its child key).
+pub async fn sign_typed_data(
+    typed_data: &TypedData,
+    keys: &DWalletUserKeys,
+    session: &mut impl DWalletSigningSession,
+) -> EthereumResult<EthereumSignature> {
+    sign_payload(typed_data.signing_payload()?, keys, session).await
+}
+
+/// Signs the keccak digest of `payload`, and recovers the y parity of the signature.
+pub async fn sign_payload(
+    payload: Vec<u8>,
+    keys: &DWalletUserKeys,
+    session: &mut impl DWalletSigningSession,
+) -> EthereumResult<EthereumSignature> {
+    let hash_type = Hash::KECCAK256 as u32;
+    let public_key = keys
+        .public_key()
+        .map_err(|e| EthereumError::InvalidDWalletKeys {
+            error: format!("{e:#}"),
+        })?;
+    let signing_error = |error: anyhow::Error| EthereumError::Signing {
+        error: format!("{error:#}"),
+    };
+
+    let sign_output = sign_with_dwallet(keys, session, payload.clone(), hash_type)
+        .await
+        .map_err(signing_error)?;
+    let (signature, recovery_id) =
+        signature_to_recoverable(sign_output, public_key, payload, hash_type)
+            .map_err(signing_error)?;
+    EthereumSignature::from_compact(&signature, recovery_id)
+}
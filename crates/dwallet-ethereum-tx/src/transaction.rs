@@ -0,0 +1,225 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Typed Ethereum transactions: EIP-2930 (access list) and EIP-1559 (dynamic fee) transactions.
+//!
+//! A typed transaction is signed over the keccak digest of its type followed by the RLP encoding
+//! of its fields, and broadcast with its signature's y parity, `r` and `s` appended to its fields.
+
+use crate::error::{EthereumError, EthereumResult};
+use crate::rlp::RlpItem;
+use sha3::Digest;
+
+pub const EIP_2930_TRANSACTION_TYPE: u8 = 0x01;
+pub const EIP_1559_TRANSACTION_TYPE: u8 = 0x02;
+
+pub type Address = [u8; 20];
+
+/// Parses a hex address, validating its EIP-55 checksum if it is mixed-case.
+pub fn parse_address(address: &str) -> EthereumResult<Address> {
+    let invalid_address = || EthereumError::InvalidAddress {
+        address: address.to_string(),
+    };
+    let hex_address = address.strip_prefix("0x").unwrap_or(address);
+    let parsed_address: Address = hex::decode(hex_address)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(invalid_address)?;
+
+    let is_mixed_case = hex_address.chars().any(|c| c.is_ascii_uppercase())
+        && hex_address.chars().any(|c| c.is_ascii_lowercase());
+    if is_mixed_case && checksum_address(&parsed_address) != format!("0x{hex_address}") {
+        return Err(invalid_address());
+    }
+    Ok(parsed_address)
+}
+
+/// Returns the EIP-55 checksummed hex encoding of an address.
+pub fn checksum_address(address: &Address) -> String {
+    let hex_address = hex::encode(address);
+    let checksum = sha3::Keccak256::digest(hex_address.as_bytes());
+    let checksummed_address: String = hex_address
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let checksum_nibble = (checksum[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if checksum_nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{checksummed_address}")
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<[u8; 32]>,
+}
+
+/// An EIP-2930 transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Eip2930Transaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    /// The gas price, in wei.
+    pub gas_price: u128,
+    pub gas_limit: u64,
+    /// The recipient, or `None` for a contract creation.
+    pub to: Option<Address>,
+    /// The transferred value, in wei.
+    pub value: u128,
+    pub data: Vec<u8>,
+    pub access_list: Vec<AccessListItem>,
+}
+
+/// An EIP-1559 transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Eip1559Transaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    /// The maximal priority fee per gas, in wei.
+    pub max_priority_fee_per_gas: u128,
+    /// The maximal fee per gas, in wei.
+    pub max_fee_per_gas: u128,
+    pub gas_limit: u64,
+    /// The recipient, or `None` for a contract creation.
+    pub to: Option<Address>,
+    /// The transferred value, in wei.
+    pub value: u128,
+    pub data: Vec<u8>,
+    pub access_list: Vec<AccessListItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypedTransaction {
+    Eip2930(Eip2930Transaction),
+    Eip1559(Eip1559Transaction),
+}
+
+/// A secp256k1 signature, normalized to low-s, with the parity of the y coordinate of its `R`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EthereumSignature {
+    pub y_parity: u8,
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+}
+
+impl EthereumSignature {
+    /// Builds a signature from its 64-byte compact `r || s` encoding and its recovery ID.
+    pub fn from_compact(signature: &[u8], recovery_id: u8) -> EthereumResult<Self> {
+        // Recovery IDs 2 and 3 mark an `R` whose x coordinate overflowed the group order,
+        // which Ethereum can't represent.
+        if recovery_id > 1 {
+            return Err(EthereumError::InvalidRecoveryId { recovery_id });
+        }
+        let (r, s) = signature
+            .split_at_checked(32)
+            .and_then(|(r, s)| Some((r.try_into().ok()?, s.try_into().ok()?)))
+            .ok_or_else(|| EthereumError::Signing {
+                error: "a compact signature is 64 bytes long".to_string(),
+            })?;
+        Ok(Self {
+            y_parity: recovery_id,
+            r,
+            s,
+        })
+    }
+
+    /// Returns the 65-byte `r || s || v` encoding of the signature, where `v` is `27 + y_parity`,
+    /// as used by `eth_sign` and EIP-712 signatures.
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0; 65];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..64].copy_from_slice(&self.s);
+        bytes[64] = 27 + self.y_parity;
+        bytes
+    }
+}
+
+impl TypedTransaction {
+    pub fn transaction_type(&self) -> u8 {
+        match self {
+            TypedTransaction::Eip2930(_) => EIP_2930_TRANSACTION_TYPE,
+            TypedTransaction::Eip1559(_) => EIP_1559_TRANSACTION_TYPE,
+        }
+    }
+
+    /// Returns the message the transaction is signed over: its type followed by its RLP encoding.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        self.encode(self.fields())
+    }
+
+    /// Returns the keccak digest of the signing payload.
+    pub fn signing_hash(&self) -> [u8; 32] {
+        sha3::Keccak256::digest(self.signing_payload()).into()
+    }
+
+    /// Returns the raw signed transaction, ready to broadcast with `eth_sendRawTransaction`.
+    pub fn encode_signed(&self, signature: &EthereumSignature) -> Vec<u8> {
+        let mut fields = self.fields();
+        fields.extend([
+            RlpItem::uint(signature.y_parity),
+            RlpItem::uint_bytes(&signature.r),
+            RlpItem::uint_bytes(&signature.s),
+        ]);
+        self.encode(fields)
+    }
+
+    fn encode(&self, fields: Vec<RlpItem>) -> Vec<u8> {
+        let mut encoding = vec![self.transaction_type()];
+        encoding.extend(RlpItem::List(fields).encode());
+        encoding
+    }
+
+    fn fields(&self) -> Vec<RlpItem> {
+        match self {
+            TypedTransaction::Eip2930(transaction) => vec![
+                RlpItem::uint(transaction.chain_id),
+                RlpItem::uint(transaction.nonce),
+                RlpItem::uint(transaction.gas_price),
+                RlpItem::uint(transaction.gas_limit),
+                encode_to(&transaction.to),
+                RlpItem::uint(transaction.value),
+                RlpItem::Bytes(transaction.data.clone()),
+                encode_access_list(&transaction.access_list),
+            ],
+            TypedTransaction::Eip1559(transaction) => vec![
+                RlpItem::uint(transaction.chain_id),
+                RlpItem::uint(transaction.nonce),
+                RlpItem::uint(transaction.max_priority_fee_per_gas),
+                RlpItem::uint(transaction.max_fee_per_gas),
+                RlpItem::uint(transaction.gas_limit),
+                encode_to(&transaction.to),
+                RlpItem::uint(transaction.value),
+                RlpItem::Bytes(transaction.data.clone()),
+                encode_access_list(&transaction.access_list),
+            ],
+        }
+    }
+}
+
+fn encode_to(to: &Option<Address>) -> RlpItem {
+    RlpItem::Bytes(to.map(Vec::from).unwrap_or_default())
+}
+
+fn encode_access_list(access_list: &[AccessListItem]) -> RlpItem {
+    RlpItem::List(
+        access_list
+            .iter()
+            .map(|item| {
+                RlpItem::List(vec![
+                    RlpItem::Bytes(item.address.to_vec()),
+                    RlpItem::List(
+                        item.storage_keys
+                            .iter()
+                            .map(|key| RlpItem::Bytes(key.to_vec()))
+                            .collect(),
+                    ),
+                ])
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,191 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use dwallet_ethereum_tx::{
+    AccessListItem, Eip1559Transaction, Eip2930Transaction, EthereumError, EthereumSignature,
+    TypedData, TypedTransaction, checksum_address, parse_address,
+};
+
+/// The recipient of the EIP-155 example transaction.
+const RECIPIENT: &str = "0x3535353535353535353535353535353535353535";
+
+/// The `Mail` example of EIP-712.
+const MAIL_TYPED_DATA: &str = r#"{
+    "types": {
+        "EIP712Domain": [
+            { "name": "name", "type": "string" },
+            { "name": "version", "type": "string" },
+            { "name": "chainId", "type": "uint256" },
+            { "name": "verifyingContract", "type": "address" }
+        ],
+        "Person": [
+            { "name": "name", "type": "string" },
+            { "name": "wallet", "type": "address" }
+        ],
+        "Mail": [
+            { "name": "from", "type": "Person" },
+            { "name": "to", "type": "Person" },
+            { "name": "contents", "type": "string" }
+        ]
+    },
+    "primaryType": "Mail",
+    "domain": {
+        "name": "Ether Mail",
+        "version": "1",
+        "chainId": 1,
+        "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+    },
+    "message": {
+        "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+        "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+        "contents": "Hello, Bob!"
+    }
+}"#;
+
+fn eip_1559_transaction() -> TypedTransaction {
+    TypedTransaction::Eip1559(Eip1559Transaction {
+        chain_id: 1,
+        nonce: 0,
+        max_priority_fee_per_gas: 2_000_000_000,
+        max_fee_per_gas: 100_000_000_000,
+        gas_limit: 21_000,
+        to: Some(parse_address(RECIPIENT).unwrap()),
+        value: 1_000_000_000_000_000_000,
+        data: vec![],
+        access_list: vec![],
+    })
+}
+
+fn bytes32(value: &str) -> [u8; 32] {
+    hex::decode(value).unwrap().try_into().unwrap()
+}
+
+#[test]
+fn eip_1559_transaction_signing_hash() {
+    let transaction = eip_1559_transaction();
+    assert_eq!(
+        hex::encode(transaction.signing_payload()),
+        "02f00180847735940085174876e800825208943535353535353535353535353535353535353535880de0b6b3a764000080c0"
+    );
+    assert_eq!(
+        hex::encode(transaction.signing_hash()),
+        "0b90ec27784b0b819f9413e83d1eab4c84f1eda3150059f1881f867fb010f1d1"
+    );
+}
+
+#[test]
+fn eip_1559_transaction_encodes_signature() {
+    // Signed by the private key 0x4646...46 of the EIP-155 example.
+    let signature = EthereumSignature {
+        y_parity: 1,
+        r: bytes32("17f53289eac961e5adc858d3ca50dab056ddca7a1a906c0815a0369312d1aa49"),
+        s: bytes32("4427d233d5904e8a41ec6fd24b9ba2c85795368d01b26622df6196d9c6a2e938"),
+    };
+    assert_eq!(
+        hex::encode(eip_1559_transaction().encode_signed(&signature)),
+        "02f8730180847735940085174876e800825208943535353535353535353535353535353535353535880de0b6b3a764000080c001a017f53289eac961e5adc858d3ca50dab056ddca7a1a906c0815a0369312d1aa49a04427d233d5904e8a41ec6fd24b9ba2c85795368d01b26622df6196d9c6a2e938"
+    );
+}
+
+#[test]
+fn eip_2930_transaction_encodes_access_list() {
+    let mut storage_key = [0; 32];
+    storage_key[31] = 1;
+    let transaction = TypedTransaction::Eip2930(Eip2930Transaction {
+        chain_id: 1,
+        nonce: 7,
+        gas_price: 30_000_000_000,
+        gas_limit: 50_000,
+        to: Some(parse_address(RECIPIENT).unwrap()),
+        value: 0,
+        data: vec![0xde, 0xad, 0xbe, 0xef],
+        access_list: vec![AccessListItem {
+            address: [0x11; 20],
+            storage_keys: vec![storage_key, [0x22; 32]],
+        }],
+    });
+    assert_eq!(
+        hex::encode(transaction.signing_payload()),
+        "01f88301078506fc23ac0082c3509435353535353535353535353535353535353535358084deadbeeff85bf859941111111111111111111111111111111111111111f842a00000000000000000000000000000000000000000000000000000000000000001a02222222222222222222222222222222222222222222222222222222222222222"
+    );
+}
+
+#[test]
+fn signature_encodings() {
+    let signature = EthereumSignature::from_compact(&[0x01; 64], 1).unwrap();
+    let bytes = signature.to_bytes();
+    assert_eq!(bytes[..64], [0x01; 64]);
+    assert_eq!(bytes[64], 28);
+
+    assert_eq!(
+        EthereumSignature::from_compact(&[0x01; 64], 2),
+        Err(EthereumError::InvalidRecoveryId { recovery_id: 2 })
+    );
+}
+
+#[test]
+fn addresses_are_checksummed() {
+    let address = "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826";
+    assert_eq!(
+        checksum_address(&parse_address(&address.to_lowercase()).unwrap()),
+        address
+    );
+    assert!(parse_address("0xcD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826").is_err());
+    assert!(parse_address("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD8").is_err());
+}
+
+#[test]
+fn eip_712_mail_signing_hash() {
+    let typed_data = TypedData::from_json(MAIL_TYPED_DATA).unwrap();
+    assert_eq!(
+        typed_data.encode_type("Mail").unwrap(),
+        "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+    );
+    assert_eq!(
+        hex::encode(typed_data.domain_separator().unwrap()),
+        "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+    );
+    assert_eq!(
+        hex::encode(typed_data.hash_struct("Mail", &typed_data.message).unwrap()),
+        "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+    );
+    assert_eq!(
+        hex::encode(typed_data.signing_hash().unwrap()),
+        "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+    );
+}
+
+#[test]
+fn eip_712_domain_type_is_inferred() {
+    let mut typed_data = TypedData::from_json(MAIL_TYPED_DATA).unwrap();
+    let domain_separator = typed_data.domain_separator().unwrap();
+    typed_data.types.remove("EIP712Domain");
+    assert_eq!(typed_data.domain_separator().unwrap(), domain_separator);
+}
+
+#[test]
+fn eip_712_integers_are_range_checked() {
+    let typed_data = |field_type: &str, value: &str| {
+        TypedData::from_json(&format!(
+            r#"{{
+                "types": {{ "Value": [{{ "name": "value", "type": "{field_type}" }}] }},
+                "primaryType": "Value",
+                "domain": {{ "name": "Test" }},
+                "message": {{ "value": {value} }}
+            }}"#
+        ))
+        .unwrap()
+        .signing_hash()
+    };
+
+    assert!(typed_data("uint8", "255").is_ok());
+    assert!(typed_data("uint8", "256").is_err());
+    assert!(typed_data("uint8", "\"-1\"").is_err());
+    assert!(typed_data("int8", "\"-128\"").is_ok());
+    assert!(typed_data("int8", "\"-129\"").is_err());
+    assert!(typed_data("int8", "128").is_err());
+    assert_eq!(
+        typed_data("uint256", "\"0xff\"").unwrap(),
+        typed_data("uint256", "255").unwrap()
+    );
+}
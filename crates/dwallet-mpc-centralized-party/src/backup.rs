@@ -0,0 +1,478 @@
+//! Password-protected backups of the user side of a dWallet.
+//!
+//! A backup bundles everything needed to sign with a dWallet again on a new device:
+//! the user secret share, sealed with AES-256-GCM under a key derived from a password with scrypt,
+//! and the DKG output, [`DWalletPublicKeys`], dWallet ID and network encryption key ID in the clear,
+//! authenticated as the associated data of the sealed share.
+//! A backup file is `magic || BCS(VersionedDWalletBackup) || SHA3-256 checksum`, so corruption is
+//! detected before asking for the password, and a restored user secret share is verified against
+//! the DKG output with [`verify_secret_share`] before it is returned.
+//!
+//! For paper and QR code storage, a backup is split into Base58Check text chunks,
+//! each of which carries the ID of its backup, its position and its own checksum.
+
+use crate::{DWalletPublicKeys, verify_secret_share};
+use anyhow::{Context, anyhow};
+use dwallet_mpc_types::dwallet_mpc::{SerializedWrappedMPCPublicOutput, VersionedDWalletBackup};
+use fastcrypto::aes::{Aes256Gcm, AesKey, AuthenticatedCipher};
+use fastcrypto::traits::ToFromBytes;
+use group::OsCsRng;
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+use std::collections::BTreeMap;
+
+const BACKUP_MAGIC: &[u8] = b"ikadwbk";
+const BACKUP_CHECKSUM_LENGTH: usize = 32;
+const BACKUP_SALT_LENGTH: usize = 32;
+const BACKUP_KEY_LENGTH: usize = 32;
+const BACKUP_NONCE_LENGTH: usize = 12;
+/// The least scrypt parameters a new backup may be created with.
+const MIN_BACKUP_KDF_LOG_N: u8 = 15;
+const MIN_BACKUP_KDF_R: u32 = 8;
+/// The most memory (`128 * r * 2^log_n` bytes) the KDF of a restored backup may use.
+const MAX_BACKUP_KDF_MEMORY: u128 = 1 << 30;
+const MAX_BACKUP_KDF_PARALLELISM: u32 = 16;
+
+type BackupNonce = <Aes256Gcm as AuthenticatedCipher>::IVType;
+
+/// The prefix of every text chunk of a backup.
+const BACKUP_CHUNK_PREFIX: &str = "ikadwbk";
+/// The number of leading checksum bytes identifying a backup in its chunks.
+const BACKUP_ID_LENGTH: usize = 4;
+
+/// The scrypt parameters deriving the key of a backup from its password.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupKdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for BackupKdfParams {
+    /// The recommended interactive parameters, using 32 MiB of memory.
+    fn default() -> Self {
+        Self {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// The public part of a backup, readable without its password.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DWalletBackupHeader {
+    pub dwallet_id: String,
+    pub network_encryption_key_id: String,
+    pub public_keys: DWalletPublicKeys,
+    pub dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    /// The Unix time (in seconds) the backup was created at.
+    pub created_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct DWalletBackupV1 {
+    header: DWalletBackupHeader,
+    kdf_params: BackupKdfParams,
+    salt: [u8; BACKUP_SALT_LENGTH],
+    nonce: [u8; BACKUP_NONCE_LENGTH],
+    /// The user secret share encrypted with AES-256-GCM, followed by its authentication tag.
+    sealed_user_secret_share: Vec<u8>,
+}
+
+/// Creates a password-protected backup of a dWallet, after verifying that
+/// `user_secret_share` matches `dwallet_dkg_output`.
+/// `kdf_params` must be at least as strong as [`BackupKdfParams::default`].
+/// Returns the serialized backup file.
+#[allow(clippy::too_many_arguments)]
+pub fn create_dwallet_backup_inner(
+    protocol_pp: Vec<u8>,
+    dwallet_id: String,
+    network_encryption_key_id: String,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    user_secret_share: Vec<u8>,
+    password: Vec<u8>,
+    created_at: u64,
+    kdf_params: BackupKdfParams,
+) -> anyhow::Result<Vec<u8>> {
+    if kdf_params.log_n < MIN_BACKUP_KDF_LOG_N
+        || kdf_params.r < MIN_BACKUP_KDF_R
+        || kdf_params.p == 0
+    {
+        return Err(anyhow!(
+            "the backup KDF parameters {kdf_params:?} are too weak to protect a backup"
+        ));
+    }
+    if !verify_secret_share(
+        user_secret_share.clone(),
+        dwallet_dkg_output.clone(),
+        protocol_pp,
+    )? {
+        return Err(anyhow!(
+            "the user secret share does not match the dWallet output"
+        ));
+    }
+    let header = DWalletBackupHeader {
+        dwallet_id,
+        network_encryption_key_id,
+        public_keys: DWalletPublicKeys::from_dkg_output(&dwallet_dkg_output)?,
+        dwallet_dkg_output,
+        created_at,
+    };
+    let mut salt = [0u8; BACKUP_SALT_LENGTH];
+    OsCsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; BACKUP_NONCE_LENGTH];
+    OsCsRng.fill_bytes(&mut nonce);
+    let backup = seal_backup(
+        header,
+        &user_secret_share,
+        &password,
+        kdf_params,
+        salt,
+        nonce,
+    )?;
+    serialize_backup(&backup)
+}
+
+/// Reads the public part of a backup file, verifying its checksum but not its password.
+pub fn inspect_dwallet_backup_inner(backup: Vec<u8>) -> anyhow::Result<DWalletBackupHeader> {
+    Ok(deserialize_backup(&backup)?.header)
+}
+
+/// Restores a dWallet from a backup file.
+///
+/// Opens the user secret share with `password`, and verifies it against the DKG output
+/// in the backup, and that output against the public keys in the backup.
+/// Returns a tuple of the public part of the backup and the serialized user secret share.
+/// # Warning
+/// The user secret share returned from this function should never be sent
+/// and should always be kept private.
+pub fn restore_dwallet_backup_inner(
+    backup: Vec<u8>,
+    password: Vec<u8>,
+    protocol_pp: Vec<u8>,
+) -> anyhow::Result<(DWalletBackupHeader, Vec<u8>)> {
+    let backup = deserialize_backup(&backup)?;
+    let user_secret_share = open_backup(&backup, &password)?;
+    if DWalletPublicKeys::from_dkg_output(&backup.header.dwallet_dkg_output)?
+        != backup.header.public_keys
+    {
+        return Err(anyhow!(
+            "the public keys of the backup do not match its dWallet output"
+        ));
+    }
+    if !verify_secret_share(
+        user_secret_share.clone(),
+        backup.header.dwallet_dkg_output.clone(),
+        protocol_pp,
+    )? {
+        return Err(anyhow!(
+            "the user secret share of the backup does not match its dWallet output"
+        ));
+    }
+    Ok((backup.header, user_secret_share))
+}
+
+/// Splits a backup file into text chunks of at most `chunk_size` bytes of the backup each,
+/// to print on paper or encode as QR codes.
+///
+/// Each chunk is `ikadwbk:<backup ID>:<index>/<count>:<Base58Check data>`,
+/// where the backup ID is the hex prefix of the backup checksum and `index` starts at 1.
+pub fn export_dwallet_backup_chunks(
+    backup: &[u8],
+    chunk_size: usize,
+) -> anyhow::Result<Vec<String>> {
+    if chunk_size == 0 {
+        return Err(anyhow!("the chunk size must be positive"));
+    }
+    let backup_id = backup_id(backup)?;
+    let count = backup.len().div_ceil(chunk_size);
+    Ok(backup
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(index, chunk)| {
+            format!(
+                "{BACKUP_CHUNK_PREFIX}:{backup_id}:{}/{count}:{}",
+                index + 1,
+                bs58::encode(chunk).with_check().into_string()
+            )
+        })
+        .collect())
+}
+
+/// Reassembles a backup file from its text chunks, given in any order.
+///
+/// Repeated chunks (e.g. a QR code scanned twice) are ignored,
+/// while chunks of another backup, corrupted chunks and missing chunks are rejected.
+pub fn import_dwallet_backup_chunks(chunks: &[String]) -> anyhow::Result<Vec<u8>> {
+    let mut backup_chunk_count = None;
+    let mut parts = BTreeMap::new();
+    for chunk in chunks.iter().map(|chunk| chunk.trim()) {
+        let fields: Vec<_> = chunk.split(':').collect();
+        let [BACKUP_CHUNK_PREFIX, backup_id, position, data] = fields[..] else {
+            return Err(anyhow!("`{chunk}` is not a dWallet backup chunk"));
+        };
+        let (index, count) = position
+            .split_once('/')
+            .and_then(|(index, count)| Some((index.parse().ok()?, count.parse().ok()?)))
+            .filter(|&(index, count): &(usize, usize)| 0 < index && index <= count)
+            .ok_or_else(|| anyhow!("invalid position `{position}` of a backup chunk"))?;
+        if *backup_chunk_count.get_or_insert((backup_id, count)) != (backup_id, count) {
+            return Err(anyhow!("the chunks are not all of the same backup"));
+        }
+        let data = bs58::decode(data)
+            .with_check(None)
+            .into_vec()
+            .with_context(|| format!("backup chunk {index} of {count} is corrupted"))?;
+        if parts
+            .insert(index, data.clone())
+            .is_some_and(|part| part != data)
+        {
+            return Err(anyhow!("backup chunk {index} of {count} was given twice"));
+        }
+    }
+
+    let Some((backup_id, count)) = backup_chunk_count else {
+        return Err(anyhow!("no backup chunks were given"));
+    };
+    if let Some(missing) = (1..=count).find(|index| !parts.contains_key(index)) {
+        return Err(anyhow!("backup chunk {missing} of {count} is missing"));
+    }
+    let backup: Vec<u8> = parts.into_values().flatten().collect();
+    if self::backup_id(&backup)? != backup_id {
+        return Err(anyhow!("the chunks do not reassemble their backup"));
+    }
+    Ok(backup)
+}
+
+/// Seals the user secret share with the public part of the backup as associated data,
+/// so neither can be swapped.
+fn seal_backup(
+    header: DWalletBackupHeader,
+    user_secret_share: &[u8],
+    password: &[u8],
+    kdf_params: BackupKdfParams,
+    salt: [u8; BACKUP_SALT_LENGTH],
+    nonce: [u8; BACKUP_NONCE_LENGTH],
+) -> anyhow::Result<DWalletBackupV1> {
+    let cipher = backup_cipher(password, &kdf_params, &salt)?;
+    let sealed_user_secret_share = cipher.encrypt_authenticated(
+        &BackupNonce::from_bytes(&nonce).map_err(|e| anyhow!(e))?,
+        &bcs::to_bytes(&header)?,
+        user_secret_share,
+    );
+    Ok(DWalletBackupV1 {
+        header,
+        kdf_params,
+        salt,
+        nonce,
+        sealed_user_secret_share,
+    })
+}
+
+fn open_backup(backup: &DWalletBackupV1, password: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let cipher = backup_cipher(password, &backup.kdf_params, &backup.salt)?;
+    cipher
+        .decrypt_authenticated(
+            &BackupNonce::from_bytes(&backup.nonce).map_err(|e| anyhow!(e))?,
+            &bcs::to_bytes(&backup.header)?,
+            &backup.sealed_user_secret_share,
+        )
+        .map_err(|_| anyhow!("wrong password, or the backup was tampered with"))
+}
+
+fn backup_cipher(
+    password: &[u8],
+    kdf_params: &BackupKdfParams,
+    salt: &[u8],
+) -> anyhow::Result<Aes256Gcm> {
+    let key = backup_key(password, kdf_params, salt)?;
+    Ok(Aes256Gcm::new(
+        AesKey::from_bytes(&key).map_err(|e| anyhow!(e))?,
+    ))
+}
+
+fn backup_key(
+    password: &[u8],
+    kdf_params: &BackupKdfParams,
+    salt: &[u8],
+) -> anyhow::Result<[u8; BACKUP_KEY_LENGTH]> {
+    let memory = (128 * u128::from(kdf_params.r)) << kdf_params.log_n.min(64);
+    if memory > MAX_BACKUP_KDF_MEMORY || kdf_params.p > MAX_BACKUP_KDF_PARALLELISM {
+        return Err(anyhow!(
+            "the backup KDF parameters {kdf_params:?} exceed the supported limits"
+        ));
+    }
+    let params = scrypt::Params::new(kdf_params.log_n, kdf_params.r, kdf_params.p)
+        .map_err(|e| anyhow!("invalid backup KDF parameters {kdf_params:?}: {e}"))?;
+    let mut key = [0u8; BACKUP_KEY_LENGTH];
+    scrypt::scrypt(password, salt, &params, &mut key).map_err(|e| anyhow!(e))?;
+    Ok(key)
+}
+
+fn backup_checksum(data: &[u8]) -> Vec<u8> {
+    sha3::Sha3_256::digest(data).to_vec()
+}
+
+fn serialize_backup(backup: &DWalletBackupV1) -> anyhow::Result<Vec<u8>> {
+    let mut serialized_backup = BACKUP_MAGIC.to_vec();
+    serialized_backup.extend(bcs::to_bytes(&VersionedDWalletBackup::V1(bcs::to_bytes(
+        backup,
+    )?))?);
+    let checksum = backup_checksum(&serialized_backup);
+    serialized_backup.extend(checksum);
+    Ok(serialized_backup)
+}
+
+fn deserialize_backup(serialized_backup: &[u8]) -> anyhow::Result<DWalletBackupV1> {
+    let (data, checksum) = split_backup_checksum(serialized_backup)?;
+    if backup_checksum(data) != checksum {
+        return Err(anyhow!(
+            "the backup is corrupted: its checksum does not match"
+        ));
+    }
+    let VersionedDWalletBackup::V1(backup) = bcs::from_bytes(&data[BACKUP_MAGIC.len()..])?;
+    Ok(bcs::from_bytes(&backup)?)
+}
+
+fn split_backup_checksum(serialized_backup: &[u8]) -> anyhow::Result<(&[u8], &[u8])> {
+    if !serialized_backup.starts_with(BACKUP_MAGIC)
+        || serialized_backup.len() < BACKUP_MAGIC.len() + BACKUP_CHECKSUM_LENGTH
+    {
+        return Err(anyhow!("not a dWallet backup"));
+    }
+    Ok(serialized_backup.split_at(serialized_backup.len() - BACKUP_CHECKSUM_LENGTH))
+}
+
+fn backup_id(serialized_backup: &[u8]) -> anyhow::Result<String> {
+    let (_, checksum) = split_backup_checksum(serialized_backup)?;
+    Ok(hex_encode(&checksum[..BACKUP_ID_LENGTH]))
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap KDF parameters, so the tests run fast.
+    const TEST_KDF_PARAMS: BackupKdfParams = BackupKdfParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    fn header() -> DWalletBackupHeader {
+        DWalletBackupHeader {
+            dwallet_id: "0x5a1f".to_string(),
+            network_encryption_key_id: "0x7e57".to_string(),
+            public_keys: DWalletPublicKeys {
+                centralized_public_share: vec![1; 33],
+                decentralized_public_share: vec![2; 33],
+                public_key: vec![3; 33],
+            },
+            dwallet_dkg_output: vec![4; 100],
+            created_at: 1_800_000_000,
+        }
+    }
+
+    fn backup() -> Vec<u8> {
+        let backup = seal_backup(
+            header(),
+            b"user secret share",
+            b"password",
+            TEST_KDF_PARAMS,
+            [9; 32],
+            [7; 12],
+        )
+        .unwrap();
+        serialize_backup(&backup).unwrap()
+    }
+
+    #[test]
+    fn backups_open_with_their_password_only() {
+        let backup = deserialize_backup(&backup()).unwrap();
+        assert_eq!(backup.header, header());
+        assert_eq!(
+            open_backup(&backup, b"password").unwrap(),
+            b"user secret share"
+        );
+        assert!(open_backup(&backup, b"passw0rd").is_err());
+
+        // The public part is authenticated with the user secret share.
+        let mut tampered_backup = backup.clone();
+        tampered_backup.header.dwallet_id = "0xbeef".to_string();
+        assert!(open_backup(&tampered_backup, b"password").is_err());
+
+        let mut tampered_backup = backup.clone();
+        tampered_backup.sealed_user_secret_share[0] ^= 1;
+        assert!(open_backup(&tampered_backup, b"password").is_err());
+    }
+
+    #[test]
+    fn corrupted_backups_are_rejected() {
+        let mut backup = backup();
+        assert_eq!(
+            inspect_dwallet_backup_inner(backup.clone()).unwrap(),
+            header()
+        );
+
+        let middle = backup.len() / 2;
+        backup[middle] ^= 1;
+        assert!(inspect_dwallet_backup_inner(backup).is_err());
+        assert!(inspect_dwallet_backup_inner(b"ikadwbk".to_vec()).is_err());
+    }
+
+    #[test]
+    fn excessive_kdf_parameters_are_rejected() {
+        let kdf_params = BackupKdfParams {
+            log_n: 30,
+            ..TEST_KDF_PARAMS
+        };
+        assert!(backup_key(b"password", &kdf_params, &[0; 32]).is_err());
+    }
+
+    #[test]
+    fn weak_kdf_parameters_are_rejected_for_new_backups() {
+        let result = create_dwallet_backup_inner(
+            vec![],
+            "0x5a1f".to_string(),
+            "0x7e57".to_string(),
+            vec![4; 100],
+            b"user secret share".to_vec(),
+            b"password".to_vec(),
+            1_800_000_000,
+            TEST_KDF_PARAMS,
+        );
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("are too weak to protect a backup")
+        );
+    }
+
+    #[test]
+    fn backups_round_trip_through_chunks() {
+        let backup = backup();
+        let mut chunks = export_dwallet_backup_chunks(&backup, 64).unwrap();
+        assert_eq!(chunks.len(), backup.len().div_ceil(64));
+        assert!(chunks[0].starts_with(&format!("ikadwbk:{}:1/", backup_id(&backup).unwrap())));
+
+        // Chunks may be given in any order, and repeated.
+        chunks.reverse();
+        chunks.push(chunks[0].clone());
+        assert_eq!(import_dwallet_backup_chunks(&chunks).unwrap(), backup);
+
+        chunks.pop();
+        chunks.pop();
+        assert!(import_dwallet_backup_chunks(&chunks).is_err());
+
+        let mut corrupted_chunks = export_dwallet_backup_chunks(&backup, 64).unwrap();
+        corrupted_chunks[0] = corrupted_chunks[0].replacen(':', ":0", 1);
+        assert!(import_dwallet_backup_chunks(&corrupted_chunks).is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! Splitting the user secret share of a dWallet across several devices of the user.
+//!
+//! The user secret share is Shamir-shared `threshold`-of-`number_of_devices` over the secp256k1
+//! scalar field, with Feldman commitments to the coefficients of the sharing polynomial.
+//! The first commitment is the user public key share in the DKG output of the dWallet,
+//! so every device can verify its own share against the dWallet, and losing up to
+//! `number_of_devices - threshold` devices doesn't lose the dWallet.
+//!
+//! The sign round of the centralized party takes the whole user secret share, so signing and
+//! re-encrypting gather `threshold` device shares on one device, which recombines the user secret
+//! share in memory for the duration of the operation only.
+
+use crate::encodings::to_k256_scalar;
+use crate::{
+    DKGDecentralizedOutput, advance_centralized_sign_party, encrypt_secret_key_share_and_prove,
+};
+use anyhow::anyhow;
+use dwallet_mpc_types::dwallet_mpc::{
+    SerializedWrappedMPCPublicOutput, VersionedDwalletDKGSecondRoundPublicOutput,
+    VersionedDwalletUserSecretShare, VersionedUserDeviceShare,
+};
+use dwallet_mpc_types::key_derivation::{from_affine_point, to_affine_point};
+use group::{OsCsRng, Samplable, secp256k1};
+use k256::elliptic_curve::group::Curve;
+use k256::{ProjectivePoint, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters;
+
+#[derive(Clone, Serialize, Deserialize)]
+struct UserDeviceShare {
+    /// The number of device shares needed to recombine the user secret share.
+    threshold: u32,
+    /// The non-zero point the sharing polynomial is evaluated at for this device.
+    index: u32,
+    share: secp256k1::Scalar,
+    /// The commitments `a_j * G` to the coefficients of the sharing polynomial,
+    /// starting from the user public key share.
+    coefficient_commitments: Vec<secp256k1::group_element::Value>,
+}
+
+/// Splits the user secret share of a dWallet into `number_of_devices` device shares,
+/// any `threshold` of which recombine it.
+/// Returns the serialized [`VersionedUserDeviceShare`]s, in the order of their device indices.
+/// # Warning
+/// Each device share must only be sent to its device, encrypted,
+/// and the user secret share must be deleted once every device has verified its share.
+pub fn split_user_secret_share_inner(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    user_secret_share: Vec<u8>,
+    threshold: u32,
+    number_of_devices: u32,
+) -> anyhow::Result<Vec<Vec<u8>>> {
+    let protocol_public_params: ProtocolPublicParameters = bcs::from_bytes(&protocol_pp)?;
+    let VersionedDwalletUserSecretShare::V1(user_secret_share) =
+        bcs::from_bytes(&user_secret_share)?;
+    let user_secret_share = to_k256_scalar(bcs::from_bytes(&user_secret_share)?)?;
+    if ProjectivePoint::GENERATOR * user_secret_share != user_public_key_share(&dwallet_dkg_output)?
+    {
+        return Err(anyhow!(
+            "the user secret share does not match the dWallet output"
+        ));
+    }
+    split_secret(
+        &protocol_public_params,
+        user_secret_share,
+        threshold,
+        number_of_devices,
+    )
+}
+
+/// Verifies that a device share is a share of the user secret share of the dWallet.
+pub fn verify_user_device_share_inner(
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    device_share: Vec<u8>,
+) -> anyhow::Result<bool> {
+    let device_share = deserialize_device_share(&device_share)?;
+    is_valid_device_share(&device_share, &user_public_key_share(&dwallet_dkg_output)?)
+}
+
+/// Recombines the user secret share of a dWallet from at least `threshold` device shares,
+/// returning the serialized [`VersionedDwalletUserSecretShare`].
+/// # Warning
+/// The user secret share returned from this function should never be sent
+/// and should always be kept private.
+pub fn combine_user_device_shares_inner(
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    device_shares: Vec<Vec<u8>>,
+) -> anyhow::Result<Vec<u8>> {
+    let user_secret_share = combine_device_shares(&dwallet_dkg_output, &device_shares)?;
+    Ok(bcs::to_bytes(&VersionedDwalletUserSecretShare::V1(
+        bcs::to_bytes(&secp256k1::Scalar::from(U256::from(user_secret_share)))?,
+    ))?)
+}
+
+/// Like [`advance_centralized_sign_party`],
+/// but signs with the user secret share recombined from at least `threshold` device shares.
+pub fn advance_centralized_sign_party_with_device_shares(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    device_shares: Vec<Vec<u8>>,
+    presign: SerializedWrappedMPCPublicOutput,
+    message: Vec<u8>,
+    hash_type: u32,
+    derivation_path: Vec<u32>,
+) -> anyhow::Result<Vec<u8>> {
+    let user_secret_share =
+        combine_user_device_shares_inner(dwallet_dkg_output.clone(), device_shares)?;
+    advance_centralized_sign_party(
+        protocol_pp,
+        dwallet_dkg_output,
+        user_secret_share,
+        presign,
+        message,
+        hash_type,
+        derivation_path,
+    )
+}
+
+/// Encrypts the user secret share recombined from at least `threshold` device shares
+/// to `encryption_key`, with a proof of encryption, as [`encrypt_secret_key_share_and_prove`].
+pub fn encrypt_device_shares_and_prove(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    device_shares: Vec<Vec<u8>>,
+    encryption_key: Vec<u8>,
+) -> anyhow::Result<Vec<u8>> {
+    let user_secret_share = combine_user_device_shares_inner(dwallet_dkg_output, device_shares)?;
+    encrypt_secret_key_share_and_prove(user_secret_share, encryption_key, protocol_pp)
+}
+
+/// Splits the user secret share anew from at least `threshold` device shares,
+/// e.g. to replace a lost device or to change the threshold.
+/// The new device shares are independent of the old ones, which should be deleted.
+pub fn reshare_user_device_shares_inner(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    device_shares: Vec<Vec<u8>>,
+    threshold: u32,
+    number_of_devices: u32,
+) -> anyhow::Result<Vec<Vec<u8>>> {
+    let protocol_public_params: ProtocolPublicParameters = bcs::from_bytes(&protocol_pp)?;
+    let user_secret_share = combine_device_shares(&dwallet_dkg_output, &device_shares)?;
+    split_secret(
+        &protocol_public_params,
+        user_secret_share,
+        threshold,
+        number_of_devices,
+    )
+}
+
+fn split_secret(
+    protocol_public_params: &ProtocolPublicParameters,
+    secret: k256::Scalar,
+    threshold: u32,
+    number_of_devices: u32,
+) -> anyhow::Result<Vec<Vec<u8>>> {
+    if threshold == 0 || threshold > number_of_devices {
+        return Err(anyhow!(
+            "the threshold must be between 1 and the number of devices ({number_of_devices})"
+        ));
+    }
+    let mut coefficients = vec![secret];
+    for _ in 1..threshold {
+        coefficients.push(to_k256_scalar(secp256k1::Scalar::sample(
+            &protocol_public_params
+                .as_ref()
+                .scalar_group_public_parameters,
+            &mut OsCsRng,
+        )?)?);
+    }
+    share_polynomial(&coefficients, number_of_devices)?
+        .iter()
+        .map(|device_share| {
+            Ok(bcs::to_bytes(&VersionedUserDeviceShare::V1(
+                bcs::to_bytes(device_share)?,
+            ))?)
+        })
+        .collect()
+}
+
+/// Evaluates the polynomial with `coefficients` at `1..=number_of_devices`.
+fn share_polynomial(
+    coefficients: &[k256::Scalar],
+    number_of_devices: u32,
+) -> anyhow::Result<Vec<UserDeviceShare>> {
+    let coefficient_commitments = coefficients
+        .iter()
+        .map(|coefficient| {
+            from_affine_point(&(ProjectivePoint::GENERATOR * coefficient).to_affine())
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok((1..=number_of_devices)
+        .map(|index| {
+            let x = k256::Scalar::from(u64::from(index));
+            let share = coefficients
+                .iter()
+                .rev()
+                .fold(k256::Scalar::ZERO, |value, coefficient| {
+                    value * x + coefficient
+                });
+            UserDeviceShare {
+                threshold: coefficients.len() as u32,
+                index,
+                share: U256::from(share).into(),
+                coefficient_commitments: coefficient_commitments.clone(),
+            }
+        })
+        .collect())
+}
+
+/// Checks a device share against the commitments to the sharing polynomial (Feldman's check),
+/// and the commitment to the secret against the user public key share.
+fn is_valid_device_share(
+    device_share: &UserDeviceShare,
+    user_public_key_share: &ProjectivePoint,
+) -> anyhow::Result<bool> {
+    let coefficient_commitments = device_share
+        .coefficient_commitments
+        .iter()
+        .map(|commitment| Ok(ProjectivePoint::from(to_affine_point(commitment)?)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if device_share.index == 0
+        || coefficient_commitments.len() != device_share.threshold as usize
+        || coefficient_commitments.first() != Some(user_public_key_share)
+    {
+        return Ok(false);
+    }
+
+    let x = k256::Scalar::from(u64::from(device_share.index));
+    let expected_public_share = coefficient_commitments
+        .iter()
+        .rev()
+        .fold(ProjectivePoint::IDENTITY, |value, commitment| {
+            value * x + commitment
+        });
+    Ok(ProjectivePoint::GENERATOR * to_k256_scalar(device_share.share)? == expected_public_share)
+}
+
+fn combine_device_shares(
+    dwallet_dkg_output: &[u8],
+    device_shares: &[Vec<u8>],
+) -> anyhow::Result<k256::Scalar> {
+    let user_public_key_share = user_public_key_share(dwallet_dkg_output)?;
+    let device_shares = device_shares
+        .iter()
+        .map(|device_share| deserialize_device_share(device_share.as_slice()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let Some(threshold) = device_shares
+        .first()
+        .map(|device_share| device_share.threshold)
+    else {
+        return Err(anyhow!("no device shares were given"));
+    };
+
+    let mut indices = HashSet::new();
+    for device_share in &device_shares {
+        if !is_valid_device_share(device_share, &user_public_key_share)? {
+            return Err(anyhow!(
+                "the share of device {} does not match the dWallet output",
+                device_share.index
+            ));
+        }
+        if device_share.threshold != threshold
+            || bcs::to_bytes(&device_share.coefficient_commitments)?
+                != bcs::to_bytes(&device_shares[0].coefficient_commitments)?
+        {
+            return Err(anyhow!(
+                "the share of device {} belongs to another sharing",
+                device_share.index
+            ));
+        }
+        if !indices.insert(device_share.index) {
+            return Err(anyhow!(
+                "the share of device {} was given twice",
+                device_share.index
+            ));
+        }
+    }
+    if device_shares.len() < threshold as usize {
+        return Err(anyhow!(
+            "{threshold} device shares are needed, but only {} were given",
+            device_shares.len()
+        ));
+    }
+
+    interpolate_at_zero(&device_shares[..threshold as usize])
+}
+
+/// Recombines the secret from device shares with distinct indices,
+/// by Lagrange interpolation of the sharing polynomial at zero.
+fn interpolate_at_zero(device_shares: &[UserDeviceShare]) -> anyhow::Result<k256::Scalar> {
+    let mut secret = k256::Scalar::ZERO;
+    for device_share in device_shares {
+        let x = k256::Scalar::from(u64::from(device_share.index));
+        let mut lagrange_coefficient = k256::Scalar::ONE;
+        for other_device_share in device_shares {
+            if other_device_share.index != device_share.index {
+                let other_x = k256::Scalar::from(u64::from(other_device_share.index));
+                let denominator = Option::<k256::Scalar>::from((other_x - x).invert())
+                    .ok_or_else(|| anyhow!("device indices must be distinct"))?;
+                lagrange_coefficient *= other_x * denominator;
+            }
+        }
+        secret += lagrange_coefficient * to_k256_scalar(device_share.share)?;
+    }
+    Ok(secret)
+}
+
+fn deserialize_device_share(device_share: &[u8]) -> anyhow::Result<UserDeviceShare> {
+    let VersionedUserDeviceShare::V1(device_share) = bcs::from_bytes(device_share)?;
+    Ok(bcs::from_bytes(&device_share)?)
+}
+
+fn user_public_key_share(dwallet_dkg_output: &[u8]) -> anyhow::Result<ProjectivePoint> {
+    let VersionedDwalletDKGSecondRoundPublicOutput::V1(dwallet_dkg_output) =
+        bcs::from_bytes(dwallet_dkg_output)?;
+    let dwallet_dkg_output: DKGDecentralizedOutput = bcs::from_bytes(&dwallet_dkg_output)?;
+    Ok(ProjectivePoint::from(to_affine_point(
+        &dwallet_dkg_output.centralized_party_public_key_share,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coefficients() -> Vec<k256::Scalar> {
+        [42u64, 7, 1 << 40]
+            .into_iter()
+            .map(k256::Scalar::from)
+            .collect()
+    }
+
+    fn public_key_share() -> ProjectivePoint {
+        ProjectivePoint::GENERATOR * k256::Scalar::from(42u64)
+    }
+
+    #[test]
+    fn any_threshold_shares_recombine_the_secret() {
+        let device_shares = share_polynomial(&coefficients(), 5).unwrap();
+        for device_share in &device_shares {
+            assert!(is_valid_device_share(device_share, &public_key_share()).unwrap());
+        }
+
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset: Vec<_> = subset.iter().map(|i| device_shares[*i].clone()).collect();
+            assert_eq!(
+                interpolate_at_zero(&subset).unwrap(),
+                k256::Scalar::from(42u64)
+            );
+        }
+        assert_ne!(
+            interpolate_at_zero(&device_shares[..2]).unwrap(),
+            k256::Scalar::from(42u64)
+        );
+    }
+
+    #[test]
+    fn tampered_device_shares_are_rejected() {
+        let device_shares = share_polynomial(&coefficients(), 3).unwrap();
+
+        let mut tampered_share = device_shares[1].clone();
+        tampered_share.share = U256::from(k256::Scalar::from(1u64)).into();
+        assert!(!is_valid_device_share(&tampered_share, &public_key_share()).unwrap());
+
+        let other_public_key_share = ProjectivePoint::GENERATOR * k256::Scalar::from(43u64);
+        assert!(!is_valid_device_share(&device_shares[1], &other_public_key_share).unwrap());
+
+        let mut truncated_share = device_shares[1].clone();
+        truncated_share.coefficient_commitments.pop();
+        assert!(!is_valid_device_share(&truncated_share, &public_key_share()).unwrap());
+    }
+}
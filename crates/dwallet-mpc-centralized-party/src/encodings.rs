@@ -0,0 +1,295 @@
+//! Conversions of dWallet signatures and public keys to the standard encodings of other chains.
+//!
+//! The network outputs a signature as a [`VersionedSignOutput`] holding a BCS `(r, s)` pair
+//! of scalars, and a public key is a BCS-serialized secp256k1 group element value.
+//! This module converts them to DER and compact (64-byte) signatures, normalized to low-s,
+//! recoverable signatures with their recovery ID (or Ethereum `v`),
+//! SEC1 public keys, and Ethereum and Bitcoin addresses.
+
+use anyhow::anyhow;
+use bech32::{ToBase32, Variant, u5};
+use dwallet_mpc_types::dwallet_mpc::VersionedSignOutput;
+use dwallet_mpc_types::key_derivation::to_affine_point;
+use group::secp256k1;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use k256::elliptic_curve::bigint::ArrayEncoding;
+use k256::elliptic_curve::ff::PrimeField;
+use k256::elliptic_curve::group::Curve;
+use k256::elliptic_curve::scalar::IsHigh;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{AffinePoint, FieldBytes, ProjectivePoint, U256};
+use message_digest::message_digest::message_digest;
+use sha3::Digest;
+
+const TAPROOT_TWEAK_TAG: &[u8] = b"TapTweak";
+
+/// The Bitcoin network an address is encoded for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitcoinNetwork {
+    Mainnet = 0,
+    Testnet = 1,
+    Regtest = 2,
+}
+
+impl TryFrom<u32> for BitcoinNetwork {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(BitcoinNetwork::Mainnet),
+            1 => Ok(BitcoinNetwork::Testnet),
+            2 => Ok(BitcoinNetwork::Regtest),
+            _ => Err(anyhow!("invalid value for BitcoinNetwork enum: {value}")),
+        }
+    }
+}
+
+impl BitcoinNetwork {
+    fn p2pkh_version(&self) -> u8 {
+        match self {
+            BitcoinNetwork::Mainnet => 0x00,
+            BitcoinNetwork::Testnet | BitcoinNetwork::Regtest => 0x6f,
+        }
+    }
+
+    fn segwit_hrp(&self) -> &'static str {
+        match self {
+            BitcoinNetwork::Mainnet => "bc",
+            BitcoinNetwork::Testnet => "tb",
+            BitcoinNetwork::Regtest => "bcrt",
+        }
+    }
+}
+
+/// Returns the 64-byte compact `r || s` encoding of a signature, normalized to low-s.
+pub fn signature_to_compact(signature: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    Ok(low_s_signature(signature)?.to_bytes().to_vec())
+}
+
+/// Returns the ASN.1 DER encoding of a signature, normalized to low-s.
+pub fn signature_to_der(signature: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    Ok(low_s_signature(signature)?.to_der().as_bytes().to_vec())
+}
+
+/// Returns the 64-byte compact encoding of a signature normalized to low-s,
+/// and the recovery ID that recovers `public_key` from it and the signed message.
+pub fn signature_to_recoverable(
+    signature: Vec<u8>,
+    public_key: Vec<u8>,
+    message: Vec<u8>,
+    hash_type: u32,
+) -> anyhow::Result<(Vec<u8>, u8)> {
+    let signature = low_s_signature(signature)?;
+    let verifying_key = VerifyingKey::from_affine(public_key_to_affine_point(&public_key)?)?;
+    let prehash = to_k256_scalar(message_digest(&message, &hash_type.try_into()?)?)?.to_bytes();
+
+    let recovery_id = (0..=RecoveryId::MAX)
+        .filter_map(RecoveryId::from_byte)
+        .find(|recovery_id| {
+            VerifyingKey::recover_from_prehash(&prehash, &signature, *recovery_id)
+                .is_ok_and(|recovered_key| recovered_key == verifying_key)
+        })
+        .ok_or_else(|| anyhow!("the signature does not recover the public key"))?;
+
+    Ok((signature.to_bytes().to_vec(), recovery_id.to_byte()))
+}
+
+/// Returns the 65-byte Ethereum `r || s || v` encoding of a signature, normalized to low-s.
+///
+/// `v` is `27 + recovery_id`, or `35 + 2 * chain_id + recovery_id` (EIP-155) if `chain_id` is given.
+/// Since an EIP-155 `v` may not fit in a byte, it is also returned separately.
+pub fn signature_to_ethereum(
+    signature: Vec<u8>,
+    public_key: Vec<u8>,
+    message: Vec<u8>,
+    hash_type: u32,
+    chain_id: Option<u64>,
+) -> anyhow::Result<(Vec<u8>, u64)> {
+    let (mut signature, recovery_id) =
+        signature_to_recoverable(signature, public_key, message, hash_type)?;
+    let v = match chain_id {
+        Some(chain_id) => chain_id
+            .checked_mul(2)
+            .and_then(|v| v.checked_add(35 + u64::from(recovery_id)))
+            .ok_or_else(|| anyhow!("chain ID {chain_id} is too large"))?,
+        None => 27 + u64::from(recovery_id),
+    };
+    signature.push(v as u8);
+    Ok((signature, v))
+}
+
+/// Returns the SEC1 encoding of a public key, compressed (33 bytes) or uncompressed (65 bytes).
+pub fn public_key_to_sec1(public_key: Vec<u8>, compressed: bool) -> anyhow::Result<Vec<u8>> {
+    Ok(public_key_to_affine_point(&public_key)?
+        .to_encoded_point(compressed)
+        .as_bytes()
+        .to_vec())
+}
+
+/// Returns the EIP-55 checksummed Ethereum address of a public key.
+pub fn ethereum_address(public_key: Vec<u8>) -> anyhow::Result<String> {
+    let public_key = public_key_to_sec1(public_key, false)?;
+    let digest = sha3::Keccak256::digest(&public_key[1..]);
+    let address: String = digest[12..]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    let checksum = sha3::Keccak256::digest(address.as_bytes());
+    let checksummed_address: String = address
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let checksum_nibble = (checksum[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if checksum_nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    Ok(format!("0x{checksummed_address}"))
+}
+
+/// Returns the Bitcoin pay-to-public-key-hash (legacy) address of a public key.
+pub fn bitcoin_p2pkh_address(public_key: Vec<u8>, network: u32) -> anyhow::Result<String> {
+    let network = BitcoinNetwork::try_from(network)?;
+    let mut payload = vec![network.p2pkh_version()];
+    payload.extend(hash160(&public_key_to_sec1(public_key, true)?));
+    Ok(bs58::encode(payload).with_check().into_string())
+}
+
+/// Returns the Bitcoin pay-to-witness-public-key-hash (native SegWit v0) address of a public key.
+pub fn bitcoin_p2wpkh_address(public_key: Vec<u8>, network: u32) -> anyhow::Result<String> {
+    let network = BitcoinNetwork::try_from(network)?;
+    segwit_address(network, 0, &hash160(&public_key_to_sec1(public_key, true)?))
+}
+
+/// Returns the Bitcoin pay-to-taproot (SegWit v1) address of a public key,
+/// used as the internal key with no script tree (BIP-86).
+pub fn bitcoin_p2tr_address(public_key: Vec<u8>, network: u32) -> anyhow::Result<String> {
+    let network = BitcoinNetwork::try_from(network)?;
+    let public_key = public_key_to_affine_point(&public_key)?;
+    let (internal_key, _) = taproot_x_only_key(&public_key);
+    let tweak = tagged_hash(TAPROOT_TWEAK_TAG, &internal_key);
+    let tweak = Option::<k256::Scalar>::from(k256::Scalar::from_repr(FieldBytes::from(tweak)))
+        .ok_or_else(|| anyhow!("the taproot tweak of the public key is invalid"))?;
+
+    let even_public_key = even_y_point(&public_key);
+    let output_key =
+        (ProjectivePoint::from(even_public_key) + ProjectivePoint::GENERATOR * tweak).to_affine();
+    let (output_key, _) = taproot_x_only_key(&output_key);
+    segwit_address(network, 1, &output_key)
+}
+
+fn low_s_signature(signature: Vec<u8>) -> anyhow::Result<Signature> {
+    let VersionedSignOutput::V1(signature) = bcs::from_bytes(&signature)?;
+    let (r, s): (secp256k1::Scalar, secp256k1::Scalar) = bcs::from_bytes(&signature)?;
+    let r = to_k256_scalar(r)?;
+    let mut s = to_k256_scalar(s)?;
+    if bool::from(s.is_high()) {
+        s = -s;
+    }
+    Ok(Signature::from_scalars(r.to_bytes(), s.to_bytes())?)
+}
+
+pub(crate) fn to_k256_scalar(scalar: secp256k1::Scalar) -> anyhow::Result<k256::Scalar> {
+    Option::<k256::Scalar>::from(k256::Scalar::from_repr(
+        U256::from(scalar).to_be_byte_array(),
+    ))
+    .ok_or_else(|| anyhow!("scalar is not reduced modulo the group order"))
+}
+
+fn public_key_to_affine_point(public_key: &[u8]) -> anyhow::Result<AffinePoint> {
+    to_affine_point(&bcs::from_bytes(public_key)?)
+}
+
+fn hash160(data: &[u8]) -> Vec<u8> {
+    <ripemd::Ripemd160 as ripemd::Digest>::digest(<sha2::Sha256 as sha2::Digest>::digest(data))
+        .to_vec()
+}
+
+fn tagged_hash(tag: &[u8], data: &[u8]) -> [u8; 32] {
+    let tag_hash = <sha2::Sha256 as sha2::Digest>::digest(tag);
+    <sha2::Sha256 as sha2::Digest>::new_with_prefix(tag_hash)
+        .chain_update(tag_hash)
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+/// Returns the point with the same x coordinate as `point` and an even y coordinate.
+fn even_y_point(point: &AffinePoint) -> AffinePoint {
+    let (_, has_odd_y) = taproot_x_only_key(point);
+    if has_odd_y {
+        (-ProjectivePoint::from(*point)).to_affine()
+    } else {
+        *point
+    }
+}
+
+/// Returns the 32-byte x-only encoding of a point (BIP-340), and whether its y coordinate is odd.
+fn taproot_x_only_key(point: &AffinePoint) -> (Vec<u8>, bool) {
+    let encoded_point = point.to_encoded_point(true);
+    let encoded_point = encoded_point.as_bytes();
+    (encoded_point[1..].to_vec(), encoded_point[0] == 0x03)
+}
+
+fn segwit_address(
+    network: BitcoinNetwork,
+    witness_version: u8,
+    witness_program: &[u8],
+) -> anyhow::Result<String> {
+    let variant = if witness_version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    };
+    let mut data = vec![u5::try_from_u8(witness_version)?];
+    data.extend(witness_program.to_base32());
+    Ok(bech32::encode(network.segwit_hrp(), data, variant)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dwallet_mpc_types::key_derivation::from_affine_point;
+
+    /// The public key of the secret key `1`, i.e., the generator.
+    fn generator_public_key() -> Vec<u8> {
+        bcs::to_bytes(&from_affine_point(&AffinePoint::GENERATOR).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn generator_addresses_match_known_vectors() {
+        assert_eq!(
+            ethereum_address(generator_public_key()).unwrap(),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+        assert_eq!(
+            bitcoin_p2pkh_address(generator_public_key(), BitcoinNetwork::Mainnet as u32).unwrap(),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        assert_eq!(
+            bitcoin_p2wpkh_address(generator_public_key(), BitcoinNetwork::Mainnet as u32).unwrap(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+    }
+
+    #[test]
+    fn sec1_encodings_have_standard_lengths() {
+        let compressed = public_key_to_sec1(generator_public_key(), true).unwrap();
+        let uncompressed = public_key_to_sec1(generator_public_key(), false).unwrap();
+
+        assert_eq!(compressed.len(), 33);
+        assert_eq!(compressed[0], 0x02);
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(uncompressed[0], 0x04);
+        assert_eq!(compressed[1..], uncompressed[1..33]);
+    }
+
+    #[test]
+    fn invalid_bitcoin_network_is_rejected() {
+        assert!(bitcoin_p2tr_address(generator_public_key(), 3).is_err());
+    }
+}
@@ -3,6 +3,13 @@
 // Allowed to improve code readability.
 #![allow(unused_qualifications)]
 
+pub mod backup;
+pub mod device_shares;
+pub mod encodings;
+pub mod mnemonic;
+pub mod output_verification;
+pub mod sign_session;
+
 use anyhow::{Context, anyhow};
 use class_groups::dkg::Secp256k1Party;
 use class_groups::setup::get_setup_parameters_secp256k1;
@@ -16,8 +23,9 @@ use dwallet_mpc_types::dwallet_mpc::{
     VersionedCentralizedDKGPublicOutput, VersionedDwalletDKGFirstRoundPublicOutput,
     VersionedDwalletDKGSecondRoundPublicOutput, VersionedDwalletUserSecretShare,
     VersionedEncryptedUserShare, VersionedImportedDWalletPublicOutput,
-    VersionedImportedDwalletOutgoingMessage, VersionedNetworkDkgOutput, VersionedPresignOutput,
-    VersionedPublicKeyShareAndProof, VersionedSignOutput, VersionedUserSignedMessage,
+    VersionedImportedDwalletOutgoingMessage, VersionedNetworkDkgOutput,
+    VersionedNetworkEncryptedKey, VersionedPresignOutput, VersionedPublicKeyShareAndProof,
+    VersionedSignOutput, VersionedThresholdDecryptionOutput, VersionedUserSignedMessage,
 };
 use group::{CyclicGroupElement, GroupElement, OsCsRng, Samplable, secp256k1};
 use homomorphic_encryption::{
@@ -26,13 +34,15 @@ use homomorphic_encryption::{
 };
 use mpc::Party;
 use mpc::two_party::Round;
-use rand_core::SeedableRng;
+use rand_core::{RngCore, SeedableRng};
 use twopc_mpc::secp256k1::SCALAR_LIMBS;
 
 use class_groups::encryption_key::public_parameters::Instantiate;
 use commitment::CommitmentSizedNumber;
+use dwallet_mpc_types::key_derivation::derive_dwallet_public_key;
 use message_digest::message_digest::message_digest;
 use serde::{Deserialize, Serialize};
+use sha3::Digest;
 use twopc_mpc::dkg::Protocol;
 use twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters;
 use twopc_mpc::sign::verify_signature;
@@ -48,6 +58,23 @@ pub struct DWalletPublicKeys {
     pub decentralized_public_share: Vec<u8>,
     pub public_key: Vec<u8>,
 }
+
+impl DWalletPublicKeys {
+    /// Extracts the serialized public keys from the DKG output of a dWallet.
+    pub fn from_dkg_output(dwallet_dkg_output: &[u8]) -> anyhow::Result<Self> {
+        let VersionedDwalletDKGSecondRoundPublicOutput::V1(dwallet_dkg_output) =
+            bcs::from_bytes(dwallet_dkg_output)?;
+        let dwallet_dkg_output: DKGDecentralizedOutput = bcs::from_bytes(&dwallet_dkg_output)?;
+        Ok(Self {
+            centralized_public_share: bcs::to_bytes(
+                &dwallet_dkg_output.centralized_party_public_key_share,
+            )?,
+            decentralized_public_share: bcs::to_bytes(&dwallet_dkg_output.public_key_share)?,
+            public_key: bcs::to_bytes(&dwallet_dkg_output.public_key)?,
+        })
+    }
+}
+
 pub type DKGDecentralizedOutput =
     <AsyncProtocol as twopc_mpc::dkg::Protocol>::DecentralizedPartyDKGOutput;
 
@@ -60,6 +87,12 @@ type Secp256k1EncryptionKey = EncryptionKey<
     secp256k1::GroupElement,
 >;
 
+/// Domain separator of the keystream sealing data under a key encrypted to the network.
+const NETWORK_SEALED_DATA_KEYSTREAM_DOMAIN: &[u8] = b"ika-network-sealed-data-keystream";
+/// Domain separator of the authentication tag of data sealed under a key encrypted to the network.
+const NETWORK_SEALED_DATA_TAG_DOMAIN: &[u8] = b"ika-network-sealed-data-tag";
+const NETWORK_SEALED_DATA_TAG_LENGTH: usize = 32;
+
 type ImportSecretKeyFirstStep =
     <AsyncProtocol as twopc_mpc::dkg::Protocol>::TrustedDealerDKGCentralizedPartyRound;
 
@@ -158,6 +191,9 @@ pub fn create_dkg_output(
 ///
 /// The [`advance_centralized_sign_party`] function is
 /// called by the client (the centralized party).
+///
+/// Signs with the child key of the dWallet at `derivation_path`,
+/// or with the dWallet key itself if it is empty.
 pub fn advance_centralized_sign_party(
     protocol_pp: Vec<u8>,
     decentralized_party_dkg_public_output: SerializedWrappedMPCPublicOutput,
@@ -165,6 +201,7 @@ pub fn advance_centralized_sign_party(
     presign: SerializedWrappedMPCPublicOutput,
     message: Vec<u8>,
     hash_type: u32,
+    derivation_path: Vec<u32>,
 ) -> anyhow::Result<SignedMessage> {
     let decentralized_party_dkg_public_output =
         bcs::from_bytes(&decentralized_party_dkg_public_output)?;
@@ -177,6 +214,12 @@ pub fn advance_centralized_sign_party(
             let VersionedDwalletUserSecretShare::V1(centralized_party_secret_key_share) =
                 centralized_party_secret_key_share;
             let decentralized_output: <AsyncProtocol as twopc_mpc::dkg::Protocol>::DecentralizedPartyDKGOutput = bcs::from_bytes(&decentralized_party_dkg_public_output)?;
+            let (decentralized_output, centralized_party_secret_key_share) =
+                derive_child_key_shares(
+                    decentralized_output,
+                    bcs::from_bytes(&centralized_party_secret_key_share)?,
+                    &derivation_path,
+                )?;
             let centralized_public_output = twopc_mpc::class_groups::DKGCentralizedPartyOutput::<
                 { secp256k1::SCALAR_LIMBS },
                 secp256k1::GroupElement,
@@ -201,7 +244,7 @@ pub fn advance_centralized_sign_party(
 
             let round_result = SignCentralizedParty::advance(
                 (),
-                &bcs::from_bytes(&centralized_party_secret_key_share)?,
+                &centralized_party_secret_key_share,
                 &centralized_party_public_input,
                 &mut OsCsRng,
             )
@@ -215,6 +258,58 @@ pub fn advance_centralized_sign_party(
     }
 }
 
+/// Derives the DKG output and the user secret key share of the child key of a dWallet
+/// at `derivation_path`, the same way the network derives the DKG output when signing.
+///
+/// The user secret key share absorbs the tweak of the child key,
+/// while the network share is that of the dWallet.
+pub fn derive_child_key_shares(
+    dkg_output: DKGDecentralizedOutput,
+    user_secret_key_share: secp256k1::Scalar,
+    derivation_path: &[u32],
+) -> anyhow::Result<(DKGDecentralizedOutput, secp256k1::Scalar)> {
+    if derivation_path.is_empty() {
+        return Ok((dkg_output, user_secret_key_share));
+    }
+
+    let derived_public_key = derive_dwallet_public_key(&dkg_output.public_key, derivation_path)?;
+    let public_parameters = secp256k1::group_element::PublicParameters::default();
+    let tweak_public_share =
+        secp256k1::GroupElement::new(derived_public_key.public_key, &public_parameters)?
+            - secp256k1::GroupElement::new(dkg_output.public_key, &public_parameters)?;
+    let centralized_party_public_key_share = secp256k1::GroupElement::new(
+        dkg_output.centralized_party_public_key_share,
+        &public_parameters,
+    )? + tweak_public_share;
+
+    Ok((
+        DKGDecentralizedOutput {
+            public_key_share: dkg_output.public_key_share,
+            public_key: derived_public_key.public_key,
+            centralized_party_public_key_share: centralized_party_public_key_share.value(),
+            encrypted_secret_key_share: dkg_output.encrypted_secret_key_share,
+        },
+        user_secret_key_share + derived_public_key.tweak,
+    ))
+}
+
+/// Derives the public key of the child key of a dWallet at the non-hardened `derivation_path`.
+/// Returns a tuple of the serialized public key and its BIP-32 chain code.
+pub fn derive_dwallet_public_key_inner(
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    derivation_path: Vec<u32>,
+) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let VersionedDwalletDKGSecondRoundPublicOutput::V1(dwallet_dkg_output) =
+        bcs::from_bytes(&dwallet_dkg_output)?;
+    let dwallet_dkg_output: DKGDecentralizedOutput = bcs::from_bytes(&dwallet_dkg_output)?;
+    let derived_public_key =
+        derive_dwallet_public_key(&dwallet_dkg_output.public_key, &derivation_path)?;
+    Ok((
+        bcs::to_bytes(&derived_public_key.public_key)?,
+        derived_public_key.chain_code.to_vec(),
+    ))
+}
+
 pub fn sample_dwallet_keypair_inner(protocol_pp: Vec<u8>) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
     let protocol_public_parameters: ProtocolPublicParameters = bcs::from_bytes(&protocol_pp)?;
     let secret_key = twopc_mpc::secp256k1::Scalar::sample(
@@ -445,3 +540,216 @@ pub fn decrypt_user_share_inner(
     let secret_share_bytes = bcs::to_bytes(&plaintext.value())?;
     Ok(secret_share_bytes)
 }
+
+/// Encrypts `data` so that it can only be opened once the network threshold decrypts its key.
+///
+/// A fresh key is sampled and encrypted to the network encryption key
+/// together with a proof of its encryption, so the network only ever decrypts keys
+/// that are known to their requester.
+/// Returns the serialized encrypted key, to be registered on-chain,
+/// and the data sealed under the key, which can be stored anywhere.
+pub fn encrypt_to_network_key_inner(
+    protocol_pp: Vec<u8>,
+    data: Vec<u8>,
+) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let protocol_public_params: ProtocolPublicParameters = bcs::from_bytes(&protocol_pp)?;
+    let key = twopc_mpc::secp256k1::Scalar::sample(
+        &protocol_public_params
+            .as_ref()
+            .scalar_group_public_parameters,
+        &mut OsCsRng,
+    )?;
+    let generator_group_element =
+        group::secp256k1::group_element::GroupElement::generator_from_public_parameters(
+            &group::secp256k1::group_element::PublicParameters::default(),
+        )?;
+    let public_key_share = key * generator_group_element;
+    let network_encryption_key = protocol_public_params
+        .encryption_scheme_public_parameters
+        .encryption_key
+        .clone();
+    let encrypted_key_and_proof =
+        <AsyncProtocol as Protocol>::encrypt_and_prove_centralized_party_share(
+            &protocol_public_params,
+            network_encryption_key,
+            key,
+            &mut OsCsRng,
+        )?;
+    let encrypted_key = VersionedNetworkEncryptedKey::V1(bcs::to_bytes(&(
+        public_key_share.value(),
+        encrypted_key_and_proof,
+    ))?);
+    let sealed_data = seal_under_key(&bcs::to_bytes(&key.value())?, data);
+    Ok((bcs::to_bytes(&encrypted_key)?, sealed_data))
+}
+
+/// Creates the user's part of a dWallet key refresh.
+///
+/// Samples an offset, adds it to the user secret key share,
+/// and encrypts its negation to the network encryption key with a proof of its encryption,
+/// so that the network can subtract it from its own share without learning it.
+/// Returns a tuple of the serialized [`VersionedNetworkEncryptedKey`] of the negated offset,
+/// the refreshed [`VersionedDwalletUserSecretShare`],
+/// and the [`VersionedEncryptedUserShare`] of the refreshed share under `encryption_key`.
+/// # Warning
+/// The refreshed user secret share returned from this function should never be sent
+/// and should always be kept private.
+/// It only becomes valid once the network has completed the refresh,
+/// until then the current user secret share must be kept.
+pub fn create_key_refresh_inner(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    user_secret_share: Vec<u8>,
+    encryption_key: Vec<u8>,
+) -> anyhow::Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    let protocol_public_params: ProtocolPublicParameters = bcs::from_bytes(&protocol_pp)?;
+    let VersionedDwalletUserSecretShare::V1(user_secret_share) =
+        bcs::from_bytes(&user_secret_share)?;
+    let VersionedDwalletDKGSecondRoundPublicOutput::V1(dwallet_dkg_output) =
+        bcs::from_bytes(&dwallet_dkg_output)?;
+    <AsyncProtocol as Protocol>::verify_centralized_party_secret_key_share(
+        &protocol_public_params,
+        bcs::from_bytes(&dwallet_dkg_output)?,
+        bcs::from_bytes(&user_secret_share)?,
+    )
+    .map_err(|_| anyhow!("the user secret share does not match the dWallet output"))?;
+    let user_secret_share: twopc_mpc::secp256k1::Scalar = bcs::from_bytes(&user_secret_share)?;
+
+    let refresh_share = twopc_mpc::secp256k1::Scalar::sample(
+        &protocol_public_params
+            .as_ref()
+            .scalar_group_public_parameters,
+        &mut OsCsRng,
+    )?;
+    let negated_refresh_share = -refresh_share;
+    let generator_group_element =
+        group::secp256k1::group_element::GroupElement::generator_from_public_parameters(
+            &group::secp256k1::group_element::PublicParameters::default(),
+        )?;
+    let refresh_public_share = negated_refresh_share * generator_group_element;
+    let network_encryption_key = protocol_public_params
+        .encryption_scheme_public_parameters
+        .encryption_key
+        .clone();
+    let encrypted_refresh_share_and_proof =
+        <AsyncProtocol as Protocol>::encrypt_and_prove_centralized_party_share(
+            &protocol_public_params,
+            network_encryption_key,
+            negated_refresh_share,
+            &mut OsCsRng,
+        )?;
+    let encrypted_refresh_share = VersionedNetworkEncryptedKey::V1(bcs::to_bytes(&(
+        refresh_public_share.value(),
+        encrypted_refresh_share_and_proof,
+    ))?);
+
+    let refreshed_user_secret_share = bcs::to_bytes(&VersionedDwalletUserSecretShare::V1(
+        bcs::to_bytes(&(user_secret_share + refresh_share))?,
+    ))?;
+    let encrypted_refreshed_user_secret_share = encrypt_secret_key_share_and_prove(
+        refreshed_user_secret_share.clone(),
+        encryption_key,
+        protocol_pp,
+    )?;
+    Ok((
+        bcs::to_bytes(&encrypted_refresh_share)?,
+        refreshed_user_secret_share,
+        encrypted_refreshed_user_secret_share,
+    ))
+}
+
+/// Opens data sealed by [`encrypt_to_network_key_inner`],
+/// using the output of the network's threshold decryption of its key.
+///
+/// The network re-encrypts the decrypted key to the requester's `encryption_key`,
+/// so it is decrypted with `decryption_key` after verifying the proof of its re-encryption
+/// against the public key share of the `encrypted_key` registered on-chain.
+pub fn open_network_decrypted_data(
+    decryption_key: Vec<u8>,
+    encryption_key: Vec<u8>,
+    encrypted_key: Vec<u8>,
+    threshold_decryption_output: Vec<u8>,
+    sealed_data: Vec<u8>,
+    protocol_pp: Vec<u8>,
+) -> anyhow::Result<Vec<u8>> {
+    let VersionedNetworkEncryptedKey::V1(encrypted_key) = bcs::from_bytes(&encrypted_key)?;
+    let (public_key_share, _): (
+        secp256k1::group_element::Value,
+        <AsyncProtocol as Protocol>::EncryptedSecretKeyShareMessage,
+    ) = bcs::from_bytes(&encrypted_key)?;
+    let VersionedThresholdDecryptionOutput::V1(re_encrypted_key) =
+        bcs::from_bytes(&threshold_decryption_output)?;
+    let (_, encryption_of_key): <AsyncProtocol as Protocol>::EncryptedSecretKeyShareMessage =
+        bcs::from_bytes(&re_encrypted_key)?;
+
+    // The proof of re-encryption is that of an encrypted user secret key share,
+    // so the key is decrypted like one, against a DKG output holding its public key share.
+    let statement =
+        VersionedDwalletDKGSecondRoundPublicOutput::V1(bcs::to_bytes(&DKGDecentralizedOutput {
+            public_key_share,
+            public_key: public_key_share,
+            centralized_party_public_key_share: public_key_share,
+            encrypted_secret_key_share: encryption_of_key,
+        })?);
+    let key = decrypt_user_share_inner(
+        decryption_key,
+        encryption_key,
+        bcs::to_bytes(&statement)?,
+        bcs::to_bytes(&VersionedEncryptedUserShare::V1(re_encrypted_key))?,
+        protocol_pp,
+    )?;
+    open_under_key(&key, sealed_data)
+}
+
+fn network_sealed_data_keystream(key: &[u8], length: usize) -> Vec<u8> {
+    let seed: [u8; 32] = sha3::Sha3_256::new_with_prefix(NETWORK_SEALED_DATA_KEYSTREAM_DOMAIN)
+        .chain_update(key)
+        .finalize()
+        .into();
+    let mut keystream = vec![0u8; length];
+    rand_chacha::ChaCha20Rng::from_seed(seed).fill_bytes(&mut keystream);
+    keystream
+}
+
+fn network_sealed_data_tag(key: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    sha3::Sha3_256::new_with_prefix(NETWORK_SEALED_DATA_TAG_DOMAIN)
+        .chain_update(key)
+        .chain_update(ciphertext)
+        .finalize()
+        .to_vec()
+}
+
+fn seal_under_key(key: &[u8], data: Vec<u8>) -> Vec<u8> {
+    let keystream = network_sealed_data_keystream(key, data.len());
+    let mut sealed_data: Vec<u8> = data
+        .iter()
+        .zip(keystream.iter())
+        .map(|(byte, key_byte)| byte ^ key_byte)
+        .collect();
+    let tag = network_sealed_data_tag(key, &sealed_data);
+    sealed_data.extend(tag);
+    sealed_data
+}
+
+fn open_under_key(key: &[u8], sealed_data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    let Some(ciphertext_length) = sealed_data
+        .len()
+        .checked_sub(NETWORK_SEALED_DATA_TAG_LENGTH)
+    else {
+        return Err(anyhow!(
+            "sealed data is shorter than its authentication tag"
+        ));
+    };
+    let (ciphertext, tag) = sealed_data.split_at(ciphertext_length);
+    if network_sealed_data_tag(key, ciphertext) != tag {
+        return Err(anyhow!(
+            "sealed data does not match the decrypted key, or was tampered with"
+        ));
+    }
+    let keystream = network_sealed_data_keystream(key, ciphertext.len());
+    Ok(ciphertext
+        .iter()
+        .zip(keystream.iter())
+        .map(|(byte, key_byte)| byte ^ key_byte)
+        .collect())
+}
@@ -0,0 +1,119 @@
+//! Deriving the class-groups encryption keys of a user from a BIP-39 recovery phrase.
+//!
+//! The user secret share of every dWallet is encrypted to a class-groups encryption key of the
+//! user, so a wallet that derives these keys from its recovery phrase can restore all the encrypted
+//! user secret shares of its dWallets from the phrase alone.
+//!
+//! The derivation is:
+//! 1. The 64-byte BIP-39 seed of the (English) mnemonic and passphrase:
+//!    `PBKDF2-HMAC-SHA512(NFKD(mnemonic), "mnemonic" || NFKD(passphrase), 2048)`.
+//! 2. The 32-byte class-groups seed of the account:
+//!    `SHA3-256(CLASS_GROUPS_SEED_DOMAIN || account_index (u32, little-endian) || bip39_seed)`.
+//! 3. The keypair of [`generate_secp256k1_cg_keypair_from_seed_internal`] from that seed.
+//!
+//! Different account indices give independent keys, for users that want a key per wallet account.
+//! Changing any step changes the keys of existing users, who would then not be able to restore
+//! their shares, so the derivation must stay fixed.
+
+use crate::generate_secp256k1_cg_keypair_from_seed_internal;
+use bip39::{Language, Mnemonic, Seed};
+use sha3::{Digest, Sha3_256};
+
+/// The domain separator of the class-groups seed derivation.
+pub const CLASS_GROUPS_SEED_DOMAIN: &[u8] = b"ika dwallet secp256k1 class-groups encryption key";
+
+/// Derives the 32-byte class-groups seed of `account_index` from a BIP-39 mnemonic and passphrase,
+/// rejecting mnemonics with an invalid word count, word or checksum.
+pub fn derive_cg_seed_from_mnemonic(
+    mnemonic: &str,
+    passphrase: &str,
+    account_index: u32,
+) -> anyhow::Result<[u8; 32]> {
+    let mnemonic = Mnemonic::from_phrase(mnemonic, Language::English)?;
+    let bip39_seed = Seed::new(&mnemonic, passphrase);
+    Ok(derive_cg_seed_from_bip39_seed(
+        bip39_seed.as_bytes(),
+        account_index,
+    ))
+}
+
+fn derive_cg_seed_from_bip39_seed(bip39_seed: &[u8], account_index: u32) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(CLASS_GROUPS_SEED_DOMAIN);
+    hasher.update(account_index.to_le_bytes());
+    hasher.update(bip39_seed);
+    hasher.finalize().into()
+}
+
+/// Derives the Secp256k1 class-groups keypair of `account_index` from a BIP-39 mnemonic
+/// and passphrase, see [`generate_secp256k1_cg_keypair_from_seed_internal`].
+/// # Warning
+/// The mnemonic, the passphrase and the returned decryption key should never be sent
+/// and should always be kept private.
+pub fn generate_secp256k1_cg_keypair_from_mnemonic_internal(
+    mnemonic: &str,
+    passphrase: &str,
+    account_index: u32,
+) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    generate_secp256k1_cg_keypair_from_seed_internal(derive_cg_seed_from_mnemonic(
+        mnemonic,
+        passphrase,
+        account_index,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn bip39_seed_matches_the_reference_vector() {
+        let mnemonic = Mnemonic::from_phrase(MNEMONIC, Language::English).unwrap();
+        assert_eq!(
+            hex::encode(Seed::new(&mnemonic, "TREZOR").as_bytes()),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+
+    #[test]
+    fn derivation_is_fixed() {
+        assert_eq!(
+            hex::encode(derive_cg_seed_from_mnemonic(MNEMONIC, "TREZOR", 0).unwrap()),
+            "3e1442bab1a29dd5c8fd537d754677c7e03fa29e719cc5ec02c6b6c29a60415a"
+        );
+    }
+
+    #[test]
+    fn accounts_and_passphrases_derive_distinct_seeds() {
+        let seed = derive_cg_seed_from_mnemonic(MNEMONIC, "", 0).unwrap();
+        assert_eq!(seed, derive_cg_seed_from_mnemonic(MNEMONIC, "", 0).unwrap());
+        assert_ne!(seed, derive_cg_seed_from_mnemonic(MNEMONIC, "", 1).unwrap());
+        assert_ne!(
+            seed,
+            derive_cg_seed_from_mnemonic(MNEMONIC, "TREZOR", 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn mnemonic_whitespace_is_normalized() {
+        assert_eq!(
+            derive_cg_seed_from_mnemonic(&format!("  {}\n", MNEMONIC.replace(' ', "  ")), "", 0)
+                .unwrap(),
+            derive_cg_seed_from_mnemonic(MNEMONIC, "", 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_mnemonics_are_rejected() {
+        // A bad checksum.
+        assert!(
+            derive_cg_seed_from_mnemonic(&MNEMONIC.replace("about", "abandon"), "", 0).is_err()
+        );
+        // A word out of the wordlist.
+        assert!(derive_cg_seed_from_mnemonic(&MNEMONIC.replace("about", "ika"), "", 0).is_err());
+        // A bad word count.
+        assert!(derive_cg_seed_from_mnemonic("abandon abandon about", "", 0).is_err());
+    }
+}
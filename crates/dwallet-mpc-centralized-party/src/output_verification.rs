@@ -0,0 +1,222 @@
+//! Verification of the dWallet DKG outputs and presigns the client reads from the chain.
+//!
+//! [`create_dkg_output`](crate::create_dkg_output) and
+//! [`advance_centralized_sign_party`](crate::advance_centralized_sign_party) take these outputs
+//! as they are, so a compromised RPC provider could feed the client malformed outputs,
+//! or outputs of another dWallet.
+//! The functions here check what the client can check on its own:
+//! that a DKG output is well-formed, that its key shares add up to the public key
+//! of the dWallet, and that its encrypted network share is a ciphertext under the network
+//! encryption key.
+//!
+//! That an output was certified by the network, and that a presign was created for the dWallet,
+//! is checked by verifying the inclusion of the output in a certified dWallet checkpoint,
+//! with `LightClient::verify_dwallet_dkg_output` and `LightClient::verify_presign_output`
+//! of the `ika-light-client` crate.
+
+use crate::{DKGDecentralizedOutput, Secp256k1EncryptionKey};
+use anyhow::{Context, anyhow};
+use dwallet_mpc_types::dwallet_mpc::{
+    SerializedWrappedMPCPublicOutput, VersionedCentralizedDKGPublicOutput,
+    VersionedDwalletDKGSecondRoundPublicOutput, VersionedPresignOutput,
+};
+use dwallet_mpc_types::key_derivation::to_affine_point;
+use group::{GroupElement, secp256k1};
+use homomorphic_encryption::{AdditivelyHomomorphicEncryptionKey, GroupsPublicParametersAccessors};
+use k256::ProjectivePoint;
+use k256::elliptic_curve::group::Group;
+use twopc_mpc::secp256k1::SCALAR_LIMBS;
+use twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters;
+
+type DKGCentralizedOutput =
+    twopc_mpc::class_groups::DKGCentralizedPartyOutput<SCALAR_LIMBS, secp256k1::GroupElement>;
+type NetworkCiphertext = <Secp256k1EncryptionKey as AdditivelyHomomorphicEncryptionKey<
+    SCALAR_LIMBS,
+>>::CiphertextSpaceGroupElement;
+
+/// Verifies that `dwallet_dkg_output` is a well-formed DKG output of the dWallet of `public_key`
+/// (the serialized public key, as in [`DWalletPublicKeys`](crate::DWalletPublicKeys)),
+/// under the network encryption key of `protocol_pp`.
+pub fn verify_dwallet_dkg_output_inner(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    public_key: Vec<u8>,
+) -> anyhow::Result<()> {
+    let protocol_public_params: ProtocolPublicParameters = bcs::from_bytes(&protocol_pp)?;
+    let dwallet_dkg_output = deserialize_dwallet_dkg_output(&dwallet_dkg_output)?;
+    verify_public_key_shares(
+        &dwallet_dkg_output.centralized_party_public_key_share,
+        &dwallet_dkg_output.public_key_share,
+        &dwallet_dkg_output.public_key,
+    )?;
+    if bcs::to_bytes(&dwallet_dkg_output.public_key)? != public_key {
+        return Err(anyhow!(
+            "the DKG output is not of the dWallet: its public key differs"
+        ));
+    }
+    NetworkCiphertext::new(
+        dwallet_dkg_output.encrypted_secret_key_share,
+        protocol_public_params
+            .encryption_scheme_public_parameters
+            .ciphertext_space_public_parameters(),
+    )
+    .context(
+        "the encrypted network secret key share is not a ciphertext of the network encryption key",
+    )?;
+    Ok(())
+}
+
+/// Verifies `dwallet_dkg_output`, as read from the chain after the DKG of the dWallet,
+/// against the user's own `centralized_public_output` of
+/// [`create_dkg_output`](crate::create_dkg_output): both parties must agree on the public key
+/// and on each other's key share.
+pub fn verify_dwallet_dkg_output_against_centralized_output_inner(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    centralized_public_output: SerializedWrappedMPCPublicOutput,
+) -> anyhow::Result<()> {
+    let VersionedCentralizedDKGPublicOutput::V1(centralized_public_output) =
+        bcs::from_bytes(&centralized_public_output)?;
+    let centralized_public_output: DKGCentralizedOutput =
+        bcs::from_bytes(&centralized_public_output)?;
+    let decoded_dwallet_dkg_output = deserialize_dwallet_dkg_output(&dwallet_dkg_output)?;
+    for (name, centralized_value, decentralized_value) in [
+        (
+            "user public key share",
+            &centralized_public_output.public_key_share,
+            &decoded_dwallet_dkg_output.centralized_party_public_key_share,
+        ),
+        (
+            "network public key share",
+            &centralized_public_output.decentralized_party_public_key_share,
+            &decoded_dwallet_dkg_output.public_key_share,
+        ),
+        (
+            "public key",
+            &centralized_public_output.public_key,
+            &decoded_dwallet_dkg_output.public_key,
+        ),
+    ] {
+        if to_affine_point(centralized_value)? != to_affine_point(decentralized_value)? {
+            return Err(anyhow!(
+                "the DKG output doesn't match the centralized DKG output: its {name} differs"
+            ));
+        }
+    }
+    verify_dwallet_dkg_output_inner(
+        protocol_pp,
+        dwallet_dkg_output,
+        bcs::to_bytes(&centralized_public_output.public_key)?,
+    )
+}
+
+/// Verifies the inputs of [`advance_centralized_sign_party`](crate::advance_centralized_sign_party)
+/// that are read from the chain: the DKG output of the dWallet of `public_key`,
+/// as in [`verify_dwallet_dkg_output_inner`], and that `presign` is a well-formed presign
+/// of the dWallet `dwallet_id`.
+///
+/// `presign_dwallet_id` is the dWallet the network created the presign for, as returned
+/// with the presign by `LightClient::verify_presign_output`, or `None` for a presign
+/// of any dWallet.
+pub fn verify_sign_inputs_inner(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: SerializedWrappedMPCPublicOutput,
+    public_key: Vec<u8>,
+    dwallet_id: Vec<u8>,
+    presign_dwallet_id: Option<Vec<u8>>,
+    presign: SerializedWrappedMPCPublicOutput,
+) -> anyhow::Result<()> {
+    verify_dwallet_dkg_output_inner(protocol_pp, dwallet_dkg_output, public_key)?;
+    verify_presign_dwallet(&dwallet_id, presign_dwallet_id.as_deref())?;
+    let VersionedPresignOutput::V1(presign) =
+        bcs::from_bytes(&presign).context("malformed presign")?;
+    let _: <crate::AsyncProtocol as twopc_mpc::presign::Protocol>::Presign =
+        bcs::from_bytes(&presign).context("malformed presign")?;
+    Ok(())
+}
+
+/// Checks that a presign created for `presign_dwallet_id` can sign with the dWallet `dwallet_id`.
+fn verify_presign_dwallet(
+    dwallet_id: &[u8],
+    presign_dwallet_id: Option<&[u8]>,
+) -> anyhow::Result<()> {
+    match presign_dwallet_id {
+        Some(presign_dwallet_id) if presign_dwallet_id != dwallet_id => Err(anyhow!(
+            "the presign is of another dWallet than the one it signs with"
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn deserialize_dwallet_dkg_output(
+    dwallet_dkg_output: &[u8],
+) -> anyhow::Result<DKGDecentralizedOutput> {
+    let VersionedDwalletDKGSecondRoundPublicOutput::V1(dwallet_dkg_output) =
+        bcs::from_bytes(dwallet_dkg_output).context("malformed dWallet DKG output")?;
+    bcs::from_bytes(&dwallet_dkg_output).context("malformed dWallet DKG output")
+}
+
+/// Checks that the key shares are points of the curve that add up to a non-identity public key.
+fn verify_public_key_shares(
+    centralized_party_public_key_share: &secp256k1::group_element::Value,
+    decentralized_party_public_key_share: &secp256k1::group_element::Value,
+    public_key: &secp256k1::group_element::Value,
+) -> anyhow::Result<()> {
+    let point =
+        |value: &secp256k1::group_element::Value, name: &str| -> anyhow::Result<ProjectivePoint> {
+            Ok(ProjectivePoint::from(
+                to_affine_point(value).with_context(|| format!("invalid {name}"))?,
+            ))
+        };
+    let public_key = point(public_key, "public key")?;
+    if bool::from(public_key.is_identity()) {
+        return Err(anyhow!("the public key is the identity"));
+    }
+    if point(centralized_party_public_key_share, "user public key share")?
+        + point(
+            decentralized_party_public_key_share,
+            "network public key share",
+        )?
+        != public_key
+    {
+        return Err(anyhow!(
+            "the user and network public key shares don't add up to the public key"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dwallet_mpc_types::key_derivation::from_affine_point;
+
+    fn value(scalar: u64) -> secp256k1::group_element::Value {
+        from_affine_point(&(ProjectivePoint::GENERATOR * k256::Scalar::from(scalar)).to_affine())
+            .unwrap()
+    }
+
+    #[test]
+    fn consistent_key_shares_are_accepted() {
+        assert!(verify_public_key_shares(&value(3), &value(4), &value(7)).is_ok());
+    }
+
+    #[test]
+    fn inconsistent_key_shares_are_rejected() {
+        assert!(verify_public_key_shares(&value(3), &value(4), &value(8)).is_err());
+        assert!(verify_public_key_shares(&value(4), &value(4), &value(7)).is_err());
+    }
+
+    #[test]
+    fn identity_public_key_is_rejected() {
+        let identity = from_affine_point(&k256::AffinePoint::IDENTITY).unwrap();
+        assert!(verify_public_key_shares(&value(3), &value(4), &identity).is_err());
+    }
+
+    #[test]
+    fn presigns_of_another_dwallet_are_rejected() {
+        assert!(verify_presign_dwallet(&[1; 32], Some(&[1; 32])).is_ok());
+        assert!(verify_presign_dwallet(&[1; 32], None).is_ok());
+        assert!(verify_presign_dwallet(&[1; 32], Some(&[2; 32])).is_err());
+    }
+}
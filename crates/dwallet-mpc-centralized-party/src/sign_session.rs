@@ -0,0 +1,101 @@
+//! Signing a message with a dWallet from the user side, for adapters of other chains.
+//!
+//! The user computes its part of the signature locally, while submitting the presign and sign
+//! requests to the network and awaiting their outputs is left to a [`DWalletSigningSession`],
+//! so that adapters don't depend on a Sui client.
+
+use crate::{
+    advance_centralized_sign_party, derive_dwallet_public_key_inner, verify_secp_signature_inner,
+};
+use anyhow::anyhow;
+
+/// The key material of the user (the centralized party) of a dWallet.
+#[derive(Clone, Debug)]
+pub struct DWalletUserKeys {
+    /// The serialized protocol public parameters of the network encryption key of the dWallet.
+    pub protocol_public_parameters: Vec<u8>,
+    /// The serialized DKG output of the dWallet.
+    pub dwallet_dkg_output: Vec<u8>,
+    /// The serialized user secret key share of the dWallet.
+    pub user_secret_key_share: Vec<u8>,
+    /// The non-hardened derivation path of the child key of the dWallet to sign with,
+    /// or an empty path for the dWallet key itself.
+    pub derivation_path: Vec<u32>,
+}
+
+impl DWalletUserKeys {
+    /// Returns the serialized public key of the dWallet (or its child key).
+    pub fn public_key(&self) -> anyhow::Result<Vec<u8>> {
+        let (public_key, _) = derive_dwallet_public_key_inner(
+            self.dwallet_dkg_output.clone(),
+            self.derivation_path.clone(),
+        )?;
+        Ok(public_key)
+    }
+}
+
+/// A request to the network to sign a message with a dWallet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DWalletSignRequest {
+    /// The presign output the message is signed with.
+    pub presign: Vec<u8>,
+    pub message: Vec<u8>,
+    pub hash_type: u32,
+    pub derivation_path: Vec<u32>,
+    /// The signature of the user (the centralized party) on the message.
+    pub message_centralized_signature: Vec<u8>,
+}
+
+/// Drives the network side of signing with a dWallet,
+/// e.g. by submitting requests to the Ika coordinator and awaiting their outputs.
+pub trait DWalletSigningSession {
+    /// Requests a presign for the dWallet, returning its output once it is completed.
+    fn presign(&mut self) -> impl Future<Output = anyhow::Result<Vec<u8>>>;
+
+    /// Requests a signature on a message, returning the sign output once it is completed.
+    fn sign(
+        &mut self,
+        request: DWalletSignRequest,
+    ) -> impl Future<Output = anyhow::Result<Vec<u8>>>;
+}
+
+/// Signs `message`, hashed with `hash_type`, with the dWallet (or its child key)
+/// using a new presign, and returns the sign output once it is verified.
+pub async fn sign_with_dwallet(
+    keys: &DWalletUserKeys,
+    session: &mut impl DWalletSigningSession,
+    message: Vec<u8>,
+    hash_type: u32,
+) -> anyhow::Result<Vec<u8>> {
+    let presign = session.presign().await?;
+    let message_centralized_signature = advance_centralized_sign_party(
+        keys.protocol_public_parameters.clone(),
+        keys.dwallet_dkg_output.clone(),
+        keys.user_secret_key_share.clone(),
+        presign.clone(),
+        message.clone(),
+        hash_type,
+        keys.derivation_path.clone(),
+    )?;
+    let sign_output = session
+        .sign(DWalletSignRequest {
+            presign,
+            message: message.clone(),
+            hash_type,
+            derivation_path: keys.derivation_path.clone(),
+            message_centralized_signature,
+        })
+        .await?;
+
+    let is_valid_signature = verify_secp_signature_inner(
+        keys.public_key()?,
+        sign_output.clone(),
+        message,
+        keys.protocol_public_parameters.clone(),
+        hash_type,
+    )?;
+    if !is_valid_signature {
+        return Err(anyhow!("the network signature does not verify"));
+    }
+    Ok(sign_output)
+}
@@ -164,6 +164,18 @@ pub enum VersionedDwalletUserSecretShare {
     V1(MPCPublicOutput),
 }
 
+/// A threshold share of the user secret share, held by one of several user devices.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum VersionedUserDeviceShare {
+    V1(MPCPublicOutput),
+}
+
+/// A password-protected backup of the user side of a dWallet.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum VersionedDWalletBackup {
+    V1(MPCPublicOutput),
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub enum VersionedUserSignedMessage {
     V1(MPCPublicOutput),
@@ -189,6 +201,18 @@ pub enum VersionedEncryptedUserShare {
     V1(MPCPublicOutput),
 }
 
+/// A key encrypted to the network encryption key, with a proof of its encryption.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum VersionedNetworkEncryptedKey {
+    V1(MPCPublicOutput),
+}
+
+/// The output of a threshold decryption session: the decrypted key.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub enum VersionedThresholdDecryptionOutput {
+    V1(MPCPublicOutput),
+}
+
 #[enum_dispatch(MPCDataTrait)]
 #[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
 pub enum VersionedMPCData {
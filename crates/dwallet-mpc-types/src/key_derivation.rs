@@ -0,0 +1,212 @@
+//! Non-hardened, BIP-32-style derivation of child public keys from the public key of a dWallet.
+//!
+//! A child public key is the parent public key plus a tweak times the generator,
+//! where the tweak is derived from the parent public key, its chain code and the child index
+//! exactly as in BIP-32's `CKDpub`.
+//! Whoever holds a share of the parent secret key can sign with the child key
+//! by adding the tweaks along the derivation path to its share,
+//! so a single dWallet can sign for any number of child keys.
+//!
+//! dWallets have no BIP-32 seed, so the chain code of the dWallet public key
+//! is derived from the public key itself, see [`dwallet_chain_code`].
+
+use group::secp256k1;
+use hmac::{Hmac, KeyInit, Mac};
+use k256::elliptic_curve::ff::PrimeField;
+use k256::elliptic_curve::group::{Curve, Group};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::{AffinePoint, FieldBytes, ProjectivePoint, U256};
+use sha2::Digest;
+
+/// Child indices from this one onward are hardened, and can't be derived from a public key.
+pub const HARDENED_CHILD_INDEX_OFFSET: u32 = 0x8000_0000;
+
+/// The maximal length of a derivation path, as BIP-32 serializes the depth of a key in a byte.
+pub const MAX_DERIVATION_PATH_LENGTH: usize = 255;
+
+const DWALLET_CHAIN_CODE_DOMAIN: &[u8] = b"ika-dwallet-bip32-chain-code";
+
+/// A public key derived from the public key of a dWallet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivedPublicKey {
+    /// The derived public key.
+    pub public_key: secp256k1::group_element::Value,
+    /// The chain code of the derived public key, from which its own children are derived.
+    pub chain_code: [u8; 32],
+    /// The sum of the tweaks along the derivation path,
+    /// i.e., the discrete log of the derived public key minus the dWallet public key.
+    pub tweak: secp256k1::Scalar,
+}
+
+/// Returns the chain code of the public key of a dWallet,
+/// the SHA-256 digest of its SEC1-compressed encoding under a domain separator.
+pub fn dwallet_chain_code(
+    public_key: &secp256k1::group_element::Value,
+) -> anyhow::Result<[u8; 32]> {
+    let public_key = to_affine_point(public_key)?;
+    Ok(sha2::Sha256::new_with_prefix(DWALLET_CHAIN_CODE_DOMAIN)
+        .chain_update(public_key.to_encoded_point(true).as_bytes())
+        .finalize()
+        .into())
+}
+
+/// Derives the child public key of the public key of a dWallet at `derivation_path`.
+///
+/// An empty derivation path derives the dWallet public key itself, with a zero tweak.
+pub fn derive_dwallet_public_key(
+    public_key: &secp256k1::group_element::Value,
+    derivation_path: &[u32],
+) -> anyhow::Result<DerivedPublicKey> {
+    if derivation_path.len() > MAX_DERIVATION_PATH_LENGTH {
+        return Err(anyhow::Error::msg(format!(
+            "derivation path is longer than {MAX_DERIVATION_PATH_LENGTH} indices"
+        )));
+    }
+
+    let mut public_key = to_affine_point(public_key)?;
+    let mut chain_code = dwallet_chain_code(&from_affine_point(&public_key)?)?;
+    let mut tweak = k256::Scalar::ZERO;
+    for index in derivation_path {
+        let (child_tweak, child_public_key, child_chain_code) =
+            derive_child_public_key(&public_key, &chain_code, *index)?;
+        tweak += child_tweak;
+        public_key = child_public_key;
+        chain_code = child_chain_code;
+    }
+
+    Ok(DerivedPublicKey {
+        public_key: from_affine_point(&public_key)?,
+        chain_code,
+        tweak: U256::from(tweak).into(),
+    })
+}
+
+/// BIP-32's `CKDpub`: derives the non-hardened child at `index` of `public_key`,
+/// returning the tweak, public key and chain code of the child.
+fn derive_child_public_key(
+    public_key: &AffinePoint,
+    chain_code: &[u8; 32],
+    index: u32,
+) -> anyhow::Result<(k256::Scalar, AffinePoint, [u8; 32])> {
+    if index >= HARDENED_CHILD_INDEX_OFFSET {
+        return Err(anyhow::Error::msg(format!(
+            "hardened child index {index} can't be derived from a public key"
+        )));
+    }
+
+    let mut data = public_key.to_encoded_point(true).as_bytes().to_vec();
+    data.extend(index.to_be_bytes());
+    let digest = hmac_sha512(chain_code, &data);
+    let (tweak, child_chain_code) = digest.split_at(32);
+
+    let tweak: [u8; 32] = tweak.try_into()?;
+    let Some(tweak) =
+        Option::<k256::Scalar>::from(k256::Scalar::from_repr(FieldBytes::from(tweak)))
+    else {
+        return Err(anyhow::Error::msg(format!(
+            "child index {index} derives an invalid key, use the next index"
+        )));
+    };
+    let child_public_key = ProjectivePoint::GENERATOR * tweak + ProjectivePoint::from(*public_key);
+    if bool::from(child_public_key.is_identity()) {
+        return Err(anyhow::Error::msg(format!(
+            "child index {index} derives an invalid key, use the next index"
+        )));
+    }
+
+    Ok((
+        tweak,
+        child_public_key.to_affine(),
+        child_chain_code.try_into()?,
+    ))
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut mac =
+        Hmac::<sha2::Sha512>::new_from_slice(key).expect("HMAC can take a key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Converts the value of a secp256k1 group element to its `k256` affine point.
+// The value of a secp256k1 group element serializes as its underlying affine point.
+pub fn to_affine_point(value: &secp256k1::group_element::Value) -> anyhow::Result<AffinePoint> {
+    Ok(bcs::from_bytes(&bcs::to_bytes(value)?)?)
+}
+
+/// Converts a `k256` affine point to the value of a secp256k1 group element.
+pub fn from_affine_point(point: &AffinePoint) -> anyhow::Result<secp256k1::group_element::Value> {
+    Ok(bcs::from_bytes(&bcs::to_bytes(point)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::{CyclicGroupElement, GroupElement};
+
+    fn public_key_of(secret_key: u64) -> secp256k1::group_element::Value {
+        let point = (ProjectivePoint::GENERATOR * k256::Scalar::from(secret_key)).to_affine();
+        from_affine_point(&point).unwrap()
+    }
+
+    #[test]
+    fn derived_public_key_is_tweaked_public_key() {
+        let public_parameters = secp256k1::group_element::PublicParameters::default();
+        let public_key = public_key_of(42);
+        let derived = derive_dwallet_public_key(&public_key, &[0, 7, 1 << 20]).unwrap();
+
+        let generator = secp256k1::group_element::GroupElement::generator_from_public_parameters(
+            &public_parameters,
+        )
+        .unwrap();
+        let public_key =
+            secp256k1::group_element::GroupElement::new(public_key, &public_parameters).unwrap();
+        assert_eq!(
+            (public_key + derived.tweak * generator).value(),
+            derived.public_key
+        );
+    }
+
+    #[test]
+    fn empty_derivation_path_derives_dwallet_public_key() {
+        let public_key = public_key_of(42);
+        let derived = derive_dwallet_public_key(&public_key, &[]).unwrap();
+
+        assert_eq!(derived.public_key, public_key);
+        assert_eq!(derived.chain_code, dwallet_chain_code(&public_key).unwrap());
+    }
+
+    #[test]
+    fn derivation_is_deterministic_and_index_dependent() {
+        let public_key = public_key_of(42);
+        let first = derive_dwallet_public_key(&public_key, &[1]).unwrap();
+
+        assert_eq!(first, derive_dwallet_public_key(&public_key, &[1]).unwrap());
+        assert_ne!(
+            first.public_key,
+            derive_dwallet_public_key(&public_key, &[2])
+                .unwrap()
+                .public_key
+        );
+    }
+
+    #[test]
+    fn hardened_index_is_rejected() {
+        assert!(
+            derive_dwallet_public_key(&public_key_of(42), &[HARDENED_CHILD_INDEX_OFFSET]).is_err()
+        );
+    }
+
+    #[test]
+    fn hmac_sha512_matches_rfc_4231() {
+        // Test case 2 of RFC 4231.
+        let digest = hmac_sha512(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            digest
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>(),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+}
@@ -1 +1,2 @@
 pub mod dwallet_mpc;
+pub mod key_derivation;
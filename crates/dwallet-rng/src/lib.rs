@@ -69,6 +69,21 @@ impl RootSeed {
         seed
     }
 
+    /// Derive the symmetric key with which this validator encrypts its local cache of
+    /// network decryption key shares at rest.
+    ///
+    /// We don't use the root seed directly, as it would be used for other purposes.
+    /// Instead, we derive a key from it using a distinct hard-coded label.
+    pub fn network_key_cache_encryption_key(&self) -> [u8; Self::SEED_LENGTH] {
+        let mut transcript = Transcript::new(b"Network Key Cache Encryption Key");
+        transcript.append_message(b"root seed", &self.0);
+
+        let mut key: [u8; Self::SEED_LENGTH] = [0; Self::SEED_LENGTH];
+        transcript.challenge_bytes(b"key", &mut key);
+
+        key
+    }
+
     /// Derive a seed deterministically for advancing an MPC round.
     ///
     /// We don't use the root seed directly, as it may be used for other purposes.
@@ -0,0 +1,120 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! An append-only, hash-chained log of every signing decision.
+//!
+//! Each record commits to the digest of the previous one, so removing, reordering or editing
+//! a record is detected by [`AuditLog::verify_chain`]. The log is persisted as JSON lines,
+//! one record per line.
+
+use crate::error::{PolicyError, PolicyResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditDecision {
+    Approved,
+    Rejected { violations: Vec<String> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub sequence_number: u64,
+    /// The Unix time (in seconds) the request was evaluated at.
+    pub timestamp: u64,
+    pub dwallet_id: String,
+    pub request_id: String,
+    /// The hex SHA-256 digest of the payload.
+    pub payload_digest: String,
+    /// The value the request spends, in satoshis, counted against Bitcoin rate limits.
+    pub bitcoin_spend_sats: u64,
+    /// The value the request transfers, in wei, counted against Ethereum rate limits.
+    pub ethereum_value_wei: u128,
+    pub approvers: BTreeSet<String>,
+    pub decision: AuditDecision,
+    /// The digest of the previous record, or `None` for the first record.
+    pub previous_record_digest: Option<String>,
+}
+
+impl AuditRecord {
+    /// The hex SHA-256 digest of the JSON serialization of the record.
+    pub fn digest(&self) -> String {
+        // Serializing a struct of strings, integers and sets can't fail.
+        let record = serde_json::to_vec(self).expect("audit records are serializable");
+        hex::encode(Sha256::digest(record))
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.decision == AuditDecision::Approved
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditLog {
+    records: Vec<AuditRecord>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn records(&self) -> &[AuditRecord] {
+        &self.records
+    }
+
+    /// Appends a record, setting its sequence number and chaining it to the last record.
+    pub fn append(&mut self, mut record: AuditRecord) -> &AuditRecord {
+        record.sequence_number = self.records.len() as u64;
+        record.previous_record_digest = self.records.last().map(AuditRecord::digest);
+        self.records.push(record);
+        self.records.last().expect("a record was just appended")
+    }
+
+    /// Checks that the records are numbered in order, and that each is chained to its predecessor.
+    pub fn verify_chain(&self) -> PolicyResult {
+        let mut previous_record_digest = None;
+        for (sequence_number, record) in self.records.iter().enumerate() {
+            if record.sequence_number != sequence_number as u64 {
+                return Err(PolicyError::InvalidAuditLog {
+                    error: format!(
+                        "record {} is at position {sequence_number}",
+                        record.sequence_number
+                    ),
+                });
+            }
+            if record.previous_record_digest != previous_record_digest {
+                return Err(PolicyError::InvalidAuditLog {
+                    error: format!("record {sequence_number} is not chained to its predecessor"),
+                });
+            }
+            previous_record_digest = Some(record.digest());
+        }
+        Ok(())
+    }
+
+    pub fn to_json_lines(&self) -> String {
+        self.records
+            .iter()
+            .map(|record| {
+                serde_json::to_string(record).expect("audit records are serializable") + "\n"
+            })
+            .collect()
+    }
+
+    /// Parses a log persisted with [`AuditLog::to_json_lines`], and verifies its chain.
+    pub fn from_json_lines(log: &str) -> PolicyResult<Self> {
+        let records = log
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()
+            .map_err(|e| PolicyError::InvalidAuditLog {
+                error: e.to_string(),
+            })?;
+        let log = Self { records };
+        log.verify_chain()?;
+        Ok(log)
+    }
+}
@@ -0,0 +1,405 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Evaluating signing requests against a [`SigningPolicy`].
+
+use crate::audit::{AuditDecision, AuditLog, AuditRecord};
+use crate::error::{PolicyError, PolicyResult, PolicyViolation};
+use crate::policy::{BitcoinRules, DWalletPolicy, EthereumRules, SigningPolicy};
+use crate::request::{SignPayload, SignRequest, bitcoin_fee, bitcoin_spend};
+use dwallet_bitcoin_psbt::Psbt;
+use dwallet_ethereum_tx::{Address, TypedData, TypedTransaction, checksum_address, parse_address};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Evaluates signing requests against a policy, and records every decision in an audit log.
+#[derive(Clone, Debug)]
+pub struct PolicyEngine {
+    policy: SigningPolicy,
+    audit_log: AuditLog,
+}
+
+/// The value a request moves, counted against rate limits once it is approved.
+#[derive(Default)]
+struct RequestValue {
+    bitcoin_spend_sats: u128,
+    ethereum_value_wei: u128,
+}
+
+impl PolicyEngine {
+    pub fn new(policy: SigningPolicy) -> PolicyResult<Self> {
+        Self::with_audit_log(policy, AuditLog::new())
+    }
+
+    /// Resumes from a persisted audit log, so that rate limits account for past requests.
+    pub fn with_audit_log(policy: SigningPolicy, audit_log: AuditLog) -> PolicyResult<Self> {
+        policy.validate()?;
+        audit_log.verify_chain()?;
+        Ok(Self { policy, audit_log })
+    }
+
+    pub fn policy(&self) -> &SigningPolicy {
+        &self.policy
+    }
+
+    pub fn audit_log(&self) -> &AuditLog {
+        &self.audit_log
+    }
+
+    /// Evaluates `request` at Unix time `now` (in seconds), and records the decision.
+    ///
+    /// Returns every broken rule in [`PolicyError::Rejected`], so a request is rejected
+    /// before any presign or sign session is started for it.
+    pub fn authorize(&mut self, request: &SignRequest, now: u64) -> PolicyResult {
+        let (value, violations) = match self.policy.dwallets.get(&request.dwallet_id) {
+            Some(policy) => self.evaluate(policy, request, now),
+            None => (
+                RequestValue::default(),
+                vec![PolicyViolation::UnknownDWallet {
+                    dwallet_id: request.dwallet_id.clone(),
+                }],
+            ),
+        };
+
+        let decision = if violations.is_empty() {
+            AuditDecision::Approved
+        } else {
+            AuditDecision::Rejected {
+                violations: violations.iter().map(ToString::to_string).collect(),
+            }
+        };
+        self.audit_log.append(AuditRecord {
+            sequence_number: 0,
+            timestamp: now,
+            dwallet_id: request.dwallet_id.clone(),
+            request_id: request.request_id.clone(),
+            payload_digest: request.payload.digest(),
+            bitcoin_spend_sats: u64::try_from(value.bitcoin_spend_sats).unwrap_or(u64::MAX),
+            ethereum_value_wei: value.ethereum_value_wei,
+            approvers: request.approvers.clone(),
+            decision,
+            previous_record_digest: None,
+        });
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(PolicyError::Rejected { violations })
+        }
+    }
+
+    fn evaluate(
+        &self,
+        policy: &DWalletPolicy,
+        request: &SignRequest,
+        now: u64,
+    ) -> (RequestValue, Vec<PolicyViolation>) {
+        let mut violations = Vec::new();
+
+        if let Some(not_before) = policy.not_before.filter(|&not_before| now < not_before) {
+            violations.push(PolicyViolation::TimeLocked { not_before });
+        }
+        if let Some(not_after) = policy.not_after.filter(|&not_after| now > not_after) {
+            violations.push(PolicyViolation::Expired { not_after });
+        }
+
+        if let Some(quorum) = &policy.quorum {
+            let approvals = request.approvers.intersection(&quorum.approvers).count();
+            if approvals < quorum.threshold {
+                violations.push(PolicyViolation::QuorumNotReached {
+                    approvals,
+                    threshold: quorum.threshold,
+                });
+            }
+        }
+
+        let payload = &request.payload;
+        let not_allowed = PolicyViolation::PayloadNotAllowed {
+            payload: payload.kind(),
+        };
+        let value = match payload {
+            SignPayload::BitcoinPsbt(psbt) => match &policy.bitcoin {
+                Some(rules) => check_bitcoin_psbt(rules, psbt, &mut violations),
+                None => {
+                    violations.push(not_allowed);
+                    RequestValue::default()
+                }
+            },
+            SignPayload::EthereumTransaction(transaction) => match &policy.ethereum {
+                Some(rules) => check_ethereum_transaction(rules, transaction, &mut violations),
+                None => {
+                    violations.push(not_allowed);
+                    RequestValue::default()
+                }
+            },
+            SignPayload::EthereumTypedData(typed_data) => {
+                match policy
+                    .ethereum
+                    .as_ref()
+                    .filter(|rules| rules.allow_typed_data)
+                {
+                    Some(rules) => check_ethereum_typed_data(rules, typed_data, &mut violations),
+                    None => violations.push(not_allowed),
+                }
+                RequestValue::default()
+            }
+            SignPayload::RawMessage { .. } => {
+                if !policy.allow_raw_messages {
+                    violations.push(not_allowed);
+                }
+                RequestValue::default()
+            }
+        };
+
+        self.check_rate_limits(policy, request, &value, now, &mut violations);
+        (value, violations)
+    }
+
+    /// Checks the limits of `policy` over the requests of the dWallet approved in each window,
+    /// including `request`.
+    fn check_rate_limits(
+        &self,
+        policy: &DWalletPolicy,
+        request: &SignRequest,
+        value: &RequestValue,
+        now: u64,
+        violations: &mut Vec<PolicyViolation>,
+    ) {
+        for rate_limit in &policy.rate_limits {
+            let window_start = now.saturating_sub(rate_limit.window_seconds);
+            let approved: Vec<_> = self
+                .audit_log
+                .records()
+                .iter()
+                .filter(|record| {
+                    record.dwallet_id == request.dwallet_id
+                        && record.timestamp > window_start
+                        && record.is_approved()
+                })
+                .collect();
+
+            let requests = approved.len() as u64 + 1;
+            let bitcoin_spend = approved
+                .iter()
+                .map(|record| u128::from(record.bitcoin_spend_sats))
+                .sum::<u128>()
+                + value.bitcoin_spend_sats;
+            let ethereum_value = approved
+                .iter()
+                .fold(value.ethereum_value_wei, |total, record| {
+                    total.saturating_add(record.ethereum_value_wei)
+                });
+
+            let mut rate_limit_exceeded = |error: String| {
+                violations.push(PolicyViolation::RateLimitExceeded {
+                    window_seconds: rate_limit.window_seconds,
+                    error,
+                })
+            };
+            if let Some(max_requests) = rate_limit.max_requests.filter(|&max| requests > max) {
+                rate_limit_exceeded(format!("{requests} requests exceed {max_requests}"));
+            }
+            if let Some(max_spend) = rate_limit
+                .max_bitcoin_spend_sats
+                .filter(|&max| bitcoin_spend > u128::from(max))
+            {
+                rate_limit_exceeded(format!(
+                    "spending {bitcoin_spend} sats exceeds {max_spend} sats"
+                ));
+            }
+            if let Some(max_value) = rate_limit
+                .max_ethereum_value_wei
+                .filter(|&max| ethereum_value > max)
+            {
+                rate_limit_exceeded(format!(
+                    "transferring {ethereum_value} wei exceeds {max_value} wei"
+                ));
+            }
+        }
+    }
+}
+
+fn check_bitcoin_psbt(
+    rules: &BitcoinRules,
+    psbt: &Psbt,
+    violations: &mut Vec<PolicyViolation>,
+) -> RequestValue {
+    // The scripts were validated with the policy.
+    let scripts = |scripts: &BTreeSet<String>| -> BTreeSet<Vec<u8>> {
+        scripts
+            .iter()
+            .filter_map(|script| hex::decode(script).ok())
+            .collect()
+    };
+    let change_scripts = scripts(&rules.change_scripts);
+
+    if let Some(allowed_destination_scripts) = &rules.allowed_destination_scripts {
+        let allowed_destination_scripts = scripts(allowed_destination_scripts);
+        for output in &psbt.unsigned_transaction.outputs {
+            if !change_scripts.contains(&output.script_pubkey)
+                && !allowed_destination_scripts.contains(&output.script_pubkey)
+            {
+                violations.push(PolicyViolation::DestinationNotAllowed {
+                    destination: hex::encode(&output.script_pubkey),
+                });
+            }
+        }
+    }
+
+    let spend = bitcoin_spend(psbt, &change_scripts);
+    if let Some(limit) = rules
+        .max_spend_sats
+        .map(u128::from)
+        .filter(|&max| spend > max)
+    {
+        violations.push(PolicyViolation::SpendLimitExceeded {
+            amount: spend,
+            limit,
+        });
+    }
+
+    if let Some(limit) = rules.max_fee_sats.map(u128::from) {
+        match bitcoin_fee(psbt) {
+            Ok(fee) if fee > limit => {
+                violations.push(PolicyViolation::FeeLimitExceeded { fee, limit })
+            }
+            Ok(_) => {}
+            Err(error) => violations.push(PolicyViolation::UnknownFee { error }),
+        }
+    }
+
+    RequestValue {
+        bitcoin_spend_sats: spend,
+        ethereum_value_wei: 0,
+    }
+}
+
+fn check_ethereum_transaction(
+    rules: &EthereumRules,
+    transaction: &TypedTransaction,
+    violations: &mut Vec<PolicyViolation>,
+) -> RequestValue {
+    let (chain_id, to, value, data, fee_per_gas) = match transaction {
+        TypedTransaction::Eip2930(transaction) => (
+            transaction.chain_id,
+            transaction.to,
+            transaction.value,
+            &transaction.data,
+            transaction.gas_price,
+        ),
+        TypedTransaction::Eip1559(transaction) => (
+            transaction.chain_id,
+            transaction.to,
+            transaction.value,
+            &transaction.data,
+            transaction.max_fee_per_gas,
+        ),
+    };
+
+    check_chain_id(rules, chain_id, violations);
+    match to {
+        None if !rules.allow_contract_creation => {
+            violations.push(PolicyViolation::PayloadNotAllowed {
+                payload: "contract creations",
+            })
+        }
+        None => {}
+        Some(to) => {
+            if !is_allowed_address(rules.allowed_recipients.as_ref(), &to) {
+                violations.push(PolicyViolation::DestinationNotAllowed {
+                    destination: checksum_address(&to),
+                });
+            }
+            if !data.is_empty() && !rules.allow_contract_calls {
+                violations.push(PolicyViolation::PayloadNotAllowed {
+                    payload: "contract calls",
+                });
+            }
+        }
+    }
+
+    if let Some(limit) = rules.max_value_wei.filter(|&max| value > max) {
+        violations.push(PolicyViolation::SpendLimitExceeded {
+            amount: value,
+            limit,
+        });
+    }
+    if let Some(limit) = rules.max_fee_per_gas_wei.filter(|&max| fee_per_gas > max) {
+        violations.push(PolicyViolation::FeeLimitExceeded {
+            fee: fee_per_gas,
+            limit,
+        });
+    }
+
+    RequestValue {
+        bitcoin_spend_sats: 0,
+        ethereum_value_wei: value,
+    }
+}
+
+fn check_ethereum_typed_data(
+    rules: &EthereumRules,
+    typed_data: &TypedData,
+    violations: &mut Vec<PolicyViolation>,
+) {
+    let invalid_payload = |error: &str| PolicyViolation::InvalidPayload {
+        payload: "EIP-712 typed data",
+        error: error.to_string(),
+    };
+
+    if rules.allowed_chain_ids.is_some() {
+        match domain_chain_id(&typed_data.domain) {
+            Some(chain_id) => check_chain_id(rules, chain_id, violations),
+            None => violations.push(invalid_payload("the domain has no valid chainId")),
+        }
+    }
+    if rules.allowed_verifying_contracts.is_some() {
+        let verifying_contract = typed_data
+            .domain
+            .get("verifyingContract")
+            .and_then(Value::as_str)
+            .and_then(|address| parse_address(address).ok());
+        match verifying_contract {
+            Some(contract)
+                if !is_allowed_address(rules.allowed_verifying_contracts.as_ref(), &contract) =>
+            {
+                violations.push(PolicyViolation::DestinationNotAllowed {
+                    destination: checksum_address(&contract),
+                })
+            }
+            Some(_) => {}
+            None => violations.push(invalid_payload("the domain has no valid verifyingContract")),
+        }
+    }
+}
+
+fn check_chain_id(rules: &EthereumRules, chain_id: u64, violations: &mut Vec<PolicyViolation>) {
+    let is_allowed = rules
+        .allowed_chain_ids
+        .as_ref()
+        .is_none_or(|allowed_chain_ids| allowed_chain_ids.contains(&chain_id));
+    if !is_allowed {
+        violations.push(PolicyViolation::ChainIdNotAllowed { chain_id });
+    }
+}
+
+/// Whether `address` is one of `allowed_addresses`, or any address is allowed.
+fn is_allowed_address(allowed_addresses: Option<&BTreeSet<String>>, address: &Address) -> bool {
+    allowed_addresses.is_none_or(|allowed_addresses| {
+        allowed_addresses
+            .iter()
+            .any(|allowed| parse_address(allowed).is_ok_and(|allowed| &allowed == address))
+    })
+}
+
+/// The `chainId` of an EIP-712 domain, given as a number or a decimal or hex string.
+fn domain_chain_id(domain: &Value) -> Option<u64> {
+    match domain.get("chainId")? {
+        Value::Number(chain_id) => chain_id.as_u64(),
+        Value::String(chain_id) => match chain_id.strip_prefix("0x") {
+            Some(hex_chain_id) => u64::from_str_radix(hex_chain_id, 16).ok(),
+            None => chain_id.parse().ok(),
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use dwallet_bitcoin_psbt::PsbtError;
+use dwallet_ethereum_tx::EthereumError;
+use thiserror::Error;
+
+pub type PolicyResult<T = ()> = Result<T, PolicyError>;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PolicyError {
+    #[error("invalid policy: {error}")]
+    InvalidPolicy { error: String },
+
+    #[error("the signing request was rejected: {}", format_violations(.violations))]
+    Rejected { violations: Vec<PolicyViolation> },
+
+    #[error("invalid audit log: {error}")]
+    InvalidAuditLog { error: String },
+
+    #[error(transparent)]
+    Psbt(#[from] PsbtError),
+
+    #[error(transparent)]
+    Ethereum(#[from] EthereumError),
+
+    #[error("failed to sign: {error}")]
+    Signing { error: String },
+}
+
+/// A rule of the policy of a dWallet that a signing request breaks.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum PolicyViolation {
+    #[error("dWallet {dwallet_id} has no policy")]
+    UnknownDWallet { dwallet_id: String },
+
+    #[error("the dWallet is time-locked until {not_before}")]
+    TimeLocked { not_before: u64 },
+
+    #[error("the dWallet can't sign after {not_after}")]
+    Expired { not_after: u64 },
+
+    #[error("the policy doesn't allow signing {payload}")]
+    PayloadNotAllowed { payload: &'static str },
+
+    #[error("invalid {payload}: {error}")]
+    InvalidPayload {
+        payload: &'static str,
+        error: String,
+    },
+
+    #[error("destination {destination} is not allowed")]
+    DestinationNotAllowed { destination: String },
+
+    #[error("chain ID {chain_id} is not allowed")]
+    ChainIdNotAllowed { chain_id: u64 },
+
+    #[error("spending {amount} exceeds the limit of {limit}")]
+    SpendLimitExceeded { amount: u128, limit: u128 },
+
+    #[error("a fee of {fee} exceeds the limit of {limit}")]
+    FeeLimitExceeded { fee: u128, limit: u128 },
+
+    #[error("the fee can't be computed: {error}")]
+    UnknownFee { error: String },
+
+    #[error("rate limit over {window_seconds} seconds exceeded: {error}")]
+    RateLimitExceeded { window_seconds: u64, error: String },
+
+    #[error("{approvals} of the {threshold} required approvals were given")]
+    QuorumNotReached { approvals: usize, threshold: usize },
+}
+
+fn format_violations(violations: &[PolicyViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,41 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Policy checks for dWallet signing requests.
+//!
+//! A custody service holding the user share of its dWallets declares, per dWallet, what may be
+//! signed: allowed Bitcoin destinations and spend and fee limits over parsed PSBTs, allowed
+//! Ethereum chains, recipients and value limits over parsed transactions, time locks,
+//! rate limits and a quorum of approvers. Every request is evaluated by a [`PolicyEngine`]
+//! before any presign or sign session is started for it, and every decision is appended to
+//! a hash-chained [`AuditLog`]. The policy is denied by default: whatever it doesn't
+//! explicitly allow is rejected.
+//!
+//! ```ignore
+//! let policy = SigningPolicy::from_json(&policy_json)?;
+//! let mut engine = PolicyEngine::new(policy)?;
+//! let request = SignRequest {
+//!     request_id,
+//!     dwallet_id,
+//!     payload: SignPayload::BitcoinPsbt(Psbt::from_base64(&unsigned_psbt)?),
+//!     approvers,
+//! };
+//! let signed = sign_with_policy(&mut engine, &request, now, &user_keys, &mut session).await?;
+//! std::fs::write(audit_log_path, engine.audit_log().to_json_lines())?;
+//! ```
+
+pub mod audit;
+pub mod engine;
+pub mod error;
+pub mod policy;
+pub mod request;
+pub mod signer;
+
+pub use audit::{AuditDecision, AuditLog, AuditRecord};
+pub use engine::PolicyEngine;
+pub use error::{PolicyError, PolicyResult, PolicyViolation};
+pub use policy::{
+    ApprovalQuorum, BitcoinRules, DWalletPolicy, EthereumRules, RateLimit, SigningPolicy,
+};
+pub use request::{SignPayload, SignRequest};
+pub use signer::{SignedPayload, sign_with_policy};
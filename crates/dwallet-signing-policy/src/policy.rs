@@ -0,0 +1,203 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! The declarative policy format: per-dWallet rules, loaded from JSON.
+//!
+//! Everything that is not explicitly allowed is denied: a dWallet without Bitcoin rules
+//! can't sign Bitcoin transactions, and a dWallet without a policy can't sign at all.
+//!
+//! ```json
+//! {
+//!   "dwallets": {
+//!     "0x5a1f...": {
+//!       "not_before": 1767225600,
+//!       "bitcoin": {
+//!         "allowed_destination_scripts": ["0014..."],
+//!         "change_scripts": ["0014..."],
+//!         "max_spend_sats": 100000000,
+//!         "max_fee_sats": 50000
+//!       },
+//!       "ethereum": {
+//!         "allowed_chain_ids": [1],
+//!         "allowed_recipients": ["0x..."],
+//!         "max_value_wei": 1000000000000000000
+//!       },
+//!       "rate_limits": [{ "window_seconds": 86400, "max_requests": 20 }],
+//!       "quorum": { "approvers": ["alice", "bob", "carol"], "threshold": 2 }
+//!     }
+//!   }
+//! }
+//! ```
+
+use crate::error::{PolicyError, PolicyResult};
+use dwallet_ethereum_tx::parse_address;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SigningPolicy {
+    /// The policy of each dWallet, by its ID.
+    pub dwallets: BTreeMap<String, DWalletPolicy>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DWalletPolicy {
+    /// The Unix time (in seconds) before which the dWallet can't sign.
+    #[serde(default)]
+    pub not_before: Option<u64>,
+    /// The Unix time (in seconds) after which the dWallet can't sign.
+    #[serde(default)]
+    pub not_after: Option<u64>,
+    /// Whether messages that are not parsed as transactions may be signed.
+    /// Their content can't be checked, so this should only be allowed for dedicated dWallets.
+    #[serde(default)]
+    pub allow_raw_messages: bool,
+    #[serde(default)]
+    pub bitcoin: Option<BitcoinRules>,
+    #[serde(default)]
+    pub ethereum: Option<EthereumRules>,
+    /// Limits over the requests approved in a sliding window, all of which must hold.
+    #[serde(default)]
+    pub rate_limits: Vec<RateLimit>,
+    /// The approvers required for every request, if any.
+    #[serde(default)]
+    pub quorum: Option<ApprovalQuorum>,
+}
+
+/// Rules over Bitcoin PSBTs.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BitcoinRules {
+    /// The hex output scripts payments may be sent to, or `None` to allow any destination.
+    #[serde(default)]
+    pub allowed_destination_scripts: Option<BTreeSet<String>>,
+    /// The hex output scripts of the dWallet itself, which receive change.
+    /// Outputs to them are neither destinations nor spent.
+    #[serde(default)]
+    pub change_scripts: BTreeSet<String>,
+    /// The maximal total value of the outputs other than change, in satoshis.
+    #[serde(default)]
+    pub max_spend_sats: Option<u64>,
+    /// The maximal fee, in satoshis. Requires the spent outputs of every input in the PSBT.
+    #[serde(default)]
+    pub max_fee_sats: Option<u64>,
+}
+
+/// Rules over Ethereum typed transactions and EIP-712 typed data.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EthereumRules {
+    /// The chain IDs transactions and typed data may be signed for, or `None` to allow any chain.
+    #[serde(default)]
+    pub allowed_chain_ids: Option<BTreeSet<u64>>,
+    /// The addresses transactions may be sent to, or `None` to allow any recipient.
+    #[serde(default)]
+    pub allowed_recipients: Option<BTreeSet<String>>,
+    /// The maximal value of a transaction, in wei.
+    #[serde(default)]
+    pub max_value_wei: Option<u128>,
+    /// The maximal (EIP-1559 maximal) gas price, in wei.
+    #[serde(default)]
+    pub max_fee_per_gas_wei: Option<u128>,
+    /// Whether transactions may carry call data.
+    #[serde(default)]
+    pub allow_contract_calls: bool,
+    #[serde(default)]
+    pub allow_contract_creation: bool,
+    /// Whether EIP-712 typed data may be signed.
+    /// Typed data can authorize transfers (e.g. permits), which the value limits don't cover.
+    #[serde(default)]
+    pub allow_typed_data: bool,
+    /// The verifying contracts typed data may be signed for, or `None` to allow any contract.
+    #[serde(default)]
+    pub allowed_verifying_contracts: Option<BTreeSet<String>>,
+}
+
+/// Limits over the requests of a dWallet approved in the last `window_seconds` seconds,
+/// including the evaluated request.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    pub window_seconds: u64,
+    #[serde(default)]
+    pub max_requests: Option<u64>,
+    #[serde(default)]
+    pub max_bitcoin_spend_sats: Option<u64>,
+    #[serde(default)]
+    pub max_ethereum_value_wei: Option<u128>,
+}
+
+/// `threshold` distinct approvers out of `approvers` must approve every request.
+///
+/// Approvers are identified by the custody service, which authenticates them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApprovalQuorum {
+    pub approvers: BTreeSet<String>,
+    pub threshold: usize,
+}
+
+impl SigningPolicy {
+    pub fn from_json(policy: &str) -> PolicyResult<Self> {
+        let policy: Self =
+            serde_json::from_str(policy).map_err(|e| PolicyError::InvalidPolicy {
+                error: e.to_string(),
+            })?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Checks that every script and address in the policy parses,
+    /// and that every quorum can be reached.
+    pub fn validate(&self) -> PolicyResult {
+        for (dwallet_id, policy) in &self.dwallets {
+            let invalid_policy = |error: String| PolicyError::InvalidPolicy {
+                error: format!("dWallet {dwallet_id}: {error}"),
+            };
+            if let Some(bitcoin) = &policy.bitcoin {
+                for script in bitcoin
+                    .allowed_destination_scripts
+                    .iter()
+                    .flatten()
+                    .chain(&bitcoin.change_scripts)
+                {
+                    hex::decode(script)
+                        .map_err(|_| invalid_policy(format!("invalid script {script}")))?;
+                }
+            }
+            if let Some(ethereum) = &policy.ethereum {
+                for address in ethereum
+                    .allowed_recipients
+                    .iter()
+                    .flatten()
+                    .chain(ethereum.allowed_verifying_contracts.iter().flatten())
+                {
+                    parse_address(address).map_err(|e| invalid_policy(e.to_string()))?;
+                }
+            }
+            if let Some(quorum) = policy
+                .quorum
+                .as_ref()
+                .filter(|quorum| quorum.threshold == 0 || quorum.threshold > quorum.approvers.len())
+            {
+                return Err(invalid_policy(format!(
+                    "a quorum threshold of {} out of {} approvers",
+                    quorum.threshold,
+                    quorum.approvers.len()
+                )));
+            }
+            if policy
+                .rate_limits
+                .iter()
+                .any(|rate_limit| rate_limit.window_seconds == 0)
+            {
+                return Err(invalid_policy(
+                    "a rate limit over an empty window".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Signing requests, as submitted to a custody service before any MPC session is started.
+
+use dwallet_bitcoin_psbt::Psbt;
+use dwallet_ethereum_tx::{TypedData, TypedTransaction};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignRequest {
+    /// An ID of the request, chosen by the custody service, that is recorded in the audit log.
+    pub request_id: String,
+    pub dwallet_id: String,
+    pub payload: SignPayload,
+    /// The (authenticated) approvers of the request.
+    pub approvers: BTreeSet<String>,
+}
+
+/// What a dWallet is asked to sign.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignPayload {
+    BitcoinPsbt(Psbt),
+    EthereumTransaction(TypedTransaction),
+    EthereumTypedData(TypedData),
+    /// A message that is signed as is, the content of which the policy can't check.
+    RawMessage {
+        message: Vec<u8>,
+        hash_type: u32,
+    },
+}
+
+impl SignPayload {
+    /// The name of the kind of the payload, as used in policy violations.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SignPayload::BitcoinPsbt(_) => "Bitcoin PSBTs",
+            SignPayload::EthereumTransaction(_) => "Ethereum transactions",
+            SignPayload::EthereumTypedData(_) => "EIP-712 typed data",
+            SignPayload::RawMessage { .. } => "raw messages",
+        }
+    }
+
+    /// The hex SHA-256 digest of the serialized payload, which identifies it in the audit log.
+    ///
+    /// Typed data that can't be encoded is digested as its JSON message instead.
+    pub fn digest(&self) -> String {
+        let payload = match self {
+            SignPayload::BitcoinPsbt(psbt) => psbt.to_bytes(),
+            SignPayload::EthereumTransaction(transaction) => transaction.signing_payload(),
+            SignPayload::EthereumTypedData(typed_data) => typed_data
+                .signing_payload()
+                .unwrap_or_else(|_| typed_data.message.to_string().into_bytes()),
+            SignPayload::RawMessage { message, .. } => message.clone(),
+        };
+        hex::encode(Sha256::digest(payload))
+    }
+}
+
+/// The total value of the outputs of `psbt` that are not sent to one of `change_scripts`.
+pub(crate) fn bitcoin_spend(psbt: &Psbt, change_scripts: &BTreeSet<Vec<u8>>) -> u128 {
+    psbt.unsigned_transaction
+        .outputs
+        .iter()
+        .filter(|output| !change_scripts.contains(&output.script_pubkey))
+        .map(|output| u128::from(output.value))
+        .sum()
+}
+
+/// The fee of `psbt`: the value of the outputs it spends, minus the value of its outputs.
+pub(crate) fn bitcoin_fee(psbt: &Psbt) -> Result<u128, String> {
+    let spent = (0..psbt.unsigned_transaction.inputs.len())
+        .map(|index| {
+            psbt.spent_output(index)
+                .map(|output| u128::from(output.value))
+                .map_err(|e| e.to_string())
+        })
+        .sum::<Result<u128, _>>()?;
+    let sent: u128 = psbt
+        .unsigned_transaction
+        .outputs
+        .iter()
+        .map(|output| u128::from(output.value))
+        .sum();
+    spent
+        .checked_sub(sent)
+        .ok_or_else(|| format!("the outputs ({sent}) exceed the spent outputs ({spent})"))
+}
@@ -0,0 +1,61 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Signing a request only once the policy approves it.
+
+use crate::engine::PolicyEngine;
+use crate::error::{PolicyError, PolicyResult};
+use crate::request::{SignPayload, SignRequest};
+use dwallet_bitcoin_psbt::{Psbt, sign_psbt};
+use dwallet_ethereum_tx::{EthereumSignature, sign_transaction, sign_typed_data};
+use dwallet_mpc_centralized_party::sign_session::{
+    DWalletSigningSession, DWalletUserKeys, sign_with_dwallet,
+};
+
+/// The result of signing a [`SignPayload`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignedPayload {
+    /// The PSBT, with the inputs of the dWallet signed and finalized.
+    BitcoinPsbt(Psbt),
+    /// The raw signed transaction, ready to broadcast.
+    EthereumTransaction(Vec<u8>),
+    EthereumTypedData(EthereumSignature),
+    /// The sign output of the network, as returned for a raw message.
+    RawMessage(Vec<u8>),
+}
+
+/// Authorizes `request` with `engine`, and only if it is approved, signs its payload.
+///
+/// `keys` must be the keys of the dWallet `request.dwallet_id`,
+/// which the policy engine can't check by itself.
+pub async fn sign_with_policy(
+    engine: &mut PolicyEngine,
+    request: &SignRequest,
+    now: u64,
+    keys: &DWalletUserKeys,
+    session: &mut impl DWalletSigningSession,
+) -> PolicyResult<SignedPayload> {
+    engine.authorize(request, now)?;
+
+    match &request.payload {
+        SignPayload::BitcoinPsbt(psbt) => {
+            let mut psbt = psbt.clone();
+            sign_psbt(&mut psbt, keys, session).await?;
+            Ok(SignedPayload::BitcoinPsbt(psbt))
+        }
+        SignPayload::EthereumTransaction(transaction) => Ok(SignedPayload::EthereumTransaction(
+            sign_transaction(transaction, keys, session).await?,
+        )),
+        SignPayload::EthereumTypedData(typed_data) => Ok(SignedPayload::EthereumTypedData(
+            sign_typed_data(typed_data, keys, session).await?,
+        )),
+        SignPayload::RawMessage { message, hash_type } => {
+            let sign_output = sign_with_dwallet(keys, session, message.clone(), *hash_type)
+                .await
+                .map_err(|e| PolicyError::Signing {
+                    error: format!("{e:#}"),
+                })?;
+            Ok(SignedPayload::RawMessage(sign_output))
+        }
+    }
+}
@@ -0,0 +1,389 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use dwallet_bitcoin_psbt::psbt::{KeyValueMap, PSBT_IN_WITNESS_UTXO};
+use dwallet_bitcoin_psbt::{OutPoint, Psbt, Transaction, TxIn, TxOut};
+use dwallet_ethereum_tx::{Eip1559Transaction, TypedData, TypedTransaction, parse_address};
+use dwallet_signing_policy::{
+    AuditDecision, AuditLog, PolicyEngine, PolicyError, PolicyViolation, SignPayload, SignRequest,
+    SigningPolicy,
+};
+use std::collections::BTreeSet;
+
+const DWALLET_ID: &str = "0x5a1f";
+const DESTINATION_SCRIPT: &str = "0014aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+const CHANGE_SCRIPT: &str = "0014bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+const RECIPIENT: &str = "0x3535353535353535353535353535353535353535";
+const VERIFYING_CONTRACT: &str = "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC";
+const NOW: u64 = 1_800_000_000;
+
+fn engine(dwallet_policy: &str) -> PolicyEngine {
+    let policy = SigningPolicy::from_json(&format!(
+        r#"{{ "dwallets": {{ "{DWALLET_ID}": {dwallet_policy} }} }}"#
+    ))
+    .unwrap();
+    PolicyEngine::new(policy).unwrap()
+}
+
+fn request(payload: SignPayload, approvers: &[&str]) -> SignRequest {
+    SignRequest {
+        request_id: "request".to_string(),
+        dwallet_id: DWALLET_ID.to_string(),
+        payload,
+        approvers: approvers.iter().map(ToString::to_string).collect(),
+    }
+}
+
+fn violations(result: Result<(), PolicyError>) -> Vec<PolicyViolation> {
+    match result {
+        Err(PolicyError::Rejected { violations }) => violations,
+        result => panic!("expected a rejection, got {result:?}"),
+    }
+}
+
+/// A PSBT spending a 100,000 sats P2WPKH output to `outputs`.
+fn psbt(outputs: &[(&str, u64)]) -> SignPayload {
+    let mut witness_utxo = 100_000u64.to_le_bytes().to_vec();
+    let spent_script = hex::decode(CHANGE_SCRIPT).unwrap();
+    witness_utxo.push(spent_script.len() as u8);
+    witness_utxo.extend(spent_script);
+
+    SignPayload::BitcoinPsbt(Psbt {
+        unsigned_transaction: Transaction {
+            version: 2,
+            inputs: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: [1; 32],
+                    vout: 0,
+                },
+                script_sig: vec![],
+                sequence: u32::MAX,
+                witness: vec![],
+            }],
+            outputs: outputs
+                .iter()
+                .map(|(script, value)| TxOut {
+                    value: *value,
+                    script_pubkey: hex::decode(script).unwrap(),
+                })
+                .collect(),
+            lock_time: 0,
+        },
+        global: KeyValueMap::new(),
+        inputs: vec![KeyValueMap::from([(
+            vec![PSBT_IN_WITNESS_UTXO],
+            witness_utxo,
+        )])],
+        outputs: vec![KeyValueMap::new(); outputs.len()],
+    })
+}
+
+fn ethereum_transaction(chain_id: u64, value: u128, data: Vec<u8>) -> SignPayload {
+    SignPayload::EthereumTransaction(TypedTransaction::Eip1559(Eip1559Transaction {
+        chain_id,
+        nonce: 0,
+        max_priority_fee_per_gas: 2_000_000_000,
+        max_fee_per_gas: 100_000_000_000,
+        gas_limit: 21_000,
+        to: Some(parse_address(RECIPIENT).unwrap()),
+        value,
+        data,
+        access_list: vec![],
+    }))
+}
+
+fn typed_data(verifying_contract: &str) -> SignPayload {
+    SignPayload::EthereumTypedData(
+        TypedData::from_json(&format!(
+            r#"{{
+                "types": {{
+                    "EIP712Domain": [
+                        {{ "name": "chainId", "type": "uint256" }},
+                        {{ "name": "verifyingContract", "type": "address" }}
+                    ],
+                    "Approval": [{{ "name": "amount", "type": "uint256" }}]
+                }},
+                "primaryType": "Approval",
+                "domain": {{ "chainId": "0x1", "verifyingContract": "{verifying_contract}" }},
+                "message": {{ "amount": 1 }}
+            }}"#
+        ))
+        .unwrap(),
+    )
+}
+
+#[test]
+fn unknown_dwallets_and_payloads_are_denied_by_default() {
+    let mut engine = engine("{}");
+
+    let mut unknown_dwallet = request(
+        SignPayload::RawMessage {
+            message: b"hello".to_vec(),
+            hash_type: 0,
+        },
+        &[],
+    );
+    unknown_dwallet.dwallet_id = "0xbeef".to_string();
+    assert_eq!(
+        violations(engine.authorize(&unknown_dwallet, NOW)),
+        vec![PolicyViolation::UnknownDWallet {
+            dwallet_id: "0xbeef".to_string()
+        }]
+    );
+
+    for payload in [
+        psbt(&[(DESTINATION_SCRIPT, 1_000)]),
+        ethereum_transaction(1, 1, vec![]),
+        typed_data(VERIFYING_CONTRACT),
+        SignPayload::RawMessage {
+            message: b"hello".to_vec(),
+            hash_type: 0,
+        },
+    ] {
+        let kind = payload.kind();
+        assert_eq!(
+            violations(engine.authorize(&request(payload, &[]), NOW)),
+            vec![PolicyViolation::PayloadNotAllowed { payload: kind }]
+        );
+    }
+    assert_eq!(engine.audit_log().records().len(), 5);
+}
+
+#[test]
+fn bitcoin_rules_are_enforced() {
+    let mut engine = engine(&format!(
+        r#"{{ "bitcoin": {{
+            "allowed_destination_scripts": ["{DESTINATION_SCRIPT}"],
+            "change_scripts": ["{CHANGE_SCRIPT}"],
+            "max_spend_sats": 50000,
+            "max_fee_sats": 1000
+        }} }}"#
+    ));
+
+    // Change is neither a destination nor spent.
+    let payload = psbt(&[(DESTINATION_SCRIPT, 40_000), (CHANGE_SCRIPT, 59_500)]);
+    engine.authorize(&request(payload, &[]), NOW).unwrap();
+
+    let other_script = "0014cccccccccccccccccccccccccccccccccccccccc";
+    let payload = psbt(&[
+        (DESTINATION_SCRIPT, 40_000),
+        (other_script, 20_000),
+        (CHANGE_SCRIPT, 30_000),
+    ]);
+    assert_eq!(
+        violations(engine.authorize(&request(payload, &[]), NOW)),
+        vec![
+            PolicyViolation::DestinationNotAllowed {
+                destination: other_script.to_string()
+            },
+            PolicyViolation::SpendLimitExceeded {
+                amount: 60_000,
+                limit: 50_000
+            },
+            PolicyViolation::FeeLimitExceeded {
+                fee: 10_000,
+                limit: 1_000
+            },
+        ]
+    );
+    let record = engine.audit_log().records().last().unwrap();
+    assert_eq!(record.bitcoin_spend_sats, 60_000);
+    assert!(!record.is_approved());
+}
+
+#[test]
+fn bitcoin_fee_limits_require_the_spent_outputs() {
+    let mut engine = engine(r#"{ "bitcoin": { "max_fee_sats": 1000 } }"#);
+    let SignPayload::BitcoinPsbt(mut psbt) = psbt(&[(DESTINATION_SCRIPT, 99_000)]) else {
+        unreachable!()
+    };
+    psbt.inputs[0].clear();
+
+    assert!(matches!(
+        violations(engine.authorize(&request(SignPayload::BitcoinPsbt(psbt), &[]), NOW))[..],
+        [PolicyViolation::UnknownFee { .. }]
+    ));
+}
+
+#[test]
+fn ethereum_transaction_rules_are_enforced() {
+    let mut engine = engine(&format!(
+        r#"{{ "ethereum": {{
+            "allowed_chain_ids": [1],
+            "allowed_recipients": ["{RECIPIENT}"],
+            "max_value_wei": 1000000000000000000,
+            "max_fee_per_gas_wei": 50000000000
+        }} }}"#
+    ));
+
+    assert_eq!(
+        violations(engine.authorize(
+            &request(
+                ethereum_transaction(5, 2_000_000_000_000_000_000, vec![0xa9]),
+                &[]
+            ),
+            NOW
+        )),
+        vec![
+            PolicyViolation::ChainIdNotAllowed { chain_id: 5 },
+            PolicyViolation::PayloadNotAllowed {
+                payload: "contract calls"
+            },
+            PolicyViolation::SpendLimitExceeded {
+                amount: 2_000_000_000_000_000_000,
+                limit: 1_000_000_000_000_000_000
+            },
+            PolicyViolation::FeeLimitExceeded {
+                fee: 100_000_000_000,
+                limit: 50_000_000_000
+            },
+        ]
+    );
+}
+
+#[test]
+fn typed_data_is_checked_against_its_domain() {
+    let mut engine = engine(&format!(
+        r#"{{ "ethereum": {{
+            "allow_typed_data": true,
+            "allowed_chain_ids": [1],
+            "allowed_verifying_contracts": ["{VERIFYING_CONTRACT}"]
+        }} }}"#
+    ));
+
+    engine
+        .authorize(&request(typed_data(VERIFYING_CONTRACT), &[]), NOW)
+        .unwrap();
+    assert_eq!(
+        violations(engine.authorize(&request(typed_data(RECIPIENT), &[]), NOW)),
+        vec![PolicyViolation::DestinationNotAllowed {
+            destination: RECIPIENT.to_string()
+        }]
+    );
+}
+
+#[test]
+fn time_locks_and_quorums_are_enforced() {
+    let mut engine = engine(&format!(
+        r#"{{
+            "not_before": {NOW},
+            "allow_raw_messages": true,
+            "quorum": {{ "approvers": ["alice", "bob", "carol"], "threshold": 2 }}
+        }}"#
+    ));
+    let raw_message = || SignPayload::RawMessage {
+        message: b"hello".to_vec(),
+        hash_type: 0,
+    };
+
+    // Approvers outside of the quorum don't count.
+    assert_eq!(
+        violations(engine.authorize(&request(raw_message(), &["alice", "mallory"]), NOW - 1)),
+        vec![
+            PolicyViolation::TimeLocked { not_before: NOW },
+            PolicyViolation::QuorumNotReached {
+                approvals: 1,
+                threshold: 2
+            },
+        ]
+    );
+    engine
+        .authorize(&request(raw_message(), &["alice", "carol"]), NOW)
+        .unwrap();
+}
+
+#[test]
+fn rate_limits_count_approved_requests_in_the_window() {
+    let mut engine = engine(&format!(
+        r#"{{
+            "bitcoin": {{ "change_scripts": ["{CHANGE_SCRIPT}"] }},
+            "rate_limits": [
+                {{ "window_seconds": 3600, "max_requests": 2 }},
+                {{ "window_seconds": 86400, "max_bitcoin_spend_sats": 100000 }}
+            ]
+        }}"#
+    ));
+    let spend = |value| request(psbt(&[(DESTINATION_SCRIPT, value)]), &[]);
+
+    engine.authorize(&spend(40_000), NOW).unwrap();
+    engine.authorize(&spend(40_000), NOW + 1).unwrap();
+    assert!(matches!(
+        violations(engine.authorize(&spend(1_000), NOW + 2))[..],
+        [PolicyViolation::RateLimitExceeded {
+            window_seconds: 3600,
+            ..
+        }]
+    ));
+
+    // Rejected requests don't count, and the hourly window has passed.
+    assert!(matches!(
+        violations(engine.authorize(&spend(40_000), NOW + 3600))[..],
+        [PolicyViolation::RateLimitExceeded {
+            window_seconds: 86400,
+            ..
+        }]
+    ));
+    engine.authorize(&spend(20_000), NOW + 3600).unwrap();
+}
+
+#[test]
+fn audit_log_is_hash_chained() {
+    let mut engine = engine(r#"{ "allow_raw_messages": true }"#);
+    let raw_message = SignPayload::RawMessage {
+        message: b"hello".to_vec(),
+        hash_type: 0,
+    };
+    engine
+        .authorize(&request(raw_message.clone(), &["alice"]), NOW)
+        .unwrap();
+    engine
+        .authorize(&request(psbt(&[(DESTINATION_SCRIPT, 1)]), &[]), NOW)
+        .unwrap_err();
+
+    let log = engine.audit_log();
+    log.verify_chain().unwrap();
+    let records = log.records();
+    assert_eq!(records[0].previous_record_digest, None);
+    assert_eq!(records[1].previous_record_digest, Some(records[0].digest()));
+    assert_eq!(records[0].payload_digest, raw_message.digest());
+    assert_eq!(records[0].approvers, BTreeSet::from(["alice".to_string()]));
+    assert!(matches!(
+        records[1].decision,
+        AuditDecision::Rejected { .. }
+    ));
+
+    let persisted = log.to_json_lines();
+    assert_eq!(&AuditLog::from_json_lines(&persisted).unwrap(), log);
+
+    // Editing a record breaks the chain.
+    let tampered = persisted.replacen(&format!("\"timestamp\":{NOW}"), "\"timestamp\":0", 1);
+    assert_eq!(
+        AuditLog::from_json_lines(&tampered),
+        Err(PolicyError::InvalidAuditLog {
+            error: "record 1 is not chained to its predecessor".to_string()
+        })
+    );
+    let tampered = persisted.lines().nth(1).unwrap();
+    assert!(matches!(
+        AuditLog::from_json_lines(tampered),
+        Err(PolicyError::InvalidAuditLog { .. })
+    ));
+}
+
+#[test]
+fn invalid_policies_are_rejected() {
+    for policy in [
+        r#"{ "quorum": { "approvers": ["alice"], "threshold": 2 } }"#,
+        r#"{ "bitcoin": { "change_scripts": ["not hex"] } }"#,
+        r#"{ "ethereum": { "allowed_recipients": ["0x35"] } }"#,
+        r#"{ "rate_limits": [{ "window_seconds": 0 }] }"#,
+        r#"{ "allow_everything": true }"#,
+    ] {
+        assert!(matches!(
+            SigningPolicy::from_json(&format!(
+                r#"{{ "dwallets": {{ "{DWALLET_ID}": {policy} }} }}"#
+            )),
+            Err(PolicyError::InvalidPolicy { .. })
+        ));
+    }
+}
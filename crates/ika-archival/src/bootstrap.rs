@@ -0,0 +1,158 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use crate::reader::ArchiveReader;
+use anyhow::{Result, anyhow};
+use ika_types::committee::{Committee, EpochId};
+use ika_types::storage::WriteStore;
+use tracing::info;
+
+/// What [`bootstrap_from_archive`] restored into the store.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ArchiveBootstrapSummary {
+    pub restored_epochs: Vec<EpochId>,
+    pub dwallet_checkpoints_restored: u64,
+    pub system_checkpoints_restored: u64,
+}
+
+/// Restores the committee store and the dWallet and system checkpoint stores from the archive,
+/// epoch by epoch, up to and including `up_to_epoch`.
+///
+/// Every checkpoint certificate is verified against the committee of its epoch, and the
+/// committees are verified as a chain: the bootstrap starts from a committee the store
+/// already trusts (the genesis committee, or the last committee a previous bootstrap restored),
+/// and the committee of every following epoch must be the one certified by the last system
+/// checkpoints of the epoch before it.
+/// The committee of `trusted_committee.epoch` (read from Sui by the node) must be certified
+/// the same way.
+/// If any committee can't be verified, the bootstrap stops with an error.
+///
+/// Restoring resumes from the highest synced checkpoints of the store, so an interrupted
+/// bootstrap can simply be restarted.
+pub async fn bootstrap_from_archive<S>(
+    archive_reader: &ArchiveReader,
+    store: S,
+    trusted_committee: &Committee,
+    up_to_epoch: EpochId,
+) -> Result<ArchiveBootstrapSummary>
+where
+    S: WriteStore + Clone,
+{
+    if up_to_epoch > trusted_committee.epoch {
+        return Err(anyhow!(
+            "Cannot bootstrap up to epoch {up_to_epoch}, the current epoch is {}",
+            trusted_committee.epoch
+        ));
+    }
+    archive_reader.sync_manifest_once().await?;
+    let archived_epoch = archive_reader.get_manifest().await?.epoch_num();
+    let up_to_epoch = up_to_epoch.min(archived_epoch);
+
+    let start_epoch = store
+        .get_highest_synced_dwallet_checkpoint()
+        .map_err(|e| anyhow!("Store op failed: {e}"))?
+        .map(|checkpoint| checkpoint.epoch)
+        .unwrap_or(0);
+    let mut committee = starting_committee(&store, trusted_committee, start_epoch)?;
+
+    let mut summary = ArchiveBootstrapSummary::default();
+    for epoch in start_epoch..=up_to_epoch {
+        if epoch > start_epoch {
+            committee =
+                next_epoch_committee(archive_reader, &store, trusted_committee, &committee).await?;
+        }
+        store
+            .insert_committee(committee.clone())
+            .map_err(|e| anyhow!("Failed to insert committee: {e}"))?;
+
+        let dwallet_checkpoints_restored = archive_reader
+            .read_verified_dwallet_checkpoints_for_epoch(store.clone(), epoch, &committee)
+            .await?;
+        let system_checkpoints_restored = archive_reader
+            .read_verified_system_checkpoints_for_epoch(store.clone(), epoch, &committee)
+            .await?;
+        info!(
+            epoch,
+            dwallet_checkpoints_restored,
+            system_checkpoints_restored,
+            "Restored epoch from archive"
+        );
+
+        summary.restored_epochs.push(epoch);
+        summary.dwallet_checkpoints_restored += dwallet_checkpoints_restored;
+        summary.system_checkpoints_restored += system_checkpoints_restored;
+    }
+
+    Ok(summary)
+}
+
+/// The committee of `start_epoch`, which must already be trusted.
+fn starting_committee<S>(
+    store: &S,
+    trusted_committee: &Committee,
+    start_epoch: EpochId,
+) -> Result<Committee>
+where
+    S: WriteStore,
+{
+    if start_epoch == trusted_committee.epoch {
+        return Ok(trusted_committee.clone());
+    }
+    store
+        .get_committee(start_epoch)
+        .map_err(|e| anyhow!("Store op failed: {e}"))?
+        .map(|committee| (*committee).clone())
+        .ok_or_else(|| {
+            anyhow!("No trusted committee of epoch {start_epoch} to start the bootstrap from")
+        })
+}
+
+/// The committee of the epoch that follows the epoch of `committee`, verified against the
+/// committee certified by the system checkpoints of that epoch, which were already restored
+/// and verified against `committee`.
+async fn next_epoch_committee<S>(
+    archive_reader: &ArchiveReader,
+    store: &S,
+    trusted_committee: &Committee,
+    committee: &Committee,
+) -> Result<Committee>
+where
+    S: WriteStore,
+{
+    let epoch = committee.epoch;
+    let next_epoch = epoch + 1;
+    let next_committee = if next_epoch == trusted_committee.epoch {
+        trusted_committee.clone()
+    } else {
+        archive_reader.get_committee(next_epoch).await?
+    };
+
+    // The committee is certified at the end of the epoch, so look for it from the last
+    // restored system checkpoint backwards.
+    let mut system_checkpoint = store
+        .get_highest_synced_system_checkpoint()
+        .map_err(|e| anyhow!("Store op failed: {e}"))?;
+    while let Some(checkpoint) = system_checkpoint {
+        if checkpoint.epoch != epoch {
+            break;
+        }
+        if checkpoint.has_next_epoch_committee() {
+            if !checkpoint.certifies_next_epoch_committee(&next_committee) {
+                return Err(anyhow!(
+                    "The committee of epoch {next_epoch} doesn't match the one certified in epoch {epoch}"
+                ));
+            }
+            return Ok(next_committee);
+        }
+        let Some(previous_sequence_number) = checkpoint.sequence_number.checked_sub(1) else {
+            break;
+        };
+        system_checkpoint = store
+            .get_system_checkpoint_by_sequence_number(previous_sequence_number)
+            .map_err(|e| anyhow!("Store op failed: {e}"))?;
+    }
+
+    Err(anyhow!(
+        "No certified committee of epoch {next_epoch} was found in the system checkpoints of epoch {epoch}"
+    ))
+}
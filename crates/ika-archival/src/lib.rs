@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: BSD-3-Clause-Clear
 #![allow(dead_code)]
 
+pub mod bootstrap;
 pub mod reader;
 pub mod writer;
 
@@ -12,12 +13,14 @@ use bytes::Bytes;
 use fastcrypto::hash::{HashFunction, Sha3_256};
 use ika_config::node::ArchiveReaderConfig;
 use ika_config::object_storage_config::ObjectStoreConfig;
+use ika_types::committee::{Committee, EpochId};
 use ika_types::storage::WriteStore;
 use indicatif::{ProgressBar, ProgressStyle};
 use num_enum::IntoPrimitive;
 use num_enum::TryFromPrimitive;
 use object_store::path::Path;
 use prometheus::Registry;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
@@ -43,12 +46,14 @@ use tracing::info;
 ///  - archive/
 ///     - MANIFEST
 ///     - epoch_0/
+///        - COMMITTEE
 ///        - 0.ika_checkpoint
 ///        - 1000.ika_checkpoint
 ///        - 3000.ika_checkpoint
 ///        - ...
 ///        - 100000.ika_checkpoint
 ///     - epoch_1/
+///        - COMMITTEE
 ///        - 101000.ika_checkpoint
 ///        - ...
 ///
@@ -81,14 +86,19 @@ use tracing::info;
 ///├──────────────────────────────┤
 ///│      sha3 <32 bytes>         │
 ///└──────────────────────────────┘
+///
+/// COMMITTEE files share the MANIFEST disk format, with their own magic,
+/// and hold the committee that certified the checkpoints of their epoch.
 pub const SYSTEM_CHECKPOINT_FILE_MAGIC: u32 = 0x0000C0DE;
 pub const DWALLET_CHECKPOINT_FILE_MAGIC: u32 = 0x00000DAD;
 const MANIFEST_FILE_MAGIC: u32 = 0x00C0FFEE;
+const COMMITTEE_FILE_MAGIC: u32 = 0x0000CAFE;
 const MAGIC_BYTES: usize = 4;
 const SYSTEM_CHECKPOINT_FILE_SUFFIX: &str = "system_checkpoint";
 const DWALLET_CHECKPOINT_FILE_SUFFIX: &str = "dwallet_checkpoint";
 const EPOCH_DIR_PREFIX: &str = "epoch_";
 const MANIFEST_FILENAME: &str = "MANIFEST";
+const COMMITTEE_FILENAME: &str = "COMMITTEE";
 
 #[derive(
     Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, TryFromPrimitive, IntoPrimitive,
@@ -300,38 +310,83 @@ pub async fn read_manifest<S: ObjectStoreGetExt>(remote_store: S) -> Result<Mani
 }
 
 pub fn read_manifest_from_bytes(vec: Vec<u8>) -> Result<Manifest> {
-    let manifest_file_size = vec.len();
-    let mut manifest_reader = Cursor::new(vec);
-    manifest_reader.rewind()?;
-    let magic = manifest_reader.read_u32::<BigEndian>()?;
-    if magic != MANIFEST_FILE_MAGIC {
-        return Err(anyhow!("Unexpected magic byte in manifest: {}", magic));
+    read_checksummed_blob_from_bytes(vec, MANIFEST_FILE_MAGIC, "Manifest")
+}
+
+pub fn finalize_manifest(manifest: Manifest) -> Result<Bytes> {
+    finalize_checksummed_blob(&manifest, MANIFEST_FILE_MAGIC)
+}
+
+pub fn committee_file_path(epoch: EpochId) -> Path {
+    Path::from(format!("{EPOCH_DIR_PREFIX}{epoch}")).child(COMMITTEE_FILENAME)
+}
+
+pub async fn read_committee<S: ObjectStoreGetExt>(
+    epoch: EpochId,
+    remote_store: S,
+) -> Result<Committee> {
+    let vec = get(&remote_store, &committee_file_path(epoch))
+        .await?
+        .to_vec();
+    let committee: Committee =
+        read_checksummed_blob_from_bytes(vec, COMMITTEE_FILE_MAGIC, "Committee")?;
+    if committee.epoch != epoch {
+        return Err(anyhow!(
+            "Unexpected committee epoch {} in the committee file of epoch {}",
+            committee.epoch,
+            epoch
+        ));
+    }
+    Ok(committee)
+}
+
+pub async fn write_committee<S: ObjectStorePutExt>(
+    committee: &Committee,
+    remote_store: S,
+) -> Result<()> {
+    let bytes = finalize_checksummed_blob(committee, COMMITTEE_FILE_MAGIC)?;
+    put(&remote_store, &committee_file_path(committee.epoch), bytes).await?;
+    Ok(())
+}
+
+fn read_checksummed_blob_from_bytes<T: DeserializeOwned>(
+    vec: Vec<u8>,
+    file_magic: u32,
+    name: &str,
+) -> Result<T> {
+    let file_size = vec.len();
+    let mut reader = Cursor::new(vec);
+    reader.rewind()?;
+    let magic = reader.read_u32::<BigEndian>()?;
+    if magic != file_magic {
+        return Err(anyhow!("Unexpected magic byte in {}: {}", name, magic));
     }
-    manifest_reader.seek(SeekFrom::End(-(SHA3_BYTES as i64)))?;
+    reader.seek(SeekFrom::End(-(SHA3_BYTES as i64)))?;
     let mut sha3_digest = [0u8; SHA3_BYTES];
-    manifest_reader.read_exact(&mut sha3_digest)?;
-    manifest_reader.rewind()?;
-    let mut content_buf = vec![0u8; manifest_file_size - SHA3_BYTES];
-    manifest_reader.read_exact(&mut content_buf)?;
+    reader.read_exact(&mut sha3_digest)?;
+    reader.rewind()?;
+    let mut content_buf = vec![0u8; file_size - SHA3_BYTES];
+    reader.read_exact(&mut content_buf)?;
     let mut hasher = Sha3_256::default();
     hasher.update(&content_buf);
     let computed_digest = hasher.finalize().digest;
     if computed_digest != sha3_digest {
         return Err(anyhow!(
-            "Manifest corrupted, computed checksum: {:?}, stored checksum: {:?}",
+            "{} corrupted, computed checksum: {:?}, stored checksum: {:?}",
+            name,
             computed_digest,
             sha3_digest
         ));
     }
-    manifest_reader.rewind()?;
-    manifest_reader.seek(SeekFrom::Start(MAGIC_BYTES as u64))?;
-    Blob::read(&mut manifest_reader)?.decode()
+    reader.rewind()?;
+    reader.seek(SeekFrom::Start(MAGIC_BYTES as u64))?;
+    Blob::read(&mut reader)?.decode()
 }
 
-pub fn finalize_manifest(manifest: Manifest) -> Result<Bytes> {
+fn finalize_checksummed_blob<T: Serialize>(value: &T, file_magic: u32) -> Result<Bytes> {
     let mut buf = BufWriter::new(vec![]);
-    buf.write_u32::<BigEndian>(MANIFEST_FILE_MAGIC)?;
-    let blob = Blob::encode(&manifest, BlobEncoding::Bcs)?;
+    buf.write_u32::<BigEndian>(file_magic)?;
+    let blob = Blob::encode(value, BlobEncoding::Bcs)?;
     blob.write(&mut buf)?;
     buf.flush()?;
     let mut hasher = Sha3_256::default();
@@ -3,13 +3,14 @@
 
 use crate::{
     DWALLET_CHECKPOINT_FILE_MAGIC, FileMetadata, FileType, Manifest, SYSTEM_CHECKPOINT_FILE_MAGIC,
-    read_manifest,
+    read_committee, read_manifest,
 };
 use anyhow::{Context, Result, anyhow};
 use bytes::buf::Reader;
 use bytes::{Buf, Bytes};
 use futures::{StreamExt, TryStreamExt};
 use ika_config::node::ArchiveReaderConfig;
+use ika_types::committee::{Committee, EpochId};
 use ika_types::messages_dwallet_checkpoint::{
     CertifiedDWalletCheckpointMessage, DWalletCheckpointSequenceNumber,
     VerifiedDWalletCheckpointMessage,
@@ -80,10 +81,17 @@ pub struct ArchiveReaderBalancer {
 
 impl ArchiveReaderBalancer {
     pub fn new(configs: Vec<ArchiveReaderConfig>, registry: &Registry) -> Result<Self> {
+        Self::new_with_metrics(configs, &ArchiveReaderMetrics::new(registry))
+    }
+    /// Like [`Self::new`], for when the metrics are already registered, e.g. because
+    /// they are shared with another archive reader.
+    pub fn new_with_metrics(
+        configs: Vec<ArchiveReaderConfig>,
+        metrics: &Arc<ArchiveReaderMetrics>,
+    ) -> Result<Self> {
         let mut readers = vec![];
-        let metrics = ArchiveReaderMetrics::new(registry);
         for config in configs.into_iter() {
-            readers.push(Arc::new(ArchiveReader::new(config.clone(), &metrics)?));
+            readers.push(Arc::new(ArchiveReader::new(config.clone(), metrics)?));
         }
         Ok(ArchiveReaderBalancer { readers })
     }
@@ -532,6 +540,210 @@ impl ArchiveReader {
         Ok(self.manifest.lock().await.clone())
     }
 
+    /// Returns the committee that certified the checkpoints of `epoch`.
+    pub async fn get_committee(&self, epoch: EpochId) -> Result<Committee> {
+        read_committee(epoch, self.remote_object_store.clone()).await
+    }
+
+    /// Load the dWallet checkpoints of `epoch` from archive into the input store `S`,
+    /// verifying every checkpoint certificate against `committee`.
+    /// Checkpoints the store has already synced are skipped.
+    /// Returns the number of checkpoints inserted.
+    pub async fn read_verified_dwallet_checkpoints_for_epoch<S>(
+        &self,
+        store: S,
+        epoch: EpochId,
+        committee: &Committee,
+    ) -> Result<u64>
+    where
+        S: WriteStore + Clone,
+    {
+        let mut next_sequence_number = store
+            .get_highest_synced_dwallet_checkpoint()
+            .map_err(|e| anyhow!("Store op failed: {e}"))?
+            .map(|c| c.sequence_number + 1)
+            .unwrap_or(0);
+        let mut inserted = 0;
+        for file in self
+            .get_files_for_epoch(FileType::DWalletCheckpointMessage, epoch)
+            .await?
+        {
+            if file.checkpoint_seq_range.end <= next_sequence_number {
+                continue;
+            }
+            let checkpoint_data = self.get_verified_file(&file).await?;
+            for checkpoint in make_iterator::<CertifiedDWalletCheckpointMessage, Reader<Bytes>>(
+                DWALLET_CHECKPOINT_FILE_MAGIC,
+                checkpoint_data.reader(),
+            )? {
+                if checkpoint.sequence_number < next_sequence_number {
+                    continue;
+                }
+                if checkpoint.sequence_number != next_sequence_number {
+                    return Err(anyhow!(
+                        "Missing dwallet checkpoint {next_sequence_number} in archive, found {}",
+                        checkpoint.sequence_number
+                    ));
+                }
+                let size = checkpoint.messages.len();
+                let verified_checkpoint = checkpoint.try_into_verified(committee).map_err(|e| {
+                    anyhow!("Failed to verify dwallet checkpoint {next_sequence_number}: {e}")
+                })?;
+                store
+                    .insert_dwallet_checkpoint(&verified_checkpoint)
+                    .map_err(|e| anyhow!("Failed to insert checkpoint: {e}"))?;
+                store
+                    .update_highest_verified_dwallet_checkpoint(&verified_checkpoint)
+                    .map_err(|e| anyhow!("Failed to update watermark: {e}"))?;
+                store
+                    .update_highest_synced_dwallet_checkpoint(&verified_checkpoint)
+                    .map_err(|e| anyhow!("Failed to update watermark: {e}"))?;
+                self.archive_reader_metrics
+                    .archive_actions_read
+                    .with_label_values(&[&self.bucket])
+                    .inc_by(size as u64);
+                self.archive_reader_metrics
+                    .archive_dwallet_checkpoints_read
+                    .with_label_values(&[&self.bucket])
+                    .inc_by(1);
+                next_sequence_number += 1;
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Load the system checkpoints of `epoch` from archive into the input store `S`,
+    /// verifying every checkpoint certificate against `committee`.
+    /// System checkpoints the store has already synced are skipped.
+    /// Returns the number of system checkpoints inserted.
+    pub async fn read_verified_system_checkpoints_for_epoch<S>(
+        &self,
+        store: S,
+        epoch: EpochId,
+        committee: &Committee,
+    ) -> Result<u64>
+    where
+        S: WriteStore + Clone,
+    {
+        let mut next_sequence_number = store
+            .get_highest_synced_system_checkpoint()
+            .map_err(|e| anyhow!("Store op failed: {e}"))?
+            .map(|c| c.sequence_number + 1)
+            .unwrap_or(0);
+        let mut inserted = 0;
+        for file in self
+            .get_files_for_epoch(FileType::SystemCheckpointMessage, epoch)
+            .await?
+        {
+            if file.checkpoint_seq_range.end <= next_sequence_number {
+                continue;
+            }
+            let system_checkpoint_data = self.get_verified_file(&file).await?;
+            for system_checkpoint in make_iterator::<CertifiedSystemCheckpointMessage, Reader<Bytes>>(
+                SYSTEM_CHECKPOINT_FILE_MAGIC,
+                system_checkpoint_data.reader(),
+            )? {
+                if system_checkpoint.sequence_number < next_sequence_number {
+                    continue;
+                }
+                if system_checkpoint.sequence_number != next_sequence_number {
+                    return Err(anyhow!(
+                        "Missing system checkpoint {next_sequence_number} in archive, found {}",
+                        system_checkpoint.sequence_number
+                    ));
+                }
+                let size = system_checkpoint.messages.len();
+                let verified_system_checkpoint =
+                    system_checkpoint.try_into_verified(committee).map_err(|e| {
+                        anyhow!("Failed to verify system checkpoint {next_sequence_number}: {e}")
+                    })?;
+                store
+                    .insert_system_checkpoint(&verified_system_checkpoint)
+                    .map_err(|e| anyhow!("Failed to insert system_checkpoint: {e}"))?;
+                store
+                    .update_highest_verified_system_checkpoint(&verified_system_checkpoint)
+                    .map_err(|e| anyhow!("Failed to update watermark: {e}"))?;
+                store
+                    .update_highest_synced_system_checkpoint(&verified_system_checkpoint)
+                    .map_err(|e| anyhow!("Failed to update watermark: {e}"))?;
+                self.archive_reader_metrics
+                    .archive_actions_read
+                    .with_label_values(&[&self.bucket])
+                    .inc_by(size as u64);
+                self.archive_reader_metrics
+                    .archive_system_checkpoints_read
+                    .with_label_values(&[&self.bucket])
+                    .inc_by(1);
+                next_sequence_number += 1;
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Returns the dWallet checkpoints of the given range from the archive, ordered by their
+    /// sequence number, without verifying their certificates.
+    /// Each file is downloaded once and its checksum is checked against the manifest.
+    pub async fn get_dwallet_checkpoints_for_range_no_verify(
+        &self,
+        checkpoint_range: Range<DWalletCheckpointSequenceNumber>,
+    ) -> Result<Vec<CertifiedDWalletCheckpointMessage>> {
+        let manifest = self.manifest.lock().await.clone();
+        let mut files: Vec<_> = manifest
+            .files()
+            .into_iter()
+            .filter(|f| {
+                f.file_type == FileType::DWalletCheckpointMessage
+                    && f.checkpoint_seq_range.start < checkpoint_range.end
+                    && checkpoint_range.start < f.checkpoint_seq_range.end
+            })
+            .collect();
+        files.sort_by_key(|f| f.checkpoint_seq_range.start);
+
+        let mut checkpoints = vec![];
+        for file in files {
+            let checkpoint_data = self.get_verified_file(&file).await?;
+            checkpoints.extend(
+                make_iterator::<CertifiedDWalletCheckpointMessage, Reader<Bytes>>(
+                    DWALLET_CHECKPOINT_FILE_MAGIC,
+                    checkpoint_data.reader(),
+                )?
+                .filter(|c| checkpoint_range.contains(&c.sequence_number)),
+            );
+        }
+        Ok(checkpoints)
+    }
+
+    /// Returns the files of `file_type` written in `epoch`, ordered by their checkpoint range.
+    async fn get_files_for_epoch(
+        &self,
+        file_type: FileType,
+        epoch: EpochId,
+    ) -> Result<Vec<FileMetadata>> {
+        let manifest = self.manifest.lock().await.clone();
+        let mut files: Vec<_> = manifest
+            .files()
+            .into_iter()
+            .filter(|f| f.file_type == file_type && f.epoch_num == epoch)
+            .collect();
+        files.sort_by_key(|f| f.checkpoint_seq_range.start);
+        Ok(files)
+    }
+
+    /// Downloads a file and ensures its checksum matches the one in the manifest.
+    async fn get_verified_file(&self, file: &FileMetadata) -> Result<Bytes> {
+        let data = get(&self.remote_object_store, &file.file_path()).await?;
+        let checksum = compute_sha3_checksum_for_bytes(data.clone())?;
+        if checksum != file.sha3_digest {
+            return Err(anyhow!(
+                "Checkpoint checksum doesn't match for file: {:?}",
+                file.file_path()
+            ));
+        }
+        Ok(data)
+    }
+
     async fn sync_manifest(
         remote_store: Arc<dyn ObjectStoreGetExt>,
         manifest: Arc<Mutex<Manifest>>,
@@ -6,12 +6,13 @@ use crate::{
     CheckpointUpdates, DWALLET_CHECKPOINT_FILE_MAGIC, DWALLET_CHECKPOINT_FILE_SUFFIX,
     EPOCH_DIR_PREFIX, FileMetadata, FileType, MAGIC_BYTES, Manifest, SYSTEM_CHECKPOINT_FILE_MAGIC,
     SYSTEM_CHECKPOINT_FILE_SUFFIX, SystemCheckpointUpdates, create_file_metadata, read_manifest,
-    write_manifest,
+    write_committee, write_manifest,
 };
 use anyhow::Result;
 use anyhow::{Context, anyhow};
 use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
 use ika_config::object_storage_config::ObjectStoreConfig;
+use ika_types::committee::EpochId;
 use ika_types::messages_dwallet_checkpoint::{
     CertifiedDWalletCheckpointMessage, DWalletCheckpointSequenceNumber,
 };
@@ -579,6 +580,16 @@ impl ArchiveWriter {
             .await
         });
 
+        let start_committee_epoch = manifest.epoch_num();
+        let store_clone = store.clone();
+        let kill_receiver_clone = kill_receiver.resubscribe();
+        tokio::task::spawn(Self::start_tailing_committees(
+            start_committee_epoch,
+            self.remote_object_store.clone(),
+            store_clone,
+            kill_receiver_clone,
+        ));
+
         let start_system_checkpoint_sequence_number = manifest.next_system_checkpoint_seq_num();
         let system_checkpoint_writer = SystemCheckpointWriter::new(
             self.local_staging_dir_root.clone(),
@@ -634,6 +645,37 @@ impl ArchiveWriter {
         Ok(())
     }
 
+    /// Uploads the committee of every epoch to the remote store, so that the archived
+    /// checkpoints can be verified by whoever reads them.
+    async fn start_tailing_committees<S>(
+        start_epoch: EpochId,
+        remote_object_store: Arc<DynObjectStore>,
+        store: S,
+        mut kill: tokio::sync::broadcast::Receiver<()>,
+    ) -> Result<()>
+    where
+        S: WriteStore + Send + Sync + 'static,
+    {
+        let mut epoch = start_epoch;
+        info!("Starting committee tailing from epoch: {epoch}");
+
+        while kill.try_recv().is_err() {
+            if let Some(committee) = store
+                .get_committee(epoch)
+                .map_err(|_| anyhow!("Failed to read committee from store"))?
+            {
+                write_committee(&committee, remote_object_store.clone()).await?;
+                epoch = epoch.checked_add(1).context("epoch overflow")?;
+                // There may be more committees to tail, so continue without sleeping
+                continue;
+            }
+            // The committee of `epoch` is not available to read from store yet,
+            // sleep for sometime and then retry
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        }
+        Ok(())
+    }
+
     async fn start_tailing_system_checkpoints<S>(
         start_system_checkpoint_sequence_number: SystemCheckpointSequenceNumber,
         mut system_checkpoint_writer: SystemCheckpointWriter,
@@ -155,6 +155,27 @@ pub struct NodeConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub run_with_range: Option<RunWithRange>,
+
+    /// When set, the node periodically derives a recommended pricing vote from the measured
+    /// cost of its MPC computations and writes it to disk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pricing_recommendation_config: Option<PricingRecommendationConfig>,
+
+    /// When set, the node restores its checkpoint stores and committee store from the given
+    /// archive on startup, before joining p2p state sync.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_bootstrap_config: Option<ArchiveBootstrapConfig>,
+
+    /// When set, the node indexes the outputs of certified dWallet checkpoints into a local
+    /// database and serves them over HTTP.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkpoint_output_index_config: Option<CheckpointOutputIndexConfig>,
+
+    /// When set, the root seed and the network decryption key shares are held by a separate
+    /// `ika-key-custody` process, which performs all MPC computations on behalf of the node.
+    /// `root_seed_key_pair` must not be set together with it. Only supported on Unix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_custody_config: Option<KeyCustodyConfig>,
 }
 
 fn default_sui_rpc_url() -> String {
@@ -230,10 +251,38 @@ impl NodeConfig {
         self.db_path.join("archive")
     }
 
+    pub fn recommended_pricing_path(&self) -> Option<PathBuf> {
+        self.pricing_recommendation_config.as_ref().map(|config| {
+            config
+                .output_path
+                .clone()
+                .unwrap_or_else(|| self.db_path.join("recommended_pricing.yaml"))
+        })
+    }
+
+    pub fn archive_bootstrap_reader_config(&self) -> Option<ArchiveReaderConfig> {
+        self.archive_bootstrap_config
+            .as_ref()
+            .map(|config| ArchiveReaderConfig {
+                remote_store_config: config.object_store_config.clone(),
+                download_concurrency: NonZeroUsize::new(config.concurrency)
+                    .unwrap_or(NonZeroUsize::new(5).unwrap()),
+                use_for_pruning_watermark: false,
+            })
+    }
+
+    pub fn checkpoint_output_index_path(&self) -> PathBuf {
+        self.db_path.join("checkpoint_output_index")
+    }
+
     pub fn snapshot_path(&self) -> PathBuf {
         self.db_path.join("snapshot")
     }
 
+    pub fn network_key_cache_path(&self) -> PathBuf {
+        self.db_path.join("network_key_cache")
+    }
+
     pub fn network_address(&self) -> &Multiaddr {
         &self.network_address
     }
@@ -330,6 +379,69 @@ pub struct MetricsConfig {
     pub push_url: Option<String>,
 }
 
+/// Parameters for turning the measured cost of MPC computations into a recommended pricing vote.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PricingRecommendationConfig {
+    /// The IKA fee charged for every millisecond of measured computation time of a session.
+    pub ika_per_computation_ms: u64,
+    /// The IKA fee charged for every kilobyte of MPC messages received during a session.
+    pub ika_per_message_kilobyte: u64,
+    /// The lowest `fee_ika` that will ever be recommended for a protocol.
+    #[serde(default)]
+    pub minimum_fee_ika: u64,
+    /// How often the node recomputes the recommendation, at least every second.
+    #[serde(default = "default_pricing_recommendation_interval_secs")]
+    pub interval_secs: u64,
+    /// The file the recommended pricing is written to.
+    /// Defaults to `recommended_pricing.yaml` under the node's DB path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<PathBuf>,
+}
+
+fn default_pricing_recommendation_interval_secs() -> u64 {
+    // 1 hour.
+    3_600
+}
+
+impl PricingRecommendationConfig {
+    /// The interval between recommendations, where an `interval_secs` of 0 is taken as 1.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ArchiveBootstrapConfig {
+    /// The archive to bootstrap from, either a local directory or a remote object store.
+    pub object_store_config: ObjectStoreConfig,
+    #[serde(default)]
+    pub concurrency: usize,
+    /// The last epoch to restore from the archive, defaults to the current epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up_to_epoch: Option<EpochId>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CheckpointOutputIndexConfig {
+    /// The address the checkpoint output query API listens on.
+    #[serde(default = "default_checkpoint_output_index_listen_address")]
+    pub listen_address: SocketAddr,
+}
+
+fn default_checkpoint_output_index_listen_address() -> SocketAddr {
+    "127.0.0.1:9187".parse().unwrap()
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct KeyCustodyConfig {
+    /// The Unix socket the `ika-key-custody` process listens on.
+    pub socket_path: PathBuf,
+}
+
 #[derive(Debug, Clone)]
 pub struct ArchiveReaderConfig {
     pub remote_store_config: ObjectStoreConfig,
@@ -17,7 +17,7 @@ use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use sui_types::base_types::{EpochId, ObjectID};
-use tracing::{debug, info, instrument, trace, warn};
+use tracing::{debug, error, info, instrument, trace, warn};
 use typed_store::rocks::{DBBatch, DBMap, DBOptions, MetricConf, default_db_options};
 use typed_store::rocksdb::Options;
 
@@ -269,6 +269,10 @@ pub struct AuthorityPerEpochStore {
     pub(crate) packages_config: IkaNetworkConfig,
     reconfig_state: RwLock<ReconfigState>,
     end_of_publish: Mutex<StakeAggregator<(), true>>,
+
+    /// The committee of the next epoch, once it was locked on Sui,
+    /// to be certified in the last system checkpoint of this epoch.
+    next_epoch_committee: RwLock<Option<Committee>>,
 }
 
 /// The reconfiguration state of the authority.
@@ -548,6 +552,7 @@ impl AuthorityPerEpochStore {
                 status: ReconfigCertStatus::AcceptAllCerts,
             }),
             end_of_publish: Mutex::new(end_of_publish),
+            next_epoch_committee: RwLock::new(None),
         });
 
         s.update_buffer_stake_metric();
@@ -651,6 +656,20 @@ impl AuthorityPerEpochStore {
         &self.protocol_config
     }
 
+    pub fn next_epoch_committee(&self) -> Option<Committee> {
+        self.next_epoch_committee.read().clone()
+    }
+
+    /// Records the committee of the next epoch, which is locked on Sui mid-epoch,
+    /// long before the end of publish of this epoch.
+    /// Once recorded, it is never replaced.
+    pub fn set_next_epoch_committee(&self, committee: &Committee) {
+        let mut next_epoch_committee = self.next_epoch_committee.write();
+        if next_epoch_committee.is_none() {
+            *next_epoch_committee = Some(committee.clone());
+        }
+    }
+
     pub fn epoch(&self) -> EpochId {
         self.committee.epoch
     }
@@ -1192,8 +1211,33 @@ impl AuthorityPerEpochStore {
                         }
                     }
                     verified_system_checkpoint_certificates.extend(system_transactions);
+                    // Remove the partial user signatures that can no longer be completed
+                    // before the epoch ends.
+                    if self.protocol_config.garbage_collect_future_signs() {
+                        verified_dwallet_checkpoint_certificates.push_back(
+                            DWalletCheckpointMessageKind::GarbageCollectFutureSigns(self.epoch()),
+                        );
+                    }
                     verified_dwallet_checkpoint_certificates
                         .push_back(DWalletCheckpointMessageKind::EndOfPublish);
+                    if self.protocol_config.certify_next_epoch_committee() {
+                        match self.next_epoch_committee() {
+                            Some(next_epoch_committee) => {
+                                verified_system_checkpoint_certificates.push_back(
+                                    SystemCheckpointMessageKind::new_set_next_epoch_committee(
+                                        &next_epoch_committee,
+                                    ),
+                                );
+                            }
+                            None => {
+                                error!(
+                                    should_never_happen = true,
+                                    epoch=?self.epoch(),
+                                    "the next epoch committee is not known at the end of publish"
+                                );
+                            }
+                        }
+                    }
                     verified_system_checkpoint_certificates
                         .push_back(SystemCheckpointMessageKind::EndOfPublish);
                     let mut reconfig_state = self.reconfig_state.write();
@@ -4,6 +4,7 @@
 use ika_types::sui::EpochStartSystemTrait;
 mod dwallet_checkpoint_metrics;
 mod dwallet_checkpoint_output;
+pub mod output_index;
 
 use std::collections::HashMap;
 
@@ -0,0 +1,443 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! A local index of the outputs carried by certified dWallet checkpoints.
+//!
+//! Every [`DWalletCheckpointMessageKind`] of a certified checkpoint is decomposed into
+//! queryable tables: by dWallet ID, by session sequence number, and by network
+//! encryption key ID. The index tails the [`DWalletCheckpointStore`], which is filled by
+//! both p2p state sync and archive bootstrap, so it covers checkpoints from either source.
+
+use crate::dwallet_checkpoints::DWalletCheckpointStore;
+use ika_types::committee::EpochId;
+use ika_types::message::DWalletCheckpointMessageKind;
+use ika_types::messages_dwallet_checkpoint::{
+    DWalletCheckpointSequenceNumber, VerifiedDWalletCheckpointMessage,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use typed_store::DBMapUtils;
+use typed_store::Map;
+use typed_store::{
+    TypedStoreError,
+    rocks::{DBMap, MetricConf},
+};
+
+/// The position of a message in the certified dWallet checkpoints.
+pub type OutputPosition = (DWalletCheckpointSequenceNumber, u32);
+
+/// How many one second retries of a checkpoint missing from the store to make between warnings.
+const MISSING_CHECKPOINT_WARN_INTERVAL: u64 = 60;
+
+/// A single checkpoint message, along with where it was certified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedOutput {
+    pub epoch: EpochId,
+    pub checkpoint_sequence_number: DWalletCheckpointSequenceNumber,
+    pub index_in_checkpoint: u32,
+    pub kind: String,
+    pub message: DWalletCheckpointMessageKind,
+}
+
+#[derive(DBMapUtils)]
+pub struct DWalletCheckpointOutputIndex {
+    /// Maps (dWallet ID, position) to the outputs of sessions of that dWallet.
+    outputs_by_dwallet: DBMap<(Vec<u8>, OutputPosition), IndexedOutput>,
+    /// Maps (session sequence number, position) to the outputs of that session.
+    outputs_by_session: DBMap<(u64, OutputPosition), IndexedOutput>,
+    /// Maps (network encryption key ID, position) to the network DKG and reconfiguration outputs.
+    outputs_by_network_key: DBMap<(Vec<u8>, OutputPosition), IndexedOutput>,
+    /// The highest dWallet checkpoint that was fully indexed.
+    highest_indexed_checkpoint: DBMap<(), DWalletCheckpointSequenceNumber>,
+}
+
+impl DWalletCheckpointOutputIndex {
+    pub fn new(path: &Path) -> Arc<Self> {
+        Arc::new(Self::open_tables_read_write(
+            path.to_path_buf(),
+            MetricConf::new("dwallet_checkpoint_output_index"),
+            None,
+            None,
+        ))
+    }
+
+    pub fn get_highest_indexed_checkpoint(
+        &self,
+    ) -> Result<Option<DWalletCheckpointSequenceNumber>, TypedStoreError> {
+        self.highest_indexed_checkpoint.get(&())
+    }
+
+    /// Indexes all the messages of `checkpoint` and advances the watermark, atomically.
+    pub fn index_checkpoint(
+        &self,
+        checkpoint: &VerifiedDWalletCheckpointMessage,
+    ) -> Result<(), TypedStoreError> {
+        let mut batch = self.outputs_by_session.batch();
+        for (index_in_checkpoint, message) in checkpoint.messages.iter().enumerate() {
+            let output = IndexedOutput {
+                epoch: checkpoint.epoch,
+                checkpoint_sequence_number: checkpoint.sequence_number,
+                index_in_checkpoint: index_in_checkpoint as u32,
+                kind: message.name().to_string(),
+                message: message.clone(),
+            };
+            let position = (checkpoint.sequence_number, index_in_checkpoint as u32);
+            if let Some(dwallet_id) = dwallet_id(message) {
+                batch.insert_batch(
+                    &self.outputs_by_dwallet,
+                    [((dwallet_id.to_vec(), position), output.clone())],
+                )?;
+            }
+            if let Some(network_key_id) = network_encryption_key_id(message) {
+                batch.insert_batch(
+                    &self.outputs_by_network_key,
+                    [((network_key_id.to_vec(), position), output.clone())],
+                )?;
+            }
+            if let Some(session_sequence_number) = session_sequence_number(message) {
+                batch.insert_batch(
+                    &self.outputs_by_session,
+                    [((session_sequence_number, position), output)],
+                )?;
+            }
+        }
+        batch.insert_batch(
+            &self.highest_indexed_checkpoint,
+            [((), checkpoint.sequence_number)],
+        )?;
+        batch.write()
+    }
+
+    /// Returns the outputs of the sessions of `dwallet_id`, optionally only those of kind `kind`.
+    pub fn get_dwallet_outputs(
+        &self,
+        dwallet_id: &[u8],
+        kind: Option<&str>,
+    ) -> Result<Vec<IndexedOutput>, TypedStoreError> {
+        let mut outputs = vec![];
+        for item in self
+            .outputs_by_dwallet
+            .safe_iter_with_bounds(Some((dwallet_id.to_vec(), (0, 0))), None)
+        {
+            let ((id, _), output) = item?;
+            if id != dwallet_id {
+                break;
+            }
+            if kind.is_none_or(|kind| output.kind == kind) {
+                outputs.push(output);
+            }
+        }
+        Ok(outputs)
+    }
+
+    pub fn get_session_outputs(
+        &self,
+        session_sequence_number: u64,
+    ) -> Result<Vec<IndexedOutput>, TypedStoreError> {
+        let mut outputs = vec![];
+        for item in self
+            .outputs_by_session
+            .safe_iter_with_bounds(Some((session_sequence_number, (0, 0))), None)
+        {
+            let ((sequence_number, _), output) = item?;
+            if sequence_number != session_sequence_number {
+                break;
+            }
+            outputs.push(output);
+        }
+        Ok(outputs)
+    }
+
+    pub fn get_network_key_outputs(
+        &self,
+        network_key_id: &[u8],
+    ) -> Result<Vec<IndexedOutput>, TypedStoreError> {
+        let mut outputs = vec![];
+        for item in self
+            .outputs_by_network_key
+            .safe_iter_with_bounds(Some((network_key_id.to_vec(), (0, 0))), None)
+        {
+            let ((id, _), output) = item?;
+            if id != network_key_id {
+                break;
+            }
+            outputs.push(output);
+        }
+        Ok(outputs)
+    }
+
+    /// Indexes the certified checkpoints of `checkpoint_store` as they become available,
+    /// resuming after the highest indexed checkpoint.
+    ///
+    /// Checkpoints are indexed strictly in order: a checkpoint missing from the store while
+    /// later ones are already in it, e.g. one still being synced, is waited for and retried,
+    /// never skipped, so the index doesn't silently lose its outputs.
+    pub async fn run(self: Arc<Self>, checkpoint_store: Arc<DWalletCheckpointStore>) {
+        let mut next_sequence_number = match self.get_highest_indexed_checkpoint() {
+            Ok(highest_indexed) => highest_indexed.map_or(0, |sequence_number| sequence_number + 1),
+            Err(err) => {
+                error!(error=?err, "failed to read the highest indexed dwallet checkpoint");
+                return;
+            }
+        };
+        info!(
+            next_sequence_number,
+            "starting the dwallet checkpoint output index"
+        );
+        let mut missing_checkpoint_retries = 0u64;
+        loop {
+            let checkpoint =
+                checkpoint_store.get_dwallet_checkpoint_by_sequence_number(next_sequence_number);
+            match checkpoint {
+                Ok(Some(checkpoint)) => {
+                    if let Err(err) = self.index_checkpoint(&checkpoint) {
+                        error!(
+                            error=?err,
+                            checkpoint_seq=next_sequence_number,
+                            "failed to index dwallet checkpoint"
+                        );
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    }
+                    next_sequence_number += 1;
+                    missing_checkpoint_retries = 0;
+                }
+                Ok(None) => {
+                    // The checkpoint is not certified yet, or is certified but not in the store yet.
+                    if missing_checkpoint_retries % MISSING_CHECKPOINT_WARN_INTERVAL == 0
+                        && let Ok(Some(latest)) = checkpoint_store.get_latest_certified_checkpoint()
+                        && latest.sequence_number > next_sequence_number
+                    {
+                        warn!(
+                            checkpoint_seq=next_sequence_number,
+                            latest_checkpoint_seq=latest.sequence_number,
+                            "dwallet checkpoint is missing from the checkpoint store, waiting for it to be indexed"
+                        );
+                    }
+                    missing_checkpoint_retries += 1;
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+                Err(err) => {
+                    error!(
+                        error=?err,
+                        checkpoint_seq=next_sequence_number,
+                        "failed to read dwallet checkpoint"
+                    );
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+}
+
+fn dwallet_id(message: &DWalletCheckpointMessageKind) -> Option<&[u8]> {
+    match message {
+        DWalletCheckpointMessageKind::RespondDWalletDKGFirstRoundOutput(output) => {
+            Some(&output.dwallet_id)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletDKGSecondRoundOutput(output) => {
+            Some(&output.dwallet_id)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletEncryptedUserShare(output) => {
+            Some(&output.dwallet_id)
+        }
+        DWalletCheckpointMessageKind::RespondMakeDWalletUserSecretKeySharesPublic(output) => {
+            Some(&output.dwallet_id)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletImportedKeyVerificationOutput(output) => {
+            Some(&output.dwallet_id)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletPresign(output) => output.dwallet_id.as_deref(),
+        DWalletCheckpointMessageKind::RespondDWalletSign(output) => Some(&output.dwallet_id),
+        DWalletCheckpointMessageKind::RespondDWalletBatchSign(output) => Some(&output.dwallet_id),
+        DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(output) => Some(&output.dwallet_id),
+        DWalletCheckpointMessageKind::RespondDWalletPartialSignatureVerificationOutput(output) => {
+            Some(&output.dwallet_id)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletMPCNetworkDKGOutput(_)
+        | DWalletCheckpointMessageKind::RespondDWalletMPCNetworkReconfigurationOutput(_)
+        | DWalletCheckpointMessageKind::RespondThresholdDecryption(_)
+        | DWalletCheckpointMessageKind::SetMaxActiveSessionsBuffer(_)
+        | DWalletCheckpointMessageKind::SetGasFeeReimbursementSuiSystemCallValue(_)
+        | DWalletCheckpointMessageKind::GarbageCollectFutureSigns(_)
+        | DWalletCheckpointMessageKind::EndOfPublish => None,
+    }
+}
+
+fn network_encryption_key_id(message: &DWalletCheckpointMessageKind) -> Option<&[u8]> {
+    match message {
+        DWalletCheckpointMessageKind::RespondDWalletMPCNetworkDKGOutput(output) => {
+            Some(&output.dwallet_network_encryption_key_id)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletMPCNetworkReconfigurationOutput(output) => {
+            Some(&output.dwallet_network_encryption_key_id)
+        }
+        _ => None,
+    }
+}
+
+fn session_sequence_number(message: &DWalletCheckpointMessageKind) -> Option<u64> {
+    match message {
+        DWalletCheckpointMessageKind::RespondDWalletDKGFirstRoundOutput(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletDKGSecondRoundOutput(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletEncryptedUserShare(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondMakeDWalletUserSecretKeySharesPublic(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletImportedKeyVerificationOutput(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletPresign(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletSign(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletBatchSign(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondThresholdDecryption(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletPartialSignatureVerificationOutput(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletMPCNetworkDKGOutput(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::RespondDWalletMPCNetworkReconfigurationOutput(output) => {
+            Some(output.session_sequence_number)
+        }
+        DWalletCheckpointMessageKind::SetMaxActiveSessionsBuffer(_)
+        | DWalletCheckpointMessageKind::SetGasFeeReimbursementSuiSystemCallValue(_)
+        | DWalletCheckpointMessageKind::GarbageCollectFutureSigns(_)
+        | DWalletCheckpointMessageKind::EndOfPublish => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ika_types::committee::Committee;
+    use ika_types::message::{PresignOutput, SignOutput};
+    use ika_types::messages_dwallet_checkpoint::{
+        CertifiedDWalletCheckpointMessage, DWalletCheckpointMessage,
+    };
+
+    fn checkpoint(
+        sequence_number: DWalletCheckpointSequenceNumber,
+        messages: Vec<DWalletCheckpointMessageKind>,
+    ) -> VerifiedDWalletCheckpointMessage {
+        let (committee, key_pairs) = Committee::new_simple_test_committee();
+        let message = DWalletCheckpointMessage::new(committee.epoch, sequence_number, messages);
+        VerifiedDWalletCheckpointMessage::new_unchecked(
+            CertifiedDWalletCheckpointMessage::new_from_keypairs_for_testing(
+                message, &key_pairs, &committee,
+            ),
+        )
+    }
+
+    fn sign_output(dwallet_id: u8, session_sequence_number: u64) -> DWalletCheckpointMessageKind {
+        DWalletCheckpointMessageKind::RespondDWalletSign(SignOutput {
+            dwallet_id: vec![dwallet_id; 32],
+            sign_id: vec![session_sequence_number as u8; 32],
+            signature: vec![1, 2, 3],
+            is_future_sign: false,
+            rejected: false,
+            session_sequence_number,
+        })
+    }
+
+    #[test]
+    fn indexes_outputs_by_dwallet_and_session() {
+        let index = DWalletCheckpointOutputIndex::new(&tempfile::tempdir().unwrap().keep());
+        let presign = DWalletCheckpointMessageKind::RespondDWalletPresign(PresignOutput {
+            dwallet_id: None,
+            presign_id: vec![9; 32],
+            presign: vec![],
+            rejected: false,
+            session_sequence_number: 3,
+        });
+
+        index
+            .index_checkpoint(&checkpoint(0, vec![sign_output(1, 1), presign.clone()]))
+            .unwrap();
+        index
+            .index_checkpoint(&checkpoint(1, vec![sign_output(2, 2), sign_output(1, 4)]))
+            .unwrap();
+
+        let dwallet_outputs = index
+            .get_dwallet_outputs(&[1; 32], Some("RespondDWalletSign"))
+            .unwrap();
+        assert_eq!(
+            dwallet_outputs
+                .iter()
+                .map(|output| output.message.clone())
+                .collect::<Vec<_>>(),
+            vec![sign_output(1, 1), sign_output(1, 4)]
+        );
+        assert!(
+            index
+                .get_dwallet_outputs(&[3; 32], None)
+                .unwrap()
+                .is_empty()
+        );
+
+        let session_outputs = index.get_session_outputs(3).unwrap();
+        assert_eq!(session_outputs.len(), 1);
+        assert_eq!(session_outputs[0].message, presign);
+        assert_eq!(session_outputs[0].index_in_checkpoint, 1);
+
+        assert_eq!(index.get_highest_indexed_checkpoint().unwrap(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn waits_for_checkpoints_missing_from_the_store() {
+        let checkpoint_store = DWalletCheckpointStore::new(&tempfile::tempdir().unwrap().keep());
+        checkpoint_store
+            .insert_certified_checkpoint(&checkpoint(0, vec![sign_output(1, 1)]))
+            .unwrap();
+        checkpoint_store
+            .insert_certified_checkpoint(&checkpoint(2, vec![sign_output(1, 3)]))
+            .unwrap();
+        let index = DWalletCheckpointOutputIndex::new(&tempfile::tempdir().unwrap().keep());
+
+        let handle = tokio::spawn(index.clone().run(checkpoint_store.clone()));
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(index.get_highest_indexed_checkpoint().unwrap(), Some(0));
+
+        checkpoint_store
+            .insert_certified_checkpoint(&checkpoint(1, vec![sign_output(1, 2)]))
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while index.get_highest_indexed_checkpoint().unwrap() != Some(2) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        handle.abort();
+
+        assert_eq!(
+            index
+                .get_dwallet_outputs(&[1; 32], None)
+                .unwrap()
+                .iter()
+                .map(|output| output.checkpoint_sequence_number)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+}
@@ -5,6 +5,7 @@ use crate::dwallet_mpc::dwallet_dkg::{
     DWalletDKGFirstParty, DWalletDKGSecondParty, DWalletImportedKeyVerificationParty,
 };
 use crate::dwallet_mpc::encrypt_user_share::verify_encrypted_share;
+use crate::dwallet_mpc::key_refresh::refresh_dwallet_public_output;
 use crate::dwallet_mpc::make_dwallet_user_secret_key_shares_public::verify_secret_share;
 use crate::dwallet_mpc::mpc_session::MPCRoundToMessagesHashMap;
 use crate::dwallet_mpc::mpc_session::PublicInput;
@@ -12,8 +13,10 @@ use crate::dwallet_mpc::network_dkg::advance_network_dkg;
 use crate::dwallet_mpc::presign::PresignParty;
 use crate::dwallet_mpc::reconfiguration::ReconfigurationSecp256k1Party;
 use crate::dwallet_mpc::sign::{
-    SignFirstParty, update_expected_decrypters_metrics, verify_partial_signature,
+    SignFirstParty, advance_batch_sign, update_expected_decrypters_metrics,
+    verify_partial_signature,
 };
+use crate::dwallet_mpc::threshold_decryption::advance_threshold_decryption;
 use commitment::CommitmentSizedNumber;
 use dwallet_mpc_types::dwallet_mpc::{
     MPCPrivateInput, VersionedDWalletImportedKeyVerificationOutput,
@@ -31,6 +34,7 @@ use ika_types::messages_dwallet_mpc::{
 use itertools::Itertools;
 use message_digest::message_digest::message_digest;
 use mpc::{GuaranteedOutputDeliveryRoundResult, WeightedThresholdAccessStructure};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use sui_types::base_types::ObjectID;
@@ -43,12 +47,12 @@ mod orchestrator;
 
 use crate::dwallet_mpc::dwallet_mpc_metrics::DWalletMPCMetrics;
 pub(crate) use mpc_computations::advance;
-pub(crate) use orchestrator::CryptographicComputationsOrchestrator;
+pub(crate) use orchestrator::{ComputationSecrets, CryptographicComputationsOrchestrator};
 
 const MPC_SIGN_SECOND_ROUND: u64 = 2;
 
 /// A unique key for a computation request.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub(crate) struct ComputationId {
     pub(crate) session_identifier: SessionIdentifier,
     /// The consensus round at which this computation executed (if it is synced with the consensus).
@@ -378,7 +382,7 @@ impl ComputationRequest {
                     }
                 }
             }
-            MPCRequestInput::Sign(..) => {
+            MPCRequestInput::Sign(..) | MPCRequestInput::DerivedKeySign(..) => {
                 if let Some(decryption_key_shares) = self.decryption_key_shares.clone() {
                     let PublicInput::Sign(public_input) = &self.public_input else {
                         error!(
@@ -451,6 +455,100 @@ impl ComputationRequest {
                     Err(DwalletMPCError::InvalidSessionPublicInput)
                 }
             }
+            MPCRequestInput::BatchSign(..) => {
+                if let Some(decryption_key_shares) = self.decryption_key_shares.clone() {
+                    let PublicInput::BatchSign(public_inputs) = &self.public_input else {
+                        error!(
+                            should_never_happen=?true,
+                            mpc_protocol=?self.request_input,
+                            validator=?self.validator_name,
+                            session_identifier=?computation_id.session_identifier,
+                            mpc_round=?computation_id.mpc_round,
+                            access_structure=?self.access_structure,
+                            ?messages_skeleton,
+                            "session public input does not match the session type"
+                        );
+                        return Err(DwalletMPCError::InvalidSessionPublicInput);
+                    };
+
+                    // All the signs in the batch share the same expected decrypters.
+                    if computation_id.mpc_round == MPC_SIGN_SECOND_ROUND {
+                        if let (Some(sign_first_round_messages), Some(public_input)) =
+                            (self.messages.get(&1), public_inputs.first())
+                        {
+                            let decrypters = sign_first_round_messages.keys().copied().collect();
+                            update_expected_decrypters_metrics(
+                                &public_input.expected_decrypters,
+                                decrypters,
+                                &self.access_structure,
+                                dwallet_mpc_metrics,
+                            );
+                        }
+                    }
+
+                    advance_batch_sign(
+                        computation_id.session_identifier,
+                        self.party_id,
+                        &self.access_structure,
+                        self.messages,
+                        public_inputs,
+                        decryption_key_shares,
+                        rng,
+                    )
+                } else {
+                    error!(
+                        should_never_happen=?true,
+                        mpc_protocol=?self.request_input,
+                        validator=?self.validator_name,
+                        session_identifier=?computation_id.session_identifier,
+                        mpc_round=?computation_id.mpc_round,
+                        access_structure=?self.access_structure,
+                        ?messages_skeleton,
+                        "no decryption key shares for a session that requires them (batch sign)"
+                    );
+
+                    Err(DwalletMPCError::InvalidSessionPublicInput)
+                }
+            }
+            MPCRequestInput::ThresholdDecryption(..) => {
+                if let Some(decryption_key_shares) = self.decryption_key_shares.clone() {
+                    let PublicInput::ThresholdDecryption(public_input) = &self.public_input else {
+                        error!(
+                            should_never_happen=?true,
+                            mpc_protocol=?self.request_input,
+                            validator=?self.validator_name,
+                            session_identifier=?computation_id.session_identifier,
+                            mpc_round=?computation_id.mpc_round,
+                            access_structure=?self.access_structure,
+                            ?messages_skeleton,
+                            "session public input does not match the session type"
+                        );
+                        return Err(DwalletMPCError::InvalidSessionPublicInput);
+                    };
+
+                    advance_threshold_decryption(
+                        computation_id.mpc_round,
+                        &self.access_structure,
+                        self.messages,
+                        public_input,
+                        decryption_key_shares,
+                        rng,
+                    )
+                } else {
+                    error!(
+                        should_never_happen=?true,
+                        mpc_protocol=?self.request_input,
+                        validator=?self.validator_name,
+                        session_identifier=?computation_id.session_identifier,
+                        mpc_round=?computation_id.mpc_round,
+                        access_structure=?self.access_structure,
+                        ?messages_skeleton,
+                        "no decryption key shares for a session that requires them (threshold decryption)"
+                    );
+
+                    Err(DwalletMPCError::InvalidSessionPublicInput)
+                }
+            }
             MPCRequestInput::NetworkEncryptionKeyDkg(key_scheme, _init_event) => {
                 advance_network_dkg(
                     session_id,
@@ -492,6 +590,30 @@ impl ComputationRequest {
                     Err(err) => Err(err),
                 }
             }
+            MPCRequestInput::DWalletKeyRefresh(refresh_data) => {
+                let PublicInput::DWalletKeyRefresh(public_input) = &self.public_input else {
+                    error!(
+                        should_never_happen=?true,
+                        mpc_protocol=?self.request_input,
+                        validator=?self.validator_name,
+                        session_identifier=?computation_id.session_identifier,
+                        mpc_round=?computation_id.mpc_round,
+                        access_structure=?self.access_structure,
+                        ?messages_skeleton,
+                        "session public input does not match the session type"
+                    );
+                    return Err(DwalletMPCError::InvalidSessionPublicInput);
+                };
+
+                let public_output_value =
+                    refresh_dwallet_public_output(&refresh_data.event_data, public_input.clone())?;
+
+                Ok(GuaranteedOutputDeliveryRoundResult::Finalize {
+                    public_output_value,
+                    private_output: vec![],
+                    malicious_parties: vec![],
+                })
+            }
             MPCRequestInput::PartialSignatureVerification(event_data) => {
                 let hashed_message = bcs::to_bytes(
                     &message_digest(
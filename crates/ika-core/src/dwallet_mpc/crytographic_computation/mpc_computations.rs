@@ -19,6 +19,12 @@ pub(crate) mod network_dkg;
 pub(crate) mod presign;
 pub(crate) mod reconfiguration;
 pub(crate) mod sign;
+pub(crate) mod threshold_decryption;
+
+/// The length of the prefix of a serialized message of the `mpc` crate,
+/// holding the message tag and the MPC round number.
+/// See `DWalletMPCManager::handle_message()`.
+pub(crate) const MPC_MESSAGE_HEADER_LENGTH: usize = 9;
 
 /// This function iterates over the messages from different parties sent for
 /// different MPC rounds, ordered by the consensus round they were received.
@@ -8,6 +8,7 @@
 
 use crate::dwallet_mpc::crytographic_computation::advance;
 use crate::dwallet_mpc::mpc_session::PublicInput;
+use crate::dwallet_mpc::network_key_cache::NetworkKeyCache;
 use crate::dwallet_mpc::reconfiguration::{
     ReconfigurationSecp256k1Party,
     instantiate_dwallet_mpc_network_encryption_key_public_data_from_reconfiguration_public_output,
@@ -26,6 +27,7 @@ use dwallet_mpc_types::dwallet_mpc::{
 use group::{OsCsRng, PartyID, secp256k1};
 use homomorphic_encryption::AdditivelyHomomorphicDecryptionKeyShare;
 use ika_types::committee::ClassGroupsEncryptionKeyAndProof;
+use ika_types::committee::EpochId;
 use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
 use ika_types::messages_dwallet_mpc::AsyncProtocol;
 use ika_types::messages_dwallet_mpc::{
@@ -34,10 +36,11 @@ use ika_types::messages_dwallet_mpc::{
 };
 use mpc::{GuaranteedOutputDeliveryRoundResult, WeightedThresholdAccessStructure};
 use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use sui_types::base_types::ObjectID;
 use tokio::sync::oneshot;
-use tracing::error;
+use tracing::{error, warn};
 use twopc_mpc::ProtocolPublicParameters;
 use twopc_mpc::secp256k1::class_groups::{
     FUNDAMENTAL_DISCRIMINANT_LIMBS, NON_FUNDAMENTAL_DISCRIMINANT_LIMBS,
@@ -49,7 +52,20 @@ pub struct DwalletMPCNetworkKeys {
     /// Holds all network (decryption) keys for the current network in encrypted form.
     /// This data is identical for all the Validator nodes.
     pub(crate) network_encryption_keys: HashMap<ObjectID, NetworkEncryptionKeyPublicData>,
-    pub(crate) validator_private_dec_key_data: ValidatorPrivateDecryptionKeyData,
+    /// The private decryption key data of this validator.
+    /// `None` when it is held by a key custody process instead.
+    pub(crate) validator_private_dec_key_data: Option<ValidatorPrivateDecryptionKeyData>,
+    /// The persistent cache of the data derived from the network keys.
+    cache: Option<NetworkKeyCache>,
+}
+
+/// The data derived from a network key that is kept in the [`NetworkKeyCache`].
+#[derive(Serialize, Deserialize)]
+struct CachedNetworkKey {
+    decryption_key_share_public_parameters: Secp256k1DecryptionKeySharePublicParameters,
+    protocol_public_parameters: twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters,
+    /// `None` if the decryption key shares are held by a key custody process.
+    secret_key_shares: Option<HashMap<PartyID, SecretKeyShareSizedInteger>>,
 }
 
 /// Holds the private decryption key data for a validator node.
@@ -133,13 +149,13 @@ async fn get_decryption_key_shares_from_public_output(
 impl ValidatorPrivateDecryptionKeyData {
     /// Stores the new decryption key shares of the validator.
     /// Decrypts the decryption key shares (for all the virtual parties)
-    /// from the public output of the network DKG protocol.
+    /// from the public output of the network DKG protocol, and returns them.
     pub async fn decrypt_and_store_secret_key_shares(
         &mut self,
         key_id: ObjectID,
         key: NetworkEncryptionKeyPublicData,
         access_structure: &WeightedThresholdAccessStructure,
-    ) -> DwalletMPCResult<()> {
+    ) -> DwalletMPCResult<HashMap<PartyID, SecretKeyShareSizedInteger>> {
         let secret_key_shares = get_decryption_key_shares_from_public_output(
             key.clone(),
             self.party_id,
@@ -149,13 +165,13 @@ impl ValidatorPrivateDecryptionKeyData {
         .await?;
 
         let self_decryption_key_shares = Self::convert_secret_key_shares_type_to_decryption_shares(
-            secret_key_shares,
+            secret_key_shares.clone(),
             &key.decryption_key_share_public_parameters,
         )?;
 
         self.validator_decryption_key_shares
             .insert(key_id, self_decryption_key_shares);
-        Ok(())
+        Ok(secret_key_shares)
     }
 
     /// Only for type convertion.
@@ -181,23 +197,151 @@ impl ValidatorPrivateDecryptionKeyData {
 }
 
 impl DwalletMPCNetworkKeys {
-    pub fn new(node_context: ValidatorPrivateDecryptionKeyData) -> Self {
+    pub fn new(
+        node_context: Option<ValidatorPrivateDecryptionKeyData>,
+        cache: Option<NetworkKeyCache>,
+    ) -> Self {
         Self {
             network_encryption_keys: Default::default(),
             validator_private_dec_key_data: node_context,
+            cache,
         }
     }
 
+    /// Instantiates the public data of the network key `key_id` from its on-chain data,
+    /// and decrypts this validator's shares of it.
+    ///
+    /// Both are loaded from the cache when it holds them for this `epoch` and key data,
+    /// and stored in it otherwise.
     pub async fn update_network_key(
         &mut self,
         key_id: ObjectID,
-        key: &NetworkEncryptionKeyPublicData,
+        key_data: DWalletNetworkEncryptionKeyData,
+        epoch: EpochId,
         access_structure: &WeightedThresholdAccessStructure,
     ) -> DwalletMPCResult<()> {
+        if let Some(cached_key) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.load::<CachedNetworkKey>(key_id, epoch, &key_data))
+        {
+            match self.restore_cached_network_key(key_id, &key_data, cached_key) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    warn!(error=?e, ?key_id, "failed to restore a cached network key");
+                }
+            }
+        }
+
+        let key = instantiate_dwallet_mpc_network_encryption_key_public_data_from_public_output(
+            key_data.current_epoch,
+            DWalletMPCNetworkKeyScheme::Secp256k1,
+            access_structure.clone(),
+            key_data.clone(),
+        )
+        .await?;
+
         self.network_encryption_keys.insert(key_id, key.clone());
+        let secret_key_shares = match self.validator_private_dec_key_data.as_mut() {
+            Some(validator_private_dec_key_data) => Some(
+                validator_private_dec_key_data
+                    .decrypt_and_store_secret_key_shares(key_id, key.clone(), access_structure)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        if let Some(cache) = &self.cache {
+            let cached_key = CachedNetworkKey {
+                decryption_key_share_public_parameters: key.decryption_key_share_public_parameters,
+                protocol_public_parameters: key.protocol_public_parameters,
+                secret_key_shares,
+            };
+            if let Err(e) = cache.store(key_id, epoch, &key_data, &cached_key) {
+                warn!(error=?e, ?key_id, "failed to cache the network key");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restores a network key from its cached data, skipping the instantiation of its public
+    /// parameters and the decryption of this validator's shares.
+    fn restore_cached_network_key(
+        &mut self,
+        key_id: ObjectID,
+        key_data: &DWalletNetworkEncryptionKeyData,
+        cached_key: CachedNetworkKey,
+    ) -> DwalletMPCResult<()> {
+        // The same choice of output as in
+        // `instantiate_dwallet_mpc_network_encryption_key_public_data_from_public_output()`.
+        let (state, latest_public_output) =
+            if key_data.current_reconfiguration_public_output.is_empty() {
+                (
+                    NetworkDecryptionKeyPublicOutputType::NetworkDkg,
+                    &key_data.network_dkg_public_output,
+                )
+            } else {
+                (
+                    NetworkDecryptionKeyPublicOutputType::Reconfiguration,
+                    &key_data.current_reconfiguration_public_output,
+                )
+            };
+
+        let decryption_key_shares = match (
+            self.validator_private_dec_key_data.is_some(),
+            cached_key.secret_key_shares,
+        ) {
+            (true, Some(secret_key_shares)) => Some(
+                ValidatorPrivateDecryptionKeyData::convert_secret_key_shares_type_to_decryption_shares(
+                    secret_key_shares,
+                    &cached_key.decryption_key_share_public_parameters,
+                )?,
+            ),
+            (true, None) => {
+                return Err(DwalletMPCError::NetworkKeyCache(
+                    "the cached network key is missing the decryption key shares".to_string(),
+                ));
+            }
+            (false, _) => None,
+        };
+
+        let key = NetworkEncryptionKeyPublicData {
+            epoch: key_data.current_epoch,
+            state,
+            latest_public_output: bcs::from_bytes(latest_public_output)?,
+            decryption_key_share_public_parameters: cached_key
+                .decryption_key_share_public_parameters,
+            protocol_public_parameters: cached_key.protocol_public_parameters,
+            network_dkg_output: bcs::from_bytes(&key_data.network_dkg_public_output)?,
+        };
+
+        self.network_encryption_keys.insert(key_id, key);
+        if let (Some(validator_private_dec_key_data), Some(decryption_key_shares)) = (
+            self.validator_private_dec_key_data.as_mut(),
+            decryption_key_shares,
+        ) {
+            validator_private_dec_key_data
+                .validator_decryption_key_shares
+                .insert(key_id, decryption_key_shares);
+        }
+
+        Ok(())
+    }
+
+    /// Whether this node holds its own private decryption key data,
+    /// as opposed to delegating it to a key custody process.
+    pub(crate) fn holds_validator_private_data(&self) -> bool {
+        self.validator_private_dec_key_data.is_some()
+    }
+
+    /// The validator's class groups decryption key, if this node holds it.
+    pub(crate) fn class_groups_decryption_key(&self) -> Option<ClassGroupsDecryptionKey> {
         self.validator_private_dec_key_data
-            .decrypt_and_store_secret_key_shares(key_id, key.clone(), access_structure)
-            .await
+            .as_ref()
+            .map(|validator_private_dec_key_data| {
+                validator_private_dec_key_data.class_groups_decryption_key
+            })
     }
 
     pub fn get_decryption_key_share_public_parameters(
@@ -218,6 +362,8 @@ impl DwalletMPCNetworkKeys {
         key_id: &ObjectID,
     ) -> DwalletMPCResult<HashMap<PartyID, <AsyncProtocol as Protocol>::DecryptionKeyShare>> {
         self.validator_private_dec_key_data
+            .as_ref()
+            .ok_or(DwalletMPCError::MissingDwalletMPCClassGroupsDecryptionShares)?
             .validator_decryption_key_shares
             .get(key_id)
             .cloned()
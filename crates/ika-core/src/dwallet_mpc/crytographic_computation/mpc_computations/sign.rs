@@ -4,23 +4,35 @@
 //! This module provides a wrapper around the Sign protocol from the 2PC-MPC library.
 //!
 //! It integrates the Sign party (representing a round in the protocol).
+//!
+//! A batch of messages of a single dWallet is signed in a single session by running
+//! a Sign party for each message in lockstep, see [`advance_batch_sign`].
 
+use super::{MPC_MESSAGE_HEADER_LENGTH, advance};
 use crate::dwallet_mpc::dwallet_mpc_metrics::DWalletMPCMetrics;
+use crate::dwallet_mpc::mpc_session::MPCRoundToMessagesHashMap;
 use crate::dwallet_mpc::network_dkg::DwalletMPCNetworkKeys;
+use crate::dwallet_mpc::threshold_decryption::DKGDecentralizedOutput;
+use commitment::CommitmentSizedNumber;
 use dwallet_mpc_types::dwallet_mpc::{
-    SerializedWrappedMPCPublicOutput, VersionedDwalletDKGSecondRoundPublicOutput,
-    VersionedPresignOutput, VersionedUserSignedMessage,
+    MPCMessage, SerializedWrappedMPCPublicOutput, VersionedDwalletDKGSecondRoundPublicOutput,
+    VersionedPresignOutput, VersionedSignOutput, VersionedUserSignedMessage,
 };
-use group::PartyID;
+use dwallet_mpc_types::key_derivation::derive_dwallet_public_key;
+use group::{GroupElement, PartyID};
+use ika_types::crypto::keccak256_digest;
 use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
 use ika_types::messages_dwallet_mpc::{
-    AsyncProtocol, DWalletSessionEvent, FutureSignRequestEvent, MPCRequestInput, MPCSessionRequest,
-    SessionIdentifier, SignRequestEvent,
+    AsyncProtocol, BatchSignRequestEvent, DWalletSessionEvent, DerivedKeySignRequestEvent,
+    FutureSignRequestEvent, MPCRequestInput, MPCSessionRequest, SessionIdentifier,
+    SignRequestEvent,
 };
+use itertools::Itertools;
 use message_digest::message_digest::{Hash, message_digest};
-use mpc::{Party, Weight, WeightedThresholdAccessStructure};
-use rand_core::SeedableRng;
-use std::collections::HashSet;
+use mpc::{GuaranteedOutputDeliveryRoundResult, Party, Weight, WeightedThresholdAccessStructure};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{RngCore, SeedableRng};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use twopc_mpc::dkg::Protocol;
 use twopc_mpc::secp256k1;
@@ -63,8 +75,12 @@ fn generate_expected_decrypters(
     Ok(expected_decrypters)
 }
 
+/// Generates the public input of the Sign party, signing with the child key of the
+/// dWallet at `derivation_path`, or with the dWallet key itself if it is empty.
 pub(crate) fn sign_session_public_input(
-    deserialized_event: &DWalletSessionEvent<SignRequestEvent>,
+    session_identifier: SessionIdentifier,
+    sign_request: &SignRequestEvent,
+    derivation_path: &[u32],
     access_structure: &WeightedThresholdAccessStructure,
     network_keys: &DwalletMPCNetworkKeys,
     protocol_public_parameters: ProtocolPublicParameters,
@@ -72,6 +88,42 @@ pub(crate) fn sign_session_public_input(
     let decryption_pp = network_keys.get_decryption_key_share_public_parameters(
         // The `StartSignRoundEvent` is assign with a Secp256k1 dwallet.
         // Todo (#473): Support generic network key scheme
+        &sign_request.dwallet_network_encryption_key_id,
+    )?;
+
+    let expected_decrypters = generate_expected_decrypters(access_structure, session_identifier)?;
+
+    let dwallet_decentralized_public_output = derive_dwallet_decentralized_public_output(
+        &sign_request.dwallet_decentralized_public_output,
+        derivation_path,
+        &protocol_public_parameters,
+    )?;
+
+    <SignFirstParty as SignPartyPublicInputGenerator>::generate_public_input(
+        protocol_public_parameters,
+        dwallet_decentralized_public_output,
+        hashed_message(&sign_request.message, sign_request.hash_scheme)?,
+        sign_request.presign.clone(),
+        sign_request.message_centralized_signature.clone(),
+        decryption_pp,
+        expected_decrypters,
+    )
+}
+
+/// Generates the public input of the Sign party of each message in a batch sign session.
+///
+/// All the messages are decrypted by the same expected decrypters, sampled from the session identifier.
+pub(crate) fn batch_sign_session_public_input(
+    deserialized_event: &DWalletSessionEvent<BatchSignRequestEvent>,
+    access_structure: &WeightedThresholdAccessStructure,
+    network_keys: &DwalletMPCNetworkKeys,
+    protocol_public_parameters: ProtocolPublicParameters,
+) -> DwalletMPCResult<Vec<<SignFirstParty as Party>::PublicInput>> {
+    if deserialized_event.event_data.signs.is_empty() {
+        return Err(DwalletMPCError::EmptySignBatch);
+    }
+
+    let decryption_pp = network_keys.get_decryption_key_share_public_parameters(
         &deserialized_event
             .event_data
             .dwallet_network_encryption_key_id,
@@ -82,28 +134,201 @@ pub(crate) fn sign_session_public_input(
         deserialized_event.session_identifier_digest(),
     )?;
 
-    <SignFirstParty as SignPartyPublicInputGenerator>::generate_public_input(
-        protocol_public_parameters,
-        deserialized_event
-            .event_data
-            .dwallet_decentralized_public_output
-            .clone(),
-        bcs::to_bytes(
-            &message_digest(
-                &deserialized_event.event_data.message.clone(),
-                &Hash::try_from(deserialized_event.event_data.hash_scheme)
-                    .map_err(|e| DwalletMPCError::SignatureVerificationFailed(e.to_string()))?,
+    deserialized_event
+        .event_data
+        .signs
+        .iter()
+        .map(|sign| {
+            <SignFirstParty as SignPartyPublicInputGenerator>::generate_public_input(
+                protocol_public_parameters.clone(),
+                deserialized_event
+                    .event_data
+                    .dwallet_decentralized_public_output
+                    .clone(),
+                hashed_message(&sign.message, deserialized_event.event_data.hash_scheme)?,
+                sign.presign.clone(),
+                sign.message_centralized_signature.clone(),
+                decryption_pp.clone(),
+                expected_decrypters.clone(),
             )
+        })
+        .collect()
+}
+
+/// Derives the decentralized DKG output of the child key of a dWallet at `derivation_path`,
+/// see [`derive_dwallet_public_key`].
+///
+/// The network share of the child key is that of the dWallet, so the user share absorbs the tweak:
+/// the public key and the public key share of the user move by the tweak times the generator,
+/// and the public key share and encrypted secret key share of the network stay unchanged.
+/// The user applies the same tweak to its secret key share when signing with the child key.
+fn derive_dwallet_decentralized_public_output(
+    dwallet_decentralized_public_output: &SerializedWrappedMPCPublicOutput,
+    derivation_path: &[u32],
+    protocol_public_parameters: &ProtocolPublicParameters,
+) -> DwalletMPCResult<SerializedWrappedMPCPublicOutput> {
+    if derivation_path.is_empty() {
+        return Ok(dwallet_decentralized_public_output.clone());
+    }
+
+    let VersionedDwalletDKGSecondRoundPublicOutput::V1(dkg_output) =
+        bcs::from_bytes(dwallet_decentralized_public_output)?;
+    let dkg_output: DKGDecentralizedOutput = bcs::from_bytes(&dkg_output)?;
+    let derived_public_key = derive_dwallet_public_key(&dkg_output.public_key, derivation_path)
+        .map_err(|e| DwalletMPCError::KeyDerivationFailed(e.to_string()))?;
+
+    let to_group_element = |value| {
+        secp256k1::GroupElement::new(value, &protocol_public_parameters.group_public_parameters)
+            .map_err(|e| DwalletMPCError::KeyDerivationFailed(format!("{e:?}")))
+    };
+    let tweak_public_share =
+        to_group_element(derived_public_key.public_key)? - to_group_element(dkg_output.public_key)?;
+    let centralized_party_public_key_share =
+        to_group_element(dkg_output.centralized_party_public_key_share)? + tweak_public_share;
+
+    Ok(bcs::to_bytes(
+        &VersionedDwalletDKGSecondRoundPublicOutput::V1(bcs::to_bytes(&DKGDecentralizedOutput {
+            public_key_share: dkg_output.public_key_share,
+            public_key: derived_public_key.public_key,
+            centralized_party_public_key_share: centralized_party_public_key_share.value(),
+            encrypted_secret_key_share: dkg_output.encrypted_secret_key_share,
+        })?),
+    )?)
+}
+
+/// Hashes `message` with `hash_scheme`, and serializes the digest to be signed.
+fn hashed_message(message: &[u8], hash_scheme: u32) -> DwalletMPCResult<Vec<u8>> {
+    let digest = message_digest(
+        message,
+        &Hash::try_from(hash_scheme)
             .map_err(|e| DwalletMPCError::SignatureVerificationFailed(e.to_string()))?,
-        )?,
-        deserialized_event.event_data.presign.clone(),
-        deserialized_event
-            .event_data
-            .message_centralized_signature
-            .clone(),
-        decryption_pp,
-        expected_decrypters,
     )
+    .map_err(|e| DwalletMPCError::SignatureVerificationFailed(e.to_string()))?;
+
+    Ok(bcs::to_bytes(&digest)?)
+}
+
+/// Advances the Sign parties of all the messages of a batch sign session,
+/// and combines their results into the result of the session.
+///
+/// The message of a party in a batch sign session holds its messages for every sign
+/// in the batch, prefixed like any other message, so that the signs advance in lockstep
+/// and share the consensus rounds and checkpoint entry of the session.
+/// Only those are shared: each sign is advanced separately, and verifies its own decryption shares.
+///
+/// A message that does not hold a message for every sign is ignored for all of them,
+/// as if the party did not send it, and the party is reported as malicious.
+///
+/// Once finalized, the public output is the list of the (versioned) signatures,
+/// in the order of the signs in the batch.
+pub(crate) fn advance_batch_sign(
+    session_identifier: SessionIdentifier,
+    party_id: PartyID,
+    access_structure: &WeightedThresholdAccessStructure,
+    messages: MPCRoundToMessagesHashMap,
+    public_inputs: &[<SignFirstParty as Party>::PublicInput],
+    decryption_key_shares: HashMap<
+        PartyID,
+        <AsyncProtocol as twopc_mpc::sign::Protocol>::DecryptionKeyShare,
+    >,
+    mut rng: ChaCha20Rng,
+) -> DwalletMPCResult<GuaranteedOutputDeliveryRoundResult> {
+    let batch_size = public_inputs.len();
+    let (messages_by_sign, mut malicious_parties) = split_batch_sign_messages(messages, batch_size);
+
+    let mut sign_messages = vec![];
+    let mut signatures = vec![];
+    for (index, (public_input, messages)) in public_inputs.iter().zip(messages_by_sign).enumerate()
+    {
+        // Each sign is a separate instance of the protocol, with its own session ID and randomness.
+        let session_id = CommitmentSizedNumber::from_le_slice(&keccak256_digest(
+            &[
+                session_identifier.into_bytes().as_slice(),
+                &(index as u64).to_be_bytes(),
+            ]
+            .concat(),
+        ));
+        let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+        rng.fill_bytes(&mut seed);
+
+        match advance::<SignFirstParty>(
+            session_id,
+            party_id,
+            access_structure,
+            messages,
+            public_input,
+            decryption_key_shares.clone(),
+            ChaCha20Rng::from_seed(seed),
+        )? {
+            GuaranteedOutputDeliveryRoundResult::Advance { message } => sign_messages.push(message),
+            GuaranteedOutputDeliveryRoundResult::Finalize {
+                public_output_value,
+                malicious_parties: sign_malicious_parties,
+                private_output: _,
+            } => {
+                signatures.push(bcs::to_bytes(&VersionedSignOutput::V1(
+                    public_output_value,
+                ))?);
+                malicious_parties.extend(sign_malicious_parties);
+            }
+        }
+    }
+
+    if sign_messages.len() == batch_size {
+        let prefix = sign_messages
+            .first()
+            .and_then(|message| message.get(..MPC_MESSAGE_HEADER_LENGTH))
+            .ok_or(DwalletMPCError::BatchSignResultsMismatch)?;
+        let mut message = prefix.to_vec();
+        message.extend(bcs::to_bytes(&sign_messages)?);
+
+        Ok(GuaranteedOutputDeliveryRoundResult::Advance { message })
+    } else if signatures.len() == batch_size {
+        Ok(GuaranteedOutputDeliveryRoundResult::Finalize {
+            public_output_value: bcs::to_bytes(&signatures)?,
+            malicious_parties: malicious_parties.into_iter().sorted().collect(),
+            private_output: vec![],
+        })
+    } else {
+        Err(DwalletMPCError::BatchSignResultsMismatch)
+    }
+}
+
+/// Splits the batch messages of every party into the messages of each sign in the batch.
+///
+/// Returns the messages of each sign, and the parties that sent a malformed batch message,
+/// or one that does not hold a message for every sign.
+fn split_batch_sign_messages(
+    messages: MPCRoundToMessagesHashMap,
+    batch_size: usize,
+) -> (Vec<MPCRoundToMessagesHashMap>, HashSet<PartyID>) {
+    let mut messages_by_sign = vec![MPCRoundToMessagesHashMap::new(); batch_size];
+    let mut malicious_parties = HashSet::new();
+    for (mpc_round, round_messages) in messages {
+        for sign_messages in messages_by_sign.iter_mut() {
+            sign_messages.entry(mpc_round).or_default();
+        }
+
+        for (party_id, message) in round_messages {
+            let Some(sign_messages) = message
+                .get(MPC_MESSAGE_HEADER_LENGTH..)
+                .and_then(|batch| bcs::from_bytes::<Vec<MPCMessage>>(batch).ok())
+                .filter(|sign_messages| sign_messages.len() == batch_size)
+            else {
+                malicious_parties.insert(party_id);
+                continue;
+            };
+
+            for (messages, sign_message) in messages_by_sign.iter_mut().zip(sign_messages) {
+                messages
+                    .entry(mpc_round)
+                    .or_default()
+                    .insert(party_id, sign_message);
+            }
+        }
+    }
+
+    (messages_by_sign, malicious_parties)
 }
 
 /// Update metrics on whether we are in the expected or unexpected case during threshold decryption.
@@ -145,6 +370,34 @@ pub(crate) fn sign_party_session_request(
     }
 }
 
+pub(crate) fn derived_key_sign_party_session_request(
+    deserialized_event: &DWalletSessionEvent<DerivedKeySignRequestEvent>,
+) -> MPCSessionRequest {
+    MPCSessionRequest {
+        session_type: deserialized_event.session_type,
+        session_identifier: deserialized_event.session_identifier_digest(),
+        session_sequence_number: deserialized_event.session_sequence_number,
+        epoch: deserialized_event.epoch,
+        request_input: MPCRequestInput::DerivedKeySign(deserialized_event.clone()),
+        requires_network_key_data: true,
+        requires_next_active_committee: false,
+    }
+}
+
+pub(crate) fn batch_sign_party_session_request(
+    deserialized_event: &DWalletSessionEvent<BatchSignRequestEvent>,
+) -> MPCSessionRequest {
+    MPCSessionRequest {
+        session_type: deserialized_event.session_type,
+        session_identifier: deserialized_event.session_identifier_digest(),
+        session_sequence_number: deserialized_event.session_sequence_number,
+        epoch: deserialized_event.epoch,
+        request_input: MPCRequestInput::BatchSign(deserialized_event.clone()),
+        requires_network_key_data: true,
+        requires_next_active_committee: false,
+    }
+}
+
 pub(crate) fn get_verify_partial_signatures_session_request(
     deserialized_event: &DWalletSessionEvent<FutureSignRequestEvent>,
 ) -> MPCSessionRequest {
@@ -255,3 +508,155 @@ pub(crate) fn verify_partial_signature(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use class_groups::Secp256k1DecryptionKey;
+    use class_groups::setup::get_setup_parameters_secp256k1;
+    use dwallet_mpc_centralized_party::derive_child_key_shares;
+    use group::{OsCsRng, Samplable};
+    use ika_types::messages_dwallet_mpc::SessionType;
+    use twopc_mpc::secp256k1::class_groups::{
+        FUNDAMENTAL_DISCRIMINANT_LIMBS, NON_FUNDAMENTAL_DISCRIMINANT_LIMBS,
+    };
+
+    fn batch_message(sign_messages: &[MPCMessage]) -> MPCMessage {
+        let mut message = vec![0u8; MPC_MESSAGE_HEADER_LENGTH];
+        message.extend(bcs::to_bytes(sign_messages).unwrap());
+        message
+    }
+
+    #[test]
+    fn splits_batch_messages_by_sign() {
+        let messages = HashMap::from([
+            (
+                1,
+                HashMap::from([
+                    (1u16, batch_message(&[vec![1u8], vec![2u8]])),
+                    (2, batch_message(&[vec![3u8], vec![4u8]])),
+                    // A message for a single sign of the batch is ignored for all of them,
+                    // and its sender is malicious.
+                    (3, batch_message(&[vec![5u8]])),
+                    // So is a message that is not a batch message at all.
+                    (4, vec![6u8; MPC_MESSAGE_HEADER_LENGTH + 1]),
+                ]),
+            ),
+            (2, HashMap::from([(3u16, vec![7u8])])),
+        ]);
+
+        let (messages_by_sign, malicious_parties) = split_batch_sign_messages(messages, 2);
+
+        assert_eq!(malicious_parties, HashSet::from([3, 4]));
+        assert_eq!(
+            messages_by_sign,
+            vec![
+                HashMap::from([
+                    (1, HashMap::from([(1u16, vec![1u8]), (2, vec![3u8])])),
+                    (2, HashMap::new()),
+                ]),
+                HashMap::from([
+                    (1, HashMap::from([(1u16, vec![2u8]), (2, vec![4u8])])),
+                    (2, HashMap::new()),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_an_empty_batch() {
+        let access_structure =
+            WeightedThresholdAccessStructure::uniform(3, 4, 4, &mut OsCsRng).unwrap();
+
+        let result = advance_batch_sign(
+            SessionIdentifier::new(SessionType::User, [1; SessionIdentifier::LENGTH]),
+            1,
+            &access_structure,
+            HashMap::new(),
+            &[],
+            HashMap::new(),
+            ChaCha20Rng::from_seed([0; 32]),
+        );
+
+        assert!(matches!(
+            result,
+            Err(DwalletMPCError::BatchSignResultsMismatch)
+        ));
+    }
+
+    #[test]
+    fn derives_the_child_key_output_of_the_user() {
+        let (encryption_scheme_public_parameters, _) =
+            Secp256k1DecryptionKey::generate(get_setup_parameters_secp256k1(), &mut OsCsRng)
+                .unwrap();
+        let protocol_public_parameters = twopc_mpc::ProtocolPublicParameters::new::<
+            { secp256k1::SCALAR_LIMBS },
+            { FUNDAMENTAL_DISCRIMINANT_LIMBS },
+            { NON_FUNDAMENTAL_DISCRIMINANT_LIMBS },
+            secp256k1::GroupElement,
+        >(encryption_scheme_public_parameters);
+        let scalar_group_public_parameters = &protocol_public_parameters
+            .as_ref()
+            .scalar_group_public_parameters;
+        let generator = secp256k1::group_element::GroupElement::generator_from_public_parameters(
+            &protocol_public_parameters.group_public_parameters,
+        )
+        .unwrap();
+        let secret_key_share =
+            secp256k1::Scalar::sample(scalar_group_public_parameters, &mut OsCsRng).unwrap();
+        let user_secret_key_share =
+            secp256k1::Scalar::sample(scalar_group_public_parameters, &mut OsCsRng).unwrap();
+        let (_, encrypted_secret_key_share) =
+            <AsyncProtocol as twopc_mpc::dkg::Protocol>::encrypt_and_prove_centralized_party_share(
+                &protocol_public_parameters,
+                protocol_public_parameters
+                    .encryption_scheme_public_parameters
+                    .encryption_key
+                    .clone(),
+                secret_key_share,
+                &mut OsCsRng,
+            )
+            .unwrap();
+        let public_key_share = secret_key_share * generator;
+        let centralized_party_public_key_share = user_secret_key_share * generator;
+        let dkg_output = DKGDecentralizedOutput {
+            public_key_share: public_key_share.value(),
+            public_key: (public_key_share + centralized_party_public_key_share).value(),
+            centralized_party_public_key_share: centralized_party_public_key_share.value(),
+            encrypted_secret_key_share,
+        };
+        let dwallet_public_output = bcs::to_bytes(&VersionedDwalletDKGSecondRoundPublicOutput::V1(
+            bcs::to_bytes(&dkg_output).unwrap(),
+        ))
+        .unwrap();
+
+        for derivation_path in [vec![], vec![0], vec![0, 1], vec![7, 0x7fff_ffff]] {
+            let derived_public_output = derive_dwallet_decentralized_public_output(
+                &dwallet_public_output,
+                &derivation_path,
+                &protocol_public_parameters,
+            )
+            .unwrap();
+            let (user_dkg_output, derived_user_secret_key_share) = derive_child_key_shares(
+                dkg_output.clone(),
+                user_secret_key_share,
+                &derivation_path,
+            )
+            .unwrap();
+
+            // The network and the user sign with the same DKG output of the child key.
+            assert_eq!(
+                derived_public_output,
+                bcs::to_bytes(&VersionedDwalletDKGSecondRoundPublicOutput::V1(
+                    bcs::to_bytes(&user_dkg_output).unwrap(),
+                ))
+                .unwrap()
+            );
+            // Which matches the user secret key share of the child key.
+            assert_eq!(
+                (derived_user_secret_key_share * generator).value(),
+                user_dkg_output.centralized_party_public_key_share
+            );
+        }
+    }
+}
@@ -0,0 +1,517 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! This module provides the threshold decryption of keys encrypted by users to the network
+//! encryption key, reusing the decryption key shares the network holds for Sign.
+//!
+//! A key is only decrypted alongside a proof that it was encrypted by someone who knows it,
+//! so that this session can't be used to decrypt ciphertexts the network keeps for itself
+//! (i.e., the encrypted secret key shares of dWallets and presigns).
+//!
+//! The session has two rounds:
+//! 1. every party verifies the proof of encryption, and sends its decryption shares
+//!    for each of its virtual parties, with proofs of their correctness.
+//! 2. every party combines the decryption shares of the first round into the decrypted key,
+//!    and re-encrypts it to the encryption key of the requester.
+//!
+//! The decrypted key itself is never part of the output, which is published on-chain.
+//! Its re-encryption must be identical across the parties for the output to be agreed on,
+//! so its randomness is derived from the (secret) decrypted key, see [`re_encrypt_key`].
+
+use super::MPC_MESSAGE_HEADER_LENGTH;
+use crate::dwallet_mpc::mpc_session::MPCRoundToMessagesHashMap;
+use crate::dwallet_mpc::network_dkg::DwalletMPCNetworkKeys;
+use class_groups::{
+    EncryptionKey, SECP256K1_FUNDAMENTAL_DISCRIMINANT_LIMBS,
+    SECP256K1_NON_FUNDAMENTAL_DISCRIMINANT_LIMBS, Secp256k1DecryptionKeySharePublicParameters,
+};
+use dwallet_mpc_types::dwallet_mpc::{
+    MPCMessage, VersionedNetworkEncryptedKey, VersionedThresholdDecryptionOutput,
+};
+use group::{GroupElement, PartyID, secp256k1};
+use homomorphic_encryption::{
+    AdditivelyHomomorphicDecryptionKeyShare, AdditivelyHomomorphicEncryptionKey,
+    GroupsPublicParametersAccessors,
+};
+use ika_types::crypto::keccak256_digest;
+use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
+use ika_types::messages_dwallet_mpc::{
+    AsyncProtocol, DWalletSessionEvent, MPCRequestInput, MPCSessionRequest,
+    ThresholdDecryptionRequestEvent,
+};
+use itertools::Itertools;
+use mpc::{GuaranteedOutputDeliveryRoundResult, WeightedThresholdAccessStructure};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use std::collections::{HashMap, HashSet};
+use twopc_mpc::secp256k1::SCALAR_LIMBS;
+use twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters;
+use twopc_mpc::sign::Protocol;
+
+type Secp256k1EncryptionKey = EncryptionKey<
+    SCALAR_LIMBS,
+    SECP256K1_FUNDAMENTAL_DISCRIMINANT_LIMBS,
+    SECP256K1_NON_FUNDAMENTAL_DISCRIMINANT_LIMBS,
+    secp256k1::GroupElement,
+>;
+pub(crate) type CiphertextSpaceGroupElement = <Secp256k1EncryptionKey as AdditivelyHomomorphicEncryptionKey<
+    SCALAR_LIMBS,
+>>::CiphertextSpaceGroupElement;
+type DecryptionKeyShare = <AsyncProtocol as Protocol>::DecryptionKeyShare;
+type DecryptionShare = <DecryptionKeyShare as AdditivelyHomomorphicDecryptionKeyShare<
+    SCALAR_LIMBS,
+    Secp256k1EncryptionKey,
+>>::DecryptionShare;
+type PartialDecryptionProof = <DecryptionKeyShare as AdditivelyHomomorphicDecryptionKeyShare<
+    SCALAR_LIMBS,
+    Secp256k1EncryptionKey,
+>>::PartialDecryptionProof;
+pub(crate) type DKGDecentralizedOutput =
+    <AsyncProtocol as twopc_mpc::dkg::Protocol>::DecentralizedPartyDKGOutput;
+
+/// The message of a party in the first round: its decryption shares and their proof,
+/// by the virtual party that generated them.
+type DecryptionSharesMessage = HashMap<PartyID, (Vec<DecryptionShare>, PartialDecryptionProof)>;
+
+const THRESHOLD_DECRYPTION_FIRST_ROUND: u64 = 1;
+const THRESHOLD_DECRYPTION_SECOND_ROUND: u64 = 2;
+
+/// The tag of a (non-threshold-not-reached) MPC message, see `DWalletMPCManager::handle_message()`.
+const MPC_MESSAGE_TAG: u8 = 0;
+
+/// Domain separator of the seed of the randomness re-encrypting a decrypted key.
+const RE_ENCRYPTION_SEED_DOMAIN: &[u8] = b"ika-threshold-decryption-re-encryption";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ThresholdDecryptionPublicInput {
+    pub(crate) protocol_public_parameters: ProtocolPublicParameters,
+    pub(crate) decryption_key_share_public_parameters: Secp256k1DecryptionKeySharePublicParameters,
+    /// The serialized [`VersionedNetworkEncryptedKey`].
+    pub(crate) encrypted_key: Vec<u8>,
+    /// The serialized encryption key of the requester, to re-encrypt the decrypted key to.
+    pub(crate) encryption_key: Vec<u8>,
+}
+
+pub(crate) fn threshold_decryption_session_request(
+    deserialized_event: &DWalletSessionEvent<ThresholdDecryptionRequestEvent>,
+) -> MPCSessionRequest {
+    MPCSessionRequest {
+        session_type: deserialized_event.session_type,
+        session_identifier: deserialized_event.session_identifier_digest(),
+        session_sequence_number: deserialized_event.session_sequence_number,
+        epoch: deserialized_event.epoch,
+        request_input: MPCRequestInput::ThresholdDecryption(deserialized_event.clone()),
+        requires_network_key_data: true,
+        requires_next_active_committee: false,
+    }
+}
+
+pub(crate) fn threshold_decryption_public_input(
+    deserialized_event: &DWalletSessionEvent<ThresholdDecryptionRequestEvent>,
+    network_keys: &DwalletMPCNetworkKeys,
+) -> DwalletMPCResult<ThresholdDecryptionPublicInput> {
+    let key_id = &deserialized_event
+        .event_data
+        .dwallet_network_encryption_key_id;
+
+    Ok(ThresholdDecryptionPublicInput {
+        protocol_public_parameters: network_keys.get_protocol_public_parameters(key_id)?,
+        decryption_key_share_public_parameters: network_keys
+            .get_decryption_key_share_public_parameters(key_id)?,
+        encrypted_key: deserialized_event.event_data.encrypted_key.clone(),
+        encryption_key: deserialized_event.event_data.encryption_key.clone(),
+    })
+}
+
+/// Advances the threshold decryption session by a round.
+///
+/// Once finalized, the public output is the [`VersionedThresholdDecryptionOutput`],
+/// holding the decrypted key re-encrypted to the encryption key of the requester.
+///
+/// A party whose decryption shares are malformed, or are not those of its virtual parties,
+/// is reported as malicious. If the remaining parties are not an authorized subset,
+/// [`mpc::Error::ThresholdNotReached`] is returned so the round is retried with more messages.
+pub(crate) fn advance_threshold_decryption(
+    mpc_round: u64,
+    access_structure: &WeightedThresholdAccessStructure,
+    messages: MPCRoundToMessagesHashMap,
+    public_input: &ThresholdDecryptionPublicInput,
+    decryption_key_shares: HashMap<PartyID, DecryptionKeyShare>,
+    mut rng: ChaCha20Rng,
+) -> DwalletMPCResult<GuaranteedOutputDeliveryRoundResult> {
+    let (public_key_share, ciphertext) = verify_network_encrypted_key(
+        &public_input.protocol_public_parameters,
+        &public_input.encrypted_key,
+    )?;
+
+    match mpc_round {
+        THRESHOLD_DECRYPTION_FIRST_ROUND => {
+            let decryption_shares: DecryptionSharesMessage = decryption_key_shares
+                .into_iter()
+                .map(|(virtual_party_id, decryption_key_share)| {
+                    let decryption_shares_and_proof = decryption_key_share
+                        .generate_decryption_shares(
+                            vec![ciphertext.clone()],
+                            &public_input.decryption_key_share_public_parameters,
+                            &mut rng,
+                        )
+                        .map_err(|e| {
+                            DwalletMPCError::ThresholdDecryptionFailed(format!("{e:?}"))
+                        })?;
+
+                    Ok((virtual_party_id, decryption_shares_and_proof))
+                })
+                .collect::<DwalletMPCResult<_>>()?;
+
+            let mut message = vec![MPC_MESSAGE_TAG];
+            message.extend(bcs::to_bytes(&THRESHOLD_DECRYPTION_FIRST_ROUND)?);
+            message.extend(bcs::to_bytes(&decryption_shares)?);
+
+            Ok(GuaranteedOutputDeliveryRoundResult::Advance { message })
+        }
+        THRESHOLD_DECRYPTION_SECOND_ROUND => {
+            let first_round_messages = messages
+                .get(&THRESHOLD_DECRYPTION_FIRST_ROUND)
+                .cloned()
+                .unwrap_or_default();
+            let (decryption_shares, malicious_parties) =
+                collect_decryption_shares(access_structure, first_round_messages)?;
+
+            // The decrypted key is verified against the public key share proven for its encryption
+            // before it is re-encrypted, so invalid decryption shares can't yield a wrong key.
+            let plaintexts = DecryptionKeyShare::combine_decryption_shares(
+                vec![ciphertext],
+                decryption_shares,
+                &public_input.decryption_key_share_public_parameters,
+                &mut rng,
+            )
+            .map_err(|e| DwalletMPCError::ThresholdDecryptionFailed(format!("{e:?}")))?;
+            let Some(plaintext) = plaintexts.first() else {
+                return Err(DwalletMPCError::ThresholdDecryptionFailed(
+                    "no plaintext was decrypted".to_string(),
+                ));
+            };
+
+            let re_encrypted_key = re_encrypt_key(
+                &public_input.protocol_public_parameters,
+                &public_input.encrypted_key,
+                &public_input.encryption_key,
+                public_key_share,
+                &bcs::to_bytes(&plaintext.value())?,
+            )?;
+
+            Ok(GuaranteedOutputDeliveryRoundResult::Finalize {
+                public_output_value: bcs::to_bytes(&VersionedThresholdDecryptionOutput::V1(
+                    re_encrypted_key,
+                ))?,
+                malicious_parties,
+                private_output: vec![],
+            })
+        }
+        _ => Err(DwalletMPCError::ThresholdDecryptionFailed(format!(
+            "unexpected MPC round {mpc_round}"
+        ))),
+    }
+}
+
+/// Collects the decryption shares sent in the first round, by virtual party.
+///
+/// A party may only send the decryption shares of its own virtual parties;
+/// a party whose message is malformed or violates this is returned as malicious.
+/// If the parties whose shares were collected are not an authorized subset,
+/// [`mpc::Error::ThresholdNotReached`] is returned.
+fn collect_decryption_shares(
+    access_structure: &WeightedThresholdAccessStructure,
+    first_round_messages: HashMap<PartyID, MPCMessage>,
+) -> DwalletMPCResult<(DecryptionSharesMessage, Vec<PartyID>)> {
+    let party_to_virtual_parties = access_structure.party_to_virtual_parties();
+
+    let mut malicious_parties = HashSet::new();
+    let mut decrypters = HashSet::new();
+    let mut decryption_shares = HashMap::new();
+    for (party_id, message) in first_round_messages {
+        let party_virtual_parties = party_to_virtual_parties
+            .get(&party_id)
+            .cloned()
+            .unwrap_or_default();
+        match message
+            .get(MPC_MESSAGE_HEADER_LENGTH..)
+            .and_then(|shares| bcs::from_bytes::<DecryptionSharesMessage>(shares).ok())
+            .filter(|shares| {
+                shares
+                    .keys()
+                    .copied()
+                    .collect::<HashSet<_>>()
+                    .eq(&party_virtual_parties)
+            }) {
+            Some(shares) => {
+                decrypters.insert(party_id);
+                decryption_shares.extend(shares);
+            }
+            None => {
+                malicious_parties.insert(party_id);
+            }
+        }
+    }
+    if access_structure.is_authorized_subset(&decrypters).is_err() {
+        return Err(DwalletMPCError::MPCError(mpc::Error::ThresholdNotReached));
+    }
+
+    Ok((
+        decryption_shares,
+        malicious_parties.into_iter().sorted().collect(),
+    ))
+}
+
+/// Verifies the decrypted `key` is the discrete log of the `public_key_share` proven for its
+/// encryption, and re-encrypts it to the `encryption_key` of the requester,
+/// with a proof of its encryption.
+///
+/// Returns the serialized [`EncryptedSecretKeyShareMessage`](twopc_mpc::dkg::Protocol::EncryptedSecretKeyShareMessage),
+/// i.e., the re-encrypted key is opened like an encrypted user secret key share.
+///
+/// The randomness of the re-encryption is derived from the key and from the encryption key
+/// (and ciphertext) it is encrypted to, so that every party computes the same re-encryption,
+/// while it stays unknown to anyone who does not already know the key.
+/// Binding it to the encryption key ensures the nonces of the proof of encryption are never
+/// reused across different statements.
+fn re_encrypt_key(
+    protocol_public_parameters: &ProtocolPublicParameters,
+    encrypted_key: &[u8],
+    encryption_key: &[u8],
+    public_key_share: secp256k1::group_element::Value,
+    key: &[u8],
+) -> DwalletMPCResult<Vec<u8>> {
+    let key = twopc_mpc::secp256k1::Scalar::new(
+        bcs::from_bytes(key)?,
+        &protocol_public_parameters
+            .as_ref()
+            .scalar_group_public_parameters,
+    )
+    .map_err(|e| DwalletMPCError::ThresholdDecryptionFailed(format!("{e:?}")))?;
+    let generator = secp256k1::group_element::GroupElement::generator_from_public_parameters(
+        &protocol_public_parameters.group_public_parameters,
+    )
+    .map_err(|e| DwalletMPCError::ThresholdDecryptionFailed(format!("{e:?}")))?;
+    if (key * generator).value() != public_key_share {
+        return Err(DwalletMPCError::ThresholdDecryptionFailed(
+            "the decrypted key does not match its public key share".to_string(),
+        ));
+    }
+
+    let seed = keccak256_digest(
+        &[
+            RE_ENCRYPTION_SEED_DOMAIN,
+            &bcs::to_bytes(&key.value())?,
+            encryption_key,
+            encrypted_key,
+        ]
+        .concat(),
+    );
+    let re_encrypted_key =
+        <AsyncProtocol as twopc_mpc::dkg::Protocol>::encrypt_and_prove_centralized_party_share(
+            protocol_public_parameters,
+            bcs::from_bytes(encryption_key).map_err(|e| {
+                DwalletMPCError::ThresholdDecryptionFailed(format!(
+                    "invalid encryption key of the requester: {e}"
+                ))
+            })?,
+            key,
+            &mut ChaCha20Rng::from_seed(seed),
+        )
+        .map_err(|e| DwalletMPCError::ThresholdDecryptionFailed(format!("{e:?}")))?;
+
+    Ok(bcs::to_bytes(&re_encrypted_key)?)
+}
+
+/// Verifies the proof of encryption of a serialized [`VersionedNetworkEncryptedKey`],
+/// and returns the public key share of the encrypted key (its discrete log) and its ciphertext.
+///
+/// The proof is that of the encryption of a user secret key share,
+/// proving knowledge of the discrete log of a public key share,
+/// so the DKG output it is verified against only holds that share and the ciphertext.
+pub(crate) fn verify_network_encrypted_key(
+    protocol_public_parameters: &ProtocolPublicParameters,
+    encrypted_key: &[u8],
+) -> DwalletMPCResult<(secp256k1::group_element::Value, CiphertextSpaceGroupElement)> {
+    let VersionedNetworkEncryptedKey::V1(encrypted_key) = bcs::from_bytes(encrypted_key)
+        .map_err(|e| DwalletMPCError::InvalidNetworkEncryptedKey(e.to_string()))?;
+    let (public_key_share, encrypted_key_and_proof): (
+        secp256k1::group_element::Value,
+        <AsyncProtocol as twopc_mpc::dkg::Protocol>::EncryptedSecretKeyShareMessage,
+    ) = bcs::from_bytes(&encrypted_key)
+        .map_err(|e| DwalletMPCError::InvalidNetworkEncryptedKey(e.to_string()))?;
+    let (_, encryption_of_key) = encrypted_key_and_proof.clone();
+
+    let statement = DKGDecentralizedOutput {
+        public_key_share,
+        public_key: public_key_share,
+        centralized_party_public_key_share: public_key_share,
+        encrypted_secret_key_share: encryption_of_key,
+    };
+    let network_encryption_key = protocol_public_parameters
+        .encryption_scheme_public_parameters
+        .encryption_key
+        .clone();
+    <AsyncProtocol as twopc_mpc::dkg::Protocol>::verify_encryption_of_centralized_party_share_proof(
+        protocol_public_parameters,
+        statement,
+        network_encryption_key,
+        encrypted_key_and_proof,
+        &mut group::OsCsRng,
+    )
+    .map_err(|e| DwalletMPCError::InvalidNetworkEncryptedKey(format!("{e:?}")))?;
+
+    let ciphertext = CiphertextSpaceGroupElement::new(
+        encryption_of_key,
+        protocol_public_parameters
+            .encryption_scheme_public_parameters
+            .ciphertext_space_public_parameters(),
+    )
+    .map_err(|e| DwalletMPCError::InvalidNetworkEncryptedKey(format!("{e:?}")))?;
+
+    Ok((public_key_share, ciphertext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use class_groups::Secp256k1DecryptionKey;
+    use class_groups::setup::get_setup_parameters_secp256k1;
+    use group::{OsCsRng, Samplable};
+    use twopc_mpc::secp256k1::class_groups::{
+        FUNDAMENTAL_DISCRIMINANT_LIMBS, NON_FUNDAMENTAL_DISCRIMINANT_LIMBS,
+    };
+
+    fn generate_encryption_scheme_public_parameters()
+    -> <Secp256k1EncryptionKey as AdditivelyHomomorphicEncryptionKey<SCALAR_LIMBS>>::PublicParameters
+    {
+        let (encryption_scheme_public_parameters, _) =
+            Secp256k1DecryptionKey::generate(get_setup_parameters_secp256k1(), &mut OsCsRng)
+                .unwrap();
+        encryption_scheme_public_parameters
+    }
+
+    /// Samples a key and its public key share.
+    fn sample_key(
+        protocol_public_parameters: &ProtocolPublicParameters,
+    ) -> (
+        twopc_mpc::secp256k1::Scalar,
+        secp256k1::group_element::Value,
+    ) {
+        let key = twopc_mpc::secp256k1::Scalar::sample(
+            &protocol_public_parameters
+                .as_ref()
+                .scalar_group_public_parameters,
+            &mut OsCsRng,
+        )
+        .unwrap();
+        let generator = secp256k1::group_element::GroupElement::generator_from_public_parameters(
+            &protocol_public_parameters.group_public_parameters,
+        )
+        .unwrap();
+        (key, (key * generator).value())
+    }
+
+    #[test]
+    fn reports_malformed_decryption_shares() {
+        let access_structure =
+            WeightedThresholdAccessStructure::uniform(3, 4, 4, &mut OsCsRng).unwrap();
+        let mut empty_shares = vec![0u8; MPC_MESSAGE_HEADER_LENGTH];
+        empty_shares.extend(bcs::to_bytes(&DecryptionSharesMessage::new()).unwrap());
+        let first_round_messages = HashMap::from([
+            (1, vec![1u8; MPC_MESSAGE_HEADER_LENGTH + 8]),
+            // The shares of a party must be those of its virtual parties.
+            (2, empty_shares),
+            (3, vec![]),
+        ]);
+
+        let result = collect_decryption_shares(&access_structure, first_round_messages);
+
+        assert!(matches!(
+            result,
+            Err(DwalletMPCError::MPCError(mpc::Error::ThresholdNotReached))
+        ));
+    }
+
+    #[test]
+    fn re_encrypts_the_decrypted_key_deterministically() {
+        let protocol_public_parameters = twopc_mpc::ProtocolPublicParameters::new::<
+            { SCALAR_LIMBS },
+            { FUNDAMENTAL_DISCRIMINANT_LIMBS },
+            { NON_FUNDAMENTAL_DISCRIMINANT_LIMBS },
+            secp256k1::GroupElement,
+        >(generate_encryption_scheme_public_parameters());
+        let encryption_key =
+            bcs::to_bytes(&generate_encryption_scheme_public_parameters().encryption_key).unwrap();
+        let (key, public_key_share) = sample_key(&protocol_public_parameters);
+        let key = bcs::to_bytes(&key.value()).unwrap();
+
+        let re_encrypted_key = re_encrypt_key(
+            &protocol_public_parameters,
+            b"encrypted key",
+            &encryption_key,
+            public_key_share,
+            &key,
+        )
+        .unwrap();
+
+        // Every party must compute the very same output.
+        assert_eq!(
+            re_encrypt_key(
+                &protocol_public_parameters,
+                b"encrypted key",
+                &encryption_key,
+                public_key_share,
+                &key,
+            )
+            .unwrap(),
+            re_encrypted_key
+        );
+
+        // The re-encryption is proven like that of an encrypted user secret key share.
+        let re_encrypted_key: <AsyncProtocol as twopc_mpc::dkg::Protocol>::EncryptedSecretKeyShareMessage =
+            bcs::from_bytes(&re_encrypted_key).unwrap();
+        let (_, encryption_of_key) = re_encrypted_key.clone();
+        <AsyncProtocol as twopc_mpc::dkg::Protocol>::verify_encryption_of_centralized_party_share_proof(
+            &protocol_public_parameters,
+            DKGDecentralizedOutput {
+                public_key_share,
+                public_key: public_key_share,
+                centralized_party_public_key_share: public_key_share,
+                encrypted_secret_key_share: encryption_of_key,
+            },
+            bcs::from_bytes(&encryption_key).unwrap(),
+            re_encrypted_key,
+            &mut OsCsRng,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn refuses_to_re_encrypt_a_key_of_another_public_key_share() {
+        let protocol_public_parameters = twopc_mpc::ProtocolPublicParameters::new::<
+            { SCALAR_LIMBS },
+            { FUNDAMENTAL_DISCRIMINANT_LIMBS },
+            { NON_FUNDAMENTAL_DISCRIMINANT_LIMBS },
+            secp256k1::GroupElement,
+        >(generate_encryption_scheme_public_parameters());
+        let encryption_key =
+            bcs::to_bytes(&generate_encryption_scheme_public_parameters().encryption_key).unwrap();
+        let (key, _) = sample_key(&protocol_public_parameters);
+        let (_, other_public_key_share) = sample_key(&protocol_public_parameters);
+
+        let result = re_encrypt_key(
+            &protocol_public_parameters,
+            b"encrypted key",
+            &encryption_key,
+            other_public_key_share,
+            &bcs::to_bytes(&key.value()).unwrap(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(DwalletMPCError::ThresholdDecryptionFailed(_))
+        ));
+    }
+}
@@ -2,4 +2,5 @@
 // SPDX-License-Identifier: BSD-3-Clause-Clear
 
 pub(crate) mod encrypt_user_share;
+pub(crate) mod key_refresh;
 pub(crate) mod make_dwallet_user_secret_key_shares_public;
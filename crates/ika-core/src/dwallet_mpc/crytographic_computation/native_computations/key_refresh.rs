@@ -0,0 +1,265 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! This module provides the refresh of the secret key shares of a dWallet,
+//! re-randomizing them without changing its public key.
+//!
+//! The user samples an offset, adds it to its secret key share,
+//! and encrypts its negation to the network encryption key with a proof of its encryption.
+//! Since the encryption of the network secret key share is additively homomorphic,
+//! every party computes the refreshed dWallet output locally, so no MPC rounds are required.
+
+use crate::dwallet_mpc::encrypt_user_share::verify_encrypted_share;
+use crate::dwallet_mpc::threshold_decryption::{
+    CiphertextSpaceGroupElement, DKGDecentralizedOutput, verify_network_encrypted_key,
+};
+use dwallet_mpc_types::dwallet_mpc::VersionedDwalletDKGSecondRoundPublicOutput;
+use group::{GroupElement, secp256k1};
+use homomorphic_encryption::GroupsPublicParametersAccessors;
+use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
+use ika_types::messages_dwallet_mpc::{
+    DWalletKeyRefreshRequestEvent, DWalletSessionEvent, EncryptedShareVerificationRequestEvent,
+    MPCRequestInput, MPCSessionRequest,
+};
+use sui_types::base_types::ObjectID;
+use twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters;
+
+pub(crate) fn dwallet_key_refresh_session_request(
+    deserialized_event: DWalletSessionEvent<DWalletKeyRefreshRequestEvent>,
+) -> MPCSessionRequest {
+    MPCSessionRequest {
+        session_type: deserialized_event.session_type,
+        session_identifier: deserialized_event.session_identifier_digest(),
+        session_sequence_number: deserialized_event.session_sequence_number,
+        epoch: deserialized_event.epoch,
+        request_input: MPCRequestInput::DWalletKeyRefresh(deserialized_event),
+        requires_network_key_data: true,
+        requires_next_active_committee: false,
+    }
+}
+
+/// Computes the refreshed public output of the dWallet,
+/// and verifies the refreshed user secret key share is encrypted under it.
+///
+/// The public key share of the network moves by the public share of the encrypted offset,
+/// the public key share of the user by its negation,
+/// and the offset is homomorphically added to the encryption of the network secret key share.
+///
+/// Returns the serialized [`VersionedDwalletDKGSecondRoundPublicOutput`] of the refreshed dWallet.
+pub(crate) fn refresh_dwallet_public_output(
+    refresh_data: &DWalletKeyRefreshRequestEvent,
+    protocol_public_parameters: ProtocolPublicParameters,
+) -> DwalletMPCResult<Vec<u8>> {
+    let (refresh_public_share, encryption_of_refresh_share) = verify_network_encrypted_key(
+        &protocol_public_parameters,
+        &refresh_data.encrypted_refresh_share,
+    )?;
+    let VersionedDwalletDKGSecondRoundPublicOutput::V1(dkg_output) =
+        bcs::from_bytes(&refresh_data.dwallet_public_output)?;
+    let dkg_output: DKGDecentralizedOutput = bcs::from_bytes(&dkg_output)?;
+
+    let group_public_parameters = &protocol_public_parameters.group_public_parameters;
+    let to_group_element = |value| {
+        secp256k1::GroupElement::new(value, group_public_parameters)
+            .map_err(|e| DwalletMPCError::KeyRefreshFailed(format!("{e:?}")))
+    };
+    let refresh_public_share = to_group_element(refresh_public_share)?;
+    let public_key_share = to_group_element(dkg_output.public_key_share)? + refresh_public_share;
+    let centralized_party_public_key_share =
+        to_group_element(dkg_output.centralized_party_public_key_share)? - refresh_public_share;
+    let encrypted_secret_key_share = CiphertextSpaceGroupElement::new(
+        dkg_output.encrypted_secret_key_share,
+        protocol_public_parameters
+            .encryption_scheme_public_parameters
+            .ciphertext_space_public_parameters(),
+    )
+    .map_err(|e| DwalletMPCError::KeyRefreshFailed(format!("{e:?}")))?
+        + encryption_of_refresh_share;
+
+    let refreshed_dkg_output = bcs::to_bytes(&VersionedDwalletDKGSecondRoundPublicOutput::V1(
+        bcs::to_bytes(&DKGDecentralizedOutput {
+            public_key_share: public_key_share.value(),
+            public_key: dkg_output.public_key,
+            centralized_party_public_key_share: centralized_party_public_key_share.value(),
+            encrypted_secret_key_share: encrypted_secret_key_share.value(),
+        })?,
+    ))?;
+
+    verify_encrypted_share(
+        &EncryptedShareVerificationRequestEvent {
+            decentralized_public_output: refreshed_dkg_output.clone(),
+            encrypted_centralized_secret_share_and_proof: refresh_data
+                .encrypted_centralized_secret_share_and_proof
+                .clone(),
+            encryption_key: refresh_data.encryption_key.clone(),
+            encryption_key_id: refresh_data.encryption_key_id,
+            dwallet_network_encryption_key_id: refresh_data.dwallet_network_encryption_key_id,
+            curve: refresh_data.curve,
+
+            // Fields not relevant for verification; passing empty values.
+            dwallet_id: ObjectID::new([0; 32]),
+            source_encrypted_user_secret_key_share_id: ObjectID::new([0; 32]),
+            encrypted_user_secret_key_share_id: ObjectID::new([0; 32]),
+        },
+        protocol_public_parameters,
+    )?;
+
+    Ok(refreshed_dkg_output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use class_groups::Secp256k1DecryptionKey;
+    use class_groups::setup::get_setup_parameters_secp256k1;
+    use dwallet_mpc_centralized_party::{
+        create_key_refresh_inner, decrypt_user_share_inner,
+        generate_secp256k1_cg_keypair_from_seed_internal, verify_secret_share,
+    };
+    use dwallet_mpc_types::dwallet_mpc::VersionedDwalletUserSecretShare;
+    use group::{OsCsRng, Samplable};
+    use ika_types::messages_dwallet_mpc::AsyncProtocol;
+    use twopc_mpc::secp256k1::class_groups::{
+        FUNDAMENTAL_DISCRIMINANT_LIMBS, NON_FUNDAMENTAL_DISCRIMINANT_LIMBS,
+    };
+
+    /// Creates the public output of a dWallet, and the user secret share of the dWallet.
+    fn mock_dwallet(protocol_public_parameters: &ProtocolPublicParameters) -> (Vec<u8>, Vec<u8>) {
+        let scalar_group_public_parameters = &protocol_public_parameters
+            .as_ref()
+            .scalar_group_public_parameters;
+        let generator = secp256k1::group_element::GroupElement::generator_from_public_parameters(
+            &protocol_public_parameters.group_public_parameters,
+        )
+        .unwrap();
+        let secret_key_share =
+            twopc_mpc::secp256k1::Scalar::sample(scalar_group_public_parameters, &mut OsCsRng)
+                .unwrap();
+        let user_secret_share =
+            twopc_mpc::secp256k1::Scalar::sample(scalar_group_public_parameters, &mut OsCsRng)
+                .unwrap();
+        let (_, encrypted_secret_key_share) =
+            <AsyncProtocol as twopc_mpc::dkg::Protocol>::encrypt_and_prove_centralized_party_share(
+                protocol_public_parameters,
+                protocol_public_parameters
+                    .encryption_scheme_public_parameters
+                    .encryption_key
+                    .clone(),
+                secret_key_share,
+                &mut OsCsRng,
+            )
+            .unwrap();
+
+        let public_key_share = secret_key_share * generator;
+        let centralized_party_public_key_share = user_secret_share * generator;
+        let dwallet_public_output = bcs::to_bytes(&VersionedDwalletDKGSecondRoundPublicOutput::V1(
+            bcs::to_bytes(&DKGDecentralizedOutput {
+                public_key_share: public_key_share.value(),
+                public_key: (public_key_share + centralized_party_public_key_share).value(),
+                centralized_party_public_key_share: centralized_party_public_key_share.value(),
+                encrypted_secret_key_share,
+            })
+            .unwrap(),
+        ))
+        .unwrap();
+        let user_secret_share = bcs::to_bytes(&VersionedDwalletUserSecretShare::V1(
+            bcs::to_bytes(&user_secret_share).unwrap(),
+        ))
+        .unwrap();
+
+        (dwallet_public_output, user_secret_share)
+    }
+
+    fn dkg_output(dwallet_public_output: &[u8]) -> DKGDecentralizedOutput {
+        let VersionedDwalletDKGSecondRoundPublicOutput::V1(dkg_output) =
+            bcs::from_bytes(dwallet_public_output).unwrap();
+        bcs::from_bytes(&dkg_output).unwrap()
+    }
+
+    #[test]
+    fn refreshed_user_share_matches_the_refreshed_output() {
+        let (encryption_scheme_public_parameters, _) =
+            Secp256k1DecryptionKey::generate(get_setup_parameters_secp256k1(), &mut OsCsRng)
+                .unwrap();
+        let protocol_public_parameters = twopc_mpc::ProtocolPublicParameters::new::<
+            { twopc_mpc::secp256k1::SCALAR_LIMBS },
+            { FUNDAMENTAL_DISCRIMINANT_LIMBS },
+            { NON_FUNDAMENTAL_DISCRIMINANT_LIMBS },
+            secp256k1::GroupElement,
+        >(encryption_scheme_public_parameters);
+        let protocol_pp = bcs::to_bytes(&protocol_public_parameters).unwrap();
+        let (encryption_key, decryption_key) =
+            generate_secp256k1_cg_keypair_from_seed_internal([1; 32]).unwrap();
+        let (dwallet_public_output, user_secret_share) = mock_dwallet(&protocol_public_parameters);
+
+        let (encrypted_refresh_share, refreshed_user_secret_share, encrypted_refreshed_share) =
+            create_key_refresh_inner(
+                protocol_pp.clone(),
+                dwallet_public_output.clone(),
+                user_secret_share.clone(),
+                encryption_key.clone(),
+            )
+            .unwrap();
+        let refreshed_public_output = refresh_dwallet_public_output(
+            &DWalletKeyRefreshRequestEvent {
+                dwallet_id: ObjectID::random(),
+                dwallet_public_output: dwallet_public_output.clone(),
+                curve: 0,
+                dwallet_network_encryption_key_id: ObjectID::random(),
+                encrypted_refresh_share,
+                encrypted_user_secret_key_share_id: ObjectID::random(),
+                encrypted_centralized_secret_share_and_proof: encrypted_refreshed_share.clone(),
+                encryption_key: encryption_key.clone(),
+                encryption_key_id: ObjectID::random(),
+            },
+            protocol_public_parameters,
+        )
+        .unwrap();
+
+        // The public key of the dWallet is unchanged, while both of its shares are refreshed.
+        let dkg_output_before_refresh = dkg_output(&dwallet_public_output);
+        let refreshed_dkg_output = dkg_output(&refreshed_public_output);
+        assert_eq!(
+            refreshed_dkg_output.public_key,
+            dkg_output_before_refresh.public_key
+        );
+        assert_ne!(
+            refreshed_dkg_output.public_key_share,
+            dkg_output_before_refresh.public_key_share
+        );
+
+        let VersionedDwalletUserSecretShare::V1(refreshed_user_secret_share) =
+            bcs::from_bytes(&refreshed_user_secret_share).unwrap();
+        let VersionedDwalletUserSecretShare::V1(user_secret_share) =
+            bcs::from_bytes(&user_secret_share).unwrap();
+        assert!(
+            verify_secret_share(
+                refreshed_user_secret_share.clone(),
+                refreshed_public_output.clone(),
+                protocol_pp.clone(),
+            )
+            .unwrap()
+        );
+        assert!(
+            !verify_secret_share(
+                user_secret_share,
+                refreshed_public_output.clone(),
+                protocol_pp.clone(),
+            )
+            .unwrap()
+        );
+
+        // The refreshed share encrypted to the user decrypts to the refreshed user share.
+        assert_eq!(
+            decrypt_user_share_inner(
+                decryption_key,
+                encryption_key,
+                refreshed_public_output,
+                encrypted_refreshed_share,
+                protocol_pp,
+            )
+            .unwrap(),
+            refreshed_user_secret_share
+        );
+    }
+}
@@ -19,6 +19,8 @@
 
 use crate::dwallet_mpc::crytographic_computation::{ComputationId, ComputationRequest};
 use crate::dwallet_mpc::dwallet_mpc_metrics::DWalletMPCMetrics;
+#[cfg(unix)]
+use crate::dwallet_mpc::key_custody::KeyCustodyClient;
 use crate::runtime::IkaRuntimes;
 use dwallet_rng::RootSeed;
 use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
@@ -72,15 +74,24 @@ pub(crate) struct CryptographicComputationsOrchestrator {
     /// The list of completed cryptographic computations in the current epoch.
     completed_cryptographic_computations: HashSet<ComputationId>,
 
+    /// Where the secrets used to perform the computations are held.
+    computation_secrets: ComputationSecrets,
+}
+
+/// The secrets of the validator required for performing cryptographic computations.
+pub(crate) enum ComputationSecrets {
     /// The root seed of this validator, used for deriving the per-round seed for
     /// advancing this session.
     /// SECURITY NOTICE: *MUST KEEP PRIVATE*.
-    root_seed: RootSeed,
+    Local(RootSeed),
+    /// The secrets are held by a key custody process, which performs the computations.
+    #[cfg(unix)]
+    KeyCustody(Arc<KeyCustodyClient>),
 }
 
 impl CryptographicComputationsOrchestrator {
     /// Creates a new orchestrator for cryptographic computations.
-    pub(crate) fn try_new(root_seed: RootSeed) -> DwalletMPCResult<Self> {
+    pub(crate) fn try_new(computation_secrets: ComputationSecrets) -> DwalletMPCResult<Self> {
         let (report_computation_completed_sender, report_computation_completed_receiver) =
             tokio::sync::mpsc::channel(COMPUTATION_UPDATE_CHANNEL_SIZE);
         let mut available_cores_for_computations =
@@ -103,7 +114,7 @@ impl CryptographicComputationsOrchestrator {
             completed_computation_receiver: report_computation_completed_receiver,
             currently_running_cryptographic_computations: HashSet::new(),
             completed_cryptographic_computations: HashSet::new(),
-            root_seed,
+            computation_secrets,
         })
     }
 
@@ -151,10 +162,18 @@ impl CryptographicComputationsOrchestrator {
                     "Cryptographic computation completed successfully"
                 );
 
+                // An advance of a round consumes the messages of the previous round.
+                let received_messages_bytes: usize = computation_update
+                    .computation_request
+                    .messages
+                    .get(&mpc_round.saturating_sub(1))
+                    .map(|messages| messages.values().map(Vec::len).sum())
+                    .unwrap_or_default();
                 dwallet_mpc_metrics.add_advance_completion(
                     &request_input,
-                    &mpc_round.to_string(),
+                    mpc_round,
                     elapsed_ms as i64,
+                    received_messages_bytes as i64,
                 );
 
                 dwallet_mpc_metrics.set_last_completion_duration(
@@ -237,33 +256,66 @@ impl CryptographicComputationsOrchestrator {
         );
 
         let computation_channel_sender = self.completed_computation_sender.clone();
-        let root_seed = self.root_seed.clone();
-        rayon::spawn_fifo(move || {
-            let advance_start_time = Instant::now();
-
-            let computation_result = computation_request.clone().compute(
-                computation_id,
-                root_seed,
-                dwallet_mpc_metrics.clone(),
-            );
+        match &self.computation_secrets {
+            ComputationSecrets::Local(root_seed) => {
+                let root_seed = root_seed.clone();
+                rayon::spawn_fifo(move || {
+                    let advance_start_time = Instant::now();
 
-            let elapsed = advance_start_time.elapsed();
-            let elapsed_ms = elapsed.as_millis();
-
-            handle.spawn(async move {
-                if let Err(err) = computation_channel_sender
-                    .send(ComputationCompletionUpdate {
+                    let computation_result = computation_request.clone().compute(
                         computation_id,
-                        computation_request,
-                        computation_result,
-                        elapsed_ms,
-                    })
-                    .await
-                {
-                    error!(error=?err, "failed to send a computation completion update");
-                }
-            });
-        });
+                        root_seed,
+                        dwallet_mpc_metrics.clone(),
+                    );
+
+                    let elapsed = advance_start_time.elapsed();
+                    let elapsed_ms = elapsed.as_millis();
+
+                    handle.spawn(async move {
+                        if let Err(err) = computation_channel_sender
+                            .send(ComputationCompletionUpdate {
+                                computation_id,
+                                computation_request,
+                                computation_result,
+                                elapsed_ms,
+                            })
+                            .await
+                        {
+                            error!(error=?err, "failed to send a computation completion update");
+                        }
+                    });
+                });
+            }
+            #[cfg(unix)]
+            ComputationSecrets::KeyCustody(key_custody_client) => {
+                let key_custody_client = key_custody_client.clone();
+                handle.spawn(async move {
+                    let advance_start_time = Instant::now();
+
+                    let computation_result = key_custody_client
+                        .compute(
+                            computation_id,
+                            computation_request.request_input.clone(),
+                            computation_request.messages.clone(),
+                        )
+                        .await;
+
+                    let elapsed_ms = advance_start_time.elapsed().as_millis();
+
+                    if let Err(err) = computation_channel_sender
+                        .send(ComputationCompletionUpdate {
+                            computation_id,
+                            computation_request,
+                            computation_result,
+                            elapsed_ms,
+                        })
+                        .await
+                    {
+                        error!(error=?err, "failed to send a computation completion update");
+                    }
+                });
+            }
+        }
 
         self.currently_running_cryptographic_computations
             .insert(computation_id);
@@ -19,12 +19,30 @@
 //! - **signature_algorithm**: The signature algorithm (e.g., "ECDSA")
 //! - **mpc_round**: The specific round number within a protocol session
 
+use ika_types::crypto::AuthorityName;
 use ika_types::messages_dwallet_mpc::MPCRequestInput;
 use prometheus::{
     IntGauge, IntGaugeVec, Registry, register_int_gauge_vec_with_registry,
     register_int_gauge_with_registry,
 };
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// The accumulated cost of the completed advances of an MPC round of a protocol,
+/// over all hash schemes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundCostSample {
+    pub protocol_name: String,
+    pub curve: String,
+    pub signature_algorithm: String,
+    pub mpc_round: u64,
+    /// The number of completed advances of the round.
+    pub completions: u64,
+    /// The total duration of the completed advances, in milliseconds.
+    pub total_duration_ms: u64,
+    /// The total size of the MPC messages the completed advances consumed, in bytes.
+    pub received_messages_bytes: u64,
+}
 
 /// Prometheus metrics for DWallet MPC operations.
 ///
@@ -87,6 +105,31 @@ pub struct DWalletMPCMetrics {
     /// slow-performing protocol rounds.
     last_completion_duration: IntGaugeVec,
 
+    /// Tracks the total size of the MPC messages consumed by the completed advances
+    /// of each protocol/round combination.
+    ///
+    /// Labels: protocol_name, curve, mpc_round, hash_scheme, signature_algorithm
+    /// Value: Size in bytes.
+    ///
+    /// Recorded together with `advance_completions`, so the two give the average message volume
+    /// a round of a protocol costs, which is used to derive the recommended pricing vote.
+    received_messages_bytes: IntGaugeVec,
+
+    /// The cost of every protocol round, accumulated on each advance completion,
+    /// read by the pricing recommendation.
+    round_costs: Mutex<HashMap<(String, String, String, u64), RoundCostSample>>,
+
+    /// Tracks the number of MPC protocol sessions that timed out and were rejected.
+    ///
+    /// Labels: protocol_name, curve, hash_scheme, signature_algorithm
+    session_timeouts: IntGaugeVec,
+
+    /// Tracks the number of timed out MPC sessions each authority failed to participate in,
+    /// i.e. did not send its message for the round the session was waiting for.
+    ///
+    /// Labels: authority
+    session_timeout_non_participations: IntGaugeVec,
+
     /// The number of sign sessions in which a quorum of the expected decrypters has participated.
     pub number_of_expected_sign_sessions: IntGauge,
     /// The number of sign sessions in which less than a quorum of the expected decrypters has participated.
@@ -178,6 +221,28 @@ impl DWalletMPCMetrics {
                 registry
             )
             .unwrap(),
+            received_messages_bytes: register_int_gauge_vec_with_registry!(
+                "dwallet_mpc_received_messages_bytes",
+                "Total size of the MPC messages consumed by advance completions in bytes",
+                &round_metric_labels,
+                registry
+            )
+            .unwrap(),
+            round_costs: Mutex::new(HashMap::new()),
+            session_timeouts: register_int_gauge_vec_with_registry!(
+                "dwallet_mpc_session_timeouts",
+                "Number of MPC sessions that timed out",
+                &protocol_metric_labels,
+                registry
+            )
+            .unwrap(),
+            session_timeout_non_participations: register_int_gauge_vec_with_registry!(
+                "dwallet_mpc_session_timeout_non_participations",
+                "Number of timed out MPC sessions an authority did not participate in",
+                &["authority"],
+                registry
+            )
+            .unwrap(),
             number_of_unexpected_sign_sessions: register_int_gauge_with_registry!(
                 "dwallet_mpc_number_of_unexpected_sign_sessions",
                 "Number of unexpected sign sessions",
@@ -270,17 +335,37 @@ impl DWalletMPCMetrics {
     /// Records the successful completion of an advance call for a specific MPC round.
     ///
     /// This increments the `advance_completions` metric with labels derived from the
-    /// provided MPC event data and round information.
+    /// provided MPC event data and round information, and adds the size of the messages
+    /// the advance consumed to `received_messages_bytes`.
     ///
     /// # Arguments
     /// * `mpc_event_data` - The MPC protocol initialization data containing context
-    /// * `mpc_round` — String identifier for the specific MPC round.
+    /// * `mpc_round` — The specific MPC round.
+    /// * `duration_ms` — Duration of the computation in milliseconds.
+    /// * `received_messages_bytes` — The size of the MPC messages the advance consumed, in bytes.
     pub fn add_advance_completion(
         &self,
         mpc_event_data: &MPCRequestInput,
-        mpc_round: &str,
+        mpc_round: u64,
         duration_ms: i64,
+        received_messages_bytes: i64,
     ) {
+        self.add_round_cost(
+            mpc_event_data,
+            mpc_round,
+            duration_ms,
+            received_messages_bytes,
+        );
+        let mpc_round = &mpc_round.to_string();
+        self.received_messages_bytes
+            .with_label_values(&[
+                &mpc_event_data.to_string(),
+                &mpc_event_data.get_curve(),
+                mpc_round,
+                &mpc_event_data.get_hash_scheme(),
+                &mpc_event_data.get_signature_algorithm(),
+            ])
+            .add(received_messages_bytes);
         self.advance_completions
             .with_label_values(&[
                 &mpc_event_data.to_string(),
@@ -386,6 +471,79 @@ impl DWalletMPCMetrics {
             ])
             .set(duration_ms);
     }
+
+    fn add_round_cost(
+        &self,
+        mpc_event_data: &MPCRequestInput,
+        mpc_round: u64,
+        duration_ms: i64,
+        received_messages_bytes: i64,
+    ) {
+        let key = (
+            mpc_event_data.to_string(),
+            mpc_event_data.get_curve(),
+            mpc_event_data.get_signature_algorithm(),
+            mpc_round,
+        );
+        let mut round_costs = self
+            .round_costs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let round_cost = round_costs
+            .entry(key.clone())
+            .or_insert_with(|| RoundCostSample {
+                protocol_name: key.0,
+                curve: key.1,
+                signature_algorithm: key.2,
+                mpc_round,
+                ..Default::default()
+            });
+        round_cost.completions += 1;
+        round_cost.total_duration_ms = round_cost
+            .total_duration_ms
+            .saturating_add(duration_ms.max(0) as u64);
+        round_cost.received_messages_bytes = round_cost
+            .received_messages_bytes
+            .saturating_add(received_messages_bytes.max(0) as u64);
+    }
+
+    /// Returns the accumulated cost of every MPC round of every protocol completed so far.
+    pub fn round_costs(&self) -> Vec<RoundCostSample> {
+        self.round_costs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Records the timeout of an MPC protocol session,
+    /// and the authorities that failed to participate in it.
+    ///
+    /// # Arguments
+    /// * `mpc_event_data` - The MPC protocol initialization data containing context.
+    /// * `non_participating_authorities` — The authorities that did not send their message
+    ///   for the round the session was waiting for.
+    pub fn add_session_timeout(
+        &self,
+        mpc_event_data: &MPCRequestInput,
+        non_participating_authorities: &[AuthorityName],
+    ) {
+        self.session_timeouts
+            .with_label_values(&[
+                &mpc_event_data.to_string(),
+                &mpc_event_data.get_curve(),
+                &mpc_event_data.get_hash_scheme(),
+                &mpc_event_data.get_signature_algorithm(),
+            ])
+            .inc();
+
+        for authority in non_participating_authorities {
+            self.session_timeout_non_participations
+                .with_label_values(&[&authority.to_string()])
+                .inc();
+        }
+    }
 }
 
 /// Calculating the variance using the Welford's method.
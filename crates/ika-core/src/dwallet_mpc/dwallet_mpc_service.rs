@@ -16,9 +16,15 @@ use crate::dwallet_checkpoints::{
 };
 use crate::dwallet_mpc::crytographic_computation::ComputationId;
 use crate::dwallet_mpc::dwallet_mpc_metrics::DWalletMPCMetrics;
+#[cfg(feature = "fault-injection")]
+use crate::dwallet_mpc::fault_injection::{self, ByzantineMode};
+#[cfg(unix)]
+use crate::dwallet_mpc::key_custody::fetch_class_groups_encryption_key_and_proof;
 use crate::dwallet_mpc::mpc_manager::DWalletMPCManager;
 use crate::dwallet_mpc::mpc_session::MPCEventData;
 use crate::dwallet_mpc::party_ids_to_authority_names;
+use crate::dwallet_mpc::round_delays::RoundDelaysConfig;
+use crate::dwallet_mpc::session_timeouts::SessionTimeoutsConfig;
 use dwallet_classgroups_types::ClassGroupsKeyPairAndProof;
 use dwallet_mpc_types::dwallet_mpc::MPCDataTrait;
 use dwallet_mpc_types::dwallet_mpc::{DWalletMPCNetworkKeyScheme, MPCMessage, MPCSessionStatus};
@@ -29,10 +35,11 @@ use ika_types::committee::Committee;
 use ika_types::crypto::AuthorityName;
 use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
 use ika_types::message::{
-    DKGFirstRoundOutput, DKGSecondRoundOutput, DWalletCheckpointMessageKind,
-    DWalletImportedKeyVerificationOutput, EncryptedUserShareOutput, MPCNetworkDKGOutput,
-    MPCNetworkReconfigurationOutput, MakeDWalletUserSecretKeySharesPublicOutput,
-    PartialSignatureVerificationOutput, PresignOutput, SignOutput,
+    BatchSignOutput, DKGFirstRoundOutput, DKGSecondRoundOutput, DWalletCheckpointMessageKind,
+    DWalletImportedKeyVerificationOutput, DWalletKeyRefreshOutput, EncryptedUserShareOutput,
+    MPCNetworkDKGOutput, MPCNetworkReconfigurationOutput,
+    MakeDWalletUserSecretKeySharesPublicOutput, PartialSignatureVerificationOutput, PresignOutput,
+    SignOutput, ThresholdDecryptionOutput,
 };
 use ika_types::messages_consensus::ConsensusTransaction;
 use ika_types::messages_dwallet_mpc::{
@@ -88,13 +95,10 @@ impl DWalletMPCService {
         let epoch_id = epoch_store.epoch();
         let packages_config = epoch_store.packages_config.clone();
 
-        let network_dkg_third_round_delay = epoch_store
-            .protocol_config()
-            .network_dkg_third_round_delay();
-
-        let decryption_key_reconfiguration_third_round_delay = epoch_store
-            .protocol_config()
-            .decryption_key_reconfiguration_third_round_delay();
+        let round_delays_config =
+            RoundDelaysConfig::from_protocol_config(epoch_store.protocol_config());
+        let session_timeouts_config =
+            SessionTimeoutsConfig::from_protocol_config(epoch_store.protocol_config());
 
         let dwallet_mpc_manager = DWalletMPCManager::new(
             validator_name,
@@ -104,8 +108,8 @@ impl DWalletMPCService {
             network_keys_receiver,
             next_epoch_committee_receiver,
             node_config,
-            network_dkg_third_round_delay,
-            decryption_key_reconfiguration_third_round_delay,
+            round_delays_config,
+            session_timeouts_config,
             dwallet_mpc_metrics.clone(),
         );
 
@@ -248,6 +252,10 @@ impl DWalletMPCService {
             self.dwallet_mpc_manager
                 .handle_mpc_event_batch(events)
                 .await;
+            if let Some(next_active_committee) = &self.dwallet_mpc_manager.next_active_committee {
+                self.epoch_store
+                    .set_next_epoch_committee(next_active_committee);
+            }
 
             self.process_consensus_rounds_from_storage().await;
 
@@ -393,6 +401,11 @@ impl DWalletMPCService {
                 .dwallet_mpc_manager
                 .handle_consensus_round_outputs(consensus_round, mpc_outputs);
 
+            // Sessions that did not complete by this round and timed out are rejected.
+            let timed_out_sessions = self.dwallet_mpc_manager.time_out_sessions(consensus_round);
+            self.submit_timed_out_sessions_rejections(timed_out_sessions)
+                .await;
+
             // Now we have the MPC outputs for the current round, we can
             // add messages from the consensus output such as EndOfPublish.
             checkpoint_messages.extend(verified_dwallet_checkpoint_messages);
@@ -491,6 +504,13 @@ impl DWalletMPCService {
         let validator_name = &self.epoch_store.name;
         let party_id = self.dwallet_mpc_manager.party_id;
 
+        #[cfg(feature = "fault-injection")]
+        if !completed_computation_results.is_empty() {
+            if let Some(delay) = fault_injection::consensus_submission_delay(validator_name) {
+                tokio::time::sleep(delay).await;
+            }
+        }
+
         for (computation_id, computation_result) in completed_computation_results {
             let session_identifier = computation_id.session_identifier;
             let mpc_round = computation_id.mpc_round;
@@ -513,6 +533,27 @@ impl DWalletMPCService {
                                     "Advanced MPC session"
                                 );
 
+                                #[cfg(feature = "fault-injection")]
+                                let message = {
+                                    if fault_injection::trigger_scheduled_crash(
+                                        validator_name,
+                                        &mpc_event_data.request_input.to_string(),
+                                        mpc_round,
+                                    ) {
+                                        // Halt until the node is stopped by the crash handler.
+                                        std::future::pending::<()>().await;
+                                    }
+                                    let Some(message) = fault_injection::tamper_outgoing_message(
+                                        validator_name,
+                                        &mpc_event_data.request_input,
+                                        message,
+                                    ) else {
+                                        continue;
+                                    };
+
+                                    message
+                                };
+
                                 let message =
                                     self.new_dwallet_mpc_message(session_identifier, message);
 
@@ -563,6 +604,36 @@ impl DWalletMPCService {
 
                                 let rejected = false;
 
+                                #[cfg(feature = "fault-injection")]
+                                if fault_injection::has_byzantine_mode(
+                                    validator_name,
+                                    ByzantineMode::EquivocateOutputs,
+                                ) {
+                                    let conflicting_message = self.new_dwallet_mpc_output(
+                                        session_identifier,
+                                        &mpc_event_data,
+                                        vec![],
+                                        vec![],
+                                        true,
+                                    );
+                                    info!(
+                                        ?session_identifier,
+                                        validator=?validator_name,
+                                        "Submitting a conflicting MPC output"
+                                    );
+                                    if let Err(err) = consensus_adapter
+                                        .submit_to_consensus(&[conflicting_message], &epoch_store)
+                                        .await
+                                    {
+                                        error!(
+                                            ?session_identifier,
+                                            validator=?validator_name,
+                                            error=?err,
+                                            "failed to submit a conflicting MPC output to consensus",
+                                        );
+                                    }
+                                }
+
                                 let consensus_message = self.new_dwallet_mpc_output(
                                     session_identifier,
                                     &mpc_event_data,
@@ -663,6 +734,39 @@ impl DWalletMPCService {
         }
     }
 
+    /// Submits a rejected output to consensus for each of the `timed_out_sessions`,
+    /// so the requests on Sui are responded to once a quorum agrees on the rejection.
+    async fn submit_timed_out_sessions_rejections(
+        &self,
+        timed_out_sessions: Vec<(SessionIdentifier, MPCEventData)>,
+    ) {
+        let validator_name = &self.epoch_store.name;
+        for (session_identifier, mpc_event_data) in timed_out_sessions {
+            let rejected = true;
+
+            let consensus_message = self.new_dwallet_mpc_output(
+                session_identifier,
+                &mpc_event_data,
+                vec![],
+                vec![],
+                rejected,
+            );
+
+            if let Err(err) = self
+                .consensus_adapter
+                .submit_to_consensus(&[consensus_message], &self.epoch_store)
+                .await
+            {
+                error!(
+                    ?session_identifier,
+                    validator=?validator_name,
+                    error=?err,
+                    "failed to submit an MPC session timeout rejection to consensus"
+                );
+            }
+        }
+    }
+
     /// Create a new consensus transaction with the message to be sent to the other MPC parties.
     /// Returns Error only if the epoch switched in the middle and was not available.
     fn new_dwallet_mpc_message(
@@ -761,6 +865,61 @@ impl DWalletMPCService {
                 });
                 vec![tx]
             }
+            MPCRequestInput::DerivedKeySign(request_input) => {
+                let sign_request = &request_input.event_data.sign_request;
+                let tx = DWalletCheckpointMessageKind::RespondDWalletSign(SignOutput {
+                    signature: output,
+                    dwallet_id: sign_request.dwallet_id.to_vec(),
+                    is_future_sign: sign_request.is_future_sign,
+                    sign_id: sign_request.sign_id.to_vec(),
+                    rejected,
+                    session_sequence_number: request_input.session_sequence_number,
+                });
+                vec![tx]
+            }
+            MPCRequestInput::BatchSign(request_input) => {
+                let (signatures, rejected) = if rejected {
+                    (vec![], true)
+                } else {
+                    match bcs::from_bytes::<Vec<Vec<u8>>>(&output) {
+                        Ok(signatures) => (signatures, false),
+                        Err(e) => {
+                            error!(
+                                should_never_happen=?true,
+                                error=?e,
+                                session_identifier=?session_identifier,
+                                "failed to deserialize the signatures of a batch sign output"
+                            );
+                            (vec![], true)
+                        }
+                    }
+                };
+                let tx = DWalletCheckpointMessageKind::RespondDWalletBatchSign(BatchSignOutput {
+                    dwallet_id: request_input.event_data.dwallet_id.to_vec(),
+                    batch_sign_id: request_input.event_data.batch_sign_id.to_vec(),
+                    sign_ids: request_input
+                        .event_data
+                        .signs
+                        .iter()
+                        .map(|sign| sign.sign_id.to_vec())
+                        .collect(),
+                    signatures,
+                    rejected,
+                    session_sequence_number: request_input.session_sequence_number,
+                });
+                vec![tx]
+            }
+            MPCRequestInput::ThresholdDecryption(request_input) => {
+                let tx = DWalletCheckpointMessageKind::RespondThresholdDecryption(
+                    ThresholdDecryptionOutput {
+                        decryption_id: request_input.event_data.decryption_id.to_vec(),
+                        re_encrypted_key: output,
+                        rejected,
+                        session_sequence_number: request_input.session_sequence_number,
+                    },
+                );
+                vec![tx]
+            }
             MPCRequestInput::EncryptedShareVerification(request_input) => {
                 let tx = DWalletCheckpointMessageKind::RespondDWalletEncryptedUserShare(
                     EncryptedUserShareOutput {
@@ -775,6 +934,21 @@ impl DWalletMPCService {
                 );
                 vec![tx]
             }
+            MPCRequestInput::DWalletKeyRefresh(request_input) => {
+                let tx = DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(
+                    DWalletKeyRefreshOutput {
+                        dwallet_id: request_input.event_data.dwallet_id.to_vec(),
+                        encrypted_user_secret_key_share_id: request_input
+                            .event_data
+                            .encrypted_user_secret_key_share_id
+                            .to_vec(),
+                        public_output: output,
+                        rejected,
+                        session_sequence_number: request_input.session_sequence_number,
+                    },
+                );
+                vec![tx]
+            }
             MPCRequestInput::PartialSignatureVerification(request_input) => {
                 let tx =
                     DWalletCheckpointMessageKind::RespondDWalletPartialSignatureVerificationOutput(
@@ -921,7 +1095,7 @@ impl DWalletMPCService {
         slices
     }
 
-    pub fn verify_validator_keys(
+    pub async fn verify_validator_keys(
         epoch_start_system: &EpochStartSystem,
         config: &NodeConfig,
     ) -> DwalletMPCResult<()> {
@@ -948,14 +1122,24 @@ impl DWalletMPCService {
             ));
         }
 
-        let root_seed = config
-            .root_seed_key_pair
-            .clone()
-            .ok_or(DwalletMPCError::MissingRootSeed)?
-            .root_seed()
-            .clone();
-
-        let class_groups_key_pair = ClassGroupsKeyPairAndProof::from_seed(&root_seed);
+        let class_groups_encryption_key_and_proof = match &config.key_custody_config {
+            #[cfg(unix)]
+            Some(key_custody_config) => {
+                fetch_class_groups_encryption_key_and_proof(&key_custody_config.socket_path).await?
+            }
+            #[cfg(not(unix))]
+            Some(_) => return Err(DwalletMPCError::KeyCustodyUnsupported),
+            None => {
+                let root_seed = config
+                    .root_seed_key_pair
+                    .clone()
+                    .ok_or(DwalletMPCError::MissingRootSeed)?
+                    .root_seed()
+                    .clone();
+
+                ClassGroupsKeyPairAndProof::from_seed(&root_seed).encryption_key_and_proof()
+            }
+        };
 
         // Verify that the validators local class-groups key is the
         // same as stored in the system state object onchain.
@@ -965,7 +1149,7 @@ impl DWalletMPCService {
             .get_mpc_data()
             .unwrap()
             .class_groups_public_key_and_proof()
-            != bcs::to_bytes(&class_groups_key_pair.encryption_key_and_proof())?
+            != bcs::to_bytes(&class_groups_encryption_key_and_proof)?
         {
             return Err(DwalletMPCError::MPCManagerError(
                 "validator's class-groups key does not match the one stored in the system state object".to_string(),
@@ -0,0 +1,268 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Fault injection for testing the dWallet MPC protocols under faults.
+//!
+//! Only available with the `fault-injection` feature, and driven by `ika-swarm`, whose nodes
+//! all run in the same process: the faults of every node are kept in a process-wide registry
+//! keyed by its authority name, which the MPC service of the node consults whenever it sends
+//! or receives MPC traffic.
+//!
+//! All MPC traffic goes through consensus, so faults are injected at the boundary between
+//! the MPC service and consensus rather than in the network layer:
+//! - A partition makes a node ignore the MPC messages of the nodes on the other side of it.
+//!   MPC outputs are still processed, as all validators must handle them identically to
+//!   build the same checkpoints.
+//! - Latency delays the submission of the node's MPC messages and outputs to consensus.
+//! - A scheduled crash halts the node right before it sends its message of an MPC round,
+//!   and asks the crash handler registered for the node to stop and restart it.
+//! - A [`ByzantineMode`] makes the node deviate from the protocols in a way the other
+//!   validators must detect or tolerate.
+
+use crate::dwallet_mpc::crytographic_computation::mpc_computations::MPC_MESSAGE_HEADER_LENGTH;
+use dwallet_mpc_types::dwallet_mpc::MPCMessage;
+use ika_types::crypto::AuthorityName;
+use ika_types::messages_dwallet_mpc::MPCRequestInput;
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// A way in which a byzantine node deviates from the MPC protocols.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByzantineMode {
+    /// Corrupt the payload of every outgoing MPC message while keeping its header,
+    /// so it reaches the sessions of the other validators and fails to verify there.
+    MalformedMessages,
+    /// Submit a conflicting rejection of every session before its real output.
+    EquivocateOutputs,
+    /// Never send the MPC messages of sign and threshold decryption sessions,
+    /// which carry the node's decryption shares.
+    WithholdDecryptionShares,
+}
+
+/// Crash a node right before it sends its message of an MPC round.
+#[derive(Clone, Debug)]
+pub struct ScheduledCrash {
+    /// The protocol of the session, as displayed by [`MPCRequestInput`], or `None` for any.
+    pub protocol: Option<String>,
+    pub mpc_round: u64,
+    /// How long the node stays down before it is restarted.
+    pub restart_after: Duration,
+}
+
+/// The faults injected into a single node.
+#[derive(Clone, Debug, Default)]
+pub struct NodeFaults {
+    /// The validators whose MPC messages this node ignores.
+    pub partitioned_from: HashSet<AuthorityName>,
+    pub consensus_submission_delay: Option<Duration>,
+    /// Each crash is triggered at most once.
+    pub scheduled_crashes: Vec<ScheduledCrash>,
+    pub byzantine_modes: HashSet<ByzantineMode>,
+}
+
+/// A [`ScheduledCrash`] that was triggered, to be carried out by whoever runs the node.
+#[derive(Clone, Debug)]
+pub struct TriggeredCrash {
+    pub name: AuthorityName,
+    pub restart_after: Duration,
+}
+
+#[derive(Default)]
+struct FaultRegistry {
+    faults: HashMap<AuthorityName, NodeFaults>,
+    crash_handlers: HashMap<AuthorityName, mpsc::UnboundedSender<TriggeredCrash>>,
+}
+
+static REGISTRY: LazyLock<RwLock<FaultRegistry>> = LazyLock::new(Default::default);
+
+/// Returns the faults currently injected into the node `name`.
+pub fn node_faults(name: &AuthorityName) -> NodeFaults {
+    REGISTRY
+        .read()
+        .unwrap()
+        .faults
+        .get(name)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Updates the faults injected into the node `name`.
+pub fn update_node_faults(name: AuthorityName, update: impl FnOnce(&mut NodeFaults)) {
+    update(REGISTRY.write().unwrap().faults.entry(name).or_default());
+}
+
+/// Removes all the faults injected into the node `name`, keeping its crash handler.
+pub fn clear_node_faults(name: &AuthorityName) {
+    REGISTRY.write().unwrap().faults.remove(name);
+}
+
+/// Sends the crashes triggered in the node `name` to `handler`.
+pub fn set_crash_handler(name: AuthorityName, handler: mpsc::UnboundedSender<TriggeredCrash>) {
+    REGISTRY
+        .write()
+        .unwrap()
+        .crash_handlers
+        .insert(name, handler);
+}
+
+/// Removes the faults and the crash handler of the node `name`.
+pub fn remove_node(name: &AuthorityName) {
+    let mut registry = REGISTRY.write().unwrap();
+    registry.faults.remove(name);
+    registry.crash_handlers.remove(name);
+}
+
+/// Whether the MPC messages between `name` and `peer` are dropped by a partition.
+pub(crate) fn is_partitioned(name: &AuthorityName, peer: &AuthorityName) -> bool {
+    let registry = REGISTRY.read().unwrap();
+    let partitioned = |from: &AuthorityName, to: &AuthorityName| {
+        registry
+            .faults
+            .get(from)
+            .is_some_and(|faults| faults.partitioned_from.contains(to))
+    };
+
+    partitioned(name, peer) || partitioned(peer, name)
+}
+
+pub(crate) fn consensus_submission_delay(name: &AuthorityName) -> Option<Duration> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .faults
+        .get(name)
+        .and_then(|faults| faults.consensus_submission_delay)
+}
+
+pub(crate) fn has_byzantine_mode(name: &AuthorityName, mode: ByzantineMode) -> bool {
+    REGISTRY
+        .read()
+        .unwrap()
+        .faults
+        .get(name)
+        .is_some_and(|faults| faults.byzantine_modes.contains(&mode))
+}
+
+/// Applies the byzantine modes of the node `name` to an outgoing MPC message.
+///
+/// Returns `None` if the message should be withheld.
+pub(crate) fn tamper_outgoing_message(
+    name: &AuthorityName,
+    request_input: &MPCRequestInput,
+    mut message: MPCMessage,
+) -> Option<MPCMessage> {
+    if matches!(
+        request_input,
+        MPCRequestInput::Sign(_)
+            | MPCRequestInput::DerivedKeySign(_)
+            | MPCRequestInput::BatchSign(_)
+            | MPCRequestInput::ThresholdDecryption(_)
+    ) && has_byzantine_mode(name, ByzantineMode::WithholdDecryptionShares)
+    {
+        info!(authority=?name, mpc_protocol=%request_input, "Withholding decryption shares");
+        return None;
+    }
+    if has_byzantine_mode(name, ByzantineMode::MalformedMessages) {
+        info!(authority=?name, mpc_protocol=%request_input, "Sending a malformed MPC message");
+        message
+            .iter_mut()
+            .skip(MPC_MESSAGE_HEADER_LENGTH)
+            .for_each(|byte| *byte = !*byte);
+    }
+
+    Some(message)
+}
+
+/// Triggers the first crash scheduled for the node `name` at `mpc_round` of `protocol`.
+///
+/// Returns whether a crash was triggered, in which case the node must halt
+/// until it is stopped by its crash handler.
+pub(crate) fn trigger_scheduled_crash(
+    name: &AuthorityName,
+    protocol: &str,
+    mpc_round: u64,
+) -> bool {
+    let mut registry = REGISTRY.write().unwrap();
+    let Some(faults) = registry.faults.get_mut(name) else {
+        return false;
+    };
+    let Some(position) = faults.scheduled_crashes.iter().position(|crash| {
+        crash.mpc_round == mpc_round
+            && crash
+                .protocol
+                .as_ref()
+                .is_none_or(|crash_protocol| crash_protocol == protocol)
+    }) else {
+        return false;
+    };
+    let crash = faults.scheduled_crashes.remove(position);
+
+    let Some(handler) = registry.crash_handlers.get(name) else {
+        warn!(authority=?name, "no crash handler is registered, ignoring a scheduled crash");
+        return false;
+    };
+    info!(
+        authority=?name,
+        protocol,
+        mpc_round,
+        "Triggering a scheduled crash"
+    );
+
+    handler
+        .send(TriggeredCrash {
+            name: *name,
+            restart_after: crash.restart_after,
+        })
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ika_types::crypto::{AuthorityPublicKey, AuthorityPublicKeyBytes};
+
+    // The registry is shared by all the tests, so each test uses its own names.
+    fn name(byte: u8) -> AuthorityName {
+        AuthorityPublicKeyBytes([byte; AuthorityPublicKey::LENGTH])
+    }
+
+    #[test]
+    fn test_partition_is_symmetric() {
+        let (first, second, third) = (name(1), name(2), name(3));
+        update_node_faults(first, |faults| {
+            faults.partitioned_from.insert(second);
+        });
+
+        assert!(is_partitioned(&first, &second));
+        assert!(is_partitioned(&second, &first));
+        assert!(!is_partitioned(&first, &third));
+
+        clear_node_faults(&first);
+        assert!(!is_partitioned(&second, &first));
+    }
+
+    #[test]
+    fn test_scheduled_crash_triggers_once() {
+        let node = name(4);
+        let (handler, mut crashes) = mpsc::unbounded_channel();
+        set_crash_handler(node, handler);
+        update_node_faults(node, |faults| {
+            faults.scheduled_crashes.push(ScheduledCrash {
+                protocol: Some("Presign".to_string()),
+                mpc_round: 2,
+                restart_after: Duration::from_secs(1),
+            });
+        });
+
+        assert!(!trigger_scheduled_crash(&node, "Sign", 2));
+        assert!(!trigger_scheduled_crash(&node, "Presign", 1));
+        assert!(trigger_scheduled_crash(&node, "Presign", 2));
+        assert!(!trigger_scheduled_crash(&node, "Presign", 2));
+        assert_eq!(crashes.try_recv().unwrap().name, node);
+
+        remove_node(&node);
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Isolation of the validator's long-term MPC secrets in a separate key custody process.
+//!
+//! By default, `ika-node` holds the root seed, and with it the class groups decryption key
+//! and the decryption key shares of every network key, in the same process that runs
+//! networking and RPC.
+//! When a `key-custody-config` is set, these secrets are held by the `ika-key-custody`
+//! process instead, and the node never loads them.
+//! Every MPC round is derived from the root seed, so the custody process performs all
+//! cryptographic computations on behalf of the node: it computes the private input and
+//! the decryption key shares of a session from its own secrets, and the node only ever
+//! sees the resulting round messages and public outputs.
+//! As the randomness of a round is derived from the root seed and the round alone, the
+//! custody process refuses to compute the same round of an epoch twice over different inputs.
+//!
+//! The node and the custody process communicate over a local Unix socket.
+//! Every message is a [`KeyCustodyRequest`] or a [`KeyCustodyResponse`], encoded with BCS
+//! and prefixed by its length as a big-endian `u32`.
+//! A connection carries any number of request-response pairs, in order.
+//!
+//! The custody process keeps no state across restarts except the root seed:
+//! the public state of an epoch (the committee, the next committee and the network keys)
+//! is pushed to it by the node, which re-sends it whenever the custody process reports
+//! that it doesn't know the epoch of a request.
+
+use crate::dwallet_mpc::crytographic_computation::ComputationId;
+use crate::dwallet_mpc::mpc_session::MPCRoundToMessagesHashMap;
+use group::PartyID;
+use ika_types::committee::{ClassGroupsEncryptionKeyAndProof, Committee, EpochId};
+use ika_types::crypto::AuthorityName;
+use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
+use ika_types::messages_dwallet_mpc::{DWalletNetworkEncryptionKeyData, MPCRequestInput};
+use mpc::GuaranteedOutputDeliveryRoundResult;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sui_types::base_types::ObjectID;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+mod client;
+mod server;
+
+pub(crate) use client::KeyCustodyClient;
+pub use client::fetch_class_groups_encryption_key_and_proof;
+pub use server::KeyCustodyServer;
+
+/// The maximal size of a single message, 256 MiB.
+/// The largest messages are the `Compute` requests of network key sessions, which carry
+/// the network key data and the messages of every party in every round so far.
+const MAX_MESSAGE_SIZE: usize = 256 << 20;
+
+/// A request from the node to the key custody process.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum KeyCustodyRequest {
+    /// Get the class groups encryption key and proof derived from the root seed,
+    /// to verify it against the one registered on-chain.
+    ClassGroupsEncryptionKeyAndProof,
+    /// Start a new epoch, discarding all the state of the previous one.
+    InitializeEpoch {
+        validator_name: AuthorityName,
+        committee: Committee,
+    },
+    /// Set the committee of the next epoch, required for network key reconfiguration.
+    SetNextActiveCommittee {
+        epoch: EpochId,
+        next_active_committee: Committee,
+    },
+    /// Instantiate a network key and decrypt this validator's shares of it.
+    UpdateNetworkKey {
+        epoch: EpochId,
+        key_id: ObjectID,
+        key_data: DWalletNetworkEncryptionKeyData,
+    },
+    /// Advance an MPC session by a single round.
+    Compute {
+        epoch: EpochId,
+        computation_id: ComputationId,
+        request_input: MPCRequestInput,
+        messages: MPCRoundToMessagesHashMap,
+    },
+}
+
+/// The response of the key custody process to a [`KeyCustodyRequest`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum KeyCustodyResponse {
+    Ok,
+    ClassGroupsEncryptionKeyAndProof(ClassGroupsEncryptionKeyAndProof),
+    /// The request refers to an epoch the custody process wasn't initialized for.
+    EpochNotInitialized {
+        epoch: EpochId,
+    },
+    RoundResult(KeyCustodyRoundResult),
+    /// The round must be retried with more messages, see [`mpc::Error::ThresholdNotReached`].
+    ThresholdNotReached,
+    Error(String),
+}
+
+/// The serializable form of [`GuaranteedOutputDeliveryRoundResult`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum KeyCustodyRoundResult {
+    Advance {
+        message: Vec<u8>,
+    },
+    Finalize {
+        public_output_value: Vec<u8>,
+        malicious_parties: Vec<PartyID>,
+        private_output: Vec<u8>,
+    },
+}
+
+impl From<GuaranteedOutputDeliveryRoundResult> for KeyCustodyRoundResult {
+    fn from(result: GuaranteedOutputDeliveryRoundResult) -> Self {
+        match result {
+            GuaranteedOutputDeliveryRoundResult::Advance { message } => Self::Advance { message },
+            GuaranteedOutputDeliveryRoundResult::Finalize {
+                public_output_value,
+                malicious_parties,
+                private_output,
+            } => Self::Finalize {
+                public_output_value,
+                malicious_parties,
+                private_output,
+            },
+        }
+    }
+}
+
+impl From<KeyCustodyRoundResult> for GuaranteedOutputDeliveryRoundResult {
+    fn from(result: KeyCustodyRoundResult) -> Self {
+        match result {
+            KeyCustodyRoundResult::Advance { message } => Self::Advance { message },
+            KeyCustodyRoundResult::Finalize {
+                public_output_value,
+                malicious_parties,
+                private_output,
+            } => Self::Finalize {
+                public_output_value,
+                malicious_parties,
+                private_output,
+            },
+        }
+    }
+}
+
+/// Writes a single length-prefixed BCS message to `writer`.
+async fn write_message<W: AsyncWrite + Unpin, T: Serialize>(
+    writer: &mut W,
+    message: &T,
+) -> DwalletMPCResult<()> {
+    let bytes = bcs::to_bytes(message)?;
+    if bytes.len() > MAX_MESSAGE_SIZE {
+        return Err(DwalletMPCError::KeyCustody(format!(
+            "message of {} bytes exceeds the maximal size",
+            bytes.len()
+        )));
+    }
+    writer
+        .write_u32(bytes.len() as u32)
+        .await
+        .map_err(io_error)?;
+    writer.write_all(&bytes).await.map_err(io_error)?;
+    writer.flush().await.map_err(io_error)
+}
+
+/// Reads a single length-prefixed BCS message from `reader`.
+///
+/// Returns `None` if the connection was closed before a new message started.
+async fn read_message<R: AsyncRead + Unpin, T: DeserializeOwned>(
+    reader: &mut R,
+) -> DwalletMPCResult<Option<T>> {
+    let length = match reader.read_u32().await {
+        Ok(length) => length as usize,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(io_error(e)),
+    };
+    if length > MAX_MESSAGE_SIZE {
+        return Err(DwalletMPCError::KeyCustody(format!(
+            "message of {length} bytes exceeds the maximal size"
+        )));
+    }
+    // The buffer grows with the bytes actually received rather than with the length prefix.
+    let mut bytes = Vec::new();
+    reader
+        .take(length as u64)
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(io_error)?;
+    if bytes.len() != length {
+        return Err(DwalletMPCError::KeyCustody(format!(
+            "the connection was closed after {} of {length} message bytes",
+            bytes.len()
+        )));
+    }
+    Ok(Some(bcs::from_bytes(&bytes)?))
+}
+
+fn io_error(e: std::io::Error) -> DwalletMPCError {
+    DwalletMPCError::KeyCustody(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_message_round_trip() {
+        let (mut node, mut custody) = tokio::io::duplex(1024);
+
+        write_message(&mut node, &KeyCustodyResponse::ThresholdNotReached)
+            .await
+            .unwrap();
+        write_message(
+            &mut node,
+            &KeyCustodyResponse::RoundResult(KeyCustodyRoundResult::Advance {
+                message: vec![1, 2, 3],
+            }),
+        )
+        .await
+        .unwrap();
+        drop(node);
+
+        assert!(matches!(
+            read_message(&mut custody).await.unwrap(),
+            Some(KeyCustodyResponse::ThresholdNotReached)
+        ));
+        let Some(KeyCustodyResponse::RoundResult(KeyCustodyRoundResult::Advance { message })) =
+            read_message(&mut custody).await.unwrap()
+        else {
+            panic!("unexpected message");
+        };
+        assert_eq!(message, vec![1, 2, 3]);
+        assert!(
+            read_message::<_, KeyCustodyResponse>(&mut custody)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_and_truncated_messages_are_rejected() {
+        let (mut node, mut custody) = tokio::io::duplex(1024);
+        node.write_u32(MAX_MESSAGE_SIZE as u32 + 1).await.unwrap();
+        assert!(
+            read_message::<_, KeyCustodyResponse>(&mut custody)
+                .await
+                .is_err()
+        );
+
+        let (mut node, mut custody) = tokio::io::duplex(1024);
+        node.write_u32(1_000).await.unwrap();
+        node.write_all(&[0; 10]).await.unwrap();
+        drop(node);
+        assert!(
+            read_message::<_, KeyCustodyResponse>(&mut custody)
+                .await
+                .is_err()
+        );
+    }
+}
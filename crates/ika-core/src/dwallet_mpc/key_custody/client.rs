@@ -0,0 +1,268 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use crate::dwallet_mpc::crytographic_computation::ComputationId;
+use crate::dwallet_mpc::key_custody::{
+    KeyCustodyRequest, KeyCustodyResponse, read_message, write_message,
+};
+use crate::dwallet_mpc::mpc_session::MPCRoundToMessagesHashMap;
+use ika_types::committee::{ClassGroupsEncryptionKeyAndProof, Committee};
+use ika_types::crypto::AuthorityName;
+use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
+use ika_types::messages_dwallet_mpc::{DWalletNetworkEncryptionKeyData, MPCRequestInput};
+use mpc::GuaranteedOutputDeliveryRoundResult;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use sui_types::base_types::ObjectID;
+use tokio::net::UnixStream;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Sends a single request to the key custody process listening on `socket_path`.
+async fn send_request(
+    socket_path: &Path,
+    request: &KeyCustodyRequest,
+) -> DwalletMPCResult<KeyCustodyResponse> {
+    let mut stream = UnixStream::connect(socket_path).await.map_err(|e| {
+        DwalletMPCError::KeyCustody(format!(
+            "failed to connect to the key custody process at {}: {e}",
+            socket_path.display()
+        ))
+    })?;
+    write_message(&mut stream, request).await?;
+    read_message(&mut stream).await?.ok_or_else(|| {
+        DwalletMPCError::KeyCustody("the key custody process closed the connection".to_string())
+    })
+}
+
+fn expect_ok(response: KeyCustodyResponse) -> DwalletMPCResult<()> {
+    match response {
+        KeyCustodyResponse::Ok => Ok(()),
+        KeyCustodyResponse::Error(error) => Err(DwalletMPCError::KeyCustody(error)),
+        response => Err(DwalletMPCError::KeyCustody(format!(
+            "unexpected key custody response: {response:?}"
+        ))),
+    }
+}
+
+/// Fetches the class groups encryption key and proof of the validator from the key custody
+/// process listening on `socket_path`.
+pub async fn fetch_class_groups_encryption_key_and_proof(
+    socket_path: &Path,
+) -> DwalletMPCResult<ClassGroupsEncryptionKeyAndProof> {
+    match send_request(
+        socket_path,
+        &KeyCustodyRequest::ClassGroupsEncryptionKeyAndProof,
+    )
+    .await?
+    {
+        KeyCustodyResponse::ClassGroupsEncryptionKeyAndProof(encryption_key_and_proof) => {
+            Ok(encryption_key_and_proof)
+        }
+        KeyCustodyResponse::Error(error) => Err(DwalletMPCError::KeyCustody(error)),
+        response => Err(DwalletMPCError::KeyCustody(format!(
+            "unexpected key custody response: {response:?}"
+        ))),
+    }
+}
+
+/// The public state of the epoch, as last pushed to the key custody process.
+#[derive(Default)]
+struct EpochState {
+    /// Whether the key custody process holds the state below.
+    /// Cleared whenever it might not, e.g. after it restarted.
+    initialized: bool,
+    next_active_committee: Option<Committee>,
+    network_keys: HashMap<ObjectID, DWalletNetworkEncryptionKeyData>,
+}
+
+/// The node's side of the connection to the key custody process, for a single epoch.
+///
+/// The client keeps a copy of all the public state it pushed to the custody process,
+/// so it can restore it if the custody process restarts mid-epoch.
+pub(crate) struct KeyCustodyClient {
+    socket_path: PathBuf,
+    validator_name: AuthorityName,
+    committee: Committee,
+    state: Mutex<EpochState>,
+}
+
+impl KeyCustodyClient {
+    pub(crate) fn new(
+        socket_path: PathBuf,
+        validator_name: AuthorityName,
+        committee: Committee,
+    ) -> Self {
+        Self {
+            socket_path,
+            validator_name,
+            committee,
+            state: Mutex::new(EpochState::default()),
+        }
+    }
+
+    /// Pushes the entire public state of the epoch to the key custody process,
+    /// unless it already holds it.
+    async fn ensure_initialized(&self, state: &mut EpochState) -> DwalletMPCResult<()> {
+        if state.initialized {
+            return Ok(());
+        }
+
+        info!(
+            epoch = self.committee.epoch,
+            socket_path=?self.socket_path,
+            "Initializing the key custody process for the epoch"
+        );
+        expect_ok(
+            send_request(
+                &self.socket_path,
+                &KeyCustodyRequest::InitializeEpoch {
+                    validator_name: self.validator_name,
+                    committee: self.committee.clone(),
+                },
+            )
+            .await?,
+        )?;
+        if let Some(next_active_committee) = &state.next_active_committee {
+            expect_ok(
+                send_request(
+                    &self.socket_path,
+                    &KeyCustodyRequest::SetNextActiveCommittee {
+                        epoch: self.committee.epoch,
+                        next_active_committee: next_active_committee.clone(),
+                    },
+                )
+                .await?,
+            )?;
+        }
+        for (key_id, key_data) in &state.network_keys {
+            expect_ok(
+                send_request(
+                    &self.socket_path,
+                    &KeyCustodyRequest::UpdateNetworkKey {
+                        epoch: self.committee.epoch,
+                        key_id: *key_id,
+                        key_data: key_data.clone(),
+                    },
+                )
+                .await?,
+            )?;
+        }
+        state.initialized = true;
+
+        Ok(())
+    }
+
+    /// Sends a request that updates the public state of the epoch,
+    /// after it was recorded in `state`.
+    async fn update_state(
+        &self,
+        state: &mut EpochState,
+        request: KeyCustodyRequest,
+    ) -> DwalletMPCResult<()> {
+        if !state.initialized {
+            // Initialization pushes the recorded state, including this update.
+            return self.ensure_initialized(state).await;
+        }
+
+        let result = match send_request(&self.socket_path, &request).await {
+            Ok(KeyCustodyResponse::EpochNotInitialized { .. }) => {
+                state.initialized = false;
+                self.ensure_initialized(state).await
+            }
+            Ok(response) => expect_ok(response),
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            state.initialized = false;
+        }
+
+        result
+    }
+
+    pub(crate) async fn set_next_active_committee(
+        &self,
+        next_active_committee: Committee,
+    ) -> DwalletMPCResult<()> {
+        let mut state = self.state.lock().await;
+        state.next_active_committee = Some(next_active_committee.clone());
+        self.update_state(
+            &mut state,
+            KeyCustodyRequest::SetNextActiveCommittee {
+                epoch: self.committee.epoch,
+                next_active_committee,
+            },
+        )
+        .await
+    }
+
+    pub(crate) async fn update_network_key(
+        &self,
+        key_id: ObjectID,
+        key_data: DWalletNetworkEncryptionKeyData,
+    ) -> DwalletMPCResult<()> {
+        let mut state = self.state.lock().await;
+        state.network_keys.insert(key_id, key_data.clone());
+        self.update_state(
+            &mut state,
+            KeyCustodyRequest::UpdateNetworkKey {
+                epoch: self.committee.epoch,
+                key_id,
+                key_data,
+            },
+        )
+        .await
+    }
+
+    /// Advances an MPC session by a single round in the key custody process.
+    ///
+    /// A [`mpc::Error::ThresholdNotReached`] error is returned as is,
+    /// so the caller can tell it apart from other failures.
+    pub(crate) async fn compute(
+        &self,
+        computation_id: ComputationId,
+        request_input: MPCRequestInput,
+        messages: MPCRoundToMessagesHashMap,
+    ) -> DwalletMPCResult<GuaranteedOutputDeliveryRoundResult> {
+        let request = KeyCustodyRequest::Compute {
+            epoch: self.committee.epoch,
+            computation_id,
+            request_input,
+            messages,
+        };
+
+        // Retry once if the custody process lost the state of the epoch.
+        for _ in 0..2 {
+            self.ensure_initialized(&mut *self.state.lock().await)
+                .await?;
+
+            match send_request(&self.socket_path, &request).await? {
+                KeyCustodyResponse::RoundResult(result) => return Ok(result.into()),
+                KeyCustodyResponse::ThresholdNotReached => {
+                    return Err(DwalletMPCError::MPCError(mpc::Error::ThresholdNotReached));
+                }
+                KeyCustodyResponse::EpochNotInitialized { epoch } => {
+                    warn!(
+                        epoch,
+                        session_identifier=?computation_id.session_identifier,
+                        "the key custody process isn't initialized for the epoch, re-initializing"
+                    );
+                    self.state.lock().await.initialized = false;
+                }
+                KeyCustodyResponse::Error(error) => {
+                    return Err(DwalletMPCError::KeyCustody(error));
+                }
+                response => {
+                    return Err(DwalletMPCError::KeyCustody(format!(
+                        "unexpected key custody response: {response:?}"
+                    )));
+                }
+            }
+        }
+
+        Err(DwalletMPCError::KeyCustody(format!(
+            "the key custody process failed to initialize for epoch {}",
+            self.committee.epoch
+        )))
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use crate::dwallet_mpc::crytographic_computation::{ComputationId, ComputationRequest};
+use crate::dwallet_mpc::dwallet_mpc_metrics::DWalletMPCMetrics;
+use crate::dwallet_mpc::key_custody::{
+    KeyCustodyRequest, KeyCustodyResponse, read_message, write_message,
+};
+use crate::dwallet_mpc::mpc_session::{
+    MPCRoundToMessagesHashMap, decryption_key_shares_for_request, session_input_from_event,
+};
+use crate::dwallet_mpc::network_dkg::{DwalletMPCNetworkKeys, ValidatorPrivateDecryptionKeyData};
+use crate::dwallet_mpc::network_key_cache::NetworkKeyCache;
+use crate::dwallet_mpc::{
+    authority_name_to_party_id_from_committee, generate_access_structure_from_committee,
+    get_validators_class_groups_public_keys_and_proofs,
+};
+use dwallet_classgroups_types::ClassGroupsKeyPairAndProof;
+use dwallet_rng::RootSeed;
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use group::PartyID;
+use ika_types::committee::{ClassGroupsEncryptionKeyAndProof, Committee};
+use ika_types::crypto::AuthorityName;
+use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
+use ika_types::messages_dwallet_mpc::{
+    DWalletNetworkEncryptionKeyData, MPCRequestInput, SessionIdentifier,
+};
+use mpc::{GuaranteedOutputDeliveryRoundResult, WeightedThresholdAccessStructure};
+use prometheus::Registry;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{Mutex, oneshot};
+use tracing::{error, info, warn};
+
+/// The state of the epoch the key custody process was initialized for.
+struct EpochState {
+    validator_name: AuthorityName,
+    party_id: PartyID,
+    committee: Arc<Committee>,
+    access_structure: WeightedThresholdAccessStructure,
+    validators_class_groups_public_keys_and_proofs:
+        HashMap<PartyID, ClassGroupsEncryptionKeyAndProof>,
+    next_active_committee: Option<Committee>,
+    /// The network keys, together with this validator's decryption key shares of them.
+    network_keys: DwalletMPCNetworkKeys,
+    /// The digest of the input of every round computed in this epoch, by the key its
+    /// randomness is derived from.
+    served_rounds: HashMap<RoundRngKey, [u8; 32]>,
+}
+
+/// The session identifier, MPC round and attempt number of a computation, from which the
+/// randomness of the round is derived.
+type RoundRngKey = (SessionIdentifier, u64, u64);
+
+/// The key custody process: holds the root seed of a validator,
+/// and performs the MPC computations of its node over a Unix socket.
+///
+/// See the [module documentation](crate::dwallet_mpc::key_custody) for the protocol.
+pub struct KeyCustodyServer {
+    /// SECURITY NOTICE: *MUST KEEP PRIVATE*.
+    root_seed: RootSeed,
+    class_groups_key_pair: ClassGroupsKeyPairAndProof,
+    /// The directory of the persistent network key cache, if enabled.
+    network_key_cache_path: Option<PathBuf>,
+    epoch_state: Mutex<Option<EpochState>>,
+    dwallet_mpc_metrics: Arc<DWalletMPCMetrics>,
+}
+
+impl KeyCustodyServer {
+    pub fn new(
+        root_seed: RootSeed,
+        network_key_cache_path: Option<PathBuf>,
+        registry: &Registry,
+    ) -> Self {
+        let class_groups_key_pair = ClassGroupsKeyPairAndProof::from_seed(&root_seed);
+
+        Self {
+            root_seed,
+            class_groups_key_pair,
+            network_key_cache_path,
+            epoch_state: Mutex::new(None),
+            dwallet_mpc_metrics: DWalletMPCMetrics::new(registry),
+        }
+    }
+
+    /// Serves node requests on `socket_path` until the process exits.
+    ///
+    /// The socket is only accessible to the user running the custody process,
+    /// so the node must run under the same user.
+    pub async fn run(self: Arc<Self>, socket_path: &Path) -> anyhow::Result<()> {
+        if socket_path.exists() {
+            std::fs::remove_file(socket_path)?;
+        }
+        let listener = UnixListener::bind(socket_path)?;
+        std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o600))?;
+        info!(?socket_path, "Key custody process listening");
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_connection(stream).await {
+                    error!(error=?e, "key custody connection failed");
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: UnixStream) -> DwalletMPCResult<()> {
+        while let Some(request) = read_message::<_, KeyCustodyRequest>(&mut stream).await? {
+            let response = self.handle_request(request).await;
+            write_message(&mut stream, &response).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_request(&self, request: KeyCustodyRequest) -> KeyCustodyResponse {
+        match self.try_handle_request(request).await {
+            Ok(response) => response,
+            Err(DwalletMPCError::MPCError(mpc::Error::ThresholdNotReached)) => {
+                KeyCustodyResponse::ThresholdNotReached
+            }
+            Err(e) => KeyCustodyResponse::Error(e.to_string()),
+        }
+    }
+
+    async fn try_handle_request(
+        &self,
+        request: KeyCustodyRequest,
+    ) -> DwalletMPCResult<KeyCustodyResponse> {
+        match request {
+            KeyCustodyRequest::ClassGroupsEncryptionKeyAndProof => {
+                Ok(KeyCustodyResponse::ClassGroupsEncryptionKeyAndProof(
+                    self.class_groups_key_pair.encryption_key_and_proof(),
+                ))
+            }
+            KeyCustodyRequest::InitializeEpoch {
+                validator_name,
+                committee,
+            } => {
+                self.initialize_epoch(validator_name, committee).await?;
+                Ok(KeyCustodyResponse::Ok)
+            }
+            KeyCustodyRequest::SetNextActiveCommittee {
+                epoch,
+                next_active_committee,
+            } => match self.epoch_state.lock().await.as_mut() {
+                Some(state) if state.committee.epoch == epoch => {
+                    state.next_active_committee = Some(next_active_committee);
+                    Ok(KeyCustodyResponse::Ok)
+                }
+                _ => Ok(KeyCustodyResponse::EpochNotInitialized { epoch }),
+            },
+            KeyCustodyRequest::UpdateNetworkKey {
+                epoch,
+                key_id,
+                key_data,
+            } => match self.epoch_state.lock().await.as_mut() {
+                Some(state) if state.committee.epoch == epoch => {
+                    Self::update_network_key(state, key_id, key_data).await?;
+                    Ok(KeyCustodyResponse::Ok)
+                }
+                _ => Ok(KeyCustodyResponse::EpochNotInitialized { epoch }),
+            },
+            KeyCustodyRequest::Compute {
+                epoch,
+                computation_id,
+                request_input,
+                messages,
+            } => {
+                // Release the state before computing, so computations run in parallel.
+                let computation_request = match self.epoch_state.lock().await.as_mut() {
+                    Some(state) if state.committee.epoch == epoch => {
+                        Self::record_served_round(
+                            &mut state.served_rounds,
+                            computation_id,
+                            &request_input,
+                            &messages,
+                        )?;
+                        Self::computation_request(state, computation_id, request_input, messages)?
+                    }
+                    _ => return Ok(KeyCustodyResponse::EpochNotInitialized { epoch }),
+                };
+                let result = self.compute(computation_id, computation_request).await?;
+
+                Ok(KeyCustodyResponse::RoundResult(result.into()))
+            }
+        }
+    }
+
+    async fn initialize_epoch(
+        &self,
+        validator_name: AuthorityName,
+        committee: Committee,
+    ) -> DwalletMPCResult<()> {
+        let party_id = authority_name_to_party_id_from_committee(&committee, &validator_name)?;
+        let validator_private_data = ValidatorPrivateDecryptionKeyData {
+            party_id,
+            class_groups_decryption_key: self.class_groups_key_pair.decryption_key(),
+            validator_decryption_key_shares: HashMap::new(),
+        };
+        let network_key_cache = self.network_key_cache_path.clone().and_then(|path| {
+            NetworkKeyCache::new(path, &self.root_seed)
+                .inspect_err(|e| {
+                    warn!(error=?e, "failed to open the network key cache");
+                })
+                .ok()
+        });
+
+        let state = EpochState {
+            validator_name,
+            party_id,
+            access_structure: generate_access_structure_from_committee(&committee)?,
+            validators_class_groups_public_keys_and_proofs:
+                get_validators_class_groups_public_keys_and_proofs(&committee)?,
+            committee: Arc::new(committee),
+            next_active_committee: None,
+            network_keys: DwalletMPCNetworkKeys::new(
+                Some(validator_private_data),
+                network_key_cache,
+            ),
+            served_rounds: HashMap::new(),
+        };
+        info!(
+            epoch = state.committee.epoch,
+            party_id, "Initialized the key custody process for the epoch"
+        );
+        *self.epoch_state.lock().await = Some(state);
+
+        Ok(())
+    }
+
+    async fn update_network_key(
+        state: &mut EpochState,
+        key_id: ObjectID,
+        key_data: DWalletNetworkEncryptionKeyData,
+    ) -> DwalletMPCResult<()> {
+        info!(
+            ?key_id,
+            "Decrypting the validator's shares of a network key"
+        );
+
+        state
+            .network_keys
+            .update_network_key(
+                key_id,
+                key_data,
+                state.committee.epoch,
+                &state.access_structure,
+            )
+            .await
+    }
+
+    /// Records that a round is computed with the given input, refusing to compute it again
+    /// with a different one.
+    ///
+    /// The randomness of a round is derived from its session identifier, MPC round and
+    /// attempt number alone, so computing it twice over different inputs would reuse it
+    /// and could leak this validator's secrets to whoever controls the node.
+    /// An honest node only ever repeats a round with the same input, which yields the same
+    /// result, and retries with new messages under a new attempt number.
+    fn record_served_round(
+        served_rounds: &mut HashMap<RoundRngKey, [u8; 32]>,
+        computation_id: ComputationId,
+        request_input: &MPCRequestInput,
+        messages: &MPCRoundToMessagesHashMap,
+    ) -> DwalletMPCResult<()> {
+        let input_digest = computation_input_digest(request_input, messages)?;
+        let round_rng_key = (
+            computation_id.session_identifier,
+            computation_id.mpc_round,
+            computation_id.attempt_number,
+        );
+        match served_rounds.entry(round_rng_key) {
+            Entry::Occupied(entry) if *entry.get() != input_digest => {
+                Err(DwalletMPCError::KeyCustody(format!(
+                    "round {} attempt {} of session {} was already computed with a different input",
+                    computation_id.mpc_round,
+                    computation_id.attempt_number,
+                    computation_id.session_identifier,
+                )))
+            }
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(entry) => {
+                entry.insert(input_digest);
+                Ok(())
+            }
+        }
+    }
+
+    /// Builds the computation request for a round, with the private input and decryption key
+    /// shares of this validator.
+    fn computation_request(
+        state: &EpochState,
+        computation_id: ComputationId,
+        request_input: MPCRequestInput,
+        messages: MPCRoundToMessagesHashMap,
+    ) -> DwalletMPCResult<ComputationRequest> {
+        let (public_input, private_input) = session_input_from_event(
+            computation_id.session_identifier,
+            request_input.clone(),
+            &state.access_structure,
+            &state.committee,
+            &state.network_keys,
+            state.next_active_committee.clone(),
+            state.validators_class_groups_public_keys_and_proofs.clone(),
+        )?;
+        let decryption_key_shares = decryption_key_shares_for_request(
+            computation_id.session_identifier,
+            &request_input,
+            &state.network_keys,
+        )?;
+
+        Ok(ComputationRequest {
+            party_id: state.party_id,
+            validator_name: state.validator_name,
+            committee: state.committee.clone(),
+            access_structure: state.access_structure.clone(),
+            private_input,
+            public_input,
+            request_input,
+            decryption_key_shares,
+            messages,
+        })
+    }
+
+    async fn compute(
+        &self,
+        computation_id: ComputationId,
+        computation_request: ComputationRequest,
+    ) -> DwalletMPCResult<GuaranteedOutputDeliveryRoundResult> {
+        let (result_sender, result_receiver) = oneshot::channel();
+        let root_seed = self.root_seed.clone();
+        let dwallet_mpc_metrics = self.dwallet_mpc_metrics.clone();
+
+        rayon::spawn_fifo(move || {
+            let result =
+                computation_request.compute(computation_id, root_seed, dwallet_mpc_metrics);
+            if result_sender.send(result).is_err() {
+                error!("failed to send a key custody computation result");
+            }
+        });
+
+        result_receiver
+            .await
+            .map_err(|_| DwalletMPCError::TokioRecv)?
+    }
+}
+
+/// The digest of the input of a round computation.
+///
+/// The messages are hashed in a canonical order, so the same input always has the same digest.
+fn computation_input_digest(
+    request_input: &MPCRequestInput,
+    messages: &MPCRoundToMessagesHashMap,
+) -> DwalletMPCResult<[u8; 32]> {
+    let messages: BTreeMap<_, BTreeMap<_, _>> = messages
+        .iter()
+        .map(|(mpc_round, round_messages)| (*mpc_round, round_messages.iter().collect()))
+        .collect();
+    let mut hasher = Blake2b256::default();
+    hasher.update(bcs::to_bytes(&(request_input, messages))?);
+
+    Ok(hasher.finalize().digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ika_types::messages_dwallet_mpc::SessionType;
+    use ika_types::messages_dwallet_mpc::test_helpers::mock_request_inputs;
+
+    fn computation_id(consensus_round: u64, attempt_number: u64) -> ComputationId {
+        ComputationId {
+            session_identifier: SessionIdentifier::new(SessionType::User, [1; 32]),
+            consensus_round: Some(consensus_round),
+            mpc_round: 2,
+            attempt_number,
+        }
+    }
+
+    fn messages(entries: &[(PartyID, u8)]) -> MPCRoundToMessagesHashMap {
+        HashMap::from([(
+            1,
+            entries
+                .iter()
+                .map(|(party_id, message)| (*party_id, vec![*message; 8]))
+                .collect(),
+        )])
+    }
+
+    #[test]
+    fn refuses_to_recompute_a_round_with_a_different_input() {
+        let request_input = mock_request_inputs().remove(0);
+        let mut served_rounds = HashMap::new();
+
+        KeyCustodyServer::record_served_round(
+            &mut served_rounds,
+            computation_id(10, 1),
+            &request_input,
+            &messages(&[(1, 1), (2, 2), (3, 3)]),
+        )
+        .unwrap();
+        // The same input is served again, regardless of the order of its messages.
+        KeyCustodyServer::record_served_round(
+            &mut served_rounds,
+            computation_id(10, 1),
+            &request_input,
+            &messages(&[(3, 3), (2, 2), (1, 1)]),
+        )
+        .unwrap();
+
+        // A different input for the same randomness is refused, even at another consensus round.
+        assert!(matches!(
+            KeyCustodyServer::record_served_round(
+                &mut served_rounds,
+                computation_id(11, 1),
+                &request_input,
+                &messages(&[(1, 1), (2, 2), (3, 3), (4, 4)]),
+            ),
+            Err(DwalletMPCError::KeyCustody(_))
+        ));
+        assert!(matches!(
+            KeyCustodyServer::record_served_round(
+                &mut served_rounds,
+                computation_id(10, 1),
+                &request_input,
+                &messages(&[(1, 1), (2, 2), (3, 4)]),
+            ),
+            Err(DwalletMPCError::KeyCustody(_))
+        ));
+
+        // A retry under a new attempt number derives new randomness, so it is served.
+        KeyCustodyServer::record_served_round(
+            &mut served_rounds,
+            computation_id(11, 2),
+            &request_input,
+            &messages(&[(1, 1), (2, 2), (3, 3), (4, 4)]),
+        )
+        .unwrap();
+    }
+}
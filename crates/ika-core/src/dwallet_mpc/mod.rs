@@ -17,13 +17,21 @@ pub mod mpc_session;
 
 mod crytographic_computation;
 pub mod dwallet_mpc_metrics;
+#[cfg(feature = "fault-injection")]
+pub mod fault_injection;
+#[cfg(unix)]
+pub mod key_custody;
 mod mpc_event;
+mod network_key_cache;
+pub mod pricing_recommendation;
+mod round_delays;
+mod session_timeouts;
 
 pub(crate) use crytographic_computation::mpc_computations::{
-    dwallet_dkg, network_dkg, presign, reconfiguration, sign,
+    dwallet_dkg, network_dkg, presign, reconfiguration, sign, threshold_decryption,
 };
 pub(crate) use crytographic_computation::native_computations::{
-    encrypt_user_share, make_dwallet_user_secret_key_shares_public,
+    encrypt_user_share, key_refresh, make_dwallet_user_secret_key_shares_public,
 };
 
 pub const FIRST_EPOCH_ID: EpochId = 0;
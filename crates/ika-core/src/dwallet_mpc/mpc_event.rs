@@ -7,6 +7,7 @@ use crate::dwallet_mpc::dwallet_dkg::{
 };
 use crate::dwallet_mpc::dwallet_mpc_service::DWalletMPCService;
 use crate::dwallet_mpc::encrypt_user_share::start_encrypted_share_verification_session_request;
+use crate::dwallet_mpc::key_refresh::dwallet_key_refresh_session_request;
 use crate::dwallet_mpc::make_dwallet_user_secret_key_shares_public::make_dwallet_user_secret_key_shares_public_request_event_session_request;
 use crate::dwallet_mpc::mpc_manager::DWalletMPCManager;
 use crate::dwallet_mpc::mpc_session::MPCEventData;
@@ -14,17 +15,20 @@ use crate::dwallet_mpc::network_dkg::network_dkg_session_request;
 use crate::dwallet_mpc::presign::presign_party_session_request;
 use crate::dwallet_mpc::reconfiguration::network_decryption_key_reconfiguration_session_request_from_event;
 use crate::dwallet_mpc::sign::{
+    batch_sign_party_session_request, derived_key_sign_party_session_request,
     get_verify_partial_signatures_session_request, sign_party_session_request,
 };
+use crate::dwallet_mpc::threshold_decryption::threshold_decryption_session_request;
 use dwallet_mpc_types::dwallet_mpc::DWalletMPCNetworkKeyScheme;
 use ika_types::error::{IkaError, IkaResult};
 use ika_types::messages_dwallet_mpc::{
-    DBSuiEvent, DWalletDKGFirstRoundRequestEvent, DWalletDKGSecondRoundRequestEvent,
-    DWalletEncryptionKeyReconfigurationRequestEvent, DWalletImportedKeyVerificationRequestEvent,
-    DWalletMPCEvent, DWalletNetworkDKGEncryptionKeyRequestEvent, DWalletSessionEvent,
-    DWalletSessionEventTrait, EncryptedShareVerificationRequestEvent, FutureSignRequestEvent,
+    BatchSignRequestEvent, DBSuiEvent, DWalletDKGFirstRoundRequestEvent,
+    DWalletDKGSecondRoundRequestEvent, DWalletEncryptionKeyReconfigurationRequestEvent,
+    DWalletImportedKeyVerificationRequestEvent, DWalletKeyRefreshRequestEvent, DWalletMPCEvent,
+    DWalletNetworkDKGEncryptionKeyRequestEvent, DWalletSessionEvent, DWalletSessionEventTrait,
+    DerivedKeySignRequestEvent, EncryptedShareVerificationRequestEvent, FutureSignRequestEvent,
     MakeDWalletUserSecretKeySharesPublicRequestEvent, PresignRequestEvent,
-    SESSIONS_MANAGER_MODULE_NAME, SignRequestEvent,
+    SESSIONS_MANAGER_MODULE_NAME, SignRequestEvent, ThresholdDecryptionRequestEvent,
 };
 use serde::de::DeserializeOwned;
 use std::mem;
@@ -54,6 +58,9 @@ impl DWalletMPCManager {
         if self.next_active_committee.is_none() {
             let got_next_active_committee = self.try_receiving_next_active_committee();
             if got_next_active_committee {
+                #[cfg(unix)]
+                self.forward_next_active_committee_to_key_custody().await;
+
                 let events_pending_for_next_active_committee =
                     mem::take(&mut self.events_pending_for_next_active_committee);
 
@@ -269,6 +276,27 @@ impl DWalletMPCManager {
                 deserialize_event_contents(&event.contents, event.pulled)?;
 
             sign_party_session_request(&deserialized_event)
+        } else if event.type_
+            == DWalletSessionEvent::<DerivedKeySignRequestEvent>::type_(&self.packages_config)
+        {
+            let deserialized_event: DWalletSessionEvent<DerivedKeySignRequestEvent> =
+                deserialize_event_contents(&event.contents, event.pulled)?;
+
+            derived_key_sign_party_session_request(&deserialized_event)
+        } else if event.type_
+            == DWalletSessionEvent::<BatchSignRequestEvent>::type_(&self.packages_config)
+        {
+            let deserialized_event: DWalletSessionEvent<BatchSignRequestEvent> =
+                deserialize_event_contents(&event.contents, event.pulled)?;
+
+            batch_sign_party_session_request(&deserialized_event)
+        } else if event.type_
+            == DWalletSessionEvent::<ThresholdDecryptionRequestEvent>::type_(&self.packages_config)
+        {
+            let deserialized_event: DWalletSessionEvent<ThresholdDecryptionRequestEvent> =
+                deserialize_event_contents(&event.contents, event.pulled)?;
+
+            threshold_decryption_session_request(&deserialized_event)
         } else if event.type_
             == DWalletSessionEvent::<FutureSignRequestEvent>::type_(&self.packages_config)
         {
@@ -305,6 +333,13 @@ impl DWalletMPCManager {
                 deserialize_event_contents(&event.contents, event.pulled)?;
 
             start_encrypted_share_verification_session_request(deserialized_event)
+        } else if event.type_
+            == DWalletSessionEvent::<DWalletKeyRefreshRequestEvent>::type_(&self.packages_config)
+        {
+            let deserialized_event: DWalletSessionEvent<DWalletKeyRefreshRequestEvent> =
+                deserialize_event_contents(&event.contents, event.pulled)?;
+
+            dwallet_key_refresh_session_request(deserialized_event)
         } else {
             return Ok(None);
         };
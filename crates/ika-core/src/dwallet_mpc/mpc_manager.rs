@@ -3,18 +3,22 @@
 
 use crate::dwallet_mpc::crytographic_computation::mpc_computations::build_messages_to_advance;
 use crate::dwallet_mpc::crytographic_computation::{
-    ComputationId, ComputationRequest, CryptographicComputationsOrchestrator,
+    ComputationId, ComputationRequest, ComputationSecrets, CryptographicComputationsOrchestrator,
 };
 use crate::dwallet_mpc::dwallet_mpc_metrics::DWalletMPCMetrics;
+#[cfg(unix)]
+use crate::dwallet_mpc::key_custody::KeyCustodyClient;
 use crate::dwallet_mpc::mpc_session::{DWalletMPCSession, DWalletMPCSessionOutput, MPCEventData};
-use crate::dwallet_mpc::network_dkg::instantiate_dwallet_mpc_network_encryption_key_public_data_from_public_output;
 use crate::dwallet_mpc::network_dkg::{DwalletMPCNetworkKeys, ValidatorPrivateDecryptionKeyData};
+use crate::dwallet_mpc::network_key_cache::NetworkKeyCache;
+use crate::dwallet_mpc::round_delays::{AdaptiveRoundDelays, RoundDelaysConfig};
+use crate::dwallet_mpc::session_timeouts::SessionTimeoutsConfig;
 use crate::dwallet_mpc::{
     authority_name_to_party_id_from_committee, generate_access_structure_from_committee,
     get_validators_class_groups_public_keys_and_proofs, party_id_to_authority_name,
 };
 use dwallet_classgroups_types::ClassGroupsKeyPairAndProof;
-use dwallet_mpc_types::dwallet_mpc::{DWalletMPCNetworkKeyScheme, MPCSessionStatus};
+use dwallet_mpc_types::dwallet_mpc::MPCSessionStatus;
 use group::PartyID;
 use ika_config::NodeConfig;
 use ika_types::committee::ClassGroupsEncryptionKeyAndProof;
@@ -60,6 +64,9 @@ pub(crate) struct DWalletMPCManager {
     pub(crate) validators_class_groups_public_keys_and_proofs:
         HashMap<PartyID, ClassGroupsEncryptionKeyAndProof>,
     pub(crate) cryptographic_computations_orchestrator: CryptographicComputationsOrchestrator,
+    /// The connection to the key custody process, if the validator's secrets are held by one.
+    #[cfg(unix)]
+    key_custody_client: Option<Arc<KeyCustodyClient>>,
 
     /// The set of malicious actors that were agreed upon by a quorum of validators.
     /// This agreement is done synchronically, and thus is it safe to filter malicious actors.
@@ -81,8 +88,47 @@ pub(crate) struct DWalletMPCManager {
     pub(crate) next_active_committee: Option<Committee>,
     pub(crate) dwallet_mpc_metrics: Arc<DWalletMPCMetrics>,
 
-    network_dkg_third_round_delay: u64,
-    decryption_key_reconfiguration_third_round_delay: u64,
+    /// The delays of MPC rounds, adapted to the message arrivals observed in this epoch.
+    round_delays: AdaptiveRoundDelays,
+    session_timeouts: SessionTimeoutsConfig,
+}
+
+/// The secrets of a validator that performs its computations in-process, from its root seed.
+fn local_computation_secrets(
+    node_config: &NodeConfig,
+    party_id: PartyID,
+) -> DwalletMPCResult<(
+    ComputationSecrets,
+    Option<ValidatorPrivateDecryptionKeyData>,
+    Option<NetworkKeyCache>,
+)> {
+    let root_seed = node_config
+        .root_seed_key_pair
+        .clone()
+        .ok_or(DwalletMPCError::MissingRootSeed)?
+        .root_seed()
+        .clone();
+
+    let class_groups_key_pair = ClassGroupsKeyPairAndProof::from_seed(&root_seed);
+
+    let validator_private_data = ValidatorPrivateDecryptionKeyData {
+        party_id,
+        class_groups_decryption_key: class_groups_key_pair.decryption_key(),
+        validator_decryption_key_shares: HashMap::new(),
+    };
+
+    // The cache is only an optimization, so failing to open it isn't fatal.
+    let network_key_cache = NetworkKeyCache::new(node_config.network_key_cache_path(), &root_seed)
+        .inspect_err(|e| {
+            warn!(error=?e, "failed to open the network key cache");
+        })
+        .ok();
+
+    Ok((
+        ComputationSecrets::Local(root_seed),
+        Some(validator_private_data),
+        network_key_cache,
+    ))
 }
 
 impl DWalletMPCManager {
@@ -94,8 +140,8 @@ impl DWalletMPCManager {
         network_keys_receiver: Receiver<Arc<HashMap<ObjectID, DWalletNetworkEncryptionKeyData>>>,
         next_epoch_committee_receiver: Receiver<Committee>,
         node_config: NodeConfig,
-        network_dkg_third_round_delay: u64,
-        decryption_key_reconfiguration_third_round_delay: u64,
+        round_delays_config: RoundDelaysConfig,
+        session_timeouts: SessionTimeoutsConfig,
         dwallet_mpc_metrics: Arc<DWalletMPCMetrics>,
     ) -> Self {
         Self::try_new(
@@ -106,8 +152,8 @@ impl DWalletMPCManager {
             network_keys_receiver,
             next_epoch_committee_receiver,
             node_config.clone(),
-            network_dkg_third_round_delay,
-            decryption_key_reconfiguration_third_round_delay,
+            round_delays_config,
+            session_timeouts,
             dwallet_mpc_metrics,
         )
         .unwrap_or_else(|err| {
@@ -125,43 +171,66 @@ impl DWalletMPCManager {
         network_keys_receiver: Receiver<Arc<HashMap<ObjectID, DWalletNetworkEncryptionKeyData>>>,
         next_epoch_committee_receiver: watch::Receiver<Committee>,
         node_config: NodeConfig,
-        network_dkg_third_round_delay: u64,
-        decryption_key_reconfiguration_third_round_delay: u64,
+        round_delays_config: RoundDelaysConfig,
+        session_timeouts: SessionTimeoutsConfig,
         dwallet_mpc_metrics: Arc<DWalletMPCMetrics>,
     ) -> DwalletMPCResult<Self> {
-        let root_seed = node_config
-            .root_seed_key_pair
-            .clone()
-            .ok_or(DwalletMPCError::MissingRootSeed)?
-            .root_seed()
-            .clone();
-
         let access_structure = generate_access_structure_from_committee(&committee)?;
-
-        let mpc_computations_orchestrator =
-            CryptographicComputationsOrchestrator::try_new(root_seed.clone())?;
         let party_id = authority_name_to_party_id_from_committee(&committee, &validator_name)?;
 
-        let class_groups_key_pair = ClassGroupsKeyPairAndProof::from_seed(&root_seed);
-
-        let validator_private_data = ValidatorPrivateDecryptionKeyData {
-            party_id,
-            class_groups_decryption_key: class_groups_key_pair.decryption_key(),
-            validator_decryption_key_shares: HashMap::new(),
+        // With a key custody process, the node holds no secrets: the custody process
+        // performs the computations, and the node only forwards it the public state.
+        #[cfg(unix)]
+        let key_custody_client = match &node_config.key_custody_config {
+            Some(key_custody_config) => {
+                if node_config.root_seed_key_pair.is_some() {
+                    return Err(DwalletMPCError::RootSeedConfiguredWithKeyCustody);
+                }
+                Some(Arc::new(KeyCustodyClient::new(
+                    key_custody_config.socket_path.clone(),
+                    validator_name,
+                    (*committee).clone(),
+                )))
+            }
+            None => None,
         };
-        let dwallet_network_keys = DwalletMPCNetworkKeys::new(validator_private_data);
+        #[cfg(not(unix))]
+        if node_config.key_custody_config.is_some() {
+            return Err(DwalletMPCError::KeyCustodyUnsupported);
+        }
+
+        #[cfg(unix)]
+        let (computation_secrets, validator_private_data, network_key_cache) =
+            match &key_custody_client {
+                Some(key_custody_client) => (
+                    ComputationSecrets::KeyCustody(key_custody_client.clone()),
+                    None,
+                    None,
+                ),
+                None => local_computation_secrets(&node_config, party_id)?,
+            };
+        #[cfg(not(unix))]
+        let (computation_secrets, validator_private_data, network_key_cache) =
+            local_computation_secrets(&node_config, party_id)?;
+
+        let mpc_computations_orchestrator =
+            CryptographicComputationsOrchestrator::try_new(computation_secrets)?;
+        let dwallet_network_keys =
+            DwalletMPCNetworkKeys::new(validator_private_data, network_key_cache);
 
         // Re-initialize the malicious handler every epoch. This is done intentionally:
         // We want to "forget" the malicious actors from the previous epoch and start from scratch.
         Ok(Self {
             mpc_sessions: HashMap::new(),
-            party_id: authority_name_to_party_id_from_committee(&committee, &validator_name)?,
+            party_id,
             epoch_id,
             packages_config,
             access_structure,
             validators_class_groups_public_keys_and_proofs:
                 get_validators_class_groups_public_keys_and_proofs(&committee)?,
             cryptographic_computations_orchestrator: mpc_computations_orchestrator,
+            #[cfg(unix)]
+            key_custody_client,
             malicious_actors: HashSet::new(),
             last_session_to_complete_in_current_epoch: 0,
             recognized_self_as_malicious: false,
@@ -174,8 +243,8 @@ impl DWalletMPCManager {
             next_active_committee: None,
             validator_name,
             committee,
-            network_dkg_third_round_delay,
-            decryption_key_reconfiguration_third_round_delay,
+            round_delays: AdaptiveRoundDelays::new(round_delays_config),
+            session_timeouts,
         })
     }
 
@@ -213,6 +282,24 @@ impl DWalletMPCManager {
         for message in messages {
             self.handle_message(consensus_round, message);
         }
+
+        self.round_delays.observe(
+            consensus_round,
+            self.committee.num_members(),
+            self.mpc_sessions
+                .values_mut()
+                .flat_map(|session| session.message_arrivals.values_mut()),
+        );
+        for session in self.mpc_sessions.values_mut() {
+            // An active session waits for the messages of the previous MPC round.
+            let keep_from_mpc_round = (session.status == MPCSessionStatus::Active)
+                .then(|| session.current_mpc_round.saturating_sub(1));
+            self.round_delays.retain_observable(
+                consensus_round,
+                &mut session.message_arrivals,
+                keep_from_mpc_round,
+            );
+        }
     }
 
     /// Handle the outputs of a given consensus round.
@@ -332,6 +419,23 @@ impl DWalletMPCManager {
             return;
         }
 
+        #[cfg(feature = "fault-injection")]
+        if crate::dwallet_mpc::fault_injection::is_partitioned(
+            &self.validator_name,
+            &sender_authority,
+        ) {
+            debug!(
+                session_identifier=?session_identifier,
+                sender_authority=?sender_authority,
+                receiver_authority=?self.validator_name,
+                mpc_round_number=?mpc_round_number,
+                "Dropping a message from a partitioned authority",
+            );
+
+            return;
+        }
+
+        let delay_estimate = self.round_delays.estimate();
         let session = match self.mpc_sessions.entry(session_identifier) {
             Entry::Occupied(session) => session.into_mut(),
             Entry::Vacant(_) => {
@@ -352,6 +456,22 @@ impl DWalletMPCManager {
             }
         };
 
+        session
+            .started_at_consensus_round
+            .get_or_insert(consensus_round);
+
+        // Arrivals are recorded for completed sessions as well, to observe slow parties.
+        session
+            .message_arrivals
+            .entry(mpc_round_number)
+            .or_default()
+            .record(
+                consensus_round,
+                sender_party_id,
+                &self.access_structure,
+                delay_estimate,
+            );
+
         if session.status == MPCSessionStatus::Active {
             session.add_message(consensus_round, mpc_round_number, sender_party_id, message);
         }
@@ -441,10 +561,8 @@ impl DWalletMPCManager {
         let computation_requests: Vec<_> = ready_to_advance_sessions
             .into_iter()
             .flat_map(|(session, mpc_event_data)| {
-                let rounds_to_delay = self.consensus_rounds_delay_for_mpc_round(
-                    session.current_mpc_round,
-                    &mpc_event_data,
-                );
+                let rounds_to_delay =
+                    self.consensus_rounds_delay_for_mpc_round(session, &mpc_event_data);
 
                 build_messages_to_advance(
                     session.current_mpc_round,
@@ -530,47 +648,57 @@ impl DWalletMPCManager {
         false
     }
 
+    /// Forwards the next active committee to the key custody process, if there is one.
+    #[cfg(unix)]
+    pub(crate) async fn forward_next_active_committee_to_key_custody(&self) {
+        if let (Some(key_custody_client), Some(next_active_committee)) =
+            (&self.key_custody_client, &self.next_active_committee)
+        {
+            if let Err(e) = key_custody_client
+                .set_next_active_committee(next_active_committee.clone())
+                .await
+            {
+                error!(error=?e, "failed to set the next active committee in the key custody process");
+            }
+        }
+    }
+
     pub(crate) async fn maybe_update_network_keys(&mut self) -> Vec<ObjectID> {
         match self.network_keys_receiver.has_changed() {
             Ok(has_changed) => {
                 if has_changed {
                     let new_keys = self.borrow_and_update_network_keys();
 
-                    let mut results = vec![];
-                    for (key_id, key_data) in new_keys {
-                        let res = instantiate_dwallet_mpc_network_encryption_key_public_data_from_public_output(
-                            key_data.current_epoch,
-                            DWalletMPCNetworkKeyScheme::Secp256k1,
-                            self.access_structure.clone(),
-                            key_data,
-                        ).await;
-
-                        results.push((key_id, res))
-                    }
-
                     let mut new_key_ids = vec![];
-                    for (key_id, res) in results {
-                        match res {
-                            Ok(key) => {
-                                info!(key_id=?key_id, "Updating (decrypting new shares) network key for key_id");
-                                if let Err(e) = self
-                                    .network_keys
-                                    .update_network_key(key_id, &key, &self.access_structure)
-                                    .await
-                                {
-                                    error!(error=?e, key_id=?key_id, "failed to update the network key");
-                                } else {
-                                    new_key_ids.push(key_id);
-                                }
-                            }
-                            Err(err) => {
-                                error!(
-                                    error=?err,
-                                    key_id=?key_id,
-                                    "failed to instantiate network decryption key shares from public output for"
-                                );
+                    for (key_id, key_data) in new_keys {
+                        #[cfg(unix)]
+                        if let Some(key_custody_client) = &self.key_custody_client {
+                            // The key custody process decrypts its own shares of the key.
+                            // On failure, it is sent again when the next computation
+                            // re-initializes the custody process.
+                            if let Err(e) = key_custody_client
+                                .update_network_key(key_id, key_data.clone())
+                                .await
+                            {
+                                error!(error=?e, key_id=?key_id, "failed to update the network key in the key custody process");
                             }
                         }
+
+                        info!(key_id=?key_id, "Updating (decrypting new shares) network key for key_id");
+                        if let Err(e) = self
+                            .network_keys
+                            .update_network_key(
+                                key_id,
+                                key_data,
+                                self.epoch_id,
+                                &self.access_structure,
+                            )
+                            .await
+                        {
+                            error!(error=?e, key_id=?key_id, "failed to update the network key");
+                        } else {
+                            new_key_ids.push(key_id);
+                        }
                     }
 
                     new_key_ids
@@ -665,6 +793,82 @@ impl DWalletMPCManager {
         }
     }
 
+    /// Fails the active sessions that did not complete within the timeout of their session type
+    /// by `consensus_round`, and returns them so that a rejected output can be submitted for each.
+    ///
+    /// The authorities that did not send their message for the round each session was waiting
+    /// for are recorded as not participating in it.
+    /// They are not reported as malicious, as a party might just be slow.
+    ///
+    /// Must be called once per consensus round, after its messages and outputs were handled.
+    pub(crate) fn time_out_sessions(
+        &mut self,
+        consensus_round: u64,
+    ) -> Vec<(SessionIdentifier, MPCEventData)> {
+        let mut timed_out_sessions = vec![];
+        for (session_identifier, session) in self.mpc_sessions.iter_mut() {
+            if session.status != MPCSessionStatus::Active {
+                continue;
+            }
+            if session.mpc_event_data().is_none() {
+                continue;
+            }
+            // The timeout of a session whose request was seen before any of its messages
+            // starts at the first consensus round after the request.
+            let started_at_consensus_round = *session
+                .started_at_consensus_round
+                .get_or_insert(consensus_round);
+            let Some(mpc_event_data) = session.mpc_event_data() else {
+                continue;
+            };
+            if !self.session_timeouts.is_timed_out(
+                &mpc_event_data.request_input,
+                started_at_consensus_round,
+                consensus_round,
+            ) {
+                continue;
+            }
+
+            // The current MPC round is advanced upon seeing our message for the previous round,
+            // so the session is waiting for the messages of the previous round.
+            let waiting_for_mpc_round = session.current_mpc_round.saturating_sub(1).max(1);
+            let senders = session
+                .message_arrivals
+                .get(&waiting_for_mpc_round)
+                .map(|arrivals| arrivals.senders().clone())
+                .unwrap_or_default();
+            let non_participating_authorities: Vec<AuthorityName> = self
+                .committee
+                .voting_rights
+                .iter()
+                .map(|(authority_name, _)| *authority_name)
+                .filter(|authority_name| {
+                    authority_name_to_party_id_from_committee(&self.committee, authority_name)
+                        .is_ok_and(|party_id| !senders.contains(&party_id))
+                })
+                .collect();
+
+            error!(
+                ?session_identifier,
+                mpc_protocol=?mpc_event_data.request_input,
+                started_at_consensus_round,
+                consensus_round,
+                waiting_for_mpc_round,
+                ?non_participating_authorities,
+                "MPC session timed out, rejecting"
+            );
+
+            self.dwallet_mpc_metrics.add_session_timeout(
+                &mpc_event_data.request_input,
+                &non_participating_authorities,
+            );
+            timed_out_sessions.push((*session_identifier, mpc_event_data.clone()));
+            session.mark_mpc_session_as_failed();
+        }
+
+        timed_out_sessions
+    }
+
     pub(crate) fn is_malicious_actor(&self, authority: &AuthorityName) -> bool {
         self.malicious_actors.contains(authority)
     }
@@ -685,28 +889,29 @@ impl DWalletMPCManager {
 
     /// Returns the number of additional (delay) consensus rounds the session should wait for before advancing.
     ///
-    /// This method returns the protocol-specific delay for certain MPC rounds in specific protocols
-    /// (NetworkDkg, DecryptionKeyReconfiguration).
+    /// Only certain MPC rounds in specific protocols are delayed
+    /// (the third round of NetworkDkg and DecryptionKeyReconfiguration),
+    /// other rounds are always ready to advance.
     ///
-    /// - **NetworkDkg protocol**: requires delay for the third round
-    ///   using `network_dkg_third_round_delay` config.
-    /// - **DecryptionKeyReconfiguration protocol**: requires delay for the third round
-    ///   using `decryption_key_reconfiguration_third_round_delay` config.
-    /// - **Other protocols**: No delay required, always ready to advance
+    /// The delay is the one estimated when the threshold of messages for the previous round
+    /// was reached, if adaptive delays are enabled and there were enough observations to
+    /// estimate it, and the configured delay of the protocol otherwise.
+    /// See [`crate::dwallet_mpc::round_delays`].
     pub(crate) fn consensus_rounds_delay_for_mpc_round(
         &self,
-        current_mpc_round: u64,
+        session: &DWalletMPCSession,
         mpc_event_data: &MPCEventData,
     ) -> u64 {
-        match mpc_event_data.request_input {
-            MPCRequestInput::NetworkEncryptionKeyDkg(_, _) if current_mpc_round == 3 => {
-                self.network_dkg_third_round_delay
-            }
-            MPCRequestInput::NetworkEncryptionKeyReconfiguration(_) if current_mpc_round == 3 => {
-                self.decryption_key_reconfiguration_third_round_delay
-            }
-            _ => 0,
-        }
+        let delay_estimate = session
+            .message_arrivals
+            .get(&session.current_mpc_round.saturating_sub(1))
+            .and_then(|arrivals| arrivals.delay_estimate);
+
+        self.round_delays.delay(
+            &mpc_event_data.request_input,
+            session.current_mpc_round,
+            delay_estimate,
+        )
     }
 
     /// Builds the outputs to finalize based on the outputs received in the consensus rounds.
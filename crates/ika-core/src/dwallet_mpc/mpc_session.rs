@@ -4,6 +4,7 @@
 mod input;
 mod mpc_event_data;
 
+use crate::dwallet_mpc::round_delays::RoundMessageArrivals;
 use dwallet_mpc_types::dwallet_mpc::{MPCMessage, MPCSessionStatus};
 use group::PartyID;
 use ika_types::crypto::{AuthorityName, AuthorityPublicKeyBytes};
@@ -13,7 +14,9 @@ use std::collections::hash_map::Entry::Vacant;
 use std::collections::{HashMap, HashSet};
 use tracing::{debug, error, info};
 
-pub(crate) use crate::dwallet_mpc::mpc_session::mpc_event_data::MPCEventData;
+pub(crate) use crate::dwallet_mpc::mpc_session::mpc_event_data::{
+    MPCEventData, decryption_key_shares_for_request,
+};
 pub(crate) use input::{PublicInput, session_input_from_event};
 
 pub(crate) type MPCRoundToMessagesHashMap = HashMap<u64, HashMap<PartyID, MPCMessage>>;
@@ -52,6 +55,15 @@ pub(crate) struct DWalletMPCSession {
     pub(super) messages_by_consensus_round: HashMap<u64, MPCRoundToMessagesHashMap>,
 
     outputs_by_consensus_round: HashMap<u64, HashMap<PartyID, DWalletMPCSessionOutput>>,
+
+    /// The arrival of the messages of each MPC round, used to adapt the delay of MPC rounds.
+    /// Unlike the messages themselves, kept after the session completes,
+    /// until the rounds can no longer be observed.
+    pub(crate) message_arrivals: HashMap<u64, RoundMessageArrivals>,
+
+    /// The first consensus round in which this session was known, from its request or its
+    /// first message, from which its timeout is counted.
+    pub(crate) started_at_consensus_round: Option<u64>,
 }
 
 impl DWalletMPCSession {
@@ -66,6 +78,8 @@ impl DWalletMPCSession {
             status,
             messages_by_consensus_round: HashMap::new(),
             outputs_by_consensus_round: HashMap::new(),
+            message_arrivals: HashMap::new(),
+            started_at_consensus_round: None,
             session_identifier,
             current_mpc_round: 1,
             mpc_round_to_threshold_not_reached_consensus_rounds: HashMap::new(),
@@ -239,6 +253,10 @@ impl DWalletMPCSession {
         self.status = MPCSessionStatus::Completed;
     }
 
+    pub(crate) fn mark_mpc_session_as_failed(&mut self) {
+        self.status = MPCSessionStatus::Failed;
+    }
+
     pub(crate) fn mark_mpc_session_as_computation_completed(&mut self) {
         self.status = MPCSessionStatus::ComputationCompleted;
     }
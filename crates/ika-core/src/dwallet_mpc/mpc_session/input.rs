@@ -10,7 +10,12 @@ use crate::dwallet_mpc::presign::{PresignParty, presign_public_input};
 use crate::dwallet_mpc::reconfiguration::{
     ReconfigurationPartyPublicInputGenerator, ReconfigurationSecp256k1Party,
 };
-use crate::dwallet_mpc::sign::{SignFirstParty, sign_session_public_input};
+use crate::dwallet_mpc::sign::{
+    SignFirstParty, batch_sign_session_public_input, sign_session_public_input,
+};
+use crate::dwallet_mpc::threshold_decryption::{
+    ThresholdDecryptionPublicInput, threshold_decryption_public_input,
+};
 use class_groups::dkg;
 use commitment::CommitmentSizedNumber;
 use dwallet_mpc_types::dwallet_mpc::{
@@ -19,7 +24,7 @@ use dwallet_mpc_types::dwallet_mpc::{
 use group::PartyID;
 use ika_types::committee::{ClassGroupsEncryptionKeyAndProof, Committee};
 use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
-use ika_types::messages_dwallet_mpc::{DWalletMPCEvent, MPCRequestInput};
+use ika_types::messages_dwallet_mpc::{MPCRequestInput, SessionIdentifier};
 use mpc::WeightedThresholdAccessStructure;
 use std::collections::HashMap;
 
@@ -33,8 +38,11 @@ pub enum PublicInput {
     DKGSecond(<DWalletDKGSecondParty as mpc::Party>::PublicInput),
     Presign(<PresignParty as mpc::Party>::PublicInput),
     Sign(<SignFirstParty as mpc::Party>::PublicInput),
+    BatchSign(Vec<<SignFirstParty as mpc::Party>::PublicInput>),
+    ThresholdDecryption(ThresholdDecryptionPublicInput),
     NetworkEncryptionKeyDkg(<dkg::Secp256k1Party as mpc::Party>::PublicInput),
     EncryptedShareVerification(twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters),
+    DWalletKeyRefresh(twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters),
     PartialSignatureVerification(twopc_mpc::secp256k1::class_groups::ProtocolPublicParameters),
     NetworkEncryptionKeyReconfiguration(<ReconfigurationSecp256k1Party as mpc::Party>::PublicInput),
     MakeDWalletUserSecretKeySharesPublic(
@@ -43,13 +51,14 @@ pub enum PublicInput {
 }
 
 // TODO (#542): move this logic to run before writing the event to the DB, maybe include within the session info
-/// Parses an [`Event`] to extract the corresponding [`MPCParty`],
+/// Parses the request input of an [`Event`] to extract the corresponding [`MPCParty`],
 /// public input, private input and session information.
 ///
 /// Returns an error if the event type does not correspond to any known MPC rounds
 /// or if deserialization fails.
 pub(crate) fn session_input_from_event(
-    event: DWalletMPCEvent,
+    session_identifier: SessionIdentifier,
+    request_input: MPCRequestInput,
     access_structure: &WeightedThresholdAccessStructure,
     committee: &Committee,
     network_keys: &DwalletMPCNetworkKeys,
@@ -59,10 +68,8 @@ pub(crate) fn session_input_from_event(
         ClassGroupsEncryptionKeyAndProof,
     >,
 ) -> DwalletMPCResult<(PublicInput, MPCPrivateInput)> {
-    let session_id = CommitmentSizedNumber::from_le_slice(
-        event.session_request.session_identifier.to_vec().as_slice(),
-    );
-    match event.session_request.request_input {
+    let session_id = CommitmentSizedNumber::from_le_slice(session_identifier.to_vec().as_slice());
+    match request_input {
         MPCRequestInput::DWalletImportedKeyVerificationRequest(event) => {
             let protocol_public_parameters = network_keys.get_protocol_public_parameters(
                 // The event is assign with a Secp256k1 dwallet.
@@ -96,9 +103,12 @@ pub(crate) fn session_input_from_event(
             ))
         }
         MPCRequestInput::NetworkEncryptionKeyDkg(_, _) => {
-            let class_groups_decryption_key = network_keys
-                .validator_private_dec_key_data
-                .class_groups_decryption_key;
+            // `None` when the key is held by a key custody process,
+            // which sets the private input on its own.
+            let private_input = network_keys
+                .class_groups_decryption_key()
+                .map(|class_groups_decryption_key| bcs::to_bytes(&class_groups_decryption_key))
+                .transpose()?;
 
             Ok((
                 PublicInput::NetworkEncryptionKeyDkg(network_dkg_public_input(
@@ -107,13 +117,14 @@ pub(crate) fn session_input_from_event(
                     // Todo (#473): Support generic network key scheme
                     DWalletMPCNetworkKeyScheme::Secp256k1,
                 )?),
-                Some(bcs::to_bytes(&class_groups_decryption_key)?),
+                private_input,
             ))
         }
         MPCRequestInput::NetworkEncryptionKeyReconfiguration(event) => {
-            let class_groups_decryption_key = network_keys
-                .validator_private_dec_key_data
-                .class_groups_decryption_key;
+            let private_input = network_keys
+                .class_groups_decryption_key()
+                .map(|class_groups_decryption_key| bcs::to_bytes(&class_groups_decryption_key))
+                .transpose()?;
 
             let next_active_committee = next_active_committee.ok_or(
                 DwalletMPCError::MissingNextActiveCommittee(session_id.to_be_bytes().to_vec()),
@@ -135,9 +146,7 @@ pub(crate) fn session_input_from_event(
                                     .dwallet_network_encryption_key_id,
                             )?,
                     )?),
-                    Some(bcs::to_bytes(
-                        &class_groups_decryption_key
-                    )?),
+                    private_input,
                 ))
         }
         MPCRequestInput::DKGFirst(event) => {
@@ -192,6 +201,47 @@ pub(crate) fn session_input_from_event(
 
             Ok((
                 PublicInput::Sign(sign_session_public_input(
+                    event.session_identifier_digest(),
+                    &event.event_data,
+                    &[],
+                    access_structure,
+                    network_keys,
+                    protocol_public_parameters,
+                )?),
+                None,
+            ))
+        }
+        MPCRequestInput::DerivedKeySign(event) => {
+            let protocol_public_parameters = network_keys.get_protocol_public_parameters(
+                // The event is assign with a Secp256k1 dwallet.
+                // Todo (#473): Support generic network key scheme
+                &event
+                    .event_data
+                    .sign_request
+                    .dwallet_network_encryption_key_id,
+            )?;
+
+            Ok((
+                PublicInput::Sign(sign_session_public_input(
+                    event.session_identifier_digest(),
+                    &event.event_data.sign_request,
+                    &event.event_data.derivation_path,
+                    access_structure,
+                    network_keys,
+                    protocol_public_parameters,
+                )?),
+                None,
+            ))
+        }
+        MPCRequestInput::BatchSign(event) => {
+            let protocol_public_parameters = network_keys.get_protocol_public_parameters(
+                // The event is assign with a Secp256k1 dwallet.
+                // Todo (#473): Support generic network key scheme
+                &event.event_data.dwallet_network_encryption_key_id,
+            )?;
+
+            Ok((
+                PublicInput::BatchSign(batch_sign_session_public_input(
                     &event,
                     access_structure,
                     network_keys,
@@ -200,6 +250,13 @@ pub(crate) fn session_input_from_event(
                 None,
             ))
         }
+        MPCRequestInput::ThresholdDecryption(event) => Ok((
+            PublicInput::ThresholdDecryption(threshold_decryption_public_input(
+                &event,
+                network_keys,
+            )?),
+            None,
+        )),
         MPCRequestInput::EncryptedShareVerification(event) => {
             let protocol_public_parameters = network_keys.get_protocol_public_parameters(
                 // The event is assign with a Secp256k1 dwallet.
@@ -212,6 +269,16 @@ pub(crate) fn session_input_from_event(
                 None,
             ))
         }
+        MPCRequestInput::DWalletKeyRefresh(event) => {
+            let protocol_public_parameters = network_keys.get_protocol_public_parameters(
+                &event.event_data.dwallet_network_encryption_key_id,
+            )?;
+
+            Ok((
+                PublicInput::DWalletKeyRefresh(protocol_public_parameters),
+                None,
+            ))
+        }
         MPCRequestInput::PartialSignatureVerification(event) => {
             let protocol_public_parameters = network_keys.get_protocol_public_parameters(
                 // The event is assign with a Secp256k1 dwallet.
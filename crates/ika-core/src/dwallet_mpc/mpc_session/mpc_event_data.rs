@@ -7,9 +7,9 @@ use crate::dwallet_mpc::network_dkg::DwalletMPCNetworkKeys;
 use dwallet_mpc_types::dwallet_mpc::MPCPrivateInput;
 use group::PartyID;
 use ika_types::committee::{ClassGroupsEncryptionKeyAndProof, Committee};
-use ika_types::dwallet_mpc_error::DwalletMPCError;
+use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
 use ika_types::messages_dwallet_mpc::{
-    AsyncProtocol, DWalletMPCEvent, MPCRequestInput, SessionType,
+    AsyncProtocol, DWalletMPCEvent, MPCRequestInput, SessionIdentifier, SessionType,
 };
 use mpc::WeightedThresholdAccessStructure;
 use std::cmp::Ordering;
@@ -44,7 +44,8 @@ impl MPCEventData {
         >,
     ) -> Result<Self, DwalletMPCError> {
         let (public_input, private_input) = session_input_from_event(
-            event.clone(),
+            event.session_request.session_identifier,
+            event.session_request.request_input.clone(),
             access_structure,
             committee,
             network_keys,
@@ -52,27 +53,14 @@ impl MPCEventData {
             validators_class_groups_public_keys_and_proofs,
         )?;
 
-        let needs_decryption_key_shares = matches!(
-            event.session_request.request_input.clone(),
-            MPCRequestInput::Sign(_) | MPCRequestInput::NetworkEncryptionKeyReconfiguration(_)
-        );
-
-        let decryption_key_shares = if needs_decryption_key_shares {
-            if let Some(network_encryption_key_id) = event
-                .session_request
-                .request_input
-                .get_network_encryption_key_id()
-            {
-                Some(network_keys.get_decryption_key_shares(&network_encryption_key_id)?)
-            } else {
-                error!(
-                    should_never_happen =? true,
-                    session_identifier=?event.session_request.session_identifier,
-                    "failed to get network encryption key ID for a session that requires decryption key shares",
-                );
-
-                None
-            }
+        // When the decryption key shares are held by a key custody process,
+        // it provides them to the computation instead.
+        let decryption_key_shares = if network_keys.holds_validator_private_data() {
+            decryption_key_shares_for_request(
+                event.session_request.session_identifier,
+                &event.session_request.request_input,
+                network_keys,
+            )?
         } else {
             None
         };
@@ -92,6 +80,41 @@ impl MPCEventData {
     }
 }
 
+/// Returns this validator's decryption key shares of the network key used by `request_input`,
+/// for the protocols that require them
+/// (sign, batch sign, threshold decryption and network key reconfiguration).
+pub(crate) fn decryption_key_shares_for_request(
+    session_identifier: SessionIdentifier,
+    request_input: &MPCRequestInput,
+    network_keys: &DwalletMPCNetworkKeys,
+) -> DwalletMPCResult<Option<HashMap<PartyID, <AsyncProtocol as Protocol>::DecryptionKeyShare>>> {
+    let needs_decryption_key_shares = matches!(
+        request_input,
+        MPCRequestInput::Sign(_)
+            | MPCRequestInput::DerivedKeySign(_)
+            | MPCRequestInput::BatchSign(_)
+            | MPCRequestInput::ThresholdDecryption(_)
+            | MPCRequestInput::NetworkEncryptionKeyReconfiguration(_)
+    );
+    if !needs_decryption_key_shares {
+        return Ok(None);
+    }
+
+    if let Some(network_encryption_key_id) = request_input.get_network_encryption_key_id() {
+        Ok(Some(
+            network_keys.get_decryption_key_shares(&network_encryption_key_id)?,
+        ))
+    } else {
+        error!(
+            should_never_happen =? true,
+            ?session_identifier,
+            "failed to get network encryption key ID for a session that requires decryption key shares",
+        );
+
+        Ok(None)
+    }
+}
+
 impl PartialOrd<Self> for MPCEventData {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -0,0 +1,291 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! A persistent, encrypted-at-rest cache of the data derived from the network encryption keys.
+//!
+//! Instantiating the public parameters of a network key and decrypting this validator's
+//! shares of it from the network DKG or reconfiguration public output is slow, and would
+//! otherwise be repeated on every start and epoch change before the node can participate
+//! in MPC sessions.
+//!
+//! Every network key is cached in its own file, under the (key ID, epoch, output digest) it
+//! was derived from, where the output digest commits to both the network DKG and the latest
+//! reconfiguration public output of the key.
+//! An entry is only used when all three match the requested key: a reconfiguration of the
+//! key, or a new epoch (and thus a new committee), invalidates it, and the entry is
+//! replaced once the key is derived again.
+//!
+//! Entries are encrypted with AES-256-GCM under a key derived from the root seed, and
+//! the header (key ID, epoch and output digest) is authenticated as associated data,
+//! so a corrupted, tampered or misplaced entry fails to decrypt and is discarded.
+
+use dwallet_rng::RootSeed;
+use fastcrypto::aes::{Aes256Gcm, AesKey, AuthenticatedCipher};
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use fastcrypto::traits::ToFromBytes;
+use ika_types::committee::EpochId;
+use ika_types::dwallet_mpc_error::{DwalletMPCError, DwalletMPCResult};
+use ika_types::messages_dwallet_mpc::DWalletNetworkEncryptionKeyData;
+use rand_chacha::rand_core::RngCore;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use sui_types::base_types::ObjectID;
+use tracing::{debug, warn};
+
+const NONCE_LENGTH: usize = 12;
+
+type Nonce = <Aes256Gcm as AuthenticatedCipher>::IVType;
+
+/// Identifies the network key data a cache entry was derived from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct CacheEntryHeader {
+    key_id: ObjectID,
+    epoch: EpochId,
+    output_digest: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    header: CacheEntryHeader,
+    nonce: [u8; NONCE_LENGTH],
+    ciphertext: Vec<u8>,
+}
+
+/// The digest of the public outputs a network key is derived from.
+pub(crate) fn network_key_output_digest(key_data: &DWalletNetworkEncryptionKeyData) -> [u8; 32] {
+    let mut hasher = Blake2b256::default();
+    hasher.update(
+        bcs::to_bytes(&(
+            &key_data.network_dkg_public_output,
+            &key_data.current_reconfiguration_public_output,
+        ))
+        .expect("serializing bytes should never fail"),
+    );
+
+    hasher.finalize().digest
+}
+
+/// An encrypted on-disk cache of data derived from network encryption keys.
+pub(crate) struct NetworkKeyCache {
+    directory: PathBuf,
+    cipher: Aes256Gcm,
+}
+
+impl NetworkKeyCache {
+    pub(crate) fn new(directory: PathBuf, root_seed: &RootSeed) -> DwalletMPCResult<Self> {
+        fs::create_dir_all(&directory).map_err(|e| {
+            DwalletMPCError::NetworkKeyCache(format!(
+                "failed to create the cache directory {}: {e}",
+                directory.display()
+            ))
+        })?;
+        let key = AesKey::from_bytes(&root_seed.network_key_cache_encryption_key())
+            .map_err(|e| DwalletMPCError::NetworkKeyCache(e.to_string()))?;
+
+        Ok(Self {
+            directory,
+            cipher: Aes256Gcm::new(key),
+        })
+    }
+
+    fn entry_path(&self, key_id: &ObjectID) -> PathBuf {
+        self.directory.join(format!("{key_id}.bin"))
+    }
+
+    /// Loads the cached data of `key_data` for `epoch`.
+    ///
+    /// Returns `None` if there is no valid entry for it, in which case any stale entry for
+    /// the key is removed.
+    pub(crate) fn load<T: DeserializeOwned>(
+        &self,
+        key_id: ObjectID,
+        epoch: EpochId,
+        key_data: &DWalletNetworkEncryptionKeyData,
+    ) -> Option<T> {
+        let path = self.entry_path(&key_id);
+        let bytes = fs::read(&path).ok()?;
+        let expected_header = CacheEntryHeader {
+            key_id,
+            epoch,
+            output_digest: network_key_output_digest(key_data),
+        };
+
+        match self.decrypt_entry(&bytes, &expected_header) {
+            Ok(Some(value)) => {
+                debug!(?key_id, epoch, "Loaded the network key from the cache");
+                Some(value)
+            }
+            Ok(None) => {
+                debug!(
+                    ?key_id,
+                    epoch, "Discarding a cached network key of another epoch or output"
+                );
+                self.remove(&key_id);
+                None
+            }
+            Err(e) => {
+                warn!(?key_id, epoch, error=?e, "Discarding an invalid network key cache entry");
+                self.remove(&key_id);
+                None
+            }
+        }
+    }
+
+    /// Returns `Ok(None)` if the entry is valid, but was derived from other key data.
+    fn decrypt_entry<T: DeserializeOwned>(
+        &self,
+        bytes: &[u8],
+        expected_header: &CacheEntryHeader,
+    ) -> DwalletMPCResult<Option<T>> {
+        let entry: CacheEntry = bcs::from_bytes(bytes)?;
+        if entry.header != *expected_header {
+            return Ok(None);
+        }
+        let nonce = Nonce::from_bytes(&entry.nonce)
+            .map_err(|e| DwalletMPCError::NetworkKeyCache(e.to_string()))?;
+        let plaintext = self
+            .cipher
+            .decrypt_authenticated(&nonce, &bcs::to_bytes(&entry.header)?, &entry.ciphertext)
+            .map_err(|e| {
+                DwalletMPCError::NetworkKeyCache(format!("integrity check failed: {e}"))
+            })?;
+
+        Ok(Some(bcs::from_bytes(&plaintext)?))
+    }
+
+    /// Stores `value` as the cached data of `key_data` for `epoch`,
+    /// replacing any previous entry for the key.
+    pub(crate) fn store<T: Serialize>(
+        &self,
+        key_id: ObjectID,
+        epoch: EpochId,
+        key_data: &DWalletNetworkEncryptionKeyData,
+        value: &T,
+    ) -> DwalletMPCResult<()> {
+        let header = CacheEntryHeader {
+            key_id,
+            epoch,
+            output_digest: network_key_output_digest(key_data),
+        };
+        let mut nonce_bytes = [0u8; NONCE_LENGTH];
+        group::OsCsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = Nonce::from_bytes(&nonce_bytes)
+            .map_err(|e| DwalletMPCError::NetworkKeyCache(e.to_string()))?;
+        let ciphertext = self.cipher.encrypt_authenticated(
+            &nonce,
+            &bcs::to_bytes(&header)?,
+            &bcs::to_bytes(value)?,
+        );
+        let entry = bcs::to_bytes(&CacheEntry {
+            header,
+            nonce: nonce_bytes,
+            ciphertext,
+        })?;
+
+        // Write to a temporary file first, so a crash never leaves a partial entry behind.
+        let path = self.entry_path(&key_id);
+        let temporary_path = path.with_extension("tmp");
+        let write = || -> std::io::Result<()> {
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            // Only the node may read the entries.
+            #[cfg(unix)]
+            options.mode(0o600);
+            let mut file = options.open(&temporary_path)?;
+            file.write_all(&entry)?;
+            file.sync_all()?;
+            fs::rename(&temporary_path, &path)
+        };
+        write().map_err(|e| {
+            DwalletMPCError::NetworkKeyCache(format!("failed to write {}: {e}", path.display()))
+        })
+    }
+
+    fn remove(&self, key_id: &ObjectID) {
+        let path = self.entry_path(key_id);
+        if let Err(e) = fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!(?path, error=?e, "failed to remove a network key cache entry");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ika_types::messages_dwallet_mpc::DWalletNetworkEncryptionKeyState;
+
+    fn key_data(reconfiguration_public_output: Vec<u8>) -> DWalletNetworkEncryptionKeyData {
+        DWalletNetworkEncryptionKeyData {
+            id: ObjectID::random(),
+            current_epoch: 1,
+            current_reconfiguration_public_output: reconfiguration_public_output,
+            network_dkg_public_output: vec![1, 2, 3],
+            state: DWalletNetworkEncryptionKeyState::NetworkDKGCompleted,
+        }
+    }
+
+    #[test]
+    fn test_cache_round_trip_and_invalidation() {
+        let directory = tempfile::tempdir().unwrap();
+        let cache =
+            NetworkKeyCache::new(directory.path().to_path_buf(), &RootSeed::random_seed()).unwrap();
+        let key_id = ObjectID::random();
+        let key_data = key_data(vec![]);
+
+        cache
+            .store(key_id, 1, &key_data, &vec![7u64, 8, 9])
+            .unwrap();
+        assert_eq!(
+            cache.load::<Vec<u64>>(key_id, 1, &key_data),
+            Some(vec![7, 8, 9])
+        );
+
+        // A reconfiguration of the key invalidates the entry.
+        let reconfigured_key_data = key_data(vec![4, 5, 6]);
+        assert_eq!(
+            cache.load::<Vec<u64>>(key_id, 1, &reconfigured_key_data),
+            None
+        );
+        assert_eq!(cache.load::<Vec<u64>>(key_id, 1, &key_data), None);
+
+        // So does a new epoch.
+        cache
+            .store(key_id, 1, &key_data, &vec![7u64, 8, 9])
+            .unwrap();
+        assert_eq!(cache.load::<Vec<u64>>(key_id, 2, &key_data), None);
+    }
+
+    #[test]
+    fn test_cache_rejects_tampered_and_foreign_entries() {
+        let directory = tempfile::tempdir().unwrap();
+        let cache =
+            NetworkKeyCache::new(directory.path().to_path_buf(), &RootSeed::random_seed()).unwrap();
+        let key_id = ObjectID::random();
+        let key_data = key_data(vec![]);
+
+        cache
+            .store(key_id, 1, &key_data, &vec![7u64, 8, 9])
+            .unwrap();
+        let path = cache.entry_path(&key_id);
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, bytes).unwrap();
+        assert_eq!(cache.load::<Vec<u64>>(key_id, 1, &key_data), None);
+        assert!(!path.exists());
+
+        // An entry encrypted by another validator can't be decrypted.
+        cache
+            .store(key_id, 1, &key_data, &vec![7u64, 8, 9])
+            .unwrap();
+        let other_cache =
+            NetworkKeyCache::new(directory.path().to_path_buf(), &RootSeed::random_seed()).unwrap();
+        assert_eq!(other_cache.load::<Vec<u64>>(key_id, 1, &key_data), None);
+    }
+}
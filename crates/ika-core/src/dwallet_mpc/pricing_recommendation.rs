@@ -0,0 +1,518 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Derives a recommended pricing vote from the measured cost of MPC computations.
+//!
+//! The [`DWalletMPCMetrics`](crate::dwallet_mpc::dwallet_mpc_metrics::DWalletMPCMetrics)
+//! record, per protocol and MPC round, the average computation duration, the number of
+//! completed advances and the volume of MPC messages received.
+//! The node reads the accumulated [`RoundCostSample`]s directly from its metrics, while the
+//! CLI parses them back from the Prometheus text exposition of a validator.
+//! Either way, the per-round costs are summed into a per-session cost for every
+//! `(curve, signature_algorithm, protocol)` pricing key, and converted into a `fee_ika`
+//! using the validator's [`PricingRecommendationConfig`].
+//!
+//! The gas fee reimbursements are paid in SUI and do not depend on the computation cost,
+//! so they are always carried over from the current pricing.
+
+use crate::dwallet_mpc::dwallet_mpc_metrics::RoundCostSample;
+use ika_config::node::PricingRecommendationConfig;
+use ika_types::messages_dwallet_mpc::{
+    BATCH_SIGN_STR_KEY, DKG_FIRST_ROUND_PROTOCOL_FLAG, DKG_SECOND_ROUND_PROTOCOL_FLAG,
+    DWALLET_KEY_REFRESH_PROTOCOL_FLAG, DWALLET_KEY_REFRESH_STR_KEY, FUTURE_SIGN_PROTOCOL_FLAG,
+    IMPORTED_KEY_DWALLET_VERIFICATION_PROTOCOL_FLAG,
+    MAKE_DWALLET_USER_SECRET_KEY_SHARE_PUBLIC_PROTOCOL_FLAG, PRESIGN_PROTOCOL_FLAG,
+    RE_ENCRYPT_USER_SHARE_PROTOCOL_FLAG, SIGN_PROTOCOL_FLAG, SIGN_STR_KEY,
+    SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG, THRESHOLD_DECRYPTION_PROTOCOL_FLAG,
+    THRESHOLD_DECRYPTION_STR_KEY,
+};
+use ika_types::sui::{PricingInfoKey, PricingInfoValue};
+use serde::Serialize;
+use std::collections::HashMap;
+use sui_types::collection_types::Entry;
+
+const COMPUTATION_DURATION_AVG_METRIC: &str = "dwallet_mpc_computation_duration_avg";
+const ADVANCE_COMPLETIONS_METRIC: &str = "dwallet_mpc_advance_completions";
+const RECEIVED_MESSAGES_BYTES_METRIC: &str = "dwallet_mpc_received_messages_bytes";
+
+const BYTES_IN_KILOBYTE: u128 = 1024;
+
+/// The measured cost of a single session of a protocol.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProtocolCostObservation {
+    /// The sum of the average computation duration of every round of the protocol.
+    pub computation_duration_ms: u64,
+    /// The sum of the average volume of MPC messages received in every round of the protocol.
+    pub received_messages_bytes: u64,
+}
+
+/// A pricing entry whose recommended `fee_ika` differs from the current one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PricingDiffEntry {
+    pub key: PricingInfoKey,
+    pub current_fee_ika: u64,
+    pub recommended_fee_ika: u64,
+}
+
+/// The metrics of a single MPC round of a protocol, for a single hash scheme.
+#[derive(Debug, Default)]
+struct RoundCost {
+    computation_duration_avg_ms: u128,
+    completions: u128,
+    received_messages_bytes: u128,
+}
+
+/// Computes the observed per-session cost of every pricing key from the accumulated
+/// [`RoundCostSample`]s of the dWallet MPC metrics.
+///
+/// The duration and the message volume of a round are both averaged over the same
+/// advance completions. Protocols whose rounds were never completed by this validator
+/// are omitted.
+pub fn observed_costs(
+    round_costs: &[RoundCostSample],
+) -> HashMap<PricingInfoKey, ProtocolCostObservation> {
+    // (protocol_name, curve, signature_algorithm) -> observation.
+    let mut protocols_costs: HashMap<(&str, &str, &str), ProtocolCostObservation> =
+        HashMap::new();
+    for round_cost in round_costs {
+        if round_cost.completions == 0 {
+            continue;
+        }
+        let observation = protocols_costs
+            .entry((
+                round_cost.protocol_name.as_str(),
+                round_cost.curve.as_str(),
+                round_cost.signature_algorithm.as_str(),
+            ))
+            .or_default();
+        observation.computation_duration_ms = observation
+            .computation_duration_ms
+            .saturating_add(round_cost.total_duration_ms / round_cost.completions);
+        observation.received_messages_bytes = observation
+            .received_messages_bytes
+            .saturating_add(round_cost.received_messages_bytes / round_cost.completions);
+    }
+
+    let mut observed_costs: HashMap<PricingInfoKey, ProtocolCostObservation> = HashMap::new();
+    for ((protocol_name, curve, signature_algorithm), observation) in protocols_costs {
+        let Some(curve) = curve_from_label(curve) else {
+            continue;
+        };
+        let Ok(signature_algorithm) = signature_algorithm_from_label(signature_algorithm) else {
+            continue;
+        };
+        if observation == ProtocolCostObservation::default() {
+            continue;
+        }
+        for protocol in protocols_from_label(protocol_name) {
+            observed_costs.insert(
+                PricingInfoKey {
+                    curve,
+                    signature_algorithm,
+                    protocol: *protocol,
+                },
+                observation.clone(),
+            );
+        }
+    }
+
+    observed_costs
+}
+
+/// Parses the Prometheus text exposition of the dWallet MPC metrics of a (possibly remote)
+/// validator into the observed per-session cost of every pricing key.
+///
+/// See [`observed_costs`].
+pub fn parse_observed_costs(metrics_text: &str) -> HashMap<PricingInfoKey, ProtocolCostObservation> {
+    // (protocol_name, curve, signature_algorithm, mpc_round, hash_scheme) -> cost.
+    let mut rounds_costs: HashMap<(String, String, String, String, String), RoundCost> =
+        HashMap::new();
+
+    for line in metrics_text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, labels, value)) = parse_sample(line) else {
+            continue;
+        };
+        if ![
+            COMPUTATION_DURATION_AVG_METRIC,
+            ADVANCE_COMPLETIONS_METRIC,
+            RECEIVED_MESSAGES_BYTES_METRIC,
+        ]
+        .contains(&name)
+        {
+            continue;
+        }
+        let label = |label_name: &str| labels.get(label_name).cloned().unwrap_or_default();
+        let round_cost = rounds_costs
+            .entry((
+                label("protocol_name"),
+                label("curve"),
+                label("signature_algorithm"),
+                label("mpc_round"),
+                label("hash_scheme"),
+            ))
+            .or_default();
+
+        // Gauges are exported as floats, but all the values we record are integral.
+        let value = value.max(0.0) as u128;
+        match name {
+            COMPUTATION_DURATION_AVG_METRIC => round_cost.computation_duration_avg_ms = value,
+            ADVANCE_COMPLETIONS_METRIC => round_cost.completions = value,
+            RECEIVED_MESSAGES_BYTES_METRIC => round_cost.received_messages_bytes = value,
+            _ => {}
+        }
+    }
+
+    // The hash scheme is not part of the pricing key, so the label sets of every
+    // round are merged into a single sample.
+    let mut samples: HashMap<(String, String, String, u64), RoundCostSample> = HashMap::new();
+    for ((protocol_name, curve, signature_algorithm, mpc_round, _), round_cost) in rounds_costs {
+        let Ok(mpc_round) = mpc_round.parse::<u64>() else {
+            continue;
+        };
+        let sample = samples
+            .entry((
+                protocol_name.clone(),
+                curve.clone(),
+                signature_algorithm.clone(),
+                mpc_round,
+            ))
+            .or_insert_with(|| RoundCostSample {
+                protocol_name,
+                curve,
+                signature_algorithm,
+                mpc_round,
+                ..Default::default()
+            });
+        sample.completions = sample
+            .completions
+            .saturating_add(saturating_u64(round_cost.completions));
+        sample.total_duration_ms = sample.total_duration_ms.saturating_add(saturating_u64(
+            round_cost.computation_duration_avg_ms * round_cost.completions,
+        ));
+        sample.received_messages_bytes = sample
+            .received_messages_bytes
+            .saturating_add(saturating_u64(round_cost.received_messages_bytes));
+    }
+
+    observed_costs(&samples.into_values().collect::<Vec<_>>())
+}
+
+/// Computes the recommended pricing from the `current_pricing` and the `observed_costs`.
+///
+/// Only the `fee_ika` of keys with an observed cost is changed; the gas fee reimbursements,
+/// and the pricing of protocols this validator never ran, are kept as they are.
+pub fn recommend_pricing(
+    current_pricing: &[Entry<PricingInfoKey, PricingInfoValue>],
+    observed_costs: &HashMap<PricingInfoKey, ProtocolCostObservation>,
+    config: &PricingRecommendationConfig,
+) -> Vec<Entry<PricingInfoKey, PricingInfoValue>> {
+    current_pricing
+        .iter()
+        .map(|entry| {
+            let mut value = entry.value.clone();
+            if let Some(observation) = observed_costs.get(&entry.key) {
+                value.fee_ika = fee_ika_for_observation(observation, config);
+            }
+
+            Entry {
+                key: entry.key.clone(),
+                value,
+            }
+        })
+        .collect()
+}
+
+/// Returns the entries whose `fee_ika` changed between `current_pricing` and `recommended_pricing`.
+pub fn diff_pricing(
+    current_pricing: &[Entry<PricingInfoKey, PricingInfoValue>],
+    recommended_pricing: &[Entry<PricingInfoKey, PricingInfoValue>],
+) -> Vec<PricingDiffEntry> {
+    recommended_pricing
+        .iter()
+        .filter_map(|recommended| {
+            let current_fee_ika = current_pricing
+                .iter()
+                .find(|current| current.key == recommended.key)
+                .map(|current| current.value.fee_ika)
+                .unwrap_or_default();
+
+            (current_fee_ika != recommended.value.fee_ika).then(|| PricingDiffEntry {
+                key: recommended.key.clone(),
+                current_fee_ika,
+                recommended_fee_ika: recommended.value.fee_ika,
+            })
+        })
+        .collect()
+}
+
+fn fee_ika_for_observation(
+    observation: &ProtocolCostObservation,
+    config: &PricingRecommendationConfig,
+) -> u64 {
+    let computation_fee =
+        observation.computation_duration_ms as u128 * config.ika_per_computation_ms as u128;
+    let messages_fee = (observation.received_messages_bytes as u128
+        * config.ika_per_message_kilobyte as u128)
+        .div_ceil(BYTES_IN_KILOBYTE);
+
+    saturating_u64(computation_fee + messages_fee).max(config.minimum_fee_ika)
+}
+
+fn saturating_u64(value: u128) -> u64 {
+    u64::try_from(value).unwrap_or(u64::MAX)
+}
+
+/// Parses a single sample line of the form `name{label="value",...} 123`.
+fn parse_sample(line: &str) -> Option<(&str, HashMap<String, String>, f64)> {
+    let (name, rest) = match line.find('{') {
+        Some(labels_start) => (&line[..labels_start], &line[labels_start + 1..]),
+        None => {
+            let (name, value) = line.split_once(char::is_whitespace)?;
+            return Some((name, HashMap::new(), parse_value(value)?));
+        }
+    };
+
+    let mut labels = HashMap::new();
+    let mut chars = rest.char_indices().peekable();
+    let labels_end = loop {
+        // Skip separators.
+        while let Some((_, ',' | ' ')) = chars.peek() {
+            chars.next();
+        }
+        let (name_start, c) = chars.next()?;
+        if c == '}' {
+            break name_start;
+        }
+        let mut name_end = name_start;
+        for (index, c) in chars.by_ref() {
+            if c == '=' {
+                name_end = index;
+                break;
+            }
+        }
+        let label_name = rest[name_start..name_end].trim().to_string();
+        if chars.next()?.1 != '"' {
+            return None;
+        }
+        let mut label_value = String::new();
+        loop {
+            match chars.next()? {
+                (_, '\\') => label_value.push(chars.next()?.1),
+                (_, '"') => break,
+                (_, c) => label_value.push(c),
+            }
+        }
+        labels.insert(label_name, label_value);
+    };
+
+    Some((name, labels, parse_value(&rest[labels_end + 1..])?))
+}
+
+fn parse_value(value: &str) -> Option<f64> {
+    value.split_whitespace().next()?.parse().ok()
+}
+
+fn curve_from_label(curve: &str) -> Option<u32> {
+    match curve {
+        "Secp256k1" => Some(0),
+        _ => None,
+    }
+}
+
+/// Protocols without a signature algorithm are labeled with an empty string.
+fn signature_algorithm_from_label(signature_algorithm: &str) -> Result<Option<u32>, ()> {
+    match signature_algorithm {
+        "" => Ok(None),
+        "ECDSA" => Ok(Some(0)),
+        _ => Err(()),
+    }
+}
+
+/// Maps the `protocol_name` label (the `Display` of `MPCRequestInput`) to the pricing protocol flags.
+/// System protocols (network DKG and reconfiguration) are not priced.
+fn protocols_from_label(protocol_name: &str) -> &'static [u32] {
+    match protocol_name {
+        "dWalletDKGFirstRound" => &[DKG_FIRST_ROUND_PROTOCOL_FLAG],
+        "dWalletDKGSecondRound" => &[DKG_SECOND_ROUND_PROTOCOL_FLAG],
+        "EncryptedShareVerification" => &[RE_ENCRYPT_USER_SHARE_PROTOCOL_FLAG],
+        "MakeDWalletUserSecretKeySharesPublicRequest" => {
+            &[MAKE_DWALLET_USER_SECRET_KEY_SHARE_PUBLIC_PROTOCOL_FLAG]
+        }
+        "DWalletImportedKeyVerificationRequestEvent" => {
+            &[IMPORTED_KEY_DWALLET_VERIFICATION_PROTOCOL_FLAG]
+        }
+        "Presign" => &[PRESIGN_PROTOCOL_FLAG],
+        // A sign with a partial user signature runs the very same sign session.
+        SIGN_STR_KEY => &[SIGN_PROTOCOL_FLAG, SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG],
+        // A batch sign is charged the sign price for every message in the batch,
+        // but the cost of its session grows with the batch size, which is not labeled,
+        // so it must not override the cost observed for a single sign.
+        BATCH_SIGN_STR_KEY => &[],
+        "PartialSignatureVerification" => &[FUTURE_SIGN_PROTOCOL_FLAG],
+        THRESHOLD_DECRYPTION_STR_KEY => &[THRESHOLD_DECRYPTION_PROTOCOL_FLAG],
+        DWALLET_KEY_REFRESH_STR_KEY => &[DWALLET_KEY_REFRESH_PROTOCOL_FLAG],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: &str = r#"
+# HELP dwallet_mpc_computation_duration_avg Average duration of MPC computations in milliseconds
+# TYPE dwallet_mpc_computation_duration_avg gauge
+dwallet_mpc_computation_duration_avg{curve="Secp256k1",hash_scheme="",mpc_round="1",protocol_name="Presign",signature_algorithm="ECDSA"} 100
+dwallet_mpc_computation_duration_avg{curve="Secp256k1",hash_scheme="",mpc_round="2",protocol_name="Presign",signature_algorithm="ECDSA"} 300
+dwallet_mpc_advance_completions{curve="Secp256k1",hash_scheme="",mpc_round="1",protocol_name="Presign",signature_algorithm="ECDSA"} 4
+dwallet_mpc_advance_completions{curve="Secp256k1",hash_scheme="",mpc_round="2",protocol_name="Presign",signature_algorithm="ECDSA"} 4
+dwallet_mpc_received_messages_bytes{curve="Secp256k1",hash_scheme="",mpc_round="1",protocol_name="Presign",signature_algorithm="ECDSA"} 8192
+dwallet_mpc_computation_duration_avg{curve="",hash_scheme="",mpc_round="1",protocol_name="NetworkEncryptionKeyDkg",signature_algorithm=""} 5000
+dwallet_mpc_advance_completions{curve="",hash_scheme="",mpc_round="1",protocol_name="NetworkEncryptionKeyDkg",signature_algorithm=""} 1
+"#;
+
+    fn presign_key() -> PricingInfoKey {
+        PricingInfoKey {
+            curve: 0,
+            signature_algorithm: Some(0),
+            protocol: PRESIGN_PROTOCOL_FLAG,
+        }
+    }
+
+    #[test]
+    fn parses_observed_costs() {
+        let observed_costs = parse_observed_costs(METRICS);
+
+        assert_eq!(observed_costs.len(), 1);
+        assert_eq!(
+            observed_costs.get(&presign_key()),
+            Some(&ProtocolCostObservation {
+                computation_duration_ms: 400,
+                received_messages_bytes: 2048,
+            })
+        );
+    }
+
+    #[test]
+    fn computes_observed_costs_from_round_costs() {
+        let presign_round = |mpc_round, total_duration_ms, received_messages_bytes| {
+            RoundCostSample {
+                protocol_name: "Presign".to_string(),
+                curve: "Secp256k1".to_string(),
+                signature_algorithm: "ECDSA".to_string(),
+                mpc_round,
+                completions: 4,
+                total_duration_ms,
+                received_messages_bytes,
+            }
+        };
+        let round_costs = vec![
+            presign_round(1, 400, 0),
+            presign_round(2, 1200, 8192),
+            RoundCostSample {
+                protocol_name: "NetworkEncryptionKeyDkg".to_string(),
+                mpc_round: 1,
+                completions: 1,
+                total_duration_ms: 5000,
+                ..Default::default()
+            },
+        ];
+
+        let observed_costs = observed_costs(&round_costs);
+
+        assert_eq!(observed_costs.len(), 1);
+        assert_eq!(
+            observed_costs.get(&presign_key()),
+            Some(&ProtocolCostObservation {
+                computation_duration_ms: 400,
+                received_messages_bytes: 2048,
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_batch_sign_costs() {
+        let round = |protocol_name: &str, total_duration_ms| RoundCostSample {
+            protocol_name: protocol_name.to_string(),
+            curve: "Secp256k1".to_string(),
+            signature_algorithm: "ECDSA".to_string(),
+            mpc_round: 1,
+            completions: 4,
+            total_duration_ms,
+            received_messages_bytes: 0,
+        };
+        let round_costs = vec![round(SIGN_STR_KEY, 400), round(BATCH_SIGN_STR_KEY, 4000)];
+
+        let observed_costs = observed_costs(&round_costs);
+
+        let sign_observation = ProtocolCostObservation {
+            computation_duration_ms: 100,
+            received_messages_bytes: 0,
+        };
+        assert_eq!(observed_costs.len(), 2);
+        for protocol in [
+            SIGN_PROTOCOL_FLAG,
+            SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG,
+        ] {
+            let key = PricingInfoKey {
+                curve: 0,
+                signature_algorithm: Some(0),
+                protocol,
+            };
+            assert_eq!(observed_costs.get(&key), Some(&sign_observation));
+        }
+    }
+
+    #[test]
+    fn recommends_and_diffs_pricing() {
+        let current_value = PricingInfoValue {
+            fee_ika: 10,
+            gas_fee_reimbursement_sui: 20,
+            gas_fee_reimbursement_sui_for_system_calls: 30,
+        };
+        let sign_key = PricingInfoKey {
+            curve: 0,
+            signature_algorithm: Some(0),
+            protocol: SIGN_PROTOCOL_FLAG,
+        };
+        let current_pricing = vec![
+            Entry {
+                key: presign_key(),
+                value: current_value.clone(),
+            },
+            Entry {
+                key: sign_key,
+                value: current_value.clone(),
+            },
+        ];
+        let config = PricingRecommendationConfig {
+            ika_per_computation_ms: 2,
+            ika_per_message_kilobyte: 3,
+            minimum_fee_ika: 1,
+            interval_secs: 60,
+            output_path: None,
+        };
+
+        let recommended_pricing =
+            recommend_pricing(&current_pricing, &parse_observed_costs(METRICS), &config);
+
+        // 400ms * 2 + 2KB * 3.
+        assert_eq!(recommended_pricing[0].value.fee_ika, 806);
+        assert_eq!(
+            recommended_pricing[0].value.gas_fee_reimbursement_sui,
+            current_value.gas_fee_reimbursement_sui
+        );
+        assert_eq!(recommended_pricing[1].value, current_value);
+        assert_eq!(
+            diff_pricing(&current_pricing, &recommended_pricing),
+            vec![PricingDiffEntry {
+                key: presign_key(),
+                current_fee_ika: 10,
+                recommended_fee_ika: 806,
+            }]
+        );
+    }
+}
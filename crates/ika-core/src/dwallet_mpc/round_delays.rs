@@ -0,0 +1,397 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Adaptive consensus round delays for MPC rounds.
+//!
+//! Some rounds of some protocols (the third round of the network DKG and of the network key
+//! reconfiguration) benefit from advancing with the messages of more parties than the threshold,
+//! so they wait a number of consensus rounds after the threshold is reached before advancing.
+//!
+//! Instead of always waiting a fixed number of rounds, the delay is estimated from how long
+//! it takes the rest of the committee to send its messages once the threshold is reached,
+//! as observed over the recent rounds of all sessions.
+//! The observations are made solely from the messages in consensus, in consensus order,
+//! so all validators agree on them.
+//! To keep the delay of a round agreed upon as well, it is taken from the estimate at the
+//! consensus round in which the threshold of the previous round was reached.
+
+use group::PartyID;
+use ika_protocol_config::ProtocolConfig;
+use ika_types::messages_dwallet_mpc::MPCRequestInput;
+use mpc::WeightedThresholdAccessStructure;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The minimum number of observations to estimate a delay from,
+/// before which the configured delays are used.
+const MIN_OBSERVATIONS_FOR_ESTIMATE: usize = 5;
+/// The percentile of the observed delays the estimate covers.
+const ESTIMATE_PERCENTILE: usize = 90;
+/// Consensus rounds added on top of the observed percentile, to absorb jitter.
+const ESTIMATE_MARGIN: u64 = 1;
+
+/// The delays of the MPC rounds, as configured in the protocol config.
+#[derive(Clone, Debug)]
+pub(crate) struct RoundDelaysConfig {
+    pub(crate) network_dkg_third_round_delay: u64,
+    pub(crate) decryption_key_reconfiguration_third_round_delay: u64,
+    /// Set if the delays are adapted to the observed message arrivals.
+    pub(crate) adaptive: Option<AdaptiveRoundDelaysConfig>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct AdaptiveRoundDelaysConfig {
+    pub(crate) min_delay: u64,
+    pub(crate) max_delay: u64,
+    /// The number of most recent observations the estimate is computed from.
+    pub(crate) observations_window: usize,
+}
+
+impl RoundDelaysConfig {
+    pub(crate) fn from_protocol_config(protocol_config: &ProtocolConfig) -> Self {
+        let adaptive =
+            protocol_config
+                .adaptive_mpc_round_delays()
+                .then(|| AdaptiveRoundDelaysConfig {
+                    min_delay: protocol_config.min_adaptive_mpc_round_delay(),
+                    max_delay: protocol_config.max_adaptive_mpc_round_delay(),
+                    observations_window: protocol_config
+                        .adaptive_mpc_round_delay_observations_window()
+                        as usize,
+                });
+
+        Self {
+            network_dkg_third_round_delay: protocol_config.network_dkg_third_round_delay(),
+            decryption_key_reconfiguration_third_round_delay: protocol_config
+                .decryption_key_reconfiguration_third_round_delay(),
+            adaptive,
+        }
+    }
+
+    /// The configured delay for advancing `request_input` to `mpc_round`,
+    /// or `None` if that round is never delayed.
+    pub(crate) fn configured_delay(
+        &self,
+        request_input: &MPCRequestInput,
+        mpc_round: u64,
+    ) -> Option<u64> {
+        match request_input {
+            MPCRequestInput::NetworkEncryptionKeyDkg(_, _) if mpc_round == 3 => {
+                Some(self.network_dkg_third_round_delay)
+            }
+            MPCRequestInput::NetworkEncryptionKeyReconfiguration(_) if mpc_round == 3 => {
+                Some(self.decryption_key_reconfiguration_third_round_delay)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The arrival of the messages of a single MPC round of a session, by consensus round.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RoundMessageArrivals {
+    senders: HashSet<PartyID>,
+    /// The consensus round in which the messages of the senders first satisfied the threshold.
+    threshold_reached_at: Option<u64>,
+    last_arrival_at: u64,
+    /// The estimated delay at `threshold_reached_at`,
+    /// which is the delay to advance to the next round with.
+    pub(crate) delay_estimate: Option<u64>,
+    observed: bool,
+}
+
+impl RoundMessageArrivals {
+    /// Records the first message of `sender_party_id` for this round.
+    ///
+    /// `delay_estimate` is the current estimate, kept if this message completes the threshold.
+    pub(crate) fn record(
+        &mut self,
+        consensus_round: u64,
+        sender_party_id: PartyID,
+        access_structure: &WeightedThresholdAccessStructure,
+        delay_estimate: Option<u64>,
+    ) {
+        if !self.senders.insert(sender_party_id) {
+            return;
+        }
+        self.last_arrival_at = consensus_round;
+
+        if self.threshold_reached_at.is_none()
+            && access_structure.is_authorized_subset(&self.senders).is_ok()
+        {
+            self.threshold_reached_at = Some(consensus_round);
+            self.delay_estimate = delay_estimate;
+        }
+    }
+
+    /// The parties that sent a message for this round.
+    pub(crate) fn senders(&self) -> &HashSet<PartyID> {
+        &self.senders
+    }
+
+    /// Returns the number of consensus rounds it took for the messages to arrive after the
+    /// threshold was reached, once all parties sent their message, or once `max_delay`
+    /// consensus rounds passed without them.
+    ///
+    /// Each round is observed at most once.
+    fn observe(
+        &mut self,
+        consensus_round: u64,
+        number_of_parties: usize,
+        max_delay: u64,
+    ) -> Option<u64> {
+        let threshold_reached_at = self.threshold_reached_at?;
+        if self.observed
+            || (self.senders.len() < number_of_parties
+                && consensus_round < threshold_reached_at + max_delay)
+        {
+            return None;
+        }
+        self.observed = true;
+
+        // Parties that sent no message in the window are considered faulty rather than slow,
+        // and should not delay the rounds.
+        Some(self.last_arrival_at.min(threshold_reached_at + max_delay) - threshold_reached_at)
+    }
+
+    /// Whether this round may still be observed at the end of a later consensus round.
+    ///
+    /// A round whose threshold was never reached is kept until `max_delay` consensus rounds
+    /// passed without new messages.
+    fn is_observable(&self, consensus_round: u64, max_delay: u64) -> bool {
+        let observed_from = self.threshold_reached_at.unwrap_or(self.last_arrival_at);
+        !self.observed && consensus_round < observed_from + max_delay
+    }
+}
+
+/// Estimates the delay of the MPC rounds from recent observations.
+pub(crate) struct AdaptiveRoundDelays {
+    config: RoundDelaysConfig,
+    observations: VecDeque<u64>,
+}
+
+impl AdaptiveRoundDelays {
+    pub(crate) fn new(config: RoundDelaysConfig) -> Self {
+        Self {
+            config,
+            observations: VecDeque::new(),
+        }
+    }
+
+    /// The current delay estimate, or `None` if the delays are not adaptive,
+    /// or not enough was observed yet.
+    pub(crate) fn estimate(&self) -> Option<u64> {
+        let adaptive = self.config.adaptive.as_ref()?;
+        if self.observations.len() < MIN_OBSERVATIONS_FOR_ESTIMATE {
+            return None;
+        }
+
+        let mut observations: Vec<_> = self.observations.iter().copied().collect();
+        observations.sort_unstable();
+        let percentile_index = (observations.len() * ESTIMATE_PERCENTILE).div_ceil(100) - 1;
+
+        Some(
+            (observations[percentile_index] + ESTIMATE_MARGIN)
+                .clamp(adaptive.min_delay, adaptive.max_delay),
+        )
+    }
+
+    /// Observes the rounds of `arrivals` whose messages all arrived, or stopped arriving,
+    /// by the end of `consensus_round`.
+    ///
+    /// Must be called once per consensus round, after its messages were recorded.
+    pub(crate) fn observe<'a>(
+        &mut self,
+        consensus_round: u64,
+        number_of_parties: usize,
+        arrivals: impl Iterator<Item = &'a mut RoundMessageArrivals>,
+    ) {
+        let Some(adaptive) = &self.config.adaptive else {
+            return;
+        };
+
+        let mut new_observations: Vec<_> = arrivals
+            .filter_map(|arrivals| {
+                arrivals.observe(consensus_round, number_of_parties, adaptive.max_delay)
+            })
+            .collect();
+        // Sessions are iterated in an arbitrary order, so sort the observations to make
+        // the window the same for all validators.
+        new_observations.sort_unstable();
+
+        self.observations.extend(new_observations);
+        while self.observations.len() > adaptive.observations_window {
+            self.observations.pop_front();
+        }
+    }
+
+    /// Drops the arrivals of the rounds of a session that can no longer be observed,
+    /// so only the observation window is kept in memory.
+    ///
+    /// The rounds from `keep_from_mpc_round` on are always kept, as an active session
+    /// still needs the arrivals of the round it waits for.
+    pub(crate) fn retain_observable(
+        &self,
+        consensus_round: u64,
+        arrivals: &mut HashMap<u64, RoundMessageArrivals>,
+        keep_from_mpc_round: Option<u64>,
+    ) {
+        let max_delay = self
+            .config
+            .adaptive
+            .as_ref()
+            .map_or(0, |adaptive| adaptive.max_delay);
+        arrivals.retain(|mpc_round, round_arrivals| {
+            keep_from_mpc_round.is_some_and(|keep_from_mpc_round| *mpc_round >= keep_from_mpc_round)
+                || round_arrivals.is_observable(consensus_round, max_delay)
+        });
+    }
+
+    /// The number of consensus rounds to wait before advancing to `mpc_round`,
+    /// after the threshold of messages for the previous round was reached.
+    ///
+    /// `delay_estimate` is the estimate at the time the threshold was reached, which is used
+    /// for the rounds that are delayed instead of the configured delay, once available.
+    pub(crate) fn delay(
+        &self,
+        request_input: &MPCRequestInput,
+        mpc_round: u64,
+        delay_estimate: Option<u64>,
+    ) -> u64 {
+        let Some(configured_delay) = self.config.configured_delay(request_input, mpc_round) else {
+            return 0;
+        };
+        if self.config.adaptive.is_none() {
+            return configured_delay;
+        }
+
+        delay_estimate.unwrap_or(configured_delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::OsCsRng;
+
+    fn access_structure() -> WeightedThresholdAccessStructure {
+        // Four parties of equal weight, any three of which are authorized.
+        WeightedThresholdAccessStructure::uniform(3, 4, 4, &mut OsCsRng).unwrap()
+    }
+
+    fn adaptive_round_delays() -> AdaptiveRoundDelays {
+        AdaptiveRoundDelays::new(RoundDelaysConfig {
+            network_dkg_third_round_delay: 10,
+            decryption_key_reconfiguration_third_round_delay: 10,
+            adaptive: Some(AdaptiveRoundDelaysConfig {
+                min_delay: 1,
+                max_delay: 20,
+                observations_window: 10,
+            }),
+        })
+    }
+
+    #[test]
+    fn observes_the_arrival_of_all_parties_after_the_threshold() {
+        let access_structure = access_structure();
+        let mut arrivals = RoundMessageArrivals::default();
+        for (consensus_round, party_id) in [(5, 1), (5, 2), (6, 3)] {
+            arrivals.record(consensus_round, party_id, &access_structure, Some(4));
+        }
+        assert_eq!(arrivals.threshold_reached_at, Some(6));
+        assert_eq!(arrivals.delay_estimate, Some(4));
+        assert_eq!(arrivals.observe(8, 4, 20), None);
+
+        arrivals.record(9, 4, &access_structure, Some(7));
+        assert_eq!(arrivals.delay_estimate, Some(4));
+        assert_eq!(arrivals.observe(9, 4, 20), Some(3));
+        assert_eq!(arrivals.observe(10, 4, 20), None);
+    }
+
+    #[test]
+    fn missing_parties_are_observed_once_the_window_closes() {
+        let access_structure = access_structure();
+        let mut arrivals = RoundMessageArrivals::default();
+        for (consensus_round, party_id) in [(1, 1), (1, 2), (1, 3)] {
+            arrivals.record(consensus_round, party_id, &access_structure, None);
+        }
+
+        assert_eq!(arrivals.observe(20, 4, 20), None);
+        assert_eq!(arrivals.observe(21, 4, 20), Some(0));
+    }
+
+    #[test]
+    fn keeps_only_the_observable_arrivals() {
+        let access_structure = access_structure();
+        let mut round_delays = adaptive_round_delays();
+        let mut arrivals: HashMap<u64, RoundMessageArrivals> = HashMap::new();
+        // The threshold of the first round is reached, the second round is short of it.
+        for (consensus_round, party_id) in [(1, 1), (1, 2), (1, 3)] {
+            arrivals.entry(1).or_default().record(
+                consensus_round,
+                party_id,
+                &access_structure,
+                None,
+            );
+        }
+        arrivals
+            .entry(2)
+            .or_default()
+            .record(2, 1, &access_structure, None);
+
+        round_delays.observe(10, 4, arrivals.values_mut());
+        round_delays.retain_observable(10, &mut arrivals, None);
+        assert_eq!(arrivals.len(), 2);
+
+        // The first round is observed once its window closes.
+        round_delays.observe(21, 4, arrivals.values_mut());
+        round_delays.retain_observable(21, &mut arrivals, None);
+        assert_eq!(arrivals.keys().collect::<Vec<_>>(), vec![&2]);
+
+        // The second round never reaches the threshold, and is dropped once no message
+        // arrived for the window, unless the session still waits for it.
+        round_delays.retain_observable(22, &mut arrivals, Some(2));
+        assert_eq!(arrivals.len(), 1);
+        round_delays.retain_observable(22, &mut arrivals, None);
+        assert!(arrivals.is_empty());
+    }
+
+    #[test]
+    fn estimates_a_high_percentile_within_bounds() {
+        let mut round_delays = adaptive_round_delays();
+        let mut arrivals: Vec<_> = [0, 0, 1, 1]
+            .into_iter()
+            .map(|observation| RoundMessageArrivals {
+                senders: HashSet::from([1, 2, 3, 4]),
+                threshold_reached_at: Some(1),
+                last_arrival_at: 1 + observation,
+                delay_estimate: None,
+                observed: false,
+            })
+            .collect();
+        round_delays.observe(10, 4, arrivals.iter_mut());
+        assert_eq!(round_delays.estimate(), None);
+
+        let mut slow_arrivals = RoundMessageArrivals {
+            senders: HashSet::from([1, 2, 3, 4]),
+            threshold_reached_at: Some(1),
+            last_arrival_at: 4,
+            delay_estimate: None,
+            observed: false,
+        };
+        round_delays.observe(10, 4, std::iter::once(&mut slow_arrivals));
+        assert_eq!(round_delays.estimate(), Some(4));
+
+        // Only the most recent observations are kept.
+        for _ in 0..10 {
+            let mut fast_arrivals = RoundMessageArrivals {
+                senders: HashSet::from([1, 2, 3, 4]),
+                threshold_reached_at: Some(1),
+                last_arrival_at: 1,
+                delay_estimate: None,
+                observed: false,
+            };
+            round_delays.observe(10, 4, std::iter::once(&mut fast_arrivals));
+        }
+        assert_eq!(round_delays.estimate(), Some(1));
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Timeouts of MPC sessions that never complete.
+//!
+//! A session whose threshold of messages is never reached would otherwise keep waiting
+//! for messages forever, and its request on Sui would never be responded to.
+//! Instead, once a session did not complete within the timeout of its session type,
+//! counted in consensus rounds from the first consensus round in which the session was known,
+//! either from its request or from its first message, every validator submits a rejected
+//! output for it, which is agreed upon as any other output.
+//!
+//! Validators may learn of a request from Sui at slightly different consensus rounds,
+//! so they may time a session out at different consensus rounds as well, but the session is
+//! only rejected once a quorum of them agreed on the rejected output.
+
+use ika_protocol_config::ProtocolConfig;
+use ika_types::messages_dwallet_mpc::MPCRequestInput;
+
+/// The timeouts of MPC sessions, in consensus rounds, by session type.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SessionTimeoutsConfig {
+    pub(crate) dwallet_dkg: Option<u64>,
+    pub(crate) presign: Option<u64>,
+    pub(crate) sign: Option<u64>,
+    pub(crate) network_dkg: Option<u64>,
+    pub(crate) network_key_reconfiguration: Option<u64>,
+}
+
+impl SessionTimeoutsConfig {
+    pub(crate) fn from_protocol_config(protocol_config: &ProtocolConfig) -> Self {
+        Self {
+            dwallet_dkg: protocol_config.dwallet_dkg_session_timeout_consensus_rounds_as_option(),
+            presign: protocol_config.presign_session_timeout_consensus_rounds_as_option(),
+            sign: protocol_config.sign_session_timeout_consensus_rounds_as_option(),
+            network_dkg: protocol_config.network_dkg_session_timeout_consensus_rounds_as_option(),
+            network_key_reconfiguration: protocol_config
+                .network_key_reconfiguration_session_timeout_consensus_rounds_as_option(),
+        }
+    }
+
+    /// The number of consensus rounds a session of `request_input` has to complete,
+    /// or `None` if it never times out.
+    ///
+    /// Sessions that are computed locally, without waiting for the messages of other parties,
+    /// never time out.
+    pub(crate) fn timeout(&self, request_input: &MPCRequestInput) -> Option<u64> {
+        match request_input {
+            MPCRequestInput::DKGFirst(_)
+            | MPCRequestInput::DKGSecond(_)
+            | MPCRequestInput::DWalletImportedKeyVerificationRequest(_) => self.dwallet_dkg,
+            MPCRequestInput::Presign(_) => self.presign,
+            MPCRequestInput::Sign(_)
+            | MPCRequestInput::DerivedKeySign(_)
+            | MPCRequestInput::BatchSign(_)
+            | MPCRequestInput::ThresholdDecryption(_) => self.sign,
+            MPCRequestInput::NetworkEncryptionKeyDkg(_, _) => self.network_dkg,
+            MPCRequestInput::NetworkEncryptionKeyReconfiguration(_) => {
+                self.network_key_reconfiguration
+            }
+            MPCRequestInput::EncryptedShareVerification(_)
+            | MPCRequestInput::DWalletKeyRefresh(_)
+            | MPCRequestInput::PartialSignatureVerification(_)
+            | MPCRequestInput::MakeDWalletUserSecretKeySharesPublicRequest(_) => None,
+        }
+    }
+
+    /// Whether a session of `request_input` that started at `started_at_consensus_round`
+    /// timed out by `consensus_round`.
+    pub(crate) fn is_timed_out(
+        &self,
+        request_input: &MPCRequestInput,
+        started_at_consensus_round: u64,
+        consensus_round: u64,
+    ) -> bool {
+        self.timeout(request_input).is_some_and(|timeout| {
+            consensus_round >= started_at_consensus_round.saturating_add(timeout)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ika_types::messages_dwallet_mpc::test_helpers::mock_request_inputs;
+
+    fn session_timeouts() -> SessionTimeoutsConfig {
+        SessionTimeoutsConfig {
+            dwallet_dkg: Some(1),
+            presign: Some(2),
+            sign: Some(3),
+            network_dkg: Some(4),
+            network_key_reconfiguration: Some(5),
+        }
+    }
+
+    fn mock_request_input(is_kind: fn(&MPCRequestInput) -> bool) -> MPCRequestInput {
+        mock_request_inputs()
+            .into_iter()
+            .find(is_kind)
+            .expect("there is a mock request input of every kind")
+    }
+
+    #[test]
+    fn maps_every_request_to_the_timeout_of_its_session_type() {
+        let session_timeouts = session_timeouts();
+
+        for request_input in mock_request_inputs() {
+            let expected_timeout = match &request_input {
+                MPCRequestInput::DKGFirst(_)
+                | MPCRequestInput::DKGSecond(_)
+                | MPCRequestInput::DWalletImportedKeyVerificationRequest(_) => Some(1),
+                MPCRequestInput::Presign(_) => Some(2),
+                MPCRequestInput::Sign(_)
+                | MPCRequestInput::DerivedKeySign(_)
+                | MPCRequestInput::BatchSign(_)
+                | MPCRequestInput::ThresholdDecryption(_) => Some(3),
+                MPCRequestInput::NetworkEncryptionKeyDkg(..) => Some(4),
+                MPCRequestInput::NetworkEncryptionKeyReconfiguration(_) => Some(5),
+                MPCRequestInput::EncryptedShareVerification(_)
+                | MPCRequestInput::DWalletKeyRefresh(_)
+                | MPCRequestInput::PartialSignatureVerification(_)
+                | MPCRequestInput::MakeDWalletUserSecretKeySharesPublicRequest(_) => None,
+            };
+            assert_eq!(
+                session_timeouts.timeout(&request_input),
+                expected_timeout,
+                "{request_input}"
+            );
+        }
+    }
+
+    #[test]
+    fn times_out_exactly_at_the_timeout() {
+        let session_timeouts = session_timeouts();
+        let presign = mock_request_input(|request| matches!(request, MPCRequestInput::Presign(_)));
+
+        assert!(!session_timeouts.is_timed_out(&presign, 10, 11));
+        assert!(session_timeouts.is_timed_out(&presign, 10, 12));
+        assert!(session_timeouts.is_timed_out(&presign, 10, 13));
+
+        let refresh =
+            mock_request_input(|request| matches!(request, MPCRequestInput::DWalletKeyRefresh(_)));
+        assert!(!session_timeouts.is_timed_out(&refresh, 10, u64::MAX));
+
+        assert!(!SessionTimeoutsConfig::default().is_timed_out(&presign, 10, u64::MAX));
+    }
+}
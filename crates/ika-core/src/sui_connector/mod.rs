@@ -10,7 +10,7 @@ use anyhow::anyhow;
 use async_trait::async_trait;
 use futures::{StreamExt, future};
 use ika_config::node::{RunWithRange, SuiChainIdentifier, SuiConnectorConfig};
-use ika_sui_client::{SuiClient, SuiClientInner};
+use ika_sui_client::{SuiClient, SuiClientInner, SuiConnectorClient, SuiConnectorClientInner};
 use ika_types::committee::{Committee, EpochId};
 use ika_types::error::IkaResult;
 use ika_types::messages_consensus::MovePackageDigest;
@@ -22,7 +22,6 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use sui_json_rpc_types::{Coin, SuiEvent};
-use sui_sdk::SuiClient as SuiSdkClient;
 use sui_sdk::apis::CoinReadApi;
 use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
 use sui_types::crypto::{Signature, SuiKeyPair};
@@ -44,8 +43,8 @@ pub struct SuiNotifier {
 }
 
 pub struct SuiConnectorService {
-    sui_client: Arc<SuiClient<SuiSdkClient>>,
-    sui_executor: SuiExecutor<SuiSdkClient>,
+    sui_client: Arc<SuiConnectorClient>,
+    sui_executor: SuiExecutor<SuiConnectorClientInner>,
     network_keys_receiver: watch::Receiver<Arc<HashMap<ObjectID, DWalletNetworkEncryptionKeyData>>>,
     // todo(zeev): this needs a refactor.
     #[allow(dead_code)]
@@ -60,7 +59,7 @@ impl SuiConnectorService {
     pub async fn new(
         checkpoint_store: Arc<DWalletCheckpointStore>,
         system_checkpoint_store: Arc<SystemCheckpointStore>,
-        sui_client: Arc<SuiClient<SuiSdkClient>>,
+        sui_client: Arc<SuiConnectorClient>,
         sui_connector_config: SuiConnectorConfig,
         sui_connector_metrics: Arc<SuiConnectorMetrics>,
         is_validator: bool,
@@ -131,7 +130,7 @@ impl SuiConnectorService {
 
     async fn prepare_for_sui(
         sui_connector_config: SuiConnectorConfig,
-        sui_client: Arc<SuiClient<SuiSdkClient>>,
+        sui_client: Arc<SuiConnectorClient>,
         _sui_connector_metrics: Arc<SuiConnectorMetrics>,
     ) -> anyhow::Result<Option<SuiNotifier>> {
         let Some(sui_key_path) = sui_connector_config.notifier_client_key_pair else {
@@ -17,11 +17,12 @@ use ika_types::error::{IkaError, IkaResult};
 use ika_types::messages_dwallet_checkpoint::DWalletCheckpointMessage;
 use ika_types::messages_dwallet_mpc::{
     DKG_FIRST_ROUND_PROTOCOL_FLAG, DKG_SECOND_ROUND_PROTOCOL_FLAG,
-    DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME, DWalletNetworkEncryptionKeyData,
-    FUTURE_SIGN_PROTOCOL_FLAG, IMPORTED_KEY_DWALLET_VERIFICATION_PROTOCOL_FLAG,
+    DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME, DWALLET_KEY_REFRESH_PROTOCOL_FLAG,
+    DWalletNetworkEncryptionKeyData, FUTURE_SIGN_PROTOCOL_FLAG,
+    IMPORTED_KEY_DWALLET_VERIFICATION_PROTOCOL_FLAG,
     MAKE_DWALLET_USER_SECRET_KEY_SHARE_PUBLIC_PROTOCOL_FLAG, PRESIGN_PROTOCOL_FLAG,
     RE_ENCRYPT_USER_SHARE_PROTOCOL_FLAG, SIGN_PROTOCOL_FLAG,
-    SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG,
+    SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG, THRESHOLD_DECRYPTION_PROTOCOL_FLAG,
 };
 use ika_types::messages_system_checkpoints::SystemCheckpointMessage;
 use ika_types::sui::epoch_start_system::EpochStartSystem;
@@ -566,6 +567,12 @@ where
         let sign_with_partial_user_signature_protocol_flag = ptb.input(CallArg::Pure(
             bcs::to_bytes(&SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG)?,
         ))?;
+        let threshold_decryption_protocol_flag = ptb.input(CallArg::Pure(bcs::to_bytes(
+            &THRESHOLD_DECRYPTION_PROTOCOL_FLAG,
+        )?))?;
+        let dwallet_key_refresh_protocol_flag = ptb.input(CallArg::Pure(bcs::to_bytes(
+            &DWALLET_KEY_REFRESH_PROTOCOL_FLAG,
+        )?))?;
         let dwallet_coordinator_ptb_arg = ptb.input(CallArg::Object(dwallet_coordinator_arg))?;
 
         for network_encryption_key_id in network_encryption_key_ids {
@@ -687,6 +694,30 @@ where
                 sign_with_partial_user_signature_protocol_flag,
             ],
         );
+        ptb.programmable_move_call(
+            ika_dwallet_2pc_mpc_package_id,
+            DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME.into(),
+            ident_str!("calculate_pricing_votes").into(),
+            vec![],
+            vec![
+                dwallet_coordinator_ptb_arg,
+                zero,
+                none_option,
+                threshold_decryption_protocol_flag,
+            ],
+        );
+        ptb.programmable_move_call(
+            ika_dwallet_2pc_mpc_package_id,
+            DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME.into(),
+            ident_str!("calculate_pricing_votes").into(),
+            vec![],
+            vec![
+                dwallet_coordinator_ptb_arg,
+                zero,
+                none_option,
+                dwallet_key_refresh_protocol_flag,
+            ],
+        );
         let transaction = super::build_sui_transaction(
             sui_notifier.sui_address,
             ptb.finish(),
@@ -0,0 +1,362 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use crate::committee::{Committee, EpochId};
+use crate::error::{LightClientError, LightClientResult};
+use crate::messages::{
+    CertifiedCheckpoint, CertifiedDWalletCheckpointMessage, CertifiedSystemCheckpointMessage,
+    CheckpointMessage, DWalletCheckpointMessageKind, PresignOutput, SignOutput,
+    SystemCheckpointMessageKind, intent_message_bytes,
+};
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::traits::AggregateAuthenticator;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Verifies that `certificate` carries a valid strong quorum signature of `committee`.
+///
+/// This is the same check `ika_types` performs in `verify_authority_signatures`: the
+/// certificate, its data and the committee must all be of the same epoch, the signers must
+/// hold at least the quorum threshold of voting power, and the aggregate signature must
+/// verify over the intent message of the data.
+pub fn verify_certificate<T: CheckpointMessage>(
+    certificate: &CertifiedCheckpoint<T>,
+    committee: &Committee,
+) -> LightClientResult {
+    let auth_signature = &certificate.auth_signature;
+    if auth_signature.epoch != committee.epoch {
+        return Err(LightClientError::WrongEpoch {
+            expected_epoch: committee.epoch,
+            actual_epoch: auth_signature.epoch,
+        });
+    }
+    if certificate.data.epoch() != auth_signature.epoch {
+        return Err(LightClientError::WrongEpoch {
+            expected_epoch: auth_signature.epoch,
+            actual_epoch: certificate.data.epoch(),
+        });
+    }
+
+    let signers_map = RoaringBitmap::deserialize_from(auth_signature.signers_map.as_slice())
+        .map_err(|e| LightClientError::InvalidSignersMap {
+            error: e.to_string(),
+        })?;
+    // `RoaringBitmap::deserialize_from()` doesn't reject duplicated entries,
+    // so a signer must not be counted twice towards the quorum.
+    let signers: BTreeSet<u32> = signers_map.iter().collect();
+
+    let mut weight = 0;
+    let mut public_keys = Vec::with_capacity(signers.len());
+    for index in signers {
+        let (public_key, stake) =
+            committee
+                .member_by_index(index)
+                .ok_or(LightClientError::UnknownSigner {
+                    index,
+                    epoch: committee.epoch,
+                })?;
+        if *stake == 0 {
+            return Err(LightClientError::UnknownSigner {
+                index,
+                epoch: committee.epoch,
+            });
+        }
+        weight += stake;
+        public_keys.push(public_key.clone());
+    }
+    if weight < committee.quorum_threshold {
+        return Err(LightClientError::CertificateRequiresQuorum {
+            weight,
+            threshold: committee.quorum_threshold,
+        });
+    }
+
+    let message = intent_message_bytes(T::SCOPE, &certificate.data, auth_signature.epoch);
+    auth_signature
+        .signature
+        .verify(&public_keys, &message)
+        .map_err(|e| LightClientError::InvalidSignature {
+            error: e.to_string(),
+        })
+}
+
+/// Tracks the Ika committee across epochs and verifies certified checkpoints against it.
+///
+/// The client starts from a trusted committee and holds no other state than the committees
+/// it has accepted, so it can be persisted with serde and restored later.
+/// Committee transitions follow the epoch boundary of the network: the committee of the
+/// next epoch is only accepted once a certified `EndOfPublish` system checkpoint of the
+/// current epoch has been verified, and only if it is the committee that a certified
+/// `SetNextEpochCommittee` system checkpoint message of the current epoch committed to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightClient {
+    committees: BTreeMap<EpochId, Committee>,
+    current_epoch: EpochId,
+    current_epoch_ended: bool,
+    /// The committee of the next epoch, as certified by the committee of the current epoch.
+    #[serde(default)]
+    certified_next_committee: Option<Committee>,
+}
+
+impl LightClient {
+    pub fn new(trusted_committee: Committee) -> Self {
+        let current_epoch = trusted_committee.epoch;
+        Self {
+            committees: BTreeMap::from([(current_epoch, trusted_committee)]),
+            current_epoch,
+            current_epoch_ended: false,
+            certified_next_committee: None,
+        }
+    }
+
+    pub fn current_epoch(&self) -> EpochId {
+        self.current_epoch
+    }
+
+    /// Whether the end of the current epoch was observed, i.e. the client is ready to
+    /// [`advance_epoch`](Self::advance_epoch).
+    pub fn current_epoch_ended(&self) -> bool {
+        self.current_epoch_ended
+    }
+
+    pub fn committee(&self, epoch: EpochId) -> Option<&Committee> {
+        self.committees.get(&epoch)
+    }
+
+    fn committee_for_epoch(&self, epoch: EpochId) -> LightClientResult<&Committee> {
+        self.committee(epoch)
+            .ok_or(LightClientError::UnknownCommittee { epoch })
+    }
+
+    /// Verifies a certified system checkpoint against the committee of its epoch.
+    ///
+    /// A verified `EndOfPublish` system checkpoint of the current epoch closes it, and a
+    /// verified `SetNextEpochCommittee` message of the current epoch records the committee
+    /// [`advance_epoch`](Self::advance_epoch) accepts.
+    pub fn verify_system_checkpoint(
+        &mut self,
+        certificate: &CertifiedSystemCheckpointMessage,
+    ) -> LightClientResult {
+        let epoch = certificate.data.epoch;
+        verify_certificate(certificate, self.committee_for_epoch(epoch)?)?;
+        if epoch != self.current_epoch {
+            return Ok(());
+        }
+        for message in &certificate.data.messages {
+            if let SystemCheckpointMessageKind::SetNextEpochCommittee {
+                epoch: next_epoch,
+                voting_rights,
+                quorum_threshold,
+                ..
+            } = message
+            {
+                if *next_epoch != epoch + 1 {
+                    return Err(LightClientError::WrongEpoch {
+                        expected_epoch: epoch + 1,
+                        actual_epoch: *next_epoch,
+                    });
+                }
+                self.certified_next_committee = Some(Committee::new_from_public_key_bytes(
+                    *next_epoch,
+                    voting_rights.clone(),
+                    *quorum_threshold,
+                )?);
+            }
+        }
+        if certificate.data.is_end_of_publish() {
+            self.current_epoch_ended = true;
+        }
+        Ok(())
+    }
+
+    /// Installs the committee of the epoch that follows the current, ended, epoch.
+    ///
+    /// `next_committee` must be the committee certified by the current committee in a
+    /// `SetNextEpochCommittee` system checkpoint message, so the system checkpoint carrying
+    /// it must be passed to [`verify_system_checkpoint`](Self::verify_system_checkpoint) first.
+    pub fn advance_epoch(&mut self, next_committee: Committee) -> LightClientResult {
+        if !self.current_epoch_ended {
+            return Err(LightClientError::EpochNotEnded {
+                epoch: self.current_epoch,
+            });
+        }
+        let next_epoch = self.current_epoch + 1;
+        if next_committee.epoch != next_epoch {
+            return Err(LightClientError::WrongEpoch {
+                expected_epoch: next_epoch,
+                actual_epoch: next_committee.epoch,
+            });
+        }
+        match &self.certified_next_committee {
+            Some(certified_next_committee) if *certified_next_committee == next_committee => {}
+            Some(_) => {
+                return Err(LightClientError::NextCommitteeMismatch { epoch: next_epoch });
+            }
+            None => {
+                return Err(LightClientError::NextCommitteeNotCertified { epoch: next_epoch });
+            }
+        }
+        self.committees.insert(next_epoch, next_committee);
+        self.current_epoch = next_epoch;
+        self.current_epoch_ended = false;
+        self.certified_next_committee = None;
+        Ok(())
+    }
+
+    /// Verifies a certified dWallet checkpoint against the committee of its epoch.
+    pub fn verify_dwallet_checkpoint(
+        &self,
+        certificate: &CertifiedDWalletCheckpointMessage,
+    ) -> LightClientResult {
+        verify_certificate(
+            certificate,
+            self.committee_for_epoch(certificate.data.epoch)?,
+        )
+    }
+
+    /// Verifies a certified dWallet checkpoint and returns the output of the sign session
+    /// `sign_id` it includes.
+    ///
+    /// Fails if the checkpoint doesn't respond to `sign_id`, or if the network rejected
+    /// the sign request, so a returned output always carries a network signature.
+    ///
+    /// A sign that was part of a batch sign request is returned as the output of that single sign.
+    pub fn verify_sign_output(
+        &self,
+        certificate: &CertifiedDWalletCheckpointMessage,
+        sign_id: &[u8],
+    ) -> LightClientResult<SignOutput> {
+        self.verify_dwallet_checkpoint(certificate)?;
+        let sign_output = certificate
+            .data
+            .messages
+            .iter()
+            .find_map(|message| match message {
+                DWalletCheckpointMessageKind::RespondDWalletSign(output)
+                    if output.sign_id == sign_id =>
+                {
+                    Some(Ok(output.clone()))
+                }
+                DWalletCheckpointMessageKind::RespondDWalletBatchSign(output) => {
+                    let index = output.sign_ids.iter().position(|id| id == sign_id)?;
+                    let signature = match output.signatures.get(index) {
+                        Some(signature) => signature.clone(),
+                        None if output.rejected => vec![],
+                        None => {
+                            return Some(Err(LightClientError::SignOutputMissingSignature {
+                                sign_id: Hex::encode(sign_id),
+                            }));
+                        }
+                    };
+                    Some(Ok(SignOutput {
+                        dwallet_id: output.dwallet_id.clone(),
+                        sign_id: sign_id.to_vec(),
+                        signature,
+                        is_future_sign: false,
+                        rejected: output.rejected,
+                        session_sequence_number: output.session_sequence_number,
+                    }))
+                }
+                _ => None,
+            })
+            .ok_or_else(|| LightClientError::SignOutputNotIncluded {
+                sign_id: Hex::encode(sign_id),
+                sequence_number: certificate.data.sequence_number,
+            })??;
+        if sign_output.rejected {
+            return Err(LightClientError::SignOutputRejected {
+                sign_id: Hex::encode(sign_id),
+            });
+        }
+        Ok(sign_output)
+    }
+
+    /// Verifies a certified dWallet checkpoint and returns the output of the presign session
+    /// `presign_id` it includes.
+    ///
+    /// Fails if the checkpoint doesn't respond to `presign_id`, or if the network rejected
+    /// the presign request. The caller should check that the returned `dwallet_id` is that of
+    /// the dWallet it signs with, and sign with the returned presign rather than one read
+    /// elsewhere.
+    pub fn verify_presign_output(
+        &self,
+        certificate: &CertifiedDWalletCheckpointMessage,
+        presign_id: &[u8],
+    ) -> LightClientResult<PresignOutput> {
+        self.verify_dwallet_checkpoint(certificate)?;
+        let presign_output = certificate
+            .data
+            .messages
+            .iter()
+            .find_map(|message| match message {
+                DWalletCheckpointMessageKind::RespondDWalletPresign(output)
+                    if output.presign_id == presign_id =>
+                {
+                    Some(output.clone())
+                }
+                _ => None,
+            })
+            .ok_or_else(|| LightClientError::PresignOutputNotIncluded {
+                presign_id: Hex::encode(presign_id),
+                sequence_number: certificate.data.sequence_number,
+            })?;
+        if presign_output.rejected {
+            return Err(LightClientError::PresignOutputRejected {
+                presign_id: Hex::encode(presign_id),
+            });
+        }
+        Ok(presign_output)
+    }
+
+    /// Verifies a certified dWallet checkpoint and returns the DKG output of `dwallet_id`
+    /// it includes: that of its DKG, of the verification of its imported key, or of its
+    /// key refresh, the last accepted one if there are several.
+    ///
+    /// Fails if the checkpoint includes no DKG output of `dwallet_id`, or if the network
+    /// rejected all of them. Note that a later checkpoint may refresh the returned output.
+    pub fn verify_dwallet_dkg_output(
+        &self,
+        certificate: &CertifiedDWalletCheckpointMessage,
+        dwallet_id: &[u8],
+    ) -> LightClientResult<Vec<u8>> {
+        self.verify_dwallet_checkpoint(certificate)?;
+        let dkg_outputs: Vec<(&Vec<u8>, bool)> = certificate
+            .data
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                DWalletCheckpointMessageKind::RespondDWalletDKGSecondRoundOutput(output)
+                    if output.dwallet_id == dwallet_id =>
+                {
+                    Some((&output.output, output.rejected))
+                }
+                DWalletCheckpointMessageKind::RespondDWalletImportedKeyVerificationOutput(
+                    output,
+                ) if output.dwallet_id == dwallet_id => {
+                    Some((&output.public_output, output.rejected))
+                }
+                DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(output)
+                    if output.dwallet_id == dwallet_id =>
+                {
+                    Some((&output.public_output, output.rejected))
+                }
+                _ => None,
+            })
+            .collect();
+        if dkg_outputs.is_empty() {
+            return Err(LightClientError::DWalletDKGOutputNotIncluded {
+                dwallet_id: Hex::encode(dwallet_id),
+                sequence_number: certificate.data.sequence_number,
+            });
+        }
+        dkg_outputs
+            .into_iter()
+            .rev()
+            .find(|(_, rejected)| !rejected)
+            .map(|(output, _)| output.clone())
+            .ok_or_else(|| LightClientError::DWalletDKGOutputRejected {
+                dwallet_id: Hex::encode(dwallet_id),
+            })
+    }
+}
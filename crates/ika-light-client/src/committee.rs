@@ -0,0 +1,86 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use crate::error::{LightClientError, LightClientResult};
+use fastcrypto::bls12381::min_pk::BLS12381PublicKey;
+use fastcrypto::traits::ToFromBytes;
+use serde::{Deserialize, Serialize};
+
+pub type EpochId = u64;
+pub type StakeUnit = u64;
+pub type AuthorityPublicKey = BLS12381PublicKey;
+
+/// The part of an Ika committee needed to verify checkpoint certificates.
+///
+/// Members must be listed in the same order as in the Ika system object on Sui
+/// (and in `ika_types::committee::Committee::voting_rights`), since the signers map of a
+/// certificate refers to members by their position in this list.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Committee {
+    pub epoch: EpochId,
+    pub voting_rights: Vec<(AuthorityPublicKey, StakeUnit)>,
+    pub quorum_threshold: StakeUnit,
+}
+
+impl Committee {
+    pub fn new(
+        epoch: EpochId,
+        voting_rights: Vec<(AuthorityPublicKey, StakeUnit)>,
+        quorum_threshold: StakeUnit,
+    ) -> LightClientResult<Self> {
+        if voting_rights.iter().all(|(_, stake)| *stake == 0) {
+            return Err(LightClientError::InvalidCommittee {
+                error: "the committee has no voting power".to_string(),
+            });
+        }
+        let total_votes: StakeUnit = voting_rights.iter().map(|(_, stake)| *stake).sum();
+        if quorum_threshold == 0 || quorum_threshold > total_votes {
+            return Err(LightClientError::InvalidCommittee {
+                error: format!(
+                    "quorum threshold {quorum_threshold} is out of range for total voting power {total_votes}"
+                ),
+            });
+        }
+        Ok(Self {
+            epoch,
+            voting_rights,
+            quorum_threshold,
+        })
+    }
+
+    /// Builds a committee from raw BLS public key bytes, as read from the validator set
+    /// of the Ika system object on Sui.
+    pub fn new_from_public_key_bytes(
+        epoch: EpochId,
+        voting_rights: Vec<(Vec<u8>, StakeUnit)>,
+        quorum_threshold: StakeUnit,
+    ) -> LightClientResult<Self> {
+        let voting_rights = voting_rights
+            .into_iter()
+            .map(|(public_key, stake)| {
+                AuthorityPublicKey::from_bytes(&public_key)
+                    .map(|public_key| (public_key, stake))
+                    .map_err(|e| LightClientError::InvalidCommittee {
+                        error: format!("invalid authority public key: {e}"),
+                    })
+            })
+            .collect::<LightClientResult<_>>()?;
+        Self::new(epoch, voting_rights, quorum_threshold)
+    }
+
+    pub fn epoch(&self) -> EpochId {
+        self.epoch
+    }
+
+    pub fn num_members(&self) -> usize {
+        self.voting_rights.len()
+    }
+
+    pub fn total_votes(&self) -> StakeUnit {
+        self.voting_rights.iter().map(|(_, stake)| *stake).sum()
+    }
+
+    pub fn member_by_index(&self, index: u32) -> Option<&(AuthorityPublicKey, StakeUnit)> {
+        self.voting_rights.get(index as usize)
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use crate::committee::EpochId;
+use thiserror::Error;
+
+pub type LightClientResult<T = ()> = Result<T, LightClientError>;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LightClientError {
+    #[error("no committee is known for epoch {epoch}")]
+    UnknownCommittee { epoch: EpochId },
+
+    #[error("expected epoch {expected_epoch}, got epoch {actual_epoch}")]
+    WrongEpoch {
+        expected_epoch: EpochId,
+        actual_epoch: EpochId,
+    },
+
+    #[error(
+        "epoch {epoch} has not ended: no certified `EndOfPublish` system checkpoint was verified"
+    )]
+    EpochNotEnded { epoch: EpochId },
+
+    #[error("no committee of epoch {epoch} was certified by the committee of the previous epoch")]
+    NextCommitteeNotCertified { epoch: EpochId },
+
+    #[error(
+        "the committee of epoch {epoch} differs from the one certified by the committee of the previous epoch"
+    )]
+    NextCommitteeMismatch { epoch: EpochId },
+
+    #[error("signer index {index} is not a member of the committee of epoch {epoch}")]
+    UnknownSigner { index: u32, epoch: EpochId },
+
+    #[error("signers hold {weight} voting power, the quorum threshold is {threshold}")]
+    CertificateRequiresQuorum { weight: u64, threshold: u64 },
+
+    #[error("invalid signers bitmap: {error}")]
+    InvalidSignersMap { error: String },
+
+    #[error("invalid aggregate signature: {error}")]
+    InvalidSignature { error: String },
+
+    #[error("invalid committee: {error}")]
+    InvalidCommittee { error: String },
+
+    #[error("failed to deserialize {type_name}: {error}")]
+    Deserialization {
+        type_name: &'static str,
+        error: String,
+    },
+
+    #[error(
+        "sign output for sign session {sign_id} is not included in dWallet checkpoint {sequence_number}"
+    )]
+    SignOutputNotIncluded {
+        sign_id: String,
+        sequence_number: u64,
+    },
+
+    #[error("sign session {sign_id} was rejected by the network")]
+    SignOutputRejected { sign_id: String },
+
+    #[error("the batch sign output of sign session {sign_id} holds no signature for it")]
+    SignOutputMissingSignature { sign_id: String },
+
+    #[error(
+        "presign output for presign session {presign_id} is not included in dWallet checkpoint {sequence_number}"
+    )]
+    PresignOutputNotIncluded {
+        presign_id: String,
+        sequence_number: u64,
+    },
+
+    #[error("presign session {presign_id} was rejected by the network")]
+    PresignOutputRejected { presign_id: String },
+
+    #[error(
+        "no DKG output of dWallet {dwallet_id} is included in dWallet checkpoint {sequence_number}"
+    )]
+    DWalletDKGOutputNotIncluded {
+        dwallet_id: String,
+        sequence_number: u64,
+    },
+
+    #[error("the DKG output of dWallet {dwallet_id} was rejected by the network")]
+    DWalletDKGOutputRejected { dwallet_id: String },
+}
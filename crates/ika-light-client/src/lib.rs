@@ -0,0 +1,33 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Light-client verification of Ika checkpoints.
+//!
+//! dWallet and system checkpoints are certified by a BLS aggregate signature of a quorum of
+//! the epoch committee. This crate verifies such certificates, and the inclusion of
+//! sign outputs in them, without a database or a running node, so that an external chain
+//! relayer or a wallet can trust an Ika signature on its own.
+//!
+//! A wallet can likewise check the presigns and dWallet DKG outputs it reads from an RPC
+//! provider before signing with them, by verifying their inclusion in a certified checkpoint.
+//!
+//! ```ignore
+//! let mut client = LightClient::new(trusted_committee);
+//! client.verify_system_checkpoint(&next_epoch_committee_checkpoint)?;
+//! client.verify_system_checkpoint(&end_of_epoch_checkpoint)?;
+//! client.advance_epoch(next_committee)?;
+//! let certificate = CertifiedDWalletCheckpointMessage::from_bcs_bytes(&bytes)?;
+//! let sign_output = client.verify_sign_output(&certificate, &sign_id)?;
+//! ```
+
+pub mod client;
+pub mod committee;
+pub mod error;
+pub mod messages;
+
+pub use client::{LightClient, verify_certificate};
+pub use committee::Committee;
+pub use error::{LightClientError, LightClientResult};
+pub use messages::{
+    CertifiedDWalletCheckpointMessage, CertifiedSystemCheckpointMessage, PresignOutput, SignOutput,
+};
@@ -0,0 +1,292 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Wire-compatible copies of the checkpoint types defined in `ika-types`.
+//!
+//! `ika-types` pulls in the whole node stack, so the types a light client has to decode are
+//! mirrored here field by field. The BCS encoding of every type in this module must stay
+//! identical to its `ika-types` counterpart: when a field or variant is added there, it has
+//! to be added here as well, in the same position.
+
+use crate::committee::{EpochId, StakeUnit};
+use crate::error::{LightClientError, LightClientResult};
+use fastcrypto::bls12381::min_pk::BLS12381AggregateSignature;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+pub type AggregateAuthoritySignature = BLS12381AggregateSignature;
+pub type DWalletCheckpointSequenceNumber = u64;
+pub type SystemCheckpointSequenceNumber = u64;
+
+/// Mirrors `ika_types::intent::IntentScope`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IntentScope {
+    DWalletCheckpointMessage = 1,
+    SystemCheckpointMessage = 2,
+}
+
+const INTENT_VERSION_V0: u8 = 0;
+const APP_ID_IKA: u8 = 0;
+
+/// The bytes an authority signs for `value`: the intent prefix, the BCS encoding of the
+/// value, and the BCS encoding of the epoch, exactly as in
+/// `ika_types::crypto::VerificationObligation::add_message`.
+pub(crate) fn intent_message_bytes<T: Serialize>(
+    scope: IntentScope,
+    value: &T,
+    epoch: EpochId,
+) -> Vec<u8> {
+    let mut bytes = vec![scope as u8, INTENT_VERSION_V0, APP_ID_IKA];
+    bcs::serialize_into(&mut bytes, value).expect("Message serialization should not fail");
+    bcs::serialize_into(&mut bytes, &epoch).expect("Message serialization should not fail");
+    bytes
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct DKGFirstRoundOutput {
+    pub dwallet_id: Vec<u8>,
+    pub output: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct DKGSecondRoundOutput {
+    pub dwallet_id: Vec<u8>,
+    pub encrypted_secret_share_id: Vec<u8>,
+    pub output: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct PresignOutput {
+    pub dwallet_id: Option<Vec<u8>>,
+    pub presign_id: Vec<u8>,
+    pub presign: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SignOutput {
+    pub dwallet_id: Vec<u8>,
+    pub sign_id: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub is_future_sign: bool,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BatchSignOutput {
+    pub dwallet_id: Vec<u8>,
+    pub batch_sign_id: Vec<u8>,
+    pub sign_ids: Vec<Vec<u8>>,
+    pub signatures: Vec<Vec<u8>>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ThresholdDecryptionOutput {
+    pub decryption_id: Vec<u8>,
+    pub re_encrypted_key: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct DWalletKeyRefreshOutput {
+    pub dwallet_id: Vec<u8>,
+    pub encrypted_user_secret_key_share_id: Vec<u8>,
+    pub public_output: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct EncryptedUserShareOutput {
+    pub dwallet_id: Vec<u8>,
+    pub encrypted_user_secret_key_share_id: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct PartialSignatureVerificationOutput {
+    pub dwallet_id: Vec<u8>,
+    pub partial_centralized_signed_message_id: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct MPCNetworkDKGOutput {
+    pub dwallet_network_encryption_key_id: Vec<u8>,
+    pub public_output: Vec<u8>,
+    pub supported_curves: Vec<u32>,
+    pub is_last: bool,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct MPCNetworkReconfigurationOutput {
+    pub dwallet_network_encryption_key_id: Vec<u8>,
+    pub public_output: Vec<u8>,
+    pub supported_curves: Vec<u32>,
+    pub is_last: bool,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct MakeDWalletUserSecretKeySharesPublicOutput {
+    pub dwallet_id: Vec<u8>,
+    pub public_user_secret_key_shares: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct DWalletImportedKeyVerificationOutput {
+    pub dwallet_id: Vec<u8>,
+    pub public_output: Vec<u8>,
+    pub encrypted_user_secret_key_share_id: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+/// Mirrors `ika_types::message::DWalletCheckpointMessageKind`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum DWalletCheckpointMessageKind {
+    RespondDWalletDKGFirstRoundOutput(DKGFirstRoundOutput),
+    RespondDWalletDKGSecondRoundOutput(DKGSecondRoundOutput),
+    RespondDWalletEncryptedUserShare(EncryptedUserShareOutput),
+    RespondMakeDWalletUserSecretKeySharesPublic(MakeDWalletUserSecretKeySharesPublicOutput),
+    RespondDWalletImportedKeyVerificationOutput(DWalletImportedKeyVerificationOutput),
+    RespondDWalletPresign(PresignOutput),
+    RespondDWalletSign(SignOutput),
+    RespondDWalletPartialSignatureVerificationOutput(PartialSignatureVerificationOutput),
+    RespondDWalletMPCNetworkDKGOutput(MPCNetworkDKGOutput),
+    RespondDWalletMPCNetworkReconfigurationOutput(MPCNetworkReconfigurationOutput),
+    SetMaxActiveSessionsBuffer(u64),
+    SetGasFeeReimbursementSuiSystemCallValue(u64),
+    EndOfPublish,
+    RespondDWalletBatchSign(BatchSignOutput),
+    RespondThresholdDecryption(ThresholdDecryptionOutput),
+    RespondDWalletKeyRefresh(DWalletKeyRefreshOutput),
+    GarbageCollectFutureSigns(u64),
+}
+
+/// Mirrors `ika_types::messages_dwallet_checkpoint::DWalletCheckpointMessage`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct DWalletCheckpointMessage {
+    pub epoch: EpochId,
+    pub sequence_number: DWalletCheckpointSequenceNumber,
+    pub messages: Vec<DWalletCheckpointMessageKind>,
+}
+
+/// Mirrors `ika_types::messages_system_checkpoints::SystemCheckpointMessageKind`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum SystemCheckpointMessageKind {
+    SetNextConfigVersion(u64),
+    SetEpochDurationMs(u64),
+    SetStakeSubsidyStartEpoch(EpochId),
+    SetStakeSubsidyRate(u16),
+    SetStakeSubsidyPeriodLength(u64),
+    SetMinValidatorCount(u64),
+    SetMaxValidatorCount(u64),
+    SetMinValidatorJoiningStake(u64),
+    SetMaxValidatorChangeCount(u64),
+    SetRewardSlashingRate(u64),
+    EndOfPublish,
+    SetApprovedUpgrade {
+        package_id: Vec<u8>,
+        digest: Option<Vec<u8>>,
+    },
+    SetOrRemoveWitnessApprovingAdvanceEpochMessageType {
+        witness_type: String,
+        remove: bool,
+    },
+    /// The authority names are the raw bytes of the members' BLS public keys.
+    SetNextEpochCommittee {
+        epoch: EpochId,
+        voting_rights: Vec<(Vec<u8>, StakeUnit)>,
+        quorum_threshold: StakeUnit,
+        validity_threshold: StakeUnit,
+    },
+}
+
+/// Mirrors `ika_types::messages_system_checkpoints::SystemCheckpointMessage`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct SystemCheckpointMessage {
+    pub epoch: EpochId,
+    pub sequence_number: SystemCheckpointSequenceNumber,
+    pub messages: Vec<SystemCheckpointMessageKind>,
+}
+
+impl SystemCheckpointMessage {
+    /// Whether this is the final system checkpoint of its epoch.
+    pub fn is_end_of_publish(&self) -> bool {
+        self.messages
+            .iter()
+            .any(|message| matches!(message, SystemCheckpointMessageKind::EndOfPublish))
+    }
+}
+
+/// A checkpoint message that is certified by a committee.
+pub trait CheckpointMessage: Serialize {
+    const SCOPE: IntentScope;
+
+    fn epoch(&self) -> EpochId;
+}
+
+impl CheckpointMessage for DWalletCheckpointMessage {
+    const SCOPE: IntentScope = IntentScope::DWalletCheckpointMessage;
+
+    fn epoch(&self) -> EpochId {
+        self.epoch
+    }
+}
+
+impl CheckpointMessage for SystemCheckpointMessage {
+    const SCOPE: IntentScope = IntentScope::SystemCheckpointMessage;
+
+    fn epoch(&self) -> EpochId {
+        self.epoch
+    }
+}
+
+/// Mirrors `ika_types::crypto::AuthorityStrongQuorumSignInfo`.
+///
+/// `signers_map` holds the serialized `RoaringBitmap` of the signers' committee indices.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuthorityStrongQuorumSignInfo {
+    pub epoch: EpochId,
+    pub signature: AggregateAuthoritySignature,
+    pub signers_map: Vec<u8>,
+}
+
+/// Mirrors `ika_types::message_envelope::Envelope<T, AuthorityStrongQuorumSignInfo>`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CertifiedCheckpoint<T> {
+    pub data: T,
+    pub auth_signature: AuthorityStrongQuorumSignInfo,
+}
+
+pub type CertifiedDWalletCheckpointMessage = CertifiedCheckpoint<DWalletCheckpointMessage>;
+pub type CertifiedSystemCheckpointMessage = CertifiedCheckpoint<SystemCheckpointMessage>;
+
+impl<T: DeserializeOwned> CertifiedCheckpoint<T> {
+    /// Decodes a certificate from the BCS bytes of the matching `ika-types` certificate,
+    /// as served by a node or stored in an archive.
+    pub fn from_bcs_bytes(bytes: &[u8]) -> LightClientResult<Self> {
+        bcs::from_bytes(bytes).map_err(|e| LightClientError::Deserialization {
+            type_name: std::any::type_name::<Self>(),
+            error: e.to_string(),
+        })
+    }
+}
@@ -0,0 +1,484 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use ika_light_client::messages::{
+    CertifiedDWalletCheckpointMessage, CertifiedSystemCheckpointMessage,
+};
+use ika_light_client::{Committee, LightClient, LightClientError};
+use ika_types::committee::Committee as IkaCommittee;
+use ika_types::crypto::AuthorityKeyPair;
+use ika_types::message::{
+    BatchSignOutput, DKGSecondRoundOutput, DWalletCheckpointMessageKind, DWalletKeyRefreshOutput,
+    PresignOutput, SignOutput, ThresholdDecryptionOutput,
+};
+use ika_types::messages_dwallet_checkpoint::{
+    CertifiedDWalletCheckpointMessage as IkaCertifiedDWalletCheckpointMessage,
+    DWalletCheckpointMessage,
+};
+use ika_types::messages_system_checkpoints::{
+    CertifiedSystemCheckpointMessage as IkaCertifiedSystemCheckpointMessage,
+    SystemCheckpointMessage, SystemCheckpointMessageKind,
+};
+use std::collections::HashMap;
+
+fn light_client_committee(committee: &IkaCommittee) -> Committee {
+    Committee::new(
+        committee.epoch,
+        committee
+            .voting_rights
+            .iter()
+            .map(|(name, stake)| (committee.public_key(name).unwrap().clone(), *stake))
+            .collect(),
+        committee.quorum_threshold,
+    )
+    .unwrap()
+}
+
+fn committee_of_epoch(epoch: u64) -> (IkaCommittee, Vec<AuthorityKeyPair>) {
+    let (committee, key_pairs) = IkaCommittee::new_simple_test_committee();
+    let committee = IkaCommittee::new(
+        epoch,
+        committee.voting_rights.clone(),
+        HashMap::new(),
+        committee.quorum_threshold,
+        committee.validity_threshold,
+    );
+    (committee, key_pairs)
+}
+
+fn sign_output(sign_id: &[u8], rejected: bool) -> DWalletCheckpointMessageKind {
+    DWalletCheckpointMessageKind::RespondDWalletSign(SignOutput {
+        dwallet_id: vec![1; 32],
+        sign_id: sign_id.to_vec(),
+        signature: vec![7; 64],
+        is_future_sign: false,
+        rejected,
+        session_sequence_number: 3,
+    })
+}
+
+fn certified_dwallet_checkpoint(
+    committee: &IkaCommittee,
+    key_pairs: &[AuthorityKeyPair],
+    messages: Vec<DWalletCheckpointMessageKind>,
+) -> CertifiedDWalletCheckpointMessage {
+    let certificate = IkaCertifiedDWalletCheckpointMessage::new_from_keypairs_for_testing(
+        DWalletCheckpointMessage::new(committee.epoch, 5, messages),
+        key_pairs,
+        committee,
+    );
+    CertifiedDWalletCheckpointMessage::from_bcs_bytes(&bcs::to_bytes(&certificate).unwrap())
+        .unwrap()
+}
+
+fn certified_system_checkpoint(
+    committee: &IkaCommittee,
+    key_pairs: &[AuthorityKeyPair],
+    messages: Vec<SystemCheckpointMessageKind>,
+) -> CertifiedSystemCheckpointMessage {
+    let certificate = IkaCertifiedSystemCheckpointMessage::new_from_keypairs_for_testing(
+        SystemCheckpointMessage::new(committee.epoch, 2, messages),
+        key_pairs,
+        committee,
+    );
+    CertifiedSystemCheckpointMessage::from_bcs_bytes(&bcs::to_bytes(&certificate).unwrap()).unwrap()
+}
+
+#[test]
+fn test_verify_sign_output() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![
+            sign_output(b"accepted", false),
+            sign_output(b"rejected", true),
+        ],
+    );
+
+    let output = client
+        .verify_sign_output(&certificate, b"accepted")
+        .unwrap();
+    assert_eq!(output.signature, vec![7; 64]);
+    assert_eq!(
+        client.verify_sign_output(&certificate, b"rejected"),
+        Err(LightClientError::SignOutputRejected {
+            sign_id: "72656a6563746564".to_string()
+        })
+    );
+    assert!(matches!(
+        client.verify_sign_output(&certificate, b"missing"),
+        Err(LightClientError::SignOutputNotIncluded { .. })
+    ));
+}
+
+#[test]
+fn test_verify_batch_sign_output() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![DWalletCheckpointMessageKind::RespondDWalletBatchSign(
+            BatchSignOutput {
+                dwallet_id: vec![1; 32],
+                batch_sign_id: vec![2; 32],
+                sign_ids: vec![b"first".to_vec(), b"second".to_vec()],
+                signatures: vec![vec![7; 64], vec![8; 64]],
+                rejected: false,
+                session_sequence_number: 3,
+            },
+        )],
+    );
+
+    let output = client.verify_sign_output(&certificate, b"second").unwrap();
+    assert_eq!(output.signature, vec![8; 64]);
+    assert_eq!(output.dwallet_id, vec![1; 32]);
+    assert!(matches!(
+        client.verify_sign_output(&certificate, b"missing"),
+        Err(LightClientError::SignOutputNotIncluded { .. })
+    ));
+}
+
+#[test]
+fn test_verify_presign_output() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let presign_output = |presign_id: &[u8], rejected| {
+        DWalletCheckpointMessageKind::RespondDWalletPresign(PresignOutput {
+            dwallet_id: Some(vec![1; 32]),
+            presign_id: presign_id.to_vec(),
+            presign: vec![6; 96],
+            rejected,
+            session_sequence_number: 3,
+        })
+    };
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![
+            presign_output(b"accepted", false),
+            presign_output(b"rejected", true),
+        ],
+    );
+
+    let output = client
+        .verify_presign_output(&certificate, b"accepted")
+        .unwrap();
+    assert_eq!(output.presign, vec![6; 96]);
+    assert_eq!(output.dwallet_id, Some(vec![1; 32]));
+    assert_eq!(
+        client.verify_presign_output(&certificate, b"rejected"),
+        Err(LightClientError::PresignOutputRejected {
+            presign_id: "72656a6563746564".to_string()
+        })
+    );
+    assert!(matches!(
+        client.verify_presign_output(&certificate, b"missing"),
+        Err(LightClientError::PresignOutputNotIncluded { .. })
+    ));
+}
+
+#[test]
+fn test_verify_dwallet_dkg_output() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let key_refresh_output = |public_output: Vec<u8>, rejected| {
+        DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(DWalletKeyRefreshOutput {
+            dwallet_id: vec![1; 32],
+            encrypted_user_secret_key_share_id: vec![2; 32],
+            public_output,
+            rejected,
+            session_sequence_number: 4,
+        })
+    };
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![
+            DWalletCheckpointMessageKind::RespondDWalletDKGSecondRoundOutput(
+                DKGSecondRoundOutput {
+                    dwallet_id: vec![1; 32],
+                    encrypted_secret_share_id: vec![2; 32],
+                    output: vec![5; 64],
+                    rejected: false,
+                    session_sequence_number: 3,
+                },
+            ),
+            key_refresh_output(vec![6; 64], false),
+            key_refresh_output(vec![7; 64], true),
+            sign_output(b"sign", false),
+        ],
+    );
+
+    // The last accepted output is that of the key refresh.
+    assert_eq!(
+        client.verify_dwallet_dkg_output(&certificate, &[1; 32]),
+        Ok(vec![6; 64])
+    );
+    assert!(matches!(
+        client.verify_dwallet_dkg_output(&certificate, &[3; 32]),
+        Err(LightClientError::DWalletDKGOutputNotIncluded { .. })
+    ));
+
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![key_refresh_output(vec![7; 64], true)],
+    );
+    assert!(matches!(
+        client.verify_dwallet_dkg_output(&certificate, &[1; 32]),
+        Err(LightClientError::DWalletDKGOutputRejected { .. })
+    ));
+}
+
+#[test]
+fn test_verify_threshold_decryption_output() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![DWalletCheckpointMessageKind::RespondThresholdDecryption(
+            ThresholdDecryptionOutput {
+                decryption_id: vec![4; 32],
+                re_encrypted_key: vec![9; 40],
+                rejected: false,
+                session_sequence_number: 3,
+            },
+        )],
+    );
+
+    assert!(client.verify_dwallet_checkpoint(&certificate).is_ok());
+    assert_eq!(
+        certificate.data.messages,
+        vec![
+            ika_light_client::messages::DWalletCheckpointMessageKind::RespondThresholdDecryption(
+                ika_light_client::messages::ThresholdDecryptionOutput {
+                    decryption_id: vec![4; 32],
+                    re_encrypted_key: vec![9; 40],
+                    rejected: false,
+                    session_sequence_number: 3,
+                }
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_verify_dwallet_key_refresh_output() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(
+            DWalletKeyRefreshOutput {
+                dwallet_id: vec![1; 32],
+                encrypted_user_secret_key_share_id: vec![2; 32],
+                public_output: vec![5; 64],
+                rejected: false,
+                session_sequence_number: 4,
+            },
+        )],
+    );
+
+    assert!(client.verify_dwallet_checkpoint(&certificate).is_ok());
+    assert_eq!(
+        certificate.data.messages,
+        vec![
+            ika_light_client::messages::DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(
+                ika_light_client::messages::DWalletKeyRefreshOutput {
+                    dwallet_id: vec![1; 32],
+                    encrypted_user_secret_key_share_id: vec![2; 32],
+                    public_output: vec![5; 64],
+                    rejected: false,
+                    session_sequence_number: 4,
+                }
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_verify_end_of_publish_dwallet_checkpoint() {
+    let (committee, key_pairs) = committee_of_epoch(3);
+    let client = LightClient::new(light_client_committee(&committee));
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![
+            sign_output(b"accepted", false),
+            DWalletCheckpointMessageKind::GarbageCollectFutureSigns(3),
+            DWalletCheckpointMessageKind::EndOfPublish,
+        ],
+    );
+
+    assert!(client.verify_sign_output(&certificate, b"accepted").is_ok());
+    assert_eq!(
+        certificate.data.messages[1..],
+        [
+            ika_light_client::messages::DWalletCheckpointMessageKind::GarbageCollectFutureSigns(3),
+            ika_light_client::messages::DWalletCheckpointMessageKind::EndOfPublish,
+        ]
+    );
+}
+
+#[test]
+fn test_reject_tampered_checkpoint() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let mut certificate =
+        certified_dwallet_checkpoint(&committee, &key_pairs, vec![sign_output(b"id", false)]);
+    certificate.data.sequence_number += 1;
+
+    assert!(matches!(
+        client.verify_dwallet_checkpoint(&certificate),
+        Err(LightClientError::InvalidSignature { .. })
+    ));
+}
+
+#[test]
+fn test_reject_checkpoint_without_quorum() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let certificate =
+        certified_dwallet_checkpoint(&committee, &key_pairs, vec![sign_output(b"id", false)]);
+    let mut light_committee = light_client_committee(&committee);
+    light_committee.quorum_threshold = light_committee.total_votes() + 1;
+
+    assert!(client.verify_dwallet_checkpoint(&certificate).is_ok());
+    assert!(matches!(
+        ika_light_client::verify_certificate(&certificate, &light_committee),
+        Err(LightClientError::CertificateRequiresQuorum { .. })
+    ));
+}
+
+#[test]
+fn test_committee_transition() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let (next_committee, next_key_pairs) = committee_of_epoch(1);
+    let mut client = LightClient::new(light_client_committee(&committee));
+
+    assert_eq!(
+        client.advance_epoch(light_client_committee(&next_committee)),
+        Err(LightClientError::EpochNotEnded { epoch: 0 })
+    );
+
+    client
+        .verify_system_checkpoint(&certified_system_checkpoint(
+            &committee,
+            &key_pairs,
+            vec![SystemCheckpointMessageKind::SetEpochDurationMs(1000)],
+        ))
+        .unwrap();
+    assert!(!client.current_epoch_ended());
+    client
+        .verify_system_checkpoint(&certified_system_checkpoint(
+            &committee,
+            &key_pairs,
+            vec![
+                SystemCheckpointMessageKind::new_set_next_epoch_committee(&next_committee),
+                SystemCheckpointMessageKind::EndOfPublish,
+            ],
+        ))
+        .unwrap();
+    assert!(client.current_epoch_ended());
+
+    client
+        .advance_epoch(light_client_committee(&next_committee))
+        .unwrap();
+    assert_eq!(client.current_epoch(), 1);
+
+    // Checkpoints of the new epoch must be signed by the new committee,
+    // while those of the previous epoch remain verifiable.
+    let next_certificate = certified_dwallet_checkpoint(
+        &next_committee,
+        &next_key_pairs,
+        vec![sign_output(b"id", false)],
+    );
+    assert!(client.verify_sign_output(&next_certificate, b"id").is_ok());
+    let previous_certificate =
+        certified_dwallet_checkpoint(&committee, &key_pairs, vec![sign_output(b"id", false)]);
+    assert!(
+        client
+            .verify_dwallet_checkpoint(&previous_certificate)
+            .is_ok()
+    );
+
+    let mut forged_certificate = previous_certificate;
+    forged_certificate.data.epoch = 1;
+    forged_certificate.auth_signature.epoch = 1;
+    assert!(matches!(
+        client.verify_dwallet_checkpoint(&forged_certificate),
+        Err(LightClientError::InvalidSignature { .. })
+    ));
+}
+
+#[test]
+fn test_reject_uncertified_next_committee() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let (next_committee, _) = committee_of_epoch(1);
+    let mut client = LightClient::new(light_client_committee(&committee));
+
+    client
+        .verify_system_checkpoint(&certified_system_checkpoint(
+            &committee,
+            &key_pairs,
+            vec![SystemCheckpointMessageKind::EndOfPublish],
+        ))
+        .unwrap();
+    assert_eq!(
+        client.advance_epoch(light_client_committee(&next_committee)),
+        Err(LightClientError::NextCommitteeNotCertified { epoch: 1 })
+    );
+
+    client
+        .verify_system_checkpoint(&certified_system_checkpoint(
+            &committee,
+            &key_pairs,
+            vec![SystemCheckpointMessageKind::new_set_next_epoch_committee(
+                &next_committee,
+            )],
+        ))
+        .unwrap();
+    let mut other_committee = light_client_committee(&next_committee);
+    other_committee.quorum_threshold = other_committee.total_votes();
+    assert_eq!(
+        client.advance_epoch(other_committee),
+        Err(LightClientError::NextCommitteeMismatch { epoch: 1 })
+    );
+    assert_eq!(client.current_epoch(), 0);
+
+    client
+        .advance_epoch(light_client_committee(&next_committee))
+        .unwrap();
+    assert_eq!(client.current_epoch(), 1);
+}
+
+#[test]
+fn test_reject_batch_sign_output_without_signature() {
+    let (committee, key_pairs) = committee_of_epoch(0);
+    let client = LightClient::new(light_client_committee(&committee));
+    let certificate = certified_dwallet_checkpoint(
+        &committee,
+        &key_pairs,
+        vec![DWalletCheckpointMessageKind::RespondDWalletBatchSign(
+            BatchSignOutput {
+                dwallet_id: vec![1; 32],
+                batch_sign_id: vec![2; 32],
+                sign_ids: vec![b"first".to_vec(), b"second".to_vec()],
+                signatures: vec![vec![7; 64]],
+                rejected: false,
+                session_sequence_number: 3,
+            },
+        )],
+    );
+
+    assert!(client.verify_sign_output(&certificate, b"first").is_ok());
+    assert!(matches!(
+        client.verify_sign_output(&certificate, b"second"),
+        Err(LightClientError::SignOutputMissingSignature { .. })
+    ));
+}
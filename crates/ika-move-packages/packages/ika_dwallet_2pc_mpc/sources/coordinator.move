@@ -12,9 +12,11 @@ use ika_dwallet_2pc_mpc::coordinator_inner::{
     Self,
     DWalletCap,
     DWalletCoordinatorInner,
+    DerivedKeyMessageApproval,
     ImportedKeyDWalletCap,
     ImportedKeyMessageApproval,
     MessageApproval,
+    NetworkEncryptedKey,
     UnverifiedPartialUserSignatureCap,
     UnverifiedPresignCap,
     VerifiedPartialUserSignatureCap,
@@ -241,6 +243,25 @@ public fun approve_message(
         )
 }
 
+public fun approve_derived_key_message(
+    self: &mut DWalletCoordinator,
+    dwallet_cap: &DWalletCap,
+    signature_algorithm: u32,
+    hash_scheme: u32,
+    message: vector<u8>,
+    derivation_path: vector<u32>,
+): DerivedKeyMessageApproval {
+    self
+        .inner()
+        .approve_derived_key_message(
+            dwallet_cap,
+            signature_algorithm,
+            hash_scheme,
+            message,
+            derivation_path,
+        )
+}
+
 public fun approve_imported_key_message(
     self: &mut DWalletCoordinator,
     imported_key_dwallet_cap: &ImportedKeyDWalletCap,
@@ -369,6 +390,84 @@ public fun request_make_dwallet_user_secret_key_shares_public(
         )
 }
 
+public fun register_network_encrypted_key(
+    self: &mut DWalletCoordinator,
+    dwallet_network_encryption_key_id: ID,
+    curve: u32,
+    encrypted_key: vector<u8>,
+    ctx: &mut TxContext,
+): NetworkEncryptedKey {
+    self
+        .inner_mut()
+        .register_network_encrypted_key(
+            dwallet_network_encryption_key_id,
+            curve,
+            encrypted_key,
+            ctx,
+        )
+}
+
+public fun request_threshold_decryption(
+    self: &mut DWalletCoordinator,
+    network_encrypted_key: &NetworkEncryptedKey,
+    encryption_key_address: address,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    self
+        .inner_mut()
+        .request_threshold_decryption(
+            network_encrypted_key,
+            encryption_key_address,
+            session_identifier,
+            payment_ika,
+            payment_sui,
+            ctx,
+        )
+}
+
+public fun request_dwallet_key_refresh(
+    self: &mut DWalletCoordinator,
+    dwallet_cap: &DWalletCap,
+    encrypted_refresh_share: vector<u8>,
+    encrypted_centralized_secret_share_and_proof: vector<u8>,
+    encryption_key_address: address,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    self
+        .inner_mut()
+        .request_dwallet_key_refresh(
+            dwallet_cap,
+            encrypted_refresh_share,
+            encrypted_centralized_secret_share_and_proof,
+            encryption_key_address,
+            session_identifier,
+            payment_ika,
+            payment_sui,
+            ctx,
+        )
+}
+
+public fun register_encrypted_user_secret_key_shares(
+    self: &mut DWalletCoordinator,
+    dwallet_cap: &DWalletCap,
+    encrypted_user_secret_key_share_ids: vector<ID>,
+    ctx: &mut TxContext,
+) {
+    self
+        .inner_mut()
+        .register_encrypted_user_secret_key_shares(
+            dwallet_cap,
+            encrypted_user_secret_key_share_ids,
+            ctx,
+        )
+}
+
 public fun request_re_encrypt_user_share_for(
     self: &mut DWalletCoordinator,
     dwallet_id: ID,
@@ -492,6 +591,52 @@ public fun request_sign(
         )
 }
 
+public fun request_derived_key_sign(
+    self: &mut DWalletCoordinator,
+    presign_cap: VerifiedPresignCap,
+    message_approval: DerivedKeyMessageApproval,
+    message_centralized_signature: vector<u8>,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    self
+        .inner_mut()
+        .request_derived_key_sign(
+            message_approval,
+            presign_cap,
+            message_centralized_signature,
+            session_identifier,
+            payment_ika,
+            payment_sui,
+            ctx,
+        )
+}
+
+public fun request_batch_sign(
+    self: &mut DWalletCoordinator,
+    presign_caps: vector<VerifiedPresignCap>,
+    message_approvals: vector<MessageApproval>,
+    message_centralized_signatures: vector<vector<u8>>,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    self
+        .inner_mut()
+        .request_batch_sign(
+            message_approvals,
+            presign_caps,
+            message_centralized_signatures,
+            session_identifier,
+            payment_ika,
+            payment_sui,
+            ctx,
+        )
+}
+
 public fun request_imported_key_sign(
     self: &mut DWalletCoordinator,
     presign_cap: VerifiedPresignCap,
@@ -542,6 +687,55 @@ public fun request_future_sign(
         )
 }
 
+public fun request_future_sign_with_conditions(
+    self: &mut DWalletCoordinator,
+    dwallet_id: ID,
+    presign_cap: VerifiedPresignCap,
+    message: vector<u8>,
+    hash_scheme: u32,
+    message_centralized_signature: vector<u8>,
+    expires_at_epoch: Option<u64>,
+    not_before_epoch: Option<u64>,
+    completer: Option<address>,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+): UnverifiedPartialUserSignatureCap {
+    self
+        .inner_mut()
+        .request_future_sign_with_conditions(
+            dwallet_id,
+            presign_cap,
+            message,
+            hash_scheme,
+            message_centralized_signature,
+            expires_at_epoch,
+            not_before_epoch,
+            completer,
+            session_identifier,
+            payment_ika,
+            payment_sui,
+            ctx,
+        )
+}
+
+public fun cancel_unverified_future_sign(
+    self: &mut DWalletCoordinator,
+    cap: UnverifiedPartialUserSignatureCap,
+    ctx: &mut TxContext,
+) {
+    self.inner_mut().cancel_unverified_future_sign(cap, ctx)
+}
+
+public fun cancel_future_sign(
+    self: &mut DWalletCoordinator,
+    cap: VerifiedPartialUserSignatureCap,
+    ctx: &mut TxContext,
+) {
+    self.inner_mut().cancel_future_sign(cap, ctx)
+}
+
 public fun is_partial_user_signature_valid(
     self: &DWalletCoordinator,
     cap: &UnverifiedPartialUserSignatureCap,
@@ -45,9 +45,10 @@ use sui::bcs;
 use sui::coin::Coin;
 use sui::ed25519::ed25519_verify;
 use sui::event;
+use sui::hash;
 use sui::object_table::{Self, ObjectTable};
 use sui::sui::SUI;
-use sui::table::Table;
+use sui::table::{Self, Table};
 use sui::table_vec::{Self, TableVec};
 use sui::vec_map::VecMap;
 
@@ -77,6 +78,22 @@ const SIGN_PROTOCOL_FLAG: u32 = 6;
 const FUTURE_SIGN_PROTOCOL_FLAG: u32 = 7;
 /// Signing with partial user signature protocol identifier
 const SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG: u32 = 8;
+/// Threshold decryption of a key encrypted to the network protocol identifier
+const THRESHOLD_DECRYPTION_PROTOCOL_FLAG: u32 = 9;
+/// dWallet key refresh protocol identifier
+const DWALLET_KEY_REFRESH_PROTOCOL_FLAG: u32 = 10;
+
+/// The maximal number of messages signed in a single batch sign session
+const MAX_SIGN_BATCH_SIZE: u64 = 64;
+
+/// The maximal length of a derivation path of a dWallet child key, as in BIP-32
+const MAX_DERIVATION_PATH_LENGTH: u64 = 255;
+/// Child indices from this one onward are hardened, and can't be derived without the full secret key
+const HARDENED_CHILD_INDEX_OFFSET: u32 = 0x80000000;
+
+/// The maximal number of partial user signatures a single garbage collection message goes over,
+/// so it fits in a checkpoint transaction
+const MAX_FUTURE_SIGNS_GARBAGE_COLLECTED_PER_MESSAGE: u64 = 500;
 
 // Message data type constants corresponding to MessageKind enum variants (in ika-types/src/message.rs)
 const RESPOND_DWALLET_DKG_FIRST_ROUND_OUTPUT_MESSAGE_TYPE: u32 = 0;
@@ -92,6 +109,10 @@ const RESPOND_DWALLET_MPC_NETWORK_RECONFIGURATION_OUTPUT_MESSAGE_TYPE: u32 = 9;
 const SET_MAX_ACTIVE_SESSIONS_BUFFER_MESSAGE_TYPE: u32 = 10;
 const SET_GAS_FEE_REIMBURSEMENT_SUI_SYSTEM_CALL_VALUE_MESSAGE_TYPE: u32 = 11;
 const END_OF_EPOCH_MESSAGE_TYPE: u32 = 12;
+const RESPOND_DWALLET_BATCH_SIGN_MESSAGE_TYPE: u32 = 13;
+const RESPOND_THRESHOLD_DECRYPTION_MESSAGE_TYPE: u32 = 14;
+const RESPOND_DWALLET_KEY_REFRESH_MESSAGE_TYPE: u32 = 15;
+const GARBAGE_COLLECT_FUTURE_SIGNS_MESSAGE_TYPE: u32 = 16;
 
 // === Errors ===
 
@@ -155,6 +176,30 @@ const EAlreadyInitiatedMidEpochReconfiguration: u64 = 28;
 const EHaveNotInitiatedMidEpochReconfiguration: u64 = 29;
 /// Not all network encryption keys reconfiguration have been completed
 const ENotAllNetworkEncryptionKeysReconfigurationCompleted: u64 = 30;
+/// Batch sign request has no messages, or more than `MAX_SIGN_BATCH_SIZE` messages
+const EInvalidSignBatchSize: u64 = 31;
+/// The number of message approvals, presign caps and centralized signatures in a batch differ
+const ESignBatchLengthMismatch: u64 = 32;
+/// The encrypted key was already registered for threshold decryption
+const ENetworkEncryptedKeyAlreadyRegistered: u64 = 33;
+/// The derivation path is too long, or has a hardened child index
+const EInvalidDerivationPath: u64 = 34;
+/// The future sign expiry epoch is not after the current epoch
+const EInvalidFutureSignExpiry: u64 = 35;
+/// The future sign approval expired
+const EFutureSignExpired: u64 = 36;
+/// The future sign approval was cancelled
+const EFutureSignCancelled: u64 = 37;
+/// The conditions of the future sign approval do not allow completing it
+const EFutureSignConditionsNotMet: u64 = 38;
+/// A key refresh of the dWallet is already in progress
+const EDWalletKeyRefreshInProgress: u64 = 39;
+/// Not every encrypted user secret key share of the dWallet is in the registry of its shares
+const EEncryptedUserSecretKeySharesNotRegistered: u64 = 40;
+/// Referenced encrypted user secret key share does not exist
+const EEncryptedUserSecretKeyShareNotExist: u64 = 41;
+/// The encrypted user secret key share is already in the registry of the shares of its dWallet
+const EEncryptedUserSecretKeyShareAlreadyRegistered: u64 = 42;
 
 // === Structs ===
 
@@ -402,6 +447,21 @@ public enum EncryptedUserSecretKeyShareState has copy, drop, store {
     },
 }
 
+/// The key of the registry of the encrypted user secret key shares of every dWallet
+/// (dWallet ID -> encrypted user secret key share IDs) in the `extra_fields` of the coordinator.
+///
+/// The shares of a dWallet are kept in an `ObjectTable`, which can't be iterated,
+/// so a key refresh uses this registry to invalidate the shares it replaces.
+/// Shares created before the registry existed are registered with `register_encrypted_user_secret_key_shares`,
+/// and a dWallet can only be refreshed once all its shares are registered.
+public struct EncryptedUserSecretKeySharesRegistryKey has copy, drop, store {}
+
+/// The key of the key refreshes in progress (dWallet ID -> refreshed encrypted user secret key share ID)
+/// in the `extra_fields` of the coordinator.
+///
+/// The dWallet stays `Active` during a key refresh, so sessions requested before it can still complete.
+public struct DWalletKeyRefreshesInProgressKey has copy, drop, store {}
+
 /// Unverified capability for a partial user signature requiring network validation.
 ///
 /// This capability is issued when a user creates a partial signature but must be
@@ -503,6 +563,43 @@ public enum PartialUserSignatureState has copy, drop, store {
     NetworkVerificationRejected,
 }
 
+/// Conditions on completing a partial user signature, set by the user when requesting it.
+///
+/// Kept in the `extra_fields` of the coordinator under `FutureSignConditionsKey`,
+/// by the ID of the partial user signature, for the partial user signatures that have any.
+public struct FutureSignConditions has copy, drop, store {
+    /// Epoch from which the signature can no longer be completed, or `None` if it never expires
+    expires_at_epoch: Option<u64>,
+    /// Epoch from which the signature can be completed, or `None` to allow it immediately
+    not_before_epoch: Option<u64>,
+    /// The only address that may complete the signature, or `None` to allow anyone holding the capability
+    completer: Option<address>,
+}
+
+/// The key of the `Table<ID, FutureSignConditions>` of partial user signature conditions
+/// in the `extra_fields` of the coordinator.
+public struct FutureSignConditionsKey has copy, drop, store {}
+
+/// The key of the `Table<ID, u64>` of cancelled partial user signatures, by the epoch they were cancelled at,
+/// in the `extra_fields` of the coordinator.
+public struct CancelledFutureSignsKey has copy, drop, store {}
+
+/// The key of the garbage collection index of partial user signatures in the `extra_fields` of the coordinator.
+public struct FutureSignGarbageKey has copy, drop, store {}
+
+/// Partial user signatures that can no longer be completed, by the epoch from which they can be removed.
+///
+/// Expired partial user signatures are indexed by their expiry epoch when requested,
+/// and cancelled ones by the epoch they were cancelled at.
+/// The Ika network collects them at the end of each epoch,
+/// see `GARBAGE_COLLECT_FUTURE_SIGNS_MESSAGE_TYPE`.
+public struct FutureSignGarbage has store {
+    /// The first epoch whose entries were not collected yet
+    next_epoch_to_collect: u64,
+    /// Epoch -> IDs of the partial user signatures that can be removed from it
+    by_epoch: Table<u64, vector<ID>>,
+}
+
 /// Represents a decentralized wallet (dWallet) created through DKG or key import.
 ///
 /// A dWallet encapsulates cryptographic key material and provides secure signing
@@ -745,6 +842,39 @@ public enum SignState has copy, drop, store {
     },
 }
 
+// === Network Encrypted Keys ===
+
+/// The key of the registry of network encrypted keys in the `extra_fields` of the coordinator
+/// (encrypted key digest -> `NetworkEncryptedKey` ID).
+///
+/// It also holds the digests of the encrypted refresh shares of dWallet key refreshes (mapped to the dWallet ID),
+/// which are encrypted to the network encryption key with the same proof, so they can never be registered for decryption.
+public struct NetworkEncryptedKeysRegistryKey has copy, drop, store {}
+
+/// A key encrypted by a user to a network encryption key, which the network can threshold decrypt.
+///
+/// The encrypted key comes with a proof that whoever encrypted it knows the key,
+/// so only keys encrypted by users (and never the network's own ciphertexts) can be decrypted.
+///
+/// ## Access Control
+/// Decryption is requested with a reference to this object, so its owner (or the object
+/// wrapping it, e.g. a time-lock or a governance contract) decides when it is decrypted.
+///
+/// ## Decryption
+/// The decrypted key is never published: the network re-encrypts it to an encryption key chosen
+/// when decryption is requested, so only the holder of its decryption key learns the key.
+/// The encrypted key is a single secp256k1 scalar, proven to be the discrete log of a public key share,
+/// so it is suitable for keys (e.g. to derive a symmetric key from) but not for arbitrary user data.
+public struct NetworkEncryptedKey has key, store {
+    id: UID,
+    /// Elliptic curve the key is encrypted over
+    curve: u32,
+    /// ID of the network encryption key the key is encrypted to
+    dwallet_network_encryption_key_id: ID,
+    /// The encrypted key and the proof of its encryption
+    encrypted_key: vector<u8>,
+}
+
 // === Message Approval ===
 
 /// Authorization to sign a specific message with a dWallet.
@@ -800,6 +930,28 @@ public struct ImportedKeyMessageApproval has drop, store {
     message: vector<u8>,
 }
 
+/// Authorization to sign a specific message with a child key of a dWallet.
+///
+/// Similar to `MessageApproval`, but the message is signed by the child key
+/// derived from the dWallet public key at `derivation_path`, BIP-32 style.
+///
+/// ## Differences from Standard MessageApproval
+/// - Created by `approve_derived_key_message()` and used with `request_derived_key_sign()`
+/// - The child key shares the network secret key share of the dWallet, so presigns of the dWallet can be used
+/// - Can't be used for batch signing or with partial user signatures, which are verified against the dWallet key
+public struct DerivedKeyMessageApproval has drop, store {
+    /// ID of the dWallet whose child key is authorized to sign this message
+    dwallet_id: ID,
+    /// Cryptographic signature algorithm to use
+    signature_algorithm: u32,
+    /// Hash scheme to apply to the message before signing
+    hash_scheme: u32,
+    /// Raw message bytes to be signed
+    message: vector<u8>,
+    /// Non-hardened derivation path of the child key to sign with
+    derivation_path: vector<u32>,
+}
+
 // === Events ===
 
 // === Network Encryption Key DKG Events ===
@@ -1423,6 +1575,18 @@ public struct SignRequestEvent has copy, drop, store {
     is_future_sign: bool,
 }
 
+/// Event requesting the signature of a message by a child key of a dWallet.
+///
+/// The network derives the public output of the child key at `derivation_path`
+/// from the public output of the dWallet in `sign_request`, and signs with it.
+/// The sign completes like any other, with a `CompletedSignEvent` or a `RejectedSignEvent`.
+public struct DerivedKeySignRequestEvent has copy, drop, store {
+    /// The request to sign, with the public output of the dWallet itself
+    sign_request: SignRequestEvent,
+    /// Non-hardened derivation path of the child key to sign with
+    derivation_path: vector<u32>,
+}
+
 /// Event emitted when signature generation completes successfully.
 ///
 /// This event signals the successful completion of the 2PC-MPC signing protocol
@@ -1478,6 +1642,165 @@ public struct RejectedSignEvent has copy, drop, store {
     is_future_sign: bool,
 }
 
+// === Batch Sign Events ===
+
+/// Event requesting the signing of several messages of a single dWallet in one session.
+///
+/// Every message is signed with its own presign, exactly as in a `SignRequestEvent`,
+/// but all of them are signed together by the network, sharing the consensus rounds
+/// and the decryption of a single MPC session, and are responded to in a single
+/// checkpoint message.
+///
+/// All the messages are signed with the same signature algorithm and hash scheme.
+public struct BatchSignRequestEvent has copy, drop, store {
+    /// Unique identifier for this batch signing session
+    batch_sign_id: ID,
+    /// ID of the dWallet performing the signatures
+    dwallet_id: ID,
+    /// dWallet's public output for signature verification
+    dwallet_public_output: vector<u8>,
+    /// Elliptic curve for the signatures
+    curve: u32,
+    /// Cryptographic signature algorithm
+    signature_algorithm: u32,
+    /// Hash scheme applied to the messages
+    hash_scheme: u32,
+    /// ID of the network encryption key securing network shares
+    dwallet_network_encryption_key_id: ID,
+    /// The signs of the batch, in the order their signatures are responded with
+    signs: vector<BatchSignRequestItem>,
+}
+
+/// A single sign of a `BatchSignRequestEvent`.
+public struct BatchSignRequestItem has copy, drop, store {
+    /// Unique identifier of the `SignSession` of this sign
+    sign_id: ID,
+    /// Raw message bytes to be signed
+    message: vector<u8>,
+    /// ID of the presign used for this sign
+    presign_id: ID,
+    /// Precomputed cryptographic material for fast signing
+    presign: vector<u8>,
+    /// User's partial signature on the message
+    message_centralized_signature: vector<u8>,
+}
+
+/// Event emitted when all the signatures of a batch sign session are completed.
+///
+/// The signatures are ordered as the signs of the request, and each is also
+/// stored in the `SignSession` of its `sign_id`.
+public struct CompletedBatchSignEvent has copy, drop, store {
+    /// ID of the completed batch signing session
+    batch_sign_id: ID,
+    /// IDs of the signing sessions of the batch
+    sign_ids: vector<ID>,
+    /// The signatures, in the order of `sign_ids`
+    signatures: vector<vector<u8>>,
+}
+
+/// Event emitted when a batch sign session is rejected by the network.
+///
+/// A batch is signed atomically: when rejected, none of its messages are signed.
+public struct RejectedBatchSignEvent has copy, drop, store {
+    /// ID of the batch signing session that failed
+    batch_sign_id: ID,
+    /// IDs of the signing sessions of the batch
+    sign_ids: vector<ID>,
+}
+
+// === Threshold Decryption Events ===
+
+/// Event requesting the threshold decryption of a `NetworkEncryptedKey`.
+///
+/// The validators verify the proof of encryption, jointly decrypt the key
+/// using their shares of the network decryption key, and re-encrypt it to the encryption key
+/// of the requester.
+public struct ThresholdDecryptionRequestEvent has copy, drop, store {
+    /// Unique identifier for this decryption session
+    decryption_id: ID,
+    /// Elliptic curve the key is encrypted over
+    curve: u32,
+    /// ID of the network encryption key the key is encrypted to
+    dwallet_network_encryption_key_id: ID,
+    /// The encrypted key and the proof of its encryption
+    encrypted_key: vector<u8>,
+    /// Serialized encryption key the decrypted key is re-encrypted to
+    encryption_key: vector<u8>,
+    /// ID of the encryption key object
+    encryption_key_id: ID,
+}
+
+/// Event emitted when a key is successfully threshold decrypted.
+public struct CompletedThresholdDecryptionEvent has copy, drop, store {
+    /// ID of the completed decryption session
+    decryption_id: ID,
+    /// The decrypted key re-encrypted to the encryption key of the requester,
+    /// with the proof of its encryption
+    re_encrypted_key: vector<u8>,
+}
+
+/// Event emitted when a threshold decryption is rejected by the network.
+///
+/// ## Common Rejection Reasons
+/// - Invalid proof of encryption
+/// - Key encrypted to a different network encryption key
+public struct RejectedThresholdDecryptionEvent has copy, drop, store {
+    /// ID of the decryption session that failed
+    decryption_id: ID,
+}
+
+// === Key Refresh Events ===
+
+/// Event requesting the refresh of the secret key shares of a dWallet.
+///
+/// The user has added an offset to its secret key share, and encrypted its negation
+/// to the network encryption key. The validators subtract it from the encryption of the network
+/// secret key share, so the public key of the dWallet is unchanged while both shares are re-randomized.
+public struct DWalletKeyRefreshRequestEvent has copy, drop, store {
+    /// ID of the dWallet being refreshed
+    dwallet_id: ID,
+    /// Public output of the dWallet from before the refresh
+    dwallet_public_output: vector<u8>,
+    /// Elliptic curve for the dWallet
+    curve: u32,
+    /// ID of the network encryption key securing network shares
+    dwallet_network_encryption_key_id: ID,
+    /// The negated offset encrypted to the network encryption key, and the proof of its encryption
+    encrypted_refresh_share: vector<u8>,
+    /// ID of the encrypted user secret key share holding the refreshed share
+    encrypted_user_secret_key_share_id: ID,
+    /// The refreshed user secret key share encrypted with zero-knowledge proof of correctness
+    encrypted_centralized_secret_share_and_proof: vector<u8>,
+    /// Serialized encryption key the refreshed share is encrypted to
+    encryption_key: vector<u8>,
+    /// ID of the encryption key object
+    encryption_key_id: ID,
+}
+
+/// Event emitted when a dWallet key refresh completes successfully.
+public struct CompletedDWalletKeyRefreshEvent has copy, drop, store {
+    /// ID of the refreshed dWallet
+    dwallet_id: ID,
+    /// ID of the encrypted user secret key share holding the refreshed share
+    encrypted_user_secret_key_share_id: ID,
+    /// The refreshed public output of the dWallet
+    public_output: vector<u8>,
+}
+
+/// Event emitted when a dWallet key refresh is rejected by the network.
+///
+/// The dWallet keeps its shares from before the refresh.
+///
+/// ## Common Rejection Reasons
+/// - Invalid proof of encryption of the refresh share
+/// - The refreshed user share does not match the refreshed public output
+public struct RejectedDWalletKeyRefreshEvent has copy, drop, store {
+    /// ID of the dWallet that failed to refresh
+    dwallet_id: ID,
+    /// ID of the rejected encrypted user secret key share
+    encrypted_user_secret_key_share_id: ID,
+}
+
 // === Future Sign Events ===
 
 /// Event requesting validation of a partial user signature for future signing.
@@ -1569,6 +1892,41 @@ public struct RejectedFutureSignEvent has copy, drop, store {
     partial_centralized_signed_message_id: ID,
 }
 
+/// Event emitted when a future sign is requested with conditions on completing it.
+///
+/// Emitted along with the `FutureSignRequestEvent` of the request.
+public struct FutureSignConditionsSetEvent has copy, drop, store {
+    /// ID of the dWallet associated with the future signature
+    dwallet_id: ID,
+    /// ID of the partial user signature the conditions apply to
+    partial_centralized_signed_message_id: ID,
+    /// Epoch from which the signature can no longer be completed, or `None` if it never expires
+    expires_at_epoch: Option<u64>,
+    /// Epoch from which the signature can be completed, or `None` to allow it immediately
+    not_before_epoch: Option<u64>,
+    /// The only address that may complete the signature, or `None` to allow anyone holding the capability
+    completer: Option<address>,
+}
+
+/// Event emitted when the holder of a partial user signature capability cancels it.
+///
+/// The partial user signature can no longer be completed,
+/// and is removed with its presign at the end of the epoch.
+public struct CancelledFutureSignEvent has copy, drop, store {
+    /// ID of the dWallet associated with the future signature
+    dwallet_id: ID,
+    /// ID of the cancelled partial user signature
+    partial_centralized_signed_message_id: ID,
+}
+
+/// Event emitted when the Ika network removes expired and cancelled partial user signatures.
+public struct FutureSignsGarbageCollectedEvent has copy, drop, store {
+    /// The epoch up to which expired and cancelled partial user signatures were removed
+    epoch: u64,
+    /// IDs of the removed partial user signatures
+    partial_centralized_signed_message_ids: vector<ID>,
+}
+
 // === Operational Events ===
 
 /// Event containing dwallet 2pc-mpc checkpoint information, emitted during
@@ -2384,6 +2742,64 @@ public(package) fun approve_message(
     approval
 }
 
+/// Approves a message for signing by a child key of a dWallet.
+///
+/// Like `approve_message()`, but the message is signed by the child key derived from the
+/// dWallet public key at the non-hardened `derivation_path`, BIP-32 style.
+/// An empty `derivation_path` signs with the dWallet key itself.
+/// The child key shares the network secret key share of the dWallet,
+/// so any number of child keys can sign without running a DKG for each,
+/// and presigns of the dWallet can be used with any of them.
+///
+/// ### Parameters
+/// - `self`: Reference to the coordinator
+/// - `dwallet_cap`: Capability proving control over the dWallet
+/// - `signature_algorithm`: Algorithm to use for signing
+/// - `hash_scheme`: Hash scheme to apply to the message
+/// - `message`: Raw message bytes to be signed
+/// - `derivation_path`: Child indices from the dWallet key to the signing key
+///
+/// ### Returns
+/// A `DerivedKeyMessageApproval` that can be used to request signing with `request_derived_key_sign()`
+///
+/// ### Aborts
+/// - `EInvalidDerivationPath`: If the path is longer than 255 indices, or has a hardened index
+/// - Any abort of `approve_message()`
+public(package) fun approve_derived_key_message(
+    self: &DWalletCoordinatorInner,
+    dwallet_cap: &DWalletCap,
+    signature_algorithm: u32,
+    hash_scheme: u32,
+    message: vector<u8>,
+    derivation_path: vector<u32>,
+): DerivedKeyMessageApproval {
+    assert!(derivation_path.length() <= MAX_DERIVATION_PATH_LENGTH, EInvalidDerivationPath);
+    assert!(
+        derivation_path.all!(|index| *index < HARDENED_CHILD_INDEX_OFFSET),
+        EInvalidDerivationPath,
+    );
+
+    let MessageApproval {
+        dwallet_id,
+        signature_algorithm,
+        hash_scheme,
+        message,
+    } = self.approve_message(
+        dwallet_cap,
+        signature_algorithm,
+        hash_scheme,
+        message,
+    );
+
+    DerivedKeyMessageApproval {
+        dwallet_id,
+        signature_algorithm,
+        hash_scheme,
+        message,
+        derivation_path,
+    }
+}
+
 /// Approves a message for signing by an imported key dWallet.
 ///
 /// Creates a message approval that authorizes the specified message to be signed
@@ -2779,6 +3195,11 @@ public(package) fun request_dwallet_dkg_second_round(
             gas_fee_reimbursement_sui_for_system_calls,
         );
 
+    self.register_encrypted_user_secret_key_share(
+        dwallet_cap.dwallet_id,
+        encrypted_user_secret_key_share_id,
+        ctx,
+    );
     let dwallet = self.get_dwallet_mut(dwallet_cap.dwallet_id);
     dwallet
         .encrypted_user_secret_key_shares
@@ -2900,6 +3321,11 @@ public(package) fun request_re_encrypt_user_share_for(
     dwallet
         .encrypted_user_secret_key_shares
         .add(encrypted_user_secret_key_share_id, encrypted_user_share);
+    self.register_encrypted_user_secret_key_share(
+        dwallet_id,
+        encrypted_user_secret_key_share_id,
+        ctx,
+    );
 
     let pricing_value = self
         .pricing_and_fee_manager
@@ -2977,6 +3403,10 @@ public(package) fun respond_re_encrypt_user_share_for(
                     EncryptedUserSecretKeyShareState::NetworkVerificationCompleted
                 }
             },
+            // The share was invalidated by a key refresh of the dWallet while it was verified.
+            EncryptedUserSecretKeyShareState::NetworkVerificationRejected => {
+                EncryptedUserSecretKeyShareState::NetworkVerificationRejected
+            },
             _ => abort EWrongState,
         };
     gas_fee_reimbursement_sui
@@ -3116,6 +3546,11 @@ public(package) fun request_imported_key_dwallet_verification(
     let dwallet_cap_id = object::id(&dwallet_cap);
     let mut encrypted_user_secret_key_shares = object_table::new(ctx);
     encrypted_user_secret_key_shares.add(encrypted_user_secret_key_share_id, encrypted_user_share);
+    self.register_encrypted_user_secret_key_share(
+        dwallet_id,
+        encrypted_user_secret_key_share_id,
+        ctx,
+    );
     self
         .dwallets
         .add(
@@ -3707,37 +4142,19 @@ public(package) fun verify_presign_cap(
     cap
 }
 
-/// This function is a shared logic for both the standard and future sign flows.
-///
-/// It checks the presign is valid and deletes it (and its `presign_cap`), thus assuring it is not used twice.
-///
-/// Creates a `SignSession` object and register it in `sign_sessions`.
+/// Checks the presign of `presign_cap` is completed and can be used to sign with `dwallet_id`
+/// using `signature_algorithm`, and deletes it (and its `presign_cap`), thus assuring it is not used twice.
 ///
-/// Finally it emits the sign event.
-fun validate_and_initiate_sign(
+/// Returns the presign ID, the presign and its curve.
+fun take_completed_presign(
     self: &mut DWalletCoordinatorInner,
-    pricing_value: PricingInfoValue,
-    payment_ika: &mut Coin<IKA>,
-    payment_sui: &mut Coin<SUI>,
-    session_identifier: SessionIdentifier,
     dwallet_id: ID,
     signature_algorithm: u32,
-    hash_scheme: u32,
-    message: vector<u8>,
     presign_cap: VerifiedPresignCap,
-    message_centralized_signature: vector<u8>,
-    is_future_sign: bool,
-    ctx: &mut TxContext,
-): bool {
-    let created_at_epoch = self.current_epoch;
-
+): (ID, vector<u8>, u32) {
     assert!(self.presign_sessions.contains(presign_cap.presign_id), EPresignNotExist);
     let presign = self.presign_sessions.remove(presign_cap.presign_id);
 
-    let (dwallet, dwallet_public_output) = self.get_active_dwallet_and_public_output_mut(
-        dwallet_id,
-    );
-
     let VerifiedPresignCap {
         id,
         dwallet_id: presign_cap_dwallet_id,
@@ -3778,39 +4195,99 @@ fun validate_and_initiate_sign(
     assert!(presign_id == presign_cap_presign_id, EPresignNotExist);
     assert!(presign_cap_dwallet_id == presign_dwallet_id, EPresignNotExist);
 
-    // Check that the curve of the dWallet matches that of the presign, and that the signature algorithm matches.
-    assert!(dwallet.curve == curve, EDWalletMismatch);
+    // Check that the signature algorithm of the presign matches.
     assert!(presign_signature_algorithm == signature_algorithm, EMessageApprovalMismatch);
 
-    // Emit a `SignRequestEvent` to request the Ika network to sign `message`.
+    (presign_id, presign, curve)
+}
+
+/// This function is a shared logic for both the standard and future sign flows.
+///
+/// It checks the presign is valid and deletes it (and its `presign_cap`), thus assuring it is not used twice.
+///
+/// Creates a `SignSession` object and register it in `sign_sessions`.
+///
+/// Finally it emits the sign event.
+fun validate_and_initiate_sign(
+    self: &mut DWalletCoordinatorInner,
+    pricing_value: PricingInfoValue,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    session_identifier: SessionIdentifier,
+    dwallet_id: ID,
+    signature_algorithm: u32,
+    hash_scheme: u32,
+    message: vector<u8>,
+    presign_cap: VerifiedPresignCap,
+    message_centralized_signature: vector<u8>,
+    is_future_sign: bool,
+    derivation_path: Option<vector<u32>>,
+    ctx: &mut TxContext,
+): bool {
+    let created_at_epoch = self.current_epoch;
+
+    let (presign_id, presign, curve) = self.take_completed_presign(
+        dwallet_id,
+        signature_algorithm,
+        presign_cap,
+    );
+
+    let (dwallet, dwallet_public_output) = self.get_active_dwallet_and_public_output_mut(
+        dwallet_id,
+    );
+
+    // Check that the curve of the dWallet matches that of the presign.
+    assert!(dwallet.curve == curve, EDWalletMismatch);
+
+    // Emit a `SignRequestEvent` (or a `DerivedKeySignRequestEvent` when signing with a child key)
+    // to request the Ika network to sign `message`.
     let id = object::new(ctx);
     let sign_id = id.to_inner();
     let dwallet_network_encryption_key_id = dwallet.dwallet_network_encryption_key_id;
-    let gas_fee_reimbursement_sui_for_system_calls = self
-        .sessions_manager
-        .initiate_user_session(
-            self.current_epoch,
-            session_identifier,
-            dwallet_network_encryption_key_id,
-            pricing_value,
-            payment_ika,
-            payment_sui,
-            SignRequestEvent {
-                sign_id,
-                dwallet_id,
-                dwallet_public_output,
-                curve,
-                signature_algorithm,
-                hash_scheme,
-                message,
-                dwallet_network_encryption_key_id,
-                presign_id,
-                presign,
-                message_centralized_signature,
-                is_future_sign,
-            },
-            ctx,
-        );
+    let sign_request = SignRequestEvent {
+        sign_id,
+        dwallet_id,
+        dwallet_public_output,
+        curve,
+        signature_algorithm,
+        hash_scheme,
+        message,
+        dwallet_network_encryption_key_id,
+        presign_id,
+        presign,
+        message_centralized_signature,
+        is_future_sign,
+    };
+    let gas_fee_reimbursement_sui_for_system_calls = if (derivation_path.is_some()) {
+        self
+            .sessions_manager
+            .initiate_user_session(
+                self.current_epoch,
+                session_identifier,
+                dwallet_network_encryption_key_id,
+                pricing_value,
+                payment_ika,
+                payment_sui,
+                DerivedKeySignRequestEvent {
+                    sign_request,
+                    derivation_path: derivation_path.destroy_some(),
+                },
+                ctx,
+            )
+    } else {
+        self
+            .sessions_manager
+            .initiate_user_session(
+                self.current_epoch,
+                session_identifier,
+                dwallet_network_encryption_key_id,
+                pricing_value,
+                payment_ika,
+                payment_sui,
+                sign_request,
+                ctx,
+            )
+    };
     self
         .pricing_and_fee_manager
         .join_gas_fee_reimbursement_sui_system_call_balance(
@@ -3884,6 +4361,60 @@ public(package) fun request_sign(
         presign_cap,
         message_centralized_signature,
         false,
+        option::none(),
+        ctx,
+    );
+
+    assert!(!is_imported_key_dwallet, EImportedKeyDWallet);
+}
+
+/// Initiates the Sign protocol for a child key of this dWallet.
+/// Requires a `DerivedKeyMessageApproval`, which approves a message for signing by the child key
+/// and is unpacked and deleted to ensure it is never used twice.
+///
+/// The sign is priced, and its signature is read, as that of any other sign.
+public(package) fun request_derived_key_sign(
+    self: &mut DWalletCoordinatorInner,
+    message_approval: DerivedKeyMessageApproval,
+    presign_cap: VerifiedPresignCap,
+    message_centralized_signature: vector<u8>,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    let DerivedKeyMessageApproval {
+        dwallet_id,
+        signature_algorithm,
+        hash_scheme,
+        message,
+        derivation_path,
+    } = message_approval;
+
+    let (dwallet, _) = self.get_active_dwallet_and_public_output(dwallet_id);
+
+    let curve = dwallet.curve;
+    let pricing_value = self
+        .pricing_and_fee_manager
+        .get_pricing_value_for_protocol(
+            curve,
+            option::some(signature_algorithm),
+            SIGN_PROTOCOL_FLAG,
+        );
+
+    let is_imported_key_dwallet = self.validate_and_initiate_sign(
+        pricing_value,
+        payment_ika,
+        payment_sui,
+        session_identifier,
+        dwallet_id,
+        signature_algorithm,
+        hash_scheme,
+        message,
+        presign_cap,
+        message_centralized_signature,
+        false,
+        option::some(derivation_path),
         ctx,
     );
 
@@ -3931,12 +4462,156 @@ public(package) fun request_imported_key_sign(
         presign_cap,
         message_centralized_signature,
         false,
+        option::none(),
         ctx,
     );
 
     assert!(is_imported_key_dwallet, ENotImportedKeyDWallet);
 }
 
+/// Initiates the Sign protocol for several messages of this dWallet in a single session.
+///
+/// Each message is signed with its own presign and centralized signature, as in `request_sign()`,
+/// but the network signs all of them in one MPC session, and responds with all the signatures at once.
+/// All the `message_approvals` must approve messages of the same dWallet, signature algorithm and hash scheme.
+///
+/// Creates a `SignSession` for each message, so its signature can be read as that of any other sign.
+/// The session is priced as signing each of the messages separately.
+public(package) fun request_batch_sign(
+    self: &mut DWalletCoordinatorInner,
+    mut message_approvals: vector<MessageApproval>,
+    mut presign_caps: vector<VerifiedPresignCap>,
+    mut message_centralized_signatures: vector<vector<u8>>,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    let batch_size = message_approvals.length();
+    assert!(batch_size > 0 && batch_size <= MAX_SIGN_BATCH_SIZE, EInvalidSignBatchSize);
+    assert!(
+        presign_caps.length() == batch_size && message_centralized_signatures.length() == batch_size,
+        ESignBatchLengthMismatch,
+    );
+
+    let dwallet_id = message_approvals[0].dwallet_id;
+    let signature_algorithm = message_approvals[0].signature_algorithm;
+    let hash_scheme = message_approvals[0].hash_scheme;
+
+    let (dwallet, _) = self.get_active_dwallet_and_public_output(dwallet_id);
+    assert!(!dwallet.is_imported_key_dwallet, EImportedKeyDWallet);
+    let curve = dwallet.curve;
+    let pricing_value = self
+        .pricing_and_fee_manager
+        .get_pricing_value_for_protocol(
+            curve,
+            option::some(signature_algorithm),
+            SIGN_PROTOCOL_FLAG,
+        )
+        .scale(batch_size);
+
+    // Consume the presigns and approvals in their original order.
+    message_approvals.reverse();
+    presign_caps.reverse();
+    message_centralized_signatures.reverse();
+
+    let mut signs = vector[];
+    let mut sign_session_ids = vector[];
+    while (!message_approvals.is_empty()) {
+        let MessageApproval {
+            dwallet_id: message_dwallet_id,
+            signature_algorithm: message_signature_algorithm,
+            hash_scheme: message_hash_scheme,
+            message,
+        } = message_approvals.pop_back();
+        assert!(
+            message_dwallet_id == dwallet_id &&
+            message_signature_algorithm == signature_algorithm &&
+            message_hash_scheme == hash_scheme,
+            EMessageApprovalMismatch,
+        );
+
+        let (presign_id, presign, presign_curve) = self.take_completed_presign(
+            dwallet_id,
+            signature_algorithm,
+            presign_caps.pop_back(),
+        );
+        assert!(presign_curve == curve, EDWalletMismatch);
+
+        let id = object::new(ctx);
+        signs.push_back(BatchSignRequestItem {
+            sign_id: id.to_inner(),
+            message,
+            presign_id,
+            presign,
+            message_centralized_signature: message_centralized_signatures.pop_back(),
+        });
+        sign_session_ids.push_back(id);
+    };
+    message_approvals.destroy_empty();
+    presign_caps.destroy_empty();
+
+    let (dwallet, dwallet_public_output) = self.get_active_dwallet_and_public_output_mut(
+        dwallet_id,
+    );
+    let dwallet_network_encryption_key_id = dwallet.dwallet_network_encryption_key_id;
+
+    // Emit a `BatchSignRequestEvent` to request the Ika network to sign all the messages.
+    let batch_sign_id = object::id_from_address(ctx.fresh_object_address());
+    let gas_fee_reimbursement_sui_for_system_calls = self
+        .sessions_manager
+        .initiate_user_session(
+            self.current_epoch,
+            session_identifier,
+            dwallet_network_encryption_key_id,
+            pricing_value,
+            payment_ika,
+            payment_sui,
+            BatchSignRequestEvent {
+                batch_sign_id,
+                dwallet_id,
+                dwallet_public_output,
+                curve,
+                signature_algorithm,
+                hash_scheme,
+                dwallet_network_encryption_key_id,
+                signs,
+            },
+            ctx,
+        );
+    self
+        .pricing_and_fee_manager
+        .join_gas_fee_reimbursement_sui_system_call_balance(
+            gas_fee_reimbursement_sui_for_system_calls,
+        );
+
+    // Create a `SignSession` object for each message and register it in `sign_sessions`.
+    let created_at_epoch = self.current_epoch;
+    let dwallet = self.get_dwallet_mut(dwallet_id);
+    sign_session_ids.destroy!(|id| {
+        dwallet
+            .sign_sessions
+            .add(
+                id.to_inner(),
+                SignSession {
+                    id,
+                    created_at_epoch,
+                    dwallet_id,
+                    state: SignState::Requested,
+                },
+            );
+    });
+
+    self
+        .support_config
+        .validate_curve_and_signature_algorithm_and_hash_scheme(
+            curve,
+            signature_algorithm,
+            hash_scheme,
+        );
+    self.validate_network_encryption_key_supports_curve(dwallet_network_encryption_key_id, curve);
+}
+
 /// Request the Ika network verify the user-side sign protocol (in other words, that `message` is partially signed by the user),
 /// without (yet) executing the network side sign-protocol.
 ///
@@ -3962,6 +4637,50 @@ public(package) fun request_future_sign(
     payment_sui: &mut Coin<SUI>,
     ctx: &mut TxContext,
 ): UnverifiedPartialUserSignatureCap {
+    self.request_future_sign_with_conditions(
+        dwallet_id,
+        presign_cap,
+        message,
+        hash_scheme,
+        message_centralized_signature,
+        option::none(),
+        option::none(),
+        option::none(),
+        session_identifier,
+        payment_ika,
+        payment_sui,
+        ctx,
+    )
+}
+
+/// The variant of [`request_future_sign()`] (see for documentation) that limits when and by whom
+/// the partial user signature can be completed.
+///
+/// The signature can no longer be completed from `expires_at_epoch`, which must be after the current epoch,
+/// nor before `not_before_epoch`, and only `completer` may complete it if set.
+/// `None` leaves the respective condition unset.
+///
+/// The conditions are kept by the ID of the `PartialUserSignature`, and announced with a `FutureSignConditionsSetEvent`.
+/// Once expired, the `PartialUserSignature` and its presign are removed by the Ika network.
+public(package) fun request_future_sign_with_conditions(
+    self: &mut DWalletCoordinatorInner,
+    dwallet_id: ID,
+    presign_cap: VerifiedPresignCap,
+    message: vector<u8>,
+    hash_scheme: u32,
+    message_centralized_signature: vector<u8>,
+    expires_at_epoch: Option<u64>,
+    not_before_epoch: Option<u64>,
+    completer: Option<address>,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+): UnverifiedPartialUserSignatureCap {
+    assert!(
+        expires_at_epoch.is_none() || *expires_at_epoch.borrow() > self.current_epoch,
+        EInvalidFutureSignExpiry,
+    );
     // Check that the presign is global, or that it belongs to this dWallet.
     assert!(
         presign_cap.dwallet_id.is_none() || presign_cap.dwallet_id.is_some_and!(|id| id == dwallet_id),
@@ -4058,6 +4777,31 @@ public(package) fun request_future_sign(
                 signature_algorithm,
             },
         );
+    if (expires_at_epoch.is_some() || not_before_epoch.is_some() || completer.is_some()) {
+        self.add_future_sign_conditions(
+            partial_centralized_signed_message_id,
+            FutureSignConditions {
+                expires_at_epoch,
+                not_before_epoch,
+                completer,
+            },
+            ctx,
+        );
+        event::emit(FutureSignConditionsSetEvent {
+            dwallet_id,
+            partial_centralized_signed_message_id,
+            expires_at_epoch,
+            not_before_epoch,
+            completer,
+        });
+    };
+    if (expires_at_epoch.is_some()) {
+        self.add_future_sign_garbage(
+            *expires_at_epoch.borrow(),
+            partial_centralized_signed_message_id,
+            ctx,
+        );
+    };
 
     cap
 }
@@ -4118,70 +4862,352 @@ public(package) fun respond_future_sign(
     gas_fee_reimbursement_sui
 }
 
-/// Checks that the partial user signature corresponding to `cap` is valid, by assuring it is in the `NetworkVerificationCompleted` state.
-public(package) fun is_partial_user_signature_valid(
-    self: &DWalletCoordinatorInner,
-    cap: &UnverifiedPartialUserSignatureCap,
-): bool {
-    let partial_centralized_signed_message = self
-        .partial_centralized_signed_messages
-        .borrow(cap.partial_centralized_signed_message_id);
-    partial_centralized_signed_message.cap_id == cap.id.to_inner() && partial_centralized_signed_message.state == PartialUserSignatureState::NetworkVerificationCompleted
-}
-
-/// Verifies that the partial user signature corresponding to `cap` is valid,
-/// deleting the `UnverifiedPartialUserSignatureCap` object and returning a new `VerifiedPartialUserSignatureCap` in its place.
-public(package) fun verify_partial_user_signature_cap(
+/// Cancels the partial user signature of `cap` before it was verified by the network,
+/// see [`cancel_future_sign()`].
+public(package) fun cancel_unverified_future_sign(
     self: &mut DWalletCoordinatorInner,
     cap: UnverifiedPartialUserSignatureCap,
     ctx: &mut TxContext,
-): VerifiedPartialUserSignatureCap {
+) {
     let UnverifiedPartialUserSignatureCap {
         id,
         partial_centralized_signed_message_id,
     } = cap;
+    let cap_id = id.to_inner();
+    id.delete();
+    self.cancel_partial_user_signature(partial_centralized_signed_message_id, cap_id, ctx);
+}
 
+/// Cancels the partial user signature of `cap`, deleting the capability.
+///
+/// The partial user signature can no longer be completed; it is removed, along with its presign,
+/// by the Ika network at the end of the current epoch.
+public(package) fun cancel_future_sign(
+    self: &mut DWalletCoordinatorInner,
+    cap: VerifiedPartialUserSignatureCap,
+    ctx: &mut TxContext,
+) {
+    let VerifiedPartialUserSignatureCap {
+        id,
+        partial_centralized_signed_message_id,
+    } = cap;
     let cap_id = id.to_inner();
     id.delete();
+    self.cancel_partial_user_signature(partial_centralized_signed_message_id, cap_id, ctx);
+}
 
+fun cancel_partial_user_signature(
+    self: &mut DWalletCoordinatorInner,
+    partial_centralized_signed_message_id: ID,
+    cap_id: ID,
+    ctx: &mut TxContext,
+) {
     let partial_centralized_signed_message = self
         .partial_centralized_signed_messages
-        .borrow_mut(partial_centralized_signed_message_id);
+        .borrow(partial_centralized_signed_message_id);
     assert!(partial_centralized_signed_message.cap_id == cap_id, EIncorrectCap);
+    let dwallet_id = partial_centralized_signed_message.dwallet_id;
     assert!(
-        partial_centralized_signed_message.state == PartialUserSignatureState::NetworkVerificationCompleted,
-        EUnverifiedCap,
+        !self.is_future_sign_cancelled(partial_centralized_signed_message_id),
+        EFutureSignCancelled,
     );
-    let cap = VerifiedPartialUserSignatureCap {
-        id: object::new(ctx),
+
+    if (!self.extra_fields.contains(CancelledFutureSignsKey {})) {
+        self.extra_fields.add(CancelledFutureSignsKey {}, table::new<ID, u64>(ctx));
+    };
+    let current_epoch = self.current_epoch;
+    let cancelled: &mut Table<ID, u64> = self.extra_fields.borrow_mut(CancelledFutureSignsKey {});
+    cancelled.add(partial_centralized_signed_message_id, current_epoch);
+    self.add_future_sign_garbage(current_epoch, partial_centralized_signed_message_id, ctx);
+    event::emit(CancelledFutureSignEvent {
+        dwallet_id,
         partial_centralized_signed_message_id,
+    });
+}
+
+/// Whether the partial user signature of `partial_centralized_signed_message_id` was cancelled.
+fun is_future_sign_cancelled(
+    self: &DWalletCoordinatorInner,
+    partial_centralized_signed_message_id: ID,
+): bool {
+    if (!self.extra_fields.contains(CancelledFutureSignsKey {})) {
+        return false
     };
-    partial_centralized_signed_message.cap_id = cap.id.to_inner();
-    cap
+    let cancelled: &Table<ID, u64> = self.extra_fields.borrow(CancelledFutureSignsKey {});
+    cancelled.contains(partial_centralized_signed_message_id)
 }
 
-/// Requests the Ika network to complete the signing session on a message that was already partially-signed by the user (i.e. a message with a verified [`PartialUserSignature`]).
-/// Useful is `message_approval` was only acquired after `PartialUserSignature` was created, and the caller does not own the user-share of this dWallet.
-///
-/// Takes the `presign_cap` from the `PartialUserSignature` object, and destroys it in `validate_and_initiate_sign()`,
-/// ensuring the presign was not used for any other purpose than signing this message once.
-///
-/// See the doc of [`PartialUserSignature`] for
-/// more details on when this may be used.
-public(package) fun request_sign_with_partial_user_signature(
+/// Sets the `conditions` on completing the partial user signature of `partial_centralized_signed_message_id`.
+fun add_future_sign_conditions(
     self: &mut DWalletCoordinatorInner,
-    partial_user_signature_cap: VerifiedPartialUserSignatureCap,
-    message_approval: MessageApproval,
-    session_identifier: SessionIdentifier,
-    payment_ika: &mut Coin<IKA>,
-    payment_sui: &mut Coin<SUI>,
+    partial_centralized_signed_message_id: ID,
+    conditions: FutureSignConditions,
     ctx: &mut TxContext,
 ) {
-    // Ensure that each partial user signature has a corresponding message approval; otherwise, abort.
-    let is_match = self.match_partial_user_signature_with_message_approval(
-        &partial_user_signature_cap,
-        &message_approval,
-    );
+    if (!self.extra_fields.contains(FutureSignConditionsKey {})) {
+        self
+            .extra_fields
+            .add(FutureSignConditionsKey {}, table::new<ID, FutureSignConditions>(ctx));
+    };
+    let all_conditions: &mut Table<ID, FutureSignConditions> = self
+        .extra_fields
+        .borrow_mut(FutureSignConditionsKey {});
+    all_conditions.add(partial_centralized_signed_message_id, conditions);
+}
+
+/// Removes the conditions and the cancellation of the partial user signature of `partial_centralized_signed_message_id`
+/// once it is completed or removed, returning its conditions, which are all unset if it has none.
+fun remove_future_sign_conditions(
+    self: &mut DWalletCoordinatorInner,
+    partial_centralized_signed_message_id: ID,
+): FutureSignConditions {
+    if (self.is_future_sign_cancelled(partial_centralized_signed_message_id)) {
+        let cancelled: &mut Table<ID, u64> = self
+            .extra_fields
+            .borrow_mut(CancelledFutureSignsKey {});
+        cancelled.remove(partial_centralized_signed_message_id);
+    };
+    if (self.extra_fields.contains(FutureSignConditionsKey {})) {
+        let all_conditions: &mut Table<ID, FutureSignConditions> = self
+            .extra_fields
+            .borrow_mut(FutureSignConditionsKey {});
+        if (all_conditions.contains(partial_centralized_signed_message_id)) {
+            return all_conditions.remove(partial_centralized_signed_message_id)
+        };
+    };
+    FutureSignConditions {
+        expires_at_epoch: option::none(),
+        not_before_epoch: option::none(),
+        completer: option::none(),
+    }
+}
+
+/// Aborts unless a partial user signature with `conditions` can be completed by the sender in the current epoch.
+fun assert_future_sign_can_complete(
+    self: &DWalletCoordinatorInner,
+    conditions: FutureSignConditions,
+    ctx: &TxContext,
+) {
+    let FutureSignConditions { expires_at_epoch, not_before_epoch, completer } = conditions;
+    assert!(
+        expires_at_epoch.is_none() || self.current_epoch < *expires_at_epoch.borrow(),
+        EFutureSignExpired,
+    );
+    assert!(
+        not_before_epoch.is_none() || self.current_epoch >= *not_before_epoch.borrow(),
+        EFutureSignConditionsNotMet,
+    );
+    assert!(
+        completer.is_none() || *completer.borrow() == ctx.sender(),
+        EFutureSignConditionsNotMet,
+    );
+}
+
+/// Indexes the partial user signature of `partial_centralized_signed_message_id` for removal from `epoch`.
+fun add_future_sign_garbage(
+    self: &mut DWalletCoordinatorInner,
+    epoch: u64,
+    partial_centralized_signed_message_id: ID,
+    ctx: &mut TxContext,
+) {
+    if (!self.extra_fields.contains(FutureSignGarbageKey {})) {
+        let next_epoch_to_collect = self.current_epoch;
+        self
+            .extra_fields
+            .add(
+                FutureSignGarbageKey {},
+                FutureSignGarbage {
+                    next_epoch_to_collect,
+                    by_epoch: table::new(ctx),
+                },
+            );
+    };
+    let garbage: &mut FutureSignGarbage = self.extra_fields.borrow_mut(FutureSignGarbageKey {});
+    if (!garbage.by_epoch.contains(epoch)) {
+        garbage.by_epoch.add(epoch, vector[]);
+    };
+    garbage.by_epoch.borrow_mut(epoch).push_back(partial_centralized_signed_message_id);
+}
+
+/// Called by the Ika network at the end of `epoch` to remove the partial user signatures
+/// that expired by it or were cancelled in it, along with their presigns.
+///
+/// At most `MAX_FUTURE_SIGNS_GARBAGE_COLLECTED_PER_MESSAGE` partial user signatures are gone over,
+/// the rest are left at `FutureSignGarbage.next_epoch_to_collect` for the next call.
+/// Partial user signatures that are still awaiting network verification are kept until
+/// the verification is responded to, and collected at the end of the next epoch.
+fun garbage_collect_future_signs(self: &mut DWalletCoordinatorInner, epoch: u64) {
+    // A checkpoint must never abort, so an unexpected epoch is ignored rather than asserted on.
+    if (epoch > self.current_epoch || !self.extra_fields.contains(FutureSignGarbageKey {})) {
+        return
+    };
+    let garbage: &mut FutureSignGarbage = self.extra_fields.borrow_mut(FutureSignGarbageKey {});
+    let mut ids = vector[];
+    while (
+        garbage.next_epoch_to_collect <= epoch &&
+        ids.length() < MAX_FUTURE_SIGNS_GARBAGE_COLLECTED_PER_MESSAGE
+    ) {
+        let collected_epoch = garbage.next_epoch_to_collect;
+        let mut epoch_collected = true;
+        if (garbage.by_epoch.contains(collected_epoch)) {
+            let epoch_ids = garbage.by_epoch.borrow_mut(collected_epoch);
+            while (
+                !epoch_ids.is_empty() && ids.length() < MAX_FUTURE_SIGNS_GARBAGE_COLLECTED_PER_MESSAGE
+            ) {
+                ids.push_back(epoch_ids.pop_back());
+            };
+            if (epoch_ids.is_empty()) {
+                garbage.by_epoch.remove(collected_epoch).destroy_empty();
+            } else {
+                epoch_collected = false;
+            };
+        };
+        if (epoch_collected) {
+            garbage.next_epoch_to_collect = collected_epoch + 1;
+        };
+    };
+
+    let mut collected_ids = vector[];
+    let mut postponed_ids = vector[];
+    ids.do!(|partial_centralized_signed_message_id| {
+        // Signatures that were already completed or collected are no longer in the table.
+        if (self.partial_centralized_signed_messages.contains(partial_centralized_signed_message_id)) {
+            let state = self
+                .partial_centralized_signed_messages
+                .borrow(partial_centralized_signed_message_id)
+                .state;
+            if (state == PartialUserSignatureState::AwaitingNetworkVerification) {
+                postponed_ids.push_back(partial_centralized_signed_message_id);
+            } else {
+                self.destroy_partial_user_signature(partial_centralized_signed_message_id);
+                collected_ids.push_back(partial_centralized_signed_message_id);
+            };
+        };
+    });
+
+    if (!postponed_ids.is_empty()) {
+        let garbage: &mut FutureSignGarbage = self
+            .extra_fields
+            .borrow_mut(FutureSignGarbageKey {});
+        let next_epoch = epoch + 1;
+        if (!garbage.by_epoch.contains(next_epoch)) {
+            garbage.by_epoch.add(next_epoch, vector[]);
+        };
+        garbage.by_epoch.borrow_mut(next_epoch).append(postponed_ids);
+    };
+
+    event::emit(FutureSignsGarbageCollectedEvent {
+        epoch,
+        partial_centralized_signed_message_ids: collected_ids,
+    });
+}
+
+/// Removes the partial user signature of `partial_centralized_signed_message_id`, deleting its presign.
+fun destroy_partial_user_signature(
+    self: &mut DWalletCoordinatorInner,
+    partial_centralized_signed_message_id: ID,
+) {
+    let PartialUserSignature {
+        id,
+        created_at_epoch: _,
+        presign_cap,
+        dwallet_id: _,
+        cap_id: _,
+        curve: _,
+        signature_algorithm: _,
+        hash_scheme: _,
+        message: _,
+        message_centralized_signature: _,
+        state: _,
+    } = self.partial_centralized_signed_messages.remove(partial_centralized_signed_message_id);
+    id.delete();
+    self.remove_future_sign_conditions(partial_centralized_signed_message_id);
+
+    let VerifiedPresignCap {
+        id,
+        dwallet_id: _,
+        presign_id,
+    } = presign_cap;
+    id.delete();
+
+    if (self.presign_sessions.contains(presign_id)) {
+        let PresignSession {
+            id,
+            created_at_epoch: _,
+            dwallet_id: _,
+            cap_id: _,
+            state: _,
+            curve: _,
+            signature_algorithm: _,
+        } = self.presign_sessions.remove(presign_id);
+        id.delete();
+    };
+}
+
+/// Checks that the partial user signature corresponding to `cap` is valid, by assuring it is in the `NetworkVerificationCompleted` state.
+public(package) fun is_partial_user_signature_valid(
+    self: &DWalletCoordinatorInner,
+    cap: &UnverifiedPartialUserSignatureCap,
+): bool {
+    let partial_centralized_signed_message = self
+        .partial_centralized_signed_messages
+        .borrow(cap.partial_centralized_signed_message_id);
+    partial_centralized_signed_message.cap_id == cap.id.to_inner() && partial_centralized_signed_message.state == PartialUserSignatureState::NetworkVerificationCompleted
+}
+
+/// Verifies that the partial user signature corresponding to `cap` is valid,
+/// deleting the `UnverifiedPartialUserSignatureCap` object and returning a new `VerifiedPartialUserSignatureCap` in its place.
+public(package) fun verify_partial_user_signature_cap(
+    self: &mut DWalletCoordinatorInner,
+    cap: UnverifiedPartialUserSignatureCap,
+    ctx: &mut TxContext,
+): VerifiedPartialUserSignatureCap {
+    let UnverifiedPartialUserSignatureCap {
+        id,
+        partial_centralized_signed_message_id,
+    } = cap;
+
+    let cap_id = id.to_inner();
+    id.delete();
+
+    let partial_centralized_signed_message = self
+        .partial_centralized_signed_messages
+        .borrow_mut(partial_centralized_signed_message_id);
+    assert!(partial_centralized_signed_message.cap_id == cap_id, EIncorrectCap);
+    assert!(
+        partial_centralized_signed_message.state == PartialUserSignatureState::NetworkVerificationCompleted,
+        EUnverifiedCap,
+    );
+    let cap = VerifiedPartialUserSignatureCap {
+        id: object::new(ctx),
+        partial_centralized_signed_message_id,
+    };
+    partial_centralized_signed_message.cap_id = cap.id.to_inner();
+    cap
+}
+
+/// Requests the Ika network to complete the signing session on a message that was already partially-signed by the user (i.e. a message with a verified [`PartialUserSignature`]).
+/// Useful is `message_approval` was only acquired after `PartialUserSignature` was created, and the caller does not own the user-share of this dWallet.
+///
+/// Takes the `presign_cap` from the `PartialUserSignature` object, and destroys it in `validate_and_initiate_sign()`,
+/// ensuring the presign was not used for any other purpose than signing this message once.
+///
+/// See the doc of [`PartialUserSignature`] for
+/// more details on when this may be used.
+public(package) fun request_sign_with_partial_user_signature(
+    self: &mut DWalletCoordinatorInner,
+    partial_user_signature_cap: VerifiedPartialUserSignatureCap,
+    message_approval: MessageApproval,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    // Ensure that each partial user signature has a corresponding message approval; otherwise, abort.
+    let is_match = self.match_partial_user_signature_with_message_approval(
+        &partial_user_signature_cap,
+        &message_approval,
+    );
     assert!(is_match, EMessageApprovalMismatch);
 
     let VerifiedPartialUserSignatureCap {
@@ -4206,10 +5232,16 @@ public(package) fun request_sign_with_partial_user_signature(
     } = self.partial_centralized_signed_messages.remove(partial_centralized_signed_message_id);
 
     id.delete();
+    assert!(
+        !self.is_future_sign_cancelled(partial_centralized_signed_message_id),
+        EFutureSignCancelled,
+    );
     assert!(
         cap_id == verified_cap_id && state == PartialUserSignatureState::NetworkVerificationCompleted,
         EIncorrectCap,
     );
+    let conditions = self.remove_future_sign_conditions(partial_centralized_signed_message_id);
+    self.assert_future_sign_can_complete(conditions, ctx);
 
     let MessageApproval {
         dwallet_id,
@@ -4239,6 +5271,7 @@ public(package) fun request_sign_with_partial_user_signature(
         presign_cap,
         message_centralized_signature,
         true,
+        option::none(),
         ctx,
     );
     assert!(!is_imported_key_dwallet, EImportedKeyDWallet);
@@ -4283,131 +5316,734 @@ public(package) fun request_imported_key_sign_with_partial_user_signature(
     } = self.partial_centralized_signed_messages.remove(partial_centralized_signed_message_id);
     id.delete();
     assert!(
-        cap_id == verified_cap_id && state == PartialUserSignatureState::NetworkVerificationCompleted,
-        EIncorrectCap,
+        !self.is_future_sign_cancelled(partial_centralized_signed_message_id),
+        EFutureSignCancelled,
+    );
+    assert!(
+        cap_id == verified_cap_id && state == PartialUserSignatureState::NetworkVerificationCompleted,
+        EIncorrectCap,
+    );
+    let conditions = self.remove_future_sign_conditions(partial_centralized_signed_message_id);
+    self.assert_future_sign_can_complete(conditions, ctx);
+
+    let ImportedKeyMessageApproval {
+        dwallet_id,
+        signature_algorithm,
+        hash_scheme,
+        message,
+    } = message_approval;
+
+    let pricing_value = self
+        .pricing_and_fee_manager
+        .get_pricing_value_for_protocol(
+            curve,
+            option::some(signature_algorithm),
+            SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG,
+        );
+
+    // Emit signing events to finalize the signing process.
+    let is_imported_key_dwallet = self.validate_and_initiate_sign(
+        pricing_value,
+        payment_ika,
+        payment_sui,
+        session_identifier,
+        dwallet_id,
+        signature_algorithm,
+        hash_scheme,
+        message,
+        presign_cap,
+        message_centralized_signature,
+        true,
+        option::none(),
+        ctx,
+    );
+    assert!(is_imported_key_dwallet, ENotImportedKeyDWallet);
+}
+
+/// Matches partial user signature with message approval to ensure they are consistent.
+/// This function can be called by the user to verify before calling
+/// the `request_sign_with_partial_user_signature` function.
+/// It is also called before requesting the Ika network to complete the signing.
+public(package) fun match_partial_user_signature_with_message_approval(
+    self: &DWalletCoordinatorInner,
+    partial_user_signature_cap: &VerifiedPartialUserSignatureCap,
+    message_approval: &MessageApproval,
+): bool {
+    let partial_signature = self
+        .partial_centralized_signed_messages
+        .borrow(partial_user_signature_cap.partial_centralized_signed_message_id);
+
+    partial_signature.dwallet_id == message_approval.dwallet_id &&
+    partial_signature.message == message_approval.message &&
+    partial_signature.signature_algorithm == message_approval.signature_algorithm &&
+    partial_signature.hash_scheme == message_approval.hash_scheme
+}
+
+/// Matches partial user signature with imported key message approval to ensure they are consistent.
+/// This function can be called by the user to verify before calling
+/// the `request_imported_key_sign_with_partial_user_signatures` function.
+public(package) fun match_partial_user_signature_with_imported_key_message_approval(
+    self: &DWalletCoordinatorInner,
+    partial_user_signature_cap: &VerifiedPartialUserSignatureCap,
+    message_approval: &ImportedKeyMessageApproval,
+): bool {
+    let partial_signature = self
+        .partial_centralized_signed_messages
+        .borrow(partial_user_signature_cap.partial_centralized_signed_message_id);
+
+    partial_signature.dwallet_id == message_approval.dwallet_id &&
+    partial_signature.message == message_approval.message &&
+    partial_signature.signature_algorithm == message_approval.signature_algorithm &&
+    partial_signature.hash_scheme == message_approval.hash_scheme
+}
+
+/// Called by the Ika network to respond to (and complete) a Sign protocol request.
+///
+/// Sets the `SignSession` to `Completed` and stores in it the `signature`.
+/// Also emits an event with the `signature`.
+public(package) fun respond_sign(
+    self: &mut DWalletCoordinatorInner,
+    dwallet_id: ID,
+    sign_id: ID,
+    signature: vector<u8>,
+    is_future_sign: bool,
+    rejected: bool,
+    session_sequence_number: u64,
+): Balance<SUI> {
+    let status = if (rejected) {
+        sessions_manager::create_rejected_status_event(RejectedSignEvent {
+            sign_id,
+            is_future_sign,
+        })
+    } else {
+        sessions_manager::create_success_status_event(CompletedSignEvent {
+            sign_id,
+            signature,
+            is_future_sign,
+        })
+    };
+    // A sign with a child key of the dWallet is responded to like any other sign.
+    let (fee_charged_ika, gas_fee_reimbursement_sui) = if (
+        self
+            .sessions_manager
+            .is_user_session_event_of_type<DerivedKeySignRequestEvent>(session_sequence_number)
+    ) {
+        self
+            .sessions_manager
+            .complete_user_session<DerivedKeySignRequestEvent, CompletedSignEvent, RejectedSignEvent>(
+                self.current_epoch,
+                session_sequence_number,
+                status,
+            )
+    } else {
+        self
+            .sessions_manager
+            .complete_user_session<SignRequestEvent, CompletedSignEvent, RejectedSignEvent>(
+                self.current_epoch,
+                session_sequence_number,
+                status,
+            )
+    };
+    self.pricing_and_fee_manager.join_fee_charged_ika(fee_charged_ika);
+    let (dwallet, _) = self.get_active_dwallet_and_public_output_mut(dwallet_id);
+
+    let sign = dwallet.sign_sessions.borrow_mut(sign_id);
+
+    sign.state =
+        match (sign.state) {
+            SignState::Requested => {
+                if (rejected) {
+                    SignState::NetworkRejected
+                } else {
+                    SignState::Completed { signature }
+                }
+            },
+            _ => abort ESignWrongState,
+        };
+    gas_fee_reimbursement_sui
+}
+
+/// Called by the Ika network to respond to (and complete) a batch Sign protocol request.
+///
+/// Sets the `SignSession` of every sign in the batch to `Completed` and stores in it its signature,
+/// or to `NetworkRejected` if the batch was rejected.
+/// Also emits an event with all the `signatures`.
+public(package) fun respond_batch_sign(
+    self: &mut DWalletCoordinatorInner,
+    dwallet_id: ID,
+    batch_sign_id: ID,
+    sign_ids: vector<ID>,
+    mut signatures: vector<vector<u8>>,
+    rejected: bool,
+    session_sequence_number: u64,
+): Balance<SUI> {
+    assert!(rejected || signatures.length() == sign_ids.length(), ESignBatchLengthMismatch);
+
+    let status = if (rejected) {
+        sessions_manager::create_rejected_status_event(RejectedBatchSignEvent {
+            batch_sign_id,
+            sign_ids,
+        })
+    } else {
+        sessions_manager::create_success_status_event(CompletedBatchSignEvent {
+            batch_sign_id,
+            sign_ids,
+            signatures,
+        })
+    };
+    let (fee_charged_ika, gas_fee_reimbursement_sui) = self
+        .sessions_manager
+        .complete_user_session<
+            BatchSignRequestEvent,
+            CompletedBatchSignEvent,
+            RejectedBatchSignEvent,
+        >(
+            self.current_epoch,
+            session_sequence_number,
+            status,
+        );
+    self.pricing_and_fee_manager.join_fee_charged_ika(fee_charged_ika);
+    let (dwallet, _) = self.get_active_dwallet_and_public_output_mut(dwallet_id);
+
+    signatures.reverse();
+    sign_ids.do!(|sign_id| {
+        let sign = dwallet.sign_sessions.borrow_mut(sign_id);
+        sign.state =
+            match (sign.state) {
+                SignState::Requested => {
+                    if (rejected) {
+                        SignState::NetworkRejected
+                    } else {
+                        SignState::Completed { signature: signatures.pop_back() }
+                    }
+                },
+                _ => abort ESignWrongState,
+            };
+    });
+    gas_fee_reimbursement_sui
+}
+
+/// Registers a key encrypted to a network encryption key for threshold decryption.
+///
+/// Every encrypted key can only be registered once, so that a key registered (and guarded)
+/// by one user can't be registered again, and decrypted, by another.
+///
+/// ### Parameters
+/// - `dwallet_network_encryption_key_id`: The network encryption key the key is encrypted to
+/// - `curve`: Elliptic curve the key is encrypted over
+/// - `encrypted_key`: The encrypted key and the proof of its encryption
+/// - `ctx`: Transaction context
+///
+/// ### Returns
+/// The `NetworkEncryptedKey`, whose holder can request its decryption
+///
+/// ### Aborts
+/// - `EInvalidCurve`: If the curve is not supported or is paused
+/// - `EDWalletNetworkEncryptionKeyNotExist`: If the network encryption key doesn't exist
+/// - `ENetworkEncryptedKeyAlreadyRegistered`: If the encrypted key was already registered
+public(package) fun register_network_encrypted_key(
+    self: &mut DWalletCoordinatorInner,
+    dwallet_network_encryption_key_id: ID,
+    curve: u32,
+    encrypted_key: vector<u8>,
+    ctx: &mut TxContext,
+): NetworkEncryptedKey {
+    self.support_config.validate_curve(curve);
+    assert!(
+        self.dwallet_network_encryption_keys.contains(dwallet_network_encryption_key_id),
+        EDWalletNetworkEncryptionKeyNotExist,
+    );
+    self.validate_network_encryption_key_supports_curve(dwallet_network_encryption_key_id, curve);
+
+    let network_encrypted_key = NetworkEncryptedKey {
+        id: object::new(ctx),
+        curve,
+        dwallet_network_encryption_key_id,
+        encrypted_key,
+    };
+    self.register_network_encrypted_key_digest(
+        &network_encrypted_key.encrypted_key,
+        object::id(&network_encrypted_key),
+        ctx,
+    );
+
+    network_encrypted_key
+}
+
+/// Records the digest of `encrypted_key` in the registry of network encrypted keys,
+/// see `NetworkEncryptedKeysRegistryKey`.
+///
+/// ### Aborts
+/// - `ENetworkEncryptedKeyAlreadyRegistered`: If the encrypted key was already registered
+fun register_network_encrypted_key_digest(
+    self: &mut DWalletCoordinatorInner,
+    encrypted_key: &vector<u8>,
+    id: ID,
+    ctx: &mut TxContext,
+) {
+    if (!self.extra_fields.contains(NetworkEncryptedKeysRegistryKey {})) {
+        self
+            .extra_fields
+            .add(NetworkEncryptedKeysRegistryKey {}, table::new<vector<u8>, ID>(ctx));
+    };
+    let registry: &mut Table<vector<u8>, ID> = self
+        .extra_fields
+        .borrow_mut(NetworkEncryptedKeysRegistryKey {});
+    let encrypted_key_digest = hash::keccak256(encrypted_key);
+    assert!(!registry.contains(encrypted_key_digest), ENetworkEncryptedKeyAlreadyRegistered);
+    registry.add(encrypted_key_digest, id);
+}
+
+/// Requests the threshold decryption of a `NetworkEncryptedKey`.
+///
+/// The decrypted key is never published: the network re-encrypts it to the encryption key
+/// registered at `encryption_key_address`, and only the re-encrypted key is published
+/// in the `CompletedThresholdDecryptionEvent`.
+///
+/// ### Parameters
+/// - `network_encrypted_key`: The key to decrypt
+/// - `encryption_key_address`: Address of the encryption key to re-encrypt the decrypted key to
+/// - `session_identifier`: Unique session identifier
+/// - `payment_ika`: User's IKA payment for computation
+/// - `payment_sui`: User's SUI payment for gas reimbursement
+/// - `ctx`: Transaction context
+///
+/// ### Aborts
+/// - `EMissingProtocolPricing`: If pricing is not configured for threshold decryption
+/// - `EDWalletNetworkEncryptionKeyNotActive`: If the network encryption key is not active
+/// - `EEncryptionKeyNotExist`: If no encryption key is registered at `encryption_key_address`
+/// - `EMismatchCurve`: If the encryption key is of a different curve than the encrypted key
+/// - Various payment-related errors if insufficient funds provided
+public(package) fun request_threshold_decryption(
+    self: &mut DWalletCoordinatorInner,
+    network_encrypted_key: &NetworkEncryptedKey,
+    encryption_key_address: address,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    let curve = network_encrypted_key.curve;
+    let dwallet_network_encryption_key_id = network_encrypted_key.dwallet_network_encryption_key_id;
+    self.support_config.validate_curve(curve);
+    assert!(self.encryption_keys.contains(encryption_key_address), EEncryptionKeyNotExist);
+    let encryption_key = self.encryption_keys.borrow(encryption_key_address);
+    assert!(encryption_key.curve == curve, EMismatchCurve);
+    let encryption_key_id = encryption_key.id.to_inner();
+    let encryption_key = encryption_key.encryption_key;
+    self.get_active_dwallet_network_encryption_key(dwallet_network_encryption_key_id);
+
+    let pricing_value = self
+        .pricing_and_fee_manager
+        .get_pricing_value_for_protocol(curve, option::none(), THRESHOLD_DECRYPTION_PROTOCOL_FLAG);
+
+    let decryption_id = object::id_from_address(ctx.fresh_object_address());
+    let gas_fee_reimbursement_sui_for_system_calls = self
+        .sessions_manager
+        .initiate_user_session(
+            self.current_epoch,
+            session_identifier,
+            dwallet_network_encryption_key_id,
+            pricing_value,
+            payment_ika,
+            payment_sui,
+            ThresholdDecryptionRequestEvent {
+                decryption_id,
+                curve,
+                dwallet_network_encryption_key_id,
+                encrypted_key: network_encrypted_key.encrypted_key,
+                encryption_key,
+                encryption_key_id,
+            },
+            ctx,
+        );
+    self
+        .pricing_and_fee_manager
+        .join_gas_fee_reimbursement_sui_system_call_balance(
+            gas_fee_reimbursement_sui_for_system_calls,
+        );
+}
+
+/// Called by the Ika network to respond to (and complete) a threshold decryption request.
+///
+/// Emits an event with the `re_encrypted_key`, or a rejection event if the network rejected the request.
+public(package) fun respond_threshold_decryption(
+    self: &mut DWalletCoordinatorInner,
+    decryption_id: ID,
+    re_encrypted_key: vector<u8>,
+    rejected: bool,
+    session_sequence_number: u64,
+): Balance<SUI> {
+    let status = if (rejected) {
+        sessions_manager::create_rejected_status_event(RejectedThresholdDecryptionEvent {
+            decryption_id,
+        })
+    } else {
+        sessions_manager::create_success_status_event(CompletedThresholdDecryptionEvent {
+            decryption_id,
+            re_encrypted_key,
+        })
+    };
+    let (fee_charged_ika, gas_fee_reimbursement_sui) = self
+        .sessions_manager
+        .complete_user_session<
+            ThresholdDecryptionRequestEvent,
+            CompletedThresholdDecryptionEvent,
+            RejectedThresholdDecryptionEvent,
+        >(self.current_epoch, session_sequence_number, status);
+    self.pricing_and_fee_manager.join_fee_charged_ika(fee_charged_ika);
+    gas_fee_reimbursement_sui
+}
+
+/// Requests the refresh of the secret key shares of a dWallet, without changing its public key.
+///
+/// The user adds an offset to its secret key share, and provides the negated offset encrypted to
+/// the network encryption key together with the refreshed share encrypted to its own encryption key.
+/// The dWallet stays `Active` until the network responds, so sessions requested before the refresh
+/// can still complete, but another refresh can't be requested until this one completes.
+///
+/// *IMPORTANT*: Once the refresh completes, the shares from before it are no longer valid:
+/// presigns, partial user signatures and encrypted user secret key shares created before the refresh
+/// are bound to them, and signing with them is rejected by the network.
+///
+/// ### Parameters
+/// - `dwallet_cap`: User's capability proving dWallet ownership
+/// - `encrypted_refresh_share`: The negated offset encrypted to the network encryption key, with proof
+/// - `encrypted_centralized_secret_share_and_proof`: The refreshed user share encrypted, with proof
+/// - `encryption_key_address`: Address of the encryption key the refreshed share is encrypted to
+/// - `session_identifier`: Unique session identifier
+/// - `payment_ika`: User's IKA payment for computation
+/// - `payment_sui`: User's SUI payment for gas reimbursement
+/// - `ctx`: Transaction context
+///
+/// ### Aborts
+/// - `EImportedKeyDWallet`: If called on imported key dWallet, as the importer knows the full key
+/// - `EDWalletUserSecretKeySharesAlreadyPublic`: If the user secret key share of the dWallet is public
+/// - `EMismatchCurve`: If encryption key curve doesn't match dWallet curve
+/// - `EDWalletInactive`: If the dWallet is not in active state
+/// - `EDWalletKeyRefreshInProgress`: If a key refresh of the dWallet is already in progress
+/// - `EEncryptedUserSecretKeySharesNotRegistered`: If not every encrypted user secret key share of the dWallet
+///   is registered, see `register_encrypted_user_secret_key_shares`
+/// - `ENetworkEncryptedKeyAlreadyRegistered`: If `encrypted_refresh_share` was already registered
+/// - Various payment-related errors if insufficient funds provided
+public(package) fun request_dwallet_key_refresh(
+    self: &mut DWalletCoordinatorInner,
+    dwallet_cap: &DWalletCap,
+    encrypted_refresh_share: vector<u8>,
+    encrypted_centralized_secret_share_and_proof: vector<u8>,
+    encryption_key_address: address,
+    session_identifier: SessionIdentifier,
+    payment_ika: &mut Coin<IKA>,
+    payment_sui: &mut Coin<SUI>,
+    ctx: &mut TxContext,
+) {
+    assert!(self.encryption_keys.contains(encryption_key_address), EEncryptionKeyNotExist);
+    let encryption_key = self.encryption_keys.borrow(encryption_key_address);
+    let encryption_key_curve = encryption_key.curve;
+    let encryption_key_id = encryption_key.id.to_inner();
+    let encryption_key = encryption_key.encryption_key;
+    let created_at_epoch = self.current_epoch;
+    let dwallet_id = dwallet_cap.dwallet_id;
+
+    let (dwallet, dwallet_public_output) = self.get_active_dwallet_and_public_output(dwallet_id);
+    let dwallet_network_encryption_key_id = dwallet.dwallet_network_encryption_key_id;
+    let curve = dwallet.curve;
+    assert!(!dwallet.is_imported_key_dwallet, EImportedKeyDWallet);
+    assert!(
+        dwallet.public_user_secret_key_share.is_none(),
+        EDWalletUserSecretKeySharesAlreadyPublic,
+    );
+    assert!(encryption_key_curve == curve, EMismatchCurve);
+    self.support_config.validate_curve(curve);
+    assert!(!self.is_dwallet_key_refresh_in_progress(dwallet_id), EDWalletKeyRefreshInProgress);
+    // Shares that aren't registered can't be invalidated once the refresh completes.
+    assert!(
+        self.registered_encrypted_user_secret_key_shares_count(dwallet_id)
+            == self.get_dwallet(dwallet_id).encrypted_user_secret_key_shares.length(),
+        EEncryptedUserSecretKeySharesNotRegistered,
     );
+    // The refresh share is encrypted to the network encryption key like a `NetworkEncryptedKey`,
+    // so it is registered to never be threshold decrypted, which would reveal the refresh offset.
+    self.register_network_encrypted_key_digest(&encrypted_refresh_share, dwallet_id, ctx);
 
-    let ImportedKeyMessageApproval {
+    let encrypted_user_share = EncryptedUserSecretKeyShare {
+        id: object::new(ctx),
+        created_at_epoch,
         dwallet_id,
-        signature_algorithm,
-        hash_scheme,
-        message,
-    } = message_approval;
+        encrypted_centralized_secret_share_and_proof,
+        encryption_key_id,
+        encryption_key_address,
+        source_encrypted_user_secret_key_share_id: option::none(),
+        state: EncryptedUserSecretKeyShareState::AwaitingNetworkVerification,
+    };
+    let encrypted_user_secret_key_share_id = object::id(&encrypted_user_share);
 
     let pricing_value = self
         .pricing_and_fee_manager
-        .get_pricing_value_for_protocol(
-            curve,
-            option::some(signature_algorithm),
-            SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG,
+        .get_pricing_value_for_protocol(curve, option::none(), DWALLET_KEY_REFRESH_PROTOCOL_FLAG);
+
+    let gas_fee_reimbursement_sui_for_system_calls = self
+        .sessions_manager
+        .initiate_user_session(
+            self.current_epoch,
+            session_identifier,
+            dwallet_network_encryption_key_id,
+            pricing_value,
+            payment_ika,
+            payment_sui,
+            DWalletKeyRefreshRequestEvent {
+                dwallet_id,
+                dwallet_public_output,
+                curve,
+                dwallet_network_encryption_key_id,
+                encrypted_refresh_share,
+                encrypted_user_secret_key_share_id,
+                encrypted_centralized_secret_share_and_proof,
+                encryption_key,
+                encryption_key_id,
+            },
+            ctx,
+        );
+    self
+        .pricing_and_fee_manager
+        .join_gas_fee_reimbursement_sui_system_call_balance(
+            gas_fee_reimbursement_sui_for_system_calls,
         );
 
-    // Emit signing events to finalize the signing process.
-    let is_imported_key_dwallet = self.validate_and_initiate_sign(
-        pricing_value,
-        payment_ika,
-        payment_sui,
-        session_identifier,
+    self.register_encrypted_user_secret_key_share(
         dwallet_id,
-        signature_algorithm,
-        hash_scheme,
-        message,
-        presign_cap,
-        message_centralized_signature,
-        true,
+        encrypted_user_secret_key_share_id,
         ctx,
     );
-    assert!(is_imported_key_dwallet, ENotImportedKeyDWallet);
-}
-
-/// Matches partial user signature with message approval to ensure they are consistent.
-/// This function can be called by the user to verify before calling
-/// the `request_sign_with_partial_user_signature` function.
-/// It is also called before requesting the Ika network to complete the signing.
-public(package) fun match_partial_user_signature_with_message_approval(
-    self: &DWalletCoordinatorInner,
-    partial_user_signature_cap: &VerifiedPartialUserSignatureCap,
-    message_approval: &MessageApproval,
-): bool {
-    let partial_signature = self
-        .partial_centralized_signed_messages
-        .borrow(partial_user_signature_cap.partial_centralized_signed_message_id);
-
-    partial_signature.dwallet_id == message_approval.dwallet_id &&
-    partial_signature.message == message_approval.message &&
-    partial_signature.signature_algorithm == message_approval.signature_algorithm &&
-    partial_signature.hash_scheme == message_approval.hash_scheme
+    if (!self.extra_fields.contains(DWalletKeyRefreshesInProgressKey {})) {
+        self.extra_fields.add(DWalletKeyRefreshesInProgressKey {}, table::new<ID, ID>(ctx));
+    };
+    let refreshes_in_progress: &mut Table<ID, ID> = self
+        .extra_fields
+        .borrow_mut(DWalletKeyRefreshesInProgressKey {});
+    refreshes_in_progress.add(dwallet_id, encrypted_user_secret_key_share_id);
+    self
+        .get_dwallet_mut(dwallet_id)
+        .encrypted_user_secret_key_shares
+        .add(encrypted_user_secret_key_share_id, encrypted_user_share);
 }
 
-/// Matches partial user signature with imported key message approval to ensure they are consistent.
-/// This function can be called by the user to verify before calling
-/// the `request_imported_key_sign_with_partial_user_signatures` function.
-public(package) fun match_partial_user_signature_with_imported_key_message_approval(
-    self: &DWalletCoordinatorInner,
-    partial_user_signature_cap: &VerifiedPartialUserSignatureCap,
-    message_approval: &ImportedKeyMessageApproval,
-): bool {
-    let partial_signature = self
-        .partial_centralized_signed_messages
-        .borrow(partial_user_signature_cap.partial_centralized_signed_message_id);
-
-    partial_signature.dwallet_id == message_approval.dwallet_id &&
-    partial_signature.message == message_approval.message &&
-    partial_signature.signature_algorithm == message_approval.signature_algorithm &&
-    partial_signature.hash_scheme == message_approval.hash_scheme
+/// Whether a key refresh of `dwallet_id` was requested and the network has not yet responded to it,
+/// see `DWalletKeyRefreshesInProgressKey`.
+fun is_dwallet_key_refresh_in_progress(self: &DWalletCoordinatorInner, dwallet_id: ID): bool {
+    if (!self.extra_fields.contains(DWalletKeyRefreshesInProgressKey {})) {
+        return false
+    };
+    let refreshes_in_progress: &Table<ID, ID> = self
+        .extra_fields
+        .borrow(DWalletKeyRefreshesInProgressKey {});
+    refreshes_in_progress.contains(dwallet_id)
 }
 
-/// Called by the Ika network to respond to (and complete) a Sign protocol request.
+/// Called by the Ika network to respond to (and complete) a dWallet key refresh request.
 ///
-/// Sets the `SignSession` to `Completed` and stores in it the `signature`.
-/// Also emits an event with the `signature`.
-public(package) fun respond_sign(
+/// On success, updates the dWallet to the refreshed `public_output` and marks the encrypted
+/// refreshed user share as verified; on rejection, the dWallet keeps the public output from before the refresh.
+/// Either way, the key refresh is no longer in progress.
+public(package) fun respond_dwallet_key_refresh(
     self: &mut DWalletCoordinatorInner,
     dwallet_id: ID,
-    sign_id: ID,
-    signature: vector<u8>,
-    is_future_sign: bool,
+    encrypted_user_secret_key_share_id: ID,
+    public_output: vector<u8>,
     rejected: bool,
     session_sequence_number: u64,
 ): Balance<SUI> {
     let status = if (rejected) {
-        sessions_manager::create_rejected_status_event(RejectedSignEvent {
-            sign_id,
-            is_future_sign,
+        sessions_manager::create_rejected_status_event(RejectedDWalletKeyRefreshEvent {
+            dwallet_id,
+            encrypted_user_secret_key_share_id,
         })
     } else {
-        sessions_manager::create_success_status_event(CompletedSignEvent {
-            sign_id,
-            signature,
-            is_future_sign,
+        sessions_manager::create_success_status_event(CompletedDWalletKeyRefreshEvent {
+            dwallet_id,
+            encrypted_user_secret_key_share_id,
+            public_output,
         })
     };
     let (fee_charged_ika, gas_fee_reimbursement_sui) = self
         .sessions_manager
-        .complete_user_session<SignRequestEvent, CompletedSignEvent, RejectedSignEvent>(
-            self.current_epoch,
-            session_sequence_number,
-            status,
-        );
+        .complete_user_session<
+            DWalletKeyRefreshRequestEvent,
+            CompletedDWalletKeyRefreshEvent,
+            RejectedDWalletKeyRefreshEvent,
+        >(self.current_epoch, session_sequence_number, status);
     self.pricing_and_fee_manager.join_fee_charged_ika(fee_charged_ika);
-    let (dwallet, _) = self.get_active_dwallet_and_public_output_mut(dwallet_id);
 
-    let sign = dwallet.sign_sessions.borrow_mut(sign_id);
+    let refreshes_in_progress: &mut Table<ID, ID> = self
+        .extra_fields
+        .borrow_mut(DWalletKeyRefreshesInProgressKey {});
+    refreshes_in_progress.remove(dwallet_id);
 
-    sign.state =
-        match (sign.state) {
-            SignState::Requested => {
+    let dwallet = self.get_dwallet_mut(dwallet_id);
+    if (!rejected) {
+        dwallet.state =
+            DWalletState::Active {
+                public_output,
+            };
+    };
+
+    let encrypted_user_secret_key_share = dwallet
+        .encrypted_user_secret_key_shares
+        .borrow_mut(encrypted_user_secret_key_share_id);
+    encrypted_user_secret_key_share.state =
+        match (encrypted_user_secret_key_share.state) {
+            EncryptedUserSecretKeyShareState::AwaitingNetworkVerification => {
                 if (rejected) {
-                    SignState::NetworkRejected
+                    EncryptedUserSecretKeyShareState::NetworkVerificationRejected
                 } else {
-                    SignState::Completed { signature }
+                    EncryptedUserSecretKeyShareState::NetworkVerificationCompleted
                 }
             },
-            _ => abort ESignWrongState,
+            _ => abort EWrongState,
         };
+    if (!rejected) {
+        self.invalidate_refreshed_encrypted_user_secret_key_shares(
+            dwallet_id,
+            encrypted_user_secret_key_share_id,
+        );
+    };
     gas_fee_reimbursement_sui
 }
 
+/// Records `encrypted_user_secret_key_share_id` in the registry of the encrypted user secret key shares
+/// of `dwallet_id`, see `EncryptedUserSecretKeySharesRegistryKey`.
+fun register_encrypted_user_secret_key_share(
+    self: &mut DWalletCoordinatorInner,
+    dwallet_id: ID,
+    encrypted_user_secret_key_share_id: ID,
+    ctx: &mut TxContext,
+) {
+    if (!self.extra_fields.contains(EncryptedUserSecretKeySharesRegistryKey {})) {
+        self
+            .extra_fields
+            .add(EncryptedUserSecretKeySharesRegistryKey {}, table::new<ID, vector<ID>>(ctx));
+    };
+    let registry: &mut Table<ID, vector<ID>> = self
+        .extra_fields
+        .borrow_mut(EncryptedUserSecretKeySharesRegistryKey {});
+    if (!registry.contains(dwallet_id)) {
+        registry.add(dwallet_id, vector[]);
+    };
+    registry.borrow_mut(dwallet_id).push_back(encrypted_user_secret_key_share_id);
+}
+
+/// Registers encrypted user secret key shares of a dWallet created before the registry of
+/// the encrypted user secret key shares existed, see `EncryptedUserSecretKeySharesRegistryKey`.
+///
+/// ### Parameters
+/// - `dwallet_cap`: User's capability proving dWallet ownership
+/// - `encrypted_user_secret_key_share_ids`: IDs of the unregistered encrypted user secret key shares of the dWallet
+/// - `ctx`: Transaction context
+///
+/// ### Aborts
+/// - `EDWalletNotExists`: If the dWallet doesn't exist
+/// - `EEncryptedUserSecretKeyShareNotExist`: If a share doesn't belong to the dWallet
+/// - `EEncryptedUserSecretKeyShareAlreadyRegistered`: If a share is already registered
+public(package) fun register_encrypted_user_secret_key_shares(
+    self: &mut DWalletCoordinatorInner,
+    dwallet_cap: &DWalletCap,
+    encrypted_user_secret_key_share_ids: vector<ID>,
+    ctx: &mut TxContext,
+) {
+    let dwallet_id = dwallet_cap.dwallet_id;
+    encrypted_user_secret_key_share_ids.do!(|encrypted_user_secret_key_share_id| {
+        assert!(
+            self
+                .get_dwallet(dwallet_id)
+                .encrypted_user_secret_key_shares
+                .contains(encrypted_user_secret_key_share_id),
+            EEncryptedUserSecretKeyShareNotExist,
+        );
+        assert!(
+            !self.is_encrypted_user_secret_key_share_registered(
+                dwallet_id,
+                encrypted_user_secret_key_share_id,
+            ),
+            EEncryptedUserSecretKeyShareAlreadyRegistered,
+        );
+        self.register_encrypted_user_secret_key_share(
+            dwallet_id,
+            encrypted_user_secret_key_share_id,
+            ctx,
+        );
+    });
+}
+
+/// Whether `encrypted_user_secret_key_share_id` is in the registry of
+/// the encrypted user secret key shares of `dwallet_id`.
+fun is_encrypted_user_secret_key_share_registered(
+    self: &DWalletCoordinatorInner,
+    dwallet_id: ID,
+    encrypted_user_secret_key_share_id: ID,
+): bool {
+    if (!self.extra_fields.contains(EncryptedUserSecretKeySharesRegistryKey {})) {
+        return false
+    };
+    let registry: &Table<ID, vector<ID>> = self
+        .extra_fields
+        .borrow(EncryptedUserSecretKeySharesRegistryKey {});
+    registry.contains(dwallet_id)
+        && registry.borrow(dwallet_id).contains(&encrypted_user_secret_key_share_id)
+}
+
+/// The number of encrypted user secret key shares of `dwallet_id` in the registry of
+/// the encrypted user secret key shares.
+fun registered_encrypted_user_secret_key_shares_count(
+    self: &DWalletCoordinatorInner,
+    dwallet_id: ID,
+): u64 {
+    if (!self.extra_fields.contains(EncryptedUserSecretKeySharesRegistryKey {})) {
+        return 0
+    };
+    let registry: &Table<ID, vector<ID>> = self
+        .extra_fields
+        .borrow(EncryptedUserSecretKeySharesRegistryKey {});
+    if (!registry.contains(dwallet_id)) {
+        return 0
+    };
+    registry.borrow(dwallet_id).length()
+}
+
+/// Marks every registered encrypted user secret key share of `dwallet_id` other than
+/// `refreshed_encrypted_user_secret_key_share_id` as `NetworkVerificationRejected`,
+/// as they hold the user secret key share from before the refresh, which no longer matches the dWallet.
+///
+/// The invalidated shares stay in the registry, as they stay in the dWallet.
+fun invalidate_refreshed_encrypted_user_secret_key_shares(
+    self: &mut DWalletCoordinatorInner,
+    dwallet_id: ID,
+    refreshed_encrypted_user_secret_key_share_id: ID,
+) {
+    if (!self.extra_fields.contains(EncryptedUserSecretKeySharesRegistryKey {})) {
+        return
+    };
+    let registry: &mut Table<ID, vector<ID>> = self
+        .extra_fields
+        .borrow_mut(EncryptedUserSecretKeySharesRegistryKey {});
+    if (!registry.contains(dwallet_id)) {
+        return
+    };
+    let encrypted_user_secret_key_share_ids = *registry.borrow(dwallet_id);
+
+    let dwallet = self.get_dwallet_mut(dwallet_id);
+    encrypted_user_secret_key_share_ids.do!(|encrypted_user_secret_key_share_id| {
+        if (
+            encrypted_user_secret_key_share_id != refreshed_encrypted_user_secret_key_share_id
+            && dwallet.encrypted_user_secret_key_shares.contains(encrypted_user_secret_key_share_id)
+        ) {
+            dwallet
+                .encrypted_user_secret_key_shares
+                .borrow_mut(encrypted_user_secret_key_share_id)
+                .state = EncryptedUserSecretKeyShareState::NetworkVerificationRejected;
+        };
+    });
+}
+
 /// Processes a checkpoint message that has been signed by a validator quorum.
 ///
 /// Verifies the BLS multi-signature from the active validator committee before
@@ -4651,6 +6287,57 @@ fun process_checkpoint_message(
                     epoch: self.current_epoch,
                 });
             },
+            RESPOND_DWALLET_BATCH_SIGN_MESSAGE_TYPE => {
+                let dwallet_id = object::id_from_bytes(bcs_body.peel_vec_u8());
+                let batch_sign_id = object::id_from_bytes(bcs_body.peel_vec_u8());
+                let sign_ids = bcs_body.peel_vec_vec_u8().map!(|sign_id| object::id_from_bytes(sign_id));
+                let signatures = bcs_body.peel_vec_vec_u8();
+                let rejected = bcs_body.peel_bool();
+                let session_sequence_number = bcs_body.peel_u64();
+                let gas_fee_reimbursement_sui = self.respond_batch_sign(
+                    dwallet_id,
+                    batch_sign_id,
+                    sign_ids,
+                    signatures,
+                    rejected,
+                    session_sequence_number,
+                );
+                total_gas_fee_reimbursement_sui.join(gas_fee_reimbursement_sui);
+            },
+            RESPOND_THRESHOLD_DECRYPTION_MESSAGE_TYPE => {
+                let decryption_id = object::id_from_bytes(bcs_body.peel_vec_u8());
+                let re_encrypted_key = bcs_body.peel_vec_u8();
+                let rejected = bcs_body.peel_bool();
+                let session_sequence_number = bcs_body.peel_u64();
+                let gas_fee_reimbursement_sui = self.respond_threshold_decryption(
+                    decryption_id,
+                    re_encrypted_key,
+                    rejected,
+                    session_sequence_number,
+                );
+                total_gas_fee_reimbursement_sui.join(gas_fee_reimbursement_sui);
+            },
+            RESPOND_DWALLET_KEY_REFRESH_MESSAGE_TYPE => {
+                let dwallet_id = object::id_from_bytes(bcs_body.peel_vec_u8());
+                let encrypted_user_secret_key_share_id = object::id_from_bytes(
+                    bcs_body.peel_vec_u8(),
+                );
+                let public_output = bcs_body.peel_vec_u8();
+                let rejected = bcs_body.peel_bool();
+                let session_sequence_number = bcs_body.peel_u64();
+                let gas_fee_reimbursement_sui = self.respond_dwallet_key_refresh(
+                    dwallet_id,
+                    encrypted_user_secret_key_share_id,
+                    public_output,
+                    rejected,
+                    session_sequence_number,
+                );
+                total_gas_fee_reimbursement_sui.join(gas_fee_reimbursement_sui);
+            },
+            GARBAGE_COLLECT_FUTURE_SIGNS_MESSAGE_TYPE => {
+                let epoch = bcs_body.peel_u64();
+                self.garbage_collect_future_signs(epoch);
+            },
             _ => {},
         };
         i = i + 1;
@@ -4775,6 +6462,10 @@ fun verify_pricing_exists_for_all_protocols(
             is_missing_pricing || default_pricing.try_get_pricing_value(*curve, option::none(), MAKE_DWALLET_USER_SECRET_KEY_SHARE_PUBLIC_PROTOCOL_FLAG).is_none();
         is_missing_pricing =
             is_missing_pricing || default_pricing.try_get_pricing_value(*curve, option::none(), IMPORTED_KEY_DWALLET_VERIFICATION_PROTOCOL_FLAG).is_none();
+        is_missing_pricing =
+            is_missing_pricing || default_pricing.try_get_pricing_value(*curve, option::none(), THRESHOLD_DECRYPTION_PROTOCOL_FLAG).is_none();
+        is_missing_pricing =
+            is_missing_pricing || default_pricing.try_get_pricing_value(*curve, option::none(), DWALLET_KEY_REFRESH_PROTOCOL_FLAG).is_none();
         // Add here pricing validation for new protocols per curve.
         signature_algorithms.do_ref!(|signature_algorithm| {
             is_missing_pricing =
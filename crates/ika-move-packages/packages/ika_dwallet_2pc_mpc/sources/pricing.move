@@ -138,6 +138,18 @@ public fun gas_fee_reimbursement_sui_for_system_calls(self: &PricingInfoValue):
     self.gas_fee_reimbursement_sui_for_system_calls
 }
 
+/// Returns the pricing of `count` operations performed in a single session.
+///
+/// The fee and the gas fee reimbursement are charged per operation, while the gas fee
+/// reimbursement for system calls is charged once, as the session is responded to in a single call.
+public(package) fun scale(self: &PricingInfoValue, count: u64): PricingInfoValue {
+    PricingInfoValue {
+        fee_ika: self.fee_ika * count,
+        gas_fee_reimbursement_sui: self.gas_fee_reimbursement_sui * count,
+        gas_fee_reimbursement_sui_for_system_calls: self.gas_fee_reimbursement_sui_for_system_calls,
+    }
+}
+
 public(package) fun new_pricing_calculation(
     bls_committee: BlsCommittee,
     default_pricing: PricingInfo,
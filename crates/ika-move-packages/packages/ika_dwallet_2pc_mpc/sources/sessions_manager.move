@@ -389,6 +389,24 @@ public(package) fun complete_user_session<
     (fee_charged_ika, gas_fee_reimbursement_sui)
 }
 
+/// Checks whether the event of a user-initiated session is of type `E`,
+/// for requests that can start a session with one of several event types.
+///
+/// ### Parameters
+/// - `self`: Reference to the session manager
+/// - `session_sequence_number`: The sequence number of the session
+///
+/// ### Returns
+/// Whether the session was initiated with an event of type `E`
+public(package) fun is_user_session_event_of_type<E: copy + drop + store>(
+    self: &SessionsManager,
+    session_sequence_number: u64,
+): bool {
+    let keeper = &self.user_sessions_keeper;
+    let session = keeper.sessions.borrow(session_sequence_number);
+    keeper.session_events.contains_with_type<ID, DWalletSessionEvent<E>>(session.id.to_inner())
+}
+
 /// Initiates a system-initiated session.
 ///
 /// ### Parameters
@@ -56,6 +56,7 @@ const SET_REWARD_SLASHING_RATE_MESSAGE_TYPE: u32 = 9;
 const END_OF_PUBLISH_MESSAGE_TYPE: u32 = 10;
 const SET_APPROVED_UPGRADE_MESSAGE_TYPE: u32 = 11;
 const SetOrRemoveWitnessApprovingAdvanceEpochMessageType: u32 = 12;
+const SET_NEXT_EPOCH_COMMITTEE_MESSAGE_TYPE: u32 = 13;
 
 // === Errors ===
 
@@ -956,6 +957,18 @@ public(package) fun process_checkpoint_message(
                 let remove = bcs_body.peel_bool();
                 self.set_or_remove_witness_approving_advance_epoch(witness_type, remove);
             },
+            SET_NEXT_EPOCH_COMMITTEE_MESSAGE_TYPE => {
+                // The next epoch committee is certified for off-chain verifiers, the system
+                // object already holds it, so the message is only skipped over.
+                bcs_body.peel_u64();
+                let voting_rights_len = bcs_body.peel_vec_length();
+                voting_rights_len.do!(|_| {
+                    bcs_body.peel_vec_u8();
+                    bcs_body.peel_u64();
+                });
+                bcs_body.peel_u64();
+                bcs_body.peel_u64();
+            },
             _ => {},
         };
         i = i + 1;
@@ -0,0 +1,82 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! The key custody process of an Ika validator.
+//!
+//! Holds the validator's root seed, and with it the class groups decryption key and the
+//! network decryption key shares, and performs the MPC computations of an `ika-node`
+//! configured with a matching `key-custody-config` over a local Unix socket,
+//! so it is only supported on Unix.
+
+#[cfg(unix)]
+use clap::Parser;
+#[cfg(unix)]
+use dwallet_rng::RootSeed;
+#[cfg(unix)]
+use ika_core::dwallet_mpc::key_custody::KeyCustodyServer;
+#[cfg(unix)]
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::sync::Arc;
+#[cfg(unix)]
+use tracing::info;
+
+// Define the `GIT_REVISION` and `VERSION` consts
+bin_version::bin_version!();
+
+#[cfg(unix)]
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+#[clap(name = env!("CARGO_BIN_NAME"))]
+#[clap(version = VERSION)]
+struct Args {
+    /// The file holding the validator's root seed, encoded in Base64.
+    #[clap(long)]
+    root_seed_path: PathBuf,
+
+    /// The Unix socket to listen on, matching the `socket-path` of the node's
+    /// `key-custody-config`.
+    #[clap(long)]
+    socket_path: PathBuf,
+
+    /// The directory of the encrypted cache of the network key shares.
+    /// If not set, the shares are decrypted again whenever the node starts a new epoch.
+    #[clap(long)]
+    network_key_cache_path: Option<PathBuf>,
+
+    #[clap(long, default_value = "127.0.0.1:9188")]
+    metrics_address: SocketAddr,
+}
+
+#[cfg(not(unix))]
+fn main() -> anyhow::Result<()> {
+    anyhow::bail!(
+        "ika-key-custody {VERSION} listens on a Unix socket, and is only supported on Unix"
+    )
+}
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let registry_service = mysten_metrics::start_prometheus_server(args.metrics_address);
+    let prometheus_registry = registry_service.default_registry();
+    let (_guard, _filter_handle) = telemetry_subscribers::TelemetryConfig::new()
+        .with_env()
+        .with_prom_registry(&prometheus_registry)
+        .init();
+
+    info!("Ika key custody version: {VERSION}");
+
+    let root_seed = RootSeed::from_file(&args.root_seed_path)?;
+    let server = Arc::new(KeyCustodyServer::new(
+        root_seed,
+        args.network_key_cache_path,
+        &prometheus_registry,
+    ));
+
+    server.run(&args.socket_path).await
+}
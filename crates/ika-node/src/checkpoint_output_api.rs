@@ -0,0 +1,133 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+};
+use ika_core::dwallet_checkpoints::output_index::{DWalletCheckpointOutputIndex, IndexedOutput};
+use ika_types::messages_dwallet_checkpoint::DWalletCheckpointSequenceNumber;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use tracing::info;
+
+// Example commands:
+//
+// View all the outputs of a dWallet, or only its sign outputs:
+//
+//   $ curl 'http://127.0.0.1:9187/dwallets/0x1234.../outputs'
+//   $ curl 'http://127.0.0.1:9187/dwallets/0x1234.../outputs?kind=RespondDWalletSign'
+//
+// View the outputs of the session with sequence number 42:
+//
+//   $ curl 'http://127.0.0.1:9187/sessions/42'
+//
+// View the network DKG and reconfiguration outputs of a network encryption key:
+//
+//   $ curl 'http://127.0.0.1:9187/network-keys/0x1234.../outputs'
+//
+// View the highest indexed dWallet checkpoint:
+//
+//   $ curl 'http://127.0.0.1:9187/status'
+//
+
+const DWALLET_OUTPUTS_ROUTE: &str = "/dwallets/{dwallet_id}/outputs";
+const SESSION_OUTPUTS_ROUTE: &str = "/sessions/{session_sequence_number}";
+const NETWORK_KEY_OUTPUTS_ROUTE: &str = "/network-keys/{network_key_id}/outputs";
+const STATUS_ROUTE: &str = "/status";
+
+#[derive(Deserialize)]
+struct DWalletOutputsQuery {
+    kind: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Status {
+    highest_indexed_checkpoint: Option<DWalletCheckpointSequenceNumber>,
+}
+
+type ApiResult<T> = Result<Json<T>, (StatusCode, String)>;
+
+pub async fn run_checkpoint_output_api_server(
+    index: Arc<DWalletCheckpointOutputIndex>,
+    socket_address: SocketAddr,
+) {
+    let app = Router::new()
+        .route(DWALLET_OUTPUTS_ROUTE, get(dwallet_outputs))
+        .route(SESSION_OUTPUTS_ROUTE, get(session_outputs))
+        .route(NETWORK_KEY_OUTPUTS_ROUTE, get(network_key_outputs))
+        .route(STATUS_ROUTE, get(status))
+        .with_state(index);
+
+    info!(
+        address =% socket_address,
+        "starting checkpoint output API server"
+    );
+
+    let listener = tokio::net::TcpListener::bind(&socket_address)
+        .await
+        .unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
+}
+
+async fn dwallet_outputs(
+    State(index): State<Arc<DWalletCheckpointOutputIndex>>,
+    Path(dwallet_id): Path<String>,
+    Query(query): Query<DWalletOutputsQuery>,
+) -> ApiResult<Vec<IndexedOutput>> {
+    let dwallet_id = parse_object_id(&dwallet_id)?;
+    index
+        .get_dwallet_outputs(&dwallet_id.to_vec(), query.kind.as_deref())
+        .map(Json)
+        .map_err(internal_error)
+}
+
+async fn session_outputs(
+    State(index): State<Arc<DWalletCheckpointOutputIndex>>,
+    Path(session_sequence_number): Path<u64>,
+) -> ApiResult<Vec<IndexedOutput>> {
+    index
+        .get_session_outputs(session_sequence_number)
+        .map(Json)
+        .map_err(internal_error)
+}
+
+async fn network_key_outputs(
+    State(index): State<Arc<DWalletCheckpointOutputIndex>>,
+    Path(network_key_id): Path<String>,
+) -> ApiResult<Vec<IndexedOutput>> {
+    let network_key_id = parse_object_id(&network_key_id)?;
+    index
+        .get_network_key_outputs(&network_key_id.to_vec())
+        .map(Json)
+        .map_err(internal_error)
+}
+
+async fn status(State(index): State<Arc<DWalletCheckpointOutputIndex>>) -> ApiResult<Status> {
+    index
+        .get_highest_indexed_checkpoint()
+        .map(|highest_indexed_checkpoint| {
+            Json(Status {
+                highest_indexed_checkpoint,
+            })
+        })
+        .map_err(internal_error)
+}
+
+fn parse_object_id(id: &str) -> Result<ObjectID, (StatusCode, String)> {
+    ObjectID::from_str(id).map_err(|err| (StatusCode::BAD_REQUEST, format!("invalid ID: {err}")))
+}
+
+fn internal_error(err: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+}
@@ -34,9 +34,10 @@ use tracing::info;
 use tracing::{debug, warn};
 
 pub use handle::IkaNodeHandle;
-use ika_archival::reader::ArchiveReaderBalancer;
+use ika_archival::bootstrap::bootstrap_from_archive;
+use ika_archival::reader::{ArchiveReader, ArchiveReaderBalancer, ArchiveReaderMetrics};
 use ika_archival::writer::ArchiveWriter;
-use ika_config::node::RunWithRange;
+use ika_config::node::{ArchiveReaderConfig, PricingRecommendationConfig, RunWithRange};
 use ika_config::node_config_metrics::NodeConfigMetrics;
 use ika_config::object_storage_config::{ObjectStoreConfig, ObjectStoreType};
 use ika_config::{ConsensusConfig, NodeConfig};
@@ -54,6 +55,7 @@ use ika_core::consensus_validator::{IkaTxValidator, IkaTxValidatorMetrics};
 use ika_core::dwallet_checkpoints::{
     DWalletCheckpointMetrics, DWalletCheckpointService, DWalletCheckpointStore,
     SendDWalletCheckpointToStateSync, SubmitDWalletCheckpointToConsensus,
+    output_index::DWalletCheckpointOutputIndex,
 };
 use ika_core::epoch::committee_store::CommitteeStore;
 use ika_core::epoch::consensus_store_pruner::ConsensusStorePruner;
@@ -85,6 +87,7 @@ use typed_store::rocks::default_db_options;
 use crate::metrics::IkaNodeMetrics;
 
 pub mod admin;
+pub mod checkpoint_output_api;
 mod handle;
 pub mod metrics;
 
@@ -166,6 +169,7 @@ use ika_core::authority::authority_perpetual_tables::AuthorityPerpetualTables;
 use ika_core::consensus_handler::ConsensusHandlerInitializer;
 use ika_core::dwallet_mpc::dwallet_mpc_metrics::DWalletMPCMetrics;
 use ika_core::dwallet_mpc::dwallet_mpc_service::DWalletMPCService;
+use ika_core::dwallet_mpc::pricing_recommendation;
 use ika_core::sui_connector::SuiConnectorService;
 use ika_core::sui_connector::end_of_publish_sender::EndOfPublishSender;
 use ika_core::sui_connector::metrics::SuiConnectorMetrics;
@@ -175,7 +179,7 @@ use ika_core::system_checkpoints::{
     SystemCheckpointService, SystemCheckpointStore,
 };
 use ika_sui_client::metrics::SuiClientMetrics;
-use ika_sui_client::{SuiClient, SuiConnectorClient};
+use ika_sui_client::SuiConnectorClient;
 use ika_types::messages_dwallet_mpc::{DWalletNetworkEncryptionKeyData, IkaNetworkConfig};
 #[cfg(msim)]
 pub use simulator::set_jwk_injector;
@@ -266,7 +270,7 @@ impl IkaNode {
         let sui_client_metrics = SuiClientMetrics::new(&registry_service.default_registry());
 
         let sui_client = Arc::new(
-            SuiClient::new(
+            SuiConnectorClient::new(
                 &config.sui_connector_config.sui_rpc_url,
                 sui_client_metrics,
                 config.sui_connector_config.ika_package_id,
@@ -376,6 +380,25 @@ impl IkaNode {
             system_checkpoint_store.clone(),
         );
 
+        // The archive readers share a single set of metrics, as they are all registered
+        // on the same registry.
+        let archive_reader_metrics = ArchiveReaderMetrics::new(&prometheus_registry);
+        if let Some(archive_reader_config) = config.archive_bootstrap_reader_config() {
+            let up_to_epoch = config
+                .archive_bootstrap_config
+                .as_ref()
+                .and_then(|archive_bootstrap_config| archive_bootstrap_config.up_to_epoch)
+                .unwrap_or(committee.epoch);
+            Self::bootstrap_stores_from_archive(
+                archive_reader_config,
+                state_sync_store.clone(),
+                &committee,
+                up_to_epoch,
+                &archive_reader_metrics,
+            )
+            .await?;
+        }
+
         info!("creating archive reader");
         // Create network
         // TODO only configure validators as seed/preferred peers for validators and not for
@@ -383,8 +406,10 @@ impl IkaNode {
 
         let authority_name = config.protocol_public_key();
 
-        let archive_readers =
-            ArchiveReaderBalancer::new(config.archive_reader_config(), &prometheus_registry)?;
+        let archive_readers = ArchiveReaderBalancer::new_with_metrics(
+            config.archive_reader_config(),
+            &archive_reader_metrics,
+        )?;
         let (trusted_peer_change_tx, trusted_peer_change_rx) = watch::channel(Default::default());
         let P2pComponents {
             p2p_network,
@@ -543,6 +568,28 @@ impl IkaNode {
             shutdown_channel_tx: shutdown_channel,
         };
 
+        if let (Some(pricing_recommendation_config), Some(recommended_pricing_path)) = (
+            config.pricing_recommendation_config.clone(),
+            config.recommended_pricing_path(),
+        ) {
+            spawn_monitored_task!(Self::run_pricing_recommendation(
+                pricing_recommendation_config,
+                recommended_pricing_path,
+                dwallet_mpc_metrics.clone(),
+                sui_client.clone(),
+            ));
+        }
+
+        if let Some(checkpoint_output_index_config) = &config.checkpoint_output_index_config {
+            let index =
+                DWalletCheckpointOutputIndex::new(&config.checkpoint_output_index_path());
+            spawn_monitored_task!(index.clone().run(node.dwallet_checkpoint_store.clone()));
+            spawn_monitored_task!(checkpoint_output_api::run_checkpoint_output_api_server(
+                index,
+                checkpoint_output_index_config.listen_address,
+            ));
+        }
+
         info!("IkaNode started!");
         let node = Arc::new(node);
         let node_copy = node.clone();
@@ -596,6 +643,84 @@ impl IkaNode {
             .set_override_protocol_upgrade_buffer_stake(epoch, buffer_stake_bps)
     }
 
+    /// Restores the checkpoint and committee stores from an archive, verifying every
+    /// certificate against its epoch's committee, so that p2p state sync only has to fetch
+    /// what was produced after the archive was written.
+    async fn bootstrap_stores_from_archive(
+        archive_reader_config: ArchiveReaderConfig,
+        store: RocksDbStore,
+        committee: &Committee,
+        up_to_epoch: EpochId,
+        archive_reader_metrics: &Arc<ArchiveReaderMetrics>,
+    ) -> Result<()> {
+        info!(
+            ?up_to_epoch,
+            archive=?archive_reader_config.remote_store_config,
+            "bootstrapping from archive"
+        );
+        let archive_reader = ArchiveReader::new(archive_reader_config, archive_reader_metrics)?;
+        let summary =
+            bootstrap_from_archive(&archive_reader, store, committee, up_to_epoch).await?;
+        info!(
+            restored_epochs=?summary.restored_epochs,
+            dwallet_checkpoints_restored=?summary.dwallet_checkpoints_restored,
+            system_checkpoints_restored=?summary.system_checkpoints_restored,
+            "finished bootstrapping from archive"
+        );
+        Ok(())
+    }
+
+    /// Periodically derives a recommended pricing vote from the MPC computation metrics
+    /// of this node, and writes it to `recommended_pricing_path`.
+    /// The recommendation is only written, submitting the vote is left to the operator.
+    async fn run_pricing_recommendation(
+        config: PricingRecommendationConfig,
+        recommended_pricing_path: PathBuf,
+        dwallet_mpc_metrics: Arc<DWalletMPCMetrics>,
+        sui_client: Arc<SuiConnectorClient>,
+    ) {
+        let mut interval = tokio::time::interval(config.interval());
+        // The first tick completes immediately, and there is nothing measured yet.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+
+            let observed_costs =
+                pricing_recommendation::observed_costs(&dwallet_mpc_metrics.round_costs());
+            let current_pricing = sui_client.get_pricing_info().await;
+            let recommended_pricing = pricing_recommendation::recommend_pricing(
+                &current_pricing,
+                &observed_costs,
+                &config,
+            );
+            for diff in pricing_recommendation::diff_pricing(&current_pricing, &recommended_pricing)
+            {
+                info!(
+                    curve=?diff.key.curve,
+                    signature_algorithm=?diff.key.signature_algorithm,
+                    protocol=?diff.key.protocol,
+                    current_fee_ika=?diff.current_fee_ika,
+                    recommended_fee_ika=?diff.recommended_fee_ika,
+                    "recommended pricing differs from the current pricing"
+                );
+            }
+
+            let result = serde_yaml::to_string(&recommended_pricing)
+                .map_err(anyhow::Error::from)
+                .and_then(|recommended_pricing| {
+                    std::fs::write(&recommended_pricing_path, recommended_pricing)
+                        .map_err(anyhow::Error::from)
+                });
+            if let Err(err) = result {
+                warn!(
+                    error=?err,
+                    path=?recommended_pricing_path,
+                    "failed to write the recommended pricing"
+                );
+            }
+        }
+    }
+
     async fn start_state_archival(
         config: &NodeConfig,
         prometheus_registry: &Registry,
@@ -895,6 +1020,7 @@ impl IkaNode {
             watch::channel(());
         if let Err(e) =
             DWalletMPCService::verify_validator_keys(epoch_store.epoch_start_state(), config)
+                .await
         {
             panic!("Failed to verify validator keys: {}", e);
         };
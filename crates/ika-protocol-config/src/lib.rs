@@ -16,7 +16,7 @@ use tracing::{info, warn};
 
 /// The minimum and maximum protocol versions supported by this build.
 const MIN_PROTOCOL_VERSION: u64 = 1;
-const MAX_PROTOCOL_VERSION: u64 = 1;
+const MAX_PROTOCOL_VERSION: u64 = 2;
 
 // Record history of protocol version allocations here:
 //
@@ -133,6 +133,20 @@ struct FeatureFlags {
     // If true, enforces checkpoint timestamps are non-decreasing.
     #[serde(skip_serializing_if = "is_false")]
     enforce_checkpoint_timestamp_monotonicity: bool,
+
+    // If true, the delayed MPC rounds wait a number of consensus rounds estimated from the
+    // observed message arrivals, instead of the configured delays.
+    #[serde(skip_serializing_if = "is_false")]
+    adaptive_mpc_round_delays: bool,
+
+    // If true, the last system checkpoint of an epoch certifies the committee of the next epoch.
+    #[serde(skip_serializing_if = "is_false")]
+    certify_next_epoch_committee: bool,
+
+    // If true, the last dWallet checkpoint of an epoch removes the partial user signatures
+    // that can no longer be completed.
+    #[serde(skip_serializing_if = "is_false")]
+    garbage_collect_future_signs: bool,
 }
 
 #[allow(unused)]
@@ -248,6 +262,21 @@ pub struct ProtocolConfig {
     consensus_gc_depth: Option<u32>,
     decryption_key_reconfiguration_third_round_delay: Option<u64>,
     network_dkg_third_round_delay: Option<u64>,
+
+    /// The bounds of the delay of MPC rounds, in consensus rounds, when it is adaptive.
+    min_adaptive_mpc_round_delay: Option<u64>,
+    max_adaptive_mpc_round_delay: Option<u64>,
+    /// The number of most recent message arrival observations adaptive delays are estimated from.
+    adaptive_mpc_round_delay_observations_window: Option<u64>,
+
+    /// The number of consensus rounds after the first message of an MPC session,
+    /// after which it is aborted and rejected if it did not complete, per session type.
+    /// Unset means sessions of that type never time out.
+    dwallet_dkg_session_timeout_consensus_rounds: Option<u64>,
+    presign_session_timeout_consensus_rounds: Option<u64>,
+    sign_session_timeout_consensus_rounds: Option<u64>,
+    network_dkg_session_timeout_consensus_rounds: Option<u64>,
+    network_key_reconfiguration_session_timeout_consensus_rounds: Option<u64>,
 }
 
 // feature flags
@@ -296,6 +325,18 @@ impl ProtocolConfig {
         self.feature_flags.enforce_checkpoint_timestamp_monotonicity
     }
 
+    pub fn adaptive_mpc_round_delays(&self) -> bool {
+        self.feature_flags.adaptive_mpc_round_delays
+    }
+
+    pub fn certify_next_epoch_committee(&self) -> bool {
+        self.feature_flags.certify_next_epoch_committee
+    }
+
+    pub fn garbage_collect_future_signs(&self) -> bool {
+        self.feature_flags.garbage_collect_future_signs
+    }
+
     pub fn consensus_zstd_compression(&self) -> bool {
         self.feature_flags.consensus_zstd_compression
     }
@@ -463,6 +504,14 @@ impl ProtocolConfig {
             // The delay is measured in consensus rounds.
             decryption_key_reconfiguration_third_round_delay: Some(10),
             network_dkg_third_round_delay: Some(10),
+            min_adaptive_mpc_round_delay: None,
+            max_adaptive_mpc_round_delay: None,
+            adaptive_mpc_round_delay_observations_window: None,
+            dwallet_dkg_session_timeout_consensus_rounds: None,
+            presign_session_timeout_consensus_rounds: None,
+            sign_session_timeout_consensus_rounds: None,
+            network_dkg_session_timeout_consensus_rounds: None,
+            network_key_reconfiguration_session_timeout_consensus_rounds: None,
         };
 
         cfg.feature_flags.mysticeti_num_leaders_per_round = Some(1);
@@ -475,6 +524,19 @@ impl ProtocolConfig {
         for cur in 2..=version.0 {
             match cur {
                 1 => unreachable!(),
+                2 => {
+                    cfg.feature_flags.adaptive_mpc_round_delays = true;
+                    cfg.feature_flags.certify_next_epoch_committee = true;
+                    cfg.feature_flags.garbage_collect_future_signs = true;
+                    cfg.min_adaptive_mpc_round_delay = Some(2);
+                    cfg.max_adaptive_mpc_round_delay = Some(30);
+                    cfg.adaptive_mpc_round_delay_observations_window = Some(100);
+                    cfg.dwallet_dkg_session_timeout_consensus_rounds = Some(3_000);
+                    cfg.presign_session_timeout_consensus_rounds = Some(3_000);
+                    cfg.sign_session_timeout_consensus_rounds = Some(3_000);
+                    cfg.network_dkg_session_timeout_consensus_rounds = Some(20_000);
+                    cfg.network_key_reconfiguration_session_timeout_consensus_rounds = Some(20_000);
+                }
                 // Use this template when making changes:
                 //
                 //     // modify an existing constant.
@@ -529,6 +591,18 @@ impl ProtocolConfig {
     pub fn set_enforce_checkpoint_timestamp_monotonicity_for_testing(&mut self, val: bool) {
         self.feature_flags.enforce_checkpoint_timestamp_monotonicity = val;
     }
+
+    pub fn set_adaptive_mpc_round_delays_for_testing(&mut self, val: bool) {
+        self.feature_flags.adaptive_mpc_round_delays = val;
+    }
+
+    pub fn set_certify_next_epoch_committee_for_testing(&mut self, val: bool) {
+        self.feature_flags.certify_next_epoch_committee = val;
+    }
+
+    pub fn set_garbage_collect_future_signs_for_testing(&mut self, val: bool) {
+        self.feature_flags.garbage_collect_future_signs = val;
+    }
 }
 
 type OverrideFn = dyn Fn(ProtocolVersion, ProtocolConfig) -> ProtocolConfig + Send;
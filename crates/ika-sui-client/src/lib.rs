@@ -54,6 +54,8 @@ use tracing::{debug, error, info, warn};
 pub mod ika_protocol_transactions;
 pub mod ika_validator_transactions;
 pub mod metrics;
+#[cfg(feature = "simulated-coordinator")]
+pub mod simulated_coordinator;
 
 #[macro_export]
 macro_rules! retry_with_max_elapsed_time {
@@ -100,7 +102,33 @@ pub struct SuiClient<P> {
     ika_dwallet_coordinator_object_id: ObjectID,
 }
 
-pub type SuiConnectorClient = SuiClient<SuiSdkClient>;
+pub type SuiConnectorClient = SuiClient<SuiConnectorClientInner>;
+
+/// The Sui backend of the [`SuiConnectorClient`] a node runs with.
+pub enum SuiConnectorClientInner {
+    Sdk(SuiSdkClient),
+    #[cfg(feature = "simulated-coordinator")]
+    Simulated(Arc<simulated_coordinator::SimulatedCoordinator>),
+}
+
+impl SuiConnectorClientInner {
+    /// Connects to the Sui RPC at `rpc_url`, or to the simulated coordinator registered
+    /// under it when the `simulated-coordinator` feature is enabled.
+    pub async fn connect(rpc_url: &str) -> anyhow::Result<Self> {
+        #[cfg(feature = "simulated-coordinator")]
+        if let Some(coordinator) = simulated_coordinator::coordinator_for_rpc_url(rpc_url)? {
+            return Ok(Self::Simulated(coordinator));
+        }
+
+        let client = SuiClientBuilder::default()
+            .build(rpc_url)
+            .await
+            .map_err(|e| {
+                anyhow!("Can't establish connection with Sui Rpc {rpc_url}. Error: {e}")
+            })?;
+        Ok(Self::Sdk(client))
+    }
+}
 
 impl SuiConnectorClient {
     pub async fn new(
@@ -113,12 +141,7 @@ impl SuiConnectorClient {
         ika_system_object_id: ObjectID,
         ika_dwallet_coordinator_object_id: ObjectID,
     ) -> anyhow::Result<Self> {
-        let inner = SuiClientBuilder::default()
-            .build(rpc_url)
-            .await
-            .map_err(|e| {
-                anyhow!("Can't establish connection with Sui Rpc {rpc_url}. Error: {e}")
-            })?;
+        let inner = SuiConnectorClientInner::connect(rpc_url).await?;
         let self_ = Self {
             inner,
             sui_client_metrics,
@@ -133,8 +156,13 @@ impl SuiConnectorClient {
         Ok(self_)
     }
 
-    pub fn sui_client(&self) -> &SuiSdkClient {
-        &self.inner
+    /// The Sui SDK client, unless running against a simulated coordinator.
+    pub fn sui_client(&self) -> Option<&SuiSdkClient> {
+        match &self.inner {
+            SuiConnectorClientInner::Sdk(client) => Some(client),
+            #[cfg(feature = "simulated-coordinator")]
+            SuiConnectorClientInner::Simulated(_) => None,
+        }
     }
 }
 
@@ -860,6 +888,187 @@ pub trait SuiClientInner: Send + Sync {
     ) -> Result<Vec<DBSuiEvent>, self::Error>;
 }
 
+/// Forwards a [`SuiClientInner`] call to the backend of a [`SuiConnectorClientInner`].
+macro_rules! dispatch_inner {
+    ($self:ident, $client:ident => $call:expr) => {
+        match $self {
+            SuiConnectorClientInner::Sdk($client) => $call,
+            #[cfg(feature = "simulated-coordinator")]
+            SuiConnectorClientInner::Simulated($client) => $call,
+        }
+    };
+}
+
+#[async_trait]
+impl SuiClientInner for SuiConnectorClientInner {
+    type Error = Error;
+
+    async fn query_events(
+        &self,
+        query: EventFilter,
+        cursor: Option<EventID>,
+    ) -> Result<EventPage, Self::Error> {
+        dispatch_inner!(self, client => client.query_events(query, cursor).await)
+    }
+
+    async fn get_events_by_tx_digest(
+        &self,
+        tx_digest: TransactionDigest,
+    ) -> Result<Vec<SuiEvent>, Self::Error> {
+        dispatch_inner!(self, client => client.get_events_by_tx_digest(tx_digest).await)
+    }
+
+    async fn get_chain_identifier(&self) -> Result<String, Self::Error> {
+        dispatch_inner!(self, client => client.get_chain_identifier().await)
+    }
+
+    async fn get_reference_gas_price(&self) -> Result<u64, Self::Error> {
+        dispatch_inner!(self, client => client.get_reference_gas_price().await)
+    }
+
+    async fn get_latest_checkpoint_sequence_number(&self) -> Result<u64, Self::Error> {
+        dispatch_inner!(self, client => client.get_latest_checkpoint_sequence_number().await)
+    }
+
+    async fn get_system(&self, ika_system_object_id: ObjectID) -> Result<Vec<u8>, Self::Error> {
+        dispatch_inner!(self, client => client.get_system(ika_system_object_id).await)
+    }
+
+    async fn get_clock(&self, clock_obj_id: ObjectID) -> Result<Vec<u8>, Self::Error> {
+        dispatch_inner!(self, client => client.get_clock(clock_obj_id).await)
+    }
+
+    async fn get_dwallet_coordinator(
+        &self,
+        dwallet_coordinator_id: ObjectID,
+    ) -> Result<Vec<u8>, Self::Error> {
+        dispatch_inner!(self, client => client.get_dwallet_coordinator(dwallet_coordinator_id).await)
+    }
+
+    async fn get_mpc_data_from_validators_pool(
+        &self,
+        validators: &Vec<StakingPool>,
+        read_next_epoch_mpc_data: bool,
+    ) -> Result<HashMap<ObjectID, VersionedMPCData>, self::Error> {
+        dispatch_inner!(self, client => {
+            client
+                .get_mpc_data_from_validators_pool(validators, read_next_epoch_mpc_data)
+                .await
+        })
+    }
+
+    async fn get_network_encryption_keys(
+        &self,
+        dwallet_coordinator_inner: &DWalletCoordinatorInnerV1,
+    ) -> Result<HashMap<ObjectID, DWalletNetworkEncryptionKey>, self::Error> {
+        dispatch_inner!(self, client => {
+            client.get_network_encryption_keys(dwallet_coordinator_inner).await
+        })
+    }
+
+    async fn get_network_encryption_key_with_full_data_by_epoch(
+        &self,
+        network_decryption_key: &DWalletNetworkEncryptionKey,
+        epoch: EpochId,
+    ) -> Result<DWalletNetworkEncryptionKeyData, self::Error> {
+        dispatch_inner!(self, client => {
+            client
+                .get_network_encryption_key_with_full_data_by_epoch(network_decryption_key, epoch)
+                .await
+        })
+    }
+
+    async fn get_current_reconfiguration_public_output(
+        &self,
+        epoch_id: EpochId,
+        table_id: ObjectID,
+    ) -> Result<ObjectID, Self::Error> {
+        dispatch_inner!(self, client => {
+            client.get_current_reconfiguration_public_output(epoch_id, table_id).await
+        })
+    }
+
+    async fn read_table_vec_as_raw_bytes(
+        &self,
+        table_id: ObjectID,
+    ) -> Result<Vec<u8>, self::Error> {
+        dispatch_inner!(self, client => client.read_table_vec_as_raw_bytes(table_id).await)
+    }
+
+    async fn get_system_inner(
+        &self,
+        ika_system_object_id: ObjectID,
+        version: u64,
+    ) -> Result<Vec<u8>, Self::Error> {
+        dispatch_inner!(self, client => client.get_system_inner(ika_system_object_id, version).await)
+    }
+
+    async fn get_dwallet_coordinator_inner(
+        &self,
+        dwallet_coordinator_id: ObjectID,
+        version: u64,
+    ) -> Result<Vec<u8>, Self::Error> {
+        dispatch_inner!(self, client => {
+            client.get_dwallet_coordinator_inner(dwallet_coordinator_id, version).await
+        })
+    }
+
+    async fn get_validators(
+        &self,
+        validator_ids: Vec<ObjectID>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        dispatch_inner!(self, client => client.get_validators(validator_ids).await)
+    }
+
+    async fn get_validator_inners(
+        &self,
+        validators: Vec<Validator>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        dispatch_inner!(self, client => client.get_validator_inners(validators).await)
+    }
+
+    async fn get_mutable_shared_arg(
+        &self,
+        ika_system_object_id: ObjectID,
+    ) -> Result<ObjectArg, Self::Error> {
+        dispatch_inner!(self, client => client.get_mutable_shared_arg(ika_system_object_id).await)
+    }
+
+    async fn get_shared_arg(&self, obj_id: ObjectID) -> Result<ObjectArg, Self::Error> {
+        dispatch_inner!(self, client => client.get_shared_arg(obj_id).await)
+    }
+
+    async fn get_available_move_packages(
+        &self,
+        ika_package_id: ObjectID,
+        ika_system_package_id: ObjectID,
+    ) -> Result<Vec<(ObjectID, MovePackageDigest)>, Self::Error> {
+        dispatch_inner!(self, client => {
+            client
+                .get_available_move_packages(ika_package_id, ika_system_package_id)
+                .await
+        })
+    }
+
+    async fn execute_transaction_block_with_effects(
+        &self,
+        tx: Transaction,
+    ) -> Result<SuiTransactionBlockResponse, IkaError> {
+        dispatch_inner!(self, client => client.execute_transaction_block_with_effects(tx).await)
+    }
+
+    async fn get_gas_objects(&self, address: SuiAddress) -> Vec<ObjectRef> {
+        dispatch_inner!(self, client => client.get_gas_objects(address).await)
+    }
+
+    async fn get_uncompleted_events(
+        &self,
+        events_bag_id: ObjectID,
+    ) -> Result<Vec<DBSuiEvent>, self::Error> {
+        dispatch_inner!(self, client => client.get_uncompleted_events(events_bag_id).await)
+    }
+}
+
 #[async_trait]
 impl SuiClientInner for SuiSdkClient {
     type Error = sui_sdk::error::Error;
@@ -1420,36 +1629,10 @@ impl SuiClientInner for SuiSdkClient {
         ika_package_id: ObjectID,
         ika_system_package_id: ObjectID,
     ) -> Result<Vec<(ObjectID, MovePackageDigest)>, Self::Error> {
-        let mut results = vec![];
         //let protocol_config_response = self.read_api().get_protocol_config(None).await?;
         //let protocol_config = sui_protocol_config::ProtocolConfig::get_for_version(protocol_config_response.protocol_version, chain);
         //let binary_config = sui_types::execution_config_utils::to_binary_config(&protocol_config);
-
-        let ika_packages = vec![
-            ("ika".to_string(), ika_package_id),
-            ("ika_system".to_string(), ika_system_package_id),
-        ];
-        for (name, package_id) in ika_packages.clone() {
-            //let object_response = self.read_api().get_object_with_options(package_id, SuiObjectDataOptions::full_content()).await?;
-            //let object_data = object_response.data.expect("Package object should have data.");
-            //let object: Object = object_data.try_into().map_err(|e: anyhow::Error| Self::Error::DataError(e.to_string()))?;
-            let move_package = BuiltInIkaMovePackages::get_package_by_name(&name);
-            //let modules = move_package.modules_with_deps(ika_packages.clone().into_iter().collect()).map_err(|e: anyhow::Error| Self::Error::DataError(e.to_string()))?;
-            let bytes = move_package
-                .bytes_with_deps(ika_packages.clone().into_iter().collect())
-                .map_err(|e: anyhow::Error| Self::Error::DataError(e.to_string()))?;
-            let full_deps = move_package
-                .full_deps(ika_packages.clone().into_iter().collect())
-                .map_err(|e: anyhow::Error| Self::Error::DataError(e.to_string()))?;
-            let digest = MovePackage::compute_digest_for_modules_and_deps(
-                bytes.iter(),
-                full_deps.iter(),
-                true,
-            );
-            results.push((package_id, digest))
-        }
-
-        Ok(results)
+        built_in_move_package_digests(ika_package_id, ika_system_package_id)
     }
 
     async fn execute_transaction_block_with_effects(
@@ -1498,3 +1681,33 @@ impl SuiClientInner for SuiSdkClient {
         }
     }
 }
+
+/// Computes the digests of the built-in Ika Move packages, as published under the given IDs.
+pub(crate) fn built_in_move_package_digests(
+    ika_package_id: ObjectID,
+    ika_system_package_id: ObjectID,
+) -> Result<Vec<(ObjectID, MovePackageDigest)>, Error> {
+    let mut results = vec![];
+    let ika_packages = vec![
+        ("ika".to_string(), ika_package_id),
+        ("ika_system".to_string(), ika_system_package_id),
+    ];
+    for (name, package_id) in ika_packages.clone() {
+        //let object_response = self.read_api().get_object_with_options(package_id, SuiObjectDataOptions::full_content()).await?;
+        //let object_data = object_response.data.expect("Package object should have data.");
+        //let object: Object = object_data.try_into().map_err(|e: anyhow::Error| Error::DataError(e.to_string()))?;
+        let move_package = BuiltInIkaMovePackages::get_package_by_name(&name);
+        //let modules = move_package.modules_with_deps(ika_packages.clone().into_iter().collect()).map_err(|e: anyhow::Error| Error::DataError(e.to_string()))?;
+        let bytes = move_package
+            .bytes_with_deps(ika_packages.clone().into_iter().collect())
+            .map_err(|e: anyhow::Error| Error::DataError(e.to_string()))?;
+        let full_deps = move_package
+            .full_deps(ika_packages.clone().into_iter().collect())
+            .map_err(|e: anyhow::Error| Error::DataError(e.to_string()))?;
+        let digest =
+            MovePackage::compute_digest_for_modules_and_deps(bytes.iter(), full_deps.iter(), true);
+        results.push((package_id, digest))
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,1822 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! An in-process simulation of the Ika Move packages on Sui, to run validators without
+//! a Sui network.
+//!
+//! Only available with the `simulated-coordinator` feature.
+//! The [`SimulatedCoordinator`] keeps the system and dWallet coordinator objects in memory
+//! and implements [`SuiClientInner`] over them: reads return the same BCS Sui would, and
+//! the transactions submitted by the Sui connector are executed by decoding their Move calls
+//! and applying them the way the Move code does:
+//! - `process_checkpoint_message_by_quorum` verifies the certificate against the active
+//!   committee, completes the sessions of the outputs in the checkpoint and stores the
+//!   network encryption key outputs.
+//! - The mid-epoch reconfiguration, session locking and epoch advancement calls move
+//!   the epoch along. The committee of the next epoch is always the current one.
+//!
+//! Sessions are requested with [`SimulatedCoordinator::request_session`], which emits the
+//! [`DWalletSessionEvent`] the Move code would.
+//!
+//! All the nodes of a swarm run in the same process, so coordinators are kept in a
+//! process-wide registry keyed by their system object ID, and a node connects to one
+//! by using its [`SimulatedCoordinator::rpc_url`] as the Sui RPC URL.
+
+use crate::{SuiClientInner, built_in_move_package_digests};
+use async_trait::async_trait;
+use dwallet_mpc_types::dwallet_mpc::VersionedMPCData;
+use fastcrypto::traits::{AggregateAuthenticator, ToFromBytes};
+use ika_config::initiation::InitiationParameters;
+use ika_config::validator_info::ValidatorInfo;
+use ika_types::committee::EpochId;
+use ika_types::crypto::{AggregateAuthoritySignature, AuthorityPublicKey};
+use ika_types::error::IkaError;
+use ika_types::message::DWalletCheckpointMessageKind;
+use ika_types::messages_consensus::MovePackageDigest;
+use ika_types::messages_dwallet_checkpoint::DWalletCheckpointMessage;
+use ika_types::messages_dwallet_mpc::{
+    DBSuiEvent, DWalletEncryptionKeyReconfigurationRequestEvent,
+    DWalletNetworkDKGEncryptionKeyRequestEvent, DWalletNetworkEncryptionKey,
+    DWalletNetworkEncryptionKeyData, DWalletNetworkEncryptionKeyState, DWalletSessionEvent,
+    DWalletSessionEventTrait, IkaNetworkConfig, SESSIONS_MANAGER_MODULE_NAME, SessionType,
+};
+use ika_types::messages_system_checkpoints::{
+    SystemCheckpointMessage, SystemCheckpointMessageKind,
+};
+use ika_types::sui::staking::{StakingPool, ValidatorInfo as StakingValidatorInfo};
+use ika_types::sui::system_inner_v1::{
+    BlsCommittee, BlsCommitteeMember, DWalletCoordinatorInnerV1, PricingAndFeeManagement,
+    PricingInfo, PricingInfoCalculationVotes, ProtocolTreasuryV1, SessionsKeeper, SessionsManager,
+    SupportConfig, SystemInnerV1, SystemObjectCap, ValidatorSetV1,
+};
+use ika_types::sui::{
+    ADVANCE_EPOCH_FUNCTION_NAME, APPEND_VECTOR_FUNCTION_NAME,
+    DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME, DWalletCoordinator, Element, ExtendedField,
+    INITIATE_ADVANCE_EPOCH_FUNCTION_NAME, INITIATE_MID_EPOCH_RECONFIGURATION_FUNCTION_NAME,
+    PROCESS_CHECKPOINT_MESSAGE_BY_QUORUM_FUNCTION_NAME, REQUEST_LOCK_EPOCH_SESSIONS_FUNCTION_NAME,
+    REQUEST_NETWORK_ENCRYPTION_KEY_MID_EPOCH_RECONFIGURATION_FUNCTION_NAME, SYSTEM_MODULE_NAME,
+    System, VECTOR_MODULE_NAME, Validator,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use sui_json_rpc_types::{
+    BcsEvent, EventFilter, EventPage, SuiEvent, SuiExecutionStatus, SuiTransactionBlockEffects,
+    SuiTransactionBlockEvents, SuiTransactionBlockResponse,
+};
+use sui_sdk::error::Error;
+use sui_types::balance::{Balance, Supply};
+use sui_types::base_types::{ObjectDigest, ObjectID, ObjectRef, SequenceNumber, SuiAddress};
+use sui_types::clock::Clock;
+use sui_types::coin::TreasuryCap;
+use sui_types::collection_types::{Bag, Table, TableVec, VecMap};
+use sui_types::digests::TransactionDigest;
+use sui_types::dynamic_field::Field;
+use sui_types::event::EventID;
+use sui_types::id::{ID, UID};
+use sui_types::transaction::{
+    Argument, CallArg, Command, ObjectArg, ProgrammableMoveCall, ProgrammableTransaction,
+    Transaction, TransactionDataAPI, TransactionKind,
+};
+use tracing::{info, warn};
+
+const SIMULATED_RPC_URL_SCHEME: &str = "simulated://";
+const SIMULATED_CHAIN_IDENTIFIER: &str = "simulated";
+const REFERENCE_GAS_PRICE: u64 = 1000;
+const DEFAULT_MAX_ACTIVE_SESSIONS_BUFFER: u64 = 100;
+/// The maximum number of events returned by a single `query_events` call, as on Sui.
+const EVENTS_PAGE_SIZE: usize = 50;
+/// The version of the inner system and coordinator objects,
+/// which are stored as dynamic fields under it.
+const INNER_OBJECT_VERSION: u64 = 1;
+/// The intents the committee signs checkpoint messages under, as defined in Move.
+const CHECKPOINT_MESSAGE_INTENT: [u8; 3] = [1, 0, 0];
+const SYSTEM_CHECKPOINT_MESSAGE_INTENT: [u8; 3] = [2, 0, 0];
+
+static REGISTRY: LazyLock<RwLock<HashMap<ObjectID, Arc<SimulatedCoordinator>>>> =
+    LazyLock::new(Default::default);
+
+/// Makes `coordinator` available to the nodes that use its [`SimulatedCoordinator::rpc_url`].
+pub fn register(coordinator: Arc<SimulatedCoordinator>) {
+    REGISTRY.write().unwrap().insert(
+        coordinator.network_config.objects.ika_system_object_id,
+        coordinator,
+    );
+}
+
+pub fn unregister(ika_system_object_id: &ObjectID) {
+    REGISTRY.write().unwrap().remove(ika_system_object_id);
+}
+
+/// Returns the coordinator registered for the system object `ika_system_object_id`, if any.
+pub fn registered(ika_system_object_id: &ObjectID) -> Option<Arc<SimulatedCoordinator>> {
+    REGISTRY.read().unwrap().get(ika_system_object_id).cloned()
+}
+
+/// Returns the coordinator registered for `rpc_url`,
+/// or `None` if it isn't the URL of a simulated coordinator.
+pub(crate) fn coordinator_for_rpc_url(
+    rpc_url: &str,
+) -> anyhow::Result<Option<Arc<SimulatedCoordinator>>> {
+    let Some(ika_system_object_id) = rpc_url.strip_prefix(SIMULATED_RPC_URL_SCHEME) else {
+        return Ok(None);
+    };
+    let ika_system_object_id = ObjectID::from_hex_literal(ika_system_object_id)?;
+
+    registered(&ika_system_object_id)
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("no simulated coordinator is registered for {rpc_url}"))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("the system time should be after the unix epoch")
+        .as_millis() as u64
+}
+
+fn new_bag() -> Bag {
+    Bag {
+        id: UID::new(ObjectID::random()),
+        size: 0,
+    }
+}
+
+fn new_table() -> Table {
+    Table {
+        id: ObjectID::random(),
+        size: 0,
+    }
+}
+
+fn empty_pricing_info() -> PricingInfo {
+    PricingInfo {
+        pricing_map: VecMap { contents: vec![] },
+    }
+}
+
+fn new_sessions_keeper() -> SessionsKeeper {
+    SessionsKeeper {
+        sessions: new_table(),
+        session_events: new_bag(),
+        started_sessions_count: 0,
+        completed_sessions_count: 0,
+        next_session_sequence_number: 1,
+    }
+}
+
+/// Builds a committee of `members` with the thresholds the Move code uses.
+fn bls_committee(members: Vec<BlsCommitteeMember>) -> BlsCommittee {
+    let quorum_threshold = (2 * (members.len() as u64 / 3)) + 1;
+    let validity_threshold = (members.len() as u64 / 3) + 1;
+
+    BlsCommittee {
+        members,
+        // Only used on-chain, the validators aggregate the public keys themselves.
+        aggregated_protocol_pubkey: Element::new(vec![]),
+        quorum_threshold,
+        validity_threshold,
+    }
+}
+
+fn abort(message: impl Into<String>) -> Error {
+    Error::DataError(message.into())
+}
+
+fn to_bcs<T: Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    bcs::to_bytes(value).map_err(|e| abort(e.to_string()))
+}
+
+/// A session that was requested and not completed yet.
+#[derive(Clone)]
+struct PendingSession {
+    session_object_id: ObjectID,
+    type_: move_core_types::language_storage::StructTag,
+    /// The BCS of the [`DWalletSessionEvent`].
+    contents: Vec<u8>,
+}
+
+struct SimulatedState {
+    system: SystemInnerV1,
+    coordinator: DWalletCoordinatorInnerV1,
+    validators: HashMap<ObjectID, StakingPool>,
+    network_keys: BTreeMap<ObjectID, DWalletNetworkEncryptionKey>,
+    /// The contents of every `TableVec<vector<u8>>`, by its table ID.
+    table_vecs: HashMap<ObjectID, Vec<u8>>,
+    /// The `TableVec` of every entry of the `Table<u64, TableVec>` tables, by the table ID.
+    tables_by_epoch: HashMap<ObjectID, BTreeMap<EpochId, ObjectID>>,
+    pending_user_sessions: BTreeMap<u64, PendingSession>,
+    pending_system_sessions: BTreeMap<u64, PendingSession>,
+    events: Vec<SuiEvent>,
+    /// The index in `events` of the first event of every executed transaction.
+    transactions: HashMap<TransactionDigest, usize>,
+    completed_outputs: Vec<DWalletCheckpointMessageKind>,
+    gas_objects: HashMap<SuiAddress, ObjectRef>,
+}
+
+/// An in-memory replacement of the Ika Move packages on Sui.
+///
+/// See the [module documentation](self) for what is simulated.
+pub struct SimulatedCoordinator {
+    network_config: IkaNetworkConfig,
+    state: Mutex<SimulatedState>,
+}
+
+impl SimulatedCoordinator {
+    /// Creates the system with `validators` as the committee of its first epoch,
+    /// and requests the DKG of a network encryption key, as `init_ika_on_sui` does.
+    pub fn new(validators: &[ValidatorInfo], initiation_parameters: &InitiationParameters) -> Self {
+        let network_config = IkaNetworkConfig::new(
+            ObjectID::random(),
+            ObjectID::random(),
+            ObjectID::random(),
+            ObjectID::random(),
+            ObjectID::random(),
+            ObjectID::random(),
+        );
+        let epoch = 1;
+
+        let mut table_vecs = HashMap::new();
+        let mut staking_pools = HashMap::new();
+        let mut members = vec![];
+        for validator in validators {
+            let validator_id = ObjectID::random();
+            let mpc_data_bytes = TableVec {
+                contents: Table {
+                    id: ObjectID::random(),
+                    size: 1,
+                },
+            };
+            table_vecs.insert(
+                mpc_data_bytes.contents.id,
+                bcs::to_bytes(&validator.mpc_data).expect("serializing MPC data should never fail"),
+            );
+            let protocol_pubkey_bytes = validator.protocol_public_key.as_ref().to_vec();
+            let validator_info = StakingValidatorInfo {
+                name: validator.name.clone(),
+                validator_id,
+                network_address: validator.network_address.to_string(),
+                p2p_address: validator.p2p_address.to_string(),
+                consensus_address: validator.consensus_address.to_string(),
+                protocol_pubkey_bytes: protocol_pubkey_bytes.clone(),
+                protocol_pubkey: Element::new(protocol_pubkey_bytes.clone()),
+                network_pubkey_bytes: validator.network_public_key.as_bytes().to_vec(),
+                consensus_pubkey_bytes: validator.consensus_public_key.as_bytes().to_vec(),
+                mpc_data_bytes: Some(mpc_data_bytes),
+                next_epoch_protocol_pubkey_bytes: None,
+                next_epoch_network_pubkey_bytes: None,
+                next_epoch_consensus_pubkey_bytes: None,
+                next_epoch_mpc_datd_bytes: None,
+                next_epoch_network_address: None,
+                next_epoch_p2p_address: None,
+                next_epoch_consensus_address: None,
+                previous_mpc_data_bytes: None,
+                metadata: ExtendedField {
+                    id: ObjectID::random(),
+                },
+            };
+            members.push(BlsCommitteeMember {
+                validator_id,
+                protocol_pubkey: Element::new(protocol_pubkey_bytes),
+            });
+            staking_pools.insert(
+                validator_id,
+                StakingPool::new_active(validator_id, validator_info, epoch),
+            );
+        }
+        let active_committee = bls_committee(members);
+
+        let genesis_digest = TransactionDigest::random();
+        let system = SystemInnerV1 {
+            epoch,
+            epoch_start_tx_digest: genesis_digest.into_inner().to_vec(),
+            system_object_cap: SystemObjectCap {
+                id: ObjectID::random(),
+            },
+            protocol_version: initiation_parameters.protocol_version,
+            next_protocol_version: None,
+            upgrade_caps: vec![],
+            approved_upgrades: VecMap { contents: vec![] },
+            validator_set: ValidatorSetV1 {
+                total_stake: 0,
+                reward_slashing_rate: 0,
+                validators: Table {
+                    id: ObjectID::random(),
+                    size: staking_pools.len() as u64,
+                },
+                active_committee: active_committee.clone(),
+                next_epoch_committee: None,
+                previous_committee: bls_committee(vec![]),
+                pending_active_set: ExtendedField {
+                    id: ObjectID::random(),
+                },
+                validator_report_records: VecMap { contents: vec![] },
+                extra_fields: new_bag(),
+            },
+            epoch_duration_ms: initiation_parameters.epoch_duration_ms,
+            stake_subsidy_start_epoch: initiation_parameters.stake_subsidy_start_epoch,
+            protocol_treasury: ProtocolTreasuryV1 {
+                treasury_cap: TreasuryCap {
+                    id: UID::new(ObjectID::random()),
+                    total_supply: Supply { value: 0 },
+                },
+                stake_subsidy_distribution_counter: 0,
+                stake_subsidy_rate: initiation_parameters.stake_subsidy_rate,
+                stake_subsidy_amount_per_distribution: 0,
+                stake_subsidy_period_length: initiation_parameters.stake_subsidy_period_length,
+                total_supply_at_period_start: 0,
+                extra_fields: new_bag(),
+            },
+            epoch_start_timestamp_ms: now_ms(),
+            last_processed_checkpoint_sequence_number: 0,
+            previous_epoch_last_checkpoint_sequence_number: 0,
+            total_messages_processed: 0,
+            remaining_rewards: Balance::new(0),
+            authorized_protocol_cap_ids: vec![],
+            witness_approving_advance_epoch: vec![],
+            received_end_of_publish: false,
+            extra_fields: new_bag(),
+        };
+        let coordinator = DWalletCoordinatorInnerV1 {
+            current_epoch: epoch,
+            sessions_manager: SessionsManager {
+                registered_user_session_identifiers: new_table(),
+                user_sessions_keeper: new_sessions_keeper(),
+                system_sessions_keeper: new_sessions_keeper(),
+                last_user_initiated_session_to_complete_in_current_epoch: 0,
+                locked_last_user_initiated_session_to_complete_in_current_epoch: false,
+                max_active_sessions_buffer: DEFAULT_MAX_ACTIVE_SESSIONS_BUFFER,
+            },
+            dwallets: new_table(),
+            dwallet_network_encryption_keys: new_table(),
+            epoch_dwallet_network_encryption_keys_reconfiguration_completed: 0,
+            encryption_keys: new_table(),
+            presigns: new_table(),
+            partial_centralized_signed_messages: new_table(),
+            pricing_and_fee_management: PricingAndFeeManagement {
+                current: empty_pricing_info(),
+                default: empty_pricing_info(),
+                validator_votes: new_table(),
+                calculation_votes: None,
+                gas_fee_reimbursement_sui_system_call_value: 0,
+                gas_fee_reimbursement_sui_system_call_balance: Balance::new(0),
+                fee_charged_ika: Balance::new(0),
+            },
+            active_committee,
+            next_epoch_active_committee: None,
+            total_messages_processed: 0,
+            last_processed_checkpoint_sequence_number: 0,
+            previous_epoch_last_checkpoint_sequence_number: 0,
+            support_config: SupportConfig {
+                supported_curves_to_signature_algorithms_to_hash_schemes: VecMap {
+                    contents: vec![],
+                },
+                paused_curves: vec![],
+                paused_signature_algorithms: vec![],
+                paused_hash_schemes: vec![],
+                signature_algorithms_allowed_global_presign: vec![],
+            },
+            received_end_of_publish: false,
+            extra_fields: new_bag(),
+        };
+
+        let mut state = SimulatedState {
+            system,
+            coordinator,
+            validators: staking_pools,
+            network_keys: BTreeMap::new(),
+            table_vecs,
+            tables_by_epoch: HashMap::new(),
+            pending_user_sessions: BTreeMap::new(),
+            pending_system_sessions: BTreeMap::new(),
+            events: vec![],
+            transactions: HashMap::from([(genesis_digest, 0)]),
+            completed_outputs: vec![],
+            gas_objects: HashMap::new(),
+        };
+
+        // The network DKG is requested in its own transaction, so its event isn't skipped
+        // by the event listener, which starts right after the epoch's first transaction.
+        let request_digest = TransactionDigest::random();
+        state.begin_transaction(request_digest);
+        state.request_network_encryption_key_dkg(&network_config, request_digest);
+
+        Self {
+            network_config,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub fn network_config(&self) -> &IkaNetworkConfig {
+        &self.network_config
+    }
+
+    /// The Sui RPC URL of the nodes that use this coordinator.
+    pub fn rpc_url(&self) -> String {
+        format!(
+            "{SIMULATED_RPC_URL_SCHEME}{}",
+            self.network_config.objects.ika_system_object_id
+        )
+    }
+
+    pub fn current_epoch(&self) -> EpochId {
+        self.state.lock().unwrap().system.epoch
+    }
+
+    pub fn network_encryption_keys(&self) -> Vec<DWalletNetworkEncryptionKey> {
+        self.state
+            .lock()
+            .unwrap()
+            .network_keys
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Requests a user session, by emitting its [`DWalletSessionEvent`] with `event_data`.
+    ///
+    /// Returns the sequence number of the session, whose output is then available in
+    /// [`SimulatedCoordinator::completed_outputs`] once the network completes it.
+    pub fn request_session<E: DWalletSessionEventTrait + Serialize>(&self, event_data: E) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let digest = TransactionDigest::random();
+        state.begin_transaction(digest);
+        let session_sequence_number =
+            state.initiate_session(&self.network_config, digest, SessionType::User, event_data);
+        state.update_last_user_initiated_session_to_complete_in_current_epoch();
+
+        session_sequence_number
+    }
+
+    /// The outputs of all the sessions completed so far, in the order they were processed.
+    pub fn completed_outputs(&self) -> Vec<DWalletCheckpointMessageKind> {
+        self.state.lock().unwrap().completed_outputs.clone()
+    }
+
+    /// Executes a transaction of the Sui connector.
+    ///
+    /// As on Sui, a transaction whose Move call aborts is still executed,
+    /// with a failure status and no effects.
+    fn execute(&self, transaction: &Transaction) -> SuiExecutionStatus {
+        let digest = *transaction.digest();
+        let TransactionKind::ProgrammableTransaction(programmable_transaction) =
+            transaction.data().transaction_data().kind()
+        else {
+            return SuiExecutionStatus::Failure {
+                error: "only programmable transactions are supported".to_string(),
+            };
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if state.transactions.contains_key(&digest) {
+            return SuiExecutionStatus::Failure {
+                error: format!("transaction {digest} was already executed"),
+            };
+        }
+        state.begin_transaction(digest);
+        match state.execute_programmable_transaction(
+            &self.network_config,
+            digest,
+            programmable_transaction,
+        ) {
+            Ok(()) => SuiExecutionStatus::Success,
+            Err(e) => {
+                warn!(?digest, error=?e, "a transaction failed in the simulated coordinator");
+                SuiExecutionStatus::Failure {
+                    error: e.to_string(),
+                }
+            }
+        }
+    }
+}
+
+impl SimulatedState {
+    fn begin_transaction(&mut self, digest: TransactionDigest) {
+        self.transactions.insert(digest, self.events.len());
+    }
+
+    fn emit_session_event(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+        type_: move_core_types::language_storage::StructTag,
+        contents: Vec<u8>,
+    ) {
+        let event_seq = (self.events.len() - self.transactions[&digest]) as u64;
+        self.events.push(SuiEvent {
+            id: EventID {
+                tx_digest: digest,
+                event_seq,
+            },
+            package_id: network_config.packages.ika_dwallet_2pc_mpc_package_id,
+            transaction_module: SESSIONS_MANAGER_MODULE_NAME.to_owned(),
+            sender: SuiAddress::ZERO,
+            type_,
+            parsed_json: serde_json::Value::Null,
+            bcs: BcsEvent::new(contents),
+            timestamp_ms: Some(now_ms()),
+        });
+    }
+
+    fn initiate_session<E: DWalletSessionEventTrait + Serialize>(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+        session_type: SessionType,
+        event_data: E,
+    ) -> u64 {
+        let keeper = match session_type {
+            SessionType::User => &mut self.coordinator.sessions_manager.user_sessions_keeper,
+            SessionType::System => &mut self.coordinator.sessions_manager.system_sessions_keeper,
+        };
+        let session_sequence_number = keeper.next_session_sequence_number;
+        keeper.next_session_sequence_number += 1;
+        keeper.started_sessions_count += 1;
+        keeper.session_events.size += 1;
+
+        let session_object_id = ObjectID::random();
+        let event = DWalletSessionEvent::new(
+            self.coordinator.current_epoch,
+            session_object_id,
+            session_type,
+            session_sequence_number,
+            ObjectID::random().into_bytes(),
+            event_data,
+        );
+        let type_ = DWalletSessionEvent::<E>::type_(network_config);
+        let contents = bcs::to_bytes(&event).expect("serializing an event should never fail");
+        let pending_session = PendingSession {
+            session_object_id,
+            type_: type_.clone(),
+            contents: contents.clone(),
+        };
+        match session_type {
+            SessionType::User => self
+                .pending_user_sessions
+                .insert(session_sequence_number, pending_session),
+            SessionType::System => self
+                .pending_system_sessions
+                .insert(session_sequence_number, pending_session),
+        };
+        self.emit_session_event(network_config, digest, type_, contents);
+
+        session_sequence_number
+    }
+
+    fn complete_session(
+        &mut self,
+        session_type: SessionType,
+        session_sequence_number: u64,
+    ) -> Result<(), Error> {
+        let (keeper, pending_sessions) = match session_type {
+            SessionType::User => (
+                &mut self.coordinator.sessions_manager.user_sessions_keeper,
+                &mut self.pending_user_sessions,
+            ),
+            SessionType::System => (
+                &mut self.coordinator.sessions_manager.system_sessions_keeper,
+                &mut self.pending_system_sessions,
+            ),
+        };
+        if pending_sessions.remove(&session_sequence_number).is_none() {
+            return Err(abort(format!(
+                "{session_type:?} session {session_sequence_number} doesn't exist"
+            )));
+        }
+        keeper.completed_sessions_count += 1;
+        keeper.session_events.size -= 1;
+        if session_type == SessionType::User {
+            self.update_last_user_initiated_session_to_complete_in_current_epoch();
+        }
+
+        Ok(())
+    }
+
+    fn update_last_user_initiated_session_to_complete_in_current_epoch(&mut self) {
+        let sessions_manager = &mut self.coordinator.sessions_manager;
+        if sessions_manager.locked_last_user_initiated_session_to_complete_in_current_epoch {
+            return;
+        }
+        let keeper = &sessions_manager.user_sessions_keeper;
+        let last_session_to_complete = (keeper.completed_sessions_count
+            + sessions_manager.max_active_sessions_buffer)
+            .min(keeper.next_session_sequence_number - 1);
+        sessions_manager.last_user_initiated_session_to_complete_in_current_epoch =
+            sessions_manager
+                .last_user_initiated_session_to_complete_in_current_epoch
+                .max(last_session_to_complete);
+    }
+
+    fn new_table_vec(&mut self) -> TableVec {
+        let contents = new_table();
+        self.table_vecs.insert(contents.id, vec![]);
+
+        TableVec { contents }
+    }
+
+    fn push_back_chunk(&mut self, table_vec: &mut TableVec, chunk: &[u8]) {
+        self.table_vecs
+            .entry(table_vec.contents.id)
+            .or_default()
+            .extend_from_slice(chunk);
+        table_vec.contents.size += 1;
+    }
+
+    fn clear_table_vec(&mut self, table_vec: &mut TableVec) {
+        self.table_vecs.insert(table_vec.contents.id, vec![]);
+        table_vec.contents.size = 0;
+    }
+
+    fn request_network_encryption_key_dkg(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+    ) {
+        let key_id = ObjectID::random();
+        let key = DWalletNetworkEncryptionKey {
+            id: key_id,
+            dkg_at_epoch: self.coordinator.current_epoch,
+            network_dkg_public_output: self.new_table_vec(),
+            reconfiguration_public_outputs: new_table(),
+            dkg_params_for_network: vec![],
+            supported_curves: vec![],
+            state: DWalletNetworkEncryptionKeyState::AwaitingNetworkDKG,
+        };
+        self.network_keys.insert(key_id, key);
+        self.coordinator.dwallet_network_encryption_keys.size += 1;
+        info!(
+            ?key_id,
+            "Requesting a network DKG in the simulated coordinator"
+        );
+
+        self.initiate_session(
+            network_config,
+            digest,
+            SessionType::System,
+            DWalletNetworkDKGEncryptionKeyRequestEvent {
+                dwallet_network_encryption_key_id: key_id,
+                params_for_network: vec![],
+            },
+        );
+    }
+
+    fn execute_programmable_transaction(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+        programmable_transaction: &ProgrammableTransaction,
+    ) -> Result<(), Error> {
+        // The byte vectors of the pure inputs, as appended to by `vector::append`.
+        let mut byte_vectors: HashMap<u16, Vec<u8>> = HashMap::new();
+        let pure_bytes = |argument: &Argument, byte_vectors: &HashMap<u16, Vec<u8>>| {
+            let Argument::Input(index) = argument else {
+                return Err(abort(format!("expected a pure input, got {argument:?}")));
+            };
+            if let Some(bytes) = byte_vectors.get(index) {
+                return Ok(bytes.clone());
+            }
+            match programmable_transaction.inputs.get(*index as usize) {
+                Some(CallArg::Pure(bytes)) => {
+                    bcs::from_bytes::<Vec<u8>>(bytes).map_err(|e| abort(e.to_string()))
+                }
+                _ => Err(abort(format!("input {index} isn't a pure input"))),
+            }
+        };
+
+        for command in &programmable_transaction.commands {
+            let move_call = match command {
+                Command::MoveCall(move_call) => move_call,
+                // Gas coins are not simulated.
+                Command::MergeCoins(..) => continue,
+                command => return Err(abort(format!("unsupported command {command:?}"))),
+            };
+            let ProgrammableMoveCall {
+                module,
+                function,
+                arguments,
+                ..
+            } = move_call.as_ref();
+            let argument = |index: usize| {
+                arguments
+                    .get(index)
+                    .ok_or_else(|| abort(format!("missing argument {index} of {function}")))
+            };
+
+            match (module.as_str(), function.as_str()) {
+                (module, function)
+                    if module == VECTOR_MODULE_NAME.as_str()
+                        && function == APPEND_VECTOR_FUNCTION_NAME.as_str() =>
+                {
+                    let Argument::Input(index) = argument(0)? else {
+                        return Err(abort("can only append to a pure input"));
+                    };
+                    let mut bytes = pure_bytes(argument(0)?, &byte_vectors)?;
+                    bytes.extend(pure_bytes(argument(1)?, &byte_vectors)?);
+                    byte_vectors.insert(*index, bytes);
+                }
+                (module, function)
+                    if module == DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME.as_str()
+                        && function == PROCESS_CHECKPOINT_MESSAGE_BY_QUORUM_FUNCTION_NAME.as_str() =>
+                {
+                    self.process_dwallet_checkpoint_message_by_quorum(
+                        network_config,
+                        digest,
+                        &pure_bytes(argument(1)?, &byte_vectors)?,
+                        &pure_bytes(argument(2)?, &byte_vectors)?,
+                        pure_bytes(argument(3)?, &byte_vectors)?,
+                    )?;
+                }
+                (module, function)
+                    if module == SYSTEM_MODULE_NAME.as_str()
+                        && function == PROCESS_CHECKPOINT_MESSAGE_BY_QUORUM_FUNCTION_NAME.as_str() =>
+                {
+                    self.process_system_checkpoint_message_by_quorum(
+                        &pure_bytes(argument(1)?, &byte_vectors)?,
+                        &pure_bytes(argument(2)?, &byte_vectors)?,
+                        pure_bytes(argument(3)?, &byte_vectors)?,
+                    )?;
+                }
+                (module, function)
+                    if module == SYSTEM_MODULE_NAME.as_str()
+                        && function == INITIATE_MID_EPOCH_RECONFIGURATION_FUNCTION_NAME.as_str() =>
+                {
+                    if now_ms() < self.system.epoch_start_timestamp_ms + self.system.epoch_duration_ms / 2 {
+                        return Err(abort("mid-epoch time wasn't reached"));
+                    }
+                    if self.system.validator_set.next_epoch_committee.is_some() {
+                        return Err(abort("already initiated mid-epoch reconfiguration"));
+                    }
+                    self.system.validator_set.next_epoch_committee =
+                        Some(self.system.validator_set.active_committee.clone());
+                }
+                (module, function)
+                    if module == DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME.as_str()
+                        && function == INITIATE_MID_EPOCH_RECONFIGURATION_FUNCTION_NAME.as_str() =>
+                {
+                    let Some(next_epoch_committee) =
+                        self.system.validator_set.next_epoch_committee.clone()
+                    else {
+                        return Err(abort("the system hasn't reached mid-epoch"));
+                    };
+                    if self.coordinator.next_epoch_active_committee.is_some() {
+                        return Err(abort("already initiated mid-epoch reconfiguration"));
+                    }
+                    let pricing = &mut self.coordinator.pricing_and_fee_management;
+                    pricing.calculation_votes = Some(PricingInfoCalculationVotes {
+                        bls_committee: next_epoch_committee.clone(),
+                        default_pricing: pricing.default.clone(),
+                        working_pricing: empty_pricing_info(),
+                    });
+                    self.coordinator.next_epoch_active_committee = Some(next_epoch_committee);
+                }
+                (module, function)
+                    if module == DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME.as_str()
+                        && function
+                            == REQUEST_NETWORK_ENCRYPTION_KEY_MID_EPOCH_RECONFIGURATION_FUNCTION_NAME
+                                .as_str() =>
+                {
+                    let Argument::Input(index) = argument(1)? else {
+                        return Err(abort("expected the network encryption key ID input"));
+                    };
+                    let Some(CallArg::Pure(key_id)) =
+                        programmable_transaction.inputs.get(*index as usize)
+                    else {
+                        return Err(abort("expected the network encryption key ID input"));
+                    };
+                    let key_id: ObjectID =
+                        bcs::from_bytes(key_id).map_err(|e| abort(e.to_string()))?;
+                    self.request_network_encryption_key_reconfiguration(
+                        network_config,
+                        digest,
+                        key_id,
+                    )?;
+                }
+                (module, "calculate_pricing_votes")
+                    if module == DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME.as_str() =>
+                {
+                    // Pricing is not simulated, so a single vote completes the calculation.
+                    self.coordinator
+                        .pricing_and_fee_management
+                        .calculation_votes = None;
+                }
+                (module, function)
+                    if module == DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME.as_str()
+                        && function == REQUEST_LOCK_EPOCH_SESSIONS_FUNCTION_NAME.as_str() =>
+                {
+                    if now_ms() < self.system.epoch_start_timestamp_ms + self.system.epoch_duration_ms {
+                        return Err(abort("the epoch hasn't ended"));
+                    }
+                    self.coordinator
+                        .sessions_manager
+                        .locked_last_user_initiated_session_to_complete_in_current_epoch = true;
+                }
+                (module, function)
+                    if module == SYSTEM_MODULE_NAME.as_str()
+                        && function == INITIATE_ADVANCE_EPOCH_FUNCTION_NAME.as_str() =>
+                {
+                    self.verify_can_advance_epoch()?;
+                }
+                (module, function)
+                    if module == DWALLET_2PC_MPC_COORDINATOR_MODULE_NAME.as_str()
+                        && function == ADVANCE_EPOCH_FUNCTION_NAME.as_str() =>
+                {
+                    self.advance_coordinator_epoch()?;
+                }
+                (module, function)
+                    if module == SYSTEM_MODULE_NAME.as_str()
+                        && function == ADVANCE_EPOCH_FUNCTION_NAME.as_str() =>
+                {
+                    self.advance_system_epoch(digest)?;
+                }
+                // Only returns a status object, which the simulation doesn't need.
+                (module, "create_system_current_status_info")
+                    if module == SYSTEM_MODULE_NAME.as_str() => {}
+                (module, function) => {
+                    return Err(abort(format!(
+                        "`{module}::{function}` is not supported by the simulated coordinator"
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifies a certificate of the active committee the way `bls_committee::verify_certificate` does.
+    fn verify_certificate(
+        committee: &BlsCommittee,
+        epoch: EpochId,
+        intent: [u8; 3],
+        signature: &[u8],
+        signers_bitmap: &[u8],
+        message: &[u8],
+    ) -> Result<(), Error> {
+        if signers_bitmap.len() != committee.members.len().div_ceil(8) {
+            return Err(abort("invalid signers bitmap"));
+        }
+        let signers = committee
+            .members
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| (signers_bitmap[index / 8] >> (index % 8)) & 1 == 1)
+            .map(|(_, member)| {
+                AuthorityPublicKey::from_bytes(member.protocol_pubkey.bytes())
+                    .map_err(|e| abort(e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if (signers.len() as u64) < committee.quorum_threshold {
+            return Err(abort("not enough signers"));
+        }
+
+        let signature =
+            AggregateAuthoritySignature::from_bytes(signature).map_err(|e| abort(e.to_string()))?;
+        let signed_message = [
+            intent.as_slice(),
+            message,
+            &bcs::to_bytes(&epoch).expect("serializing an epoch should never fail"),
+        ]
+        .concat();
+        signature
+            .verify(&signers, &signed_message)
+            .map_err(|e| abort(format!("invalid certificate: {e}")))
+    }
+
+    fn process_dwallet_checkpoint_message_by_quorum(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+        signature: &[u8],
+        signers_bitmap: &[u8],
+        message: Vec<u8>,
+    ) -> Result<(), Error> {
+        Self::verify_certificate(
+            &self.coordinator.active_committee,
+            self.coordinator.current_epoch,
+            CHECKPOINT_MESSAGE_INTENT,
+            signature,
+            signers_bitmap,
+            &message,
+        )?;
+        let message: DWalletCheckpointMessage =
+            bcs::from_bytes(&message).map_err(|e| abort(e.to_string()))?;
+        if message.epoch != self.coordinator.current_epoch {
+            return Err(abort("incorrect epoch in checkpoint"));
+        }
+        if self.coordinator.last_processed_checkpoint_sequence_number + 1 != message.sequence_number
+        {
+            return Err(abort("wrong checkpoint sequence number"));
+        }
+        self.coordinator.last_processed_checkpoint_sequence_number = message.sequence_number;
+        self.coordinator.total_messages_processed += message.messages.len() as u64;
+
+        for kind in message.messages {
+            self.process_dwallet_checkpoint_message_kind(network_config, digest, &kind)?;
+            self.completed_outputs.push(kind);
+        }
+
+        Ok(())
+    }
+
+    fn process_dwallet_checkpoint_message_kind(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+        kind: &DWalletCheckpointMessageKind,
+    ) -> Result<(), Error> {
+        let user_session_sequence_number = match kind {
+            DWalletCheckpointMessageKind::RespondDWalletDKGFirstRoundOutput(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondDWalletDKGSecondRoundOutput(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondDWalletEncryptedUserShare(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondMakeDWalletUserSecretKeySharesPublic(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondDWalletImportedKeyVerificationOutput(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondDWalletPresign(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondDWalletSign(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondDWalletBatchSign(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondThresholdDecryption(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(output) => {
+                output.session_sequence_number
+            }
+            DWalletCheckpointMessageKind::RespondDWalletPartialSignatureVerificationOutput(
+                output,
+            ) => output.session_sequence_number,
+            DWalletCheckpointMessageKind::RespondDWalletMPCNetworkDKGOutput(output) => {
+                return self.respond_network_encryption_key_dkg(
+                    network_config,
+                    digest,
+                    &output.dwallet_network_encryption_key_id,
+                    &output.public_output,
+                    &output.supported_curves,
+                    output.is_last,
+                    output.rejected,
+                    output.session_sequence_number,
+                );
+            }
+            DWalletCheckpointMessageKind::RespondDWalletMPCNetworkReconfigurationOutput(output) => {
+                return self.respond_network_encryption_key_reconfiguration(
+                    network_config,
+                    digest,
+                    &output.dwallet_network_encryption_key_id,
+                    &output.public_output,
+                    &output.supported_curves,
+                    output.is_last,
+                    output.rejected,
+                    output.session_sequence_number,
+                );
+            }
+            DWalletCheckpointMessageKind::SetMaxActiveSessionsBuffer(buffer) => {
+                self.coordinator.sessions_manager.max_active_sessions_buffer = *buffer;
+                return Ok(());
+            }
+            DWalletCheckpointMessageKind::SetGasFeeReimbursementSuiSystemCallValue(value) => {
+                self.coordinator
+                    .pricing_and_fee_management
+                    .gas_fee_reimbursement_sui_system_call_value = *value;
+                return Ok(());
+            }
+            DWalletCheckpointMessageKind::EndOfPublish => {
+                self.coordinator.received_end_of_publish = true;
+                return Ok(());
+            }
+            // Partial user signatures are not simulated, so there is nothing to collect.
+            DWalletCheckpointMessageKind::GarbageCollectFutureSigns(_) => return Ok(()),
+        };
+
+        self.complete_session(SessionType::User, user_session_sequence_number)
+    }
+
+    fn network_key_id(key_id: &[u8]) -> Result<ObjectID, Error> {
+        ObjectID::from_bytes(key_id).map_err(|e| abort(e.to_string()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn respond_network_encryption_key_dkg(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+        key_id: &[u8],
+        public_output_chunk: &[u8],
+        supported_curves: &[u32],
+        is_last_chunk: bool,
+        rejected: bool,
+        session_sequence_number: u64,
+    ) -> Result<(), Error> {
+        let key_id = Self::network_key_id(key_id)?;
+        let Some(mut key) = self.network_keys.remove(&key_id) else {
+            return Err(abort(format!(
+                "network encryption key {key_id} doesn't exist"
+            )));
+        };
+        let result = (|| {
+            if key.state != DWalletNetworkEncryptionKeyState::AwaitingNetworkDKG {
+                return Err(abort("wrong network encryption key state"));
+            }
+            if rejected {
+                self.complete_session(SessionType::System, session_sequence_number)?;
+                self.clear_table_vec(&mut key.network_dkg_public_output);
+                self.initiate_session(
+                    network_config,
+                    digest,
+                    SessionType::System,
+                    DWalletNetworkDKGEncryptionKeyRequestEvent {
+                        dwallet_network_encryption_key_id: key_id,
+                        params_for_network: key.dkg_params_for_network.clone(),
+                    },
+                );
+                return Ok(());
+            }
+            if is_last_chunk {
+                self.complete_session(SessionType::System, session_sequence_number)?;
+                key.supported_curves = supported_curves.to_vec();
+                key.state = DWalletNetworkEncryptionKeyState::NetworkDKGCompleted;
+                info!(
+                    ?key_id,
+                    "Network DKG completed in the simulated coordinator"
+                );
+            }
+            self.push_back_chunk(&mut key.network_dkg_public_output, public_output_chunk);
+            Ok(())
+        })();
+        self.network_keys.insert(key_id, key);
+
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn respond_network_encryption_key_reconfiguration(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+        key_id: &[u8],
+        public_output_chunk: &[u8],
+        supported_curves: &[u32],
+        is_last_chunk: bool,
+        rejected: bool,
+        session_sequence_number: u64,
+    ) -> Result<(), Error> {
+        let key_id = Self::network_key_id(key_id)?;
+        let Some(mut key) = self.network_keys.remove(&key_id) else {
+            return Err(abort(format!(
+                "network encryption key {key_id} doesn't exist"
+            )));
+        };
+        let next_epoch = self.coordinator.current_epoch + 1;
+        let result = (|| {
+            if key.state != DWalletNetworkEncryptionKeyState::AwaitingNetworkReconfiguration {
+                return Err(abort("wrong network encryption key state"));
+            }
+            let Some(output_id) = self
+                .tables_by_epoch
+                .get(&key.reconfiguration_public_outputs.id)
+                .and_then(|outputs| outputs.get(&next_epoch))
+                .copied()
+            else {
+                return Err(abort(
+                    "the reconfiguration output of the next epoch doesn't exist",
+                ));
+            };
+            if rejected {
+                self.complete_session(SessionType::System, session_sequence_number)?;
+                self.table_vecs.insert(output_id, vec![]);
+                self.initiate_session(
+                    network_config,
+                    digest,
+                    SessionType::System,
+                    DWalletEncryptionKeyReconfigurationRequestEvent {
+                        dwallet_network_encryption_key_id: key_id,
+                    },
+                );
+                return Ok(());
+            }
+            if is_last_chunk {
+                self.coordinator
+                    .epoch_dwallet_network_encryption_keys_reconfiguration_completed += 1;
+                self.complete_session(SessionType::System, session_sequence_number)?;
+                key.supported_curves = supported_curves.to_vec();
+                key.state = DWalletNetworkEncryptionKeyState::NetworkReconfigurationCompleted;
+                info!(
+                    ?key_id,
+                    "Network reconfiguration completed in the simulated coordinator"
+                );
+            }
+            self.table_vecs
+                .entry(output_id)
+                .or_default()
+                .extend_from_slice(public_output_chunk);
+            Ok(())
+        })();
+        self.network_keys.insert(key_id, key);
+
+        result
+    }
+
+    fn request_network_encryption_key_reconfiguration(
+        &mut self,
+        network_config: &IkaNetworkConfig,
+        digest: TransactionDigest,
+        key_id: ObjectID,
+    ) -> Result<(), Error> {
+        if self.coordinator.next_epoch_active_committee.is_none() {
+            return Err(abort("mid-epoch reconfiguration wasn't initiated"));
+        }
+        let next_epoch = self.coordinator.current_epoch + 1;
+        let output = self.new_table_vec();
+        let Some(key) = self.network_keys.get_mut(&key_id) else {
+            return Err(abort(format!(
+                "network encryption key {key_id} doesn't exist"
+            )));
+        };
+        key.state = match key.state {
+            DWalletNetworkEncryptionKeyState::NetworkDKGCompleted
+            | DWalletNetworkEncryptionKeyState::NetworkReconfigurationCompleted => {
+                DWalletNetworkEncryptionKeyState::AwaitingNetworkReconfiguration
+            }
+            _ => return Err(abort("wrong network encryption key state")),
+        };
+        key.reconfiguration_public_outputs.size += 1;
+        self.tables_by_epoch
+            .entry(key.reconfiguration_public_outputs.id)
+            .or_default()
+            .insert(next_epoch, output.contents.id);
+
+        self.initiate_session(
+            network_config,
+            digest,
+            SessionType::System,
+            DWalletEncryptionKeyReconfigurationRequestEvent {
+                dwallet_network_encryption_key_id: key_id,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn process_system_checkpoint_message_by_quorum(
+        &mut self,
+        signature: &[u8],
+        signers_bitmap: &[u8],
+        message: Vec<u8>,
+    ) -> Result<(), Error> {
+        Self::verify_certificate(
+            &self.system.validator_set.active_committee,
+            self.system.epoch,
+            SYSTEM_CHECKPOINT_MESSAGE_INTENT,
+            signature,
+            signers_bitmap,
+            &message,
+        )?;
+        let message: SystemCheckpointMessage =
+            bcs::from_bytes(&message).map_err(|e| abort(e.to_string()))?;
+        if message.epoch != self.system.epoch {
+            return Err(abort("incorrect epoch in system checkpoint"));
+        }
+        if self.system.last_processed_checkpoint_sequence_number + 1 != message.sequence_number {
+            return Err(abort("wrong system checkpoint sequence number"));
+        }
+        self.system.last_processed_checkpoint_sequence_number = message.sequence_number;
+        self.system.total_messages_processed += message.messages.len() as u64;
+
+        for kind in message.messages {
+            match kind {
+                SystemCheckpointMessageKind::SetNextConfigVersion(version) => {
+                    self.system.next_protocol_version = Some(version.as_u64());
+                }
+                SystemCheckpointMessageKind::SetEpochDurationMs(epoch_duration_ms) => {
+                    self.system.epoch_duration_ms = epoch_duration_ms;
+                }
+                SystemCheckpointMessageKind::EndOfPublish => {
+                    self.system.received_end_of_publish = true;
+                }
+                // The validator set and the treasury are not simulated.
+                kind => {
+                    info!(
+                        ?kind,
+                        "Ignoring a system checkpoint message in the simulated coordinator"
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn verify_can_advance_epoch(&self) -> Result<(), Error> {
+        if now_ms() < self.system.epoch_start_timestamp_ms + self.system.epoch_duration_ms {
+            return Err(abort("the epoch hasn't ended"));
+        }
+        if !self.system.received_end_of_publish || !self.coordinator.received_end_of_publish {
+            return Err(abort("cannot advance the epoch before the end of publish"));
+        }
+
+        Ok(())
+    }
+
+    fn advance_coordinator_epoch(&mut self) -> Result<(), Error> {
+        let coordinator = &mut self.coordinator;
+        if coordinator
+            .pricing_and_fee_management
+            .calculation_votes
+            .is_some()
+        {
+            return Err(abort("the pricing calculation votes must be completed"));
+        }
+        if coordinator.epoch_dwallet_network_encryption_keys_reconfiguration_completed
+            != coordinator.dwallet_network_encryption_keys.size
+        {
+            return Err(abort("not all network encryption keys were reconfigured"));
+        }
+        let sessions_manager = &coordinator.sessions_manager;
+        if !sessions_manager.locked_last_user_initiated_session_to_complete_in_current_epoch
+            || sessions_manager
+                .user_sessions_keeper
+                .completed_sessions_count
+                != sessions_manager.last_user_initiated_session_to_complete_in_current_epoch
+            || sessions_manager
+                .system_sessions_keeper
+                .completed_sessions_count
+                != sessions_manager
+                    .system_sessions_keeper
+                    .started_sessions_count
+        {
+            return Err(abort("not all the sessions of the epoch were completed"));
+        }
+        let Some(next_epoch_active_committee) = coordinator.next_epoch_active_committee.take()
+        else {
+            return Err(abort("mid-epoch reconfiguration wasn't initiated"));
+        };
+
+        coordinator.received_end_of_publish = false;
+        coordinator.epoch_dwallet_network_encryption_keys_reconfiguration_completed = 0;
+        coordinator.previous_epoch_last_checkpoint_sequence_number =
+            coordinator.last_processed_checkpoint_sequence_number;
+        coordinator
+            .sessions_manager
+            .locked_last_user_initiated_session_to_complete_in_current_epoch = false;
+        coordinator.current_epoch += 1;
+        coordinator.active_committee = next_epoch_active_committee;
+        self.update_last_user_initiated_session_to_complete_in_current_epoch();
+
+        Ok(())
+    }
+
+    fn advance_system_epoch(&mut self, digest: TransactionDigest) -> Result<(), Error> {
+        self.verify_can_advance_epoch()?;
+        let system = &mut self.system;
+        let Some(next_epoch_committee) = system.validator_set.next_epoch_committee.take() else {
+            return Err(abort("mid-epoch reconfiguration wasn't initiated"));
+        };
+
+        system.received_end_of_publish = false;
+        system.epoch_start_tx_digest = digest.into_inner().to_vec();
+        system.epoch_start_timestamp_ms = now_ms();
+        system.previous_epoch_last_checkpoint_sequence_number =
+            system.last_processed_checkpoint_sequence_number;
+        system.epoch += 1;
+        if let Some(next_protocol_version) = system.next_protocol_version.take() {
+            system.protocol_version = next_protocol_version;
+        }
+        system.validator_set.previous_committee = std::mem::replace(
+            &mut system.validator_set.active_committee,
+            next_epoch_committee,
+        );
+        for validator in self.validators.values_mut() {
+            validator.latest_epoch = system.epoch;
+        }
+        info!(
+            epoch = system.epoch,
+            "Advanced the epoch in the simulated coordinator"
+        );
+
+        Ok(())
+    }
+
+    /// The events of pending sessions, as stored in the session events bag `events_bag_id`.
+    fn uncompleted_events(&self, events_bag_id: ObjectID) -> Vec<DBSuiEvent> {
+        let sessions_manager = &self.coordinator.sessions_manager;
+        let pending_sessions = if events_bag_id
+            == sessions_manager
+                .user_sessions_keeper
+                .session_events
+                .id
+                .id
+                .bytes
+        {
+            &self.pending_user_sessions
+        } else if events_bag_id
+            == sessions_manager
+                .system_sessions_keeper
+                .session_events
+                .id
+                .id
+                .bytes
+        {
+            &self.pending_system_sessions
+        } else {
+            return vec![];
+        };
+
+        pending_sessions
+            .values()
+            .map(|session| {
+                // The BCS of a `Field<ID, DWalletSessionEvent<E>>`.
+                let mut contents = bcs::to_bytes(&(
+                    UID::new(ObjectID::random()),
+                    ID::new(session.session_object_id),
+                ))
+                .expect("serializing IDs should never fail");
+                contents.extend_from_slice(&session.contents);
+
+                DBSuiEvent {
+                    type_: session.type_.clone(),
+                    contents,
+                    pulled: true,
+                }
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl SuiClientInner for SimulatedCoordinator {
+    type Error = Error;
+
+    async fn query_events(
+        &self,
+        query: EventFilter,
+        cursor: Option<EventID>,
+    ) -> Result<EventPage, Self::Error> {
+        let state = self.state.lock().unwrap();
+        // The cursor is exclusive.
+        let start = match cursor {
+            None => 0,
+            Some(cursor) => match state.events.iter().position(|event| event.id == cursor) {
+                Some(position) => position + 1,
+                None => state
+                    .transactions
+                    .get(&cursor.tx_digest)
+                    .copied()
+                    .unwrap_or_default(),
+            },
+        };
+        let matches = |event: &SuiEvent| match &query {
+            EventFilter::MoveEventModule { package, module } => {
+                event.package_id == *package && event.type_.module == *module
+            }
+            _ => true,
+        };
+        let mut data: Vec<SuiEvent> = state.events[start.min(state.events.len())..]
+            .iter()
+            .filter(|event| matches(event))
+            .cloned()
+            .collect();
+        let has_next_page = data.len() > EVENTS_PAGE_SIZE;
+        data.truncate(EVENTS_PAGE_SIZE);
+        let next_cursor = data.last().map(|event| event.id).or(cursor);
+
+        Ok(EventPage {
+            data,
+            next_cursor,
+            has_next_page,
+        })
+    }
+
+    async fn get_events_by_tx_digest(
+        &self,
+        tx_digest: TransactionDigest,
+    ) -> Result<Vec<SuiEvent>, Self::Error> {
+        let state = self.state.lock().unwrap();
+        let Some(start) = state.transactions.get(&tx_digest) else {
+            return Err(abort(format!("transaction {tx_digest} was not executed")));
+        };
+
+        Ok(state.events[*start..]
+            .iter()
+            .filter(|event| event.id.tx_digest == tx_digest)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_chain_identifier(&self) -> Result<String, Self::Error> {
+        Ok(SIMULATED_CHAIN_IDENTIFIER.to_string())
+    }
+
+    async fn get_reference_gas_price(&self) -> Result<u64, Self::Error> {
+        Ok(REFERENCE_GAS_PRICE)
+    }
+
+    async fn get_latest_checkpoint_sequence_number(&self) -> Result<u64, Self::Error> {
+        Ok(self.state.lock().unwrap().transactions.len() as u64)
+    }
+
+    async fn get_system(&self, ika_system_object_id: ObjectID) -> Result<Vec<u8>, Self::Error> {
+        if ika_system_object_id != self.network_config.objects.ika_system_object_id {
+            return Err(abort(format!(
+                "object {ika_system_object_id} doesn't exist"
+            )));
+        }
+        to_bcs(&System {
+            id: ika_system_object_id,
+            version: INNER_OBJECT_VERSION,
+            package_id: self.network_config.packages.ika_system_package_id,
+            new_package_id: None,
+            migration_epoch: None,
+        })
+    }
+
+    async fn get_clock(&self, _clock_obj_id: ObjectID) -> Result<Vec<u8>, Self::Error> {
+        to_bcs(&Clock {
+            id: UID::new(ObjectID::from_single_byte(6)),
+            timestamp_ms: now_ms(),
+        })
+    }
+
+    async fn get_dwallet_coordinator(
+        &self,
+        dwallet_coordinator_id: ObjectID,
+    ) -> Result<Vec<u8>, Self::Error> {
+        if dwallet_coordinator_id
+            != self
+                .network_config
+                .objects
+                .ika_dwallet_coordinator_object_id
+        {
+            return Err(abort(format!(
+                "object {dwallet_coordinator_id} doesn't exist"
+            )));
+        }
+        to_bcs(&DWalletCoordinator {
+            id: dwallet_coordinator_id,
+            version: INNER_OBJECT_VERSION,
+            package_id: self.network_config.packages.ika_dwallet_2pc_mpc_package_id,
+            new_package_id: None,
+            migration_epoch: None,
+        })
+    }
+
+    async fn get_mpc_data_from_validators_pool(
+        &self,
+        validators: &Vec<StakingPool>,
+        read_next_epoch_mpc_data: bool,
+    ) -> Result<HashMap<ObjectID, VersionedMPCData>, Self::Error> {
+        let state = self.state.lock().unwrap();
+        validators
+            .iter()
+            .map(|validator| {
+                let info = &validator.validator_info;
+                let mpc_data_bytes = if read_next_epoch_mpc_data {
+                    info.next_epoch_mpc_datd_bytes
+                        .as_ref()
+                        .or(info.mpc_data_bytes.as_ref())
+                } else {
+                    info.mpc_data_bytes.as_ref()
+                };
+                let mpc_data = mpc_data_bytes
+                    .and_then(|table_vec| state.table_vecs.get(&table_vec.contents.id))
+                    .ok_or_else(|| abort(format!("validator {} has no MPC data", validator.id)))?;
+
+                Ok((
+                    validator.id,
+                    bcs::from_bytes(mpc_data).map_err(|e| abort(e.to_string()))?,
+                ))
+            })
+            .collect()
+    }
+
+    async fn get_network_encryption_keys(
+        &self,
+        _dwallet_coordinator_inner: &DWalletCoordinatorInnerV1,
+    ) -> Result<HashMap<ObjectID, DWalletNetworkEncryptionKey>, Self::Error> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .network_keys
+            .iter()
+            .map(|(key_id, key)| (*key_id, key.clone()))
+            .collect())
+    }
+
+    async fn get_network_encryption_key_with_full_data_by_epoch(
+        &self,
+        key: &DWalletNetworkEncryptionKey,
+        epoch: EpochId,
+    ) -> Result<DWalletNetworkEncryptionKeyData, Self::Error> {
+        let network_dkg_public_output = self
+            .read_table_vec_as_raw_bytes(key.network_dkg_public_output.contents.id)
+            .await?;
+        // There is no reconfiguration output in the epoch of the network DKG.
+        let current_reconfiguration_public_output = if key.dkg_at_epoch == epoch {
+            vec![]
+        } else {
+            let output_id = self
+                .get_current_reconfiguration_public_output(
+                    epoch,
+                    key.reconfiguration_public_outputs.id,
+                )
+                .await?;
+            self.read_table_vec_as_raw_bytes(output_id).await?
+        };
+
+        Ok(DWalletNetworkEncryptionKeyData {
+            id: key.id,
+            current_epoch: epoch,
+            current_reconfiguration_public_output,
+            network_dkg_public_output,
+            state: key.state.clone(),
+        })
+    }
+
+    async fn get_current_reconfiguration_public_output(
+        &self,
+        epoch_id: EpochId,
+        table_id: ObjectID,
+    ) -> Result<ObjectID, Self::Error> {
+        self.state
+            .lock()
+            .unwrap()
+            .tables_by_epoch
+            .get(&table_id)
+            .and_then(|outputs| outputs.get(&epoch_id))
+            .copied()
+            .ok_or_else(|| {
+                abort(format!(
+                    "Failed to load current reconfiguration public output for epoch {epoch_id:?} from table {table_id:?}"
+                ))
+            })
+    }
+
+    async fn read_table_vec_as_raw_bytes(
+        &self,
+        table_id: ObjectID,
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.state
+            .lock()
+            .unwrap()
+            .table_vecs
+            .get(&table_id)
+            .cloned()
+            .ok_or_else(|| abort(format!("table {table_id} doesn't exist")))
+    }
+
+    async fn get_system_inner(
+        &self,
+        ika_system_object_id: ObjectID,
+        version: u64,
+    ) -> Result<Vec<u8>, Self::Error> {
+        if version != INNER_OBJECT_VERSION {
+            return Err(abort(format!(
+                "Failed to load ika system state inner object with ID {ika_system_object_id:?} and version {version:?}"
+            )));
+        }
+        let state = self.state.lock().unwrap();
+        to_bcs(&Field {
+            id: UID::new(ika_system_object_id),
+            name: version,
+            value: &state.system,
+        })
+    }
+
+    async fn get_dwallet_coordinator_inner(
+        &self,
+        dwallet_coordinator_id: ObjectID,
+        version: u64,
+    ) -> Result<Vec<u8>, Self::Error> {
+        if version != INNER_OBJECT_VERSION {
+            return Err(abort(format!(
+                "Failed to load DWalletCoordinatorInner object with ID {dwallet_coordinator_id:?} and version {version:?}"
+            )));
+        }
+        let state = self.state.lock().unwrap();
+        to_bcs(&Field {
+            id: UID::new(dwallet_coordinator_id),
+            name: version,
+            value: &state.coordinator,
+        })
+    }
+
+    async fn get_validators(
+        &self,
+        validator_ids: Vec<ObjectID>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let state = self.state.lock().unwrap();
+        validator_ids
+            .iter()
+            .map(|validator_id| {
+                let validator = state
+                    .validators
+                    .get(validator_id)
+                    .ok_or_else(|| abort(format!("validator {validator_id} doesn't exist")))?;
+                to_bcs(validator)
+            })
+            .collect()
+    }
+
+    async fn get_validator_inners(
+        &self,
+        _validators: Vec<Validator>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Err(abort(
+            "validators are staking pools without inner objects in the simulated coordinator",
+        ))
+    }
+
+    async fn get_mutable_shared_arg(
+        &self,
+        ika_system_object_id: ObjectID,
+    ) -> Result<ObjectArg, Self::Error> {
+        Ok(ObjectArg::SharedObject {
+            id: ika_system_object_id,
+            initial_shared_version: SequenceNumber::from_u64(1),
+            mutable: true,
+        })
+    }
+
+    async fn get_shared_arg(&self, obj_id: ObjectID) -> Result<ObjectArg, Self::Error> {
+        Ok(ObjectArg::SharedObject {
+            id: obj_id,
+            initial_shared_version: SequenceNumber::from_u64(1),
+            mutable: false,
+        })
+    }
+
+    async fn get_available_move_packages(
+        &self,
+        ika_package_id: ObjectID,
+        ika_system_package_id: ObjectID,
+    ) -> Result<Vec<(ObjectID, MovePackageDigest)>, Self::Error> {
+        built_in_move_package_digests(ika_package_id, ika_system_package_id)
+    }
+
+    async fn execute_transaction_block_with_effects(
+        &self,
+        tx: Transaction,
+    ) -> Result<SuiTransactionBlockResponse, IkaError> {
+        let digest = *tx.digest();
+        let status = self.execute(&tx);
+        let events = self
+            .get_events_by_tx_digest(digest)
+            .await
+            .map_err(|e| IkaError::SuiClientTxFailureGeneric(digest, e.to_string()))?;
+
+        let mut response = SuiTransactionBlockResponse::new(digest);
+        response.effects = Some(SuiTransactionBlockEffects::new_for_testing(digest, status));
+        response.events = Some(SuiTransactionBlockEvents { data: events });
+        Ok(response)
+    }
+
+    async fn get_gas_objects(&self, address: SuiAddress) -> Vec<ObjectRef> {
+        // Gas is not charged, so every address owns a single, unchanging gas coin.
+        let gas_object = *self
+            .state
+            .lock()
+            .unwrap()
+            .gas_objects
+            .entry(address)
+            .or_insert_with(|| {
+                (
+                    ObjectID::random(),
+                    SequenceNumber::from_u64(1),
+                    ObjectDigest::random(),
+                )
+            });
+
+        vec![gas_object]
+    }
+
+    async fn get_uncompleted_events(
+        &self,
+        events_bag_id: ObjectID,
+    ) -> Result<Vec<DBSuiEvent>, Self::Error> {
+        Ok(self.state.lock().unwrap().uncompleted_events(events_bag_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinator() -> SimulatedCoordinator {
+        SimulatedCoordinator::new(&[], &InitiationParameters::default())
+    }
+
+    #[tokio::test]
+    async fn test_genesis_requests_network_dkg() {
+        let coordinator = coordinator();
+        let keys = coordinator.network_encryption_keys();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(
+            keys[0].state,
+            DWalletNetworkEncryptionKeyState::AwaitingNetworkDKG
+        );
+
+        // The request is both emitted as an event and kept as an uncompleted event.
+        let events = coordinator
+            .query_events(
+                EventFilter::MoveEventModule {
+                    package: coordinator
+                        .network_config
+                        .packages
+                        .ika_dwallet_2pc_mpc_package_id,
+                    module: SESSIONS_MANAGER_MODULE_NAME.to_owned(),
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(events.data.len(), 1);
+        let system_events_bag_id = coordinator
+            .state
+            .lock()
+            .unwrap()
+            .coordinator
+            .sessions_manager
+            .system_sessions_keeper
+            .session_events
+            .id
+            .id
+            .bytes;
+        let uncompleted_events = coordinator
+            .get_uncompleted_events(system_events_bag_id)
+            .await
+            .unwrap();
+        assert_eq!(uncompleted_events.len(), 1);
+        assert_eq!(
+            uncompleted_events[0].type_,
+            DWalletSessionEvent::<DWalletNetworkDKGEncryptionKeyRequestEvent>::type_(
+                coordinator.network_config()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_events_cursor_is_exclusive() {
+        let coordinator = coordinator();
+        let key_id = coordinator.network_encryption_keys()[0].id;
+        for _ in 0..3 {
+            coordinator.request_session(DWalletEncryptionKeyReconfigurationRequestEvent {
+                dwallet_network_encryption_key_id: key_id,
+            });
+        }
+        let filter = EventFilter::MoveEventModule {
+            package: coordinator
+                .network_config
+                .packages
+                .ika_dwallet_2pc_mpc_package_id,
+            module: SESSIONS_MANAGER_MODULE_NAME.to_owned(),
+        };
+
+        let first_page = coordinator
+            .query_events(filter.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(first_page.data.len(), 4);
+        let next_page = coordinator
+            .query_events(
+                filter.clone(),
+                first_page.data.first().map(|event| event.id),
+            )
+            .await
+            .unwrap();
+        assert_eq!(next_page.data.len(), 3);
+        let last_page = coordinator
+            .query_events(filter, first_page.next_cursor)
+            .await
+            .unwrap();
+        assert!(last_page.data.is_empty());
+        assert_eq!(last_page.next_cursor, first_page.next_cursor);
+
+        let sessions_manager = coordinator
+            .state
+            .lock()
+            .unwrap()
+            .coordinator
+            .sessions_manager
+            .clone();
+        assert_eq!(
+            sessions_manager.user_sessions_keeper.started_sessions_count,
+            3
+        );
+        assert_eq!(
+            sessions_manager.last_user_initiated_session_to_complete_in_current_epoch,
+            3
+        );
+    }
+}
@@ -19,6 +19,10 @@ use rand::rngs::OsRng;
 use std::path::PathBuf;
 use std::{num::NonZeroUsize, path::Path, sync::Arc};
 use sui_macros::nondeterministic;
+#[cfg(feature = "simulated-coordinator")]
+use sui_types::base_types::ObjectID;
+#[cfg(feature = "simulated-coordinator")]
+use sui_types::crypto::SuiKeyPair;
 
 pub enum CommitteeConfig {
     Size(NonZeroUsize),
@@ -75,6 +79,8 @@ pub struct ConfigBuilder<R = OsRng> {
     // Default to supported_protocol_versions_config, but can be overridden.
     fullnode_supported_protocol_versions_config: Option<ProtocolVersionsConfig>,
     fullnode_run_with_range: Option<RunWithRange>,
+    #[cfg(feature = "simulated-coordinator")]
+    simulated_sui: bool,
 }
 
 impl ConfigBuilder {
@@ -101,6 +107,8 @@ impl ConfigBuilder {
             fullnode_count: 0,
             fullnode_supported_protocol_versions_config: None,
             fullnode_run_with_range: None,
+            #[cfg(feature = "simulated-coordinator")]
+            simulated_sui: false,
         }
     }
 
@@ -222,6 +230,19 @@ impl<R> ConfigBuilder<R> {
         self
     }
 
+    /// Run the network against an in-process [`SimulatedCoordinator`] instead of Sui.
+    ///
+    /// The coordinator is registered when the network config is built,
+    /// and can be retrieved with [`simulated_coordinator::registered`].
+    ///
+    /// [`SimulatedCoordinator`]: ika_sui_client::simulated_coordinator::SimulatedCoordinator
+    /// [`simulated_coordinator::registered`]: ika_sui_client::simulated_coordinator::registered
+    #[cfg(feature = "simulated-coordinator")]
+    pub fn with_simulated_sui(mut self) -> Self {
+        self.simulated_sui = true;
+        self
+    }
+
     pub fn rng<N: rand::RngCore + rand::CryptoRng>(self, rng: N) -> ConfigBuilder<N> {
         ConfigBuilder {
             rng: Some(rng),
@@ -240,6 +261,8 @@ impl<R> ConfigBuilder<R> {
             fullnode_supported_protocol_versions_config: self
                 .fullnode_supported_protocol_versions_config,
             fullnode_run_with_range: self.fullnode_run_with_range,
+            #[cfg(feature = "simulated-coordinator")]
+            simulated_sui: self.simulated_sui,
         }
     }
 }
@@ -324,21 +347,40 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
         if let Some(protocol_version) = self.protocol_version {
             initiation_parameters.protocol_version = protocol_version.as_u64();
         }
+        #[cfg(feature = "simulated-coordinator")]
+        let simulated_sui = self.simulated_sui.then(|| {
+            Self::init_simulated_coordinator(
+                &validator_initialization_configs,
+                &initiation_parameters,
+                &mut rng,
+            )
+        });
+        #[cfg(not(feature = "simulated-coordinator"))]
+        let simulated_sui = None;
         let (
-            ika_package_id,
-            ika_common_package_id,
-            ika_dwallet_2pc_mpc_package_id,
-            ika_system_package_id,
-            ika_system_object_id,
-            ika_dwallet_coordinator_object_id,
-            publisher_keypair,
-        ) = crate::sui_client::init_ika_on_sui(
-            &validator_initialization_configs,
-            self.sui_fullnode_rpc_url.to_string(),
-            self.sui_faucet_url.to_string(),
-            initiation_parameters,
-        )
-        .await?;
+            sui_fullnode_rpc_url,
+            (
+                ika_package_id,
+                ika_common_package_id,
+                ika_dwallet_2pc_mpc_package_id,
+                ika_system_package_id,
+                ika_system_object_id,
+                ika_dwallet_coordinator_object_id,
+                publisher_keypair,
+            ),
+        ) = match simulated_sui {
+            Some(simulated_sui) => simulated_sui,
+            None => (
+                self.sui_fullnode_rpc_url.clone(),
+                crate::sui_client::init_ika_on_sui(
+                    &validator_initialization_configs,
+                    self.sui_fullnode_rpc_url.to_string(),
+                    self.sui_faucet_url.to_string(),
+                    initiation_parameters,
+                )
+                .await?,
+            ),
+        };
 
         let validator_configs = validator_initialization_configs
             .iter()
@@ -378,7 +420,7 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
 
                 builder.build(
                     validator,
-                    self.sui_fullnode_rpc_url.clone(),
+                    sui_fullnode_rpc_url.clone(),
                     ika_package_id,
                     ika_common_package_id,
                     ika_dwallet_2pc_mpc_package_id,
@@ -414,7 +456,7 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
                 let config = builder.build(
                     &mut OsRng,
                     &validator_initialization_configs,
-                    self.sui_fullnode_rpc_url.clone(),
+                    sui_fullnode_rpc_url.clone(),
                     ika_package_id,
                     ika_common_package_id,
                     ika_dwallet_2pc_mpc_package_id,
@@ -438,4 +480,58 @@ impl<R: rand::RngCore + rand::CryptoRng> ConfigBuilder<R> {
             ika_dwallet_coordinator_object_id,
         })
     }
+
+    /// Creates and registers a [`SimulatedCoordinator`] with the validators, in place of
+    /// publishing the Ika packages on Sui.
+    ///
+    /// Returns the RPC URL the nodes connect to it with, and what `init_ika_on_sui` returns.
+    ///
+    /// [`SimulatedCoordinator`]: ika_sui_client::simulated_coordinator::SimulatedCoordinator
+    #[cfg(feature = "simulated-coordinator")]
+    fn init_simulated_coordinator(
+        validator_initialization_configs: &[ValidatorInitializationConfig],
+        initiation_parameters: &InitiationParameters,
+        rng: &mut R,
+    ) -> (
+        String,
+        (
+            ObjectID,
+            ObjectID,
+            ObjectID,
+            ObjectID,
+            ObjectID,
+            ObjectID,
+            SuiKeyPair,
+        ),
+    ) {
+        use ika_sui_client::simulated_coordinator::{self, SimulatedCoordinator};
+
+        let validators = validator_initialization_configs
+            .iter()
+            .map(ValidatorInitializationConfig::to_validator_info)
+            .collect::<Vec<_>>();
+        let coordinator = Arc::new(SimulatedCoordinator::new(
+            &validators,
+            initiation_parameters,
+        ));
+        let rpc_url = coordinator.rpc_url();
+        let network_config = coordinator.network_config().clone();
+        simulated_coordinator::register(coordinator);
+
+        // Transactions are not charged for, so the notifier can use any key.
+        let (_, notifier_key_pair): (_, AccountKeyPair) = get_key_pair_from_rng(rng);
+
+        (
+            rpc_url,
+            (
+                network_config.packages.ika_package_id,
+                network_config.packages.ika_common_package_id,
+                network_config.packages.ika_dwallet_2pc_mpc_package_id,
+                network_config.packages.ika_system_package_id,
+                network_config.objects.ika_system_object_id,
+                network_config.objects.ika_dwallet_coordinator_object_id,
+                SuiKeyPair::Ed25519(notifier_key_pair),
+            ),
+        )
+    }
 }
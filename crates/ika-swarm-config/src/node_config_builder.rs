@@ -166,6 +166,10 @@ impl ValidatorConfigBuilder {
 
             authority_overload_config: self.authority_overload_config.unwrap_or_default(),
             run_with_range: None,
+            pricing_recommendation_config: None,
+            archive_bootstrap_config: None,
+            checkpoint_output_index_config: None,
+            key_custody_config: None,
         }
     }
 
@@ -381,6 +385,10 @@ impl FullnodeConfigBuilder {
             state_archive_read_config: vec![],
             authority_overload_config: Default::default(),
             run_with_range: self.run_with_range,
+            pricing_recommendation_config: None,
+            archive_bootstrap_config: None,
+            checkpoint_output_index_config: None,
+            key_custody_config: None,
         }
     }
 }
@@ -8,11 +8,12 @@ use ika_config::validator_info::ValidatorInfo;
 use ika_move_packages::save_contracts_to_temp_dir;
 use ika_types::ika_coin::IKACoin;
 use ika_types::messages_dwallet_mpc::{
-    DKG_FIRST_ROUND_PROTOCOL_FLAG, DKG_SECOND_ROUND_PROTOCOL_FLAG, FUTURE_SIGN_PROTOCOL_FLAG,
+    DKG_FIRST_ROUND_PROTOCOL_FLAG, DKG_SECOND_ROUND_PROTOCOL_FLAG,
+    DWALLET_KEY_REFRESH_PROTOCOL_FLAG, FUTURE_SIGN_PROTOCOL_FLAG,
     IMPORTED_KEY_DWALLET_VERIFICATION_PROTOCOL_FLAG, IkaNetworkConfig,
     MAKE_DWALLET_USER_SECRET_KEY_SHARE_PUBLIC_PROTOCOL_FLAG, PRESIGN_PROTOCOL_FLAG,
     RE_ENCRYPT_USER_SHARE_PROTOCOL_FLAG, SIGN_PROTOCOL_FLAG,
-    SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG,
+    SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG, THRESHOLD_DECRYPTION_PROTOCOL_FLAG,
 };
 use ika_types::sui::system_inner_v1::ValidatorCapV1;
 use ika_types::sui::{
@@ -505,6 +506,12 @@ pub async fn ika_system_initialize(
     let sign_with_partial_user_signature_protocol_flag = ptb.input(CallArg::Pure(
         bcs::to_bytes(&SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG)?,
     ))?;
+    let threshold_decryption_protocol_flag = ptb.input(CallArg::Pure(bcs::to_bytes(
+        &THRESHOLD_DECRYPTION_PROTOCOL_FLAG,
+    )?))?;
+    let dwallet_key_refresh_protocol_flag = ptb.input(CallArg::Pure(bcs::to_bytes(
+        &DWALLET_KEY_REFRESH_PROTOCOL_FLAG,
+    )?))?;
 
     let zero_price = ptb.input(CallArg::Pure(bcs::to_bytes(&0u64)?))?;
 
@@ -666,6 +673,38 @@ pub async fn ika_system_initialize(
         ],
     );
 
+    ptb.programmable_move_call(
+        ika_dwallet_2pc_mpc_package_id,
+        ident_str!("pricing").into(),
+        ident_str!("insert_or_update_pricing").into(),
+        vec![],
+        vec![
+            pricing,
+            zero,
+            none_option,
+            threshold_decryption_protocol_flag,
+            zero_price,
+            zero_price,
+            zero_price,
+        ],
+    );
+
+    ptb.programmable_move_call(
+        ika_dwallet_2pc_mpc_package_id,
+        ident_str!("pricing").into(),
+        ident_str!("insert_or_update_pricing").into(),
+        vec![],
+        vec![
+            pricing,
+            zero,
+            none_option,
+            dwallet_key_refresh_protocol_flag,
+            zero_price,
+            zero_price,
+            zero_price,
+        ],
+    );
+
     let supported_signature_algorithms_to_hash_schemes = ptb.programmable_move_call(
         SUI_FRAMEWORK_PACKAGE_ID,
         ident_str!("vec_map").into(),
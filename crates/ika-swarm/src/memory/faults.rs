@@ -0,0 +1,135 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Fault injection into the nodes of a [`Swarm`], to test the dWallet MPC protocols under
+//! network partitions, latency, crashes and byzantine validators.
+//!
+//! The faults are applied by the MPC service of every node, see
+//! [`ika_core::dwallet_mpc::fault_injection`] for how each of them is modeled.
+//! A node that reaches a scheduled crash halts, and is then stopped and restarted by the
+//! fault supervisor of the swarm.
+
+use super::{Node, Swarm};
+use ika_core::dwallet_mpc::fault_injection::{self, TriggeredCrash};
+use ika_types::crypto::AuthorityName;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+pub use fault_injection::{ByzantineMode, NodeFaults, ScheduledCrash};
+
+/// Carries out the crashes triggered in the nodes of a swarm.
+#[derive(Debug)]
+pub(super) struct FaultSupervisor {
+    nodes: Arc<Mutex<HashMap<AuthorityName, Arc<Node>>>>,
+    crash_sender: mpsc::UnboundedSender<TriggeredCrash>,
+    task: JoinHandle<()>,
+}
+
+pub(super) fn spawn_fault_supervisor(nodes: &HashMap<AuthorityName, Arc<Node>>) -> FaultSupervisor {
+    let (crash_sender, mut crash_receiver) = mpsc::unbounded_channel::<TriggeredCrash>();
+    for name in nodes.keys() {
+        fault_injection::set_crash_handler(*name, crash_sender.clone());
+    }
+    let nodes = Arc::new(Mutex::new(nodes.clone()));
+
+    let supervised_nodes = nodes.clone();
+    let task = tokio::spawn(async move {
+        while let Some(crash) = crash_receiver.recv().await {
+            let Some(node) = supervised_nodes.lock().unwrap().get(&crash.name).cloned() else {
+                continue;
+            };
+            tokio::spawn(async move {
+                info!(name=?crash.name, restart_after=?crash.restart_after, "crashing node");
+                // Stopping a node blocks until all of its tasks are shut down.
+                let stopped_node = node.clone();
+                if let Err(e) = tokio::task::spawn_blocking(move || stopped_node.stop()).await {
+                    error!(name=?crash.name, error=?e, "failed to stop a crashed node");
+                    return;
+                }
+                tokio::time::sleep(crash.restart_after).await;
+                if let Err(e) = node.start().await {
+                    error!(name=?crash.name, error=?e, "failed to restart a crashed node");
+                }
+            });
+        }
+    });
+
+    FaultSupervisor {
+        nodes,
+        crash_sender,
+        task,
+    }
+}
+
+impl FaultSupervisor {
+    pub(super) fn register(&self, name: AuthorityName, node: Arc<Node>) {
+        fault_injection::set_crash_handler(name, self.crash_sender.clone());
+        self.nodes.lock().unwrap().insert(name, node);
+    }
+
+    /// Stops carrying out crashes, and removes all the faults injected into `names`.
+    pub(super) fn shutdown<'a>(&self, names: impl Iterator<Item = &'a AuthorityName>) {
+        self.task.abort();
+        for name in names {
+            fault_injection::remove_node(name);
+        }
+    }
+}
+
+impl Swarm {
+    /// Partitions the MPC traffic between the nodes in `side_a` and the nodes in `side_b`,
+    /// until [`Swarm::heal_partitions`] is called.
+    pub fn partition(&self, side_a: &[AuthorityName], side_b: &[AuthorityName]) {
+        for name in side_a {
+            fault_injection::update_node_faults(*name, |faults| {
+                faults.partitioned_from.extend(side_b.iter().copied());
+            });
+        }
+    }
+
+    /// Removes all the partitions between the nodes of the swarm.
+    pub fn heal_partitions(&self) {
+        for name in self.nodes.keys() {
+            fault_injection::update_node_faults(*name, |faults| {
+                faults.partitioned_from.clear();
+            });
+        }
+    }
+
+    /// Delays every submission of the node `name` to consensus by `delay`,
+    /// or removes the delay if `None`.
+    pub fn set_consensus_delay(&self, name: &AuthorityName, delay: Option<Duration>) {
+        fault_injection::update_node_faults(*name, |faults| {
+            faults.consensus_submission_delay = delay;
+        });
+    }
+
+    /// Crashes the node `name` right before it sends its message of the scheduled MPC round,
+    /// and restarts it after `crash.restart_after`.
+    pub fn schedule_crash(&self, name: &AuthorityName, crash: ScheduledCrash) {
+        fault_injection::update_node_faults(*name, |faults| {
+            faults.scheduled_crashes.push(crash);
+        });
+    }
+
+    /// Makes the node `name` deviate from the MPC protocols in the way described by `mode`.
+    pub fn set_byzantine_mode(&self, name: &AuthorityName, mode: ByzantineMode) {
+        fault_injection::update_node_faults(*name, |faults| {
+            faults.byzantine_modes.insert(mode);
+        });
+    }
+
+    /// Returns the faults currently injected into the node `name`.
+    pub fn node_faults(&self, name: &AuthorityName) -> NodeFaults {
+        fault_injection::node_faults(name)
+    }
+
+    /// Removes all the faults injected into the node `name`.
+    pub fn clear_faults(&self, name: &AuthorityName) {
+        fault_injection::clear_node_faults(name);
+    }
+}
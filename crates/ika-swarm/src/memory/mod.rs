@@ -14,6 +14,11 @@ pub use node::{Node, RuntimeType};
 mod swarm;
 pub use swarm::{Swarm, SwarmBuilder};
 
+#[cfg(feature = "fault-injection")]
+mod faults;
+#[cfg(feature = "fault-injection")]
+pub use faults::{ByzantineMode, NodeFaults, ScheduledCrash};
+
 #[cfg(msim)]
 #[path = "./container-sim.rs"]
 mod container;
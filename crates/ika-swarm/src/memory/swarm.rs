@@ -7,6 +7,7 @@ use futures::future::try_join_all;
 use rand::rngs::OsRng;
 use std::collections::HashMap;
 use std::num::NonZeroUsize;
+use std::sync::Arc;
 use std::{
     ops,
     path::{Path, PathBuf},
@@ -46,6 +47,8 @@ pub struct SwarmBuilder<R = OsRng> {
     fullnode_run_with_range: Option<RunWithRange>,
     max_submit_position: Option<usize>,
     submit_delay_step_override_millis: Option<u64>,
+    #[cfg(feature = "simulated-coordinator")]
+    simulated_sui: bool,
 }
 
 impl SwarmBuilder {
@@ -65,6 +68,8 @@ impl SwarmBuilder {
             fullnode_run_with_range: None,
             max_submit_position: None,
             submit_delay_step_override_millis: None,
+            #[cfg(feature = "simulated-coordinator")]
+            simulated_sui: false,
         }
     }
 }
@@ -86,6 +91,8 @@ impl<R> SwarmBuilder<R> {
             fullnode_run_with_range: self.fullnode_run_with_range,
             max_submit_position: self.max_submit_position,
             submit_delay_step_override_millis: self.submit_delay_step_override_millis,
+            #[cfg(feature = "simulated-coordinator")]
+            simulated_sui: self.simulated_sui,
         }
     }
 
@@ -186,6 +193,15 @@ impl<R> SwarmBuilder<R> {
         self.submit_delay_step_override_millis = Some(submit_delay_step_override_millis);
         self
     }
+
+    /// Run the swarm against an in-process simulated coordinator instead of a local Sui network.
+    ///
+    /// Sessions are then requested with [`Swarm::simulated_coordinator`].
+    #[cfg(feature = "simulated-coordinator")]
+    pub fn with_simulated_sui(mut self) -> Self {
+        self.simulated_sui = true;
+        self
+    }
 }
 
 impl<R: rand::RngCore + rand::CryptoRng> SwarmBuilder<R> {
@@ -248,6 +264,11 @@ impl<R: rand::RngCore + rand::CryptoRng> SwarmBuilder<R> {
                 );
             }
 
+            #[cfg(feature = "simulated-coordinator")]
+            if self.simulated_sui {
+                config_builder = config_builder.with_simulated_sui();
+            }
+
             config_builder
                 .committee(self.committee)
                 .rng(self.rng)
@@ -267,7 +288,10 @@ impl<R: rand::RngCore + rand::CryptoRng> SwarmBuilder<R> {
                     "SwarmBuilder configuring validator with name {}",
                     config.protocol_public_key()
                 );
-                (config.protocol_public_key(), Node::new(config.to_owned()))
+                (
+                    config.protocol_public_key(),
+                    Arc::new(Node::new(config.to_owned())),
+                )
             })
             .collect();
 
@@ -278,14 +302,19 @@ impl<R: rand::RngCore + rand::CryptoRng> SwarmBuilder<R> {
             );
             nodes.insert(
                 fullnode_config.protocol_public_key(),
-                Node::new(fullnode_config.to_owned()),
+                Arc::new(Node::new(fullnode_config.to_owned())),
             );
         }
 
+        #[cfg(feature = "fault-injection")]
+        let fault_supervisor = super::faults::spawn_fault_supervisor(&nodes);
+
         Ok(Swarm {
             dir,
             network_config,
             nodes,
+            #[cfg(feature = "fault-injection")]
+            fault_supervisor,
         })
     }
 }
@@ -295,28 +324,41 @@ impl<R: rand::RngCore + rand::CryptoRng> SwarmBuilder<R> {
 pub struct Swarm {
     dir: SwarmDirectory,
     pub network_config: NetworkConfig,
-    nodes: HashMap<AuthorityName, Node>,
+    pub(super) nodes: HashMap<AuthorityName, Arc<Node>>,
+    /// Restarts the nodes that crashed at a scheduled point.
+    #[cfg(feature = "fault-injection")]
+    pub(super) fault_supervisor: super::faults::FaultSupervisor,
 }
 
 impl Drop for Swarm {
     fn drop(&mut self) {
-        self.nodes_iter_mut().for_each(|node| node.stop());
+        #[cfg(feature = "fault-injection")]
+        self.fault_supervisor.shutdown(self.nodes.keys());
+        self.all_nodes().for_each(|node| node.stop());
+        #[cfg(feature = "simulated-coordinator")]
+        ika_sui_client::simulated_coordinator::unregister(
+            &self.network_config.ika_system_object_id,
+        );
     }
 }
 
 impl Swarm {
-    fn nodes_iter_mut(&mut self) -> impl Iterator<Item = &mut Node> {
-        self.nodes.values_mut()
-    }
-
     /// Return a new Builder
     pub fn builder() -> SwarmBuilder {
         SwarmBuilder::new()
     }
 
+    /// The coordinator this Swarm runs against, if it was built [`SwarmBuilder::with_simulated_sui`].
+    #[cfg(feature = "simulated-coordinator")]
+    pub fn simulated_coordinator(
+        &self,
+    ) -> Option<Arc<ika_sui_client::simulated_coordinator::SimulatedCoordinator>> {
+        ika_sui_client::simulated_coordinator::registered(&self.network_config.ika_system_object_id)
+    }
+
     /// Start all nodes associated with this Swarm
     pub async fn launch(&mut self) -> Result<()> {
-        try_join_all(self.nodes_iter_mut().map(|node| node.start())).await?;
+        try_join_all(self.all_nodes().map(|node| node.start())).await?;
         tracing::info!("Successfully launched Swarm");
         Ok(())
     }
@@ -338,23 +380,18 @@ impl Swarm {
     }
 
     pub fn all_nodes(&self) -> impl Iterator<Item = &Node> {
-        self.nodes.values()
+        self.nodes.values().map(AsRef::as_ref)
     }
 
     pub fn node(&self, name: &AuthorityName) -> Option<&Node> {
-        self.nodes.get(name)
-    }
-
-    pub fn node_mut(&mut self, name: &AuthorityName) -> Option<&mut Node> {
-        self.nodes.get_mut(name)
+        self.nodes.get(name).map(AsRef::as_ref)
     }
 
     /// Return an iterator over shared references of all nodes that are set up as validators.
     /// This means that they have a consensus config. This however doesn't mean this validator is
     /// currently active (i.e. it's not necessarily in the validator set at the moment).
     pub fn validator_nodes(&self) -> impl Iterator<Item = &Node> {
-        self.nodes
-            .values()
+        self.all_nodes()
             .filter(|node| node.config().consensus_config.is_some())
     }
 
@@ -376,16 +413,17 @@ impl Swarm {
 
     /// Return an iterator over shared references of all Fullnodes.
     pub fn fullnodes(&self) -> impl Iterator<Item = &Node> {
-        self.nodes
-            .values()
+        self.all_nodes()
             .filter(|node| node.config().consensus_config.is_none())
     }
 
     pub async fn spawn_new_node(&mut self, config: NodeConfig) -> IkaNodeHandle {
         let name = config.protocol_public_key();
-        let node = Node::new(config);
+        let node = Arc::new(Node::new(config));
         node.start().await.unwrap();
         let handle = node.get_node_handle().unwrap();
+        #[cfg(feature = "fault-injection")]
+        self.fault_supervisor.register(name, node.clone());
         self.nodes.insert(name, node);
         handle
     }
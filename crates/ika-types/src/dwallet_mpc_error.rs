@@ -171,6 +171,36 @@ pub enum DwalletMPCError {
 
     #[error("checkpoint message is empty")]
     CheckpointMessageIsEmpty,
+
+    #[error("key custody error: {0}")]
+    KeyCustody(String),
+
+    #[error("both a root seed and a key custody process are configured")]
+    RootSeedConfiguredWithKeyCustody,
+
+    #[error("a key custody process is configured, but key custody is only supported on Unix")]
+    KeyCustodyUnsupported,
+
+    #[error("network key cache error: {0}")]
+    NetworkKeyCache(String),
+
+    #[error("batch sign request has no messages to sign")]
+    EmptySignBatch,
+
+    #[error("the signs of a batch advanced to different results")]
+    BatchSignResultsMismatch,
+
+    #[error("invalid key encrypted to the network encryption key: {0}")]
+    InvalidNetworkEncryptedKey(String),
+
+    #[error("threshold decryption failed: {0}")]
+    ThresholdDecryptionFailed(String),
+
+    #[error("dWallet key refresh failed: {0}")]
+    KeyRefreshFailed(String),
+
+    #[error("dWallet child key derivation failed: {0}")]
+    KeyDerivationFailed(String),
 }
 
 /// A wrapper type for the result of a runtime operation.
@@ -45,6 +45,38 @@ pub struct SignOutput {
     pub session_sequence_number: u64,
 }
 
+/// The output of a batch sign session, holding a signature for each sign in the batch,
+/// in the order of the request.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct BatchSignOutput {
+    pub dwallet_id: Vec<u8>,
+    pub batch_sign_id: Vec<u8>,
+    pub sign_ids: Vec<Vec<u8>>,
+    pub signatures: Vec<Vec<u8>>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+/// The output of a threshold decryption session: the key decrypted by the network,
+/// re-encrypted to the encryption key of the requester.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct ThresholdDecryptionOutput {
+    pub decryption_id: Vec<u8>,
+    pub re_encrypted_key: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+/// The output of a dWallet key refresh session: the refreshed public output of the dWallet.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct DWalletKeyRefreshOutput {
+    pub dwallet_id: Vec<u8>,
+    pub encrypted_user_secret_key_share_id: Vec<u8>,
+    pub public_output: Vec<u8>,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct EncryptedUserShareOutput {
     pub dwallet_id: Vec<u8>,
@@ -115,6 +147,12 @@ pub enum DWalletCheckpointMessageKind {
     SetMaxActiveSessionsBuffer(u64),
     SetGasFeeReimbursementSuiSystemCallValue(u64),
     EndOfPublish,
+    // Added after `EndOfPublish` so the tags of the existing kinds are unchanged.
+    RespondDWalletBatchSign(BatchSignOutput),
+    RespondThresholdDecryption(ThresholdDecryptionOutput),
+    RespondDWalletKeyRefresh(DWalletKeyRefreshOutput),
+    /// Removes the partial user signatures that expired by, or were cancelled in, the given epoch.
+    GarbageCollectFutureSigns(u64),
 }
 
 impl DWalletCheckpointMessageKind {
@@ -131,6 +169,11 @@ impl DWalletCheckpointMessageKind {
             }
             DWalletCheckpointMessageKind::RespondDWalletPresign(_) => "RespondDWalletPresign",
             DWalletCheckpointMessageKind::RespondDWalletSign(_) => "RespondDWalletSign",
+            DWalletCheckpointMessageKind::RespondDWalletBatchSign(_) => "RespondDWalletBatchSign",
+            DWalletCheckpointMessageKind::RespondThresholdDecryption(_) => {
+                "RespondThresholdDecryption"
+            }
+            DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(_) => "RespondDWalletKeyRefresh",
             DWalletCheckpointMessageKind::RespondDWalletPartialSignatureVerificationOutput(_) => {
                 "RespondDWalletPartialSignatureVerificationOutput"
             }
@@ -153,6 +196,9 @@ impl DWalletCheckpointMessageKind {
                 "SetGasFeeReimbursementSuiSystemCallValue"
             }
             DWalletCheckpointMessageKind::EndOfPublish => "EndOfPublish",
+            DWalletCheckpointMessageKind::GarbageCollectFutureSigns(_) => {
+                "GarbageCollectFutureSigns"
+            }
         }
     }
 
@@ -180,6 +226,15 @@ impl Display for DWalletCheckpointMessageKind {
             DWalletCheckpointMessageKind::RespondDWalletSign(_) => {
                 writeln!(writer, "MessageKind : RespondDwalletSign")?;
             }
+            DWalletCheckpointMessageKind::RespondDWalletBatchSign(_) => {
+                writeln!(writer, "MessageKind : RespondDWalletBatchSign")?;
+            }
+            DWalletCheckpointMessageKind::RespondThresholdDecryption(_) => {
+                writeln!(writer, "MessageKind : RespondThresholdDecryption")?;
+            }
+            DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(_) => {
+                writeln!(writer, "MessageKind : RespondDWalletKeyRefresh")?;
+            }
             DWalletCheckpointMessageKind::RespondDWalletEncryptedUserShare(_) => {
                 writeln!(writer, "MessageKind : RespondDwalletEncryptedUserShare")?;
             }
@@ -222,6 +277,9 @@ impl Display for DWalletCheckpointMessageKind {
             DWalletCheckpointMessageKind::EndOfPublish => {
                 writeln!(writer, "MessageKind : EndOfPublish")?;
             }
+            DWalletCheckpointMessageKind::GarbageCollectFutureSigns(epoch) => {
+                writeln!(writer, "MessageKind : GarbageCollectFutureSigns({epoch})")?;
+            }
         }
         write!(f, "{writer}")
     }
@@ -266,6 +324,27 @@ impl Debug for DWalletCheckpointMessageKind {
                     self.digest()
                 )?;
             }
+            DWalletCheckpointMessageKind::RespondDWalletBatchSign(_) => {
+                writeln!(
+                    writer,
+                    "MessageKind : RespondDWalletBatchSign {:?}",
+                    self.digest()
+                )?;
+            }
+            DWalletCheckpointMessageKind::RespondThresholdDecryption(_) => {
+                writeln!(
+                    writer,
+                    "MessageKind : RespondThresholdDecryption {:?}",
+                    self.digest()
+                )?;
+            }
+            DWalletCheckpointMessageKind::RespondDWalletKeyRefresh(_) => {
+                writeln!(
+                    writer,
+                    "MessageKind : RespondDWalletKeyRefresh {:?}",
+                    self.digest()
+                )?;
+            }
             DWalletCheckpointMessageKind::RespondDWalletEncryptedUserShare(_) => {
                 writeln!(
                     writer,
@@ -316,6 +395,9 @@ impl Debug for DWalletCheckpointMessageKind {
             DWalletCheckpointMessageKind::EndOfPublish => {
                 writeln!(writer, "MessageKind : EndOfPublish")?;
             }
+            DWalletCheckpointMessageKind::GarbageCollectFutureSigns(epoch) => {
+                writeln!(writer, "MessageKind : GarbageCollectFutureSigns({epoch})")?;
+            }
         }
         write!(f, "{writer}")
     }
@@ -32,6 +32,13 @@ pub const DWALLET_DKG_SECOND_ROUND_REQUEST_EVENT_STRUCT_NAME: &IdentStr =
 // TODO (#650): Rename Move structs
 pub const PRESIGN_REQUEST_EVENT_STRUCT_NAME: &IdentStr = ident_str!("PresignRequestEvent");
 pub const SIGN_REQUEST_EVENT_STRUCT_NAME: &IdentStr = ident_str!("SignRequestEvent");
+pub const DERIVED_KEY_SIGN_REQUEST_EVENT_STRUCT_NAME: &IdentStr =
+    ident_str!("DerivedKeySignRequestEvent");
+pub const BATCH_SIGN_REQUEST_EVENT_STRUCT_NAME: &IdentStr = ident_str!("BatchSignRequestEvent");
+pub const THRESHOLD_DECRYPTION_REQUEST_EVENT_STRUCT_NAME: &IdentStr =
+    ident_str!("ThresholdDecryptionRequestEvent");
+pub const DWALLET_KEY_REFRESH_REQUEST_EVENT_STRUCT_NAME: &IdentStr =
+    ident_str!("DWalletKeyRefreshRequestEvent");
 pub const LOCKED_NEXT_COMMITTEE_EVENT_STRUCT_NAME: &IdentStr =
     ident_str!("LockedNextEpochCommitteeEvent");
 pub const VALIDATOR_DATA_FOR_SECRET_SHARE_STRUCT_NAME: &IdentStr =
@@ -42,6 +49,9 @@ pub const NETWORK_ENCRYPTION_KEY_RECONFIGURATION_STR_KEY: &str =
     "NetworkEncryptionKeyReconfiguration";
 pub const NETWORK_ENCRYPTION_KEY_DKG_STR_KEY: &str = "NetworkEncryptionKeyDkg";
 pub const SIGN_STR_KEY: &str = "Sign";
+pub const BATCH_SIGN_STR_KEY: &str = "BatchSign";
+pub const THRESHOLD_DECRYPTION_STR_KEY: &str = "ThresholdDecryption";
+pub const DWALLET_KEY_REFRESH_STR_KEY: &str = "DWalletKeyRefresh";
 
 pub const DKG_FIRST_ROUND_PROTOCOL_FLAG: u32 = 0;
 pub const DKG_SECOND_ROUND_PROTOCOL_FLAG: u32 = 1;
@@ -52,6 +62,8 @@ pub const PRESIGN_PROTOCOL_FLAG: u32 = 5;
 pub const SIGN_PROTOCOL_FLAG: u32 = 6;
 pub const FUTURE_SIGN_PROTOCOL_FLAG: u32 = 7;
 pub const SIGN_WITH_PARTIAL_USER_SIGNATURE_PROTOCOL_FLAG: u32 = 8;
+pub const THRESHOLD_DECRYPTION_PROTOCOL_FLAG: u32 = 9;
+pub const DWALLET_KEY_REFRESH_PROTOCOL_FLAG: u32 = 10;
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum MPCRequestInput {
@@ -78,6 +90,17 @@ pub enum MPCRequestInput {
     /// The first and only round of the Sign protocol.
     /// Contains all the data needed to sign the message.
     Sign(DWalletSessionEvent<SignRequestEvent>),
+    /// The Sign protocol with a child key of a dWallet,
+    /// whose public output is derived from that of the dWallet.
+    DerivedKeySign(DWalletSessionEvent<DerivedKeySignRequestEvent>),
+    /// The Sign protocol for a batch of messages of a single dWallet, each with its own presign,
+    /// run in lockstep in a single session.
+    BatchSign(DWalletSessionEvent<BatchSignRequestEvent>),
+    /// Threshold decryption of a key encrypted by a user to the network encryption key.
+    ThresholdDecryption(DWalletSessionEvent<ThresholdDecryptionRequestEvent>),
+    /// Refresh the secret key shares of a dWallet without changing its public key.
+    /// This is not a real MPC round, as every party computes the refreshed output locally.
+    DWalletKeyRefresh(DWalletSessionEvent<DWalletKeyRefreshRequestEvent>),
     /// The only round of the network DKG protocol.
     /// Contains the network key scheme, the dWallet network decryption key object ID
     /// and at the end of the session holds the new key version.
@@ -103,7 +126,12 @@ impl Display for MPCRequestInput {
             MPCRequestInput::DKGFirst(_) => write!(f, "dWalletDKGFirstRound"),
             MPCRequestInput::DKGSecond(_) => write!(f, "dWalletDKGSecondRound"),
             MPCRequestInput::Presign(_) => write!(f, "Presign"),
-            MPCRequestInput::Sign(_) => write!(f, "{SIGN_STR_KEY}"),
+            MPCRequestInput::Sign(_) | MPCRequestInput::DerivedKeySign(_) => {
+                write!(f, "{SIGN_STR_KEY}")
+            }
+            MPCRequestInput::BatchSign(_) => write!(f, "{BATCH_SIGN_STR_KEY}"),
+            MPCRequestInput::ThresholdDecryption(_) => write!(f, "{THRESHOLD_DECRYPTION_STR_KEY}"),
+            MPCRequestInput::DWalletKeyRefresh(_) => write!(f, "{DWALLET_KEY_REFRESH_STR_KEY}"),
             MPCRequestInput::NetworkEncryptionKeyDkg(_, _) => {
                 write!(f, "{NETWORK_ENCRYPTION_KEY_DKG_STR_KEY}")
             }
@@ -133,6 +161,10 @@ impl MPCRequestInput {
             MPCRequestInput::DKGSecond(event) => Some(event.event_data.curve),
             MPCRequestInput::Presign(event) => Some(event.event_data.curve),
             MPCRequestInput::Sign(event) => Some(event.event_data.curve),
+            MPCRequestInput::DerivedKeySign(event) => Some(event.event_data.sign_request.curve),
+            MPCRequestInput::BatchSign(event) => Some(event.event_data.curve),
+            MPCRequestInput::ThresholdDecryption(event) => Some(event.event_data.curve),
+            MPCRequestInput::DWalletKeyRefresh(event) => Some(event.event_data.curve),
             MPCRequestInput::NetworkEncryptionKeyDkg(_, _event) => None,
             MPCRequestInput::EncryptedShareVerification(event) => Some(event.event_data.curve),
             MPCRequestInput::PartialSignatureVerification(event) => Some(event.event_data.curve),
@@ -163,6 +195,12 @@ impl MPCRequestInput {
             MPCRequestInput::DKGSecond(_) => None,
             MPCRequestInput::Presign(_) => None,
             MPCRequestInput::Sign(event) => Some(event.event_data.hash_scheme),
+            MPCRequestInput::DerivedKeySign(event) => {
+                Some(event.event_data.sign_request.hash_scheme)
+            }
+            MPCRequestInput::BatchSign(event) => Some(event.event_data.hash_scheme),
+            MPCRequestInput::ThresholdDecryption(_) => None,
+            MPCRequestInput::DWalletKeyRefresh(_) => None,
             MPCRequestInput::NetworkEncryptionKeyDkg(_, _event) => None,
             MPCRequestInput::EncryptedShareVerification(_) => None,
             MPCRequestInput::PartialSignatureVerification(event) => {
@@ -192,6 +230,12 @@ impl MPCRequestInput {
             MPCRequestInput::DKGSecond(_event) => None,
             MPCRequestInput::Presign(event) => Some(event.event_data.signature_algorithm),
             MPCRequestInput::Sign(event) => Some(event.event_data.signature_algorithm),
+            MPCRequestInput::DerivedKeySign(event) => {
+                Some(event.event_data.sign_request.signature_algorithm)
+            }
+            MPCRequestInput::BatchSign(event) => Some(event.event_data.signature_algorithm),
+            MPCRequestInput::ThresholdDecryption(_) => None,
+            MPCRequestInput::DWalletKeyRefresh(_) => None,
             MPCRequestInput::NetworkEncryptionKeyDkg(_, _event) => None,
             MPCRequestInput::EncryptedShareVerification(_) => None,
             MPCRequestInput::PartialSignatureVerification(event) => {
@@ -227,6 +271,21 @@ impl MPCRequestInput {
             MPCRequestInput::Sign(event) => {
                 Some(event.event_data.dwallet_network_encryption_key_id)
             }
+            MPCRequestInput::DerivedKeySign(event) => Some(
+                event
+                    .event_data
+                    .sign_request
+                    .dwallet_network_encryption_key_id,
+            ),
+            MPCRequestInput::BatchSign(event) => {
+                Some(event.event_data.dwallet_network_encryption_key_id)
+            }
+            MPCRequestInput::ThresholdDecryption(event) => {
+                Some(event.event_data.dwallet_network_encryption_key_id)
+            }
+            MPCRequestInput::DWalletKeyRefresh(event) => {
+                Some(event.event_data.dwallet_network_encryption_key_id)
+            }
             MPCRequestInput::NetworkEncryptionKeyDkg(_, event) => {
                 Some(event.event_data.dwallet_network_encryption_key_id)
             }
@@ -256,6 +315,10 @@ impl Debug for MPCRequestInput {
             MPCRequestInput::DKGSecond(_) => write!(f, "dWalletDKGSecondRound"),
             MPCRequestInput::Presign(_) => write!(f, "Presign"),
             MPCRequestInput::Sign(_) => write!(f, "Sign"),
+            MPCRequestInput::DerivedKeySign(_) => write!(f, "DerivedKeySign"),
+            MPCRequestInput::BatchSign(_) => write!(f, "BatchSign"),
+            MPCRequestInput::ThresholdDecryption(_) => write!(f, "ThresholdDecryption"),
+            MPCRequestInput::DWalletKeyRefresh(_) => write!(f, "DWalletKeyRefresh"),
             MPCRequestInput::NetworkEncryptionKeyDkg(_, _) => write!(f, "NetworkDkg"),
             MPCRequestInput::EncryptedShareVerification(_) => {
                 write!(f, "EncryptedShareVerification")
@@ -502,6 +565,24 @@ impl<E: DWalletSessionEventTrait> DWalletSessionEventTrait for DWalletSessionEve
 }
 
 impl<E: DWalletSessionEventTrait> DWalletSessionEvent<E> {
+    pub fn new(
+        epoch: u64,
+        session_object_id: ObjectID,
+        session_type: SessionType,
+        session_sequence_number: u64,
+        session_identifier_preimage: [u8; 32],
+        event_data: E,
+    ) -> Self {
+        Self {
+            epoch,
+            session_object_id,
+            session_type,
+            session_sequence_number,
+            session_identifier_preimage: session_identifier_preimage.to_vec(),
+            event_data,
+        }
+    }
+
     pub fn is_dwallet_mpc_event(event: StructTag, package_id: AccountAddress) -> bool {
         event.address == package_id
             && event.module == SESSIONS_MANAGER_MODULE_NAME.to_owned()
@@ -849,6 +930,143 @@ impl DWalletSessionEventTrait for SignRequestEvent {
     }
 }
 
+/// Represents the Rust version of the Move
+/// struct `ika_system::dwallet_2pc_mpc_coordinator_inner::DerivedKeySignRequestEvent`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema, Eq, PartialEq, Hash)]
+pub struct DerivedKeySignRequestEvent {
+    /// The request to sign, with the public output of the dWallet itself.
+    pub sign_request: SignRequestEvent,
+    /// The non-hardened derivation path of the child key of the dWallet to sign with.
+    pub derivation_path: Vec<u32>,
+}
+
+impl DWalletSessionEventTrait for DerivedKeySignRequestEvent {
+    /// This function allows comparing this event with the Move event.
+    /// It is used to detect [`DerivedKeySignRequestEvent`]
+    /// events from the chain and initiate the MPC session.
+    fn type_(packages_config: &IkaNetworkConfig) -> StructTag {
+        StructTag {
+            address: *packages_config.packages.ika_dwallet_2pc_mpc_package_id,
+            name: DERIVED_KEY_SIGN_REQUEST_EVENT_STRUCT_NAME.to_owned(),
+            module: DWALLET_2PC_MPC_COORDINATOR_INNER_MODULE_NAME.to_owned(),
+            type_params: vec![],
+        }
+    }
+}
+
+/// Represents the Rust version of the Move
+/// struct `ika_system::dwallet_2pc_mpc_coordinator_inner::BatchSignRequestEvent`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema, Eq, PartialEq, Hash)]
+pub struct BatchSignRequestEvent {
+    pub batch_sign_id: ObjectID,
+    /// The `DWallet` object's ObjectID associated with the DKG output.
+    pub dwallet_id: ObjectID,
+    /// The public output of the decentralized party in the dWallet DKG process.
+    pub dwallet_decentralized_public_output: Vec<u8>,
+    pub curve: u32,
+    pub signature_algorithm: u32,
+    pub hash_scheme: u32,
+    /// The dWallet mpc network key version
+    pub dwallet_network_encryption_key_id: ObjectID,
+    /// The signs of the batch, in the order their signatures are responded with.
+    pub signs: Vec<BatchSignRequestItem>,
+}
+
+/// Represents the Rust version of the Move
+/// struct `ika_system::dwallet_2pc_mpc_coordinator_inner::BatchSignRequestItem`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema, Eq, PartialEq, Hash)]
+pub struct BatchSignRequestItem {
+    pub sign_id: ObjectID,
+    /// Hashed message to Sign.
+    pub message: Vec<u8>,
+    pub presign_id: ObjectID,
+    /// The presign protocol output as bytes.
+    pub presign: Vec<u8>,
+    /// The centralized party signature of the message.
+    pub message_centralized_signature: Vec<u8>,
+}
+
+impl DWalletSessionEventTrait for BatchSignRequestEvent {
+    /// This function allows comparing this event with the Move event.
+    /// It is used to detect [`BatchSignRequestEvent`]
+    /// events from the chain and initiate the MPC session.
+    fn type_(packages_config: &IkaNetworkConfig) -> StructTag {
+        StructTag {
+            address: *packages_config.packages.ika_dwallet_2pc_mpc_package_id,
+            name: BATCH_SIGN_REQUEST_EVENT_STRUCT_NAME.to_owned(),
+            module: DWALLET_2PC_MPC_COORDINATOR_INNER_MODULE_NAME.to_owned(),
+            type_params: vec![],
+        }
+    }
+}
+
+/// Represents the Rust version of the Move
+/// struct `ika_system::dwallet_2pc_mpc_coordinator_inner::ThresholdDecryptionRequestEvent`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema, Eq, PartialEq, Hash)]
+pub struct ThresholdDecryptionRequestEvent {
+    pub decryption_id: ObjectID,
+    pub curve: u32,
+    /// The network encryption key the key is encrypted to.
+    pub dwallet_network_encryption_key_id: ObjectID,
+    /// The key encrypted by the user to the network encryption key, with a proof of its encryption.
+    pub encrypted_key: Vec<u8>,
+    /// The `EncryptionKey` object the decrypted key is re-encrypted to.
+    pub encryption_key: Vec<u8>,
+    /// The unique identifier of the `EncryptionKey` object.
+    pub encryption_key_id: ObjectID,
+}
+
+impl DWalletSessionEventTrait for ThresholdDecryptionRequestEvent {
+    /// This function allows comparing this event with the Move event.
+    /// It is used to detect [`ThresholdDecryptionRequestEvent`]
+    /// events from the chain and initiate the MPC session.
+    fn type_(packages_config: &IkaNetworkConfig) -> StructTag {
+        StructTag {
+            address: *packages_config.packages.ika_dwallet_2pc_mpc_package_id,
+            name: THRESHOLD_DECRYPTION_REQUEST_EVENT_STRUCT_NAME.to_owned(),
+            module: DWALLET_2PC_MPC_COORDINATOR_INNER_MODULE_NAME.to_owned(),
+            type_params: vec![],
+        }
+    }
+}
+
+/// Represents the Rust version of the Move
+/// struct `ika_system::dwallet_2pc_mpc_coordinator_inner::DWalletKeyRefreshRequestEvent`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema, Eq, PartialEq, Hash)]
+pub struct DWalletKeyRefreshRequestEvent {
+    pub dwallet_id: ObjectID,
+    /// The public output of the decentralized party in the dWallet DKG process,
+    /// from before the refresh.
+    pub dwallet_public_output: Vec<u8>,
+    pub curve: u32,
+    pub dwallet_network_encryption_key_id: ObjectID,
+    /// The offset subtracted from the network secret key share,
+    /// encrypted by the user to the network encryption key with a proof of its encryption.
+    pub encrypted_refresh_share: Vec<u8>,
+    pub encrypted_user_secret_key_share_id: ObjectID,
+    /// The refreshed user secret key share encrypted to `encryption_key`,
+    /// and the associated cryptographic proof of encryption.
+    pub encrypted_centralized_secret_share_and_proof: Vec<u8>,
+    /// The `EncryptionKey` object used for encrypting the refreshed secret key share.
+    pub encryption_key: Vec<u8>,
+    /// The unique identifier of the `EncryptionKey` object.
+    pub encryption_key_id: ObjectID,
+}
+
+impl DWalletSessionEventTrait for DWalletKeyRefreshRequestEvent {
+    /// This function allows comparing this event with the Move event.
+    /// It is used to detect [`DWalletKeyRefreshRequestEvent`]
+    /// events from the chain and initiate the session.
+    fn type_(packages_config: &IkaNetworkConfig) -> StructTag {
+        StructTag {
+            address: *packages_config.packages.ika_dwallet_2pc_mpc_package_id,
+            name: DWALLET_KEY_REFRESH_REQUEST_EVENT_STRUCT_NAME.to_owned(),
+            module: DWALLET_2PC_MPC_COORDINATOR_INNER_MODULE_NAME.to_owned(),
+            type_params: vec![],
+        }
+    }
+}
+
 /// Rust version of the Move [`ika_system::dwallet_2pc_mpc_coordinator_inner::StartNetworkDKGEvent`] type.
 /// It is used to trigger the start of the network DKG process.
 #[derive(Debug, Serialize, Deserialize, Clone, JsonSchema, Eq, PartialEq, Hash)]
@@ -945,4 +1163,196 @@ pub mod test_helpers {
             event_data,
         }
     }
+    /// One request input of every kind, with placeholder event data,
+    /// for tests in which only the kind of the request matters.
+    pub fn mock_request_inputs() -> Vec<MPCRequestInput> {
+        let sign_request = SignRequestEvent {
+            sign_id: ObjectID::random(),
+            dwallet_id: ObjectID::random(),
+            dwallet_decentralized_public_output: vec![],
+            curve: 0,
+            signature_algorithm: 0,
+            hash_scheme: 0,
+            message: vec![],
+            dwallet_network_encryption_key_id: ObjectID::random(),
+            presign_id: ObjectID::random(),
+            presign: vec![],
+            message_centralized_signature: vec![],
+            is_future_sign: false,
+        };
+
+        vec![
+            MPCRequestInput::MakeDWalletUserSecretKeySharesPublicRequest(
+                mock_dwallet_session_event(
+                    false,
+                    1,
+                    MakeDWalletUserSecretKeySharesPublicRequestEvent {
+                        public_user_secret_key_shares: vec![],
+                        public_output: vec![],
+                        curve: 0,
+                        dwallet_id: ObjectID::random(),
+                        dwallet_network_encryption_key_id: ObjectID::random(),
+                    },
+                ),
+            ),
+            MPCRequestInput::DWalletImportedKeyVerificationRequest(mock_dwallet_session_event(
+                false,
+                2,
+                DWalletImportedKeyVerificationRequestEvent {
+                    dwallet_id: ObjectID::random(),
+                    encrypted_user_secret_key_share_id: ObjectID::random(),
+                    centralized_party_message: vec![],
+                    dwallet_cap_id: ObjectID::random(),
+                    encrypted_centralized_secret_share_and_proof: vec![],
+                    encryption_key: vec![],
+                    encryption_key_id: ObjectID::random(),
+                    encryption_key_address: SuiAddress::ZERO,
+                    user_public_output: vec![],
+                    signer_public_key: vec![],
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                    curve: 0,
+                },
+            )),
+            MPCRequestInput::DKGFirst(mock_dwallet_session_event(
+                false,
+                3,
+                DWalletDKGFirstRoundRequestEvent {
+                    dwallet_id: ObjectID::random(),
+                    dwallet_cap_id: ObjectID::random(),
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                    curve: 0,
+                },
+            )),
+            MPCRequestInput::DKGSecond(mock_dwallet_session_event(
+                false,
+                4,
+                DWalletDKGSecondRoundRequestEvent {
+                    encrypted_user_secret_key_share_id: ObjectID::random(),
+                    dwallet_id: ObjectID::random(),
+                    first_round_output: vec![],
+                    centralized_public_key_share_and_proof: vec![],
+                    dwallet_cap_id: ObjectID::random(),
+                    encrypted_centralized_secret_share_and_proof: vec![],
+                    encryption_key: vec![],
+                    encryption_key_id: ObjectID::random(),
+                    encryption_key_address: SuiAddress::ZERO,
+                    user_public_output: vec![],
+                    signer_public_key: vec![],
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                    curve: 0,
+                },
+            )),
+            MPCRequestInput::Presign(mock_dwallet_session_event(
+                false,
+                5,
+                PresignRequestEvent {
+                    dwallet_id: None,
+                    presign_id: ObjectID::random(),
+                    dwallet_public_output: None,
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                    curve: 0,
+                    signature_algorithm: 0,
+                },
+            )),
+            MPCRequestInput::Sign(mock_dwallet_session_event(false, 6, sign_request.clone())),
+            MPCRequestInput::DerivedKeySign(mock_dwallet_session_event(
+                false,
+                7,
+                DerivedKeySignRequestEvent {
+                    sign_request,
+                    derivation_path: vec![0],
+                },
+            )),
+            MPCRequestInput::BatchSign(mock_dwallet_session_event(
+                false,
+                8,
+                BatchSignRequestEvent {
+                    batch_sign_id: ObjectID::random(),
+                    dwallet_id: ObjectID::random(),
+                    dwallet_decentralized_public_output: vec![],
+                    curve: 0,
+                    signature_algorithm: 0,
+                    hash_scheme: 0,
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                    signs: vec![],
+                },
+            )),
+            MPCRequestInput::ThresholdDecryption(mock_dwallet_session_event(
+                false,
+                9,
+                ThresholdDecryptionRequestEvent {
+                    decryption_id: ObjectID::random(),
+                    curve: 0,
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                    encrypted_key: vec![],
+                    encryption_key: vec![],
+                    encryption_key_id: ObjectID::random(),
+                },
+            )),
+            MPCRequestInput::DWalletKeyRefresh(mock_dwallet_session_event(
+                false,
+                10,
+                DWalletKeyRefreshRequestEvent {
+                    dwallet_id: ObjectID::random(),
+                    dwallet_public_output: vec![],
+                    curve: 0,
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                    encrypted_refresh_share: vec![],
+                    encrypted_user_secret_key_share_id: ObjectID::random(),
+                    encrypted_centralized_secret_share_and_proof: vec![],
+                    encryption_key: vec![],
+                    encryption_key_id: ObjectID::random(),
+                },
+            )),
+            MPCRequestInput::NetworkEncryptionKeyDkg(
+                DWalletMPCNetworkKeyScheme::Secp256k1,
+                mock_dwallet_session_event(
+                    true,
+                    11,
+                    DWalletNetworkDKGEncryptionKeyRequestEvent {
+                        dwallet_network_encryption_key_id: ObjectID::random(),
+                        params_for_network: vec![],
+                    },
+                ),
+            ),
+            MPCRequestInput::EncryptedShareVerification(mock_dwallet_session_event(
+                false,
+                12,
+                EncryptedShareVerificationRequestEvent {
+                    encrypted_centralized_secret_share_and_proof: vec![],
+                    decentralized_public_output: vec![],
+                    dwallet_id: ObjectID::random(),
+                    encryption_key: vec![],
+                    encryption_key_id: ObjectID::random(),
+                    encrypted_user_secret_key_share_id: ObjectID::random(),
+                    source_encrypted_user_secret_key_share_id: ObjectID::random(),
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                    curve: 0,
+                },
+            )),
+            MPCRequestInput::PartialSignatureVerification(mock_dwallet_session_event(
+                false,
+                13,
+                FutureSignRequestEvent {
+                    dwallet_id: ObjectID::random(),
+                    partial_centralized_signed_message_id: ObjectID::random(),
+                    message: vec![],
+                    presign: vec![],
+                    dkg_output: vec![],
+                    curve: 0,
+                    signature_algorithm: 0,
+                    hash_scheme: 0,
+                    message_centralized_signature: vec![],
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                },
+            )),
+            MPCRequestInput::NetworkEncryptionKeyReconfiguration(mock_dwallet_session_event(
+                true,
+                14,
+                DWalletEncryptionKeyReconfigurationRequestEvent {
+                    dwallet_network_encryption_key_id: ObjectID::random(),
+                },
+            )),
+        ]
+    }
 }
@@ -1,9 +1,9 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: BSD-3-Clause-Clear
 
-use crate::committee::{EpochId, ProtocolVersion};
+use crate::committee::{EpochId, ProtocolVersion, StakeUnit};
 use crate::crypto::{
-    AggregateAuthoritySignature, AuthoritySignInfo, AuthoritySignInfoTrait,
+    AggregateAuthoritySignature, AuthorityName, AuthoritySignInfo, AuthoritySignInfoTrait,
     AuthorityStrongQuorumSignInfo, default_hash,
 };
 use crate::error::IkaResult;
@@ -65,6 +65,26 @@ pub enum SystemCheckpointMessageKind {
         /// If false, the witness is being set, if true, the witness is being removed.
         remove: bool,
     },
+    /// The committee of the next epoch, as locked on Sui.
+    /// Certifying it lets anyone who trusts the committee of this epoch verify the next one
+    /// from the system checkpoints alone, e.g. when restoring from an archive.
+    SetNextEpochCommittee {
+        epoch: EpochId,
+        voting_rights: Vec<(AuthorityName, StakeUnit)>,
+        quorum_threshold: StakeUnit,
+        validity_threshold: StakeUnit,
+    },
+}
+
+impl SystemCheckpointMessageKind {
+    pub fn new_set_next_epoch_committee(committee: &Committee) -> Self {
+        Self::SetNextEpochCommittee {
+            epoch: committee.epoch,
+            voting_rights: committee.voting_rights.clone(),
+            quorum_threshold: committee.quorum_threshold,
+            validity_threshold: committee.validity_threshold,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -113,6 +133,26 @@ impl SystemCheckpointMessage {
     pub fn sequence_number(&self) -> &SystemCheckpointSequenceNumber {
         &self.sequence_number
     }
+
+    /// Whether this checkpoint certifies a committee for the next epoch.
+    pub fn has_next_epoch_committee(&self) -> bool {
+        self.messages.iter().any(|message| {
+            matches!(
+                message,
+                SystemCheckpointMessageKind::SetNextEpochCommittee { .. }
+            )
+        })
+    }
+
+    /// Whether this checkpoint certifies `committee` as the committee of the next epoch.
+    pub fn certifies_next_epoch_committee(&self, committee: &Committee) -> bool {
+        committee.epoch == self.epoch + 1
+            && self
+                .messages
+                .contains(&SystemCheckpointMessageKind::new_set_next_epoch_committee(
+                    committee,
+                ))
+    }
 }
 
 impl Display for SystemCheckpointMessage {
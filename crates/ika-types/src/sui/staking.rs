@@ -197,7 +197,7 @@ pub struct ValidatorInfo {
     pub next_epoch_network_address: Option<String>,
     pub next_epoch_p2p_address: Option<String>,
     pub next_epoch_consensus_address: Option<String>,
-    previous_mpc_data_bytes: Option<TableVec>,
+    pub previous_mpc_data_bytes: Option<TableVec>,
     pub metadata: ExtendedField,
 }
 
@@ -235,6 +235,40 @@ pub enum PoolState {
 }
 
 impl StakingPool {
+    /// Creates the staking pool of a validator that is active since `activation_epoch`,
+    /// without any stake or rewards.
+    pub fn new_active(id: ObjectID, validator_info: ValidatorInfo, activation_epoch: u64) -> Self {
+        let empty_pending_values = || PendingValues {
+            values: VecMap { contents: vec![] },
+        };
+
+        Self {
+            id,
+            validator_info,
+            verified_validator_info: OnceCell::new(),
+            state: PoolState::Active,
+            activation_epoch: Some(activation_epoch),
+            latest_epoch: activation_epoch,
+            ika_balance: 0,
+            num_shares: 0,
+            pending_shares_withdraw: empty_pending_values(),
+            pre_active_withdrawals: empty_pending_values(),
+            pending_commission_rate: empty_pending_values(),
+            commission_rate: 0,
+            exchange_rates: Table {
+                id: ObjectID::random(),
+                size: 0,
+            },
+            pending_stake: empty_pending_values(),
+            rewards_pool: Balance::new(0),
+            commission: Balance::new(0),
+            validator_cap_id: ObjectID::random(),
+            operation_cap_id: ObjectID::random(),
+            commission_cap_id: ObjectID::random(),
+            extra_fields: Bag::default(),
+        }
+    }
+
     pub fn verified_validator_info(&self) -> &VerifiedValidatorInfo {
         // Todo (#1298): Remove unwrap.
         self.verified_validator_info
@@ -87,7 +87,7 @@ pub struct PricingInfo {
     pub pricing_map: VecMap<PricingInfoKey, PricingInfoValue>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
 pub struct PricingInfoKey {
     pub curve: u32,
     pub signature_algorithm: Option<u32>,
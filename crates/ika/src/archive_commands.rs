@@ -0,0 +1,370 @@
+// Copyright (c) dWallet Labs, Ltd.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use anyhow::{Result, anyhow};
+use clap::*;
+use colored::Colorize;
+use ika_archival::reader::{ArchiveReader, ArchiveReaderMetrics};
+use ika_archival::{FileType, verify_archive_with_checksums};
+use ika_config::node::ArchiveReaderConfig;
+use ika_config::object_storage_config::ObjectStoreConfig;
+use ika_types::message::DWalletCheckpointMessageKind;
+use ika_types::message_envelope::Message;
+use ika_types::messages_dwallet_checkpoint::{
+    CertifiedDWalletCheckpointMessage, DWalletCheckpointSequenceNumber,
+};
+use prometheus::Registry;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter, Write};
+use std::fs::File;
+use std::io::BufWriter;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::PathBuf;
+use sui_types::base_types::ObjectID;
+
+const DEFAULT_CONCURRENCY: usize = 5;
+
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+pub enum IkaArchiveCommand {
+    /// List the epochs and files recorded in the archive MANIFEST.
+    #[clap(name = "list")]
+    List {
+        #[clap(flatten)]
+        object_store_config: ObjectStoreConfig,
+    },
+    /// Download every archive file and verify its checksum against the MANIFEST.
+    #[clap(name = "verify")]
+    Verify {
+        #[clap(flatten)]
+        object_store_config: ObjectStoreConfig,
+        #[clap(long, default_value_t = DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+    },
+    /// Dump the dWallet checkpoints in `[start, end)` as JSON.
+    #[clap(name = "dump-checkpoints")]
+    DumpCheckpoints {
+        #[clap(flatten)]
+        object_store_config: ObjectStoreConfig,
+        #[clap(long)]
+        start: DWalletCheckpointSequenceNumber,
+        #[clap(long)]
+        end: DWalletCheckpointSequenceNumber,
+        /// The file to write the JSON to, defaults to stdout.
+        #[clap(long)]
+        output_file_path: Option<PathBuf>,
+    },
+    /// Export every sign output of a dWallet found in the archive as JSON.
+    #[clap(name = "export-sign-outputs")]
+    ExportSignOutputs {
+        #[clap(flatten)]
+        object_store_config: ObjectStoreConfig,
+        #[clap(long)]
+        dwallet_id: ObjectID,
+        #[clap(long)]
+        output_file_path: Option<PathBuf>,
+    },
+}
+
+#[derive(Serialize)]
+pub struct ArchiveEpochInfo {
+    pub epoch: u64,
+    pub dwallet_checkpoint_files: usize,
+    pub dwallet_checkpoints: Range<u64>,
+    pub system_checkpoint_files: usize,
+    pub system_checkpoints: Range<u64>,
+}
+
+#[derive(Serialize)]
+pub struct DumpedCheckpointMessage {
+    pub kind: &'static str,
+    pub message: DWalletCheckpointMessageKind,
+}
+
+#[derive(Serialize)]
+pub struct DumpedCheckpoint {
+    pub epoch: u64,
+    pub sequence_number: DWalletCheckpointSequenceNumber,
+    pub digest: String,
+    pub messages: Vec<DumpedCheckpointMessage>,
+}
+
+#[derive(Serialize)]
+pub struct ExportedSignOutput {
+    pub checkpoint_sequence_number: DWalletCheckpointSequenceNumber,
+    pub sign_id: String,
+    pub signature: String,
+    pub is_future_sign: bool,
+    pub rejected: bool,
+    pub session_sequence_number: u64,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum IkaArchiveCommandResponse {
+    List(Vec<ArchiveEpochInfo>),
+    Verify,
+    DumpCheckpoints(Vec<DumpedCheckpoint>, Option<PathBuf>),
+    ExportSignOutputs(Vec<ExportedSignOutput>, Option<PathBuf>),
+}
+
+impl IkaArchiveCommand {
+    pub async fn execute(self) -> Result<IkaArchiveCommandResponse, anyhow::Error> {
+        Ok(match self {
+            IkaArchiveCommand::List {
+                object_store_config,
+            } => {
+                let archive_reader = archive_reader(object_store_config, DEFAULT_CONCURRENCY).await?;
+                let manifest = archive_reader.get_manifest().await?;
+                let mut epochs: BTreeMap<u64, ArchiveEpochInfo> = BTreeMap::new();
+                for file in manifest.files() {
+                    let epoch_info =
+                        epochs
+                            .entry(file.epoch_num)
+                            .or_insert_with(|| ArchiveEpochInfo {
+                                epoch: file.epoch_num,
+                                dwallet_checkpoint_files: 0,
+                                dwallet_checkpoints: u64::MAX..0,
+                                system_checkpoint_files: 0,
+                                system_checkpoints: u64::MAX..0,
+                            });
+                    let (files, range) = match file.file_type {
+                        FileType::DWalletCheckpointMessage => (
+                            &mut epoch_info.dwallet_checkpoint_files,
+                            &mut epoch_info.dwallet_checkpoints,
+                        ),
+                        FileType::SystemCheckpointMessage => (
+                            &mut epoch_info.system_checkpoint_files,
+                            &mut epoch_info.system_checkpoints,
+                        ),
+                    };
+                    *files += 1;
+                    range.start = range.start.min(file.checkpoint_seq_range.start);
+                    range.end = range.end.max(file.checkpoint_seq_range.end);
+                }
+                IkaArchiveCommandResponse::List(
+                    epochs
+                        .into_values()
+                        .map(|mut epoch_info| {
+                            // Epochs without files of some type have an empty range.
+                            for range in [
+                                &mut epoch_info.dwallet_checkpoints,
+                                &mut epoch_info.system_checkpoints,
+                            ] {
+                                if range.start > range.end {
+                                    *range = 0..0;
+                                }
+                            }
+                            epoch_info
+                        })
+                        .collect(),
+                )
+            }
+            IkaArchiveCommand::Verify {
+                object_store_config,
+                concurrency,
+            } => {
+                verify_archive_with_checksums(object_store_config, concurrency).await?;
+                IkaArchiveCommandResponse::Verify
+            }
+            IkaArchiveCommand::DumpCheckpoints {
+                object_store_config,
+                start,
+                end,
+                output_file_path,
+            } => {
+                if start >= end {
+                    return Err(anyhow!("Empty checkpoint range {start}..{end}"));
+                }
+                let archive_reader = archive_reader(object_store_config, DEFAULT_CONCURRENCY).await?;
+                let checkpoints: Vec<_> = archive_reader
+                    .get_dwallet_checkpoints_for_range_no_verify(start..end)
+                    .await?
+                    .into_iter()
+                    .map(dump_checkpoint)
+                    .collect();
+                write_json_output(&checkpoints, output_file_path.as_ref())?;
+                IkaArchiveCommandResponse::DumpCheckpoints(checkpoints, output_file_path)
+            }
+            IkaArchiveCommand::ExportSignOutputs {
+                object_store_config,
+                dwallet_id,
+                output_file_path,
+            } => {
+                let archive_reader = archive_reader(object_store_config, DEFAULT_CONCURRENCY).await?;
+                let manifest = archive_reader.get_manifest().await?;
+                let mut files: Vec<_> = manifest
+                    .files()
+                    .into_iter()
+                    .filter(|file| file.file_type == FileType::DWalletCheckpointMessage)
+                    .collect();
+                files.sort_by_key(|file| file.checkpoint_seq_range.start);
+
+                let mut sign_outputs = vec![];
+                // Read one file at a time, so that the whole archive is never held in memory.
+                for file in files {
+                    let checkpoints = archive_reader
+                        .get_dwallet_checkpoints_for_range_no_verify(file.checkpoint_seq_range)
+                        .await?;
+                    for checkpoint in checkpoints {
+                        for message in &checkpoint.messages {
+                            match message {
+                                DWalletCheckpointMessageKind::RespondDWalletSign(sign_output)
+                                    if sign_output.dwallet_id == dwallet_id.to_vec() =>
+                                {
+                                    sign_outputs.push(ExportedSignOutput {
+                                        checkpoint_sequence_number: checkpoint.sequence_number,
+                                        sign_id: format!("0x{}", hex::encode(&sign_output.sign_id)),
+                                        signature: hex::encode(&sign_output.signature),
+                                        is_future_sign: sign_output.is_future_sign,
+                                        rejected: sign_output.rejected,
+                                        session_sequence_number: sign_output
+                                            .session_sequence_number,
+                                    });
+                                }
+                                // Each sign of a batch is exported as a separate sign output.
+                                DWalletCheckpointMessageKind::RespondDWalletBatchSign(
+                                    batch_sign_output,
+                                ) if batch_sign_output.dwallet_id == dwallet_id.to_vec() => {
+                                    for (index, sign_id) in
+                                        batch_sign_output.sign_ids.iter().enumerate()
+                                    {
+                                        sign_outputs.push(ExportedSignOutput {
+                                            checkpoint_sequence_number: checkpoint.sequence_number,
+                                            sign_id: format!("0x{}", hex::encode(sign_id)),
+                                            signature: batch_sign_output
+                                                .signatures
+                                                .get(index)
+                                                .map(hex::encode)
+                                                .unwrap_or_default(),
+                                            is_future_sign: false,
+                                            rejected: batch_sign_output.rejected,
+                                            session_sequence_number: batch_sign_output
+                                                .session_sequence_number,
+                                        });
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                write_json_output(&sign_outputs, output_file_path.as_ref())?;
+                IkaArchiveCommandResponse::ExportSignOutputs(sign_outputs, output_file_path)
+            }
+        })
+    }
+}
+
+async fn archive_reader(
+    object_store_config: ObjectStoreConfig,
+    concurrency: usize,
+) -> Result<ArchiveReader> {
+    let metrics = ArchiveReaderMetrics::new(&Registry::default());
+    let config = ArchiveReaderConfig {
+        remote_store_config: object_store_config,
+        download_concurrency: NonZeroUsize::new(concurrency)
+            .ok_or_else(|| anyhow!("Concurrency must be greater than zero"))?,
+        use_for_pruning_watermark: false,
+    };
+    let archive_reader = ArchiveReader::new(config, &metrics)?;
+    archive_reader.sync_manifest_once().await?;
+    Ok(archive_reader)
+}
+
+fn dump_checkpoint(checkpoint: CertifiedDWalletCheckpointMessage) -> DumpedCheckpoint {
+    let digest = checkpoint.data().digest().to_string();
+    let checkpoint = checkpoint.into_data();
+    DumpedCheckpoint {
+        epoch: checkpoint.epoch,
+        sequence_number: checkpoint.sequence_number,
+        digest,
+        messages: checkpoint
+            .messages
+            .into_iter()
+            .map(|message| DumpedCheckpointMessage {
+                kind: message.name(),
+                message,
+            })
+            .collect(),
+    }
+}
+
+/// Writes `value` as JSON to `output_file_path`, when one is given.
+fn write_json_output<T: Serialize>(value: &T, output_file_path: Option<&PathBuf>) -> Result<()> {
+    if let Some(output_file_path) = output_file_path {
+        let file = BufWriter::new(File::create(output_file_path)?);
+        serde_json::to_writer_pretty(file, value)?;
+    }
+    Ok(())
+}
+
+impl Display for IkaArchiveCommandResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut writer = String::new();
+        match self {
+            IkaArchiveCommandResponse::List(epochs) => {
+                for epoch in epochs {
+                    writeln!(
+                        writer,
+                        "Epoch {}: {} dwallet checkpoint files ({:?}), {} system checkpoint files ({:?})",
+                        epoch.epoch,
+                        epoch.dwallet_checkpoint_files,
+                        epoch.dwallet_checkpoints,
+                        epoch.system_checkpoint_files,
+                        epoch.system_checkpoints,
+                    )?;
+                }
+            }
+            IkaArchiveCommandResponse::Verify => {
+                writeln!(writer, "All archive files are valid")?;
+            }
+            IkaArchiveCommandResponse::DumpCheckpoints(checkpoints, Some(path)) => {
+                writeln!(
+                    writer,
+                    "Dumped {} checkpoints to: {path:?}",
+                    checkpoints.len()
+                )?;
+            }
+            IkaArchiveCommandResponse::ExportSignOutputs(sign_outputs, Some(path)) => {
+                writeln!(
+                    writer,
+                    "Exported {} sign outputs to: {path:?}",
+                    sign_outputs.len()
+                )?;
+            }
+            IkaArchiveCommandResponse::DumpCheckpoints(checkpoints, None) => {
+                writeln!(writer, "{}", to_json(checkpoints))?;
+            }
+            IkaArchiveCommandResponse::ExportSignOutputs(sign_outputs, None) => {
+                writeln!(writer, "{}", to_json(sign_outputs))?;
+            }
+        }
+        write!(f, "{}", writer.trim_end_matches('\n'))
+    }
+}
+
+impl Debug for IkaArchiveCommandResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", to_json(self))
+    }
+}
+
+impl IkaArchiveCommandResponse {
+    pub fn print(&self, pretty: bool) {
+        let line = if pretty {
+            format!("{self}")
+        } else {
+            format!("{self:?}")
+        };
+        for line in line.lines() {
+            println!("{line}");
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|err| format!("{err}").red().to_string())
+}
@@ -0,0 +1,293 @@
+// Copyright (c) dWallet Labs, Ltd.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use anyhow::{Context, Result};
+use clap::*;
+use colored::Colorize;
+use dwallet_mpc_centralized_party::backup::{
+    BackupKdfParams, DWalletBackupHeader, create_dwallet_backup_inner,
+    export_dwallet_backup_chunks, import_dwallet_backup_chunks, inspect_dwallet_backup_inner,
+    restore_dwallet_backup_inner,
+};
+use serde::Serialize;
+use std::fmt::{Debug, Display, Formatter, Write};
+use std::fs;
+use std::io::Write as _;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DEFAULT_CHUNK_SIZE: usize = 256;
+
+#[derive(Parser)]
+#[clap(rename_all = "kebab-case")]
+pub enum IkaBackupCommand {
+    /// Create a password-protected backup file of a dWallet, after verifying that
+    /// the user secret share matches the dWallet DKG output.
+    #[clap(name = "create")]
+    Create {
+        #[clap(long)]
+        dwallet_id: String,
+        #[clap(long)]
+        network_encryption_key_id: String,
+        /// The BCS DKG output of the dWallet, as read from the chain.
+        #[clap(long)]
+        dwallet_dkg_output_file: PathBuf,
+        /// The user secret share of the dWallet, as written by `restore`.
+        #[clap(long)]
+        user_secret_share_file: PathBuf,
+        /// A file holding the password of the backup (a single trailing newline is ignored).
+        #[clap(long)]
+        password_file: PathBuf,
+        /// The BCS protocol public parameters of the network encryption key of the dWallet.
+        #[clap(long)]
+        protocol_public_parameters_file: PathBuf,
+        /// The file to write the backup to.
+        #[clap(long)]
+        output_file: PathBuf,
+    },
+    /// Show the dWallet a backup file is of, verifying its checksum but not its password.
+    #[clap(name = "inspect")]
+    Inspect {
+        #[clap(long)]
+        backup_file: PathBuf,
+    },
+    /// Restore the user secret share of a dWallet from a backup file,
+    /// verifying it against the dWallet DKG output in the backup.
+    #[clap(name = "restore")]
+    Restore {
+        #[clap(long)]
+        backup_file: PathBuf,
+        /// A file holding the password of the backup (a single trailing newline is ignored).
+        #[clap(long)]
+        password_file: PathBuf,
+        /// The BCS protocol public parameters of the network encryption key of the dWallet.
+        #[clap(long)]
+        protocol_public_parameters_file: PathBuf,
+        /// The file to write the user secret share to, readable by its owner only.
+        #[clap(long)]
+        output_file: PathBuf,
+    },
+    /// Split a backup file into text chunks, one per line, to print on paper or as QR codes.
+    #[clap(name = "export-chunks")]
+    ExportChunks {
+        #[clap(long)]
+        backup_file: PathBuf,
+        /// The number of backup bytes in each chunk.
+        #[clap(long, default_value_t = DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+        /// The file to write the chunks to, defaults to stdout.
+        #[clap(long)]
+        output_file: Option<PathBuf>,
+    },
+    /// Reassemble a backup file from its text chunks, one per line, in any order.
+    #[clap(name = "import-chunks")]
+    ImportChunks {
+        #[clap(long)]
+        chunks_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+    },
+}
+
+/// The public part of a backup, as displayed.
+#[derive(Serialize)]
+pub struct BackupInfo {
+    pub dwallet_id: String,
+    pub network_encryption_key_id: String,
+    pub public_key: String,
+    pub centralized_public_share: String,
+    pub decentralized_public_share: String,
+    pub created_at: u64,
+}
+
+impl From<DWalletBackupHeader> for BackupInfo {
+    fn from(header: DWalletBackupHeader) -> Self {
+        Self {
+            dwallet_id: header.dwallet_id,
+            network_encryption_key_id: header.network_encryption_key_id,
+            public_key: hex::encode(header.public_keys.public_key),
+            centralized_public_share: hex::encode(header.public_keys.centralized_public_share),
+            decentralized_public_share: hex::encode(header.public_keys.decentralized_public_share),
+            created_at: header.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum IkaBackupCommandResponse {
+    Create(BackupInfo, PathBuf),
+    Inspect(BackupInfo),
+    Restore(BackupInfo, PathBuf),
+    ExportChunks(Vec<String>, Option<PathBuf>),
+    ImportChunks(BackupInfo, PathBuf),
+}
+
+impl IkaBackupCommand {
+    pub fn execute(self) -> Result<IkaBackupCommandResponse> {
+        Ok(match self {
+            IkaBackupCommand::Create {
+                dwallet_id,
+                network_encryption_key_id,
+                dwallet_dkg_output_file,
+                user_secret_share_file,
+                password_file,
+                protocol_public_parameters_file,
+                output_file,
+            } => {
+                let created_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .context("The system clock is before the Unix epoch")?
+                    .as_secs();
+                let backup = create_dwallet_backup_inner(
+                    read_file(&protocol_public_parameters_file)?,
+                    dwallet_id,
+                    network_encryption_key_id,
+                    read_file(&dwallet_dkg_output_file)?,
+                    read_file(&user_secret_share_file)?,
+                    read_password(&password_file)?,
+                    created_at,
+                    BackupKdfParams::default(),
+                )?;
+                let header = inspect_dwallet_backup_inner(backup.clone())?;
+                fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&output_file)
+                    .and_then(|mut file| file.write_all(&backup))
+                    .with_context(|| format!("Cannot write the backup to {output_file:?}"))?;
+                IkaBackupCommandResponse::Create(header.into(), output_file)
+            }
+            IkaBackupCommand::Inspect { backup_file } => IkaBackupCommandResponse::Inspect(
+                inspect_dwallet_backup_inner(read_file(&backup_file)?)?.into(),
+            ),
+            IkaBackupCommand::Restore {
+                backup_file,
+                password_file,
+                protocol_public_parameters_file,
+                output_file,
+            } => {
+                let (header, user_secret_share) = restore_dwallet_backup_inner(
+                    read_file(&backup_file)?,
+                    read_password(&password_file)?,
+                    read_file(&protocol_public_parameters_file)?,
+                )?;
+                let mut options = fs::OpenOptions::new();
+                options.write(true).create_new(true);
+                #[cfg(unix)]
+                options.mode(0o600);
+                options
+                    .open(&output_file)
+                    .and_then(|mut file| file.write_all(&user_secret_share))
+                    .with_context(|| {
+                        format!("Cannot write the user secret share to {output_file:?}")
+                    })?;
+                IkaBackupCommandResponse::Restore(header.into(), output_file)
+            }
+            IkaBackupCommand::ExportChunks {
+                backup_file,
+                chunk_size,
+                output_file,
+            } => {
+                let chunks = export_dwallet_backup_chunks(&read_file(&backup_file)?, chunk_size)?;
+                if let Some(output_file) = &output_file {
+                    fs::write(output_file, chunks.join("\n") + "\n")?;
+                }
+                IkaBackupCommandResponse::ExportChunks(chunks, output_file)
+            }
+            IkaBackupCommand::ImportChunks {
+                chunks_file,
+                output_file,
+            } => {
+                let chunks: Vec<String> = fs::read_to_string(&chunks_file)
+                    .with_context(|| format!("Cannot read {chunks_file:?}"))?
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(str::to_string)
+                    .collect();
+                let backup = import_dwallet_backup_chunks(&chunks)?;
+                let header = inspect_dwallet_backup_inner(backup.clone())?;
+                fs::write(&output_file, backup)?;
+                IkaBackupCommandResponse::ImportChunks(header.into(), output_file)
+            }
+        })
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| format!("Cannot read {path:?}"))
+}
+
+fn read_password(path: &Path) -> Result<Vec<u8>> {
+    let mut password = read_file(path)?;
+    if password.last() == Some(&b'\n') {
+        password.pop();
+    }
+    Ok(password)
+}
+
+impl IkaBackupCommandResponse {
+    pub fn print(&self, pretty: bool) {
+        let line = if pretty {
+            format!("{self}")
+        } else {
+            format!("{self:?}")
+        };
+        for line in line.lines() {
+            println!("{line}");
+        }
+    }
+}
+
+fn write_backup_info(writer: &mut String, info: &BackupInfo) -> std::fmt::Result {
+    writeln!(writer, "dWallet ID: {}", info.dwallet_id)?;
+    writeln!(
+        writer,
+        "Network encryption key ID: {}",
+        info.network_encryption_key_id
+    )?;
+    writeln!(writer, "Public key: {}", info.public_key)?;
+    writeln!(writer, "Created at: {}", info.created_at)
+}
+
+impl Display for IkaBackupCommandResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut writer = String::new();
+        match self {
+            IkaBackupCommandResponse::Create(info, path) => {
+                write_backup_info(&mut writer, info)?;
+                writeln!(writer, "Created the backup: {path:?}")?;
+            }
+            IkaBackupCommandResponse::Inspect(info) => {
+                write_backup_info(&mut writer, info)?;
+            }
+            IkaBackupCommandResponse::Restore(info, path) => {
+                write_backup_info(&mut writer, info)?;
+                writeln!(writer, "Restored the user secret share to: {path:?}")?;
+            }
+            IkaBackupCommandResponse::ExportChunks(chunks, Some(path)) => {
+                writeln!(writer, "Exported {} chunks to: {path:?}", chunks.len())?;
+            }
+            IkaBackupCommandResponse::ExportChunks(chunks, None) => {
+                for chunk in chunks {
+                    writeln!(writer, "{chunk}")?;
+                }
+            }
+            IkaBackupCommandResponse::ImportChunks(info, path) => {
+                write_backup_info(&mut writer, info)?;
+                writeln!(writer, "Reassembled the backup to: {path:?}")?;
+            }
+        }
+        write!(f, "{}", writer.trim_end_matches('\n'))
+    }
+}
+
+impl Debug for IkaBackupCommandResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let json = serde_json::to_string_pretty(self)
+            .unwrap_or_else(|err| format!("{err}").red().to_string());
+        write!(f, "{json}")
+    }
+}
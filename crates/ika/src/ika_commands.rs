@@ -14,6 +14,8 @@ use std::path::PathBuf;
 use std::thread;
 use sui_config::{SUI_CLIENT_CONFIG, sui_config_dir};
 
+use crate::archive_commands::IkaArchiveCommand;
+use crate::backup_commands::IkaBackupCommand;
 #[cfg(feature = "protocol-commands")]
 use crate::protocol_commands::IkaProtocolCommand;
 use crate::validator_commands::IkaValidatorCommand;
@@ -120,6 +122,26 @@ pub enum IkaCommand {
         accept_defaults: bool,
     },
 
+    /// Inspect and export the contents of a checkpoint archive.
+    #[clap(name = "archive")]
+    Archive {
+        #[clap(subcommand)]
+        cmd: Option<IkaArchiveCommand>,
+        /// Return command outputs in JSON format.
+        #[clap(long, global = true)]
+        json: bool,
+    },
+
+    /// Create, inspect, restore and export password-protected dWallet backups.
+    #[clap(name = "backup")]
+    Backup {
+        #[clap(subcommand)]
+        cmd: Option<IkaBackupCommand>,
+        /// Return command outputs in JSON format.
+        #[clap(long, global = true)]
+        json: bool,
+    },
+
     #[cfg(feature = "protocol-commands")]
     /// A tool for protocol governance operations.
     #[clap(name = "protocol")]
@@ -229,6 +251,28 @@ impl IkaCommand {
                 }
                 Ok(())
             }
+            IkaCommand::Archive { cmd, json } => {
+                if let Some(cmd) = cmd {
+                    cmd.execute().await?.print(!json);
+                } else {
+                    // Print help
+                    let mut app: Command = IkaCommand::command();
+                    app.build();
+                    app.find_subcommand_mut("archive").unwrap().print_help()?;
+                }
+                Ok(())
+            }
+            IkaCommand::Backup { cmd, json } => {
+                if let Some(cmd) = cmd {
+                    cmd.execute()?.print(!json);
+                } else {
+                    // Print help
+                    let mut app: Command = IkaCommand::command();
+                    app.build();
+                    app.find_subcommand_mut("backup").unwrap().print_help()?;
+                }
+                Ok(())
+            }
             #[cfg(feature = "protocol-commands")]
             IkaCommand::Protocol {
                 config, cmd, json, ..
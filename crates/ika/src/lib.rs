@@ -8,6 +8,8 @@ use std::path::PathBuf;
 use sui_config::PersistedConfig;
 use sui_sdk::wallet_context::WalletContext;
 
+pub(crate) mod archive_commands;
+pub(crate) mod backup_commands;
 #[macro_use]
 pub mod ika_commands;
 #[cfg(feature = "protocol-commands")]
@@ -18,9 +18,10 @@ use dwallet_classgroups_types::ClassGroupsKeyPairAndProof;
 use dwallet_mpc_types::dwallet_mpc::{MPCDataV1, VersionedMPCData};
 use dwallet_rng::RootSeed;
 use fastcrypto::traits::{KeyPair, ToFromBytes};
-use ika_config::node::read_authority_keypair_from_file;
+use ika_config::node::{PricingRecommendationConfig, read_authority_keypair_from_file};
 use ika_config::validator_info::ValidatorInfo;
 use ika_config::{IKA_SUI_CONFIG, ika_config_dir};
+use ika_core::dwallet_mpc::pricing_recommendation::{self, PricingDiffEntry};
 use ika_sui_client::SuiClient;
 use ika_sui_client::ika_validator_transactions::{
     BecomeCandidateValidatorData, collect_commission, report_validator, request_add_validator,
@@ -54,6 +55,7 @@ use sui_types::crypto::get_authority_key_pair;
 use sui_types::crypto::{NetworkKeyPair, SignatureScheme, SuiKeyPair};
 
 const DEFAULT_GAS_BUDGET: u64 = 200_000_000; // 0.2 SUI
+const DEFAULT_METRICS_URL: &str = "http://127.0.0.1:9184/metrics";
 
 #[derive(Parser)]
 #[clap(rename_all = "kebab-case")]
@@ -361,6 +363,28 @@ pub enum IkaValidatorCommand {
         #[clap(name = "ika-sui-config", long)]
         ika_sui_config: Option<PathBuf>,
     },
+    /// Recommend a pricing vote from the MPC computation costs measured by a running node,
+    /// and optionally submit it.
+    #[clap(name = "recommend-pricing-vote")]
+    RecommendPricingVote {
+        #[clap(name = "gas-budget", long)]
+        gas_budget: Option<u64>,
+        /// The prometheus endpoint of the validator's node.
+        #[clap(name = "metrics-url", long, default_value = DEFAULT_METRICS_URL)]
+        metrics_url: String,
+        /// A YAML file with the `PricingRecommendationConfig` to derive the fees with.
+        #[clap(name = "pricing-recommendation-config", long)]
+        pricing_recommendation_config: PathBuf,
+        #[clap(name = "output-file-path", long)]
+        output_file_path: Option<PathBuf>,
+        /// Submit the recommended pricing as this validator's pricing vote.
+        #[clap(name = "submit", long, requires = "validator-operation-cap-id")]
+        submit: bool,
+        #[clap(name = "validator-operation-cap-id", long)]
+        validator_operation_cap_id: Option<ObjectID>,
+        #[clap(name = "ika-sui-config", long)]
+        ika_sui_config: Option<PathBuf>,
+    },
 }
 
 #[derive(Serialize)]
@@ -396,6 +420,11 @@ pub enum IkaValidatorCommandResponse {
     VerifyCommissionCap(SuiTransactionBlockResponse),
     SetPricingVote(SuiTransactionBlockResponse),
     FetchCurrentPricingInfo(PathBuf),
+    RecommendPricingVote(
+        PathBuf,
+        Vec<PricingDiffEntry>,
+        Option<SuiTransactionBlockResponse>,
+    ),
 }
 
 impl IkaValidatorCommand {
@@ -1076,6 +1105,71 @@ impl IkaValidatorCommand {
                 serde_yaml::to_writer(file, &current_pricing_info)?;
                 IkaValidatorCommandResponse::FetchCurrentPricingInfo(PathBuf::from(path))
             }
+            IkaValidatorCommand::RecommendPricingVote {
+                gas_budget,
+                metrics_url,
+                pricing_recommendation_config,
+                output_file_path,
+                submit,
+                validator_operation_cap_id,
+                ika_sui_config,
+            } => {
+                let file = BufReader::new(File::open(pricing_recommendation_config)?);
+                let pricing_recommendation_config: PricingRecommendationConfig =
+                    serde_yaml::from_reader(file)?;
+                let gas_budget = gas_budget.unwrap_or(DEFAULT_GAS_BUDGET);
+                let config_path = ika_sui_config.unwrap_or(ika_config_dir()?.join(IKA_SUI_CONFIG));
+                let config = read_ika_sui_config_yaml(context, &config_path)?;
+
+                let metrics_text = reqwest::get(&metrics_url)
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?;
+                let observed_costs = pricing_recommendation::parse_observed_costs(&metrics_text);
+
+                let client = SuiClient::new(
+                    &context.get_active_env()?.rpc,
+                    SuiClientMetrics::new_for_testing(),
+                    config.packages.ika_package_id,
+                    config.packages.ika_common_package_id,
+                    config.packages.ika_dwallet_2pc_mpc_package_id,
+                    config.packages.ika_system_package_id,
+                    config.objects.ika_system_object_id,
+                    config.objects.ika_dwallet_coordinator_object_id,
+                )
+                .await?;
+                let current_pricing = client.get_pricing_info().await;
+                let recommended_pricing = pricing_recommendation::recommend_pricing(
+                    &current_pricing,
+                    &observed_costs,
+                    &pricing_recommendation_config,
+                );
+                let diff =
+                    pricing_recommendation::diff_pricing(&current_pricing, &recommended_pricing);
+
+                let path = output_file_path.unwrap_or(PathBuf::from("recommended_pricing.yaml"));
+                let file = BufWriter::new(File::create(&path)?);
+                serde_yaml::to_writer(file, &recommended_pricing)?;
+
+                let response = match validator_operation_cap_id {
+                    Some(validator_operation_cap_id) if submit => Some(
+                        set_pricing_vote(
+                            context,
+                            config.packages.ika_system_package_id,
+                            config.objects.ika_system_object_id,
+                            config.packages.ika_dwallet_2pc_mpc_package_id,
+                            config.objects.ika_dwallet_coordinator_object_id,
+                            validator_operation_cap_id,
+                            recommended_pricing,
+                            gas_budget,
+                        )
+                        .await?,
+                    ),
+                    _ => None,
+                };
+                IkaValidatorCommandResponse::RecommendPricingVote(path, diff, response)
+            }
         })
     }
 }
@@ -1153,6 +1247,27 @@ impl Display for IkaValidatorCommandResponse {
                     "Fetched current pricing info from Sui, you can view & edit it at: {path:?}"
                 )?;
             }
+            IkaValidatorCommandResponse::RecommendPricingVote(path, diff, response) => {
+                if diff.is_empty() {
+                    writeln!(writer, "The recommended pricing matches the current pricing.")?;
+                }
+                for PricingDiffEntry {
+                    key,
+                    current_fee_ika,
+                    recommended_fee_ika,
+                } in diff
+                {
+                    writeln!(
+                        writer,
+                        "curve: {}, signature algorithm: {:?}, protocol: {}, fee IKA: {current_fee_ika} -> {recommended_fee_ika}",
+                        key.curve, key.signature_algorithm, key.protocol,
+                    )?;
+                }
+                writeln!(writer, "Recommended pricing saved at: {path:?}")?;
+                if let Some(response) = response {
+                    write!(writer, "{}", write_transaction_response(response)?)?;
+                }
+            }
         }
         write!(f, "{}", writer.trim_end_matches('\n'))
     }
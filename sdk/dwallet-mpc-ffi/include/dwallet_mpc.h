@@ -0,0 +1,152 @@
+/*
+ * Copyright (c) dWallet Labs, Inc.
+ * SPDX-License-Identifier: BSD-3-Clause-Clear
+ *
+ * C ABI of the dWallet MPC centralized party (the `dwallet-mpc-ffi` crate).
+ * Keep in sync with `src/`; see the crate documentation for the calling conventions.
+ */
+
+#ifndef DWALLET_MPC_H
+#define DWALLET_MPC_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum DWalletErrorCode {
+    DWALLET_OK = 0,
+    DWALLET_ERROR_NULL_POINTER = 1,
+    DWALLET_ERROR_INVALID_ARGUMENT = 2,
+    DWALLET_ERROR_PROTOCOL = 3,
+    DWALLET_ERROR_PANIC = 4,
+} DWalletErrorCode;
+
+/* A buffer owned by the caller, to be freed exactly once with `dwallet_buffer_free`. */
+typedef struct DWalletBuffer {
+    uint8_t *data;
+    size_t len;
+} DWalletBuffer;
+
+/* An opaque handle to the key material of the user of a dWallet. */
+typedef struct DWalletUserKeys DWalletUserKeys;
+
+/* Zeroizes and frees a buffer. Does nothing if `buffer.data` is NULL. */
+void dwallet_buffer_free(DWalletBuffer buffer);
+
+/* Returns the UTF-8 error message (not NUL-terminated) of the last call on this thread. */
+DWalletBuffer dwallet_last_error_message(void);
+
+DWalletErrorCode dwallet_network_dkg_public_output_to_protocol_pp(
+    const uint8_t *network_dkg_public_output, size_t network_dkg_public_output_len,
+    DWalletBuffer *out_protocol_pp);
+
+DWalletErrorCode dwallet_generate_secp_cg_keypair_from_seed(
+    const uint8_t *seed, size_t seed_len,
+    DWalletBuffer *out_encryption_key,
+    DWalletBuffer *out_decryption_key);
+
+/* `mnemonic` and `passphrase` are UTF-8, and need not be NUL-terminated. */
+DWalletErrorCode dwallet_generate_secp_cg_keypair_from_mnemonic(
+    const uint8_t *mnemonic, size_t mnemonic_len,
+    const uint8_t *passphrase, size_t passphrase_len,
+    uint32_t account_index,
+    DWalletBuffer *out_encryption_key,
+    DWalletBuffer *out_decryption_key);
+
+DWalletErrorCode dwallet_create_dkg_centralized_output(
+    const uint8_t *protocol_pp, size_t protocol_pp_len,
+    const uint8_t *decentralized_first_round_public_output,
+    size_t decentralized_first_round_public_output_len,
+    const uint8_t *session_identifier, size_t session_identifier_len,
+    DWalletBuffer *out_public_key_share_and_proof,
+    DWalletBuffer *out_public_output,
+    DWalletBuffer *out_user_secret_share);
+
+DWalletErrorCode dwallet_create_imported_dwallet_centralized_step(
+    const uint8_t *protocol_pp, size_t protocol_pp_len,
+    const uint8_t *session_identifier, size_t session_identifier_len,
+    const uint8_t *secret_key, size_t secret_key_len,
+    DWalletBuffer *out_user_secret_share,
+    DWalletBuffer *out_public_output,
+    DWalletBuffer *out_outgoing_message);
+
+DWalletErrorCode dwallet_encrypt_secret_share(
+    const uint8_t *secret_share, size_t secret_share_len,
+    const uint8_t *encryption_key, size_t encryption_key_len,
+    const uint8_t *protocol_pp, size_t protocol_pp_len,
+    DWalletBuffer *out_encrypted_share);
+
+DWalletErrorCode dwallet_decrypt_user_share(
+    const uint8_t *decryption_key, size_t decryption_key_len,
+    const uint8_t *encryption_key, size_t encryption_key_len,
+    const uint8_t *dwallet_dkg_output, size_t dwallet_dkg_output_len,
+    const uint8_t *encrypted_user_share_and_proof, size_t encrypted_user_share_and_proof_len,
+    const uint8_t *protocol_pp, size_t protocol_pp_len,
+    DWalletBuffer *out_secret_share);
+
+DWalletErrorCode dwallet_verify_user_share(
+    const uint8_t *secret_share, size_t secret_share_len,
+    const uint8_t *dwallet_dkg_output, size_t dwallet_dkg_output_len,
+    const uint8_t *protocol_pp, size_t protocol_pp_len,
+    bool *out_is_valid);
+
+DWalletErrorCode dwallet_verify_secp_signature(
+    const uint8_t *public_key, size_t public_key_len,
+    const uint8_t *signature, size_t signature_len,
+    const uint8_t *message, size_t message_len,
+    const uint8_t *protocol_pp, size_t protocol_pp_len,
+    uint32_t hash_type,
+    bool *out_is_valid);
+
+DWalletErrorCode dwallet_derive_dwallet_public_key(
+    const uint8_t *dwallet_dkg_output, size_t dwallet_dkg_output_len,
+    const uint32_t *derivation_path, size_t derivation_path_len,
+    DWalletBuffer *out_public_key,
+    DWalletBuffer *out_chain_code);
+
+DWalletErrorCode dwallet_user_keys_new(
+    const uint8_t *protocol_pp, size_t protocol_pp_len,
+    const uint8_t *dwallet_dkg_output, size_t dwallet_dkg_output_len,
+    const uint8_t *user_secret_share, size_t user_secret_share_len,
+    const uint32_t *derivation_path, size_t derivation_path_len,
+    DWalletUserKeys **out_keys);
+
+/* Zeroizes the secret share of a handle and frees it. Does nothing if `keys` is NULL. */
+void dwallet_user_keys_free(DWalletUserKeys *keys);
+
+DWalletErrorCode dwallet_user_keys_public_key(
+    const DWalletUserKeys *keys,
+    DWalletBuffer *out_public_key);
+
+DWalletErrorCode dwallet_user_keys_verify_share(
+    const DWalletUserKeys *keys,
+    bool *out_is_valid);
+
+DWalletErrorCode dwallet_user_keys_encrypt_share(
+    const DWalletUserKeys *keys,
+    const uint8_t *encryption_key, size_t encryption_key_len,
+    DWalletBuffer *out_encrypted_share);
+
+DWalletErrorCode dwallet_user_keys_sign(
+    const DWalletUserKeys *keys,
+    const uint8_t *presign, size_t presign_len,
+    const uint8_t *message, size_t message_len,
+    uint32_t hash_type,
+    DWalletBuffer *out_message_centralized_signature);
+
+DWalletErrorCode dwallet_user_keys_verify_signature(
+    const DWalletUserKeys *keys,
+    const uint8_t *signature, size_t signature_len,
+    const uint8_t *message, size_t message_len,
+    uint32_t hash_type,
+    bool *out_is_valid);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DWALLET_MPC_H */
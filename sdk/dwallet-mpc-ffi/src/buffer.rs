@@ -0,0 +1,73 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use crate::error::{FfiError, FfiResult};
+use std::mem::MaybeUninit;
+use zeroize::Zeroize;
+
+/// A byte buffer allocated by this library.
+///
+/// The caller owns it, and must free it exactly once with [`dwallet_buffer_free`],
+/// which zeroizes it first, as buffers may hold secret key shares or decryption keys.
+#[repr(C)]
+#[derive(Debug)]
+pub struct DWalletBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl DWalletBuffer {
+    pub(crate) fn from_vec(bytes: Vec<u8>) -> Self {
+        let bytes = Box::into_raw(bytes.into_boxed_slice());
+        Self {
+            data: bytes.cast(),
+            len: bytes.len(),
+        }
+    }
+}
+
+/// Zeroizes and frees a buffer returned by this library. Does nothing if `buffer.data` is null.
+///
+/// # Safety
+/// `buffer` must have been returned by this library and not freed before.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_buffer_free(buffer: DWalletBuffer) {
+    if buffer.data.is_null() {
+        return;
+    }
+    let mut bytes =
+        unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)) };
+    bytes.zeroize();
+}
+
+/// Borrows the `len` bytes at `data` passed by the caller.
+/// `data` may only be null if `len` is zero.
+///
+/// # Safety
+/// If not null, `data` must point to `len` readable bytes that outlive the call.
+pub(crate) unsafe fn input<'a, T>(
+    data: *const T,
+    len: usize,
+    argument: &str,
+) -> FfiResult<&'a [T]> {
+    if data.is_null() {
+        return if len == 0 {
+            Ok(&[])
+        } else {
+            Err(FfiError::null_pointer(argument))
+        };
+    }
+    Ok(unsafe { std::slice::from_raw_parts(data, len) })
+}
+
+/// Borrows an output argument, possibly uninitialized,
+/// so that null outputs are rejected before any work is done.
+///
+/// # Safety
+/// If not null, `out` must point to a writable `T`.
+pub(crate) unsafe fn output<'a, T>(
+    out: *mut T,
+    argument: &str,
+) -> FfiResult<&'a mut MaybeUninit<T>> {
+    unsafe { out.cast::<MaybeUninit<T>>().as_mut() }.ok_or_else(|| FfiError::null_pointer(argument))
+}
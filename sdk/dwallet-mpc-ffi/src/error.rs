@@ -0,0 +1,91 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use crate::buffer::DWalletBuffer;
+use std::cell::RefCell;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// The status returned by every fallible function of this library.
+///
+/// On any status other than [`DWalletErrorCode::Ok`], the output arguments are left untouched,
+/// and a description of the error is available from [`dwallet_last_error_message`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DWalletErrorCode {
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// An argument was malformed, e.g. a seed of the wrong length.
+    InvalidArgument = 2,
+    /// The cryptographic operation failed, e.g. on an input that doesn't deserialize or verify.
+    ProtocolError = 3,
+    /// The library panicked. Only reported when built with `panic = "unwind"`,
+    /// as the workspace release profile aborts on panic instead.
+    Panic = 4,
+}
+
+#[derive(Debug)]
+pub(crate) struct FfiError {
+    code: DWalletErrorCode,
+    message: String,
+}
+
+pub(crate) type FfiResult<T = ()> = Result<T, FfiError>;
+
+impl FfiError {
+    pub(crate) fn null_pointer(argument: &str) -> Self {
+        Self {
+            code: DWalletErrorCode::NullPointer,
+            message: format!("`{argument}` is null"),
+        }
+    }
+
+    pub(crate) fn invalid_argument(message: impl Into<String>) -> Self {
+        Self {
+            code: DWalletErrorCode::InvalidArgument,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for FfiError {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            code: DWalletErrorCode::ProtocolError,
+            message: format!("{error:#}"),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR_MESSAGE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Runs the body of an exported function, recording its error (or panic) for
+/// [`dwallet_last_error_message`] and converting it to a status.
+pub(crate) fn ffi_call(body: impl FnOnce() -> FfiResult) -> DWalletErrorCode {
+    let (code, message) = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => (DWalletErrorCode::Ok, String::new()),
+        Ok(Err(error)) => (error.code, error.message),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            (DWalletErrorCode::Panic, format!("panicked: {message}"))
+        }
+    };
+    LAST_ERROR_MESSAGE.with(|last_error_message| *last_error_message.borrow_mut() = message);
+    code
+}
+
+/// Returns the UTF-8 description (not NUL-terminated) of the error of the last call
+/// on the current thread, or an empty buffer if it succeeded.
+/// The buffer must be freed with [`dwallet_buffer_free`](crate::buffer::dwallet_buffer_free).
+#[unsafe(no_mangle)]
+pub extern "C" fn dwallet_last_error_message() -> DWalletBuffer {
+    LAST_ERROR_MESSAGE.with(|last_error_message| {
+        DWalletBuffer::from_vec(last_error_message.borrow().clone().into_bytes())
+    })
+}
@@ -0,0 +1,428 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! C ABI bindings for the dWallet MPC centralized party, mirroring `sdk/dwallet-mpc-wasm`,
+//! for native clients (mobile apps through JNI or Swift, Go through cgo, Python through ctypes).
+//! The C declarations are in `include/dwallet_mpc.h`.
+//!
+//! Conventions:
+//! - Every fallible function returns a [`DWalletErrorCode`], and writes its results to its
+//!   `out_` arguments only on success. The error message of the last call on the current thread
+//!   is available from [`dwallet_last_error_message`].
+//! - Inputs are passed as `(pointer, length)` pairs and copied; the pointer may be null only if
+//!   the length is zero.
+//! - Byte outputs are [`DWalletBuffer`]s owned by the caller, which must free each of them with
+//!   [`dwallet_buffer_free`]. Freeing zeroizes the buffer, since it may hold a secret.
+//! - The key material of a dWallet user is held behind an opaque [`DWalletUserKeys`] handle,
+//!   whose secret share is zeroized by [`dwallet_user_keys_free`].
+
+pub mod buffer;
+pub mod error;
+pub mod user_keys;
+
+pub use buffer::{DWalletBuffer, dwallet_buffer_free};
+pub use error::{DWalletErrorCode, dwallet_last_error_message};
+pub use user_keys::{
+    DWalletUserKeys, dwallet_user_keys_encrypt_share, dwallet_user_keys_free,
+    dwallet_user_keys_new, dwallet_user_keys_public_key, dwallet_user_keys_sign,
+    dwallet_user_keys_verify_share, dwallet_user_keys_verify_signature,
+};
+
+use buffer::{input, output};
+use dwallet_mpc_centralized_party::mnemonic::generate_secp256k1_cg_keypair_from_mnemonic_internal;
+use dwallet_mpc_centralized_party::{
+    create_dkg_output, create_imported_dwallet_centralized_step_inner, decrypt_user_share_inner,
+    derive_dwallet_public_key_inner, encrypt_secret_key_share_and_prove,
+    generate_secp256k1_cg_keypair_from_seed_internal,
+    network_dkg_public_output_to_protocol_pp_inner, verify_secp_signature_inner,
+    verify_secret_share,
+};
+use error::{FfiError, ffi_call};
+
+/// Returns the serialized protocol public parameters of a network encryption key
+/// from its network DKG public output.
+///
+/// # Safety
+/// `(network_dkg_public_output, network_dkg_public_output_len)` must describe a readable array,
+/// and `out_protocol_pp` must point to a writable buffer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_network_dkg_public_output_to_protocol_pp(
+    network_dkg_public_output: *const u8,
+    network_dkg_public_output_len: usize,
+    out_protocol_pp: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let network_dkg_public_output = unsafe {
+            input(
+                network_dkg_public_output,
+                network_dkg_public_output_len,
+                "network_dkg_public_output",
+            )
+        }?;
+        let out_protocol_pp = unsafe { output(out_protocol_pp, "out_protocol_pp") }?;
+        out_protocol_pp.write(DWalletBuffer::from_vec(
+            network_dkg_public_output_to_protocol_pp_inner(network_dkg_public_output.to_vec())?,
+        ));
+        Ok(())
+    })
+}
+
+/// Derives a Secp256k1 class groups keypair from a 32-byte seed.
+///
+/// # Safety
+/// `(seed, seed_len)` must describe a readable array, and both outputs must point to
+/// writable buffers.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_generate_secp_cg_keypair_from_seed(
+    seed: *const u8,
+    seed_len: usize,
+    out_encryption_key: *mut DWalletBuffer,
+    out_decryption_key: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let seed: [u8; 32] = unsafe { input(seed, seed_len, "seed") }?
+            .try_into()
+            .map_err(|_| FfiError::invalid_argument("seed must be 32 bytes long"))?;
+        let out_encryption_key = unsafe { output(out_encryption_key, "out_encryption_key") }?;
+        let out_decryption_key = unsafe { output(out_decryption_key, "out_decryption_key") }?;
+        let (encryption_key, decryption_key) =
+            generate_secp256k1_cg_keypair_from_seed_internal(seed)?;
+        out_encryption_key.write(DWalletBuffer::from_vec(encryption_key));
+        out_decryption_key.write(DWalletBuffer::from_vec(decryption_key));
+        Ok(())
+    })
+}
+
+/// Derives the Secp256k1 class groups keypair of `account_index` from a UTF-8 BIP-39 mnemonic
+/// and passphrase.
+///
+/// # Safety
+/// `(mnemonic, mnemonic_len)` and `(passphrase, passphrase_len)` must describe readable arrays,
+/// and both outputs must point to writable buffers.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_generate_secp_cg_keypair_from_mnemonic(
+    mnemonic: *const u8,
+    mnemonic_len: usize,
+    passphrase: *const u8,
+    passphrase_len: usize,
+    account_index: u32,
+    out_encryption_key: *mut DWalletBuffer,
+    out_decryption_key: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let mnemonic = std::str::from_utf8(unsafe { input(mnemonic, mnemonic_len, "mnemonic") }?)
+            .map_err(|_| FfiError::invalid_argument("mnemonic must be UTF-8"))?;
+        let passphrase =
+            std::str::from_utf8(unsafe { input(passphrase, passphrase_len, "passphrase") }?)
+                .map_err(|_| FfiError::invalid_argument("passphrase must be UTF-8"))?;
+        let out_encryption_key = unsafe { output(out_encryption_key, "out_encryption_key") }?;
+        let out_decryption_key = unsafe { output(out_decryption_key, "out_decryption_key") }?;
+        let (encryption_key, decryption_key) =
+            generate_secp256k1_cg_keypair_from_mnemonic_internal(
+                mnemonic,
+                passphrase,
+                account_index,
+            )?;
+        out_encryption_key.write(DWalletBuffer::from_vec(encryption_key));
+        out_decryption_key.write(DWalletBuffer::from_vec(decryption_key));
+        Ok(())
+    })
+}
+
+/// Executes the user's round of the dWallet DKG.
+/// Outputs the public key share and proof to submit to the network, the user's DKG public
+/// output, and the user secret share, which must never leave the device unencrypted.
+///
+/// # Safety
+/// Every `(pointer, length)` pair must describe a readable array,
+/// and every output must point to a writable buffer.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dwallet_create_dkg_centralized_output(
+    protocol_pp: *const u8,
+    protocol_pp_len: usize,
+    decentralized_first_round_public_output: *const u8,
+    decentralized_first_round_public_output_len: usize,
+    session_identifier: *const u8,
+    session_identifier_len: usize,
+    out_public_key_share_and_proof: *mut DWalletBuffer,
+    out_public_output: *mut DWalletBuffer,
+    out_user_secret_share: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let protocol_pp = unsafe { input(protocol_pp, protocol_pp_len, "protocol_pp") }?;
+        let decentralized_first_round_public_output = unsafe {
+            input(
+                decentralized_first_round_public_output,
+                decentralized_first_round_public_output_len,
+                "decentralized_first_round_public_output",
+            )
+        }?;
+        let session_identifier = unsafe {
+            input(
+                session_identifier,
+                session_identifier_len,
+                "session_identifier",
+            )
+        }?;
+        let out_public_key_share_and_proof = unsafe {
+            output(
+                out_public_key_share_and_proof,
+                "out_public_key_share_and_proof",
+            )
+        }?;
+        let out_public_output = unsafe { output(out_public_output, "out_public_output") }?;
+        let out_user_secret_share =
+            unsafe { output(out_user_secret_share, "out_user_secret_share") }?;
+
+        let dkg_output = create_dkg_output(
+            protocol_pp.to_vec(),
+            decentralized_first_round_public_output.to_vec(),
+            session_identifier.to_vec(),
+        )?;
+        out_public_key_share_and_proof.write(DWalletBuffer::from_vec(
+            dkg_output.public_key_share_and_proof,
+        ));
+        out_public_output.write(DWalletBuffer::from_vec(dkg_output.public_output));
+        out_user_secret_share.write(DWalletBuffer::from_vec(
+            dkg_output.centralized_secret_output,
+        ));
+        Ok(())
+    })
+}
+
+/// Executes the user's round of importing an existing secret key as a dWallet.
+/// Outputs the user secret share, the user's public output,
+/// and the outgoing message to submit to the network.
+///
+/// # Safety
+/// Every `(pointer, length)` pair must describe a readable array,
+/// and every output must point to a writable buffer.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dwallet_create_imported_dwallet_centralized_step(
+    protocol_pp: *const u8,
+    protocol_pp_len: usize,
+    session_identifier: *const u8,
+    session_identifier_len: usize,
+    secret_key: *const u8,
+    secret_key_len: usize,
+    out_user_secret_share: *mut DWalletBuffer,
+    out_public_output: *mut DWalletBuffer,
+    out_outgoing_message: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let protocol_pp = unsafe { input(protocol_pp, protocol_pp_len, "protocol_pp") }?;
+        let session_identifier = unsafe {
+            input(
+                session_identifier,
+                session_identifier_len,
+                "session_identifier",
+            )
+        }?;
+        let secret_key = unsafe { input(secret_key, secret_key_len, "secret_key") }?;
+        let out_user_secret_share =
+            unsafe { output(out_user_secret_share, "out_user_secret_share") }?;
+        let out_public_output = unsafe { output(out_public_output, "out_public_output") }?;
+        let out_outgoing_message = unsafe { output(out_outgoing_message, "out_outgoing_message") }?;
+
+        let (user_secret_share, public_output, outgoing_message) =
+            create_imported_dwallet_centralized_step_inner(
+                protocol_pp.to_vec(),
+                session_identifier.to_vec(),
+                secret_key.to_vec(),
+            )?;
+        out_user_secret_share.write(DWalletBuffer::from_vec(user_secret_share));
+        out_public_output.write(DWalletBuffer::from_vec(public_output));
+        out_outgoing_message.write(DWalletBuffer::from_vec(outgoing_message));
+        Ok(())
+    })
+}
+
+/// Encrypts a user secret share to `encryption_key`, with a proof of encryption.
+///
+/// # Safety
+/// Every `(pointer, length)` pair must describe a readable array,
+/// and `out_encrypted_share` must point to a writable buffer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_encrypt_secret_share(
+    secret_share: *const u8,
+    secret_share_len: usize,
+    encryption_key: *const u8,
+    encryption_key_len: usize,
+    protocol_pp: *const u8,
+    protocol_pp_len: usize,
+    out_encrypted_share: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let secret_share = unsafe { input(secret_share, secret_share_len, "secret_share") }?;
+        let encryption_key =
+            unsafe { input(encryption_key, encryption_key_len, "encryption_key") }?;
+        let protocol_pp = unsafe { input(protocol_pp, protocol_pp_len, "protocol_pp") }?;
+        let out_encrypted_share = unsafe { output(out_encrypted_share, "out_encrypted_share") }?;
+        out_encrypted_share.write(DWalletBuffer::from_vec(encrypt_secret_key_share_and_prove(
+            secret_share.to_vec(),
+            encryption_key.to_vec(),
+            protocol_pp.to_vec(),
+        )?));
+        Ok(())
+    })
+}
+
+/// Decrypts an encrypted user share with `decryption_key`, after verifying its proof of encryption.
+///
+/// # Safety
+/// Every `(pointer, length)` pair must describe a readable array,
+/// and `out_secret_share` must point to a writable buffer.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dwallet_decrypt_user_share(
+    decryption_key: *const u8,
+    decryption_key_len: usize,
+    encryption_key: *const u8,
+    encryption_key_len: usize,
+    dwallet_dkg_output: *const u8,
+    dwallet_dkg_output_len: usize,
+    encrypted_user_share_and_proof: *const u8,
+    encrypted_user_share_and_proof_len: usize,
+    protocol_pp: *const u8,
+    protocol_pp_len: usize,
+    out_secret_share: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let decryption_key =
+            unsafe { input(decryption_key, decryption_key_len, "decryption_key") }?;
+        let encryption_key =
+            unsafe { input(encryption_key, encryption_key_len, "encryption_key") }?;
+        let dwallet_dkg_output = unsafe {
+            input(
+                dwallet_dkg_output,
+                dwallet_dkg_output_len,
+                "dwallet_dkg_output",
+            )
+        }?;
+        let encrypted_user_share_and_proof = unsafe {
+            input(
+                encrypted_user_share_and_proof,
+                encrypted_user_share_and_proof_len,
+                "encrypted_user_share_and_proof",
+            )
+        }?;
+        let protocol_pp = unsafe { input(protocol_pp, protocol_pp_len, "protocol_pp") }?;
+        let out_secret_share = unsafe { output(out_secret_share, "out_secret_share") }?;
+        out_secret_share.write(DWalletBuffer::from_vec(decrypt_user_share_inner(
+            decryption_key.to_vec(),
+            encryption_key.to_vec(),
+            dwallet_dkg_output.to_vec(),
+            encrypted_user_share_and_proof.to_vec(),
+            protocol_pp.to_vec(),
+        )?));
+        Ok(())
+    })
+}
+
+/// Verifies that a user secret share matches the public share in the dWallet's DKG output.
+///
+/// # Safety
+/// Every `(pointer, length)` pair must describe a readable array,
+/// and `out_is_valid` must point to a writable `bool`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_verify_user_share(
+    secret_share: *const u8,
+    secret_share_len: usize,
+    dwallet_dkg_output: *const u8,
+    dwallet_dkg_output_len: usize,
+    protocol_pp: *const u8,
+    protocol_pp_len: usize,
+    out_is_valid: *mut bool,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let secret_share = unsafe { input(secret_share, secret_share_len, "secret_share") }?;
+        let dwallet_dkg_output = unsafe {
+            input(
+                dwallet_dkg_output,
+                dwallet_dkg_output_len,
+                "dwallet_dkg_output",
+            )
+        }?;
+        let protocol_pp = unsafe { input(protocol_pp, protocol_pp_len, "protocol_pp") }?;
+        let out_is_valid = unsafe { output(out_is_valid, "out_is_valid") }?;
+        out_is_valid.write(verify_secret_share(
+            secret_share.to_vec(),
+            dwallet_dkg_output.to_vec(),
+            protocol_pp.to_vec(),
+        )?);
+        Ok(())
+    })
+}
+
+/// Verifies a sign output of the network on `message` against a serialized dWallet public key.
+///
+/// # Safety
+/// Every `(pointer, length)` pair must describe a readable array,
+/// and `out_is_valid` must point to a writable `bool`.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dwallet_verify_secp_signature(
+    public_key: *const u8,
+    public_key_len: usize,
+    signature: *const u8,
+    signature_len: usize,
+    message: *const u8,
+    message_len: usize,
+    protocol_pp: *const u8,
+    protocol_pp_len: usize,
+    hash_type: u32,
+    out_is_valid: *mut bool,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let public_key = unsafe { input(public_key, public_key_len, "public_key") }?;
+        let signature = unsafe { input(signature, signature_len, "signature") }?;
+        let message = unsafe { input(message, message_len, "message") }?;
+        let protocol_pp = unsafe { input(protocol_pp, protocol_pp_len, "protocol_pp") }?;
+        let out_is_valid = unsafe { output(out_is_valid, "out_is_valid") }?;
+        out_is_valid.write(verify_secp_signature_inner(
+            public_key.to_vec(),
+            signature.to_vec(),
+            message.to_vec(),
+            protocol_pp.to_vec(),
+            hash_type,
+        )?);
+        Ok(())
+    })
+}
+
+/// Derives the serialized public key and chain code of the child key of a dWallet
+/// at the non-hardened `derivation_path`.
+///
+/// # Safety
+/// Every `(pointer, length)` pair must describe a readable array,
+/// and both outputs must point to writable buffers.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_derive_dwallet_public_key(
+    dwallet_dkg_output: *const u8,
+    dwallet_dkg_output_len: usize,
+    derivation_path: *const u32,
+    derivation_path_len: usize,
+    out_public_key: *mut DWalletBuffer,
+    out_chain_code: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let dwallet_dkg_output = unsafe {
+            input(
+                dwallet_dkg_output,
+                dwallet_dkg_output_len,
+                "dwallet_dkg_output",
+            )
+        }?;
+        let derivation_path =
+            unsafe { input(derivation_path, derivation_path_len, "derivation_path") }?;
+        let out_public_key = unsafe { output(out_public_key, "out_public_key") }?;
+        let out_chain_code = unsafe { output(out_chain_code, "out_chain_code") }?;
+        let (public_key, chain_code) =
+            derive_dwallet_public_key_inner(dwallet_dkg_output.to_vec(), derivation_path.to_vec())?;
+        out_public_key.write(DWalletBuffer::from_vec(public_key));
+        out_chain_code.write(DWalletBuffer::from_vec(chain_code));
+        Ok(())
+    })
+}
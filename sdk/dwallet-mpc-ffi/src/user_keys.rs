@@ -0,0 +1,233 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! An opaque handle to the key material of the user of a dWallet,
+//! so that its secret share is copied across the ABI once, and zeroized when the handle is freed.
+
+use crate::buffer::{DWalletBuffer, input, output};
+use crate::error::{DWalletErrorCode, FfiError, FfiResult, ffi_call};
+use dwallet_mpc_centralized_party::sign_session;
+use dwallet_mpc_centralized_party::{
+    advance_centralized_sign_party, encrypt_secret_key_share_and_prove,
+    verify_secp_signature_inner, verify_secret_share,
+};
+use zeroize::Zeroize;
+
+/// The key material of the user of a dWallet (or of its child key).
+pub struct DWalletUserKeys(sign_session::DWalletUserKeys);
+
+impl Drop for DWalletUserKeys {
+    fn drop(&mut self) {
+        self.0.user_secret_key_share.zeroize();
+    }
+}
+
+/// Creates a handle to the key material of the user of a dWallet,
+/// signing with its child key at the non-hardened `derivation_path` if it is not empty.
+/// The handle must be freed with [`dwallet_user_keys_free`].
+///
+/// The secret share is not checked against the DKG output;
+/// use [`dwallet_user_keys_verify_share`] to do so.
+///
+/// # Safety
+/// Every `(pointer, length)` pair must describe a readable array that outlives the call,
+/// and `out_keys` must point to a writable handle pointer.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn dwallet_user_keys_new(
+    protocol_pp: *const u8,
+    protocol_pp_len: usize,
+    dwallet_dkg_output: *const u8,
+    dwallet_dkg_output_len: usize,
+    user_secret_share: *const u8,
+    user_secret_share_len: usize,
+    derivation_path: *const u32,
+    derivation_path_len: usize,
+    out_keys: *mut *mut DWalletUserKeys,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let protocol_pp = unsafe { input(protocol_pp, protocol_pp_len, "protocol_pp") }?;
+        let dwallet_dkg_output = unsafe {
+            input(
+                dwallet_dkg_output,
+                dwallet_dkg_output_len,
+                "dwallet_dkg_output",
+            )
+        }?;
+        let user_secret_share = unsafe {
+            input(
+                user_secret_share,
+                user_secret_share_len,
+                "user_secret_share",
+            )
+        }?;
+        let derivation_path =
+            unsafe { input(derivation_path, derivation_path_len, "derivation_path") }?;
+        let out_keys = unsafe { output(out_keys, "out_keys") }?;
+        let keys = sign_session::DWalletUserKeys {
+            protocol_public_parameters: protocol_pp.to_vec(),
+            dwallet_dkg_output: dwallet_dkg_output.to_vec(),
+            user_secret_key_share: user_secret_share.to_vec(),
+            derivation_path: derivation_path.to_vec(),
+        };
+        out_keys.write(Box::into_raw(Box::new(DWalletUserKeys(keys))));
+        Ok(())
+    })
+}
+
+/// Zeroizes the secret share of a handle and frees it. Does nothing if `keys` is null.
+///
+/// # Safety
+/// `keys` must have been returned by [`dwallet_user_keys_new`] and not freed before.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_user_keys_free(keys: *mut DWalletUserKeys) {
+    if !keys.is_null() {
+        drop(unsafe { Box::from_raw(keys) });
+    }
+}
+
+/// Returns the serialized public key of the dWallet (or of its child key).
+///
+/// # Safety
+/// `keys` must be a live handle, and `out_public_key` must point to a writable buffer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_user_keys_public_key(
+    keys: *const DWalletUserKeys,
+    out_public_key: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let keys = unsafe { user_keys(keys) }?;
+        let out_public_key = unsafe { output(out_public_key, "out_public_key") }?;
+        out_public_key.write(DWalletBuffer::from_vec(keys.public_key()?));
+        Ok(())
+    })
+}
+
+/// Verifies that the secret share of the handle matches the public share in its DKG output.
+///
+/// # Safety
+/// `keys` must be a live handle, and `out_is_valid` must point to a writable `bool`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_user_keys_verify_share(
+    keys: *const DWalletUserKeys,
+    out_is_valid: *mut bool,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let keys = unsafe { user_keys(keys) }?;
+        let out_is_valid = unsafe { output(out_is_valid, "out_is_valid") }?;
+        out_is_valid.write(verify_secret_share(
+            keys.user_secret_key_share.clone(),
+            keys.dwallet_dkg_output.clone(),
+            keys.protocol_public_parameters.clone(),
+        )?);
+        Ok(())
+    })
+}
+
+/// Encrypts the secret share of the handle to `encryption_key`, with a proof of encryption.
+///
+/// # Safety
+/// `keys` must be a live handle, `(encryption_key, encryption_key_len)` must describe
+/// a readable array, and `out_encrypted_share` must point to a writable buffer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_user_keys_encrypt_share(
+    keys: *const DWalletUserKeys,
+    encryption_key: *const u8,
+    encryption_key_len: usize,
+    out_encrypted_share: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let keys = unsafe { user_keys(keys) }?;
+        let encryption_key =
+            unsafe { input(encryption_key, encryption_key_len, "encryption_key") }?;
+        let out_encrypted_share = unsafe { output(out_encrypted_share, "out_encrypted_share") }?;
+        out_encrypted_share.write(DWalletBuffer::from_vec(encrypt_secret_key_share_and_prove(
+            keys.user_secret_key_share.clone(),
+            encryption_key.to_vec(),
+            keys.protocol_public_parameters.clone(),
+        )?));
+        Ok(())
+    })
+}
+
+/// Computes the user's signature on `message` with `presign`,
+/// to submit to the network in a sign request.
+///
+/// # Safety
+/// `keys` must be a live handle, every `(pointer, length)` pair must describe a readable array,
+/// and `out_message_centralized_signature` must point to a writable buffer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_user_keys_sign(
+    keys: *const DWalletUserKeys,
+    presign: *const u8,
+    presign_len: usize,
+    message: *const u8,
+    message_len: usize,
+    hash_type: u32,
+    out_message_centralized_signature: *mut DWalletBuffer,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let keys = unsafe { user_keys(keys) }?;
+        let presign = unsafe { input(presign, presign_len, "presign") }?;
+        let message = unsafe { input(message, message_len, "message") }?;
+        let out_message_centralized_signature = unsafe {
+            output(
+                out_message_centralized_signature,
+                "out_message_centralized_signature",
+            )
+        }?;
+        out_message_centralized_signature.write(DWalletBuffer::from_vec(
+            advance_centralized_sign_party(
+                keys.protocol_public_parameters.clone(),
+                keys.dwallet_dkg_output.clone(),
+                keys.user_secret_key_share.clone(),
+                presign.to_vec(),
+                message.to_vec(),
+                hash_type,
+                keys.derivation_path.clone(),
+            )?,
+        ));
+        Ok(())
+    })
+}
+
+/// Verifies a sign output of the network on `message` against the public key of the handle.
+///
+/// # Safety
+/// `keys` must be a live handle, every `(pointer, length)` pair must describe a readable array,
+/// and `out_is_valid` must point to a writable `bool`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dwallet_user_keys_verify_signature(
+    keys: *const DWalletUserKeys,
+    signature: *const u8,
+    signature_len: usize,
+    message: *const u8,
+    message_len: usize,
+    hash_type: u32,
+    out_is_valid: *mut bool,
+) -> DWalletErrorCode {
+    ffi_call(|| {
+        let keys = unsafe { user_keys(keys) }?;
+        let signature = unsafe { input(signature, signature_len, "signature") }?;
+        let message = unsafe { input(message, message_len, "message") }?;
+        let out_is_valid = unsafe { output(out_is_valid, "out_is_valid") }?;
+        out_is_valid.write(verify_secp_signature_inner(
+            keys.public_key()?,
+            signature.to_vec(),
+            message.to_vec(),
+            keys.protocol_public_parameters.clone(),
+            hash_type,
+        )?);
+        Ok(())
+    })
+}
+
+/// # Safety
+/// If not null, `keys` must be a live handle.
+unsafe fn user_keys<'a>(
+    keys: *const DWalletUserKeys,
+) -> FfiResult<&'a sign_session::DWalletUserKeys> {
+    unsafe { keys.as_ref() }
+        .map(|keys| &keys.0)
+        .ok_or_else(|| FfiError::null_pointer("keys"))
+}
@@ -0,0 +1,169 @@
+// Copyright (c) dWallet Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use dwallet_mpc_ffi::*;
+use std::ptr;
+
+fn last_error_message() -> String {
+    let buffer = dwallet_last_error_message();
+    let message = if buffer.len == 0 {
+        String::new()
+    } else {
+        String::from_utf8(unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) }.to_vec())
+            .unwrap()
+    };
+    unsafe { dwallet_buffer_free(buffer) };
+    message
+}
+
+fn empty_buffer() -> DWalletBuffer {
+    DWalletBuffer {
+        data: ptr::null_mut(),
+        len: 0,
+    }
+}
+
+#[test]
+fn null_inputs_with_a_length_are_rejected() {
+    let mut protocol_pp = empty_buffer();
+    let code = unsafe {
+        dwallet_network_dkg_public_output_to_protocol_pp(ptr::null(), 1, &mut protocol_pp)
+    };
+
+    assert_eq!(code, DWalletErrorCode::NullPointer);
+    assert_eq!(last_error_message(), "`network_dkg_public_output` is null");
+    assert!(protocol_pp.data.is_null());
+}
+
+#[test]
+fn null_outputs_are_rejected() {
+    let seed = [0; 32];
+    let mut encryption_key = empty_buffer();
+    let code = unsafe {
+        dwallet_generate_secp_cg_keypair_from_seed(
+            seed.as_ptr(),
+            seed.len(),
+            &mut encryption_key,
+            ptr::null_mut(),
+        )
+    };
+
+    assert_eq!(code, DWalletErrorCode::NullPointer);
+    assert_eq!(last_error_message(), "`out_decryption_key` is null");
+    assert!(encryption_key.data.is_null());
+}
+
+#[test]
+fn seed_length_is_checked() {
+    let seed = [0; 31];
+    let (mut encryption_key, mut decryption_key) = (empty_buffer(), empty_buffer());
+    let code = unsafe {
+        dwallet_generate_secp_cg_keypair_from_seed(
+            seed.as_ptr(),
+            seed.len(),
+            &mut encryption_key,
+            &mut decryption_key,
+        )
+    };
+
+    assert_eq!(code, DWalletErrorCode::InvalidArgument);
+    assert_eq!(last_error_message(), "seed must be 32 bytes long");
+}
+
+#[test]
+fn mnemonics_are_validated() {
+    let passphrase = b"";
+    for (mnemonic, code) in [
+        (&b"abandon \xff"[..], DWalletErrorCode::InvalidArgument),
+        (
+            &b"abandon abandon abandon"[..],
+            DWalletErrorCode::ProtocolError,
+        ),
+    ] {
+        let (mut encryption_key, mut decryption_key) = (empty_buffer(), empty_buffer());
+        let returned_code = unsafe {
+            dwallet_generate_secp_cg_keypair_from_mnemonic(
+                mnemonic.as_ptr(),
+                mnemonic.len(),
+                passphrase.as_ptr(),
+                passphrase.len(),
+                0,
+                &mut encryption_key,
+                &mut decryption_key,
+            )
+        };
+
+        assert_eq!(returned_code, code);
+        assert!(encryption_key.data.is_null());
+    }
+}
+
+#[test]
+fn protocol_errors_are_reported() {
+    let dwallet_dkg_output = [0xff; 8];
+    let (mut public_key, mut chain_code) = (empty_buffer(), empty_buffer());
+    let code = unsafe {
+        dwallet_derive_dwallet_public_key(
+            dwallet_dkg_output.as_ptr(),
+            dwallet_dkg_output.len(),
+            ptr::null(),
+            0,
+            &mut public_key,
+            &mut chain_code,
+        )
+    };
+
+    assert_eq!(code, DWalletErrorCode::ProtocolError);
+    assert!(!last_error_message().is_empty());
+    assert!(public_key.data.is_null());
+}
+
+#[test]
+fn user_keys_handle_lifecycle() {
+    let user_secret_share = [0x01; 8];
+    let derivation_path = [0u32, 1];
+    let mut keys = ptr::null_mut();
+    let code = unsafe {
+        dwallet_user_keys_new(
+            ptr::null(),
+            0,
+            ptr::null(),
+            0,
+            user_secret_share.as_ptr(),
+            user_secret_share.len(),
+            derivation_path.as_ptr(),
+            derivation_path.len(),
+            &mut keys,
+        )
+    };
+    assert_eq!(code, DWalletErrorCode::Ok);
+    assert_eq!(last_error_message(), "");
+    assert!(!keys.is_null());
+
+    // The handle holds no valid DKG output.
+    let mut public_key = empty_buffer();
+    let code = unsafe { dwallet_user_keys_public_key(keys, &mut public_key) };
+    assert_eq!(code, DWalletErrorCode::ProtocolError);
+
+    unsafe {
+        dwallet_user_keys_free(keys);
+        dwallet_user_keys_free(ptr::null_mut());
+    }
+
+    let code = unsafe { dwallet_user_keys_public_key(ptr::null(), &mut public_key) };
+    assert_eq!(code, DWalletErrorCode::NullPointer);
+    assert_eq!(last_error_message(), "`keys` is null");
+}
+
+#[test]
+fn buffers_are_freed() {
+    let code = unsafe { dwallet_user_keys_verify_share(ptr::null(), ptr::null_mut()) };
+    assert_eq!(code, DWalletErrorCode::NullPointer);
+    let message = dwallet_last_error_message();
+    assert_eq!(message.len, "`keys` is null".len());
+    unsafe {
+        dwallet_buffer_free(message);
+        // Freeing a null buffer does nothing.
+        dwallet_buffer_free(empty_buffer());
+    }
+}
@@ -1,12 +1,32 @@
 // Copyright (c) dWallet Labs, Ltd.
 // SPDX-License-Identifier: BSD-3-Clause-Clear
 
+use dwallet_mpc_centralized_party::backup::{
+    BackupKdfParams, create_dwallet_backup_inner, export_dwallet_backup_chunks,
+    import_dwallet_backup_chunks, inspect_dwallet_backup_inner, restore_dwallet_backup_inner,
+};
+use dwallet_mpc_centralized_party::device_shares::{
+    advance_centralized_sign_party_with_device_shares, combine_user_device_shares_inner,
+    encrypt_device_shares_and_prove, reshare_user_device_shares_inner,
+    split_user_secret_share_inner, verify_user_device_share_inner,
+};
+use dwallet_mpc_centralized_party::encodings::{
+    bitcoin_p2pkh_address, bitcoin_p2tr_address, bitcoin_p2wpkh_address, ethereum_address,
+    public_key_to_sec1, signature_to_compact, signature_to_der, signature_to_ethereum,
+    signature_to_recoverable,
+};
+use dwallet_mpc_centralized_party::mnemonic::generate_secp256k1_cg_keypair_from_mnemonic_internal;
+use dwallet_mpc_centralized_party::output_verification::{
+    verify_dwallet_dkg_output_against_centralized_output_inner, verify_dwallet_dkg_output_inner,
+    verify_sign_inputs_inner,
+};
 use dwallet_mpc_centralized_party::{
     advance_centralized_sign_party, create_dkg_output,
-    create_imported_dwallet_centralized_step_inner, decrypt_user_share_inner,
-    encrypt_secret_key_share_and_prove, generate_secp256k1_cg_keypair_from_seed_internal,
-    network_dkg_public_output_to_protocol_pp_inner, sample_dwallet_keypair_inner,
-    verify_secp_signature_inner, verify_secret_share,
+    create_imported_dwallet_centralized_step_inner, create_key_refresh_inner,
+    decrypt_user_share_inner, derive_dwallet_public_key_inner, encrypt_secret_key_share_and_prove,
+    encrypt_to_network_key_inner, generate_secp256k1_cg_keypair_from_seed_internal,
+    network_dkg_public_output_to_protocol_pp_inner, open_network_decrypted_data,
+    sample_dwallet_keypair_inner, verify_secp_signature_inner, verify_secret_share,
 };
 use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::*;
@@ -49,6 +69,21 @@ pub fn generate_secp_cg_keypair_from_seed(seed: &[u8]) -> Result<JsValue, JsErro
     Ok(serde_wasm_bindgen::to_value(&(public_key, private_key))?)
 }
 
+/// Derives the Secp256k1 class groups keypair of `account_index` from a BIP-39 mnemonic
+/// and passphrase, so that every encrypted user share can be restored from the mnemonic.
+/// The same mnemonic, passphrase and account index always derive the same keypair.
+#[wasm_bindgen]
+pub fn generate_secp_cg_keypair_from_mnemonic(
+    mnemonic: &str,
+    passphrase: &str,
+    account_index: u32,
+) -> Result<JsValue, JsError> {
+    let (public_key, private_key) =
+        generate_secp256k1_cg_keypair_from_mnemonic_internal(mnemonic, passphrase, account_index)
+            .map_err(to_js_err)?;
+    Ok(serde_wasm_bindgen::to_value(&(public_key, private_key))?)
+}
+
 #[wasm_bindgen]
 pub fn network_dkg_public_output_to_protocol_pp(
     network_dkg_public_output: Vec<u8>,
@@ -106,6 +141,57 @@ pub fn verify_user_share(
     ))
 }
 
+/// Verifies that a dWallet DKG output read from the chain is a well-formed DKG output
+/// of the dWallet of `public_key`, under the network encryption key of `protocol_pp`.
+/// Throws if it is not.
+#[wasm_bindgen]
+pub fn verify_dwallet_dkg_output(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: Vec<u8>,
+    public_key: Vec<u8>,
+) -> Result<(), JsError> {
+    verify_dwallet_dkg_output_inner(protocol_pp, dwallet_dkg_output, public_key).map_err(to_js_err)
+}
+
+/// Verifies that a dWallet DKG output read from the chain matches the user's own
+/// centralized DKG public output. Throws if it doesn't.
+#[wasm_bindgen]
+pub fn verify_dwallet_dkg_output_against_centralized_output(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: Vec<u8>,
+    centralized_public_output: Vec<u8>,
+) -> Result<(), JsError> {
+    verify_dwallet_dkg_output_against_centralized_output_inner(
+        protocol_pp,
+        dwallet_dkg_output,
+        centralized_public_output,
+    )
+    .map_err(to_js_err)
+}
+
+/// Verifies the dWallet DKG output and the presign read from the chain before signing with them.
+/// `presign_dwallet_id` is the dWallet the presign was certified for, if any.
+/// Throws if either is malformed, or if the presign is of another dWallet.
+#[wasm_bindgen]
+pub fn verify_sign_inputs(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: Vec<u8>,
+    public_key: Vec<u8>,
+    dwallet_id: Vec<u8>,
+    presign_dwallet_id: Option<Vec<u8>>,
+    presign: Vec<u8>,
+) -> Result<(), JsError> {
+    verify_sign_inputs_inner(
+        protocol_pp,
+        dwallet_dkg_output,
+        public_key,
+        dwallet_id,
+        presign_dwallet_id,
+        presign,
+    )
+    .map_err(to_js_err)
+}
+
 #[wasm_bindgen]
 pub fn sample_dwallet_keypair(network_dkg_public_output: Vec<u8>) -> Result<JsValue, JsError> {
     Ok(serde_wasm_bindgen::to_value(
@@ -165,12 +251,382 @@ pub fn create_sign_centralized_output(
         presign,
         message,
         hash_type,
+        Vec::new(),
+    )
+    .map_err(|e| JsError::new(&e.to_string()))?;
+
+    serde_wasm_bindgen::to_value(&signed_message).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Like `create_sign_centralized_output`,
+/// but signs with the child key of the dWallet at the non-hardened `derivation_path`.
+#[wasm_bindgen]
+pub fn create_derived_key_sign_centralized_output(
+    network_dkg_public_output: Vec<u8>,
+    decentralized_party_dkg_public_output: Vec<u8>,
+    centralized_party_dkg_secret_output: Vec<u8>,
+    presign: Vec<u8>,
+    message: Vec<u8>,
+    hash_type: u32,
+    derivation_path: Vec<u32>,
+) -> Result<JsValue, JsError> {
+    let signed_message = advance_centralized_sign_party(
+        network_dkg_public_output,
+        decentralized_party_dkg_public_output,
+        centralized_party_dkg_secret_output,
+        presign,
+        message,
+        hash_type,
+        derivation_path,
     )
     .map_err(|e| JsError::new(&e.to_string()))?;
 
     serde_wasm_bindgen::to_value(&signed_message).map_err(|e| JsError::new(&e.to_string()))
 }
 
+/// Derives the public key of the child key of a dWallet at the non-hardened `derivation_path`.
+/// Returns a tuple of the serialized public key and its chain code.
+#[wasm_bindgen]
+pub fn derive_dwallet_public_key(
+    dwallet_dkg_output: Vec<u8>,
+    derivation_path: Vec<u32>,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &derive_dwallet_public_key_inner(dwallet_dkg_output, derivation_path).map_err(to_js_err)?,
+    )?)
+}
+
+/// Encrypts the given data so that it can only be opened after the network threshold decrypts it.
+/// Returns a tuple of the key encrypted to the network key and the sealed data.
+#[wasm_bindgen]
+pub fn encrypt_to_network_key(protocol_pp: Vec<u8>, data: Vec<u8>) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &encrypt_to_network_key_inner(protocol_pp, data).map_err(to_js_err)?,
+    )?)
+}
+
+/// Opens data sealed by `encrypt_to_network_key` using the threshold decryption output,
+/// which holds its key re-encrypted to `encryption_key`.
+#[wasm_bindgen]
+pub fn open_network_decrypted(
+    decryption_key: Vec<u8>,
+    encryption_key: Vec<u8>,
+    encrypted_key: Vec<u8>,
+    threshold_decryption_output: Vec<u8>,
+    sealed_data: Vec<u8>,
+    protocol_pp: Vec<u8>,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &open_network_decrypted_data(
+            decryption_key,
+            encryption_key,
+            encrypted_key,
+            threshold_decryption_output,
+            sealed_data,
+            protocol_pp,
+        )
+        .map_err(to_js_err)?,
+    )?)
+}
+
+/// Creates the user's part of a dWallet key refresh.
+/// Returns a tuple of the refresh share encrypted to the network key,
+/// the refreshed user secret share, and the refreshed user secret share encrypted to `encryption_key`.
+#[wasm_bindgen]
+pub fn create_key_refresh(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: Vec<u8>,
+    user_secret_share: Vec<u8>,
+    encryption_key: Vec<u8>,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &create_key_refresh_inner(
+            protocol_pp,
+            dwallet_dkg_output,
+            user_secret_share,
+            encryption_key,
+        )
+        .map_err(to_js_err)?,
+    )?)
+}
+
+/// Splits the user secret share of a dWallet into `number_of_devices` device shares,
+/// any `threshold` of which recombine it.
+#[wasm_bindgen]
+pub fn split_user_secret_share(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: Vec<u8>,
+    user_secret_share: Vec<u8>,
+    threshold: u32,
+    number_of_devices: u32,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &split_user_secret_share_inner(
+            protocol_pp,
+            dwallet_dkg_output,
+            user_secret_share,
+            threshold,
+            number_of_devices,
+        )
+        .map_err(to_js_err)?,
+    )?)
+}
+
+/// Verifies that a device share is a share of the user secret share of the dWallet.
+#[wasm_bindgen]
+pub fn verify_user_device_share(
+    dwallet_dkg_output: Vec<u8>,
+    device_share: Vec<u8>,
+) -> Result<JsValue, JsError> {
+    Ok(JsValue::from(
+        verify_user_device_share_inner(dwallet_dkg_output, device_share).map_err(to_js_err)?,
+    ))
+}
+
+/// Recombines the user secret share of a dWallet from at least `threshold` device shares.
+/// `device_shares` is an array of serialized device shares.
+#[wasm_bindgen]
+pub fn combine_user_device_shares(
+    dwallet_dkg_output: Vec<u8>,
+    device_shares: JsValue,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &combine_user_device_shares_inner(
+            dwallet_dkg_output,
+            serde_wasm_bindgen::from_value(device_shares)?,
+        )
+        .map_err(to_js_err)?,
+    )?)
+}
+
+/// Like `create_derived_key_sign_centralized_output`,
+/// but signs with the user secret share recombined from at least `threshold` device shares.
+#[wasm_bindgen]
+pub fn create_sign_centralized_output_with_device_shares(
+    network_dkg_public_output: Vec<u8>,
+    decentralized_party_dkg_public_output: Vec<u8>,
+    device_shares: JsValue,
+    presign: Vec<u8>,
+    message: Vec<u8>,
+    hash_type: u32,
+    derivation_path: Vec<u32>,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &advance_centralized_sign_party_with_device_shares(
+            network_dkg_public_output,
+            decentralized_party_dkg_public_output,
+            serde_wasm_bindgen::from_value(device_shares)?,
+            presign,
+            message,
+            hash_type,
+            derivation_path,
+        )
+        .map_err(to_js_err)?,
+    )?)
+}
+
+/// Encrypts the user secret share recombined from at least `threshold` device shares
+/// to the given encryption key, with a proof of encryption.
+#[wasm_bindgen]
+pub fn encrypt_device_shares(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: Vec<u8>,
+    device_shares: JsValue,
+    encryption_key: Vec<u8>,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &encrypt_device_shares_and_prove(
+            protocol_pp,
+            dwallet_dkg_output,
+            serde_wasm_bindgen::from_value(device_shares)?,
+            encryption_key,
+        )
+        .map_err(to_js_err)?,
+    )?)
+}
+
+/// Splits the user secret share anew from at least `threshold` device shares,
+/// e.g. to replace a lost device.
+#[wasm_bindgen]
+pub fn reshare_user_device_shares(
+    protocol_pp: Vec<u8>,
+    dwallet_dkg_output: Vec<u8>,
+    device_shares: JsValue,
+    threshold: u32,
+    number_of_devices: u32,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &reshare_user_device_shares_inner(
+            protocol_pp,
+            dwallet_dkg_output,
+            serde_wasm_bindgen::from_value(device_shares)?,
+            threshold,
+            number_of_devices,
+        )
+        .map_err(to_js_err)?,
+    )?)
+}
+
+/// Creates a password-protected backup file of a dWallet,
+/// with the default (32 MiB, scrypt) key derivation parameters.
+/// `created_at` is the Unix time (in seconds) of the backup.
+#[wasm_bindgen]
+pub fn create_dwallet_backup(
+    protocol_pp: Vec<u8>,
+    dwallet_id: String,
+    network_encryption_key_id: String,
+    dwallet_dkg_output: Vec<u8>,
+    user_secret_share: Vec<u8>,
+    password: Vec<u8>,
+    created_at: u64,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &create_dwallet_backup_inner(
+            protocol_pp,
+            dwallet_id,
+            network_encryption_key_id,
+            dwallet_dkg_output,
+            user_secret_share,
+            password,
+            created_at,
+            BackupKdfParams::default(),
+        )
+        .map_err(to_js_err)?,
+    )?)
+}
+
+/// Returns the public part of a backup file, without its password.
+#[wasm_bindgen]
+pub fn inspect_dwallet_backup(backup: Vec<u8>) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &inspect_dwallet_backup_inner(backup).map_err(to_js_err)?,
+    )?)
+}
+
+/// Restores a dWallet from a backup file, verifying the restored user secret share.
+/// Returns a tuple of the public part of the backup and the user secret share.
+#[wasm_bindgen]
+pub fn restore_dwallet_backup(
+    backup: Vec<u8>,
+    password: Vec<u8>,
+    protocol_pp: Vec<u8>,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &restore_dwallet_backup_inner(backup, password, protocol_pp).map_err(to_js_err)?,
+    )?)
+}
+
+/// Splits a backup file into text chunks for paper or QR code storage.
+#[wasm_bindgen]
+pub fn export_dwallet_backup_to_chunks(
+    backup: Vec<u8>,
+    chunk_size: usize,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &export_dwallet_backup_chunks(&backup, chunk_size).map_err(to_js_err)?,
+    )?)
+}
+
+/// Reassembles a backup file from an array of its text chunks, in any order.
+#[wasm_bindgen]
+pub fn import_dwallet_backup_from_chunks(chunks: JsValue) -> Result<JsValue, JsError> {
+    let chunks: Vec<String> = serde_wasm_bindgen::from_value(chunks)?;
+    Ok(serde_wasm_bindgen::to_value(
+        &import_dwallet_backup_chunks(&chunks).map_err(to_js_err)?,
+    )?)
+}
+
+/// Encodes a dWallet signature as a 64-byte compact `r || s` signature, normalized to low-s.
+#[wasm_bindgen]
+pub fn signature_to_compact_bytes(signature: Vec<u8>) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &signature_to_compact(signature).map_err(to_js_err)?,
+    )?)
+}
+
+/// Encodes a dWallet signature as an ASN.1 DER signature, normalized to low-s.
+#[wasm_bindgen]
+pub fn signature_to_der_bytes(signature: Vec<u8>) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &signature_to_der(signature).map_err(to_js_err)?,
+    )?)
+}
+
+/// Encodes a dWallet signature as a recoverable signature.
+/// Returns a tuple of the 64-byte compact signature and its recovery ID.
+#[wasm_bindgen]
+pub fn signature_to_recoverable_bytes(
+    signature: Vec<u8>,
+    public_key: Vec<u8>,
+    message: Vec<u8>,
+    hash_type: u32,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &signature_to_recoverable(signature, public_key, message, hash_type).map_err(to_js_err)?,
+    )?)
+}
+
+/// Encodes a dWallet signature as a 65-byte Ethereum `r || s || v` signature,
+/// with an EIP-155 `v` if `chain_id` is given.
+/// Returns a tuple of the signature and `v`.
+#[wasm_bindgen]
+pub fn signature_to_ethereum_bytes(
+    signature: Vec<u8>,
+    public_key: Vec<u8>,
+    message: Vec<u8>,
+    hash_type: u32,
+    chain_id: Option<u64>,
+) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &signature_to_ethereum(signature, public_key, message, hash_type, chain_id)
+            .map_err(to_js_err)?,
+    )?)
+}
+
+/// Encodes a dWallet public key in SEC1, compressed or uncompressed.
+#[wasm_bindgen]
+pub fn public_key_to_sec1_bytes(public_key: Vec<u8>, compressed: bool) -> Result<JsValue, JsError> {
+    Ok(serde_wasm_bindgen::to_value(
+        &public_key_to_sec1(public_key, compressed).map_err(to_js_err)?,
+    )?)
+}
+
+/// Returns the EIP-55 checksummed Ethereum address of a dWallet public key.
+#[wasm_bindgen]
+pub fn public_key_to_ethereum_address(public_key: Vec<u8>) -> Result<String, JsError> {
+    ethereum_address(public_key).map_err(to_js_err)
+}
+
+/// Returns the Bitcoin P2PKH address of a dWallet public key.
+/// `network` is 0 for mainnet, 1 for testnet and 2 for regtest.
+#[wasm_bindgen]
+pub fn public_key_to_bitcoin_p2pkh_address(
+    public_key: Vec<u8>,
+    network: u32,
+) -> Result<String, JsError> {
+    bitcoin_p2pkh_address(public_key, network).map_err(to_js_err)
+}
+
+/// Returns the Bitcoin P2WPKH address of a dWallet public key.
+/// `network` is 0 for mainnet, 1 for testnet and 2 for regtest.
+#[wasm_bindgen]
+pub fn public_key_to_bitcoin_p2wpkh_address(
+    public_key: Vec<u8>,
+    network: u32,
+) -> Result<String, JsError> {
+    bitcoin_p2wpkh_address(public_key, network).map_err(to_js_err)
+}
+
+/// Returns the Bitcoin P2TR (BIP-86 key path) address of a dWallet public key.
+/// `network` is 0 for mainnet, 1 for testnet and 2 for regtest.
+#[wasm_bindgen]
+pub fn public_key_to_bitcoin_p2tr_address(
+    public_key: Vec<u8>,
+    network: u32,
+) -> Result<String, JsError> {
+    bitcoin_p2tr_address(public_key, network).map_err(to_js_err)
+}
+
 // There is no way to implement From<anyhow::Error> for JsErr
 // since the current From<Error> is generic, and it results in a conflict.
 fn to_js_err(e: anyhow::Error) -> JsError {